@@ -0,0 +1,226 @@
+//! Orphan audit across workspaces.
+//!
+//! Pods keep their halldyll tags after the repository or state that created
+//! them is gone. This module finds such pods in the `RunPod` account by
+//! comparing every tagged pod with the workspaces (configuration plus state)
+//! known on this machine.
+
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+use crate::config::{DeployConfig, PodConfig};
+use crate::failover::standby_environment;
+use crate::planner::DiffEngine;
+use crate::runpod::ObservedPod;
+use crate::state::DeploymentState;
+
+/// Configuration file name searched for when discovering workspaces.
+const CONFIG_FILE_NAME: &str = "halldyll.deploy.yaml";
+
+/// Directories never searched for workspaces.
+const SKIPPED_DIRS: &[&str] = &["target", "node_modules"];
+
+/// A workspace known to the audit: a configuration and its saved state.
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    /// Path of the configuration file.
+    pub path: PathBuf,
+    /// Parsed configuration.
+    pub config: DeployConfig,
+    /// Saved deployment state (if any).
+    pub state: Option<DeploymentState>,
+}
+
+/// Why a pod is considered orphaned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrphanReason {
+    /// No known workspace uses the pod's project.
+    UnknownProject,
+    /// The project is known, but not in the pod's environment.
+    UnknownEnvironment,
+    /// The workspace neither configures nor records the pod.
+    Untracked,
+}
+
+/// A tagged pod no known workspace accounts for.
+#[derive(Debug, Clone)]
+pub struct Orphan {
+    /// The pod as observed on `RunPod`.
+    pub pod: ObservedPod,
+    /// Why it is orphaned.
+    pub reason: OrphanReason,
+    /// Index of the workspace matching the pod's project and environment (if any).
+    pub workspace: Option<usize>,
+}
+
+impl Workspace {
+    /// Returns true if this workspace configures or records the pod.
+    #[must_use]
+    pub fn tracks(&self, pod: &ObservedPod) -> bool {
+        if self.state.as_ref().is_some_and(|s| {
+            s.pods.values().any(|p| p.runpod_id == pod.id)
+                || s.failover
+                    .as_ref()
+                    .is_some_and(|f| f.standby_pods.values().any(|p| p.runpod_id == pod.id))
+        }) {
+            return true;
+        }
+
+        let Some(name) = pod.pod_name.as_deref() else {
+            return false;
+        };
+        let mut desired = DiffEngine::desired_instances(&self.config, self.state.as_ref());
+        desired.extend(self.config.pods.iter().flat_map(PodConfig::warm_instances));
+        desired.iter().any(|p| p.name == name)
+    }
+
+    /// Returns true if this workspace deploys to the pod's project and environment.
+    ///
+    /// Standby pods of a failover deployment belong to the workspace too.
+    fn owns(&self, pod: &ObservedPod) -> bool {
+        let project = &self.config.project;
+        pod.project.as_deref() == Some(project.name.as_str())
+            && pod.environment.as_deref().is_some_and(|env| {
+                env == project.environment
+                    || (project.failover.is_some()
+                        && env == standby_environment(&project.environment))
+            })
+    }
+}
+
+/// Returns the halldyll-tagged pods that no workspace accounts for.
+#[must_use]
+pub fn find_orphans(pods: &[ObservedPod], workspaces: &[Workspace]) -> Vec<Orphan> {
+    pods.iter()
+        .filter(|pod| pod.is_managed())
+        .filter_map(|pod| {
+            let workspace = workspaces.iter().position(|w| w.owns(pod));
+            let reason = match workspace {
+                Some(index) if workspaces[index].tracks(pod) => return None,
+                Some(_) => OrphanReason::Untracked,
+                None if workspaces
+                    .iter()
+                    .any(|w| pod.project.as_deref() == Some(w.config.project.name.as_str())) =>
+                {
+                    OrphanReason::UnknownEnvironment
+                }
+                None => OrphanReason::UnknownProject,
+            };
+
+            Some(Orphan {
+                pod: pod.clone(),
+                reason,
+                workspace,
+            })
+        })
+        .collect()
+}
+
+/// Finds workspace configuration files below the given directories.
+///
+/// Hidden directories and build output are skipped; unreadable directories
+/// are ignored.
+#[must_use]
+pub fn discover_configs(roots: &[PathBuf]) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    for root in roots {
+        collect_configs(root, &mut found);
+    }
+    found.sort();
+    found.dedup();
+    found
+}
+
+/// Recursively collects configuration files below `dir`.
+fn collect_configs(dir: &Path, found: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        debug!("Skipping unreadable directory: {}", dir.display());
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+
+        if path.is_dir() {
+            if !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_ref()) {
+                collect_configs(&path, found);
+            }
+        } else if name == CONFIG_FILE_NAME {
+            found.push(path);
+        }
+    }
+}
+
+impl std::fmt::Display for OrphanReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownProject => write!(f, "unknown project"),
+            Self::UnknownEnvironment => write!(f, "unknown environment"),
+            Self::Untracked => write!(f, "not in config or state"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runpod::PodStatus;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_find_orphans() {
+        let yaml = "project:\n  name: proj\nstate:\n  backend: local\npods:\n  - name: llm\n    gpu:\n      type: A40\n    runtime:\n      image: img\n";
+        let mut state = DeploymentState::new("proj", "dev");
+        state.set_pod(crate::state::PodState::new("old", "id-recorded", "hash"));
+        let workspaces = [Workspace {
+            path: PathBuf::from("halldyll.deploy.yaml"),
+            config: serde_yaml::from_str(yaml).unwrap(),
+            state: Some(state),
+        }];
+
+        let pod = |id: &str, project: &str, env: &str, name: &str| ObservedPod {
+            id: id.to_string(),
+            name: format!("{project}-{env}-{name}"),
+            project: Some(project.to_string()),
+            environment: Some(env.to_string()),
+            pod_name: Some(name.to_string()),
+            spec_hash: None,
+            status: PodStatus::Running,
+            gpu_type: None,
+            gpu_count: 1,
+            image: String::from("img"),
+            endpoints: HashMap::new(),
+            gpu_utilization: None,
+            tags: HashMap::new(),
+            volume_gb: 0,
+            uptime_secs: 0,
+            env: None,
+        };
+        let pods = [
+            pod("id-llm", "proj", "dev", "llm"),
+            pod("id-recorded", "proj", "dev", "old"),
+            pod("id-stray", "proj", "dev", "stray"),
+            pod("id-prod", "proj", "prod", "llm"),
+            pod("id-gone", "deleted-repo", "dev", "llm"),
+        ];
+
+        let orphans: Vec<_> = find_orphans(&pods, &workspaces)
+            .into_iter()
+            .map(|o| (o.pod.id, o.reason, o.workspace))
+            .collect();
+        assert_eq!(
+            orphans,
+            [
+                (String::from("id-stray"), OrphanReason::Untracked, Some(0)),
+                (
+                    String::from("id-prod"),
+                    OrphanReason::UnknownEnvironment,
+                    None
+                ),
+                (String::from("id-gone"), OrphanReason::UnknownProject, None),
+            ]
+        );
+    }
+}
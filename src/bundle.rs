@@ -0,0 +1,335 @@
+//! Deployment bundles.
+//!
+//! A bundle is a `.tar.gz` archive recording exactly what an apply would
+//! deploy: the rendered configuration with secrets redacted, the values of
+//! the environment variables it references, the image and model revisions in
+//! use, and the plan. It is meant to be kept as an audit artifact.
+
+use chrono::{DateTime, Utc};
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::config::{DeployConfig, ModelProvider, is_secret_env_key};
+use crate::error::{ErrorContext, HalldyllError, Result, ResultExt};
+use crate::version::{GIT_SHA, VERSION};
+
+/// Placeholder written in place of secret values.
+pub const REDACTED: &str = "<redacted>";
+
+/// Metadata describing a bundle.
+#[derive(Debug, Clone, Serialize)]
+pub struct BundleManifest {
+    /// Project name.
+    pub project: String,
+    /// Environment name.
+    pub environment: String,
+    /// Hash of the full configuration.
+    pub config_hash: String,
+    /// When the bundle was created.
+    pub created_at: DateTime<Utc>,
+    /// Halldyll version that created the bundle.
+    pub halldyll_version: String,
+    /// Git commit of the Halldyll build.
+    pub halldyll_git_sha: String,
+    /// Whether the plan observed pods on `RunPod` (false: planned from state).
+    pub refreshed: bool,
+}
+
+/// A pod's container image, pinned if the reference carries a digest.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct ImagePin {
+    /// Pod name.
+    pub pod: String,
+    /// Image reference as configured.
+    pub image: String,
+    /// Content digest (`sha256:...`) if the reference is pinned.
+    pub digest: Option<String>,
+}
+
+/// A model's source revision.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct ModelPin {
+    /// Pod name.
+    pub pod: String,
+    /// Model ID.
+    pub model: String,
+    /// `HuggingFace` repository (if any).
+    pub repo: Option<String>,
+    /// Commit the repository's `main` branch pointed to (if resolved).
+    pub revision: Option<String>,
+}
+
+/// Image and model revisions recorded in a bundle.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BundleLock {
+    /// Container images.
+    pub images: Vec<ImagePin>,
+    /// Models.
+    pub models: Vec<ModelPin>,
+}
+
+/// An auditable snapshot of a deployment.
+#[derive(Debug, Clone)]
+pub struct Bundle {
+    /// Bundle metadata.
+    pub manifest: BundleManifest,
+    /// Configuration with secrets redacted.
+    pub config: DeployConfig,
+    /// Environment variables referenced as `${VAR}` (`None` if unset).
+    pub variables: BTreeMap<String, Option<String>>,
+    /// Image and model revisions.
+    pub lock: BundleLock,
+    /// The plan, as rendered by `halldyll plan --output json`.
+    pub plan_json: String,
+}
+
+impl BundleLock {
+    /// Collects the images and models of a configuration.
+    ///
+    /// Only explicitly set (or locked) model revisions are recorded.
+    #[must_use]
+    pub fn from_config(config: &DeployConfig) -> Self {
+        let images = config
+            .pods
+            .iter()
+            .map(|pod| ImagePin {
+                pod: pod.name.clone(),
+                image: pod.runtime.image.clone(),
+                digest: pod
+                    .runtime
+                    .image
+                    .split_once('@')
+                    .map(|(_, digest)| digest.to_string()),
+            })
+            .collect();
+
+        let models = config
+            .pods
+            .iter()
+            .flat_map(|pod| {
+                pod.models.iter().map(|model| ModelPin {
+                    pod: pod.name.clone(),
+                    model: model.id.clone(),
+                    repo: match model.provider {
+                        ModelProvider::Huggingface => model.repo.clone(),
+                        _ => None,
+                    },
+                    revision: model.revision.clone(),
+                })
+            })
+            .collect();
+
+        Self { images, models }
+    }
+}
+
+impl Bundle {
+    /// Builds a bundle from a configuration and its rendered plan.
+    #[must_use]
+    pub fn new(
+        config: &DeployConfig,
+        config_hash: &str,
+        plan_json: String,
+        refreshed: bool,
+    ) -> Self {
+        Self {
+            manifest: BundleManifest {
+                project: config.project.name.clone(),
+                environment: config.project.environment.clone(),
+                config_hash: config_hash.to_string(),
+                created_at: Utc::now(),
+                halldyll_version: VERSION.to_string(),
+                halldyll_git_sha: GIT_SHA.to_string(),
+                refreshed,
+            },
+            config: redact_config(config),
+            variables: resolve_variables(config),
+            lock: BundleLock::from_config(config),
+            plan_json,
+        }
+    }
+
+    /// Sets the resolved model revisions.
+    #[must_use]
+    pub fn with_lock(mut self, lock: BundleLock) -> Self {
+        self.lock = lock;
+        self
+    }
+
+    /// Default file name: `halldyll-<project>-<env>-<timestamp>.tar.gz`.
+    #[must_use]
+    pub fn file_name(&self) -> String {
+        format!(
+            "halldyll-{}-{}-{}.tar.gz",
+            self.manifest.project,
+            self.manifest.environment,
+            self.manifest.created_at.format("%Y%m%dT%H%M%SZ")
+        )
+    }
+
+    /// Writes the bundle as a gzipped tarball.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bundle cannot be serialized or written.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let config_yaml = serde_yaml::to_string(&self.config)
+            .map_err(|e| HalldyllError::internal(format!("Failed to render configuration: {e}")))?;
+
+        let entries = [
+            ("manifest.json", to_json(&self.manifest)?),
+            ("halldyll.deploy.yaml", config_yaml),
+            ("variables.json", to_json(&self.variables)?),
+            ("lock.json", to_json(&self.lock)?),
+            ("plan.json", self.plan_json.clone()),
+        ];
+
+        let write = || -> std::io::Result<()> {
+            let file = std::fs::File::create(path)?;
+            let mut archive = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+            let mtime = u64::try_from(self.manifest.created_at.timestamp()).unwrap_or_default();
+
+            for (name, contents) in &entries {
+                let mut header = tar::Header::new_gnu();
+                header.set_size(contents.len() as u64);
+                header.set_mode(0o644);
+                header.set_mtime(mtime);
+                header.set_cksum();
+                archive.append_data(&mut header, name, contents.as_bytes())?;
+            }
+
+            archive.into_inner()?.finish()?;
+            Ok(())
+        };
+
+        write().context(|| {
+            ErrorContext::new("writing bundle").with_resource(path.display().to_string())
+        })
+    }
+}
+
+/// Renders a bundle entry as pretty JSON.
+fn to_json<T: Serialize>(value: &T) -> Result<String> {
+    serde_json::to_string_pretty(value)
+        .map_err(|e| HalldyllError::internal(format!("Failed to render bundle entry: {e}")))
+}
+
+/// Returns true if a value only references environment variables.
+fn is_reference(value: &str) -> bool {
+    value.trim().starts_with("${") && value.trim().ends_with('}')
+}
+
+/// Copies a configuration with inlined secret values replaced by [`REDACTED`].
+///
+/// Env vars and health check headers whose names look secret, and alert
+/// webhooks (which embed their credentials), are redacted unless the value is
+/// a `${VAR}` reference.
+#[must_use]
+pub fn redact_config(config: &DeployConfig) -> DeployConfig {
+    let redact = |key: &str, value: &mut String| {
+        if (is_secret_env_key(key) || key.eq_ignore_ascii_case("authorization"))
+            && !is_reference(value)
+        {
+            *value = REDACTED.to_string();
+        }
+    };
+
+    let mut redacted = config.clone();
+    for pod in &mut redacted.pods {
+        for (key, value) in &mut pod.runtime.env {
+            redact(key, value);
+        }
+        if let Some(health_check) = &mut pod.health_check {
+            for (key, value) in &mut health_check.headers {
+                redact(key, value);
+            }
+        }
+    }
+    for webhook in redacted
+        .alerts
+        .iter_mut()
+        .flat_map(|a| a.webhooks.iter_mut())
+    {
+        if !is_reference(webhook) {
+            *webhook = REDACTED.to_string();
+        }
+    }
+    redacted
+}
+
+/// Resolves the `${VAR}` references of a configuration from the environment.
+///
+/// `${gpu.*}` and `${pod.*}` placeholders are not environment variables and are
+/// skipped. Values of secret-looking names are redacted.
+#[must_use]
+pub fn resolve_variables(config: &DeployConfig) -> BTreeMap<String, Option<String>> {
+    let rendered = serde_yaml::to_string(config).unwrap_or_default();
+    let mut variables = BTreeMap::new();
+
+    let mut rest = rendered.as_str();
+    while let Some(start) = rest.find("${") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('}') else {
+            break;
+        };
+
+        let name = &after[..end];
+        if !name.is_empty() && !name.starts_with("gpu.") && !name.starts_with("pod.") {
+            let value = std::env::var(name).ok().map(|value| {
+                if is_secret_env_key(name) {
+                    REDACTED.to_string()
+                } else {
+                    value
+                }
+            });
+            variables.insert(name.to_string(), value);
+        }
+        rest = &after[end + 1..];
+    }
+
+    variables
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redaction_and_lock() {
+        let yaml = r#"
+project:
+  name: proj
+state:
+  backend: local
+pods:
+  - name: llm
+    gpu:
+      type: A40
+    runtime:
+      image: "vllm/vllm-openai@sha256:abc"
+      env:
+        HF_TOKEN: "${HF_TOKEN}"
+        API_KEY: "inline-secret"
+        MODE: prod
+    models:
+      - id: llama
+        provider: huggingface
+        repo: meta-llama/Llama-3
+"#;
+        let config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+
+        let env = &redact_config(&config).pods[0].runtime.env;
+        assert_eq!(env["API_KEY"], REDACTED);
+        assert_eq!(env["HF_TOKEN"], "${HF_TOKEN}");
+        assert_eq!(env["MODE"], "prod");
+
+        assert!(resolve_variables(&config).contains_key("HF_TOKEN"));
+
+        let lock = BundleLock::from_config(&config);
+        assert_eq!(lock.images[0].digest.as_deref(), Some("sha256:abc"));
+        assert_eq!(lock.models[0].repo.as_deref(), Some("meta-llama/Llama-3"));
+    }
+}
@@ -2,8 +2,12 @@
 //!
 //! This module defines all CLI commands and their arguments using clap.
 
-use clap::{Parser, Subcommand};
+use clap::{ArgAction, Parser, Subcommand};
 use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::config::{ConfigOverride, parse_var};
+use crate::planner::DeadlinePolicy;
 
 /// Halldyll - Declarative `RunPod` deployment manager.
 #[derive(Parser, Debug)]
@@ -23,6 +27,22 @@ pub struct Cli {
     #[arg(long, global = true, default_value = "text")]
     pub output: OutputFormat,
 
+    /// Accept any SSH host key instead of checking `.halldyll/known_hosts`.
+    #[arg(long, global = true)]
+    pub insecure_skip_host_key: bool,
+
+    /// Environment to deploy to; its overlay is merged over the configuration.
+    #[arg(long = "env", global = true, value_name = "ENV")]
+    pub environment: Option<String>,
+
+    /// Set a `${var.name}` configuration variable, e.g. `tag=v2` (repeatable).
+    #[arg(long = "var", global = true, value_name = "NAME=VALUE", value_parser = parse_var)]
+    pub vars: Vec<(String, String)>,
+
+    /// Override a configuration value, e.g. `pods[0].runtime.image=repo:v2` (repeatable).
+    #[arg(long = "set", global = true, value_name = "PATH=VALUE", value_parser = ConfigOverride::parse)]
+    pub overrides: Vec<ConfigOverride>,
+
     /// Subcommand to execute.
     #[command(subcommand)]
     pub command: Commands,
@@ -49,14 +69,26 @@ pub enum Commands {
         warnings: bool,
     },
 
+    /// Simulate the `--set` changes and check them against validation, guardrails and cost.
+    Check,
+
     /// Generate and display the deployment plan.
     Plan {
         /// Show detailed diff information.
         #[arg(short, long)]
         detailed: bool,
+
+        /// Observe pods on `RunPod`; `--refresh=false` plans from saved state only.
+        #[arg(long, default_value_t = true, action = ArgAction::Set)]
+        refresh: bool,
+
+        /// Also write the report to this file, in the chosen `--output` format.
+        #[arg(long, value_name = "FILE")]
+        out_file: Option<PathBuf>,
     },
 
     /// Apply the deployment plan.
+    #[command(visible_alias = "deploy")]
     Apply {
         /// Skip confirmation prompt.
         #[arg(short, long)]
@@ -65,6 +97,26 @@ pub enum Commands {
         /// Continue on errors.
         #[arg(long)]
         continue_on_error: bool,
+
+        /// Stop starting actions once this much time has passed (e.g. `30m`, `1h30m`).
+        #[arg(long, value_parser = parse_duration)]
+        deadline: Option<Duration>,
+
+        /// What to do with an action still running at the deadline.
+        #[arg(long, value_enum, default_value_t, requires = "deadline")]
+        on_deadline: DeadlinePolicy,
+
+        /// Maximum number of independent actions to run at once.
+        #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u16).range(1..))]
+        parallelism: u16,
+
+        /// Create a new release next to the live pods instead of changing them (see `promote`).
+        #[arg(long, conflicts_with_all = ["deadline", "continue_on_error", "replace"])]
+        blue_green: bool,
+
+        /// Recreate this pod (or its replicas) even if it matches the configuration; repeatable.
+        #[arg(long, value_name = "POD")]
+        replace: Vec<String>,
     },
 
     /// Show current deployment status.
@@ -74,8 +126,16 @@ pub enum Commands {
         detailed: bool,
 
         /// Include health check results.
-        #[arg(long)]
+        #[arg(long, conflicts_with = "cached")]
         health: bool,
+
+        /// Show the last observed status without contacting `RunPod`.
+        #[arg(long)]
+        cached: bool,
+
+        /// Also write the report to this file, in the chosen `--output` format.
+        #[arg(long, value_name = "FILE")]
+        out_file: Option<PathBuf>,
     },
 
     /// Reconcile deployment to match configuration.
@@ -87,6 +147,10 @@ pub enum Commands {
         /// Maximum reconciliation attempts.
         #[arg(long, default_value = "3")]
         max_attempts: u32,
+
+        /// Recreate this pod (or its replicas) even if it matches the configuration; repeatable.
+        #[arg(long, value_name = "POD")]
+        replace: Vec<String>,
     },
 
     /// Destroy all deployed resources.
@@ -102,7 +166,8 @@ pub enum Commands {
 
     /// Show deployment logs.
     Logs {
-        /// Pod name (optional, shows all pods if not specified).
+        /// Pod name (shows all pods if not specified).
+        #[arg(short, long)]
         pod: Option<String>,
 
         /// Follow log output.
@@ -114,8 +179,149 @@ pub enum Commands {
         tail: u32,
     },
 
+    /// Run a command on a deployed pod.
+    Exec {
+        /// Pod name (or replica name) from the deployment state.
+        pod: String,
+
+        /// Command timeout in seconds.
+        #[arg(long)]
+        timeout: Option<u64>,
+
+        /// Command to run, after `--`.
+        #[arg(last = true, required = true)]
+        command: Vec<String>,
+    },
+
     /// Check for drift between config and actual state.
-    Drift,
+    Drift {
+        /// Probe each recorded endpoint and report unreachable ones.
+        #[arg(long)]
+        probe_endpoints: bool,
+
+        /// Also write the report to this file, in the chosen `--output` format.
+        #[arg(long, value_name = "FILE")]
+        out_file: Option<PathBuf>,
+    },
+
+    /// Continuously autoscale and reconcile the deployment.
+    Watch {
+        /// Seconds between iterations.
+        #[arg(long, default_value = "60")]
+        interval: u64,
+
+        /// Only evaluate autoscaling; do not reconcile.
+        #[arg(long)]
+        no_reconcile: bool,
+
+        /// Run a single iteration and exit.
+        #[arg(long)]
+        once: bool,
+
+        /// Only act while holding the state lock, so one of several watchers leads.
+        #[arg(long)]
+        leader_election: bool,
+
+        /// Seconds the leader's lease lasts without renewal.
+        #[arg(long, default_value = "60", requires = "leader_election")]
+        lease_secs: u64,
+    },
+
+    /// Resolve GPU types, image digests and model revisions into `halldyll.lock`.
+    Lock {
+        /// Re-resolve every reference instead of only new ones.
+        #[arg(long)]
+        update: bool,
+    },
+
+    /// Write a bundle of the redacted config, variables, pinned revisions and plan.
+    Package {
+        /// Bundle file (defaults to `halldyll-<project>-<env>-<timestamp>.tar.gz`).
+        #[arg(short, long)]
+        file: Option<PathBuf>,
+
+        /// Observe pods on `RunPod` and resolve model revisions; `--refresh=false` works offline.
+        #[arg(long, default_value_t = true, action = ArgAction::Set)]
+        refresh: bool,
+    },
+
+    /// Show version, build commit and supported state version.
+    Version {
+        /// Check crates.io for a newer release.
+        #[arg(long)]
+        check: bool,
+    },
+
+    /// List the external hosts a command may contact, from the config and enabled features.
+    Endpoints {
+        /// Command to report on (e.g. `apply`).
+        #[arg(long, value_parser = clap::builder::PossibleValuesParser::new(crate::egress::command_names()))]
+        command: String,
+    },
+
+    /// Show what the project's pods cost per hour and since they were created.
+    Cost {
+        /// Also write the report to this file, in the chosen `--output` format.
+        #[arg(long, value_name = "FILE")]
+        out_file: Option<PathBuf>,
+    },
+
+    /// Report a pod's availability, longest outage and MTTR from the health history `watch` records.
+    Slo {
+        /// Pod name (covers all its replicas).
+        pod: String,
+
+        /// How far back to look (e.g. `30d`, `12h`).
+        #[arg(long, default_value = "30d", value_parser = parse_duration)]
+        window: Duration,
+
+        /// Also write the report to this file, in the chosen `--output` format.
+        #[arg(long, value_name = "FILE")]
+        out_file: Option<PathBuf>,
+    },
+
+    /// List the project's pods across all environments (read-only).
+    Inventory {
+        /// Project to list (defaults to the configured project).
+        #[arg(long)]
+        project: Option<String>,
+
+        /// Also write the report to this file, in the chosen `--output` format.
+        #[arg(long, value_name = "FILE")]
+        out_file: Option<PathBuf>,
+    },
+
+    /// Find halldyll-tagged pods no local workspace (config or state) accounts for.
+    AuditOrphans {
+        /// Directories searched for `halldyll.deploy.yaml` workspaces.
+        #[arg(long, default_value = ".")]
+        search: Vec<PathBuf>,
+
+        /// Ask whether to adopt, terminate or skip each orphan.
+        #[arg(short, long)]
+        interactive: bool,
+    },
+
+    /// Resume a pod whose replicas were stopped for being idle.
+    Wake {
+        /// Pod name to wake (required unless --listen is used).
+        #[arg(required_unless_present = "listen")]
+        pod: Option<String>,
+
+        /// Serve a wake webhook (`POST /wake/<pod>`) on this address instead.
+        #[arg(long, value_name = "ADDR")]
+        listen: Option<String>,
+    },
+
+    /// Set the number of replicas of a pod.
+    Scale {
+        /// Pod name to scale.
+        pod: String,
+
+        /// Number of replicas to run.
+        #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+        count: u32,
+    },
 
     /// Manage state backend.
     State {
@@ -123,13 +329,162 @@ pub enum Commands {
         #[command(subcommand)]
         command: StateCommands,
     },
+
+    /// Share pod configurations as `RunPod` templates.
+    Template {
+        /// Template subcommand.
+        #[command(subcommand)]
+        command: TemplateCommands,
+    },
+
+    /// Inspect the environment variables loaded from dotenv files.
+    Env {
+        /// Env subcommand.
+        #[command(subcommand)]
+        command: EnvCommands,
+    },
+
+    /// Exempt a pod from reconcile, watch and drift checks while it is debugged.
+    Maintenance {
+        /// Maintenance subcommand.
+        #[command(subcommand)]
+        command: MaintenanceCommands,
+    },
+
+    /// Set, remove or list a pod's annotations, shown by `status`.
+    Annotate {
+        /// Pod or replica name.
+        pod: String,
+
+        /// Annotations to set, as `key=value` (lists them when none are given).
+        #[arg(value_name = "KEY=VALUE", value_parser = parse_annotation)]
+        annotations: Vec<(String, String)>,
+
+        /// Annotation keys to remove.
+        #[arg(long, value_name = "KEY")]
+        remove: Vec<String>,
+    },
+
+    /// Adopt a pod created outside halldyll (e.g. in the dashboard) into state.
+    Import {
+        /// Pod or replica name from the configuration.
+        pod: String,
+
+        /// `RunPod` ID of the existing pod.
+        runpod_id: String,
+    },
+
+    /// Switch between the primary pods and standbys in the failover data centers.
+    Failover {
+        /// Failover subcommand.
+        #[command(subcommand)]
+        command: FailoverCommands,
+    },
+
+    /// Terminate the live pods and make the blue/green release live.
+    Promote {
+        /// Skip confirmation prompt.
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Terminate the blue/green release and keep the live pods.
+    Rollback {
+        /// Skip confirmation prompt.
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+/// Env subcommands.
+#[derive(Subcommand, Debug)]
+pub enum EnvCommands {
+    /// Print the effective variables and where each comes from (secrets masked).
+    Print,
+}
+
+/// Maintenance subcommands.
+#[derive(Subcommand, Debug)]
+pub enum MaintenanceCommands {
+    /// Put a pod (all replicas of a replicated pod, or one replica) under maintenance.
+    On {
+        /// Pod or replica name.
+        pod: String,
+
+        /// Why the pod is under maintenance.
+        #[arg(long)]
+        reason: String,
+
+        /// Who is working on the pod (defaults to the current user).
+        #[arg(long)]
+        owner: Option<String>,
+    },
+
+    /// End a pod's maintenance so it is managed again.
+    Off {
+        /// Pod or replica name.
+        pod: String,
+    },
+}
+
+/// Failover subcommands.
+#[derive(Subcommand, Debug)]
+pub enum FailoverCommands {
+    /// Provision the standby pods in the failover data centers and make them active.
+    Activate {
+        /// Skip confirmation prompt.
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Tear down the standby pods and make the primary side active again.
+    Deactivate {
+        /// Skip confirmation prompt.
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Show the active side and the standby pods.
+    Status,
+}
+
+/// `RunPod` template subcommands.
+#[derive(Subcommand, Debug)]
+pub enum TemplateCommands {
+    /// Save a configured pod as a `RunPod` template (updated if it exists).
+    Push {
+        /// Pod name from the configuration.
+        pod: String,
+    },
+
+    /// Print a pod configuration built from a `RunPod` template.
+    Pull {
+        /// Template name (or ID).
+        template: String,
+
+        /// Pod name to use (defaults to the template name).
+        #[arg(long)]
+        name: Option<String>,
+
+        /// GPU type for the pod (templates do not store one).
+        #[arg(long)]
+        gpu: String,
+    },
 }
 
 /// State management subcommands.
 #[derive(Subcommand, Debug)]
 pub enum StateCommands {
     /// Show current state.
-    Show,
+    Show {
+        /// Only show this pod (a replicated pod includes its replicas).
+        #[arg(long)]
+        pod: Option<String>,
+
+        /// Only show these pod fields (e.g. `endpoints,status,config_hash`).
+        #[arg(long, value_delimiter = ',')]
+        fields: Vec<String>,
+    },
 
     /// Lock the state.
     Lock {
@@ -158,6 +513,24 @@ pub enum StateCommands {
         #[arg(long)]
         force: bool,
     },
+
+    /// Show deployment history.
+    History {
+        /// List stored versions of the state instead (local snapshots or S3 object versions).
+        #[arg(long, visible_alias = "remote")]
+        versions: bool,
+    },
+
+    /// Restore a previous version of the state.
+    Rollback {
+        /// Version to restore (see `state history --versions`).
+        #[arg(long = "version", visible_alias = "version-id")]
+        version_id: String,
+
+        /// Skip confirmation prompt.
+        #[arg(short, long)]
+        yes: bool,
+    },
 }
 
 /// Output format options.
@@ -177,3 +550,74 @@ impl Cli {
         Self::parse()
     }
 }
+
+/// Parses a `key=value` annotation.
+fn parse_annotation(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((key, annotation)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), annotation.to_string()))
+        }
+        _ => Err(format!("invalid annotation '{value}' (expected key=value)")),
+    }
+}
+
+/// Parses a duration such as `90s`, `30m`, `1h30m`, `30d`, or a bare number of seconds.
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let invalid = || format!("invalid duration '{value}' (expected e.g. 90s, 30m, 1h30m, 30d)");
+
+    if let Ok(secs) = value.parse::<u64>() {
+        return Ok(Duration::from_secs(secs));
+    }
+
+    let mut total = 0u64;
+    let mut digits = String::new();
+    for c in value.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let unit = match c {
+            'd' => 86_400,
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return Err(invalid()),
+        };
+        let amount: u64 = digits.parse().map_err(|_| invalid())?;
+        total = total.saturating_add(amount.saturating_mul(unit));
+        digits.clear();
+    }
+
+    if !digits.is_empty() || total == 0 {
+        return Err(invalid());
+    }
+    Ok(Duration::from_secs(total))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_annotation() {
+        assert_eq!(
+            parse_annotation("note=keep until Friday = demo"),
+            Ok((
+                String::from("note"),
+                String::from("keep until Friday = demo")
+            ))
+        );
+        assert!(parse_annotation("note").is_err());
+        assert!(parse_annotation("=value").is_err());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("30m"), Ok(Duration::from_secs(1800)));
+        assert_eq!(parse_duration("1h30m"), Ok(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("30d"), Ok(Duration::from_secs(30 * 86_400)));
+        assert!(parse_duration("10x").is_err());
+        assert!(parse_duration("m").is_err());
+    }
+}
@@ -6,5 +6,8 @@
 mod commands;
 mod output;
 
-pub use commands::{Cli, Commands, OutputFormat, StateCommands};
-pub use output::OutputFormatter;
+pub use commands::{
+    Cli, Commands, EnvCommands, FailoverCommands, MaintenanceCommands, OutputFormat, StateCommands,
+    TemplateCommands,
+};
+pub use output::{OutputFormatter, ReadyPod};
@@ -5,20 +5,51 @@
 
 use colored::Colorize;
 use std::fmt::Write;
+use std::path::PathBuf;
 use tabled::{Table, Tabled};
 
-use crate::planner::{ActionType, DeploymentPlan};
+use crate::audit::Orphan;
+use crate::bundle::REDACTED;
+use crate::config::{ResolvedEnvVar, is_secret_env_key};
+use crate::egress::Egress;
+use crate::error::{HalldyllError, Result};
+use crate::planner::{
+    ActionType, CostReport, DeploymentPlan, ExecutionResult, GpuPrices, PreflightReport, SloReport,
+};
 use crate::reconciler::{DriftReport, ReconciliationResult};
-use crate::runpod::{ObservedPod, ProjectStatus, PodStatus, HealthStatus};
-use crate::state::DeploymentState;
+use crate::runpod::{HealthStatus, ObservedPod, PodStatus, ProjectStatus};
+use crate::state::{CachedStatus, DeploymentState, PodState, StateVersion};
+use crate::version::VersionInfo;
 
 use super::commands::OutputFormat;
 
 /// Output formatter for CLI.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct OutputFormatter {
     /// Output format.
     format: OutputFormat,
+    /// File reports are also written to.
+    out_file: Option<PathBuf>,
+}
+
+/// A pod `apply` brought up, with how to start using it.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ReadyPod {
+    /// Pod instance name.
+    pub name: String,
+    /// `RunPod` pod ID.
+    pub pod_id: String,
+    /// Public endpoint URLs by port.
+    pub endpoints: std::collections::BTreeMap<u16, String>,
+    /// Command connecting to the pod over SSH (if it exposes SSH).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ssh: Option<String>,
+    /// Engines serving the pod's models.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub engines: Vec<String>,
+    /// Whether the pod's endpoints answered their health check (`None` if not checked).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub healthy: Option<bool>,
 }
 
 /// Pod status row for table display.
@@ -36,6 +67,77 @@ struct PodStatusRow {
     id: String,
 }
 
+/// Inventory row for table display.
+#[derive(Tabled)]
+struct InventoryRow {
+    #[tabled(rename = "Env")]
+    environment: String,
+    #[tabled(rename = "Name")]
+    name: String,
+    #[tabled(rename = "Status")]
+    status: String,
+    #[tabled(rename = "GPU")]
+    gpu: String,
+    #[tabled(rename = "$/hr")]
+    cost: String,
+    #[tabled(rename = "Age")]
+    age: String,
+}
+
+/// Cost row for table display.
+#[derive(Tabled)]
+struct CostRow {
+    #[tabled(rename = "Name")]
+    name: String,
+    #[tabled(rename = "Status")]
+    status: String,
+    #[tabled(rename = "GPU")]
+    gpu: String,
+    #[tabled(rename = "$/hr")]
+    cost: String,
+    #[tabled(rename = "Since")]
+    since: String,
+    #[tabled(rename = "Accumulated")]
+    accumulated: String,
+}
+
+/// Environment variable row for table display.
+#[derive(Tabled)]
+struct EnvRow {
+    #[tabled(rename = "Variable")]
+    key: String,
+    #[tabled(rename = "Value")]
+    value: String,
+    #[tabled(rename = "Source")]
+    source: String,
+}
+
+/// Outbound host row for table display.
+#[derive(Tabled)]
+struct EgressRow {
+    #[tabled(rename = "Host")]
+    host: String,
+    #[tabled(rename = "Purpose")]
+    purpose: String,
+}
+
+/// Orphaned pod row for table display.
+#[derive(Tabled)]
+struct OrphanRow {
+    #[tabled(rename = "Project")]
+    project: String,
+    #[tabled(rename = "Env")]
+    environment: String,
+    #[tabled(rename = "Name")]
+    name: String,
+    #[tabled(rename = "Status")]
+    status: String,
+    #[tabled(rename = "Reason")]
+    reason: String,
+    #[tabled(rename = "ID")]
+    id: String,
+}
+
 /// Plan action row for table display.
 #[derive(Tabled)]
 struct PlanActionRow {
@@ -47,13 +149,49 @@ struct PlanActionRow {
     resource: String,
     #[tabled(rename = "Reason")]
     reason: String,
+    #[tabled(rename = "Est.")]
+    estimate: String,
+    #[tabled(rename = "Cost/hr")]
+    cost: String,
 }
 
 impl OutputFormatter {
     /// Creates a new output formatter.
     #[must_use]
     pub const fn new(format: OutputFormat) -> Self {
-        Self { format }
+        Self {
+            format,
+            out_file: None,
+        }
+    }
+
+    /// Also writes emitted reports to `path` (`None` keeps them on the terminal only).
+    #[must_use]
+    pub fn with_out_file(mut self, path: Option<PathBuf>) -> Self {
+        self.out_file = path;
+        self
+    }
+
+    /// Prints a formatted report, and writes it to the output file if one is
+    /// set (without terminal colors).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the output file cannot be written.
+    pub fn emit(&self, output: &str) -> Result<()> {
+        eprintln!("{output}");
+
+        if let Some(path) = &self.out_file {
+            let mut content = strip_ansi(output);
+            if !content.ends_with('\n') {
+                content.push('\n');
+            }
+            std::fs::write(path, content).map_err(|e| {
+                HalldyllError::internal(format!("Failed to write {}: {e}", path.display()))
+            })?;
+        }
+
+        Ok(())
     }
 
     /// Formats a deployment plan for display.
@@ -69,24 +207,49 @@ impl OutputFormatter {
 
     /// Formats a plan as text.
     fn format_plan_text(plan: &DeploymentPlan) -> String {
+        let mut output = String::new();
+
+        if !plan.refreshed {
+            let _ = writeln!(
+                output,
+                "\n{}",
+                "⚠ Refresh skipped: planned from saved state only. \
+                 Drift on RunPod is NOT considered."
+                    .yellow()
+                    .bold()
+            );
+        }
+
         if plan.is_empty() {
-            return format!(
-                "{} No changes required - infrastructure is up to date.\n",
+            let _ = writeln!(
+                output,
+                "{} No changes required - infrastructure is up to date.",
                 "✓".green()
             );
+            return output;
         }
 
-        let mut output = String::new();
-
-        let _ = write!(
-            output,
-            "\n📋 Deployment Plan\n"
-        );
-        let _ = write!(
-            output,
-            "   Config hash: {}\n\n",
-            &plan.config_hash[..8]
-        );
+        let _ = write!(output, "\n📋 Deployment Plan\n");
+        let _ = writeln!(output, "   Config hash: {}", &plan.config_hash[..8]);
+        if let (Some(cost), Some(delta)) = (plan.estimated_hourly_cost, plan.estimated_cost_delta) {
+            let _ = writeln!(
+                output,
+                "   Estimated cost: ${cost:.2}/hr ({}/hr)",
+                format_cost_delta(delta)
+            );
+        }
+        if let Some(runway) = &plan.runway {
+            let _ = writeln!(output, "   {runway}");
+        }
+        if let Some(secs) = plan.estimated_duration_secs() {
+            let unestimated = plan.unestimated_count();
+            let _ = write!(output, "   Estimated duration: ~{}", format_duration(secs));
+            if unestimated > 0 {
+                let _ = write!(output, " (+{unestimated} actions without recorded timings)");
+            }
+            output.push('\n');
+        }
+        output.push('\n');
 
         // Create action table
         let rows: Vec<PlanActionRow> = plan
@@ -98,6 +261,12 @@ impl OutputFormatter {
                 action: Self::format_action_type(a.action_type),
                 resource: a.resource_name.clone(),
                 reason: Self::truncate(&a.reason, 40),
+                estimate: a
+                    .estimated_secs
+                    .map_or_else(|| String::from("-"), format_duration),
+                cost: a
+                    .hourly_cost_delta
+                    .map_or_else(|| String::from("-"), format_cost_delta),
             })
             .collect();
 
@@ -126,6 +295,13 @@ impl OutputFormatter {
             }
         }
 
+        if !plan.warnings.is_empty() {
+            let _ = write!(output, "\n{} Warnings:\n", "⚠".yellow());
+            for warning in &plan.warnings {
+                let _ = writeln!(output, "   - {warning}");
+            }
+        }
+
         output
     }
 
@@ -140,6 +316,35 @@ impl OutputFormatter {
         }
     }
 
+    /// Formats a cached project status, noting when it was observed.
+    #[must_use]
+    pub fn format_cached_status(&self, cached: &CachedStatus) -> String {
+        match self.format {
+            OutputFormat::Json => {
+                let mut json = StatusJson::from(&cached.status);
+                json.observed_at = Some(cached.observed_at);
+                serde_json::to_string_pretty(&json).unwrap_or_default()
+            }
+            OutputFormat::Text => {
+                let age = chrono::Utc::now().signed_duration_since(cached.observed_at);
+                let mut output = Self::format_status_text(&cached.status, None);
+                let _ = write!(
+                    output,
+                    "
+{}
+",
+                    format!(
+                        "Cached status observed at {} ({} min ago)",
+                        cached.observed_at.format("%Y-%m-%d %H:%M:%S UTC"),
+                        age.num_minutes()
+                    )
+                    .dimmed()
+                );
+                output
+            }
+        }
+    }
+
     /// Formats status as text.
     fn format_status_text(status: &ProjectStatus, health: Option<&[HealthStatus]>) -> String {
         let mut output = String::new();
@@ -147,8 +352,7 @@ impl OutputFormatter {
         let _ = write!(
             output,
             "\n📦 Project: {}/{}\n\n",
-            status.project,
-            status.environment
+            status.project, status.environment
         );
 
         if status.pods.is_empty() {
@@ -198,6 +402,37 @@ impl OutputFormatter {
             health_status, status.running, status.stopped, status.error
         );
 
+        let stopped_gb = status.stopped_volume_gb();
+        if stopped_gb > 0 {
+            let _ = writeln!(
+                output,
+                "{}",
+                format!(
+                    "Stopped pods still bill {stopped_gb} GB of volume storage (~${:.2}/month)",
+                    status.stopped_storage_cost_per_month()
+                )
+                .yellow()
+            );
+        }
+
+        if !status.maintenance.is_empty() {
+            output.push_str("\nMaintenance:\n");
+            for (pod, note) in &status.maintenance {
+                let _ = writeln!(output, "   {} {pod}: {note}", "🔧".yellow());
+            }
+        }
+
+        if !status.annotations.is_empty() {
+            output.push_str("\nAnnotations:\n");
+            for (pod, annotations) in &status.annotations {
+                let values: Vec<String> = annotations
+                    .iter()
+                    .map(|(k, v)| format!("{k}={v}"))
+                    .collect();
+                let _ = writeln!(output, "   📝 {pod}: {}", values.join(", "));
+            }
+        }
+
         // Endpoints
         let has_endpoints = status.pods.iter().any(|p| !p.endpoints.is_empty());
         if has_endpoints {
@@ -215,19 +450,369 @@ impl OutputFormatter {
         output
     }
 
+    /// Formats the pods of a project across all environments.
+    ///
+    /// Running pods are priced by GPU, stopped pods by their volume storage.
+    #[must_use]
+    pub fn format_inventory(
+        &self,
+        project: &str,
+        pods: &[ObservedPod],
+        prices: &GpuPrices,
+    ) -> String {
+        let hourly = |pod: &ObservedPod| {
+            if pod.is_stopped() {
+                pod.stopped_storage_cost_per_hour()
+            } else {
+                prices.hourly(pod.gpu_type.as_deref()?, pod.gpu_count)
+            }
+        };
+        let total: f64 = pods.iter().filter_map(hourly).sum();
+
+        match self.format {
+            OutputFormat::Json => {
+                let json = InventoryJson {
+                    project: project.to_string(),
+                    total_cost_per_hour: total,
+                    pods: pods
+                        .iter()
+                        .map(|pod| InventoryPodJson {
+                            environment: pod.environment.clone(),
+                            pod: PodJson::from(pod),
+                            cost_per_hour: hourly(pod),
+                            uptime_secs: pod.uptime_secs,
+                        })
+                        .collect(),
+                };
+                serde_json::to_string_pretty(&json).unwrap_or_default()
+            }
+            OutputFormat::Text => {
+                let mut output = String::new();
+                let _ = write!(output, "\n📦 Project: {project} (all environments)\n\n");
+
+                if pods.is_empty() {
+                    output.push_str("   No pods found.\n");
+                    return output;
+                }
+
+                let rows: Vec<InventoryRow> = pods
+                    .iter()
+                    .map(|p| InventoryRow {
+                        environment: p.environment.clone().unwrap_or_default(),
+                        name: p.pod_name.clone().unwrap_or_else(|| p.name.clone()),
+                        status: Self::format_pod_status(p.status),
+                        gpu: format!(
+                            "{}x {}",
+                            p.gpu_count,
+                            p.gpu_type.as_deref().unwrap_or("unknown")
+                        ),
+                        cost: hourly(p).map_or_else(|| String::from("-"), |c| format!("{c:.3}")),
+                        #[allow(clippy::cast_precision_loss)]
+                        age: if p.uptime_secs > 0 {
+                            format_duration(p.uptime_secs as f64)
+                        } else {
+                            String::from("-")
+                        },
+                    })
+                    .collect();
+
+                output.push_str(&Table::new(rows).to_string());
+                output.push('\n');
+
+                let mut environments: Vec<&str> = pods
+                    .iter()
+                    .filter_map(|p| p.environment.as_deref())
+                    .collect();
+                environments.dedup();
+                let _ = writeln!(
+                    output,
+                    "\n{} pods across {} environments, ~${total:.2}/hr",
+                    pods.len(),
+                    environments.len()
+                );
+                output
+            }
+        }
+    }
+
+    /// Formats a cost report.
+    #[must_use]
+    pub fn format_cost(&self, report: &CostReport) -> String {
+        match self.format {
+            OutputFormat::Json => {
+                serde_json::to_string_pretty(&CostJson::from(report)).unwrap_or_default()
+            }
+            OutputFormat::Text => {
+                let mut output = String::new();
+                let _ = write!(
+                    output,
+                    "\n💰 Project: {} ({})\n\n",
+                    report.project, report.environment
+                );
+
+                if report.pods.is_empty() {
+                    output.push_str("   No pods found.\n");
+                    return output;
+                }
+
+                let rows: Vec<CostRow> = report
+                    .pods
+                    .iter()
+                    .map(|p| CostRow {
+                        name: p.name.clone(),
+                        status: Self::format_pod_status(p.status),
+                        gpu: format!(
+                            "{}x {}",
+                            p.gpu_count,
+                            p.gpu_type.as_deref().unwrap_or("unknown")
+                        ),
+                        cost: p
+                            .cost_per_hour
+                            .map_or_else(|| String::from("-"), |c| format!("{c:.3}")),
+                        since: p.since.map_or_else(
+                            || String::from("-"),
+                            |t| t.format("%Y-%m-%d %H:%M").to_string(),
+                        ),
+                        accumulated: p
+                            .accumulated
+                            .map_or_else(|| String::from("-"), |c| format!("${c:.2}")),
+                    })
+                    .collect();
+
+                output.push_str(&Table::new(rows).to_string());
+                output.push('\n');
+                let _ = writeln!(
+                    output,
+                    "\nTotal: ~${:.2}/hr now, ~${:.2} accumulated",
+                    report.cost_per_hour(),
+                    report.accumulated()
+                );
+                output
+            }
+        }
+    }
+
+    /// Formats a pod's availability report.
+    #[must_use]
+    pub fn format_slo(&self, report: &SloReport) -> String {
+        #[allow(clippy::cast_precision_loss)]
+        let secs = |d: chrono::Duration| d.num_seconds() as f64;
+
+        match self.format {
+            OutputFormat::Json => {
+                serde_json::to_string_pretty(&SloJson::from(report)).unwrap_or_default()
+            }
+            OutputFormat::Text => {
+                let mut output = String::new();
+                let _ = write!(
+                    output,
+                    "\n📈 SLO: {} (since {})\n\n",
+                    report.pod,
+                    report.window_start.format("%Y-%m-%d %H:%M")
+                );
+
+                let Some(availability) = report.availability() else {
+                    output.push_str(
+                        "   No health history in this window. Run `halldyll watch` to record it.\n",
+                    );
+                    return output;
+                };
+
+                #[allow(clippy::cast_precision_loss)]
+                let monitored = report.monitored_secs as f64;
+                let _ = writeln!(
+                    output,
+                    "   Availability:   {availability:.3}% ({} monitored, {} samples)",
+                    format_duration(monitored),
+                    report.samples
+                );
+                let _ = writeln!(output, "   Outages:        {}", report.outages.len());
+                let _ = writeln!(
+                    output,
+                    "   Longest outage: {}",
+                    report
+                        .longest_outage()
+                        .map_or_else(|| String::from("-"), |d| format_duration(secs(d)))
+                );
+                let _ = writeln!(
+                    output,
+                    "   MTTR:           {}",
+                    report
+                        .mttr()
+                        .map_or_else(|| String::from("-"), |d| format_duration(secs(d)))
+                );
+                if report.outages.last().is_some_and(|o| o.end.is_none()) {
+                    let _ = writeln!(output, "   {}", "Currently down".red());
+                }
+                output
+            }
+        }
+    }
+
+    /// Formats resolved environment variables, masking secret values.
+    #[must_use]
+    pub fn format_env(&self, vars: &[ResolvedEnvVar]) -> String {
+        let masked = |var: &ResolvedEnvVar| {
+            if is_secret_env_key(&var.key) {
+                String::from(REDACTED)
+            } else {
+                var.value.clone()
+            }
+        };
+
+        match self.format {
+            OutputFormat::Json => {
+                let json: Vec<EnvVarJson> = vars
+                    .iter()
+                    .map(|var| EnvVarJson {
+                        key: var.key.clone(),
+                        value: masked(var),
+                        source: var.source.clone(),
+                    })
+                    .collect();
+                serde_json::to_string_pretty(&json).unwrap_or_default()
+            }
+            OutputFormat::Text => {
+                if vars.is_empty() {
+                    return String::from("No variables set by dotenv files or the environment.");
+                }
+                let rows: Vec<EnvRow> = vars
+                    .iter()
+                    .map(|var| EnvRow {
+                        key: var.key.clone(),
+                        value: Self::truncate(&masked(var), 60),
+                        source: var.source.clone(),
+                    })
+                    .collect();
+                Table::new(rows).to_string()
+            }
+        }
+    }
+
+    /// Formats the outbound hosts `command` may contact.
+    #[must_use]
+    pub fn format_egress(&self, command: &str, hosts: &[Egress]) -> String {
+        match self.format {
+            OutputFormat::Json => serde_json::to_string_pretty(hosts).unwrap_or_default(),
+            OutputFormat::Text => {
+                if hosts.is_empty() {
+                    return format!("`{command}` makes no outbound connections.");
+                }
+                let rows: Vec<EgressRow> = hosts
+                    .iter()
+                    .map(|h| EgressRow {
+                        host: h.host.clone(),
+                        purpose: h.purpose.clone(),
+                    })
+                    .collect();
+                format!("Hosts `{command}` may contact:\n{}", Table::new(rows))
+            }
+        }
+    }
+
+    /// Formats version details, with an upgrade hint if a newer release exists.
+    #[must_use]
+    pub fn format_version(&self, info: &VersionInfo) -> String {
+        match self.format {
+            OutputFormat::Json => serde_json::to_string_pretty(info).unwrap_or_default(),
+            OutputFormat::Text => {
+                let mut output = format!(
+                    "halldyll {} ({})\nState version: {}\n",
+                    info.version, info.git_sha, info.state_version
+                );
+                if let Some(latest) = info.update_available() {
+                    let _ = writeln!(
+                        output,
+                        "{} halldyll {latest} is available: cargo install halldyll_deploy_pods",
+                        "⬆".cyan()
+                    );
+                } else if info.latest.is_some() {
+                    let _ = writeln!(output, "{} Up to date", "✓".green());
+                }
+                output
+            }
+        }
+    }
+
+    /// Formats orphaned pods found by an audit.
+    #[must_use]
+    pub fn format_orphans(&self, orphans: &[Orphan], workspaces: usize) -> String {
+        match self.format {
+            OutputFormat::Json => {
+                let json: Vec<OrphanJson> = orphans
+                    .iter()
+                    .map(|o| OrphanJson {
+                        project: o.pod.project.clone(),
+                        environment: o.pod.environment.clone(),
+                        pod: PodJson::from(&o.pod),
+                        reason: o.reason.to_string(),
+                    })
+                    .collect();
+                serde_json::to_string_pretty(&json).unwrap_or_default()
+            }
+            OutputFormat::Text => {
+                if orphans.is_empty() {
+                    return format!(
+                        "{} No orphaned pods ({workspaces} workspaces checked).\n",
+                        "✓".green()
+                    );
+                }
+
+                let rows: Vec<OrphanRow> = orphans
+                    .iter()
+                    .map(|o| OrphanRow {
+                        project: o.pod.project.clone().unwrap_or_default(),
+                        environment: o.pod.environment.clone().unwrap_or_default(),
+                        name: o.pod.pod_name.clone().unwrap_or_else(|| o.pod.name.clone()),
+                        status: Self::format_pod_status(o.pod.status),
+                        reason: o.reason.to_string(),
+                        id: o.pod.id.clone(),
+                    })
+                    .collect();
+
+                format!(
+                    "{}\n\n{} orphaned pods ({workspaces} workspaces checked)\n",
+                    Table::new(rows),
+                    orphans.len()
+                )
+            }
+        }
+    }
+
+    /// Formats a pre-flight check report.
+    #[must_use]
+    pub fn format_preflight(&self, report: &PreflightReport) -> String {
+        match self.format {
+            OutputFormat::Json => serde_json::to_string_pretty(report).unwrap_or_default(),
+            OutputFormat::Text => format!("{report}\n"),
+        }
+    }
+
     /// Formats a drift report.
     #[must_use]
     pub fn format_drift(&self, report: &DriftReport) -> String {
         match self.format {
             OutputFormat::Json => serde_json::to_string_pretty(report).unwrap_or_default(),
             OutputFormat::Text => {
-                if report.is_converged() {
+                let mut output = if report.is_converged() {
                     format!("{} No drift detected - state is converged.\n", "✓".green())
                 } else {
                     let mut output = format!("{} Drift detected:\n\n", "⚠".yellow());
                     for resource in &report.drifted_resources {
                         let _ = writeln!(output, "   - {resource}");
                     }
+                    if !report.model_drift.is_empty() {
+                        let _ = writeln!(output, "\n   Served models differ from configuration:");
+                        for models in &report.model_drift {
+                            let _ = writeln!(output, "   - {models}");
+                        }
+                    }
+                    if !report.endpoint_drift.is_empty() {
+                        let _ = writeln!(output, "\n   Configured but unreachable endpoints:");
+                        for endpoint in &report.endpoint_drift {
+                            let _ = writeln!(output, "   - {endpoint}");
+                        }
+                    }
                     let _ = write!(
                         output,
                         "\n{}/{} resources have drifted.\n",
@@ -235,7 +820,99 @@ impl OutputFormatter {
                         report.total_resources
                     );
                     output
+                };
+                if !report.maintenance.is_empty() {
+                    let _ = writeln!(
+                        output,
+                        "{}",
+                        format!(
+                            "Not checked (under maintenance): {}",
+                            report.maintenance.join(", ")
+                        )
+                        .dimmed()
+                    );
+                }
+                output
+            }
+        }
+    }
+
+    /// Formats the result of applying a plan, with how long each action took.
+    #[must_use]
+    pub fn format_execution(&self, result: &ExecutionResult) -> String {
+        match self.format {
+            OutputFormat::Json => {
+                serde_json::to_string_pretty(&ExecutionJson::from(result)).unwrap_or_default()
+            }
+            OutputFormat::Text => {
+                let mut output = format!(
+                    "Executed {} actions: {} successful, {} failed, {} skipped",
+                    result.total_executed, result.successful, result.failed, result.skipped
+                );
+                if let Some(secs) = result.duration_secs() {
+                    let _ = write!(output, " in {}", format_duration(secs));
+                }
+                output.push('\n');
+
+                for action in &result.results {
+                    let mark = if action.success {
+                        "✓".green()
+                    } else {
+                        "✗".red()
+                    };
+                    let duration = action
+                        .timing
+                        .map_or_else(|| String::from("-"), |t| format_duration(t.duration_secs));
+                    let _ = writeln!(
+                        output,
+                        "  {mark} {:<14} {:<24} {duration}",
+                        action.action.action_type.to_string(),
+                        action.action.resource_name
+                    );
+                    if let Some(error) = action.error.as_deref().filter(|_| !action.success) {
+                        let _ = writeln!(output, "      {error}");
+                    }
+                    if let Some(hint) = &action.hint {
+                        let _ = writeln!(output, "      hint: {hint}");
+                    }
+                }
+
+                if result.deadline_reached {
+                    let _ = writeln!(output, "  Stopped at the apply deadline");
+                }
+                output
+            }
+        }
+    }
+
+    /// Formats the pods an apply brought up: their endpoints, SSH command and engine status.
+    #[must_use]
+    pub fn format_ready_pods(&self, pods: &[ReadyPod]) -> String {
+        match self.format {
+            OutputFormat::Json => serde_json::to_string_pretty(pods).unwrap_or_default(),
+            OutputFormat::Text => {
+                let mut output = String::from("Ready to use:\n");
+                for pod in pods {
+                    let _ = writeln!(output, "  {} ({})", pod.name.bold(), pod.pod_id);
+                    for (port, url) in &pod.endpoints {
+                        let _ = writeln!(output, "    {port} -> {url}");
+                    }
+                    if let Some(ssh) = &pod.ssh {
+                        let _ = writeln!(output, "    {ssh}");
+                    }
+                    let status = match pod.healthy {
+                        Some(true) => "healthy".green().to_string(),
+                        Some(false) => "not responding yet".yellow().to_string(),
+                        None => "not checked".dimmed().to_string(),
+                    };
+                    if pod.engines.is_empty() {
+                        let _ = writeln!(output, "    status: {status}");
+                    } else {
+                        let _ =
+                            writeln!(output, "    engine: {} ({status})", pod.engines.join(", "));
+                    }
                 }
+                output
             }
         }
     }
@@ -281,15 +958,35 @@ impl OutputFormatter {
                 let _ = write!(
                     output,
                     "\n💾 State: {}/{}\n\n",
-                    state.project,
-                    state.environment
+                    state.project, state.environment
                 );
 
                 let _ = writeln!(output, "   Version: {}", state.version);
-                let _ = writeln!(output, "   Config hash: {}", &state.config_hash[..8.min(state.config_hash.len())]);
+                let _ = writeln!(
+                    output,
+                    "   Config hash: {}",
+                    &state.config_hash[..8.min(state.config_hash.len())]
+                );
                 let _ = writeln!(output, "   Last updated: {}", state.last_updated);
                 let _ = writeln!(output, "   Pods: {}", state.pods.len());
-                let _ = writeln!(output, "   Volumes: {}", state.volumes.len());
+                let detached = state.volumes.values().filter(|v| v.detached).count();
+                if detached > 0 {
+                    let _ = writeln!(
+                        output,
+                        "   Volumes: {} ({detached} detached)",
+                        state.volumes.len()
+                    );
+                } else {
+                    let _ = writeln!(output, "   Volumes: {}", state.volumes.len());
+                }
+                if let Some(release) = &state.release {
+                    let _ = writeln!(
+                        output,
+                        "   Release: {} ({} pods, waiting for promote or rollback)",
+                        release.color,
+                        release.pods.len()
+                    );
+                }
 
                 if !state.history.is_empty() {
                     let _ = writeln!(output, "\n   Recent history ({}):", state.history.len());
@@ -310,6 +1007,111 @@ impl OutputFormatter {
         }
     }
 
+    /// Formats selected fields of pod states (all fields if `fields` is empty).
+    #[must_use]
+    pub fn format_pod_states(&self, pods: &[&PodState], fields: &[String]) -> String {
+        let selected: serde_json::Map<String, serde_json::Value> = pods
+            .iter()
+            .map(|pod| {
+                (
+                    pod.name.clone(),
+                    serde_json::Value::Object(pod.fields(fields)),
+                )
+            })
+            .collect();
+
+        match self.format {
+            OutputFormat::Json => serde_json::to_string_pretty(&selected).unwrap_or_default(),
+            OutputFormat::Text => {
+                if selected.is_empty() {
+                    return String::from("No pods in state.");
+                }
+
+                let mut output = String::new();
+                for (name, values) in &selected {
+                    let _ = writeln!(output, "{}", name.bold());
+                    for (field, value) in values.as_object().into_iter().flatten() {
+                        match value {
+                            serde_json::Value::String(s) => {
+                                let _ = writeln!(output, "   {field}: {s}");
+                            }
+                            value => {
+                                let _ = writeln!(output, "   {field}: {value}");
+                            }
+                        }
+                    }
+                }
+                output
+            }
+        }
+    }
+
+    /// Formats the deployment history recorded in the state.
+    #[must_use]
+    pub fn format_history(&self, state: &DeploymentState) -> String {
+        match self.format {
+            OutputFormat::Json => serde_json::to_string_pretty(&state.history).unwrap_or_default(),
+            OutputFormat::Text => {
+                if state.history.is_empty() {
+                    return String::from("No deployment history.");
+                }
+
+                let mut output = String::new();
+                let _ = writeln!(output, "\n📜 History ({} entries):\n", state.history.len());
+                for entry in state.history.iter().rev() {
+                    let status = if entry.success { "✓" } else { "✗" };
+                    let _ = write!(
+                        output,
+                        "   {status} {} - {} [{}]",
+                        entry.timestamp.format("%Y-%m-%d %H:%M"),
+                        entry.operation,
+                        &entry.config_hash[..8.min(entry.config_hash.len())]
+                    );
+                    if let Some(details) = entry.details.as_ref().or(entry.error.as_ref()) {
+                        let _ = write!(output, " {details}");
+                    }
+                    let _ = writeln!(output);
+                }
+
+                output
+            }
+        }
+    }
+
+    /// Formats stored state object versions.
+    #[must_use]
+    pub fn format_state_versions(&self, versions: &[StateVersion]) -> String {
+        match self.format {
+            OutputFormat::Json => serde_json::to_string_pretty(versions).unwrap_or_default(),
+            OutputFormat::Text => {
+                if versions.is_empty() {
+                    return String::from("No stored state versions.");
+                }
+
+                let mut output = String::new();
+                let _ = writeln!(output, "\n🗂  State versions ({}):\n", versions.len());
+                for version in versions {
+                    let modified = version.last_modified.map_or_else(
+                        || String::from("unknown"),
+                        |t| t.format("%Y-%m-%d %H:%M:%S").to_string(),
+                    );
+                    let latest = if version.is_latest {
+                        " (current)".green().to_string()
+                    } else {
+                        String::new()
+                    };
+                    let _ = writeln!(
+                        output,
+                        "   {}  {modified}  {} bytes{latest}",
+                        version.version_id, version.size
+                    );
+                }
+
+                output
+            }
+        }
+    }
+
     /// Formats an action type with color.
     fn format_action_type(action_type: ActionType) -> String {
         match action_type {
@@ -318,6 +1120,15 @@ impl OutputFormatter {
             ActionType::DeletePod => "-delete".red().to_string(),
             ActionType::StopPod => "stop".yellow().to_string(),
             ActionType::ResumePod => "resume".green().to_string(),
+            ActionType::DestroyPod => "-destroy".red().to_string(),
+            ActionType::CreateVolume => "+volume".green().to_string(),
+            ActionType::DeleteVolume => "-volume".red().to_string(),
+            ActionType::CreateEndpoint => "+endpoint".green().to_string(),
+            ActionType::UpdateEndpoint => "~endpoint".yellow().to_string(),
+            ActionType::DeleteEndpoint => "-endpoint".red().to_string(),
+            ActionType::CreateTemplate => "+template".green().to_string(),
+            ActionType::UpdateTemplate => "~template".yellow().to_string(),
+            ActionType::DeleteTemplate => "-template".red().to_string(),
             ActionType::Noop => "noop".dimmed().to_string(),
         }
     }
@@ -385,6 +1196,42 @@ impl OutputFormatter {
     }
 }
 
+/// Removes terminal color sequences (`ESC [ ... m`) from text.
+fn strip_ansi(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' && chars.peek() == Some(&'[') {
+            for next in chars.by_ref() {
+                if next.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            stripped.push(c);
+        }
+    }
+    stripped
+}
+
+/// Formats seconds compactly, e.g. `45s` or `4m12s`.
+fn format_duration(secs: f64) -> String {
+    // Saturating float-to-int cast; durations are never negative
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let secs = secs.round() as u64;
+    match (secs / 3600, secs % 3600 / 60, secs % 60) {
+        (0, 0, s) => format!("{s}s"),
+        (0, m, s) => format!("{m}m{s:02}s"),
+        (h, m, _) => format!("{h}h{m:02}m"),
+    }
+}
+
+/// Formats a change in hourly cost with its sign, e.g. `+$0.79` or `-$0.40`.
+fn format_cost_delta(delta: f64) -> String {
+    let sign = if delta < 0.0 { '-' } else { '+' };
+    format!("{sign}${:.2}", delta.abs())
+}
+
 // JSON serialization helpers
 
 #[derive(serde::Serialize)]
@@ -394,14 +1241,102 @@ struct PlanJson {
     creates: usize,
     deletes: usize,
     passes_guardrails: bool,
+    refreshed: bool,
+    warnings: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    runway: Option<RunwayJson>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    estimated_duration_secs: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    estimated_hourly_cost: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    estimated_cost_delta: Option<f64>,
     actions: Vec<ActionJson>,
 }
 
+#[derive(serde::Serialize)]
+struct ExecutionJson {
+    success: bool,
+    successful: usize,
+    failed: usize,
+    skipped: usize,
+    deadline_reached: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duration_secs: Option<f64>,
+    actions: Vec<ActionResultJson>,
+}
+
+#[derive(serde::Serialize)]
+struct ActionResultJson {
+    action_type: String,
+    resource: String,
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pod_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    started_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    finished_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duration_secs: Option<f64>,
+}
+
+impl From<&ExecutionResult> for ExecutionJson {
+    fn from(result: &ExecutionResult) -> Self {
+        Self {
+            success: result.success,
+            successful: result.successful,
+            failed: result.failed,
+            skipped: result.skipped,
+            deadline_reached: result.deadline_reached,
+            duration_secs: result.duration_secs(),
+            actions: result
+                .results
+                .iter()
+                .map(|r| ActionResultJson {
+                    action_type: r.action.action_type.to_string(),
+                    resource: r.action.resource_name.clone(),
+                    success: r.success,
+                    pod_id: r.pod_id.clone(),
+                    error: r.error.clone(),
+                    started_at: r.timing.map(|t| t.started_at),
+                    finished_at: r.timing.map(|t| t.finished_at),
+                    duration_secs: r.timing.map(|t| t.duration_secs),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+struct RunwayJson {
+    balance: f64,
+    current_spend_per_hour: f64,
+    planned_spend_per_hour: f64,
+    hours: Option<f64>,
+}
+
 #[derive(serde::Serialize)]
 struct ActionJson {
     action_type: String,
     resource: String,
     reason: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    estimated_secs: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hourly_cost_delta: Option<f64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    changes: Vec<ChangeJson>,
+}
+
+#[derive(serde::Serialize)]
+struct ChangeJson {
+    field: String,
+    old_value: Option<String>,
+    new_value: Option<String>,
+    forces_replacement: bool,
 }
 
 impl From<&DeploymentPlan> for PlanJson {
@@ -412,6 +1347,17 @@ impl From<&DeploymentPlan> for PlanJson {
             creates: plan.create_count(),
             deletes: plan.delete_count(),
             passes_guardrails: plan.passes_guardrails,
+            refreshed: plan.refreshed,
+            warnings: plan.warnings.clone(),
+            runway: plan.runway.map(|r| RunwayJson {
+                balance: r.balance,
+                current_spend_per_hour: r.current_spend_per_hour,
+                planned_spend_per_hour: r.planned_spend_per_hour,
+                hours: r.hours(),
+            }),
+            estimated_duration_secs: plan.estimated_duration_secs(),
+            estimated_hourly_cost: plan.estimated_hourly_cost,
+            estimated_cost_delta: plan.estimated_cost_delta,
             actions: plan
                 .actions
                 .iter()
@@ -419,6 +1365,18 @@ impl From<&DeploymentPlan> for PlanJson {
                     action_type: a.action_type.to_string(),
                     resource: a.resource_name.clone(),
                     reason: a.reason.clone(),
+                    estimated_secs: a.estimated_secs,
+                    hourly_cost_delta: a.hourly_cost_delta,
+                    changes: a
+                        .changes
+                        .iter()
+                        .map(|c| ChangeJson {
+                            field: c.field.clone(),
+                            old_value: c.old_value.clone(),
+                            new_value: c.new_value.clone(),
+                            forces_replacement: c.forces_replacement,
+                        })
+                        .collect(),
                 })
                 .collect(),
         }
@@ -433,7 +1391,136 @@ struct StatusJson {
     running: usize,
     stopped: usize,
     error: usize,
+    stopped_storage_cost_per_month: f64,
     pods: Vec<PodJson>,
+    #[serde(skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    maintenance: std::collections::BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    annotations: std::collections::BTreeMap<String, std::collections::BTreeMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    observed_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(serde::Serialize)]
+struct OrphanJson {
+    project: Option<String>,
+    environment: Option<String>,
+    #[serde(flatten)]
+    pod: PodJson,
+    reason: String,
+}
+
+#[derive(serde::Serialize)]
+struct InventoryJson {
+    project: String,
+    total_cost_per_hour: f64,
+    pods: Vec<InventoryPodJson>,
+}
+
+#[derive(serde::Serialize)]
+struct InventoryPodJson {
+    environment: Option<String>,
+    #[serde(flatten)]
+    pod: PodJson,
+    cost_per_hour: Option<f64>,
+    uptime_secs: u64,
+}
+
+#[derive(serde::Serialize)]
+struct EnvVarJson {
+    key: String,
+    value: String,
+    source: String,
+}
+
+#[derive(serde::Serialize)]
+struct SloJson {
+    pod: String,
+    window_start: chrono::DateTime<chrono::Utc>,
+    window_end: chrono::DateTime<chrono::Utc>,
+    samples: usize,
+    monitored_secs: i64,
+    availability_percent: Option<f64>,
+    longest_outage_secs: Option<i64>,
+    mttr_secs: Option<i64>,
+    outages: Vec<OutageJson>,
+}
+
+#[derive(serde::Serialize)]
+struct OutageJson {
+    start: chrono::DateTime<chrono::Utc>,
+    end: Option<chrono::DateTime<chrono::Utc>>,
+    duration_secs: i64,
+}
+
+impl From<&SloReport> for SloJson {
+    fn from(report: &SloReport) -> Self {
+        Self {
+            pod: report.pod.clone(),
+            window_start: report.window_start,
+            window_end: report.window_end,
+            samples: report.samples,
+            monitored_secs: report.monitored_secs,
+            availability_percent: report.availability(),
+            longest_outage_secs: report.longest_outage().map(|d| d.num_seconds()),
+            mttr_secs: report.mttr().map(|d| d.num_seconds()),
+            outages: report
+                .outages
+                .iter()
+                .map(|o| OutageJson {
+                    start: o.start,
+                    end: o.end,
+                    duration_secs: o.duration(report.window_end).num_seconds(),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+struct CostJson {
+    project: String,
+    environment: String,
+    total_cost_per_hour: f64,
+    total_accumulated: f64,
+    pods: Vec<PodCostJson>,
+}
+
+#[derive(serde::Serialize)]
+struct PodCostJson {
+    name: String,
+    id: String,
+    status: String,
+    gpu_type: Option<String>,
+    gpu_count: u32,
+    cost_per_hour: Option<f64>,
+    since: Option<chrono::DateTime<chrono::Utc>>,
+    accumulated: Option<f64>,
+}
+
+impl From<&CostReport> for CostJson {
+    fn from(report: &CostReport) -> Self {
+        Self {
+            project: report.project.clone(),
+            environment: report.environment.clone(),
+            total_cost_per_hour: report.cost_per_hour(),
+            total_accumulated: report.accumulated(),
+            pods: report
+                .pods
+                .iter()
+                .map(|p| PodCostJson {
+                    name: p.name.clone(),
+                    id: p.pod_id.clone(),
+                    status: p.status.to_string(),
+                    gpu_type: p.gpu_type.clone(),
+                    gpu_count: p.gpu_count,
+                    cost_per_hour: p.cost_per_hour,
+                    since: p.since,
+                    accumulated: p.accumulated,
+                })
+                .collect(),
+        }
+    }
 }
 
 #[derive(serde::Serialize)]
@@ -455,6 +1542,7 @@ impl From<&ProjectStatus> for StatusJson {
             running: status.running,
             stopped: status.stopped,
             error: status.error,
+            stopped_storage_cost_per_month: status.stopped_storage_cost_per_month(),
             pods: status
                 .pods
                 .iter()
@@ -467,6 +1555,9 @@ impl From<&ProjectStatus> for StatusJson {
                     image: p.image.clone(),
                 })
                 .collect(),
+            maintenance: status.maintenance.clone(),
+            annotations: status.annotations.clone(),
+            observed_at: None,
         }
     }
 }
@@ -483,3 +1574,172 @@ impl From<&ObservedPod> for PodJson {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_emit_writes_out_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("report.txt");
+        let formatter = OutputFormatter::new(OutputFormat::Text).with_out_file(Some(path.clone()));
+
+        formatter.emit("\u{1b}[1;32m✓\u{1b}[0m up to date").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "✓ up to date\n");
+    }
+
+    #[test]
+    fn test_format_inventory_json() {
+        let gpu: crate::runpod::GpuType = serde_json::from_value(serde_json::json!({
+            "id": "NVIDIA A40",
+            "displayName": "A40",
+            "memoryInGb": 48,
+            "securePrice": 0.4
+        }))
+        .unwrap();
+        let prices = GpuPrices::new(&[gpu], crate::config::CloudType::Secure);
+        let pods: Vec<ObservedPod> = ["dev", "prod"]
+            .into_iter()
+            .map(|env| ObservedPod {
+                id: format!("id-{env}"),
+                name: format!("proj-{env}-llm"),
+                project: Some(String::from("proj")),
+                environment: Some(env.to_string()),
+                pod_name: Some(String::from("llm")),
+                spec_hash: None,
+                status: PodStatus::Running,
+                gpu_type: Some(String::from("NVIDIA A40")),
+                gpu_count: 2,
+                image: String::from("img"),
+                endpoints: std::collections::HashMap::new(),
+                gpu_utilization: None,
+                tags: std::collections::HashMap::new(),
+                volume_gb: 0,
+                uptime_secs: 3600,
+                env: None,
+            })
+            .collect();
+
+        // Emitted like the other reports, so --out-file gets the JSON too
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("inventory.json");
+        let formatter = OutputFormatter::new(OutputFormat::Json).with_out_file(Some(path.clone()));
+        formatter
+            .emit(&formatter.format_inventory("proj", &pods, &prices))
+            .unwrap();
+
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(json["project"], "proj");
+        assert_eq!(json["total_cost_per_hour"], 1.6);
+        assert_eq!(json["pods"][1]["environment"], "prod");
+        assert_eq!(json["pods"][1]["id"], "id-prod");
+        assert_eq!(json["pods"][1]["cost_per_hour"], 0.8);
+        assert_eq!(json["pods"][1]["uptime_secs"], 3600);
+    }
+
+    #[test]
+    fn test_format_pod_states_selects_pods_and_fields() {
+        let mut state = DeploymentState::new("proj", "prod");
+        for (name, id) in [
+            ("llm-0", "pod-a"),
+            ("llm-1", "pod-b"),
+            ("llm-cache", "pod-c"),
+        ] {
+            state.set_pod(PodState::new(name, id, "hash"));
+        }
+
+        let pods = state.select_pods(Some("llm"));
+        assert_eq!(
+            pods.iter().map(|p| p.name.as_str()).collect::<Vec<_>>(),
+            ["llm-0", "llm-1"]
+        );
+
+        let fields = vec![String::from("runpod_id"), String::from("color")];
+        let json = OutputFormatter::new(OutputFormat::Json).format_pod_states(&pods, &fields);
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            value["llm-1"],
+            serde_json::json!({"runpod_id": "pod-b", "color": null})
+        );
+
+        let text = OutputFormatter::new(OutputFormat::Text).format_pod_states(&pods[..1], &fields);
+        assert!(text.contains("runpod_id: pod-a"));
+        assert!(!text.contains("status"));
+    }
+
+    #[test]
+    fn test_format_execution_shows_action_durations() {
+        use crate::planner::{ActionResult, ActionTimes, DiffEngine};
+
+        let yaml = "project:\n  name: proj\nstate:\n  backend: local\npods:\n  - name: pod-text\n    gpu:\n      type: A40\n    runtime:\n      image: img\n";
+        let config: crate::config::DeployConfig = serde_yaml::from_str(yaml).unwrap();
+        let diff = DiffEngine::new().compute_diff(&config, None, &[]);
+        let plan = DeploymentPlan::from_diff(&diff, &config, "hash");
+        let started_at = chrono::Utc::now();
+        let result = ExecutionResult {
+            results: vec![ActionResult {
+                index: 0,
+                action: plan.actions[0].clone(),
+                success: true,
+                pod_id: Some(String::from("pod-1")),
+                error: None,
+                hint: None,
+                quota_exceeded: false,
+                timing: Some(ActionTimes {
+                    started_at,
+                    finished_at: started_at + chrono::Duration::seconds(252),
+                    duration_secs: 252.0,
+                }),
+            }],
+            total_executed: 1,
+            successful: 1,
+            failed: 0,
+            skipped: 0,
+            success: true,
+            deadline_reached: false,
+        };
+
+        let text = strip_ansi(&OutputFormatter::new(OutputFormat::Text).format_execution(&result));
+        assert!(text.contains("1 successful, 0 failed, 0 skipped in 4m12s"));
+        assert!(text.contains("✓ create"));
+        assert!(
+            text.lines()
+                .any(|l| l.contains("pod-text") && l.ends_with("4m12s"))
+        );
+
+        let json: serde_json::Value = serde_json::from_str(
+            &OutputFormatter::new(OutputFormat::Json).format_execution(&result),
+        )
+        .unwrap();
+        assert_eq!(json["actions"][0]["duration_secs"], 252.0);
+    }
+
+    #[test]
+    fn test_format_ready_pods() {
+        let pods = [ReadyPod {
+            name: String::from("llm"),
+            pod_id: String::from("pod-1"),
+            endpoints: [(8000, String::from("https://pod-1-8000.proxy.runpod.net"))].into(),
+            ssh: Some(String::from("ssh root@213.173.1.9 -p 40122")),
+            engines: vec![String::from("vllm")],
+            healthy: Some(false),
+        }];
+
+        let text = strip_ansi(&OutputFormatter::new(OutputFormat::Text).format_ready_pods(&pods));
+        assert!(text.contains("llm (pod-1)"));
+        assert!(text.contains("8000 -> https://pod-1-8000.proxy.runpod.net"));
+        assert!(text.contains("ssh root@213.173.1.9 -p 40122"));
+        assert!(text.contains("engine: vllm (not responding yet)"));
+
+        let json: serde_json::Value = serde_json::from_str(
+            &OutputFormatter::new(OutputFormat::Json).format_ready_pods(&pods),
+        )
+        .unwrap();
+        assert_eq!(
+            json[0]["endpoints"]["8000"],
+            "https://pod-1-8000.proxy.runpod.net"
+        );
+    }
+}
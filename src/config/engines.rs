@@ -0,0 +1,313 @@
+//! Known command-line options of the supported inference engines.
+//!
+//! `load.options` are passed verbatim as `--key value` flags to the engine
+//! on the pod, so a typo only surfaces once the engine fails to start. These
+//! tables let `validate` catch unknown or malformed options locally.
+
+use super::spec::LoadConfig;
+
+/// Expected value of an engine option.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptionKind {
+    /// Boolean switch, passed as a bare `--flag`.
+    Flag,
+    /// Integer value.
+    Int,
+    /// Floating point value.
+    Float,
+    /// Free-form string value.
+    Str,
+    /// One of a fixed set of values.
+    Choice(&'static [&'static str]),
+}
+
+/// A known engine option.
+#[derive(Debug, Clone, Copy)]
+pub struct EngineOption {
+    /// Option name without the leading `--`.
+    pub name: &'static str,
+    /// Expected value kind.
+    pub kind: OptionKind,
+}
+
+/// Shorthand for building option tables.
+const fn opt(name: &'static str, kind: OptionKind) -> EngineOption {
+    EngineOption { name, kind }
+}
+
+/// Known vLLM `api_server` options.
+const VLLM_OPTIONS: &[EngineOption] = &[
+    opt("api-key", OptionKind::Str),
+    opt("block-size", OptionKind::Int),
+    opt("chat-template", OptionKind::Str),
+    opt("cpu-offload-gb", OptionKind::Float),
+    opt("disable-custom-all-reduce", OptionKind::Flag),
+    opt("disable-log-requests", OptionKind::Flag),
+    opt("disable-log-stats", OptionKind::Flag),
+    opt(
+        "distributed-executor-backend",
+        OptionKind::Choice(&["ray", "mp"]),
+    ),
+    opt("download-dir", OptionKind::Str),
+    opt(
+        "dtype",
+        OptionKind::Choice(&["auto", "half", "float16", "bfloat16", "float", "float32"]),
+    ),
+    opt("enable-auto-tool-choice", OptionKind::Flag),
+    opt("enable-chunked-prefill", OptionKind::Flag),
+    opt("enable-lora", OptionKind::Flag),
+    opt("enable-prefix-caching", OptionKind::Flag),
+    opt("enforce-eager", OptionKind::Flag),
+    opt("gpu-memory-utilization", OptionKind::Float),
+    opt("guided-decoding-backend", OptionKind::Str),
+    opt(
+        "kv-cache-dtype",
+        OptionKind::Choice(&["auto", "fp8", "fp8_e5m2", "fp8_e4m3"]),
+    ),
+    opt("limit-mm-per-prompt", OptionKind::Str),
+    opt("load-format", OptionKind::Str),
+    opt("lora-modules", OptionKind::Str),
+    opt("max-logprobs", OptionKind::Int),
+    opt("max-lora-rank", OptionKind::Int),
+    opt("max-loras", OptionKind::Int),
+    opt("max-num-batched-tokens", OptionKind::Int),
+    opt("max-num-seqs", OptionKind::Int),
+    opt("max-seq-len-to-capture", OptionKind::Int),
+    opt("num-scheduler-steps", OptionKind::Int),
+    opt("pipeline-parallel-size", OptionKind::Int),
+    opt("revision", OptionKind::Str),
+    opt("rope-scaling", OptionKind::Str),
+    opt("seed", OptionKind::Int),
+    opt("served-model-name", OptionKind::Str),
+    opt("swap-space", OptionKind::Float),
+    opt("tensor-parallel-size", OptionKind::Int),
+    opt("tokenizer", OptionKind::Str),
+    opt(
+        "tokenizer-mode",
+        OptionKind::Choice(&["auto", "slow", "mistral"]),
+    ),
+    opt("tool-call-parser", OptionKind::Str),
+    opt("trust-remote-code", OptionKind::Flag),
+    opt(
+        "uvicorn-log-level",
+        OptionKind::Choice(&["debug", "info", "warning", "error", "critical", "trace"]),
+    ),
+];
+
+/// Known `text-generation-launcher` options.
+const TGI_OPTIONS: &[EngineOption] = &[
+    opt("cuda-graphs", OptionKind::Str),
+    opt("cuda-memory-fraction", OptionKind::Float),
+    opt("disable-custom-kernels", OptionKind::Flag),
+    opt("disable-grammar-support", OptionKind::Flag),
+    opt("dtype", OptionKind::Choice(&["float16", "bfloat16"])),
+    opt(
+        "kv-cache-dtype",
+        OptionKind::Choice(&["fp8_e4m3fnuz", "fp8_e5m2"]),
+    ),
+    opt("lora-adapters", OptionKind::Str),
+    opt("max-batch-prefill-tokens", OptionKind::Int),
+    opt("max-batch-total-tokens", OptionKind::Int),
+    opt("max-best-of", OptionKind::Int),
+    opt("max-client-batch-size", OptionKind::Int),
+    opt("max-concurrent-requests", OptionKind::Int),
+    opt("max-stop-sequences", OptionKind::Int),
+    opt("max-top-n-tokens", OptionKind::Int),
+    opt("max-total-tokens", OptionKind::Int),
+    opt("max-waiting-tokens", OptionKind::Int),
+    opt("num-shard", OptionKind::Int),
+    opt("payload-limit", OptionKind::Int),
+    opt("revision", OptionKind::Str),
+    opt("rope-factor", OptionKind::Float),
+    opt("rope-scaling", OptionKind::Choice(&["linear", "dynamic"])),
+    opt("sharded", OptionKind::Choice(&["true", "false"])),
+    opt("speculate", OptionKind::Int),
+    opt("trust-remote-code", OptionKind::Flag),
+    opt("validation-workers", OptionKind::Int),
+    opt("waiting-served-ratio", OptionKind::Float),
+];
+
+/// Options that halldyll sets itself, with the config field to use instead.
+const MANAGED_OPTIONS: &[(&str, &str)] = &[
+    ("model", "repo"),
+    ("model-id", "repo"),
+    ("port", "ports"),
+    ("host", "ports"),
+    ("hostname", "ports"),
+    ("quantization", "load.quant"),
+    ("quantize", "load.quant"),
+    ("max-model-len", "load.max_seq_len"),
+    ("max-input-length", "load.max_seq_len"),
+];
+
+/// Returns the known options of an engine, or `None` if the engine takes no options.
+#[must_use]
+pub fn engine_options(engine: &str) -> Option<&'static [EngineOption]> {
+    match engine.to_lowercase().as_str() {
+        "vllm" => Some(VLLM_OPTIONS),
+        "tgi" | "text-generation-inference" => Some(TGI_OPTIONS),
+        _ => None,
+    }
+}
+
+/// Returns the config field to use instead of a halldyll-managed option.
+#[must_use]
+pub fn managed_option(name: &str) -> Option<&'static str> {
+    MANAGED_OPTIONS
+        .iter()
+        .find(|(option, _)| *option == name)
+        .map(|(_, field)| *field)
+}
+
+/// Returns the option an engine uses to shard a model across GPUs.
+#[must_use]
+pub fn parallelism_option(engine: &str) -> Option<&'static str> {
+    match engine.to_lowercase().as_str() {
+        "vllm" => Some("tensor-parallel-size"),
+        "tgi" | "text-generation-inference" => Some("num-shard"),
+        _ => None,
+    }
+}
+
+/// Returns the explicitly configured tensor parallel size, if any.
+#[must_use]
+pub fn explicit_parallelism(load: &LoadConfig) -> Option<u64> {
+    let option = parallelism_option(&load.engine)?;
+    load.options.get(option).and_then(serde_json::Value::as_u64)
+}
+
+/// Returns the configured pipeline parallel size (1 if unset).
+#[must_use]
+pub fn pipeline_parallelism(load: &LoadConfig) -> u64 {
+    load.options
+        .get("pipeline-parallel-size")
+        .and_then(serde_json::Value::as_u64)
+        .unwrap_or(1)
+        .max(1)
+}
+
+/// Returns the tensor parallel size to request on a pod with `gpu_count`
+/// GPUs: the explicit size, else the GPUs left per pipeline stage.
+#[must_use]
+pub fn requested_parallelism(load: &LoadConfig, gpu_count: u32) -> u32 {
+    explicit_parallelism(load).map_or_else(
+        || u32::try_from(u64::from(gpu_count) / pipeline_parallelism(load)).unwrap_or(u32::MAX),
+        |size| u32::try_from(size).unwrap_or(u32::MAX),
+    )
+}
+
+/// Finds the closest known option name, for "did you mean" hints.
+#[must_use]
+pub fn suggest_option(options: &[EngineOption], name: &str) -> Option<&'static str> {
+    let normalized = name
+        .trim_start_matches('-')
+        .replace('_', "-")
+        .to_lowercase();
+
+    options
+        .iter()
+        .map(|o| (o.name, edit_distance(o.name, &normalized)))
+        .filter(|(_, distance)| *distance <= 2)
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(option, _)| option)
+}
+
+impl OptionKind {
+    /// Checks a YAML option value, returning a description of the problem if malformed.
+    #[must_use]
+    pub fn check(self, value: &serde_json::Value) -> Option<String> {
+        match (self, value) {
+            (Self::Flag, serde_json::Value::Bool(_))
+            | (Self::Float, serde_json::Value::Number(_))
+            | (Self::Str, serde_json::Value::String(_)) => None,
+            (Self::Int, serde_json::Value::Number(n)) if n.is_i64() || n.is_u64() => None,
+            (Self::Choice(choices), serde_json::Value::String(s))
+                if choices.contains(&s.as_str()) =>
+            {
+                None
+            }
+            (Self::Choice(choices), _) => Some(format!("expected one of: {}", choices.join(", "))),
+            (Self::Flag, _) => Some(String::from("expected true or false")),
+            (Self::Int, _) => Some(String::from("expected an integer")),
+            (Self::Float, _) => Some(String::from("expected a number")),
+            (Self::Str, _) => Some(String::from("expected a string")),
+        }
+    }
+}
+
+/// Renders `load.options` as engine command-line arguments.
+///
+/// Keys are sorted so the generated command is stable.
+#[must_use]
+pub fn option_args(load: &LoadConfig) -> Vec<String> {
+    let mut keys: Vec<&String> = load.options.keys().collect();
+    keys.sort();
+
+    keys.into_iter()
+        .filter_map(|key| match &load.options[key] {
+            serde_json::Value::Bool(true) => Some(format!("--{key}")),
+            serde_json::Value::String(v) => Some(format!("--{key} {v}")),
+            serde_json::Value::Number(v) => Some(format!("--{key} {v}")),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Levenshtein distance between two short strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            current.push(
+                (previous[j] + cost)
+                    .min(previous[j + 1] + 1)
+                    .min(current[j] + 1),
+            );
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_option_lookup_and_suggestions() {
+        let vllm = engine_options("vllm").unwrap();
+
+        assert_eq!(suggest_option(vllm, "max_num_seqs"), Some("max-num-seqs"));
+        assert_eq!(
+            suggest_option(vllm, "tensor-paralel-size"),
+            Some("tensor-parallel-size")
+        );
+        assert_eq!(suggest_option(vllm, "completely-unknown"), None);
+        assert_eq!(managed_option("max-model-len"), Some("load.max_seq_len"));
+        assert!(engine_options("ollama").is_none());
+
+        assert!(OptionKind::Int.check(&serde_json::json!(2)).is_none());
+        assert!(OptionKind::Int.check(&serde_json::json!("two")).is_some());
+        assert!(OptionKind::Float.check(&serde_json::json!(0.9)).is_none());
+    }
+
+    #[test]
+    fn test_requested_parallelism_accounts_for_pipeline() {
+        let mut load: LoadConfig =
+            serde_yaml::from_str("engine: vllm\noptions:\n  pipeline-parallel-size: 2\n").unwrap();
+        assert_eq!(pipeline_parallelism(&load), 2);
+        assert_eq!(requested_parallelism(&load, 4), 2);
+
+        load.options
+            .insert(String::from("tensor-parallel-size"), serde_json::json!(4));
+        assert_eq!(requested_parallelism(&load, 4), 4);
+
+        load.options.clear();
+        assert_eq!(requested_parallelism(&load, 4), 4);
+    }
+}
@@ -2,10 +2,17 @@
 //!
 //! This module provides deterministic hashing of configuration structures
 //! to detect changes between deployments and enable idempotent operations.
+//!
+//! Pod spec hashes carry the version of the algorithm that computed them
+//! (`v2:<hex>`). Hashes from older versions (unprefixed) cannot be compared
+//! with current ones, so the planner compares such pods field by field.
 
 use sha2::{Digest, Sha256};
 
-use super::spec::{DeployConfig, PodConfig};
+use super::spec::{DeployConfig, EndpointConfig, PodConfig, TemplateConfig};
+
+/// Version prefix of the spec hashes computed by [`ConfigHasher::hash_pod`].
+pub const SPEC_HASH_VERSION: &str = "v2";
 
 /// Hasher for computing configuration hashes.
 #[derive(Debug, Default)]
@@ -32,9 +39,22 @@ impl ConfigHasher {
             hasher.update(region.as_bytes());
         }
 
-        // Hash each pod
+        // Hash each pod (replica counts live here, not in the per-instance hash)
         for pod in &config.pods {
             hasher.update(self.hash_pod(pod).as_bytes());
+            hasher.update(pod.replicas.to_be_bytes());
+            if let Some(autoscale) = &pod.autoscale {
+                hasher.update(autoscale.min.to_be_bytes());
+                hasher.update(autoscale.max.to_be_bytes());
+            }
+        }
+
+        for endpoint in &config.endpoints {
+            hasher.update(self.hash_endpoint(endpoint).as_bytes());
+        }
+
+        for template in &config.templates {
+            hasher.update(self.hash_template(template).as_bytes());
         }
 
         // Hash guardrails if present
@@ -52,7 +72,8 @@ impl ConfigHasher {
 
     /// Computes a hash for a single pod configuration.
     ///
-    /// This hash is used to detect changes to individual pods.
+    /// This hash is used to detect changes to individual pods. It is
+    /// prefixed with [`SPEC_HASH_VERSION`].
     #[must_use]
     pub fn hash_pod(&self, pod: &PodConfig) -> String {
         let mut hasher = Sha256::new();
@@ -70,6 +91,17 @@ impl ConfigHasher {
             hasher.update(fallback.as_bytes());
         }
 
+        // Placement overriding the project's region
+        if let Some(region) = &pod.region {
+            hasher.update(region.as_bytes());
+        }
+
+        // Pricing (switching between on-demand and spot needs a new pod)
+        if let Some(bid) = pod.pricing.and_then(|p| p.spot_bid()) {
+            hasher.update(b"spot");
+            hasher.update(bid.to_be_bytes());
+        }
+
         // Ports (sorted for determinism)
         let mut ports: Vec<_> = pod.ports.iter().map(|p| p.port).collect();
         ports.sort_unstable();
@@ -87,10 +119,19 @@ impl ConfigHasher {
             if let Some(size) = volume.size_gb {
                 hasher.update(size.to_be_bytes());
             }
+            if let Some(id) = &volume.network_volume_id {
+                hasher.update(id.as_bytes());
+            }
+            if let Some(data_center) = &volume.data_center {
+                hasher.update(data_center.as_bytes());
+            }
         }
 
         // Runtime
         hasher.update(pod.runtime.image.as_bytes());
+        if let Some(template) = &pod.template {
+            hasher.update(template.as_bytes());
+        }
 
         // Environment variables (sorted for determinism)
         let mut env_vars: Vec<_> = pod.runtime.env.iter().collect();
@@ -120,6 +161,9 @@ impl ConfigHasher {
             if let Some(repo) = &model.repo {
                 hasher.update(repo.as_bytes());
             }
+            if let Some(revision) = &model.revision {
+                hasher.update(revision.as_bytes());
+            }
             if let Some(load) = &model.load {
                 hasher.update(load.engine.as_bytes());
                 if let Some(quant) = &load.quant {
@@ -139,13 +183,78 @@ impl ConfigHasher {
             hasher.update(value.as_bytes());
         }
 
-        hex::encode(hasher.finalize())
+        format!("{SPEC_HASH_VERSION}:{}", hex::encode(hasher.finalize()))
+    }
+
+    /// Computes a hash of a serverless endpoint's configuration.
+    ///
+    /// Prefixed with [`SPEC_HASH_VERSION`] like pod spec hashes.
+    #[must_use]
+    pub fn hash_endpoint(&self, endpoint: &EndpointConfig) -> String {
+        let mut hasher = Sha256::new();
+
+        hasher.update(endpoint.name.as_bytes());
+        hasher.update(endpoint.template_id.as_bytes());
+        if let Some(template) = &endpoint.template {
+            hasher.update(template.as_bytes());
+        }
+        // Pool order is the order of preference, so it is hashed as given
+        for pool in &endpoint.gpu.pools {
+            hasher.update(pool.as_bytes());
+        }
+        hasher.update(endpoint.gpu.count.to_be_bytes());
+        hasher.update(endpoint.workers.min.to_be_bytes());
+        hasher.update(endpoint.workers.max.to_be_bytes());
+        hasher.update(endpoint.idle_timeout_secs.to_be_bytes());
+
+        format!("{SPEC_HASH_VERSION}:{}", hex::encode(hasher.finalize()))
+    }
+
+    /// Computes a hash of a template's configuration.
+    ///
+    /// Prefixed with [`SPEC_HASH_VERSION`] like pod spec hashes.
+    #[must_use]
+    pub fn hash_template(&self, template: &TemplateConfig) -> String {
+        let mut hasher = Sha256::new();
+
+        hasher.update(template.name.as_bytes());
+        hasher.update(template.image.as_bytes());
+
+        let mut env_vars: Vec<_> = template.env.iter().collect();
+        env_vars.sort_by(|a, b| a.0.cmp(b.0));
+        for (key, value) in env_vars {
+            hasher.update(key.as_bytes());
+            hasher.update(value.as_bytes());
+        }
+
+        let mut ports: Vec<_> = template.ports.iter().map(|p| p.port).collect();
+        ports.sort_unstable();
+        for port in ports {
+            hasher.update(port.to_be_bytes());
+        }
+
+        hasher.update(template.container_disk_gb.to_be_bytes());
+        hasher.update(template.volume_gb.to_be_bytes());
+        if let Some(mount) = &template.volume_mount {
+            hasher.update(mount.as_bytes());
+        }
+        hasher.update([u8::from(template.serverless)]);
+
+        format!("{SPEC_HASH_VERSION}:{}", hex::encode(hasher.finalize()))
+    }
+
+    /// Returns true if `hash` was computed by the current spec hash algorithm.
+    #[must_use]
+    pub fn is_current(hash: &str) -> bool {
+        hash.split_once(':')
+            .is_some_and(|(version, _)| version == SPEC_HASH_VERSION)
     }
 
-    /// Computes a short hash (first 8 characters) for display purposes.
+    /// Computes a short hash (first 8 characters, without the version) for display purposes.
     #[must_use]
     pub fn short_hash(&self, hash: &str) -> String {
-        hash.chars().take(8).collect()
+        let digest = hash.split_once(':').map_or(hash, |(_, digest)| digest);
+        digest.chars().take(8).collect()
     }
 
     /// Compares two hashes to determine if they are equal.
@@ -167,18 +276,23 @@ impl ConfigHasher {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::spec::{GpuConfig, RuntimeConfig};
+    use crate::config::spec::{GpuConfig, MaxPriceAction, RuntimeConfig};
     use std::collections::HashMap;
 
     fn create_test_pod(name: &str) -> PodConfig {
         PodConfig {
             name: name.to_string(),
+            enabled: true,
             gpu: GpuConfig {
                 gpu_type: String::from("NVIDIA A40"),
                 count: 1,
                 min_vram_gb: None,
                 fallback: vec![],
+                max_price_per_hour: None,
+                max_price_action: MaxPriceAction::default(),
             },
+            pricing: None,
+            region: None,
             ports: vec![],
             volumes: vec![],
             runtime: RuntimeConfig {
@@ -190,6 +304,17 @@ mod tests {
             models: vec![],
             health_check: None,
             tags: HashMap::new(),
+            replicas: 1,
+            autoscale: None,
+            load_balancer: None,
+            replace_strategy: None,
+            warm_pool: None,
+            drain: None,
+            depends_on: vec![],
+            ssh: None,
+            notes: None,
+            model_downloads: None,
+            template: None,
         }
     }
 
@@ -202,6 +327,10 @@ mod tests {
         let hash2 = hasher.hash_pod(&pod);
 
         assert_eq!(hash1, hash2);
+        assert!(ConfigHasher::is_current(&hash1));
+        assert!(!ConfigHasher::is_current("0123abcd"));
+        assert_eq!(hasher.short_hash(&hash1).len(), 8);
+        assert!(!hasher.short_hash(&hash1).contains(':'));
     }
 
     #[test]
@@ -4,17 +4,34 @@
 //! - Parsing and deserializing `halldyll.deploy.yaml`
 //! - Validation of configuration values
 //! - Computing configuration hashes for change detection
+//! - Known inference engine options
+//! - `path=value` overrides from the command line
 
-mod spec;
+mod engines;
+mod hash;
+mod overrides;
 mod parser;
+mod spec;
 mod validator;
-mod hash;
 
+pub use engines::{
+    EngineOption, OptionKind, engine_options, explicit_parallelism, option_args,
+    parallelism_option, pipeline_parallelism, requested_parallelism,
+};
+pub use hash::{ConfigHasher, SPEC_HASH_VERSION};
+pub use overrides::{ConfigOverride, apply_overrides};
+pub use parser::{
+    ConfigParser, MissingVariable, PROCESS_ENV_SOURCE, ResolvedEnvVar, configured_environment,
+    find_config_file, parse_var,
+};
 pub use spec::{
-    CloudType, ComputeType, DeployConfig, GpuConfig, GuardrailsConfig, HealthCheckConfig,
-    LoadConfig, ModelConfig, ModelProvider, PodConfig, PortConfig, PortProtocol, ProjectConfig,
-    RuntimeConfig, StateBackend, StateConfig, VolumeConfig,
+    AlertsConfig, AutoscaleConfig, CloudType, ComputeType, DEFAULT_TAG_NAMESPACE, DeployConfig,
+    DiffConfig, DownloadConfig, DrainConfig, EndpointConfig, EndpointGpuConfig, FailoverConfig,
+    GpuConfig, GuardrailsConfig, HealthCheckConfig, LoadBalancerConfig, LoadBalancerFormat,
+    LoadConfig, MaxPriceAction, ModelConfig, ModelDownloadsConfig, ModelProvider, PodConfig,
+    PortConfig, PortProtocol, PricingConfig, PricingMode, ProjectConfig, ReplaceStrategy,
+    RuntimeConfig, SmokeTestConfig, SshConfig, StateBackend, StateCompression, StateConfig,
+    StoppedRetentionAction, TemplateConfig, VolumeConfig, WarmPoolConfig, WorkersConfig,
+    is_secret_env_key,
 };
-pub use parser::{ConfigParser, find_config_file};
-pub use validator::ConfigValidator;
-pub use hash::ConfigHasher;
+pub use validator::{ConfigValidator, ValidationError, ValidationResult};
@@ -0,0 +1,306 @@
+//! Command-line overrides of configuration values.
+//!
+//! An override is a `path=value` pair such as `pods[0].gpu.count=4` or
+//! `project.environment=staging`. Paths address the configuration as it is
+//! written (YAML keys and list indices), and values are parsed as YAML so
+//! numbers, booleans and flow lists (`[a, b]`) keep their type. A value
+//! replacing a string stays a string, so `image=repo:1.0` or `tag=0123` are
+//! not turned into numbers.
+
+use serde_yaml::{Mapping, Value};
+use std::fmt::Write;
+
+use crate::error::{ConfigError, HalldyllError, Result};
+
+use super::spec::DeployConfig;
+
+/// One step of an override path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PathSegment {
+    /// A mapping key.
+    Key(String),
+    /// A list index.
+    Index(usize),
+}
+
+/// A `path=value` override of a configuration value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigOverride {
+    /// Path as given, e.g. `pods[0].gpu.count`.
+    pub path: String,
+    /// Parsed path.
+    segments: Vec<PathSegment>,
+    /// Raw value.
+    pub value: String,
+}
+
+impl ConfigOverride {
+    /// Parses a `path=value` override.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no `=` or the path is malformed.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (path, value) = spec.split_once('=').ok_or_else(|| {
+            HalldyllError::Config(ConfigError::validation_general(format!(
+                "Invalid override '{spec}': expected PATH=VALUE"
+            )))
+        })?;
+
+        let path = path.trim();
+        let segments = parse_path(path)?;
+
+        Ok(Self {
+            path: path.to_string(),
+            segments,
+            value: value.to_string(),
+        })
+    }
+
+    /// Returns the value as YAML, coerced to the type of the value it
+    /// replaces: strings stay strings, lists and sections accept flow YAML,
+    /// and anything else keeps its parsed scalar type.
+    fn yaml_value(&self, current: &Value) -> Value {
+        if current.is_string() {
+            return Value::String(self.value.clone());
+        }
+        match serde_yaml::from_str::<Value>(&self.value) {
+            Ok(value @ (Value::Bool(_) | Value::Number(_) | Value::String(_))) => value,
+            Ok(value @ Value::Sequence(_)) if current.is_sequence() || current.is_null() => value,
+            Ok(value @ Value::Mapping(_)) if current.is_mapping() || current.is_null() => value,
+            _ => Value::String(self.value.clone()),
+        }
+    }
+
+    /// Sets this override's value in a YAML document.
+    fn set(&self, document: &mut Value) -> Result<()> {
+        let mut node = document;
+        for (i, segment) in self.segments.iter().enumerate() {
+            node = match segment {
+                PathSegment::Key(key) => {
+                    if node.is_null() {
+                        *node = Value::Mapping(Mapping::new());
+                    }
+                    let Value::Mapping(map) = node else {
+                        return Err(self.error(&format!("'{}' is not a section", self.prefix(i))));
+                    };
+                    map.entry(Value::String(key.clone())).or_insert(Value::Null)
+                }
+                PathSegment::Index(index) => {
+                    let Value::Sequence(seq) = node else {
+                        return Err(self.error(&format!("'{}' is not a list", self.prefix(i))));
+                    };
+                    let len = seq.len();
+                    seq.get_mut(*index).ok_or_else(|| {
+                        self.error(&format!("index {index} is out of range ({len} entries)"))
+                    })?
+                }
+            };
+        }
+
+        *node = self.yaml_value(node);
+        Ok(())
+    }
+
+    /// Returns true if the path exists in a YAML document.
+    fn exists_in(&self, document: &Value) -> bool {
+        let mut node = document;
+        for segment in &self.segments {
+            let next = match segment {
+                PathSegment::Key(key) => node.get(key.as_str()),
+                PathSegment::Index(index) => node.get(*index),
+            };
+            match next {
+                Some(next) => node = next,
+                None => return false,
+            }
+        }
+        true
+    }
+
+    /// Renders the path up to (excluding) segment `end`.
+    fn prefix(&self, end: usize) -> String {
+        let mut rendered = String::new();
+        for segment in &self.segments[..end] {
+            match segment {
+                PathSegment::Key(key) if rendered.is_empty() => rendered.push_str(key),
+                PathSegment::Key(key) => {
+                    rendered.push('.');
+                    rendered.push_str(key);
+                }
+                PathSegment::Index(index) => {
+                    let _ = write!(rendered, "[{index}]");
+                }
+            }
+        }
+        rendered
+    }
+
+    /// Builds an error about this override.
+    fn error(&self, message: &str) -> HalldyllError {
+        HalldyllError::Config(ConfigError::validation(
+            format!("Cannot set '{}': {message}", self.path),
+            self.path.clone(),
+        ))
+    }
+}
+
+impl std::fmt::Display for ConfigOverride {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}={}", self.path, self.value)
+    }
+}
+
+/// Parses a path such as `pods[0].gpu.count` into segments.
+fn parse_path(path: &str) -> Result<Vec<PathSegment>> {
+    let invalid = || {
+        HalldyllError::Config(ConfigError::validation_general(format!(
+            "Invalid override path '{path}': use keys and [index], e.g. pods[0].gpu.count"
+        )))
+    };
+
+    let mut segments = Vec::new();
+    for part in path.split('.') {
+        let (key, mut rest) = part
+            .split_once('[')
+            .map_or((part, ""), |(key, rest)| (key, rest));
+        if key.is_empty() && segments.is_empty() {
+            return Err(invalid());
+        }
+        if !key.is_empty() {
+            segments.push(PathSegment::Key(key.to_string()));
+        }
+
+        while !rest.is_empty() {
+            let (index, after) = rest.split_once(']').ok_or_else(invalid)?;
+            segments.push(PathSegment::Index(index.parse().map_err(|_| invalid())?));
+            rest = match after.strip_prefix('[') {
+                Some(next) => next,
+                None if after.is_empty() => "",
+                None => return Err(invalid()),
+            };
+        }
+    }
+
+    if segments.is_empty() {
+        return Err(invalid());
+    }
+    Ok(segments)
+}
+
+/// Returns a copy of `config` with the overrides applied in order.
+///
+/// # Errors
+///
+/// Returns an error if a path does not exist in the configuration or a value
+/// has the wrong type for its field.
+pub fn apply_overrides(
+    config: &DeployConfig,
+    overrides: &[ConfigOverride],
+) -> Result<DeployConfig> {
+    if overrides.is_empty() {
+        return Ok(config.clone());
+    }
+
+    let render_error = |e: serde_yaml::Error| {
+        HalldyllError::internal(format!("Failed to render configuration: {e}"))
+    };
+    let mut document = serde_yaml::to_value(config).map_err(render_error)?;
+
+    // Deserialize after each override so a bad value names its override
+    let mut updated = config.clone();
+    for item in overrides {
+        item.set(&mut document)?;
+        updated = serde_yaml::from_value(document.clone())
+            .map_err(|e| item.error(&format!("invalid value '{}': {e}", item.value)))?;
+    }
+
+    // Keys the configuration does not know are dropped when deserializing
+    let rendered = serde_yaml::to_value(&updated).map_err(render_error)?;
+    if let Some(unknown) = overrides.iter().find(|o| !o.exists_in(&rendered)) {
+        return Err(unknown.error("no such configuration field"));
+    }
+
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_overrides() {
+        let yaml = "project:\n  name: proj\nstate:\n  backend: local\npods:\n  - name: llm\n    gpu:\n      type: A40\n    runtime:\n      image: img:v1\n";
+        let config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+
+        let overrides = [
+            ConfigOverride::parse("pods[0].gpu.count=4").unwrap(),
+            ConfigOverride::parse("pods[0].runtime.image=img:v2").unwrap(),
+            ConfigOverride::parse("guardrails.max_gpus=2").unwrap(),
+        ];
+        let updated = apply_overrides(&config, &overrides).unwrap();
+        assert_eq!(updated.pods[0].gpu.count, 4);
+        assert_eq!(updated.pods[0].runtime.image, "img:v2");
+        assert_eq!(updated.guardrails.and_then(|g| g.max_gpus), Some(2));
+
+        assert!(ConfigOverride::parse("pods[0].gpu.count").is_err());
+        assert!(ConfigOverride::parse("pods[x].gpu=1").is_err());
+        let out_of_range = ConfigOverride::parse("pods[3].gpu.count=1").unwrap();
+        assert!(apply_overrides(&config, &[out_of_range]).is_err());
+        let unknown = ConfigOverride::parse("pods[0].gpu.cuont=1").unwrap();
+        assert!(apply_overrides(&config, &[unknown]).is_err());
+
+        // Strings stay strings, and type errors name the override
+        let numeric_tag = ConfigOverride::parse("pods[0].runtime.image=1.10").unwrap();
+        assert_eq!(
+            apply_overrides(&config, &[numeric_tag]).unwrap().pods[0]
+                .runtime
+                .image,
+            "1.10"
+        );
+        let bad_count = ConfigOverride::parse("pods[0].gpu.count=many").unwrap();
+        let err = apply_overrides(&config, &[bad_count]).unwrap_err();
+        assert!(err.to_string().contains("pods[0].gpu.count"));
+    }
+
+    #[test]
+    fn test_override_coercion_and_unknown_path() {
+        let yaml = "project:\n  name: proj\nstate:\n  backend: local\npods:\n  - name: llm\n    gpu:\n      type: A40\n    runtime:\n      image: img:v1\n  - name: web\n    gpu:\n      type: A40\n    runtime:\n      image: web:v1\n";
+        let config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+
+        // Index paths address the right list entry
+        let image = ConfigOverride::parse("pods[1].runtime.image=web:v2").unwrap();
+        let retagged = apply_overrides(&config, &[image]).unwrap();
+        assert_eq!(retagged.pods[0].runtime.image, "img:v1");
+        assert_eq!(retagged.pods[1].runtime.image, "web:v2");
+
+        // Numbers and booleans keep their type
+        let overrides = [
+            ConfigOverride::parse("pods[0].replicas=3").unwrap(),
+            ConfigOverride::parse("pods[0].enabled=false").unwrap(),
+            ConfigOverride::parse("pods[1].gpu.count=2").unwrap(),
+        ];
+        let updated = apply_overrides(&config, &overrides).unwrap();
+        assert_eq!(updated.pods[0].replicas, 3);
+        assert!(!updated.pods[0].enabled);
+        assert!(updated.pods[1].enabled);
+        assert_eq!(updated.pods[1].gpu.count, 2);
+
+        let not_bool = ConfigOverride::parse("pods[0].enabled=maybe").unwrap();
+        let bool_err = apply_overrides(&config, &[not_bool]).unwrap_err();
+        assert!(bool_err.to_string().contains("pods[0].enabled"));
+
+        // Unknown paths are rejected by name, not silently dropped
+        let unknown = ConfigOverride::parse("pods[0].runtime.imag=img:v2").unwrap();
+        let field_err = apply_overrides(&config, &[unknown])
+            .unwrap_err()
+            .to_string();
+        assert!(field_err.contains("pods[0].runtime.imag"));
+        assert!(field_err.contains("no such configuration field"));
+        let unknown_section = ConfigOverride::parse("projetc.name=other").unwrap();
+        let section_err = apply_overrides(&config, &[unknown_section])
+            .unwrap_err()
+            .to_string();
+        assert!(section_err.contains("projetc.name"));
+    }
+}
@@ -2,25 +2,92 @@
 //!
 //! This module handles loading configuration from YAML files and environment
 //! variables, with proper precedence and error handling.
+//!
+//! Pods can live in their own files: `pods: { include: pods/*.yaml }` (or an
+//! `include` entry inside the `pods` list) pulls in every matching file,
+//! relative to the configuration file. Each file holds one pod or a list of
+//! pods, and a pod name may only be defined once across all files.
+//!
+//! A top-level `include:` pulls in configuration fragments the same way.
+//! Their lists (pods, endpoints, templates) are appended to the base ones and
+//! their sections merged into it; a value set in two files is an error.
+//!
+//! Dotenv files are layered: variables already set in the process win over
+//! `.env.<environment>`, which wins over `.env`.
+//!
+//! Environments patch the base configuration: the selected environment's
+//! entry under `environments:`, then `halldyll.deploy.<environment>.yaml`
+//! next to the configuration file, are merged over it before it is
+//! deserialized. Mappings merge key by key, lists of named entries (pods,
+//! endpoints, templates) merge by `name`, and anything else is replaced.
+//!
+//! String values may then reference variables: `${var.name}` resolves from
+//! `--var name=value`, then `HALLDYLL_VAR_NAME`, then the `vars:` section,
+//! and `${env.NAME}` from the process environment (dotenv files included).
+//! Other `${...}` references are left for later stages.
 
 use crate::error::{ConfigError, HalldyllError, Result};
-use std::path::Path;
+use serde_yaml::Value;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use tracing::{debug, info};
 
 use super::spec::DeployConfig;
 
+/// Key of a `pods` entry that includes pods from other files.
+const INCLUDE_KEY: &str = "include";
+
+/// Top-level key holding the per-environment patches.
+const ENVIRONMENTS_KEY: &str = "environments";
+
+/// Top-level key declaring the variables `${var.name}` resolves.
+const VARS_KEY: &str = "vars";
+
+/// Prefix of the variables overriding `vars:` entries.
+const VAR_ENV_PREFIX: &str = "HALLDYLL_VAR_";
+
+/// Variable overriding `project.environment`.
+const ENVIRONMENT_ENV: &str = "HALLDYLL_PROJECT_ENVIRONMENT";
+
+/// Variables `halldyll env print` shows even when no dotenv file sets them.
+const KNOWN_ENV_VARS: &[&str] = &["RUNPOD_API_KEY", "HF_TOKEN", "RUNPOD_SSH_KEY"];
+
+/// Source of variables set in the process environment.
+pub const PROCESS_ENV_SOURCE: &str = "process";
+
 /// Configuration parser for loading deployment configuration.
 #[derive(Debug, Default)]
 pub struct ConfigParser {
     /// Base path for resolving relative paths.
     base_path: Option<std::path::PathBuf>,
+    /// Environment whose `.env.<environment>` file and configuration overlay
+    /// are layered over the base ones.
+    environment: Option<String>,
+    /// Values of `${var.name}` overriding the environment and `vars:`.
+    vars: Vec<(String, String)>,
+}
+
+/// An environment variable and where its effective value comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedEnvVar {
+    /// Variable name.
+    pub key: String,
+    /// Effective value.
+    pub value: String,
+    /// [`PROCESS_ENV_SOURCE`] or the dotenv file name.
+    pub source: String,
 }
 
 impl ConfigParser {
     /// Creates a new configuration parser.
     #[must_use]
     pub const fn new() -> Self {
-        Self { base_path: None }
+        Self {
+            base_path: None,
+            environment: None,
+            vars: Vec::new(),
+        }
     }
 
     /// Sets the base path for resolving relative paths.
@@ -30,6 +97,33 @@ impl ConfigParser {
         self
     }
 
+    /// Layers `.env.<environment>` over `.env` and the environment's overlay
+    /// over the configuration, and deploys to that environment.
+    #[must_use]
+    pub fn with_environment(mut self, environment: impl Into<String>) -> Self {
+        self.environment = Some(environment.into());
+        self
+    }
+
+    /// Sets the value of `${var.name}`, over `HALLDYLL_VAR_NAME` and `vars:`.
+    #[must_use]
+    pub fn with_var(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.vars.push((name.into(), value.into()));
+        self
+    }
+
+    /// Creates a parser for a configuration file: relative paths resolve
+    /// next to it, and the dotenv layer follows its `project.environment`
+    /// (or `HALLDYLL_PROJECT_ENVIRONMENT`).
+    #[must_use]
+    pub fn for_config_file(path: &Path) -> Self {
+        let parser = Self::new().with_base_path(path.parent().unwrap_or_else(|| Path::new(".")));
+        match configured_environment(path) {
+            Some(environment) => parser.with_environment(environment),
+            None => parser,
+        }
+    }
+
     /// Loads configuration from a YAML file.
     ///
     /// # Errors
@@ -39,20 +133,41 @@ impl ConfigParser {
         let path = path.as_ref();
         info!("Loading configuration from: {}", path.display());
 
-        if !path.exists() {
-            return Err(HalldyllError::Config(ConfigError::FileNotFound {
-                path: path.to_path_buf(),
-            }));
-        }
+        let content = read_config(path)?;
+        self.parse_yaml(&content, Some(path))
+    }
 
-        let content = std::fs::read_to_string(path).map_err(|e| {
-            HalldyllError::Config(ConfigError::ParseError {
-                message: format!("Failed to read file: {e}"),
-                location: Some(path.display().to_string()),
-            })
+    /// Lists the `${var.name}` references in a configuration file that
+    /// neither `--var`, `HALLDYLL_VAR_<NAME>` nor a `vars:` default sets,
+    /// so they can be asked for before loading it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed.
+    pub fn missing_variables(&self, path: impl AsRef<Path>) -> Result<Vec<MissingVariable>> {
+        let path = path.as_ref();
+        let content = read_config(path)?;
+        let (mut document, _) = self.prepare_document(&content, Some(path))?;
+
+        let missing = RefCell::new(Vec::new());
+        let (vars, declarations) = self.resolve_vars(&mut document, &missing)?;
+        interpolate_value(&mut document, "", &|reference| {
+            collect_missing(reference, &vars, &missing)
         })?;
 
-        self.parse_yaml(&content, Some(path))
+        Ok(missing
+            .into_inner()
+            .iter()
+            .filter_map(|reference| reference.strip_prefix("var."))
+            .map(|name| {
+                let declaration = declarations.get(name).cloned().unwrap_or_default();
+                MissingVariable {
+                    name: name.to_string(),
+                    description: declaration.description,
+                    sensitive: declaration.sensitive,
+                }
+            })
+            .collect())
     }
 
     /// Parses configuration from a YAML string.
@@ -63,18 +178,46 @@ impl ConfigParser {
     pub fn parse_yaml(&self, content: &str, source: Option<&Path>) -> Result<DeployConfig> {
         debug!("Parsing YAML configuration");
 
-        let config: DeployConfig = serde_yaml::from_str(content).map_err(|e| {
-            let location = source.map(|p| p.display().to_string());
-            HalldyllError::Config(ConfigError::ParseError {
-                message: format!("YAML parse error: {e}"),
-                location,
-            })
-        })?;
+        let (mut document, expanded) = self.prepare_document(content, source)?;
+        let interpolated = self.interpolate(&mut document)?;
 
-        debug!("Successfully parsed configuration for project: {}", config.project.name);
+        // Deserialize from the text when possible, its errors carry line numbers
+        let parse_error = |e: serde_yaml::Error| yaml_error(&e, source);
+        let mut config: DeployConfig = if expanded || interpolated {
+            serde_yaml::from_value(document).map_err(parse_error)?
+        } else {
+            serde_yaml::from_str(content).map_err(parse_error)?
+        };
+        if let Some(environment) = &self.environment {
+            config.project.environment.clone_from(environment);
+        }
+        config.apply_templates();
+
+        debug!(
+            "Successfully parsed configuration for project: {}",
+            config.project.name
+        );
         Ok(config)
     }
 
+    /// Parses `content` and expands its includes and environment.
+    ///
+    /// Returns the document, and true if it no longer matches `content`.
+    fn prepare_document(&self, content: &str, source: Option<&Path>) -> Result<(Value, bool)> {
+        let mut document: Value =
+            serde_yaml::from_str(content).map_err(|e| yaml_error(&e, source))?;
+        let base_dir = source
+            .and_then(Path::parent)
+            .map(Path::to_path_buf)
+            .or_else(|| self.base_path.clone())
+            .unwrap_or_default();
+
+        let included = expand_config_includes(&mut document, &base_dir)?
+            | expand_pod_includes(&mut document, &base_dir)?;
+        let overlaid = self.apply_environment(&mut document, source)?;
+        Ok((document, included || overlaid))
+    }
+
     /// Loads configuration with environment variable overrides.
     ///
     /// Environment variables are checked in the format:
@@ -88,10 +231,168 @@ impl ConfigParser {
 
         // Apply environment overrides
         Self::apply_env_overrides(&mut config);
+        if let Some(environment) = &self.environment {
+            config.project.environment.clone_from(environment);
+        }
 
         Ok(config)
     }
 
+    /// Resolves `${var.name}` and `${env.NAME}` in the string values of
+    /// `document`, after taking out its `vars:` section.
+    ///
+    /// A value that is a single `${var.name}` takes the variable's type, so
+    /// numbers and booleans declared in `vars:` fill numeric fields.
+    ///
+    /// Returns true if `document` changed. Every variable without a value
+    /// and every unset environment variable is listed in a single error.
+    fn interpolate(&self, document: &mut Value) -> Result<bool> {
+        let had_vars = document.get(VARS_KEY).is_some_and(|vars| !vars.is_null());
+        let missing = RefCell::new(Vec::new());
+        let (vars, _) = self.resolve_vars(document, &missing)?;
+        let changed = interpolate_value(document, "", &|reference| {
+            collect_missing(reference, &vars, &missing)
+        })?;
+
+        let missing = missing.into_inner();
+        if !missing.is_empty() {
+            return Err(missing_error(&missing));
+        }
+        Ok(had_vars || changed)
+    }
+
+    /// Takes the `vars:` section out of `document` and resolves each
+    /// variable from `--var`, `HALLDYLL_VAR_<NAME>` and its default.
+    ///
+    /// Returns the values and the declarations. A declared variable without
+    /// a value has no entry in the values; unset environment variables that
+    /// defaults reference are added to `missing`.
+    fn resolve_vars(
+        &self,
+        document: &mut Value,
+        missing: &RefCell<Vec<String>>,
+    ) -> Result<(HashMap<String, Value>, HashMap<String, VarDeclaration>)> {
+        let declared = match document.as_mapping_mut().and_then(|m| m.remove(VARS_KEY)) {
+            Some(Value::Mapping(vars)) => vars,
+            Some(Value::Null) | None => serde_yaml::Mapping::new(),
+            Some(_) => {
+                return Err(HalldyllError::Config(ConfigError::validation(
+                    "vars must map variable names to values",
+                    VARS_KEY,
+                )));
+            }
+        };
+
+        // Variables may reference the environment, but not each other
+        let mut vars = HashMap::new();
+        let mut declarations = HashMap::new();
+        for (name, value) in declared {
+            let Some(name) = name.as_str() else { continue };
+            let mut declaration = VarDeclaration::parse(value);
+            if let Some(default) = &mut declaration.default {
+                interpolate_value(default, &format!("{VARS_KEY}.{name}"), &|reference| {
+                    collect_missing(reference, &HashMap::new(), missing)
+                })?;
+                vars.insert(name.to_string(), default.clone());
+            }
+            declarations.insert(name.to_string(), declaration);
+        }
+        // `--var` wins over `HALLDYLL_VAR_*`, which only sets declared variables
+        let from_env: Vec<(String, String)> = std::env::vars()
+            .filter_map(|(key, value)| {
+                let suffix = key.strip_prefix(VAR_ENV_PREFIX)?;
+                let name = declarations
+                    .keys()
+                    .find(|name| var_env_suffix(name) == suffix)?;
+                Some((name.clone(), value))
+            })
+            .collect();
+        for (name, value) in from_env.into_iter().chain(self.vars.iter().cloned()) {
+            let default = declarations.get(&name).and_then(|d| d.default.as_ref());
+            let typed = match default {
+                Some(Value::String(_)) | None => Value::String(value),
+                Some(_) => match serde_yaml::from_str::<Value>(&value) {
+                    Ok(scalar @ (Value::Bool(_) | Value::Number(_) | Value::String(_))) => scalar,
+                    _ => Value::String(value),
+                },
+            };
+            vars.insert(name, typed);
+        }
+        Ok((vars, declarations))
+    }
+
+    /// Merges the environment's patches over `document`: its entry under
+    /// `environments:`, then its overlay file. The environment is the
+    /// parser's, else the document's `project.environment`.
+    ///
+    /// Returns true if `document` changed. Selecting an environment that
+    /// `environments:` does not list and that has no overlay file is an
+    /// error, so a misspelt `--env` does not deploy the base configuration.
+    fn apply_environment(&self, document: &mut Value, source: Option<&Path>) -> Result<bool> {
+        let mut patches = match document
+            .as_mapping_mut()
+            .and_then(|m| m.remove(ENVIRONMENTS_KEY))
+        {
+            Some(Value::Mapping(patches)) => patches,
+            Some(Value::Null) | None => serde_yaml::Mapping::new(),
+            Some(_) => {
+                return Err(HalldyllError::Config(ConfigError::validation(
+                    "environments must map environment names to configuration patches",
+                    ENVIRONMENTS_KEY,
+                )));
+            }
+        };
+        let declared = !patches.is_empty();
+
+        let environment = self.environment.clone().or_else(|| {
+            document
+                .get("project")
+                .and_then(|p| p.get("environment"))
+                .and_then(Value::as_str)
+                .map(String::from)
+        });
+        let Some(environment) = environment else {
+            return Ok(declared);
+        };
+
+        let patch = patches.remove(environment.as_str());
+        let mut found = patch.is_some();
+        if let Some(patch) = patch {
+            debug!("Applying environments.{environment}");
+            merge_overlay(document, patch);
+        }
+        if let Some(path) = source.and_then(|p| overlay_path(p, &environment))
+            && path.exists()
+        {
+            info!("Applying environment overlay: {}", path.display());
+            let location = path.display().to_string();
+            let content = std::fs::read_to_string(&path).map_err(|e| {
+                HalldyllError::Config(ConfigError::ParseError {
+                    message: format!("Failed to read environment overlay: {e}"),
+                    location: Some(location.clone()),
+                })
+            })?;
+            let overlay: Value = serde_yaml::from_str(&content).map_err(|e| {
+                HalldyllError::Config(ConfigError::ParseError {
+                    message: format!("YAML parse error: {e}"),
+                    location: Some(location.clone()),
+                })
+            })?;
+            merge_overlay(document, overlay);
+            found = true;
+        }
+
+        if declared && !found {
+            return Err(HalldyllError::Config(ConfigError::validation(
+                format!(
+                    "Environment '{environment}' is not listed under environments and has no overlay file"
+                ),
+                ENVIRONMENTS_KEY,
+            )));
+        }
+        Ok(declared || found)
+    }
+
     /// Applies environment variable overrides to the configuration.
     fn apply_env_overrides(config: &mut DeployConfig) {
         // Project overrides
@@ -120,34 +421,127 @@ impl ConfigParser {
             debug!("Overriding state.prefix from environment");
             config.state.prefix = Some(prefix);
         }
+
+        if let Ok(endpoint) = std::env::var("HALLDYLL_STATE_ENDPOINT_URL") {
+            debug!("Overriding state.endpoint_url from environment");
+            config.state.endpoint_url = Some(endpoint);
+        }
+
+        if let Ok(address) = std::env::var("HALLDYLL_STATE_ADDRESS") {
+            debug!("Overriding state.address from environment");
+            config.state.address = Some(address);
+        }
+
+        if let Ok(username) = std::env::var("HALLDYLL_STATE_USERNAME") {
+            debug!("Overriding state.username from environment");
+            config.state.username = Some(username);
+        }
+
+        if let Ok(password) = std::env::var("HALLDYLL_STATE_PASSWORD") {
+            debug!("Overriding state.password from environment");
+            config.state.password = Some(password);
+        }
+
+        if let Ok(path) = std::env::var("HALLDYLL_STATE_PATH") {
+            debug!("Overriding state.path from environment");
+            config.state.path = Some(path);
+        }
     }
 
-    /// Loads the .env file if present.
+    /// Returns the dotenv files that exist, highest precedence first:
+    /// `.env.<environment>`, then `.env`.
+    #[must_use]
+    pub fn dotenv_files(&self) -> Vec<PathBuf> {
+        let dir = self.base_path.clone().unwrap_or_default();
+        let mut names = Vec::new();
+        if let Some(environment) = &self.environment {
+            names.push(format!(".env.{environment}"));
+        }
+        names.push(String::from(".env"));
+
+        names
+            .into_iter()
+            .map(|name| dir.join(name))
+            .filter(|path| {
+                let exists = path.exists();
+                if !exists {
+                    debug!("Dotenv file not found at: {}", path.display());
+                }
+                exists
+            })
+            .collect()
+    }
+
+    /// Loads the dotenv files into the process environment.
+    ///
+    /// Variables already set are kept, and `.env.<environment>` wins over
+    /// `.env`.
     ///
     /// # Errors
     ///
-    /// Returns an error if the .env file exists but cannot be loaded.
+    /// Returns an error if a dotenv file exists but cannot be loaded.
     pub fn load_dotenv(&self) -> Result<()> {
-        let env_path = self
-            .base_path
-            .as_ref()
-            .map_or_else(|| std::path::PathBuf::from(".env"), |p| p.join(".env"));
-
-        if env_path.exists() {
+        // Loading never overrides, so the highest precedence file goes first
+        for env_path in self.dotenv_files() {
             info!("Loading environment from: {}", env_path.display());
-            dotenvy::from_path(&env_path).map_err(|e| {
-                HalldyllError::Config(ConfigError::ParseError {
-                    message: format!("Failed to load .env file: {e}"),
-                    location: Some(env_path.display().to_string()),
-                })
-            })?;
-        } else {
-            debug!(".env file not found at: {}", env_path.display());
+            dotenvy::from_path(&env_path).map_err(|e| dotenv_error(&env_path, &e))?;
         }
 
         Ok(())
     }
 
+    /// Resolves the variables set by the dotenv files, plus `HALLDYLL_*` and
+    /// credential variables, with the source of each effective value.
+    ///
+    /// This reads the files without loading them, so call it before
+    /// [`Self::load_dotenv`] to tell process values from file values.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a dotenv file exists but cannot be read.
+    pub fn resolve_env(&self) -> Result<Vec<ResolvedEnvVar>> {
+        let mut resolved: HashMap<String, ResolvedEnvVar> = HashMap::new();
+
+        for env_path in self.dotenv_files() {
+            let source = env_path.file_name().map_or_else(
+                || env_path.display().to_string(),
+                |n| n.to_string_lossy().into_owned(),
+            );
+            let entries =
+                dotenvy::from_path_iter(&env_path).map_err(|e| dotenv_error(&env_path, &e))?;
+            for entry in entries {
+                let (key, value) = entry.map_err(|e| dotenv_error(&env_path, &e))?;
+                resolved
+                    .entry(key.clone())
+                    .or_insert_with(|| ResolvedEnvVar {
+                        key,
+                        value,
+                        source: source.clone(),
+                    });
+            }
+        }
+
+        for (key, value) in std::env::vars() {
+            if resolved.contains_key(&key)
+                || key.starts_with("HALLDYLL_")
+                || KNOWN_ENV_VARS.contains(&key.as_str())
+            {
+                resolved.insert(
+                    key.clone(),
+                    ResolvedEnvVar {
+                        key,
+                        value,
+                        source: String::from(PROCESS_ENV_SOURCE),
+                    },
+                );
+            }
+        }
+
+        let mut vars: Vec<ResolvedEnvVar> = resolved.into_values().collect();
+        vars.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(vars)
+    }
+
     /// Validates that required environment variables are set.
     ///
     /// # Errors
@@ -187,6 +581,600 @@ impl ConfigParser {
     }
 }
 
+/// Returns the environment a configuration file deploys to, for picking its
+/// dotenv layer: `HALLDYLL_PROJECT_ENVIRONMENT` if set in the process, else
+/// the file's `project.environment`.
+#[must_use]
+pub fn configured_environment(path: &Path) -> Option<String> {
+    if let Ok(environment) = std::env::var(ENVIRONMENT_ENV)
+        && !environment.is_empty()
+    {
+        return Some(environment);
+    }
+
+    let content = std::fs::read_to_string(path).ok()?;
+    let document: Value = serde_yaml::from_str(&content).ok()?;
+    document
+        .get("project")?
+        .get("environment")?
+        .as_str()
+        .map(String::from)
+}
+
+/// Parses a `--var name=value` argument.
+///
+/// # Errors
+///
+/// Returns an error if there is no `=` or the name is empty.
+pub fn parse_var(spec: &str) -> Result<(String, String)> {
+    match spec.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), value.to_string()))
+        }
+        _ => Err(HalldyllError::Config(ConfigError::validation_general(
+            format!("Invalid variable '{spec}': expected NAME=VALUE"),
+        ))),
+    }
+}
+
+/// Returns the `HALLDYLL_VAR_` suffix of a variable (`hf-repo` → `HF_REPO`).
+fn var_env_suffix(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Resolves `var.name` or `env.NAME`; `None` for other references.
+///
+/// A reference without a value is added to `missing` and resolves to an
+/// empty string, so one pass finds all of them.
+fn collect_missing(
+    reference: &str,
+    vars: &HashMap<String, Value>,
+    missing: &RefCell<Vec<String>>,
+) -> Option<std::result::Result<Value, String>> {
+    let value = if let Some(name) = reference.strip_prefix("var.") {
+        vars.get(name).cloned()
+    } else {
+        let name = reference.strip_prefix("env.")?;
+        std::env::var(name).ok().map(Value::String)
+    };
+    Some(Ok(value.unwrap_or_else(|| {
+        let mut missing = missing.borrow_mut();
+        if !missing.iter().any(|r| r == reference) {
+            missing.push(reference.to_string());
+        }
+        Value::String(String::new())
+    })))
+}
+
+/// Builds the error listing the `var.` and `env.` references without a value.
+fn missing_error(missing: &[String]) -> HalldyllError {
+    let names = |prefix: &str| -> Vec<&str> {
+        missing
+            .iter()
+            .filter_map(|r| r.strip_prefix(prefix))
+            .collect()
+    };
+    let (vars, env) = (names("var."), names("env."));
+    let mut problems = Vec::new();
+    if !vars.is_empty() {
+        problems.push(format!(
+            "variables without a value: {} (set them with --var <name>=<value> or HALLDYLL_VAR_<NAME>)",
+            vars.join(", ")
+        ));
+    }
+    if !env.is_empty() {
+        problems.push(format!("environment variables not set: {}", env.join(", ")));
+    }
+    HalldyllError::Config(ConfigError::validation(
+        format!("Missing {}", problems.join("; ")),
+        VARS_KEY,
+    ))
+}
+
+/// Reads a configuration file.
+fn read_config(path: &Path) -> Result<String> {
+    if !path.exists() {
+        return Err(HalldyllError::Config(ConfigError::FileNotFound {
+            path: path.to_path_buf(),
+        }));
+    }
+
+    std::fs::read_to_string(path).map_err(|e| {
+        HalldyllError::Config(ConfigError::ParseError {
+            message: format!("Failed to read file: {e}"),
+            location: Some(path.display().to_string()),
+        })
+    })
+}
+
+/// Wraps a YAML error with the file it came from.
+fn yaml_error(e: &serde_yaml::Error, source: Option<&Path>) -> HalldyllError {
+    HalldyllError::Config(ConfigError::ParseError {
+        message: format!("YAML parse error: {e}"),
+        location: source.map(|p| p.display().to_string()),
+    })
+}
+
+/// A `${var.name}` that nothing gives a value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingVariable {
+    /// Variable name
+    pub name: String,
+    /// Its `description` under `vars:`
+    pub description: Option<String>,
+    /// Whether its value is secret and must not be echoed (`sensitive: true`)
+    pub sensitive: bool,
+}
+
+/// An entry under `vars:`: a default value, `null` for a required
+/// variable, or a mapping of `default`, `description` and `sensitive`.
+#[derive(Debug, Clone, Default)]
+struct VarDeclaration {
+    default: Option<Value>,
+    description: Option<String>,
+    sensitive: bool,
+}
+
+impl VarDeclaration {
+    fn parse(value: Value) -> Self {
+        match value {
+            Value::Null => Self::default(),
+            Value::Mapping(mut fields) => Self {
+                default: fields.remove("default").filter(|d| !d.is_null()),
+                description: fields
+                    .get("description")
+                    .and_then(Value::as_str)
+                    .map(String::from),
+                sensitive: fields
+                    .get("sensitive")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false),
+            },
+            default => Self {
+                default: Some(default),
+                ..Self::default()
+            },
+        }
+    }
+}
+
+/// Replaces the references `resolve` knows in the strings of `value`.
+///
+/// Returns true if anything was replaced.
+fn interpolate_value(
+    value: &mut Value,
+    path: &str,
+    resolve: &dyn Fn(&str) -> Option<std::result::Result<Value, String>>,
+) -> Result<bool> {
+    match value {
+        Value::Mapping(mapping) => {
+            let mut changed = false;
+            for (key, item) in mapping.iter_mut() {
+                let key = key.as_str().map_or_else(String::new, String::from);
+                let item_path = if path.is_empty() {
+                    key
+                } else {
+                    format!("{path}.{key}")
+                };
+                changed |= interpolate_value(item, &item_path, resolve)?;
+            }
+            Ok(changed)
+        }
+        Value::Sequence(items) => {
+            let mut changed = false;
+            for (i, item) in items.iter_mut().enumerate() {
+                changed |= interpolate_value(item, &format!("{path}[{i}]"), resolve)?;
+            }
+            Ok(changed)
+        }
+        Value::String(text) => {
+            let error =
+                |message: String| HalldyllError::Config(ConfigError::validation(message, path));
+            let mut result = String::new();
+            let mut rest = text.as_str();
+            let mut changed = false;
+            while let Some(start) = rest.find("${") {
+                let Some(len) = rest[start..].find('}') else {
+                    break;
+                };
+                let reference = &rest[start + 2..start + len];
+                result.push_str(&rest[..start]);
+                match resolve(reference) {
+                    Some(resolved) => {
+                        let resolved = resolved.map_err(error)?;
+                        // A lone reference keeps the variable's type
+                        if !changed
+                            && result.is_empty()
+                            && start == 0
+                            && len + 1 == rest.len()
+                            && !resolved.is_string()
+                        {
+                            *value = resolved;
+                            return Ok(true);
+                        }
+                        result.push_str(&scalar_text(&resolved).ok_or_else(|| {
+                            error(format!(
+                                "'${{{reference}}}' is not a string, number or boolean"
+                            ))
+                        })?);
+                        changed = true;
+                    }
+                    None => result.push_str(&rest[start..=start + len]),
+                }
+                rest = &rest[start + len + 1..];
+            }
+            if changed {
+                result.push_str(rest);
+                *text = result;
+            }
+            Ok(changed)
+        }
+        _ => Ok(false),
+    }
+}
+
+/// Returns the text of a scalar value.
+fn scalar_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// Returns `<stem>.<environment>.yaml` next to the configuration file
+/// (`halldyll.deploy.prod.yaml` for `halldyll.deploy.yaml`).
+fn overlay_path(config_file: &Path, environment: &str) -> Option<PathBuf> {
+    let stem = config_file.file_stem()?.to_str()?;
+    let extension = config_file
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("yaml");
+    Some(config_file.with_file_name(format!("{stem}.{environment}.{extension}")))
+}
+
+/// Merges `patch` into `base`: mappings key by key, lists whose entries all
+/// have a `name` entry by name (unmatched entries are appended), and
+/// anything else by replacement.
+fn merge_overlay(base: &mut Value, patch: Value) {
+    let named = |items: &[Value]| {
+        items
+            .iter()
+            .all(|item| item.get("name").and_then(Value::as_str).is_some())
+    };
+    match (base, patch) {
+        (Value::Mapping(base), Value::Mapping(patch)) => {
+            for (key, value) in patch {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_overlay(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (Value::Sequence(base), Value::Sequence(patch)) if named(base) && named(&patch) => {
+            for item in patch {
+                let name = item.get("name").cloned();
+                match base
+                    .iter_mut()
+                    .find(|existing| existing.get("name") == name.as_ref())
+                {
+                    Some(existing) => merge_overlay(existing, item),
+                    None => base.push(item),
+                }
+            }
+        }
+        (base, patch) => *base = patch,
+    }
+}
+
+/// Wraps a failure to read a dotenv file.
+fn dotenv_error(path: &Path, e: &dotenvy::Error) -> HalldyllError {
+    HalldyllError::Config(ConfigError::ParseError {
+        message: format!("Failed to load dotenv file: {e}"),
+        location: Some(path.display().to_string()),
+    })
+}
+
+/// Replaces `include` entries of the `pods` section with the pods they name.
+///
+/// Returns true if anything was included.
+fn expand_pod_includes(document: &mut Value, base_dir: &Path) -> Result<bool> {
+    let Some(pods) = document.get_mut("pods") else {
+        return Ok(false);
+    };
+    let entries = match pods {
+        Value::Sequence(entries) => entries.clone(),
+        Value::Mapping(_) => vec![pods.clone()],
+        _ => return Ok(false),
+    };
+    if !entries.iter().any(|entry| entry.get(INCLUDE_KEY).is_some()) {
+        return Ok(false);
+    }
+
+    let mut expanded = Vec::new();
+    let mut defined_in: HashMap<String, String> = HashMap::new();
+    let mut add = |pod: Value, origin: String| -> Result<()> {
+        if let Some(name) = pod.get("name").and_then(Value::as_str)
+            && let Some(previous) = defined_in.insert(name.to_string(), origin.clone())
+        {
+            return Err(HalldyllError::Config(ConfigError::validation(
+                format!("Pod '{name}' is defined in both {previous} and {origin}"),
+                "pods",
+            )));
+        }
+        expanded.push(pod);
+        Ok(())
+    };
+
+    for entry in entries {
+        let Some(include) = entry.get(INCLUDE_KEY) else {
+            add(entry, String::from("the configuration file"))?;
+            continue;
+        };
+
+        for pattern in include_patterns(include, "pods.include")? {
+            for file in resolve_include(base_dir, &pattern)? {
+                let location = file.display().to_string();
+                debug!("Including pods from {location}");
+                match read_included(&file)? {
+                    Value::Sequence(included_pods) => {
+                        for pod in included_pods {
+                            add(pod, location.clone())?;
+                        }
+                    }
+                    Value::Null => {}
+                    pod => add(pod, location.clone())?,
+                }
+            }
+        }
+    }
+
+    *pods = Value::Sequence(expanded);
+    Ok(true)
+}
+
+/// Merges the configuration fragments named by the top-level `include`
+/// into `document`.
+///
+/// Returns true if anything was included.
+fn expand_config_includes(document: &mut Value, base_dir: &Path) -> Result<bool> {
+    let Some(include) = document
+        .as_mapping_mut()
+        .and_then(|m| m.remove(INCLUDE_KEY))
+    else {
+        return Ok(false);
+    };
+
+    let mut defined_in: HashMap<String, String> = document
+        .get("pods")
+        .and_then(Value::as_sequence)
+        .into_iter()
+        .flatten()
+        .filter_map(|pod| pod.get("name").and_then(Value::as_str))
+        .map(|name| (name.to_string(), String::from("the configuration file")))
+        .collect();
+
+    for pattern in include_patterns(&include, INCLUDE_KEY)? {
+        for file in resolve_include(base_dir, &pattern)? {
+            let location = file.display().to_string();
+            debug!("Including configuration from {location}");
+            let fragment = match read_included(&file)? {
+                Value::Mapping(fragment) => fragment,
+                Value::Null => continue,
+                _ => {
+                    return Err(HalldyllError::Config(ConfigError::ParseError {
+                        message: String::from(
+                            "Included configuration must be a mapping of sections",
+                        ),
+                        location: Some(location),
+                    }));
+                }
+            };
+            if fragment.contains_key(INCLUDE_KEY) {
+                return Err(HalldyllError::Config(ConfigError::ParseError {
+                    message: String::from(
+                        "Included configuration files cannot include other files",
+                    ),
+                    location: Some(location),
+                }));
+            }
+
+            for pod in fragment
+                .get("pods")
+                .and_then(Value::as_sequence)
+                .into_iter()
+                .flatten()
+            {
+                if let Some(name) = pod.get("name").and_then(Value::as_str)
+                    && let Some(previous) = defined_in.insert(name.to_string(), location.clone())
+                {
+                    return Err(HalldyllError::Config(ConfigError::validation(
+                        format!("Pod '{name}' is defined in both {previous} and {location}"),
+                        "pods",
+                    )));
+                }
+            }
+            for (key, value) in fragment {
+                let field = key.as_str().unwrap_or_default().to_string();
+                merge_fragment(document, key, value, &field, &location)?;
+            }
+        }
+    }
+    Ok(true)
+}
+
+/// Merges a section of an included fragment into `base`: lists are
+/// appended, mappings merged key by key, and other values must be unset.
+fn merge_fragment(
+    base: &mut Value,
+    key: Value,
+    value: Value,
+    field: &str,
+    location: &str,
+) -> Result<()> {
+    let Some(base) = base.as_mapping_mut() else {
+        return Err(HalldyllError::Config(ConfigError::validation(
+            format!("{field} in {location} conflicts with a value in the configuration file"),
+            field,
+        )));
+    };
+    let Some(existing) = base.get_mut(&key) else {
+        base.insert(key, value);
+        return Ok(());
+    };
+    match (existing, value) {
+        (Value::Sequence(existing), Value::Sequence(items)) => existing.extend(items),
+        (existing @ Value::Mapping(_), Value::Mapping(section)) => {
+            for (child, child_value) in section {
+                let child_field = format!("{field}.{}", child.as_str().unwrap_or_default());
+                merge_fragment(existing, child, child_value, &child_field, location)?;
+            }
+        }
+        (existing @ Value::Null, value) => *existing = value,
+        _ => {
+            return Err(HalldyllError::Config(ConfigError::validation(
+                format!("{field} is set in both the configuration file and {location}"),
+                field,
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Returns the file patterns of an `include` value.
+fn include_patterns(include: &Value, field: &str) -> Result<Vec<String>> {
+    let patterns: Vec<String> = match include {
+        Value::String(pattern) => vec![pattern.clone()],
+        Value::Sequence(items) => items
+            .iter()
+            .filter_map(Value::as_str)
+            .map(String::from)
+            .collect(),
+        _ => vec![],
+    };
+    if patterns.is_empty() {
+        return Err(HalldyllError::Config(ConfigError::validation(
+            format!("{field} must be a file pattern or a list of patterns"),
+            field,
+        )));
+    }
+    Ok(patterns)
+}
+
+/// Reads and parses an included YAML file.
+fn read_included(file: &Path) -> Result<Value> {
+    let location = file.display().to_string();
+    let content = std::fs::read_to_string(file).map_err(|e| {
+        HalldyllError::Config(ConfigError::ParseError {
+            message: format!("Failed to read included file: {e}"),
+            location: Some(location.clone()),
+        })
+    })?;
+    serde_yaml::from_str(&content).map_err(|e| {
+        HalldyllError::Config(ConfigError::ParseError {
+            message: format!("YAML parse error: {e}"),
+            location: Some(location),
+        })
+    })
+}
+
+/// Lists the files an include pattern matches, sorted by name.
+///
+/// Wildcards (`*`, `?`) are allowed in the file name only.
+fn resolve_include(base_dir: &Path, pattern: &str) -> Result<Vec<PathBuf>> {
+    let (dir, file_pattern) = pattern.rsplit_once('/').unwrap_or(("", pattern));
+    let invalid =
+        |message: String| HalldyllError::Config(ConfigError::validation(message, "pods.include"));
+    if dir.contains(['*', '?']) {
+        return Err(invalid(format!(
+            "Invalid include '{pattern}': wildcards are only supported in the file name"
+        )));
+    }
+
+    let dir = base_dir.join(dir);
+    if !file_pattern.contains(['*', '?']) {
+        let file = dir.join(file_pattern);
+        if !file.is_file() {
+            return Err(invalid(format!(
+                "Included file {} does not exist",
+                file.display()
+            )));
+        }
+        return Ok(vec![file]);
+    }
+
+    let entries = std::fs::read_dir(&dir).map_err(|e| {
+        invalid(format!(
+            "Cannot read include directory {}: {e}",
+            dir.display()
+        ))
+    })?;
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(std::result::Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| wildcard_match(file_pattern, name))
+        })
+        .collect();
+    files.sort();
+
+    if files.is_empty() {
+        return Err(invalid(format!(
+            "Include '{pattern}' matches no files in {}",
+            dir.display()
+        )));
+    }
+    Ok(files)
+}
+
+/// Matches a name against a pattern where `*` is any run of characters and
+/// `?` any single character.
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                // Let the last '*' absorb one more character
+                Some((star, matched)) => {
+                    backtrack = Some((star, matched + 1));
+                    p = star + 1;
+                    n = matched + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
 /// Default configuration file names to search for.
 pub const DEFAULT_CONFIG_FILES: &[&str] = &[
     "halldyll.deploy.yaml",
@@ -201,8 +1189,9 @@ pub const DEFAULT_CONFIG_FILES: &[&str] = &[
 ///
 /// Returns an error if no configuration file is found.
 pub fn find_config_file(start_dir: impl AsRef<Path>) -> Result<std::path::PathBuf> {
-    let start = start_dir.as_ref();
-    let mut current = start.to_path_buf();
+    // Walking up a relative path like "." would stop immediately
+    let start = crate::platform::absolute(start_dir.as_ref());
+    let mut current = start.clone();
 
     loop {
         for filename in DEFAULT_CONFIG_FILES {
@@ -295,4 +1284,245 @@ pods:
         assert_eq!(config.pods[0].name, "pod-text");
         assert_eq!(config.pods[0].models.len(), 1);
     }
+
+    #[test]
+    fn test_pod_includes() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let pods_dir = dir.path().join("pods");
+        std::fs::create_dir(&pods_dir).unwrap();
+        let pod = |name: &str| format!("name: {name}\ngpu:\n  type: A40\nruntime:\n  image: img\n");
+        std::fs::write(pods_dir.join("b-embed.yaml"), pod("embed")).unwrap();
+        std::fs::write(pods_dir.join("a-llm.yaml"), pod("llm")).unwrap();
+        std::fs::write(pods_dir.join("notes.txt"), "ignored").unwrap();
+
+        let config_path = dir.path().join("halldyll.deploy.yaml");
+        let header = "project:\n  name: proj\nstate:\n  backend: local\n";
+        std::fs::write(
+            &config_path,
+            format!("{header}pods:\n  include: pods/*.yaml\n"),
+        )
+        .unwrap();
+
+        let config = ConfigParser::new().load_file(&config_path).unwrap();
+        let names: Vec<&str> = config.pods.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["llm", "embed"]);
+
+        let inline = format!(
+            "{header}pods:\n  - include: pods/a-*.yaml\n  - {}",
+            pod("llm").replace('\n', "\n    ")
+        );
+        std::fs::write(&config_path, inline).unwrap();
+        let err = ConfigParser::new().load_file(&config_path).unwrap_err();
+        assert!(err.to_string().contains("defined in both"));
+
+        assert!(wildcard_match("*.y*ml", "llm.yaml"));
+        assert!(!wildcard_match("a-?.yaml", "a-llm.yaml"));
+    }
+
+    #[test]
+    fn test_environment_overlays() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config_path = dir.path().join("halldyll.deploy.yaml");
+        let base = "project:\n  name: proj\nstate:\n  backend: local\npods:\n  - name: llm\n    gpu:\n      type: A40\n    runtime:\n      image: img\n  - name: embed\n    gpu:\n      type: A40\n    runtime:\n      image: img\nenvironments:\n  dev: {}\n  prod:\n    pods:\n      - name: llm\n        gpu:\n          type: H100\n          count: 2\n";
+        std::fs::write(&config_path, base).unwrap();
+        std::fs::write(
+            dir.path().join("halldyll.deploy.prod.yaml"),
+            "guardrails:\n  max_hourly_cost: 50\npods:\n  - name: llm\n    replicas: 3\n",
+        )
+        .unwrap();
+
+        let dev = ConfigParser::new().load_file(&config_path).unwrap();
+        assert_eq!(dev.project.environment, "dev");
+        assert_eq!(dev.pods[0].gpu.gpu_type, "A40");
+
+        let prod = ConfigParser::new()
+            .with_environment("prod")
+            .load_file(&config_path)
+            .unwrap();
+        assert_eq!(prod.project.environment, "prod");
+        assert_eq!(prod.pods.len(), 2);
+        assert_eq!(prod.pods[0].gpu.gpu_type, "H100");
+        assert_eq!(prod.pods[0].gpu.count, 2);
+        assert_eq!(prod.pods[0].replicas, 3);
+        assert_eq!(prod.pods[0].runtime.image, "img");
+        assert_eq!(prod.pods[1].gpu.gpu_type, "A40");
+        assert!(prod.guardrails.is_some());
+
+        let err = ConfigParser::new()
+            .with_environment("prdo")
+            .load_file(&config_path)
+            .unwrap_err();
+        assert!(err.to_string().contains("prdo"));
+    }
+
+    #[test]
+    fn test_variable_interpolation() {
+        let yaml = "vars:\n  tag: v1\n  disk: 50\n  repo: org/model\nproject:\n  name: proj\nstate:\n  backend: local\npods:\n  - name: llm\n    gpu:\n      type: A40\n    volumes:\n      - name: data\n        mount: /data\n        size_gb: ${var.disk}\n    runtime:\n      image: repo/app:${var.tag}\n      env:\n        SEARCH_PATH: ${env.PATH}\n        HF_TOKEN: ${HF_TOKEN}\n      args: [\"--shards\", \"${gpu.count}\"]\n    models:\n      - id: m\n        provider: huggingface\n        repo: ${var.repo}\n";
+        let config = ConfigParser::new()
+            .with_var("tag", "v2")
+            .parse_yaml(yaml, None)
+            .unwrap();
+        let pod = &config.pods[0];
+        assert_eq!(pod.runtime.image, "repo/app:v2");
+        assert_eq!(pod.volumes[0].size_gb, Some(50));
+        assert_eq!(pod.models[0].repo.as_deref(), Some("org/model"));
+        assert_eq!(
+            pod.runtime.env["SEARCH_PATH"],
+            std::env::var("PATH").unwrap()
+        );
+        assert_eq!(pod.runtime.env["HF_TOKEN"], "${HF_TOKEN}");
+
+        let err = ConfigParser::new()
+            .parse_yaml(&yaml.replace("${var.tag}", "${var.missing}"), None)
+            .unwrap_err();
+        assert!(err.to_string().contains("missing"));
+        assert_eq!(
+            parse_var("tag=v3").unwrap(),
+            (String::from("tag"), String::from("v3"))
+        );
+        assert!(parse_var("tag").is_err());
+        assert_eq!(var_env_suffix("hf-repo"), "HF_REPO");
+    }
+
+    #[test]
+    fn test_missing_variables() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config_path = dir.path().join("halldyll.deploy.yaml");
+        let yaml = "vars:\n  tag: v1\n  token:\n    description: Registry token\n    sensitive: true\n  region: null\nproject:\n  name: proj\n  region: ${var.region}\nstate:\n  backend: local\npods:\n  - name: llm\n    gpu:\n      type: A40\n    runtime:\n      image: repo/app:${var.tag}-${var.flavour}\n      env:\n        TOKEN: ${var.token}\n        OTHER: ${var.flavour}\n        HOME_DIR: ${env.HALLDYLL_TEST_UNSET_VARIABLE}\n";
+        std::fs::write(&config_path, yaml).unwrap();
+
+        // Every missing value is reported at once
+        let err = ConfigParser::new()
+            .load_file(&config_path)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("variables without a value: "), "{err}");
+        assert!(
+            ["region", "flavour", "token"]
+                .iter()
+                .all(|name| err.contains(name)),
+            "{err}"
+        );
+        assert!(
+            err.contains("environment variables not set: HALLDYLL_TEST_UNSET_VARIABLE"),
+            "{err}"
+        );
+
+        let missing = ConfigParser::new()
+            .with_var("region", "EU-RO-1")
+            .missing_variables(&config_path)
+            .unwrap();
+        assert_eq!(
+            missing,
+            [
+                MissingVariable {
+                    name: "flavour".into(),
+                    description: None,
+                    sensitive: false
+                },
+                MissingVariable {
+                    name: "token".into(),
+                    description: Some("Registry token".into()),
+                    sensitive: true
+                },
+            ]
+        );
+
+        std::fs::write(
+            &config_path,
+            yaml.replace("${env.HALLDYLL_TEST_UNSET_VARIABLE}", "x"),
+        )
+        .unwrap();
+        let config = ConfigParser::new()
+            .with_var("region", "EU-RO-1")
+            .with_var("flavour", "cuda")
+            .with_var("token", "secret")
+            .load_file(&config_path)
+            .unwrap();
+        assert_eq!(config.pods[0].runtime.image, "repo/app:v1-cuda");
+        assert_eq!(config.pods[0].runtime.env["TOKEN"], "secret");
+    }
+
+    #[test]
+    fn test_config_includes() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("pods")).unwrap();
+        let pod = |name: &str| {
+            format!("  - name: {name}\n    gpu:\n      type: A40\n    runtime:\n      image: img\n")
+        };
+        std::fs::write(
+            dir.path().join("pods/inference.yaml"),
+            format!("guardrails:\n  max_hourly_cost: 20\npods:\n{}", pod("llm")),
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("pods/embeddings.yaml"),
+            format!("pods:\n{}", pod("embed")),
+        )
+        .unwrap();
+
+        let config_path = dir.path().join("halldyll.deploy.yaml");
+        let header = "project:\n  name: proj\nstate:\n  backend: local\n";
+        let include = "include: [pods/inference.yaml, pods/embeddings.yaml]\n";
+        std::fs::write(
+            &config_path,
+            format!("{header}{include}pods:\n{}", pod("web")),
+        )
+        .unwrap();
+
+        let config = ConfigParser::new().load_file(&config_path).unwrap();
+        let names: Vec<&str> = config.pods.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["web", "llm", "embed"]);
+        assert!(config.guardrails.is_some());
+
+        std::fs::write(
+            &config_path,
+            format!("{header}{include}pods:\n{}", pod("embed")),
+        )
+        .unwrap();
+        let err = ConfigParser::new().load_file(&config_path).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("'embed' is defined in both the configuration file and")
+        );
+
+        let guarded = "guardrails:\n  max_hourly_cost: 10\n";
+        std::fs::write(&config_path, format!("{header}{guarded}{include}")).unwrap();
+        let conflict = ConfigParser::new().load_file(&config_path).unwrap_err();
+        assert!(
+            conflict
+                .to_string()
+                .contains("guardrails.max_hourly_cost is set in both")
+        );
+    }
+
+    #[test]
+    fn test_dotenv_layering() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join(".env"),
+            "HALLDYLL_TEST_LAYER=base\nHALLDYLL_TEST_BASE_ONLY=1\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join(".env.staging"),
+            "HALLDYLL_TEST_LAYER=staging\n",
+        )
+        .unwrap();
+        let config_path = dir.path().join("halldyll.deploy.yaml");
+        std::fs::write(
+            &config_path,
+            "project:\n  name: proj\n  environment: staging\n",
+        )
+        .unwrap();
+
+        let parser = ConfigParser::for_config_file(&config_path);
+        assert_eq!(parser.dotenv_files().len(), 2);
+
+        let vars = parser.resolve_env().unwrap();
+        let find = |key: &str| vars.iter().find(|v| v.key == key).unwrap();
+        assert_eq!(find("HALLDYLL_TEST_LAYER").value, "staging");
+        assert_eq!(find("HALLDYLL_TEST_LAYER").source, ".env.staging");
+        assert_eq!(find("HALLDYLL_TEST_BASE_ONLY").source, ".env");
+    }
 }
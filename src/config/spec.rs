@@ -18,8 +18,139 @@ pub struct DeployConfig {
     /// Optional guardrails configuration.
     #[serde(default)]
     pub guardrails: Option<GuardrailsConfig>,
+    /// Optional diff settings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff: Option<DiffConfig>,
+    /// Optional alerts raised by `halldyll watch`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alerts: Option<AlertsConfig>,
+    /// Serverless endpoints deployed alongside the pods.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub endpoints: Vec<EndpointConfig>,
+    /// `RunPod` templates managed with the pods and endpoints using them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub templates: Vec<TemplateConfig>,
 }
 
+/// A `RunPod` template, saved as `<project>-<env>-<name>`.
+///
+/// Pods and endpoints refer to it by name with `template`. Endpoints need a
+/// `serverless` template; pods need a regular one.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TemplateConfig {
+    /// Template name (unique within the project).
+    pub name: String,
+    /// Container image.
+    pub image: String,
+    /// Environment variables.
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Ports to expose.
+    #[serde(default)]
+    pub ports: Vec<PortConfig>,
+    /// Container disk in GB.
+    #[serde(default = "default_container_disk_gb")]
+    pub container_disk_gb: u32,
+    /// Volume in GB (0 for none).
+    #[serde(default)]
+    pub volume_gb: u32,
+    /// Volume mount path.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volume_mount: Option<String>,
+    /// Whether the template is for serverless endpoint workers.
+    #[serde(default)]
+    pub serverless: bool,
+}
+
+/// A `RunPod` serverless endpoint.
+///
+/// Endpoints run the workers of an existing serverless template and scale
+/// between `workers.min` and `workers.max`. Changes are applied in place.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EndpointConfig {
+    /// Endpoint name (unique within the project).
+    pub name: String,
+    /// ID of an existing `RunPod` serverless template the workers run.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub template_id: String,
+    /// Configured serverless template the workers run, instead of `template_id`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// GPUs of each worker.
+    pub gpu: EndpointGpuConfig,
+    /// Worker scaling bounds.
+    #[serde(default)]
+    pub workers: WorkersConfig,
+    /// Seconds an idle worker is kept before it is scaled down.
+    #[serde(default = "default_idle_timeout_secs")]
+    pub idle_timeout_secs: u32,
+}
+
+/// GPU selection of a serverless endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EndpointGpuConfig {
+    /// `RunPod` GPU pools workers may run on, in order of preference (e.g. `AMPERE_24`).
+    pub pools: Vec<String>,
+    /// GPUs per worker.
+    #[serde(default = "default_gpu_count")]
+    pub count: u32,
+}
+
+/// Worker scaling bounds of a serverless endpoint.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WorkersConfig {
+    /// Workers kept running (and billed) while idle.
+    #[serde(default)]
+    pub min: u32,
+    /// Maximum number of workers.
+    #[serde(default = "default_workers_max")]
+    pub max: u32,
+}
+
+impl Default for WorkersConfig {
+    fn default() -> Self {
+        Self {
+            min: 0,
+            max: default_workers_max(),
+        }
+    }
+}
+
+/// Thresholds `halldyll watch` alerts on, and where alerts are sent.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AlertsConfig {
+    /// Alert when the project's pods cost more than this many USD per hour.
+    #[serde(default)]
+    pub hourly_cost_above: Option<f64>,
+    /// Alert when a pod has failed its health check for this many minutes.
+    #[serde(default)]
+    pub unhealthy_minutes: Option<u64>,
+    /// Alert when a pod has drifted from the configuration for this many minutes.
+    #[serde(default)]
+    pub drift_minutes: Option<u64>,
+    /// Minutes before an alert that is still firing is sent again.
+    #[serde(default = "default_alert_cooldown_minutes")]
+    pub cooldown_minutes: u64,
+    /// Webhooks alerts are posted to as `{"text": ...}` (Slack-compatible).
+    ///
+    /// Alerts are always printed by `halldyll watch`.
+    #[serde(default)]
+    pub webhooks: Vec<String>,
+}
+
+/// Diff settings.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DiffConfig {
+    /// Diff fields never reported (e.g. `env.MY_SIDECAR_VAR`, `env.OTEL_*`).
+    ///
+    /// Extends the built-in list of fields `RunPod` sets on its own.
+    #[serde(default)]
+    pub ignore: Vec<String>,
+}
+
+/// Namespace of the system tags unless `project.tag_namespace` sets another.
+pub const DEFAULT_TAG_NAMESPACE: &str = "halldyll.io";
+
 /// Project-level configuration.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct ProjectConfig {
@@ -28,21 +159,56 @@ pub struct ProjectConfig {
     /// Environment (e.g., "dev", "staging", "prod").
     #[serde(default = "default_environment")]
     pub environment: String,
-    /// `RunPod` region preference.
+    /// `RunPod` data center pods are created in (e.g. `EU-RO-1`).
     #[serde(default)]
     pub region: Option<String>,
+    /// Data centers tried in order when the region has no capacity.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub region_fallback: Vec<String>,
     /// Cloud type (SECURE or COMMUNITY).
     #[serde(default)]
     pub cloud_type: CloudType,
     /// Compute type (GPU or CPU).
     #[serde(default)]
     pub compute_type: ComputeType,
+    /// How pods whose spec changed are replaced (pods may override it).
+    #[serde(default)]
+    pub replace_strategy: ReplaceStrategy,
+    /// Secondary data centers for active/passive failover.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failover: Option<FailoverConfig>,
+    /// SSH public keys installed on every pod (pods may override them).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssh: Option<SshConfig>,
+    /// Prefix of the system tags (`<namespace>/project`, ...).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag_namespace: Option<String>,
+}
+
+impl ProjectConfig {
+    /// Returns the namespace of the system tags.
+    #[must_use]
+    pub fn tag_namespace(&self) -> &str {
+        self.tag_namespace
+            .as_deref()
+            .unwrap_or(DEFAULT_TAG_NAMESPACE)
+    }
+}
+
+/// Secondary side of an active/passive deployment.
+///
+/// `halldyll failover activate` provisions standby copies of the pods in
+/// these data centers; `failover deactivate` tears them down again.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FailoverConfig {
+    /// `RunPod` data center IDs, tried in order for each standby pod.
+    pub data_centers: Vec<String>,
 }
 
 /// State backend configuration.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct StateConfig {
-    /// Backend type (local or s3).
+    /// Backend type (local, s3 or http).
     pub backend: StateBackend,
     /// S3 bucket name (required for s3 backend).
     #[serde(default)]
@@ -53,9 +219,40 @@ pub struct StateConfig {
     /// S3 region (optional, uses AWS default if not specified).
     #[serde(default)]
     pub region: Option<String>,
-    /// Local state file path (for local backend).
+    /// Custom S3 endpoint for S3-compatible stores such as `MinIO`.
+    #[serde(default)]
+    pub endpoint_url: Option<String>,
+    /// Address buckets as `endpoint/bucket` instead of `bucket.endpoint`.
+    ///
+    /// Most self-hosted S3-compatible stores require this.
+    #[serde(default)]
+    pub force_path_style: bool,
+    /// Compression of the remote state object (s3 backend).
+    #[serde(default)]
+    pub compression: StateCompression,
+    /// State URL (required for http backend).
+    #[serde(default)]
+    pub address: Option<String>,
+    /// URL that receives `LOCK` requests (http backend; locking is disabled without it).
+    #[serde(default)]
+    pub lock_address: Option<String>,
+    /// URL that receives `UNLOCK` requests (defaults to `lock_address`).
+    #[serde(default)]
+    pub unlock_address: Option<String>,
+    /// Basic auth username (http backend).
+    #[serde(default)]
+    pub username: Option<String>,
+    /// Basic auth password (http backend; prefer `HALLDYLL_STATE_PASSWORD`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    /// Local state directory (for local backend).
+    ///
+    /// Relative to the working directory; `~` and environment variables are expanded.
     #[serde(default)]
     pub path: Option<String>,
+    /// Number of state snapshots kept in `snapshots/` (local backend; 0 disables them).
+    #[serde(default = "default_keep_snapshots")]
+    pub keep_snapshots: usize,
 }
 
 /// State backend types.
@@ -67,6 +264,21 @@ pub enum StateBackend {
     Local,
     /// AWS S3-based state storage.
     S3,
+    /// Remote state over HTTP (Terraform `http` backend protocol).
+    Http,
+}
+
+/// Compression applied to remote state objects.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum StateCompression {
+    /// Store pretty-printed JSON.
+    #[default]
+    None,
+    /// Gzip-compressed JSON.
+    Gzip,
+    /// Zstandard-compressed JSON.
+    Zstd,
 }
 
 /// Cloud type options.
@@ -80,6 +292,40 @@ pub enum CloudType {
     Community,
 }
 
+/// How a pod whose spec changed is replaced.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplaceStrategy {
+    /// Terminate the old pod first, freeing its GPUs for the new one.
+    #[default]
+    DeleteBeforeCreate,
+    /// Create the new pod first and terminate the old one once it exists.
+    CreateBeforeDelete,
+    /// Never replace automatically; the plan only reports the pending change.
+    Manual,
+}
+
+/// What to do with a pod stopped longer than the retention period.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum StoppedRetentionAction {
+    /// Log a warning on every watch iteration.
+    #[default]
+    Warn,
+    /// Terminate the pod, releasing its volume disk.
+    Terminate,
+}
+
+impl std::fmt::Display for ReplaceStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DeleteBeforeCreate => write!(f, "delete_before_create"),
+            Self::CreateBeforeDelete => write!(f, "create_before_delete"),
+            Self::Manual => write!(f, "manual"),
+        }
+    }
+}
+
 /// Compute type options.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
 #[serde(rename_all = "UPPERCASE")]
@@ -92,20 +338,40 @@ pub enum ComputeType {
 }
 
 /// Configuration for a single pod.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct PodConfig {
     /// Unique name for the pod within this project.
     pub name: String,
+    /// Whether the pod should run.
+    ///
+    /// A disabled pod is declared but kept stopped: its running instances
+    /// are stopped and missing ones are not created. Not part of the spec
+    /// hash, so toggling it never replaces the pod.
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
     /// GPU configuration.
     pub gpu: GpuConfig,
+    /// On-demand or spot (interruptible) pricing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pricing: Option<PricingConfig>,
+    /// Data center overriding `project.region` for this pod.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
     /// Network ports to expose.
     #[serde(default)]
     pub ports: Vec<PortConfig>,
     /// Volume mounts.
     #[serde(default)]
     pub volumes: Vec<VolumeConfig>,
-    /// Container runtime configuration.
+    /// Container runtime configuration (may be left out with a `template`).
+    #[serde(default)]
     pub runtime: RuntimeConfig,
+    /// Configured template the pod is deployed from.
+    ///
+    /// The pod inherits the template's image, ports and any env vars it
+    /// does not set itself.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
     /// Models to load on this pod.
     #[serde(default)]
     pub models: Vec<ModelConfig>,
@@ -115,12 +381,46 @@ pub struct PodConfig {
     /// Pod-specific tags (merged with project tags).
     #[serde(default)]
     pub tags: HashMap<String, String>,
+    /// Number of identical instances to run.
+    #[serde(default = "default_replicas")]
+    pub replicas: u32,
+    /// Optional autoscaling policy for the replica count.
+    #[serde(default)]
+    pub autoscale: Option<AutoscaleConfig>,
+    /// Optional load balancer manifest listing the replica endpoints.
+    #[serde(default)]
+    pub load_balancer: Option<LoadBalancerConfig>,
+    /// Replacement strategy overriding `project.replace_strategy`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replace_strategy: Option<ReplaceStrategy>,
+    /// Stopped clones kept ready for fast scale-ups and replacements.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warm_pool: Option<WarmPoolConfig>,
+    /// Graceful engine drain before the pod is replaced or scaled away.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub drain: Option<DrainConfig>,
+    /// Pods that must be up and healthy before this one is provisioned.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+    /// SSH public keys overriding `project.ssh`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssh: Option<SshConfig>,
+    /// Free-form notes for operators, shown by `halldyll status`.
+    ///
+    /// Not part of the spec hash, so editing them never replaces the pod.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    /// Concurrency and bandwidth of model downloads (not part of the spec hash).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_downloads: Option<ModelDownloadsConfig>,
 }
 
 /// GPU configuration for a pod.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct GpuConfig {
-    /// GPU type identifier (e.g., "NVIDIA A40", "NVIDIA RTX 4090").
+    /// GPU type identifier (e.g., "NVIDIA A40", "NVIDIA RTX 4090"), or
+    /// `cheapest` (alias `auto`) to pick the cheapest available type with at
+    /// least `min_vram_gb` when the pod is created.
     #[serde(rename = "type")]
     pub gpu_type: String,
     /// Number of GPUs to request.
@@ -132,6 +432,69 @@ pub struct GpuConfig {
     /// Fallback GPU types if primary is unavailable.
     #[serde(default)]
     pub fallback: Vec<String>,
+    /// Highest acceptable community cloud price per GPU per hour, in USD.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_price_per_hour: Option<f64>,
+    /// What to do when no candidate GPU type is under `max_price_per_hour`.
+    #[serde(default)]
+    pub max_price_action: MaxPriceAction,
+}
+
+impl GpuConfig {
+    /// Returns true if the GPU type is picked by price (`type: cheapest` or `auto`).
+    #[must_use]
+    pub fn is_auto(&self) -> bool {
+        ["cheapest", "auto"]
+            .iter()
+            .any(|auto| self.gpu_type.eq_ignore_ascii_case(auto))
+    }
+}
+
+/// What to do when every available GPU type costs more than the ceiling.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MaxPriceAction {
+    /// Fail the pod creation.
+    #[default]
+    Fail,
+    /// Use the cheapest available candidate anyway, with a warning.
+    Cheapest,
+}
+
+/// How a pod is billed.
+///
+/// Spot pods are interruptible: `RunPod` may stop them when someone bids
+/// more, and `reconcile` recreates pods that were preempted.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub struct PricingConfig {
+    /// Pricing mode.
+    #[serde(default)]
+    pub mode: PricingMode,
+    /// Bid per GPU in USD per hour (required for `spot`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_bid: Option<f64>,
+}
+
+/// Pricing mode of a pod.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PricingMode {
+    /// Regular on-demand pod.
+    #[default]
+    OnDemand,
+    /// Interruptible pod rented at a bid.
+    Spot,
+}
+
+impl PricingConfig {
+    /// Returns the bid per GPU if this is spot pricing.
+    #[must_use]
+    pub const fn spot_bid(&self) -> Option<f64> {
+        match self.mode {
+            PricingMode::Spot => self.max_bid,
+            PricingMode::OnDemand => None,
+        }
+    }
 }
 
 /// Port configuration for a pod.
@@ -174,18 +537,42 @@ pub struct VolumeConfig {
     /// Size in GB (for new volumes).
     #[serde(default)]
     pub size_gb: Option<u32>,
+    /// Existing `RunPod` network volume to mount instead of a pod volume.
+    ///
+    /// Network volumes outlive the pod, so `destroy --keep-volumes` can keep them.
+    #[serde(default)]
+    pub network_volume_id: Option<String>,
+    /// `RunPod` data center to create a network volume in (e.g. `EU-RO-1`).
+    ///
+    /// A persistent volume with a data center and no `network_volume_id` is
+    /// created as a network volume by `apply`, one per pod instance, and the
+    /// pod is placed in that data center.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_center: Option<String>,
+}
+
+impl VolumeConfig {
+    /// Returns true if halldyll creates this volume as a network volume.
+    #[must_use]
+    pub const fn is_managed(&self) -> bool {
+        self.persistent && self.network_volume_id.is_none() && self.data_center.is_some()
+    }
 }
 
 /// Container runtime configuration.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
 pub struct RuntimeConfig {
-    /// Container image to use.
+    /// Container image to use (inherited from the pod's template when empty).
+    #[serde(default)]
     pub image: String,
     /// Environment variables.
     #[serde(default)]
     pub env: HashMap<String, String>,
-    /// Optional command override.
-    #[serde(default)]
+    /// Optional command override (`entrypoint` is accepted as an alias).
+    ///
+    /// `command`, `args` and `env` values may use `${gpu.count}` and
+    /// `${pod.name}`, resolved when the pod request is built.
+    #[serde(default, alias = "entrypoint")]
     pub command: Option<Vec<String>>,
     /// Optional arguments.
     #[serde(default)]
@@ -202,12 +589,96 @@ pub struct ModelConfig {
     /// `HuggingFace` repository (for huggingface provider).
     #[serde(default)]
     pub repo: Option<String>,
+    /// Hub revision (branch, tag or commit) to download; the default branch if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision: Option<String>,
     /// Model loading configuration.
     #[serde(default)]
     pub load: Option<LoadConfig>,
     /// Bundle components (for bundle provider).
     #[serde(default)]
     pub components: Option<Vec<String>>,
+    /// Download retries and Hub mirror (not part of the spec hash).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub download: Option<DownloadConfig>,
+}
+
+/// How a model is downloaded from the `HuggingFace` Hub.
+///
+/// With a mirror, downloads go to it first (as `HF_ENDPOINT`) and fall back
+/// to the pod's default endpoint once its attempts are used up.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DownloadConfig {
+    /// Extra attempts per endpoint after a failed download.
+    #[serde(default = "default_download_retries")]
+    pub retries: u32,
+    /// Hub mirror: a host such as `hf-mirror.com`, or an endpoint URL.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mirror: Option<String>,
+}
+
+impl Default for DownloadConfig {
+    fn default() -> Self {
+        Self {
+            retries: default_download_retries(),
+            mirror: None,
+        }
+    }
+}
+
+impl DownloadConfig {
+    /// Returns the mirror as an endpoint URL (`https://` is assumed for bare hosts).
+    #[must_use]
+    pub fn mirror_endpoint(&self) -> Option<String> {
+        let mirror = self.mirror.as_deref()?.trim().trim_end_matches('/');
+        if mirror.is_empty() {
+            return None;
+        }
+        Some(if mirror.contains("://") {
+            mirror.to_string()
+        } else {
+            format!("https://{mirror}")
+        })
+    }
+}
+
+/// How a pod's models are downloaded during post-provision setup.
+///
+/// Models are downloaded `parallel` at a time. The bandwidth cap is shared
+/// evenly between concurrent downloads and enforced with `trickle` when the
+/// image has it; without it, downloads run unthrottled.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ModelDownloadsConfig {
+    /// Models downloaded at the same time.
+    #[serde(default = "default_parallel_downloads")]
+    pub parallel: u32,
+    /// Total download bandwidth in MB/s.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_mb_per_sec: Option<u32>,
+}
+
+impl Default for ModelDownloadsConfig {
+    fn default() -> Self {
+        Self {
+            parallel: default_parallel_downloads(),
+            max_mb_per_sec: None,
+        }
+    }
+}
+
+impl ModelDownloadsConfig {
+    /// Returns how many models may download at once (at least one).
+    #[must_use]
+    pub fn concurrency(&self) -> usize {
+        usize::try_from(self.parallel.max(1)).unwrap_or(1)
+    }
+
+    /// Returns the bandwidth cap of each concurrent download, in KB/s.
+    #[must_use]
+    pub fn per_download_kb_per_sec(&self) -> Option<u32> {
+        self.max_mb_per_sec
+            .map(|total| (total.saturating_mul(1024) / self.parallel.max(1)).max(1))
+    }
 }
 
 /// Model provider types.
@@ -237,6 +708,23 @@ pub struct LoadConfig {
     /// Additional engine-specific options.
     #[serde(default)]
     pub options: HashMap<String, serde_json::Value>,
+    /// Completion request sent once the engine is up (vLLM/TGI only).
+    #[serde(default)]
+    pub smoke_test: Option<SmokeTestConfig>,
+}
+
+/// Post-start smoke test against the engine's OpenAI-compatible API.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SmokeTestConfig {
+    /// Prompt sent to `/v1/completions`.
+    #[serde(default = "default_smoke_prompt")]
+    pub prompt: String,
+    /// Maximum tokens to generate.
+    #[serde(default = "default_smoke_max_tokens")]
+    pub max_tokens: u32,
+    /// Seconds to wait for the engine to accept requests (model load time included).
+    #[serde(default = "default_smoke_timeout")]
+    pub timeout_secs: u64,
 }
 
 /// Health check configuration.
@@ -255,6 +743,159 @@ pub struct HealthCheckConfig {
     /// Number of failures before marking unhealthy.
     #[serde(default = "default_health_threshold")]
     pub failure_threshold: u32,
+    /// Extra request headers; values may reference environment variables as `${VAR}`.
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// Expected HTTP status code (any 2xx if not set).
+    #[serde(default)]
+    pub expect_status: Option<u16>,
+    /// Substring the response body must contain.
+    #[serde(default)]
+    pub expect_body_contains: Option<String>,
+}
+
+/// Autoscaling policy for a replicated pod.
+///
+/// At least one of `target_concurrent_requests`, `target_gpu_util` or
+/// `idle_timeout_secs` must be set. When both targets are set, the larger
+/// resulting replica count wins.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AutoscaleConfig {
+    /// Minimum number of replicas.
+    #[serde(default = "default_autoscale_min")]
+    pub min: u32,
+    /// Maximum number of replicas.
+    pub max: u32,
+    /// Target in-flight requests per replica (running + waiting, from engine metrics).
+    #[serde(default)]
+    pub target_concurrent_requests: Option<u32>,
+    /// Target average GPU utilization in percent (as reported by `RunPod`).
+    #[serde(default)]
+    pub target_gpu_util: Option<u32>,
+    /// Minimum seconds between two scale-up events.
+    #[serde(default = "default_scale_up_cooldown")]
+    pub scale_up_cooldown_secs: u64,
+    /// Minimum seconds between two scale-down events.
+    #[serde(default = "default_scale_down_cooldown")]
+    pub scale_down_cooldown_secs: u64,
+    /// Port serving engine metrics (defaults to the first HTTP port).
+    #[serde(default)]
+    pub metrics_port: Option<u16>,
+    /// Path of the Prometheus metrics endpoint.
+    #[serde(default = "default_metrics_path")]
+    pub metrics_path: String,
+    /// Stop all replicas after this many seconds without in-flight requests.
+    #[serde(default)]
+    pub idle_timeout_secs: Option<u64>,
+}
+
+/// Pool of stopped clones kept for a pod.
+///
+/// Warm pods are created, left to pull their image and models, then stopped,
+/// so only their storage is billed. A scale-up or replacement resumes one
+/// instead of provisioning from scratch, and the pool is refilled.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WarmPoolConfig {
+    /// Number of stopped clones to keep.
+    pub size: u32,
+}
+
+/// Graceful drain of an inference engine before its pod is terminated.
+///
+/// The optional `endpoint` is called first so the engine stops taking new
+/// requests, then the engine's metrics are polled until no request is in
+/// flight or `timeout_secs` passes.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DrainConfig {
+    /// Path to POST to before waiting (e.g. a custom `/drain` route).
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    /// Engine port (defaults to the first HTTP port).
+    #[serde(default)]
+    pub port: Option<u16>,
+    /// Prometheus path polled for in-flight requests.
+    #[serde(default = "default_metrics_path")]
+    pub metrics_path: String,
+    /// Longest time to wait for in-flight requests to finish, in seconds.
+    #[serde(default = "default_drain_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+/// SSH public keys allowed to log in to pods.
+///
+/// Keys are passed to new pods through `RunPod`'s `PUBLIC_KEY` variable and
+/// rewritten in `~/.ssh/authorized_keys` on running pods when they change.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SshConfig {
+    /// Public keys, one per entry.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub authorized_keys: Vec<String>,
+    /// File with more public keys, one per line (`~` is expanded).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from_file: Option<String>,
+}
+
+impl SshConfig {
+    /// Returns the public keys, reading `from_file` if set.
+    ///
+    /// Blank lines and `#` comments in the file are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `from_file` cannot be read.
+    pub fn public_keys(&self) -> crate::error::Result<Vec<String>> {
+        let mut keys: Vec<String> = self
+            .authorized_keys
+            .iter()
+            .map(|key| key.trim().to_string())
+            .filter(|key| !key.is_empty())
+            .collect();
+
+        if let Some(file) = &self.from_file {
+            let path = crate::platform::expand_path(file);
+            let content = std::fs::read_to_string(&path).map_err(|e| {
+                crate::error::HalldyllError::Config(crate::error::ConfigError::validation(
+                    format!("Cannot read SSH keys from {}: {e}", path.display()),
+                    "ssh.from_file",
+                ))
+            })?;
+            for line in content.lines().map(str::trim) {
+                if !line.is_empty() && !line.starts_with('#') && !keys.iter().any(|k| k == line) {
+                    keys.push(line.to_string());
+                }
+            }
+        }
+
+        Ok(keys)
+    }
+}
+
+/// Load balancer manifest generated for a pod's replicas.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LoadBalancerConfig {
+    /// Manifest format to generate.
+    pub format: LoadBalancerFormat,
+    /// Output file path (defaults to `.halldyll/lb/<pod>.<ext>`).
+    #[serde(default)]
+    pub output: Option<String>,
+    /// Pod port to balance (defaults to the first HTTP port).
+    #[serde(default)]
+    pub port: Option<u16>,
+    /// Port the load balancer listens on.
+    #[serde(default = "default_lb_listen_port")]
+    pub listen_port: u16,
+}
+
+/// Supported load balancer manifest formats.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LoadBalancerFormat {
+    /// nginx `upstream` + `server` blocks.
+    Nginx,
+    /// Caddyfile site block with `reverse_proxy`.
+    Caddy,
+    /// `HAProxy` frontend/backend sections.
+    Haproxy,
 }
 
 /// Guardrails configuration for cost and resource limits.
@@ -269,6 +910,15 @@ pub struct GuardrailsConfig {
     /// Time-to-live in hours (auto-stop after this time).
     #[serde(default)]
     pub ttl_hours: Option<u32>,
+    /// Minimum hours the account balance must cover at the planned spend rate.
+    #[serde(default)]
+    pub min_runway_hours: Option<f64>,
+    /// Hours a pod may stay stopped (still billing storage) before `watch` acts.
+    #[serde(default)]
+    pub stopped_retention_hours: Option<u32>,
+    /// What `watch` does with pods stopped longer than `stopped_retention_hours`.
+    #[serde(default)]
+    pub stopped_retention_action: StoppedRetentionAction,
     /// Whether to allow fallback GPU types.
     #[serde(default = "default_allow_fallback")]
     pub allow_gpu_fallback: bool,
@@ -276,10 +926,82 @@ pub struct GuardrailsConfig {
 
 // Default value functions
 
+const fn default_alert_cooldown_minutes() -> u64 {
+    60
+}
+
+const fn default_container_disk_gb() -> u32 {
+    20
+}
+
+const fn default_idle_timeout_secs() -> u32 {
+    5
+}
+
+const fn default_workers_max() -> u32 {
+    3
+}
+
+const fn default_keep_snapshots() -> usize {
+    10
+}
+
 const fn default_gpu_count() -> u32 {
     1
 }
 
+const fn default_replicas() -> u32 {
+    1
+}
+
+const fn default_autoscale_min() -> u32 {
+    1
+}
+
+const fn default_scale_up_cooldown() -> u64 {
+    60
+}
+
+const fn default_scale_down_cooldown() -> u64 {
+    300
+}
+
+fn default_metrics_path() -> String {
+    String::from("/metrics")
+}
+
+const fn default_drain_timeout_secs() -> u64 {
+    120
+}
+
+const fn default_lb_listen_port() -> u16 {
+    8080
+}
+
+const fn default_download_retries() -> u32 {
+    2
+}
+
+const fn default_parallel_downloads() -> u32 {
+    2
+}
+
+fn default_smoke_prompt() -> String {
+    String::from("Hello")
+}
+
+const fn default_smoke_max_tokens() -> u32 {
+    8
+}
+
+const fn default_smoke_timeout() -> u64 {
+    600
+}
+
+const fn default_enabled() -> bool {
+    true
+}
+
 const fn default_persistent() -> bool {
     true
 }
@@ -300,6 +1022,26 @@ const fn default_allow_fallback() -> bool {
     false
 }
 
+/// Key fragments marking an env var as secret.
+const SECRET_ENV_MARKERS: &[&str] = &["TOKEN", "SECRET", "PASSWORD", "KEY"];
+
+/// Returns true if an env var name looks like it holds a secret.
+#[must_use]
+pub fn is_secret_env_key(key: &str) -> bool {
+    let key = key.to_ascii_uppercase();
+    SECRET_ENV_MARKERS.iter().any(|marker| key.contains(marker))
+}
+
+/// Quotes a word for a POSIX shell if it contains anything beyond safe characters.
+fn shell_quote(word: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "_-./=:,@%+".contains(c);
+    if !word.is_empty() && word.chars().all(safe) {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', "'\\''"))
+    }
+}
+
 fn default_environment() -> String {
     String::from("dev")
 }
@@ -334,7 +1076,9 @@ impl PortConfig {
     pub fn parse(s: &str) -> Result<Self, String> {
         let parts: Vec<&str> = s.split('/').collect();
         if parts.len() != 2 {
-            return Err(format!("Invalid port format: {s}. Expected format: PORT/PROTOCOL"));
+            return Err(format!(
+                "Invalid port format: {s}. Expected format: PORT/PROTOCOL"
+            ));
         }
 
         let port = parts[0]
@@ -346,7 +1090,11 @@ impl PortConfig {
             "http" => PortProtocol::Http,
             "https" => PortProtocol::Https,
             "udp" => PortProtocol::Udp,
-            other => return Err(format!("Invalid protocol: {other}. Expected: tcp, http, https, or udp")),
+            other => {
+                return Err(format!(
+                    "Invalid protocol: {other}. Expected: tcp, http, https, or udp"
+                ));
+            }
         };
 
         Ok(Self {
@@ -374,10 +1122,50 @@ impl DeployConfig {
         format!("{}-{}", self.project.name, self.project.environment)
     }
 
-    /// Returns the total number of GPUs requested across all pods.
+    /// Returns the total number of GPUs requested across all pods and replicas.
     #[must_use]
     pub fn total_gpus(&self) -> u32 {
-        self.pods.iter().map(|p| p.gpu.count).sum()
+        self.pods.iter().map(|p| p.gpu.count * p.replicas).sum()
+    }
+
+    /// Returns the configured serverless endpoint named `name`.
+    #[must_use]
+    pub fn endpoint(&self, name: &str) -> Option<&EndpointConfig> {
+        self.endpoints.iter().find(|e| e.name == name)
+    }
+
+    /// Returns the configured template named `name`.
+    #[must_use]
+    pub fn template(&self, name: &str) -> Option<&TemplateConfig> {
+        self.templates.iter().find(|t| t.name == name)
+    }
+
+    /// Fills in what pods leave out from the templates they are deployed from.
+    ///
+    /// A pod inherits its template's image when it sets none, its ports when
+    /// it lists none, and each template env var it does not set itself.
+    pub fn apply_templates(&mut self) {
+        for pod in &mut self.pods {
+            let Some(template) = pod
+                .template
+                .as_deref()
+                .and_then(|name| self.templates.iter().find(|t| t.name == name))
+            else {
+                continue;
+            };
+            if pod.runtime.image.is_empty() {
+                pod.runtime.image.clone_from(&template.image);
+            }
+            if pod.ports.is_empty() {
+                pod.ports.clone_from(&template.ports);
+            }
+            for (key, value) in &template.env {
+                pod.runtime
+                    .env
+                    .entry(key.clone())
+                    .or_insert_with(|| value.clone());
+            }
+        }
     }
 
     /// Returns pod names.
@@ -385,6 +1173,65 @@ impl DeployConfig {
     pub fn pod_names(&self) -> Vec<&str> {
         self.pods.iter().map(|p| p.name.as_str()).collect()
     }
+
+    /// Returns the pod an instance name (`llm` or replica `llm-2`) belongs to.
+    #[must_use]
+    pub fn pod_for_instance(&self, name: &str) -> Option<&PodConfig> {
+        self.pods.iter().find(|p| {
+            p.name == name
+                || (p.is_replicated()
+                    && name
+                        .strip_prefix(p.name.as_str())
+                        .and_then(|rest| rest.strip_prefix('-'))
+                        .is_some_and(|index| index.parse::<u32>().is_ok()))
+        })
+    }
+}
+
+impl LoadBalancerFormat {
+    /// Returns the conventional file extension for this format.
+    #[must_use]
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Nginx => "conf",
+            Self::Caddy => "caddy",
+            Self::Haproxy => "cfg",
+        }
+    }
+}
+
+impl ModelConfig {
+    /// Returns the name the model is served under by an OpenAI-compatible engine.
+    ///
+    /// `None` if the model is not loaded by a serving engine (vLLM, TGI, Ollama).
+    #[must_use]
+    pub fn served_model_name(&self) -> Option<String> {
+        let load = self.load.as_ref()?;
+        match load.engine.to_lowercase().as_str() {
+            "vllm" => Some(
+                load.options
+                    .get("served-model-name")
+                    .and_then(serde_json::Value::as_str)
+                    .map_or_else(
+                        || self.repo.clone().unwrap_or_else(|| self.id.clone()),
+                        String::from,
+                    ),
+            ),
+            "tgi" | "text-generation-inference" => {
+                Some(self.repo.clone().unwrap_or_else(|| self.id.clone()))
+            }
+            "ollama" => Some(self.id.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl AutoscaleConfig {
+    /// Returns true if this policy needs request metrics scraped from the engine.
+    #[must_use]
+    pub const fn uses_request_metrics(&self) -> bool {
+        self.target_concurrent_requests.is_some() || self.idle_timeout_secs.is_some()
+    }
 }
 
 impl PodConfig {
@@ -394,6 +1241,156 @@ impl PodConfig {
         format!("{}-{}-{}", project.name, project.environment, self.name)
     }
 
+    /// Returns the replacement strategy in effect for this pod.
+    #[must_use]
+    pub const fn replace_strategy(&self, project: &ProjectConfig) -> ReplaceStrategy {
+        match self.replace_strategy {
+            Some(strategy) => strategy,
+            None => project.replace_strategy,
+        }
+    }
+
+    /// Returns the data centers to create this pod in, in order of preference.
+    ///
+    /// The pod's region (or the project's) comes first, then
+    /// `project.region_fallback`. Empty when no region is set.
+    #[must_use]
+    pub fn regions<'a>(&'a self, project: &'a ProjectConfig) -> Vec<&'a str> {
+        let Some(region) = self.region.as_deref().or(project.region.as_deref()) else {
+            return vec![];
+        };
+        let mut regions = vec![region];
+        for fallback in &project.region_fallback {
+            if !regions.contains(&fallback.as_str()) {
+                regions.push(fallback);
+            }
+        }
+        regions
+    }
+
+    /// Returns the SSH keys for this pod: its own, or the project's.
+    #[must_use]
+    pub fn ssh_config<'a>(&'a self, project: &'a ProjectConfig) -> Option<&'a SshConfig> {
+        self.ssh.as_ref().or(project.ssh.as_ref())
+    }
+
+    /// Returns true if this pod runs as a set of replicas.
+    ///
+    /// A pod with `replicas: 1` and no autoscaling is a single plain pod.
+    #[must_use]
+    pub const fn is_replicated(&self) -> bool {
+        self.replicas > 1 || self.autoscale.is_some()
+    }
+
+    /// Returns the instance name of the replica at `index`.
+    ///
+    /// The first replica keeps the pod's plain name, so scaling a single pod
+    /// out adds `<name>-1`, `<name>-2`, ... next to it instead of replacing it.
+    #[must_use]
+    pub fn replica_name(&self, index: u32) -> String {
+        if index == 0 {
+            self.name.clone()
+        } else {
+            format!("{}-{index}", self.name)
+        }
+    }
+
+    /// Clamps a replica count into the autoscaling bounds (if any).
+    #[must_use]
+    pub fn clamp_replicas(&self, count: u32) -> u32 {
+        self.autoscale
+            .as_ref()
+            .map_or(count, |a| count.clamp(a.min, a.max.max(a.min)))
+    }
+
+    /// Expands this pod into one configuration per replica instance.
+    ///
+    /// Each instance carries its own name and no replica settings, so it can be
+    /// hashed, diffed and provisioned like a standalone pod.
+    #[must_use]
+    pub fn instances(&self, count: u32) -> Vec<Self> {
+        if !self.is_replicated() {
+            return vec![self.clone()];
+        }
+
+        (0..count)
+            .map(|i| {
+                let mut instance = self.clone();
+                instance.name = self.replica_name(i);
+                instance.replicas = 1;
+                instance.autoscale = None;
+                instance
+            })
+            .collect()
+    }
+
+    /// Resolves `${gpu.count}` and `${pod.name}` in a runtime value.
+    ///
+    /// Other `${...}` references are left untouched.
+    #[must_use]
+    pub fn interpolate(&self, value: &str) -> String {
+        value
+            .replace("${gpu.count}", &self.gpu.count.to_string())
+            .replace("${pod.name}", &self.name)
+    }
+
+    /// Returns the runtime env with placeholders resolved.
+    #[must_use]
+    pub fn resolved_env(&self) -> HashMap<String, String> {
+        self.runtime
+            .env
+            .iter()
+            .map(|(key, value)| (key.clone(), self.interpolate(value)))
+            .collect()
+    }
+
+    /// Returns the container start command (`command` followed by `args`),
+    /// resolved and shell-quoted, or `None` if neither is set.
+    #[must_use]
+    pub fn docker_args(&self) -> Option<String> {
+        let words: Vec<String> = self
+            .runtime
+            .command
+            .iter()
+            .chain(&self.runtime.args)
+            .flatten()
+            .map(|word| shell_quote(&self.interpolate(word)))
+            .collect();
+
+        (!words.is_empty()).then(|| words.join(" "))
+    }
+
+    /// Returns the instance name of the warm pool clone at `index`.
+    #[must_use]
+    pub fn warm_name(&self, index: u32) -> String {
+        format!("{}-warm-{index}", self.name)
+    }
+
+    /// Expands this pod's warm pool into one configuration per stopped clone.
+    #[must_use]
+    pub fn warm_instances(&self) -> Vec<Self> {
+        let size = self.warm_pool.as_ref().map_or(0, |w| w.size);
+        (0..size)
+            .map(|i| {
+                let mut instance = self.clone();
+                instance.name = self.warm_name(i);
+                instance.replicas = 1;
+                instance.autoscale = None;
+                instance.warm_pool = None;
+                instance
+            })
+            .collect()
+    }
+
+    /// Returns true if the pod exposes SSH (`22/tcp`), which commands run on
+    /// the pod during setup go through.
+    #[must_use]
+    pub fn exposes_ssh(&self) -> bool {
+        self.ports
+            .iter()
+            .any(|p| p.port == 22 && p.protocol == PortProtocol::Tcp)
+    }
+
     /// Returns HTTP ports configured for this pod.
     #[must_use]
     pub fn http_ports(&self) -> Vec<u16> {
@@ -403,12 +1400,44 @@ impl PodConfig {
             .map(|p| p.port)
             .collect()
     }
+
+    /// Returns the drain settings with the port resolved, if draining is configured.
+    #[must_use]
+    pub fn resolved_drain(&self) -> Option<DrainConfig> {
+        let mut drain = self.drain.clone()?;
+        drain.port = drain.port.or_else(|| self.http_ports().first().copied());
+        Some(drain)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_served_model_name() {
+        let yaml = r#"
+id: llama
+provider: huggingface
+repo: "meta-llama/Meta-Llama-3-8B-Instruct"
+load:
+  engine: vllm
+  options:
+    served-model-name: "llama-3"
+"#;
+        let mut model: ModelConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(model.served_model_name().as_deref(), Some("llama-3"));
+
+        model.load.as_mut().unwrap().options.clear();
+        assert_eq!(
+            model.served_model_name().as_deref(),
+            Some("meta-llama/Meta-Llama-3-8B-Instruct")
+        );
+
+        model.load.as_mut().unwrap().engine = String::from("transformers");
+        assert!(model.served_model_name().is_none());
+    }
+
     #[test]
     fn test_port_config_parse() {
         let port = PortConfig::parse("8000/http");
@@ -432,4 +1461,109 @@ mod tests {
         let port = PortConfig::parse("invalid");
         assert!(port.is_err());
     }
+
+    #[test]
+    fn test_pod_instances() {
+        let yaml = r#"
+name: inference
+gpu:
+  type: "NVIDIA A40"
+runtime:
+  image: "vllm/vllm-openai:v0.6.0"
+"#;
+        let mut pod: PodConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(pod.replicas, 1);
+        assert!(!pod.is_replicated());
+        assert_eq!(pod.instances(1)[0].name, "inference");
+
+        pod.replicas = 2;
+        let names: Vec<String> = pod.instances(3).into_iter().map(|p| p.name).collect();
+        assert_eq!(names, vec!["inference", "inference-1", "inference-2"]);
+    }
+
+    #[test]
+    fn test_pod_regions() {
+        let mut project: ProjectConfig =
+            serde_yaml::from_str("name: proj\nregion_fallback: [EU-SE-1, US-TX-3]").unwrap();
+        let mut pod: PodConfig = serde_yaml::from_str("name: web\ngpu:\n  type: A40").unwrap();
+        // Fallbacks only apply once a region is chosen
+        assert!(pod.regions(&project).is_empty());
+
+        project.region = Some(String::from("EU-RO-1"));
+        assert_eq!(pod.regions(&project), ["EU-RO-1", "EU-SE-1", "US-TX-3"]);
+
+        pod.region = Some(String::from("US-TX-3"));
+        assert_eq!(pod.regions(&project), ["US-TX-3", "EU-SE-1"]);
+    }
+
+    #[test]
+    fn test_download_mirror_endpoint() {
+        let model: ModelConfig =
+            serde_yaml::from_str("id: llama\nprovider: huggingface\nrepo: meta-llama/Llama-3.1-8B\ndownload:\n  mirror: hf-mirror.com").unwrap();
+        let download = model.download.unwrap();
+        assert_eq!(download.retries, 2);
+        assert_eq!(
+            download.mirror_endpoint().as_deref(),
+            Some("https://hf-mirror.com")
+        );
+
+        let custom = DownloadConfig {
+            retries: 0,
+            mirror: Some(String::from("http://mirror.internal:8080/")),
+        };
+        assert_eq!(
+            custom.mirror_endpoint().as_deref(),
+            Some("http://mirror.internal:8080")
+        );
+        assert_eq!(DownloadConfig::default().mirror_endpoint(), None);
+    }
+
+    #[test]
+    fn test_runtime_interpolation() {
+        let yaml = r#"
+name: inference
+gpu:
+  type: "NVIDIA A40"
+  count: 2
+runtime:
+  image: "vllm/vllm-openai:v0.6.0"
+  entrypoint: ["python", "-m", "vllm.entrypoints.openai.api_server"]
+  args: ["--tensor-parallel-size", "${gpu.count}", "--served-model-name", "${pod.name} chat"]
+  env:
+    SHARDS: "${gpu.count}"
+    TOKEN: "${HF_TOKEN}"
+"#;
+        let pod: PodConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(
+            pod.docker_args().as_deref(),
+            Some(
+                "python -m vllm.entrypoints.openai.api_server --tensor-parallel-size 2 --served-model-name 'inference chat'"
+            )
+        );
+
+        let env = pod.resolved_env();
+        assert_eq!(env["SHARDS"], "2");
+        assert_eq!(env["TOKEN"], "${HF_TOKEN}");
+    }
+
+    #[test]
+    fn test_autoscale_defaults_and_clamp() {
+        let yaml = r#"
+name: inference
+gpu:
+  type: "NVIDIA A40"
+runtime:
+  image: "vllm/vllm-openai:v0.6.0"
+autoscale:
+  max: 4
+  target_concurrent_requests: 8
+"#;
+        let pod: PodConfig = serde_yaml::from_str(yaml).unwrap();
+        let autoscale = pod.autoscale.as_ref().unwrap();
+        assert_eq!(autoscale.min, 1);
+        assert_eq!(autoscale.metrics_path, "/metrics");
+        assert!(pod.is_replicated());
+        assert_eq!(pod.clamp_replicas(0), 1);
+        assert_eq!(pod.clamp_replicas(9), 4);
+    }
 }
@@ -7,7 +7,14 @@ use crate::error::{ConfigError, HalldyllError, Result};
 use std::collections::HashSet;
 use tracing::debug;
 
-use super::spec::{DeployConfig, PodConfig, StateBackend, VolumeConfig};
+use super::engines::{
+    engine_options, explicit_parallelism, managed_option, parallelism_option, pipeline_parallelism,
+    suggest_option,
+};
+use super::spec::{
+    CloudType, DeployConfig, ModelConfig, PodConfig, PricingMode, StateBackend, StateCompression,
+    VolumeConfig,
+};
 
 /// Validator for deployment configurations.
 #[derive(Debug, Default)]
@@ -81,12 +88,13 @@ impl ConfigValidator {
     ///
     /// Returns an error if validation fails.
     pub fn validate(&self, config: &DeployConfig) -> Result<ValidationResult> {
-        let mut result = ValidationResult::default();
+        if let Some(cycle) = dependency_cycle(&config.pods) {
+            return Err(HalldyllError::Config(ConfigError::CircularDependency {
+                cycle,
+            }));
+        }
 
-        Self::validate_project(&config.project, &mut result);
-        Self::validate_state(&config.state, &mut result);
-        self.validate_pods(&config.pods, &mut result);
-        Self::validate_guardrails(config, &mut result);
+        let result = self.check(config);
 
         if result.errors.is_empty() {
             debug!("Configuration validation passed");
@@ -100,11 +108,27 @@ impl ConfigValidator {
         }
     }
 
+    /// Collects every validation error and warning without failing.
+    #[must_use]
+    pub fn check(&self, config: &DeployConfig) -> ValidationResult {
+        let mut result = ValidationResult::default();
+
+        Self::validate_project(&config.project, &mut result);
+        Self::validate_state(&config.state, &mut result);
+        self.validate_pods(&config.pods, &mut result);
+        Self::validate_dependencies(&config.pods, &mut result);
+        Self::validate_guardrails(config, &mut result);
+        Self::validate_price_ceilings(config, &mut result);
+        Self::validate_diff(config, &mut result);
+        Self::validate_alerts(config, &mut result);
+        Self::validate_endpoints(config, &mut result);
+        Self::validate_templates(config, &mut result);
+
+        result
+    }
+
     /// Validates project configuration.
-    fn validate_project(
-        project: &super::spec::ProjectConfig,
-        result: &mut ValidationResult,
-    ) {
+    fn validate_project(project: &super::spec::ProjectConfig, result: &mut ValidationResult) {
         // Project name must be valid
         if project.name.is_empty() {
             result.errors.push(ValidationError {
@@ -128,6 +152,42 @@ impl ConfigValidator {
                 message: String::from("Environment cannot be empty"),
             });
         }
+
+        if let Some(namespace) = &project.tag_namespace
+            && (namespace.is_empty()
+                || namespace.ends_with('/')
+                || namespace.contains(char::is_whitespace))
+        {
+            result.errors.push(ValidationError {
+                field: String::from("project.tag_namespace"),
+                message: format!("Tag namespace '{namespace}' must be non-empty, without whitespace or a trailing '/'"),
+            });
+        }
+
+        if project.region_fallback.iter().any(String::is_empty) {
+            result.errors.push(ValidationError {
+                field: String::from("project.region_fallback"),
+                message: String::from("Data center IDs cannot be empty"),
+            });
+        }
+
+        if let Some(failover) = &project.failover {
+            if failover.data_centers.is_empty() {
+                result.errors.push(ValidationError {
+                    field: String::from("project.failover.data_centers"),
+                    message: String::from("Failover needs at least one data center"),
+                });
+            }
+            if project
+                .region
+                .as_ref()
+                .is_some_and(|region| failover.data_centers.contains(region))
+            {
+                result.warnings.push(String::from(
+                    "project.failover.data_centers includes the primary region; a data center outage would take out both sides",
+                ));
+            }
+        }
     }
 
     /// Validates state configuration.
@@ -140,9 +200,57 @@ impl ConfigValidator {
                         message: String::from("S3 bucket name is required when using S3 backend"),
                     });
                 }
+                if let Some(endpoint) = &state.endpoint_url
+                    && !(endpoint.starts_with("http://") || endpoint.starts_with("https://"))
+                {
+                    result.errors.push(ValidationError {
+                        field: String::from("state.endpoint_url"),
+                        message: format!(
+                            "S3 endpoint must be an http:// or https:// URL, got '{endpoint}'"
+                        ),
+                    });
+                }
+            }
+            StateBackend::Http => {
+                match state.address.as_deref() {
+                    None | Some("") => result.errors.push(ValidationError {
+                        field: String::from("state.address"),
+                        message: String::from("State address is required when using HTTP backend"),
+                    }),
+                    Some(_) => {}
+                }
+                for (field, url) in [
+                    ("state.address", &state.address),
+                    ("state.lock_address", &state.lock_address),
+                    ("state.unlock_address", &state.unlock_address),
+                ] {
+                    if let Some(url) = url
+                        && !url.is_empty()
+                        && !(url.starts_with("http://") || url.starts_with("https://"))
+                    {
+                        result.errors.push(ValidationError {
+                            field: String::from(field),
+                            message: format!(
+                                "State URL must be an http:// or https:// URL, got '{url}'"
+                            ),
+                        });
+                    }
+                }
+                if state.unlock_address.is_some() && state.lock_address.is_none() {
+                    result.warnings.push(String::from(
+                        "state.unlock_address has no effect without state.lock_address",
+                    ));
+                }
             }
             StateBackend::Local => {
-                // Local backend is always valid
+                if state.endpoint_url.is_some()
+                    || state.force_path_style
+                    || state.compression != StateCompression::None
+                {
+                    result.warnings.push(String::from(
+                        "state.endpoint_url, state.force_path_style and state.compression only apply to the s3 backend",
+                    ));
+                }
             }
         }
     }
@@ -150,7 +258,9 @@ impl ConfigValidator {
     /// Validates all pod configurations.
     fn validate_pods(&self, pods: &[PodConfig], result: &mut ValidationResult) {
         if pods.is_empty() {
-            result.warnings.push(String::from("No pods defined in configuration"));
+            result
+                .warnings
+                .push(String::from("No pods defined in configuration"));
             return;
         }
 
@@ -183,6 +293,8 @@ impl ConfigValidator {
 
             // Validate GPU
             self.validate_gpu(&pod.gpu, &prefix, result);
+            Self::validate_pricing(pod, &prefix, result);
+            Self::validate_region(pod, &prefix, result);
 
             // Validate ports
             Self::validate_ports(&pod.ports, &prefix, &mut all_ports, result);
@@ -195,6 +307,303 @@ impl ConfigValidator {
 
             // Validate models
             Self::validate_models(&pod.models, &prefix, result);
+            Self::validate_exec(pod, &prefix, result);
+            Self::validate_model_downloads(pod, &prefix, result);
+
+            // Validate replicas and autoscaling
+            Self::validate_scaling(pod, &prefix, result);
+
+            // Validate load balancer manifest
+            Self::validate_load_balancer(pod, &prefix, result);
+
+            // Validate health check
+            Self::validate_health_check(pod, &prefix, result);
+
+            // Validate tensor parallelism against the GPU count
+            Self::validate_parallelism(pod, &prefix, result);
+
+            // Validate engine drain
+            Self::validate_drain(pod, &prefix, result);
+        }
+    }
+
+    /// Validates `depends_on` references and rejects dependency cycles.
+    fn validate_dependencies(pods: &[PodConfig], result: &mut ValidationResult) {
+        for (i, pod) in pods.iter().enumerate() {
+            for dependency in &pod.depends_on {
+                let message = if *dependency == pod.name {
+                    format!("Pod '{}' cannot depend on itself", pod.name)
+                } else if let Some(target) = pods.iter().find(|p| p.name == *dependency) {
+                    if pod.enabled && !target.enabled {
+                        result.warnings.push(format!(
+                            "Pod '{}' depends on disabled pod '{dependency}', which will not be running",
+                            pod.name
+                        ));
+                    }
+                    continue;
+                } else {
+                    format!("Pod '{}' depends on unknown pod '{dependency}'", pod.name)
+                };
+                result.errors.push(ValidationError {
+                    field: format!("pods[{i}].depends_on"),
+                    message,
+                });
+            }
+        }
+
+        if let Some(cycle) = dependency_cycle(pods) {
+            result.errors.push(ValidationError {
+                field: String::from("pods.depends_on"),
+                message: ConfigError::CircularDependency { cycle }.to_string(),
+            });
+        }
+    }
+
+    /// Validates engine drain settings.
+    fn validate_drain(pod: &PodConfig, prefix: &str, result: &mut ValidationResult) {
+        let Some(drain) = pod.resolved_drain() else {
+            return;
+        };
+
+        match drain.port {
+            None => result.errors.push(ValidationError {
+                field: format!("{prefix}.drain.port"),
+                message: String::from("Draining needs an HTTP port to reach the engine"),
+            }),
+            Some(port) if !pod.http_ports().contains(&port) => {
+                result.errors.push(ValidationError {
+                    field: format!("{prefix}.drain.port"),
+                    message: format!("Port {port} is not an HTTP port exposed by the pod"),
+                });
+            }
+            Some(_) => {}
+        }
+
+        if let Some(endpoint) = &drain.endpoint
+            && !endpoint.starts_with('/')
+        {
+            result.errors.push(ValidationError {
+                field: format!("{prefix}.drain.endpoint"),
+                message: format!("Drain endpoint '{endpoint}' must be a path starting with '/'"),
+            });
+        }
+    }
+
+    /// Validates explicit tensor/pipeline parallelism against the pod's GPU count.
+    fn validate_parallelism(pod: &PodConfig, prefix: &str, result: &mut ValidationResult) {
+        for (i, model) in pod.models.iter().enumerate() {
+            let Some(load) = &model.load else {
+                continue;
+            };
+            let (Some(option), Some(size)) =
+                (parallelism_option(&load.engine), explicit_parallelism(load))
+            else {
+                continue;
+            };
+
+            let field = format!("{prefix}.models[{i}].load.options.{option}");
+            let pipeline = pipeline_parallelism(load);
+            let gpus = u64::from(pod.gpu.count);
+            let required = size.saturating_mul(pipeline);
+
+            if size == 0 {
+                result.errors.push(ValidationError {
+                    field,
+                    message: String::from("Parallel size must be at least 1"),
+                });
+            } else if required > gpus {
+                result.errors.push(ValidationError {
+                    field,
+                    message: format!("Model needs {required} GPUs but the pod has {gpus}"),
+                });
+            } else if required < gpus {
+                result.warnings.push(format!(
+                    "{field}: Model uses {required} of the pod's {gpus} GPUs"
+                ));
+            }
+        }
+    }
+
+    /// Validates the health check probe settings.
+    fn validate_health_check(pod: &PodConfig, prefix: &str, result: &mut ValidationResult) {
+        let Some(check) = &pod.health_check else {
+            return;
+        };
+
+        if !pod.http_ports().contains(&check.port) {
+            result.warnings.push(format!(
+                "{prefix}.health_check.port: Port {} is not an HTTP port exposed by the pod",
+                check.port
+            ));
+        }
+
+        if let Some(status) = check.expect_status
+            && !(100..=599).contains(&status)
+        {
+            result.errors.push(ValidationError {
+                field: format!("{prefix}.health_check.expect_status"),
+                message: format!("{status} is not a valid HTTP status code"),
+            });
+        }
+
+        for (name, value) in &check.headers {
+            if name.is_empty()
+                || !name
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+            {
+                result.errors.push(ValidationError {
+                    field: format!("{prefix}.health_check.headers"),
+                    message: format!("Invalid header name '{name}'"),
+                });
+            }
+            if name.eq_ignore_ascii_case("authorization") && !value.contains("${") {
+                result.warnings.push(format!(
+                    "{prefix}.health_check.headers.{name}: Credential is inlined; reference an environment variable with ${{VAR}} instead"
+                ));
+            }
+        }
+    }
+
+    /// Validates the load balancer manifest configuration.
+    fn validate_load_balancer(pod: &PodConfig, prefix: &str, result: &mut ValidationResult) {
+        let Some(lb) = &pod.load_balancer else {
+            return;
+        };
+
+        match lb.port.or_else(|| pod.http_ports().first().copied()) {
+            None => result.errors.push(ValidationError {
+                field: format!("{prefix}.load_balancer.port"),
+                message: String::from("Load balancer needs an HTTP port to route to"),
+            }),
+            Some(port) if !pod.http_ports().contains(&port) => {
+                result.errors.push(ValidationError {
+                    field: format!("{prefix}.load_balancer.port"),
+                    message: format!("Port {port} is not an HTTP port exposed by the pod"),
+                });
+            }
+            Some(_) => {}
+        }
+
+        if !pod.is_replicated() {
+            result.warnings.push(format!(
+                "{prefix}.load_balancer: Pod has a single replica; the manifest will list one upstream"
+            ));
+        }
+    }
+
+    /// Validates replica count and autoscaling policy.
+    fn validate_scaling(pod: &PodConfig, prefix: &str, result: &mut ValidationResult) {
+        if pod.replicas == 0 {
+            result.errors.push(ValidationError {
+                field: format!("{prefix}.replicas"),
+                message: String::from("Replica count must be at least 1"),
+            });
+        }
+
+        if pod.warm_pool.as_ref().is_some_and(|w| w.size == 0) {
+            result.errors.push(ValidationError {
+                field: format!("{prefix}.warm_pool.size"),
+                message: String::from("Warm pool size must be at least 1"),
+            });
+        }
+
+        let Some(autoscale) = &pod.autoscale else {
+            return;
+        };
+
+        if autoscale.min == 0 {
+            result.errors.push(ValidationError {
+                field: format!("{prefix}.autoscale.min"),
+                message: String::from("Autoscale minimum must be at least 1"),
+            });
+        }
+
+        if autoscale.max < autoscale.min {
+            result.errors.push(ValidationError {
+                field: format!("{prefix}.autoscale.max"),
+                message: format!(
+                    "Autoscale maximum ({}) must be greater than or equal to minimum ({})",
+                    autoscale.max, autoscale.min
+                ),
+            });
+        }
+
+        if autoscale.target_concurrent_requests.is_none()
+            && autoscale.target_gpu_util.is_none()
+            && autoscale.idle_timeout_secs.is_none()
+        {
+            result.errors.push(ValidationError {
+                field: format!("{prefix}.autoscale"),
+                message: String::from(
+                    "Autoscale requires target_concurrent_requests, target_gpu_util or idle_timeout_secs",
+                ),
+            });
+        }
+
+        if autoscale.idle_timeout_secs == Some(0) {
+            result.errors.push(ValidationError {
+                field: format!("{prefix}.autoscale.idle_timeout_secs"),
+                message: String::from("Idle timeout must be at least 1 second"),
+            });
+        }
+
+        if autoscale.target_concurrent_requests == Some(0) {
+            result.errors.push(ValidationError {
+                field: format!("{prefix}.autoscale.target_concurrent_requests"),
+                message: String::from("Target concurrent requests must be at least 1"),
+            });
+        }
+
+        if let Some(util) = autoscale.target_gpu_util
+            && !(1..=100).contains(&util)
+        {
+            result.errors.push(ValidationError {
+                field: format!("{prefix}.autoscale.target_gpu_util"),
+                message: format!("Target GPU utilization must be between 1 and 100, got {util}"),
+            });
+        }
+
+        if !(autoscale.min..=autoscale.max).contains(&pod.replicas) {
+            result.warnings.push(format!(
+                "{prefix}.replicas: {} is outside autoscale bounds [{}, {}] and will be clamped",
+                pod.replicas, autoscale.min, autoscale.max
+            ));
+        }
+
+        if autoscale.uses_request_metrics() {
+            let metrics_port = autoscale
+                .metrics_port
+                .or_else(|| pod.http_ports().first().copied());
+            match metrics_port {
+                None => result.errors.push(ValidationError {
+                    field: format!("{prefix}.autoscale.metrics_port"),
+                    message: String::from(
+                        "Request-based autoscaling and idle stop need an HTTP port to scrape metrics from",
+                    ),
+                }),
+                Some(port) if !pod.ports.iter().any(|p| p.port == port) => {
+                    result.warnings.push(format!(
+                        "{prefix}.autoscale.metrics_port: Port {port} is not exposed by the pod"
+                    ));
+                }
+                Some(_) => {}
+            }
+        }
+    }
+
+    /// Warns about price ceilings that have no effect.
+    fn validate_price_ceilings(config: &DeployConfig, result: &mut ValidationResult) {
+        if config.project.cloud_type != CloudType::Secure {
+            return;
+        }
+
+        for (i, pod) in config.pods.iter().enumerate() {
+            if pod.gpu.max_price_per_hour.is_some() {
+                result.warnings.push(format!(
+                    "pods[{i}].gpu.max_price_per_hour: Only applies to COMMUNITY cloud, ignored for SECURE"
+                ));
+            }
         }
     }
 
@@ -219,13 +628,38 @@ impl ConfigValidator {
             ));
         }
 
-        if !self.known_gpu_types.contains(&gpu.gpu_type) {
+        if gpu.is_auto() {
+            if gpu.min_vram_gb.is_none() {
+                result.errors.push(ValidationError {
+                    field: format!("{prefix}.gpu.min_vram_gb"),
+                    message: format!(
+                        "`type: {}` needs min_vram_gb to pick a GPU large enough",
+                        gpu.gpu_type
+                    ),
+                });
+            }
+            if !gpu.fallback.is_empty() {
+                result.warnings.push(format!(
+                    "{prefix}.gpu.fallback: Ignored with `type: {}`, which considers every GPU type",
+                    gpu.gpu_type
+                ));
+            }
+        } else if !self.known_gpu_types.contains(&gpu.gpu_type) {
             result.warnings.push(format!(
                 "{prefix}.gpu.type: Unknown GPU type '{}'. This may fail if not available.",
                 gpu.gpu_type
             ));
         }
 
+        if let Some(max_price) = gpu.max_price_per_hour
+            && max_price <= 0.0
+        {
+            result.errors.push(ValidationError {
+                field: format!("{prefix}.gpu.max_price_per_hour"),
+                message: String::from("Price ceiling must be greater than 0"),
+            });
+        }
+
         // Validate fallback GPU types
         for (i, fallback) in gpu.fallback.iter().enumerate() {
             if !self.known_gpu_types.contains(fallback) {
@@ -236,6 +670,52 @@ impl ConfigValidator {
         }
     }
 
+    /// Validates spot pricing.
+    fn validate_pricing(pod: &PodConfig, prefix: &str, result: &mut ValidationResult) {
+        let Some(pricing) = &pod.pricing else {
+            return;
+        };
+        match (pricing.mode, pricing.max_bid) {
+            (PricingMode::Spot, None) => result.errors.push(ValidationError {
+                field: format!("{prefix}.pricing.max_bid"),
+                message: String::from("Spot pricing needs a max_bid per GPU"),
+            }),
+            (_, Some(bid)) if bid <= 0.0 => result.errors.push(ValidationError {
+                field: format!("{prefix}.pricing.max_bid"),
+                message: String::from("Bid must be greater than 0"),
+            }),
+            (PricingMode::OnDemand, Some(_)) => {
+                result.warnings.push(format!(
+                    "{prefix}.pricing.max_bid: Only applies to spot pricing, ignored"
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    /// Validates a pod's region override.
+    fn validate_region(pod: &PodConfig, prefix: &str, result: &mut ValidationResult) {
+        let Some(region) = &pod.region else {
+            return;
+        };
+        if region.is_empty() {
+            result.errors.push(ValidationError {
+                field: format!("{prefix}.region"),
+                message: String::from("Region cannot be empty"),
+            });
+        }
+        let pinned = pod
+            .volumes
+            .iter()
+            .filter_map(|v| v.data_center.as_ref())
+            .find(|dc| *dc != region);
+        if let Some(data_center) = pinned {
+            result.warnings.push(format!(
+                "{prefix}.region: The pod's network volume is in {data_center}, so the pod is created there instead"
+            ));
+        }
+    }
+
     /// Validates port configurations.
     fn validate_ports(
         ports: &[super::spec::PortConfig],
@@ -270,11 +750,7 @@ impl ConfigValidator {
     }
 
     /// Validates volume configurations.
-    fn validate_volumes(
-        volumes: &[VolumeConfig],
-        prefix: &str,
-        result: &mut ValidationResult,
-    ) {
+    fn validate_volumes(volumes: &[VolumeConfig], prefix: &str, result: &mut ValidationResult) {
         let mut seen_names = HashSet::new();
         let mut seen_mounts = HashSet::new();
 
@@ -306,6 +782,29 @@ impl ConfigValidator {
                     message: format!("Mount path must be absolute: {}", volume.mount),
                 });
             }
+
+            // A network volume is created with a fixed size
+            if volume.is_managed() && volume.size_gb.is_none_or(|size| size == 0) {
+                result.errors.push(ValidationError {
+                    field: format!("{prefix}.volumes[{i}].size_gb"),
+                    message: format!(
+                        "Volume '{}' is created as a network volume and needs a size_gb",
+                        volume.name
+                    ),
+                });
+            }
+        }
+
+        // RunPod mounts at most one network volume per pod
+        let network = volumes
+            .iter()
+            .filter(|v| v.network_volume_id.is_some() || v.is_managed())
+            .count();
+        if network > 1 {
+            result.errors.push(ValidationError {
+                field: format!("{prefix}.volumes"),
+                message: format!("A pod can mount only one network volume, found {network}"),
+            });
         }
     }
 
@@ -350,9 +849,7 @@ impl ConfigValidator {
             }
 
             // Validate HuggingFace models have a repo
-            if model.provider == super::spec::ModelProvider::Huggingface
-                && model.repo.is_none()
-            {
+            if model.provider == super::spec::ModelProvider::Huggingface && model.repo.is_none() {
                 result.errors.push(ValidationError {
                     field: format!("{prefix}.models[{i}].repo"),
                     message: format!(
@@ -374,6 +871,120 @@ impl ConfigValidator {
                     ),
                 });
             }
+
+            // Validate the download mirror
+            if let Some(download) = &model.download {
+                let mirror = download.mirror_endpoint();
+                if download.mirror.is_some()
+                    && mirror.is_none_or(|m| reqwest::Url::parse(&m).is_err())
+                {
+                    result.errors.push(ValidationError {
+                        field: format!("{prefix}.models[{i}].download.mirror"),
+                        message: format!("Model '{}' has an invalid download mirror", model.id),
+                    });
+                }
+                if model.provider == super::spec::ModelProvider::Custom {
+                    result.warnings.push(format!(
+                        "{prefix}.models[{i}].download: Ignored for custom models, which are not downloaded"
+                    ));
+                }
+            }
+
+            // Validate engine options against the engine's known flags
+            Self::validate_engine_options(model, &format!("{prefix}.models[{i}]"), result);
+        }
+    }
+
+    /// Validates the model download settings of a pod.
+    fn validate_model_downloads(pod: &PodConfig, prefix: &str, result: &mut ValidationResult) {
+        let Some(downloads) = &pod.model_downloads else {
+            return;
+        };
+        if downloads.parallel == 0 {
+            result.errors.push(ValidationError {
+                field: format!("{prefix}.model_downloads.parallel"),
+                message: String::from("At least one model must download at a time"),
+            });
+        }
+        if downloads.max_mb_per_sec == Some(0) {
+            result.errors.push(ValidationError {
+                field: format!("{prefix}.model_downloads.max_mb_per_sec"),
+                message: String::from("Bandwidth cap must be positive; leave it out for no cap"),
+            });
+        }
+    }
+
+    /// Warns when model setup would have no way to run commands on the pod.
+    fn validate_exec(pod: &PodConfig, prefix: &str, result: &mut ValidationResult) {
+        if !pod.models.is_empty() && !pod.exposes_ssh() {
+            result.warnings.push(format!(
+                "{prefix}.ports: Pod '{}' declares models but does not expose 22/tcp, so model setup \
+                 cannot run commands on it; add \"22/tcp\" to ports or use an image with exec support",
+                pod.name
+            ));
+        }
+        if pod.ssh.is_some() && !pod.exposes_ssh() {
+            result.warnings.push(format!(
+                "{prefix}.ssh: Pod '{}' declares SSH keys but does not expose 22/tcp",
+                pod.name
+            ));
+        }
+    }
+
+    /// Validates `load.options` against the known options of the engine.
+    fn validate_engine_options(model: &ModelConfig, prefix: &str, result: &mut ValidationResult) {
+        let Some(load) = &model.load else {
+            return;
+        };
+        if load.options.is_empty() {
+            return;
+        }
+
+        let Some(known) = engine_options(&load.engine) else {
+            result.warnings.push(format!(
+                "{prefix}.load.options: Options are ignored by the '{}' engine",
+                load.engine
+            ));
+            return;
+        };
+
+        let mut keys: Vec<&String> = load.options.keys().collect();
+        keys.sort();
+
+        for key in keys {
+            let field = format!("{prefix}.load.options.{key}");
+
+            if let Some(instead) = managed_option(key) {
+                result.errors.push(ValidationError {
+                    field,
+                    message: format!("'{key}' is set by halldyll; use {instead} instead"),
+                });
+                continue;
+            }
+
+            match known.iter().find(|o| o.name == key) {
+                Some(option) => {
+                    if let Some(problem) = option.kind.check(&load.options[key]) {
+                        result.errors.push(ValidationError {
+                            field,
+                            message: format!("Invalid value for '{key}': {problem}"),
+                        });
+                    }
+                }
+                None => match suggest_option(known, key) {
+                    Some(suggestion) => result.errors.push(ValidationError {
+                        field,
+                        message: format!(
+                            "Unknown {} option '{key}', did you mean '{suggestion}'?",
+                            load.engine
+                        ),
+                    }),
+                    None => result.warnings.push(format!(
+                        "{field}: Unknown {} option, passed through unchanged",
+                        load.engine
+                    )),
+                },
+            }
         }
     }
 
@@ -382,12 +993,22 @@ impl ConfigValidator {
         if let Some(guardrails) = &config.guardrails {
             // Validate max_hourly_cost
             if let Some(cost) = guardrails.max_hourly_cost
-                && cost <= 0.0 {
-                    result.errors.push(ValidationError {
-                        field: String::from("guardrails.max_hourly_cost"),
-                        message: String::from("Maximum hourly cost must be positive"),
-                    });
-                }
+                && cost <= 0.0
+            {
+                result.errors.push(ValidationError {
+                    field: String::from("guardrails.max_hourly_cost"),
+                    message: String::from("Maximum hourly cost must be positive"),
+                });
+            }
+
+            if let Some(hours) = guardrails.min_runway_hours
+                && hours <= 0.0
+            {
+                result.errors.push(ValidationError {
+                    field: String::from("guardrails.min_runway_hours"),
+                    message: String::from("Minimum runway must be positive"),
+                });
+            }
 
             // Validate max_gpus against actual pod requirements
             if let Some(max_gpus) = guardrails.max_gpus {
@@ -400,16 +1021,214 @@ impl ConfigValidator {
                         ),
                     });
                 }
+
+                let peak_gpus: u32 = config
+                    .pods
+                    .iter()
+                    .map(|p| p.gpu.count * p.autoscale.as_ref().map_or(p.replicas, |a| a.max))
+                    .sum();
+                if peak_gpus > max_gpus && total_gpus <= max_gpus {
+                    result.warnings.push(format!(
+                        "guardrails.max_gpus: Autoscaling may need up to {peak_gpus} GPUs; scale-ups past {max_gpus} will be blocked"
+                    ));
+                }
             }
 
             // Validate TTL
             if let Some(ttl) = guardrails.ttl_hours
-                && ttl == 0 {
-                    result.errors.push(ValidationError {
-                        field: String::from("guardrails.ttl_hours"),
-                        message: String::from("TTL must be at least 1 hour"),
-                    });
+                && ttl == 0
+            {
+                result.errors.push(ValidationError {
+                    field: String::from("guardrails.ttl_hours"),
+                    message: String::from("TTL must be at least 1 hour"),
+                });
+            }
+
+            if guardrails.stopped_retention_hours == Some(0) {
+                result.errors.push(ValidationError {
+                    field: String::from("guardrails.stopped_retention_hours"),
+                    message: String::from("Stopped pod retention must be at least 1 hour"),
+                });
+            }
+        }
+    }
+
+    /// Validates alert thresholds and webhooks.
+    fn validate_alerts(config: &DeployConfig, result: &mut ValidationResult) {
+        let Some(alerts) = &config.alerts else {
+            return;
+        };
+
+        if alerts.hourly_cost_above.is_some_and(|cost| cost <= 0.0) {
+            result.errors.push(ValidationError {
+                field: String::from("alerts.hourly_cost_above"),
+                message: String::from("Hourly cost threshold must be positive"),
+            });
+        }
+        for webhook in &alerts.webhooks {
+            if !webhook.starts_with("https://")
+                && !webhook.starts_with("http://")
+                && !webhook.starts_with("${")
+            {
+                result.errors.push(ValidationError {
+                    field: String::from("alerts.webhooks"),
+                    message: format!("Webhook '{webhook}' must be an http(s) URL"),
+                });
+            }
+        }
+        if alerts.hourly_cost_above.is_none()
+            && alerts.unhealthy_minutes.is_none()
+            && alerts.drift_minutes.is_none()
+        {
+            result.warnings.push(String::from(
+                "alerts: No thresholds are set, so no alerts will be raised",
+            ));
+        }
+    }
+
+    /// Validates serverless endpoints.
+    fn validate_endpoints(config: &DeployConfig, result: &mut ValidationResult) {
+        let mut seen_names = HashSet::new();
+
+        for (i, endpoint) in config.endpoints.iter().enumerate() {
+            let prefix = format!("endpoints[{i}]");
+            let mut error = |field: &str, message: String| {
+                result.errors.push(ValidationError {
+                    field: format!("{prefix}.{field}"),
+                    message,
+                });
+            };
+
+            if !is_valid_name(&endpoint.name) {
+                error(
+                    "name",
+                    format!(
+                        "Endpoint name '{}' is invalid. Must be lowercase alphanumeric with hyphens.",
+                        endpoint.name
+                    ),
+                );
+            } else if !seen_names.insert(&endpoint.name)
+                || config.pod_names().contains(&endpoint.name.as_str())
+            {
+                error(
+                    "name",
+                    format!("Duplicate pod or endpoint name: {}", endpoint.name),
+                );
+            }
+            match (&endpoint.template, endpoint.template_id.trim().is_empty()) {
+                (None, true) => error(
+                    "template_id",
+                    String::from("Template ID or template is required"),
+                ),
+                (Some(_), false) => error(
+                    "template",
+                    String::from("Set either template_id or template, not both"),
+                ),
+                (Some(name), true) => match config.template(name) {
+                    None => error("template", format!("Unknown template: {name}")),
+                    Some(template) if !template.serverless => {
+                        error(
+                            "template",
+                            format!("Template '{name}' is not a serverless template"),
+                        );
+                    }
+                    Some(_) => {}
+                },
+                (None, false) => {}
+            }
+            if endpoint.gpu.pools.is_empty() {
+                error(
+                    "gpu.pools",
+                    String::from("At least one GPU pool is required"),
+                );
+            }
+            if endpoint.gpu.count == 0 {
+                error("gpu.count", String::from("GPU count must be at least 1"));
+            }
+            if endpoint.workers.max == 0 || endpoint.workers.min > endpoint.workers.max {
+                error(
+                    "workers",
+                    format!(
+                        "Workers must satisfy min <= max and max >= 1 (min {}, max {})",
+                        endpoint.workers.min, endpoint.workers.max
+                    ),
+                );
+            }
+        }
+    }
+
+    /// Validates templates and the pods deployed from them.
+    fn validate_templates(config: &DeployConfig, result: &mut ValidationResult) {
+        let mut seen_names = HashSet::new();
+
+        for (i, template) in config.templates.iter().enumerate() {
+            let prefix = format!("templates[{i}]");
+            let mut error = |field: &str, message: String| {
+                result.errors.push(ValidationError {
+                    field: format!("{prefix}.{field}"),
+                    message,
+                });
+            };
+
+            if !is_valid_name(&template.name) {
+                error(
+                    "name",
+                    format!(
+                        "Template name '{}' is invalid. Must be lowercase alphanumeric with hyphens.",
+                        template.name
+                    ),
+                );
+            } else if !seen_names.insert(&template.name) {
+                error(
+                    "name",
+                    format!("Duplicate template name: {}", template.name),
+                );
+            } else if config.pod_names().contains(&template.name.as_str()) {
+                // Pods pushed with `template push` are saved under their own name
+                error(
+                    "name",
+                    format!("Template name '{}' is also a pod name", template.name),
+                );
+            }
+            if template.image.trim().is_empty() {
+                error("image", String::from("Container image cannot be empty"));
+            }
+        }
+
+        for (i, pod) in config.pods.iter().enumerate() {
+            let Some(name) = &pod.template else {
+                continue;
+            };
+            let message = match config.template(name) {
+                None => format!("Unknown template: {name}"),
+                Some(template) if template.serverless => {
+                    format!("Template '{name}' is a serverless template and cannot deploy pods")
                 }
+                Some(_) => continue,
+            };
+            result.errors.push(ValidationError {
+                field: format!("pods[{i}].template"),
+                message,
+            });
+        }
+    }
+
+    /// Validates diff settings.
+    fn validate_diff(config: &DeployConfig, result: &mut ValidationResult) {
+        let Some(diff) = &config.diff else {
+            return;
+        };
+
+        for pattern in &diff.ignore {
+            let prefix = pattern.strip_suffix('*').unwrap_or(pattern);
+            if prefix.is_empty() || prefix.contains('*') {
+                result.errors.push(ValidationError {
+                    field: String::from("diff.ignore"),
+                    message: format!(
+                        "Invalid pattern '{pattern}': use a field name, optionally ending in '*'"
+                    ),
+                });
+            }
         }
     }
 }
@@ -425,9 +1244,10 @@ fn is_valid_name(name: &str) -> bool {
 
     // First character must be a letter
     if let Some(first) = chars.next()
-        && !first.is_ascii_lowercase() {
-            return false;
-        }
+        && !first.is_ascii_lowercase()
+    {
+        return false;
+    }
 
     // Rest must be lowercase alphanumeric or hyphen
     for c in chars {
@@ -475,9 +1295,48 @@ impl std::fmt::Display for ValidationError {
     }
 }
 
+/// Returns the first `depends_on` cycle between distinct pods, as `a -> b -> a`.
+fn dependency_cycle(pods: &[PodConfig]) -> Option<String> {
+    /// Depth-first search returning the path from a pod back to itself.
+    fn visit<'a>(
+        pods: &'a [PodConfig],
+        name: &'a str,
+        path: &mut Vec<&'a str>,
+        done: &mut HashSet<&'a str>,
+    ) -> Option<String> {
+        if let Some(start) = path.iter().position(|p| *p == name) {
+            let mut cycle = path[start..].to_vec();
+            cycle.push(name);
+            return Some(cycle.join(" -> "));
+        }
+        if done.contains(name) {
+            return None;
+        }
+
+        path.push(name);
+        let dependencies = pods
+            .iter()
+            .find(|p| p.name == name)
+            .map_or(&[][..], |p| &p.depends_on[..]);
+        for dependency in dependencies.iter().filter(|d| *d != name) {
+            if let Some(cycle) = visit(pods, dependency, path, done) {
+                return Some(cycle);
+            }
+        }
+        path.pop();
+        done.insert(name);
+        None
+    }
+
+    let mut done = HashSet::new();
+    pods.iter()
+        .find_map(|pod| visit(pods, &pod.name, &mut Vec::new(), &mut done))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::StoppedRetentionAction;
 
     #[test]
     fn test_valid_name() {
@@ -487,6 +1346,187 @@ mod tests {
         assert!(is_valid_name("test"));
     }
 
+    #[test]
+    fn test_autoscale_validation() {
+        let yaml = r#"
+project:
+  name: test
+state:
+  backend: local
+pods:
+  - name: inference
+    gpu:
+      type: "NVIDIA A40"
+    ports:
+      - "8000/http"
+    runtime:
+      image: "vllm/vllm-openai:v0.6.0"
+    autoscale:
+      min: 3
+      max: 2
+"#;
+        let config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+        let mut result = ValidationResult::default();
+        ConfigValidator::validate_scaling(&config.pods[0], "pods[0]", &mut result);
+
+        let fields: Vec<&str> = result.errors.iter().map(|e| e.field.as_str()).collect();
+        assert!(fields.contains(&"pods[0].autoscale.max"));
+        assert!(fields.contains(&"pods[0].autoscale"));
+    }
+
+    #[test]
+    fn test_dependency_validation() {
+        let yaml = "project:\n  name: test\nstate:\n  backend: local\npods:\n  - name: db\n    gpu:\n      type: NVIDIA A40\n    runtime:\n      image: qdrant/qdrant\n    depends_on: [llm]\n  - name: llm\n    gpu:\n      type: NVIDIA A40\n    runtime:\n      image: vllm/vllm-openai\n    depends_on: [db, cache]\n";
+        let config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+
+        let err = ConfigValidator::new().validate(&config).unwrap_err();
+        assert!(matches!(
+            err,
+            HalldyllError::Config(ConfigError::CircularDependency { ref cycle }) if cycle == "db -> llm -> db"
+        ));
+
+        let mut result = ValidationResult::default();
+        ConfigValidator::validate_dependencies(&config.pods, &mut result);
+        assert!(
+            result
+                .errors
+                .iter()
+                .any(|e| e.message.contains("unknown pod 'cache'"))
+        );
+    }
+
+    #[test]
+    fn test_stopped_retention_validation() {
+        let yaml = "project:\n  name: test\nstate:\n  backend: local\nguardrails:\n  stopped_retention_hours: 0\n  stopped_retention_action: terminate\npods: []\n";
+        let config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+        let guardrails = config.guardrails.as_ref().unwrap();
+        assert_eq!(
+            guardrails.stopped_retention_action,
+            StoppedRetentionAction::Terminate
+        );
+
+        let mut result = ValidationResult::default();
+        ConfigValidator::validate_guardrails(&config, &mut result);
+        assert_eq!(result.errors[0].field, "guardrails.stopped_retention_hours");
+    }
+
+    #[test]
+    fn test_health_check_validation() {
+        let yaml = r#"
+project:
+  name: test
+state:
+  backend: local
+pods:
+  - name: inference
+    gpu:
+      type: "NVIDIA A40"
+    ports:
+      - "8000/http"
+    runtime:
+      image: "vllm/vllm-openai:v0.6.0"
+    health_check:
+      endpoint: /v1/models
+      port: 8000
+      expect_status: 700
+      headers:
+        Authorization: "Bearer sk-inline"
+"#;
+        let config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+        let mut result = ValidationResult::default();
+        ConfigValidator::validate_health_check(&config.pods[0], "pods[0]", &mut result);
+
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].field, "pods[0].health_check.expect_status");
+        assert_eq!(result.warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_engine_option_validation() {
+        let yaml = r#"
+id: llama
+provider: huggingface
+repo: "meta-llama/Meta-Llama-3-8B-Instruct"
+load:
+  engine: vllm
+  options:
+    max_num_seqs: 64
+    gpu-memory-utilization: "high"
+    max-model-len: 8192
+    enable-prefix-caching: true
+    some-new-flag: 1
+"#;
+        let model: ModelConfig = serde_yaml::from_str(yaml).unwrap();
+        let mut result = ValidationResult::default();
+        ConfigValidator::validate_engine_options(&model, "pods[0].models[0]", &mut result);
+
+        let fields: Vec<&str> = result.errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            vec![
+                "pods[0].models[0].load.options.gpu-memory-utilization",
+                "pods[0].models[0].load.options.max-model-len",
+                "pods[0].models[0].load.options.max_num_seqs",
+            ]
+        );
+        assert_eq!(result.warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_parallelism_validation() {
+        let yaml = r#"
+name: inference
+gpu:
+  type: "NVIDIA A40"
+  count: 2
+runtime:
+  image: "vllm/vllm-openai:v0.6.0"
+models:
+  - id: llama
+    provider: huggingface
+    repo: "meta-llama/Meta-Llama-3-70B-Instruct"
+    load:
+      engine: vllm
+      options:
+        tensor-parallel-size: 4
+"#;
+        let pod: PodConfig = serde_yaml::from_str(yaml).unwrap();
+        let mut result = ValidationResult::default();
+        ConfigValidator::validate_parallelism(&pod, "pods[0]", &mut result);
+
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(
+            result.errors[0].field,
+            "pods[0].models[0].load.options.tensor-parallel-size"
+        );
+    }
+
+    #[test]
+    fn test_exec_validation() {
+        let yaml = r#"
+name: inference
+gpu:
+  type: "NVIDIA A40"
+ports: ["8000/http"]
+runtime:
+  image: "vllm/vllm-openai:v0.6.0"
+models:
+  - id: llama
+    provider: huggingface
+    repo: "meta-llama/Meta-Llama-3-8B-Instruct"
+"#;
+        let mut pod: PodConfig = serde_yaml::from_str(yaml).unwrap();
+        let mut result = ValidationResult::default();
+        ConfigValidator::validate_exec(&pod, "pods[0]", &mut result);
+        assert_eq!(result.warnings.len(), 1);
+        assert!(result.warnings[0].starts_with("pods[0].ports:"));
+
+        pod.ports.push(serde_yaml::from_str("\"22/tcp\"").unwrap());
+        let mut with_ssh = ValidationResult::default();
+        ConfigValidator::validate_exec(&pod, "pods[0]", &mut with_ssh);
+        assert!(with_ssh.warnings.is_empty());
+    }
+
     #[test]
     fn test_invalid_name() {
         assert!(!is_valid_name(""));
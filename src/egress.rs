@@ -0,0 +1,266 @@
+//! Outbound hosts each command may contact.
+//!
+//! `halldyll endpoints --command <command>` lists them for security reviews.
+//! The report is static: it is derived from the command, the configuration and
+//! the enabled cargo features, and nothing is contacted to build it. Hosts
+//! only known at run time (pod addresses, webhooks read from the environment)
+//! are shown as placeholders.
+
+use serde::Serialize;
+
+use crate::config::{DeployConfig, ModelProvider, StateBackend};
+use crate::registry::ImageReference;
+
+/// An external service a command uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Service {
+    /// `RunPod` GraphQL API.
+    RunPodApi,
+    /// Commands run on pods, over SSH or `RunPod`'s exec API.
+    PodShell,
+    /// Pod ports through `RunPod`'s HTTP proxy (health checks, engine probes, drain).
+    PodProxy,
+    /// Remote state backend.
+    State,
+    /// `HuggingFace` Hub (model architecture and revisions).
+    HuggingFace,
+    /// Container registries (image digests).
+    Registry,
+    /// Alert webhooks.
+    Webhooks,
+    /// crates.io (release check).
+    CratesIo,
+}
+
+use Service::{CratesIo, HuggingFace, PodProxy, PodShell, Registry, RunPodApi, State, Webhooks};
+
+/// Services used by each command, by command name.
+const COMMANDS: &[(&str, &[Service])] = &[
+    ("init", &[]),
+    ("validate", &[]),
+    ("check", &[RunPodApi]),
+    ("plan", &[RunPodApi, State]),
+    (
+        "apply",
+        &[RunPodApi, State, PodShell, PodProxy, HuggingFace],
+    ),
+    ("status", &[RunPodApi, State, PodProxy]),
+    (
+        "reconcile",
+        &[RunPodApi, State, PodShell, PodProxy, HuggingFace],
+    ),
+    ("destroy", &[RunPodApi, State, PodProxy]),
+    ("logs", &[RunPodApi, State, PodShell]),
+    ("exec", &[RunPodApi, State, PodShell]),
+    ("drift", &[RunPodApi, State, PodProxy]),
+    (
+        "watch",
+        &[RunPodApi, State, PodShell, PodProxy, HuggingFace, Webhooks],
+    ),
+    ("lock", &[RunPodApi, Registry, HuggingFace]),
+    ("package", &[RunPodApi, State, HuggingFace]),
+    ("version", &[CratesIo]),
+    ("cost", &[RunPodApi, State]),
+    ("slo", &[State]),
+    ("inventory", &[RunPodApi]),
+    ("audit-orphans", &[RunPodApi, State]),
+    ("wake", &[RunPodApi, State, PodProxy]),
+    ("scale", &[State]),
+    ("state", &[RunPodApi, State, PodShell]),
+    ("template", &[RunPodApi]),
+    ("env", &[]),
+    ("maintenance", &[State]),
+    ("annotate", &[State]),
+    ("import", &[RunPodApi, State]),
+    ("failover", &[RunPodApi, State, PodShell, PodProxy]),
+    ("promote", &[RunPodApi, State, PodShell, PodProxy]),
+    ("rollback", &[RunPodApi, State, PodShell, PodProxy]),
+    ("endpoints", &[]),
+];
+
+/// An outbound host a command may contact.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Egress {
+    /// Host name (or a placeholder for hosts only known at run time).
+    pub host: String,
+    /// Why the command contacts it.
+    pub purpose: String,
+}
+
+impl Egress {
+    fn new(host: impl Into<String>, purpose: impl Into<String>) -> Self {
+        Self {
+            host: host.into(),
+            purpose: purpose.into(),
+        }
+    }
+}
+
+/// Returns the names of the commands egress can be reported for.
+pub fn command_names() -> impl Iterator<Item = &'static str> {
+    COMMANDS.iter().map(|(name, _)| *name)
+}
+
+/// Returns the hosts `command` may contact with `config` (if any).
+///
+/// Without a configuration, hosts that depend on it (state bucket, webhooks,
+/// registries) are left out. Unknown commands contact nothing.
+#[must_use]
+pub fn command_egress(command: &str, config: Option<&DeployConfig>) -> Vec<Egress> {
+    let services = COMMANDS
+        .iter()
+        .find(|(name, _)| *name == command)
+        .map_or(&[][..], |(_, services)| *services);
+
+    let mut hosts = Vec::new();
+    for service in services {
+        match service {
+            RunPodApi => hosts.push(Egress::new("api.runpod.io", "RunPod GraphQL API")),
+            PodShell => {
+                if cfg!(feature = "ssh") {
+                    hosts.push(Egress::new(
+                        "<pod public IP>",
+                        "SSH to pods when RUNPOD_SSH_KEY is set",
+                    ));
+                }
+                hosts.push(Egress::new(
+                    "api.runpod.ai",
+                    "Commands run on pods through RunPod's exec API",
+                ));
+            }
+            PodProxy => hosts.push(Egress::new(
+                "<pod>-<port>.proxy.runpod.net",
+                "Pod health checks, engine probes and drain requests",
+            )),
+            State => hosts.extend(config.map(state_egress).unwrap_or_default()),
+            HuggingFace => {
+                let uses_hub = config.is_none_or(|c| {
+                    c.pods
+                        .iter()
+                        .flat_map(|p| &p.models)
+                        .any(|m| m.provider == ModelProvider::Huggingface)
+                });
+                if uses_hub {
+                    hosts.push(Egress::new(
+                        "huggingface.co",
+                        "Model architecture and revisions",
+                    ));
+                }
+            }
+            Registry => {
+                for pod in config.map(|c| c.pods.as_slice()).unwrap_or_default() {
+                    let registry = ImageReference::parse(&pod.runtime.image).registry;
+                    if registry == "registry-1.docker.io" {
+                        hosts.push(Egress::new("auth.docker.io", "Docker Hub pull token"));
+                    }
+                    hosts.push(Egress::new(registry, "Image digest resolution"));
+                }
+            }
+            Webhooks => {
+                let webhooks = config
+                    .and_then(|c| c.alerts.as_ref())
+                    .map(|a| a.webhooks.as_slice());
+                for webhook in webhooks.unwrap_or_default() {
+                    hosts.push(webhook_egress(webhook));
+                }
+            }
+            CratesIo => hosts.push(Egress::new(
+                "crates.io",
+                "Release check (`version --check` only)",
+            )),
+        }
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    hosts.retain(|h| seen.insert(h.host.clone()));
+    hosts
+}
+
+/// Returns the hosts of the configured remote state backend.
+fn state_egress(config: &DeployConfig) -> Vec<Egress> {
+    let state = &config.state;
+    match state.backend {
+        StateBackend::Local => vec![],
+        StateBackend::S3 if !cfg!(feature = "s3") => vec![],
+        StateBackend::S3 => {
+            let bucket = state.bucket.as_deref().unwrap_or("<bucket>");
+            let host = match state.endpoint_url.as_deref().and_then(url_host) {
+                Some(endpoint) if state.force_path_style => endpoint,
+                Some(endpoint) => format!("{bucket}.{endpoint}"),
+                None => {
+                    let region = state.region.as_deref().unwrap_or("<AWS region>");
+                    format!("{bucket}.s3.{region}.amazonaws.com")
+                }
+            };
+            vec![Egress::new(host, "S3 state backend")]
+        }
+        StateBackend::Http => [&state.address, &state.lock_address, &state.unlock_address]
+            .into_iter()
+            .filter_map(|address| address.as_deref().and_then(url_host))
+            .map(|host| Egress::new(host, "HTTP state backend"))
+            .collect(),
+    }
+}
+
+/// Returns the host a webhook posts to.
+fn webhook_egress(webhook: &str) -> Egress {
+    let host = webhook
+        .trim()
+        .strip_prefix("${")
+        .and_then(|v| v.strip_suffix('}'))
+        .map_or_else(
+            || url_host(webhook).unwrap_or_else(|| webhook.to_string()),
+            |var| format!("<host in ${var}>"),
+        );
+    Egress::new(host, "Alert webhook")
+}
+
+/// Returns the host (and port, if any) of a URL.
+fn url_host(url: &str) -> Option<String> {
+    let url = reqwest::Url::parse(url).ok()?;
+    let host = url.host_str()?;
+    Some(
+        url.port()
+            .map_or_else(|| host.to_string(), |port| format!("{host}:{port}")),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_every_command_listed() {
+        let cli = crate::cli::Cli::command();
+        for command in cli.get_subcommands() {
+            assert!(
+                command_names().any(|name| name == command.get_name()),
+                "{}",
+                command.get_name()
+            );
+        }
+    }
+
+    #[test]
+    fn test_apply_egress() {
+        let yaml = "project:\n  name: proj\nstate:\n  backend: http\n  address: https://state.example.com:8443/proj\nalerts:\n  webhooks: [\"https://hooks.slack.com/services/x\", \"${TEAMS_WEBHOOK}\"]\npods:\n  - name: web\n    gpu:\n      type: A40\n    runtime:\n      image: ghcr.io/org/app:1\n";
+        let config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+        let hosts = |command| {
+            command_egress(command, Some(&config))
+                .into_iter()
+                .map(|e| e.host)
+                .collect::<Vec<_>>()
+        };
+
+        let apply = hosts("apply");
+        assert!(apply.contains(&String::from("api.runpod.io")));
+        assert!(apply.contains(&String::from("state.example.com:8443")));
+        // No HuggingFace models configured
+        assert!(!apply.contains(&String::from("huggingface.co")));
+
+        assert!(hosts("watch").contains(&String::from("<host in $TEAMS_WEBHOOK>")));
+        assert!(hosts("lock").contains(&String::from("ghcr.io")));
+        assert!(hosts("validate").is_empty());
+    }
+}
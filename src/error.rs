@@ -37,6 +37,82 @@ pub enum HalldyllError {
     /// Generic internal error.
     #[error("Internal error: {0}")]
     Internal(String),
+
+    /// An error annotated with the operation that produced it.
+    #[error("{context}: {source}")]
+    Context {
+        /// What was being done when the error occurred.
+        context: ErrorContext,
+        /// The underlying error.
+        #[source]
+        source: Box<Self>,
+    },
+}
+
+/// Describes the operation during which an error occurred.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorContext {
+    /// Operation being performed (e.g. "creating pod").
+    pub operation: String,
+    /// Resource involved (pod name, pod ID, state path).
+    pub resource: Option<String>,
+    /// Attempt number, for retried operations.
+    pub attempt: Option<u32>,
+}
+
+impl ErrorContext {
+    /// Creates a context for an operation.
+    #[must_use]
+    pub fn new(operation: impl Into<String>) -> Self {
+        Self {
+            operation: operation.into(),
+            resource: None,
+            attempt: None,
+        }
+    }
+
+    /// Sets the resource involved.
+    #[must_use]
+    pub fn with_resource(mut self, resource: impl Into<String>) -> Self {
+        self.resource = Some(resource.into());
+        self
+    }
+
+    /// Sets the attempt number.
+    #[must_use]
+    pub const fn with_attempt(mut self, attempt: u32) -> Self {
+        self.attempt = Some(attempt);
+        self
+    }
+}
+
+impl std::fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.operation)?;
+        if let Some(resource) = &self.resource {
+            write!(f, " {resource}")?;
+        }
+        if let Some(attempt) = self.attempt {
+            write!(f, " (attempt {attempt})")?;
+        }
+        Ok(())
+    }
+}
+
+/// Adds operation context to fallible results.
+pub trait ResultExt<T> {
+    /// Wraps the error, if any, with the given context.
+    ///
+    /// # Errors
+    ///
+    /// Returns the original error annotated with the context.
+    fn context(self, context: impl FnOnce() -> ErrorContext) -> Result<T>;
+}
+
+impl<T, E: Into<HalldyllError>> ResultExt<T> for std::result::Result<T, E> {
+    fn context(self, context: impl FnOnce() -> ErrorContext) -> Result<T> {
+        self.map_err(|e| e.into().with_context(context()))
+    }
 }
 
 /// Configuration-related errors.
@@ -103,6 +179,15 @@ pub enum ConfigError {
         /// Description of the cycle.
         cycle: String,
     },
+
+    /// The configuration needs a cargo feature this build leaves out.
+    #[error("{needed_for} requires the `{feature}` feature, which this build does not include")]
+    FeatureDisabled {
+        /// Cargo feature name.
+        feature: String,
+        /// What needs the feature (e.g. "The S3 state backend").
+        needed_for: String,
+    },
 }
 
 /// State management errors.
@@ -138,6 +223,13 @@ pub enum StateError {
         since: String,
     },
 
+    /// A lock this process held was released or taken over.
+    #[error("State lock {lock_id} is no longer held")]
+    LockLost {
+        /// Identifier of the lost lock.
+        lock_id: String,
+    },
+
     /// S3 backend error.
     #[error("S3 state backend error: {message}")]
     S3Error {
@@ -145,6 +237,23 @@ pub enum StateError {
         message: String,
     },
 
+    /// HTTP backend error.
+    #[error("HTTP state backend error: {message}")]
+    Http {
+        /// Description of the HTTP error.
+        message: String,
+    },
+
+    /// Filesystem error in the local state backend.
+    #[error("Failed to access {}: {source}", path.display())]
+    Io {
+        /// File or directory being accessed.
+        path: PathBuf,
+        /// Underlying I/O error.
+        #[source]
+        source: std::io::Error,
+    },
+
     /// Serialization error.
     #[error("State serialization error: {message}")]
     SerializationError {
@@ -152,6 +261,22 @@ pub enum StateError {
         message: String,
     },
 
+    /// State was modified by another writer since it was loaded.
+    #[error("State was modified concurrently: {message}")]
+    Conflict {
+        /// Description of the conflict.
+        message: String,
+    },
+
+    /// Operation not supported by the configured state backend.
+    #[error("{operation} is not supported by the {backend} state backend")]
+    Unsupported {
+        /// Operation that was attempted.
+        operation: String,
+        /// Backend type name.
+        backend: String,
+    },
+
     /// State version mismatch.
     #[error("State version mismatch: expected {expected}, found {found}")]
     VersionMismatch {
@@ -204,6 +329,41 @@ pub enum RunPodError {
         region: String,
     },
 
+    /// Every available GPU type costs more than the configured ceiling.
+    #[error(
+        "No {gpu_type} GPU (or fallback) available under ${max_price:.2}/hr; cheapest is {cheapest}"
+    )]
+    PriceCeilingExceeded {
+        /// Requested GPU type.
+        gpu_type: String,
+        /// Ceiling per GPU per hour in USD.
+        max_price: f64,
+        /// Cheapest available candidate and its price.
+        cheapest: String,
+    },
+
+    /// Every candidate GPU type has less memory than `gpu.min_vram_gb`, or is unavailable.
+    #[error(
+        "No {gpu_type} GPU (or fallback) with {min_vram_gb} GB of VRAM available; too small: {too_small}"
+    )]
+    InsufficientVram {
+        /// Requested GPU type.
+        gpu_type: String,
+        /// Required VRAM per GPU in GB.
+        min_vram_gb: u32,
+        /// Candidates skipped for their memory, with their VRAM.
+        too_small: String,
+    },
+
+    /// A command run on a pod exited with a non-zero code.
+    #[error("Command on {pod} exited with code {exit_code}")]
+    RemoteCommandFailed {
+        /// Pod the command ran on.
+        pod: String,
+        /// Remote exit code.
+        exit_code: i32,
+    },
+
     /// Insufficient quota.
     #[error("Insufficient quota: {message}")]
     InsufficientQuota {
@@ -211,6 +371,35 @@ pub enum RunPodError {
         message: String,
     },
 
+    /// A GraphQL error `RunPod` reported with a recognized code or message.
+    #[error("{kind}: {message}")]
+    Api {
+        /// What went wrong.
+        kind: ApiErrorKind,
+        /// Error code from the GraphQL error extensions, if any.
+        code: Option<String>,
+        /// Error message from the API.
+        message: String,
+    },
+
+    /// SSH connection or command failure.
+    #[error("SSH to {host} failed: {message}")]
+    Ssh {
+        /// Host and port connected to.
+        host: String,
+        /// Description of the failure.
+        message: String,
+    },
+
+    /// A pod presented a different SSH host key than the one recorded.
+    #[error("SSH host key of pod {pod_id} ({host}) does not match the recorded one")]
+    HostKeyMismatch {
+        /// Pod connected to.
+        pod_id: String,
+        /// Host and port connected to.
+        host: String,
+    },
+
     /// Network error.
     #[error("Network error communicating with RunPod: {message}")]
     NetworkError {
@@ -225,6 +414,19 @@ pub enum RunPodError {
         message: String,
     },
 
+    /// Exec job that did not finish in time and was cancelled.
+    #[error(
+        "Command on pod {pod_id} did not finish within {timeout_secs}s (job {job_id} cancelled)"
+    )]
+    ExecTimeout {
+        /// ID of the pod.
+        pod_id: String,
+        /// ID of the exec job.
+        job_id: String,
+        /// Timeout that was exceeded.
+        timeout_secs: u64,
+    },
+
     /// Timeout waiting for pod.
     #[error("Timeout waiting for pod {pod_id} to reach state {expected_state}")]
     Timeout {
@@ -235,6 +437,84 @@ pub enum RunPodError {
     },
 }
 
+/// Kinds of `RunPod` GraphQL errors that callers can act on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiErrorKind {
+    /// The account has reached its maximum number of pods.
+    PodLimitReached,
+    /// The account balance or spend limit does not cover the request.
+    InsufficientBalance,
+    /// The requested `gpuTypeId` does not exist or is not offered.
+    InvalidGpuType,
+    /// The requested region or data center cannot serve the request.
+    RegionUnsupported,
+    /// No machine with the requested GPUs is free right now.
+    NoCapacity,
+}
+
+impl std::fmt::Display for ApiErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self {
+            Self::PodLimitReached => "Pod limit reached",
+            Self::InsufficientBalance => "Insufficient balance",
+            Self::InvalidGpuType => "Invalid GPU type",
+            Self::RegionUnsupported => "Region not supported",
+            Self::NoCapacity => "No capacity",
+        };
+        write!(f, "{kind}")
+    }
+}
+
+impl ApiErrorKind {
+    /// Recognizes an error from its GraphQL code, or failing that its message.
+    #[must_use]
+    pub fn classify(code: Option<&str>, message: &str) -> Option<Self> {
+        let by_code = code.and_then(|code| {
+            let code = code.to_uppercase();
+            API_ERROR_CODES
+                .iter()
+                .find(|(_, markers)| markers.iter().any(|marker| code.contains(marker)))
+                .map(|(kind, _)| *kind)
+        });
+
+        by_code.or_else(|| {
+            let lower = message.to_lowercase();
+            API_ERROR_MESSAGES
+                .iter()
+                .find(|(_, markers)| markers.iter().any(|marker| lower.contains(marker)))
+                .map(|(kind, _)| *kind)
+        })
+    }
+
+    /// Returns a suggestion for resolving the error.
+    #[must_use]
+    pub const fn hint(self) -> &'static str {
+        match self {
+            Self::PodLimitReached => {
+                "Terminate pods you no longer need (see `halldyll audit-orphans`) or ask RunPod to raise the limit"
+            }
+            Self::InsufficientBalance => {
+                "Check your RunPod balance and spend limits, or reduce gpu.count"
+            }
+            Self::InvalidGpuType => {
+                "Check gpu.type and gpu.fallback against the GPU types RunPod lists"
+            }
+            Self::RegionUnsupported => {
+                "Pick another project.region or data center, or list fallbacks in gpu.fallback"
+            }
+            Self::NoCapacity => {
+                "Retry later, or list other data centers in project.region_fallback or GPU types in gpu.fallback"
+            }
+        }
+    }
+
+    /// Returns true if the error is caused by an account limit.
+    #[must_use]
+    pub const fn is_quota(self) -> bool {
+        matches!(self, Self::PodLimitReached | Self::InsufficientBalance)
+    }
+}
+
 /// Planning errors.
 #[derive(Debug, Error)]
 pub enum PlanError {
@@ -273,6 +553,13 @@ pub enum PlanError {
         /// Description of the dependency issue.
         message: String,
     },
+
+    /// A simulated configuration change fails validation or guardrails.
+    #[error("Simulated change would fail: {problems} problem(s)")]
+    PreflightFailed {
+        /// Number of validation errors and guardrail violations.
+        problems: usize,
+    },
 }
 
 /// Reconciliation errors.
@@ -313,8 +600,91 @@ pub enum ReconcileError {
         /// Reason for abort.
         reason: String,
     },
+
+    /// Apply stopped at its deadline with actions left.
+    #[error("Apply deadline reached: {remaining} actions not completed")]
+    DeadlineReached {
+        /// Number of actions skipped or cancelled.
+        remaining: usize,
+    },
 }
 
+/// Fragments of `RunPod` GraphQL error codes, by error kind.
+const API_ERROR_CODES: &[(ApiErrorKind, &[&str])] = &[
+    (
+        ApiErrorKind::PodLimitReached,
+        &["POD_LIMIT", "MAX_PODS", "TOO_MANY_PODS"],
+    ),
+    (
+        ApiErrorKind::InsufficientBalance,
+        &["INSUFFICIENT_BALANCE", "INSUFFICIENT_FUNDS", "SPEND_LIMIT"],
+    ),
+    (
+        ApiErrorKind::InvalidGpuType,
+        &["INVALID_GPU", "GPU_TYPE_NOT_FOUND", "UNKNOWN_GPU"],
+    ),
+    (
+        ApiErrorKind::RegionUnsupported,
+        &["REGION", "DATA_CENTER", "DATACENTER"],
+    ),
+    (
+        ApiErrorKind::NoCapacity,
+        &["NO_CAPACITY", "INSUFFICIENT_CAPACITY", "NO_INSTANCES"],
+    ),
+];
+
+/// Phrases in `RunPod` error messages, by error kind.
+const API_ERROR_MESSAGES: &[(ApiErrorKind, &[&str])] = &[
+    (
+        ApiErrorKind::PodLimitReached,
+        &["pod limit", "maximum number of pods", "too many pods"],
+    ),
+    (
+        ApiErrorKind::InsufficientBalance,
+        &[
+            "insufficient funds",
+            "insufficient balance",
+            "not enough funds",
+            "not enough balance",
+            "balance is too low",
+            "low balance",
+            "add funds",
+            "spend limit",
+            "spending limit",
+        ],
+    ),
+    (
+        ApiErrorKind::InvalidGpuType,
+        &[
+            "invalid gputypeid",
+            "invalid gpu type",
+            "gpu type not found",
+            "unknown gpu type",
+        ],
+    ),
+    (
+        ApiErrorKind::RegionUnsupported,
+        &[
+            "region not supported",
+            "unsupported region",
+            "not available in this region",
+            "data center not supported",
+        ],
+    ),
+    (
+        ApiErrorKind::NoCapacity,
+        &[
+            "no longer any instances available",
+            "no instances available",
+            "not enough capacity",
+            "out of capacity",
+        ],
+    ),
+];
+
+/// Phrases in other `RunPod` error messages that indicate an account limit.
+const QUOTA_MARKERS: &[&str] = &["quota"];
+
 /// Result type alias for Halldyll operations.
 pub type Result<T> = std::result::Result<T, HalldyllError>;
 
@@ -325,27 +695,188 @@ impl HalldyllError {
         Self::Internal(message.into())
     }
 
+    /// Wraps this error with the operation that produced it.
+    #[must_use]
+    pub fn with_context(self, context: ErrorContext) -> Self {
+        Self::Context {
+            context,
+            source: Box::new(self),
+        }
+    }
+
+    /// Returns the innermost error, looking through any context layers.
+    #[must_use]
+    pub fn root(&self) -> &Self {
+        match self {
+            Self::Context { source, .. } => source.root(),
+            other => other,
+        }
+    }
+
+    /// Returns the context layers, outermost first.
+    #[must_use]
+    pub fn contexts(&self) -> Vec<&ErrorContext> {
+        let mut contexts = Vec::new();
+        let mut current = self;
+        while let Self::Context { context, source } = current {
+            contexts.push(context);
+            current = source;
+        }
+        contexts
+    }
+
+    /// Returns true if this error is caused by a `RunPod` account limit.
+    #[must_use]
+    pub fn is_quota(&self) -> bool {
+        matches!(self.root(), Self::RunPod(e) if e.is_quota())
+    }
+
+    /// Returns the kind of a recognized `RunPod` GraphQL error.
+    #[must_use]
+    pub fn api_kind(&self) -> Option<ApiErrorKind> {
+        match self.root() {
+            Self::RunPod(e) => e.api_kind(),
+            _ => None,
+        }
+    }
+
     /// Returns true if this error is retryable.
     #[must_use]
-    pub const fn is_retryable(&self) -> bool {
+    pub fn is_retryable(&self) -> bool {
         matches!(
-            self,
-            Self::RunPod(
-                RunPodError::RateLimited { .. } | RunPodError::NetworkError { .. }
-            ) | Self::State(StateError::LockFailed { .. })
+            self.root(),
+            Self::RunPod(RunPodError::RateLimited { .. } | RunPodError::NetworkError { .. })
+                | Self::State(StateError::LockFailed { .. })
         )
     }
 
     /// Returns the suggested retry delay in seconds, if applicable.
     #[must_use]
-    pub const fn retry_delay_secs(&self) -> Option<u64> {
-        match self {
+    pub fn retry_delay_secs(&self) -> Option<u64> {
+        match self.root() {
             Self::RunPod(RunPodError::RateLimited { retry_after_secs }) => Some(*retry_after_secs),
             Self::RunPod(RunPodError::NetworkError { .. }) => Some(5),
             Self::State(StateError::LockFailed { .. }) => Some(2),
             _ => None,
         }
     }
+
+    /// Returns an actionable suggestion for resolving this error, if known.
+    #[must_use]
+    pub fn hint(&self) -> Option<String> {
+        let hint = match self.root() {
+            Self::Config(ConfigError::FileNotFound { .. }) => {
+                String::from("Run `halldyll init` to create a config, or pass one with --config")
+            }
+            Self::Config(ConfigError::MissingEnvVar { name }) => {
+                format!("Set {name} in the environment or in a .env file next to the config")
+            }
+            Self::Config(ConfigError::FeatureDisabled { feature, .. }) => {
+                format!("Reinstall with `cargo install halldyll_deploy_pods --features {feature}`")
+            }
+            Self::Config(_) => String::from("Run `halldyll validate --warnings` for details"),
+            Self::State(StateError::LockedByOther { .. }) => String::from(
+                "Wait for the other run to finish, or `halldyll state unlock --force` if the lock is stale",
+            ),
+            Self::State(StateError::LockLost { .. }) => String::from(
+                "Another process took over the lock after it expired; use a longer lease or check for clock skew",
+            ),
+            Self::State(StateError::Conflict { .. }) => {
+                String::from("Another run updated the state; re-run the command")
+            }
+            Self::State(StateError::Io { .. }) => String::from(
+                "Check the directory permissions, or point state.path (HALLDYLL_STATE_PATH) elsewhere",
+            ),
+            Self::State(StateError::S3Error { .. }) => String::from(
+                "Check AWS credentials, state.bucket and state.region (and state.endpoint_url for S3-compatible stores)",
+            ),
+            Self::State(StateError::Http { .. }) => String::from(
+                "Check state.address, state.lock_address and the state credentials (HALLDYLL_STATE_USERNAME/PASSWORD)",
+            ),
+            Self::State(StateError::Corrupted { .. }) => String::from(
+                "Inspect the state file; `halldyll state rollback` can restore an earlier version on S3",
+            ),
+            Self::RunPod(RunPodError::AuthenticationFailed { .. }) => {
+                String::from("Check that RUNPOD_API_KEY is set and still valid")
+            }
+            Self::RunPod(RunPodError::ApiRequestFailed {
+                status: 401 | 403, ..
+            }) => String::from("Check that RUNPOD_API_KEY is set and has access to this account"),
+            Self::RunPod(RunPodError::ApiRequestFailed { status, .. }) if *status >= 500 => {
+                String::from("RunPod may be having issues; retry in a few minutes")
+            }
+            Self::RunPod(RunPodError::ApiRequestFailed { .. }) => String::from(
+                "Check the pod spec with `halldyll validate`; the request was rejected by RunPod",
+            ),
+            Self::RunPod(RunPodError::InsufficientQuota { .. }) => {
+                String::from("Check your RunPod balance and spend limits, or reduce gpu.count")
+            }
+            Self::RunPod(RunPodError::Api { kind, .. }) => String::from(kind.hint()),
+            Self::RunPod(RunPodError::GpuNotAvailable { .. }) => {
+                String::from("Try another region, or list fallbacks in gpu.fallback")
+            }
+            Self::RunPod(RunPodError::InsufficientVram { .. }) => String::from(
+                "List GPU types with enough memory in gpu.fallback, or lower gpu.min_vram_gb",
+            ),
+            Self::RunPod(RunPodError::PriceCeilingExceeded { .. }) => String::from(
+                "Raise gpu.max_price_per_hour, add cheaper gpu.fallback types, or set gpu.max_price_action: cheapest",
+            ),
+            Self::RunPod(RunPodError::Ssh { .. }) => String::from(
+                "Check that the public key matching RUNPOD_SSH_KEY is in the pod's PUBLIC_KEY and that 22/tcp is exposed",
+            ),
+            Self::RunPod(RunPodError::HostKeyMismatch { pod_id, .. }) => format!(
+                "If the pod's host key changed legitimately, remove the {pod_id} line from .halldyll/known_hosts, \
+                 or pass --insecure-skip-host-key"
+            ),
+            Self::RunPod(RunPodError::RateLimited { .. }) => {
+                String::from("Wait a moment and retry, or lower watch frequency")
+            }
+            Self::RunPod(RunPodError::NetworkError { .. }) => {
+                String::from("Check network connectivity to api.runpod.io")
+            }
+            Self::RunPod(RunPodError::ExecTimeout { .. }) => String::from(
+                "Raise the timeout with `halldyll exec --timeout`, or set RUNPOD_SSH_KEY to run commands over SSH",
+            ),
+            Self::RunPod(RunPodError::Timeout { pod_id, .. }) => {
+                format!("Check the pod in the RunPod console or with `halldyll logs` ({pod_id})")
+            }
+            Self::Plan(PlanError::BudgetExceeded { .. }) => {
+                String::from("Raise guardrails.max_hourly_cost or reduce GPU count/replicas")
+            }
+            Self::Plan(PlanError::GpuQuotaExceeded { .. }) => {
+                String::from("Raise guardrails.max_gpus or reduce GPU count/replicas")
+            }
+            Self::Plan(PlanError::PreflightFailed { .. }) => {
+                String::from("Adjust the --set values (or the guardrails) and check again")
+            }
+            Self::Reconcile(ReconcileError::DriftDetected { .. }) => {
+                String::from("Run `halldyll reconcile` to converge the deployment")
+            }
+            Self::Reconcile(ReconcileError::DeadlineReached { .. }) => String::from(
+                "State was saved; re-run `halldyll apply` to finish the remaining actions",
+            ),
+            _ => return None,
+        };
+        Some(hint)
+    }
+
+    /// Returns the process exit code for this error.
+    ///
+    /// A deadline stop exits with 124 (like `timeout(1)`) so CI can tell it
+    /// apart from a failed apply.
+    #[must_use]
+    pub fn exit_code(&self) -> u8 {
+        match self.root() {
+            Self::Reconcile(ReconcileError::DeadlineReached { .. }) => 124,
+            Self::RunPod(RunPodError::RemoteCommandFailed { exit_code, .. }) => {
+                u8::try_from(*exit_code)
+                    .ok()
+                    .filter(|code| *code != 0)
+                    .unwrap_or(1)
+            }
+            _ => 1,
+        }
+    }
 }
 
 impl ConfigError {
@@ -358,6 +889,15 @@ impl ConfigError {
         }
     }
 
+    /// Creates an error for a configuration that needs the cargo feature `feature`.
+    #[must_use]
+    pub fn feature_disabled(feature: &str, needed_for: impl Into<String>) -> Self {
+        Self::FeatureDisabled {
+            feature: feature.to_string(),
+            needed_for: needed_for.into(),
+        }
+    }
+
     /// Creates a validation error without a specific field.
     #[must_use]
     pub fn validation_general(message: impl Into<String>) -> Self {
@@ -377,6 +917,23 @@ impl StateError {
         }
     }
 
+    /// Creates an HTTP backend error with the given message.
+    #[must_use]
+    pub fn http(message: impl Into<String>) -> Self {
+        Self::Http {
+            message: message.into(),
+        }
+    }
+
+    /// Creates a local filesystem error for the given path.
+    #[must_use]
+    pub fn io(path: impl Into<PathBuf>, source: std::io::Error) -> Self {
+        Self::Io {
+            path: path.into(),
+            source,
+        }
+    }
+
     /// Creates a serialization error with the given message.
     #[must_use]
     pub fn serialization(message: impl Into<String>) -> Self {
@@ -396,6 +953,60 @@ impl RunPodError {
         }
     }
 
+    /// Creates an error from a failed API response.
+    ///
+    /// Recognized messages become [`RunPodError::Api`], other quota messages
+    /// [`RunPodError::InsufficientQuota`]; anything else is an API request error.
+    #[must_use]
+    pub fn from_api_response(status: u16, message: impl Into<String>) -> Self {
+        let message = message.into();
+        let lower = message.to_lowercase();
+        if let Some(kind) = ApiErrorKind::classify(None, &message) {
+            Self::Api {
+                kind,
+                code: None,
+                message,
+            }
+        } else if QUOTA_MARKERS.iter().any(|marker| lower.contains(marker)) {
+            Self::InsufficientQuota { message }
+        } else {
+            Self::api_error(status, message)
+        }
+    }
+
+    /// Creates an error from a GraphQL error and its `extensions.code`.
+    #[must_use]
+    pub fn from_graphql(code: Option<String>, message: impl Into<String>) -> Self {
+        let message = message.into();
+        match ApiErrorKind::classify(code.as_deref(), &message) {
+            Some(kind) => Self::Api {
+                kind,
+                code,
+                message,
+            },
+            None => Self::from_api_response(400, message),
+        }
+    }
+
+    /// Returns the kind of a recognized GraphQL error.
+    #[must_use]
+    pub const fn api_kind(&self) -> Option<ApiErrorKind> {
+        match self {
+            Self::Api { kind, .. } => Some(*kind),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the error is caused by an account limit.
+    #[must_use]
+    pub const fn is_quota(&self) -> bool {
+        match self {
+            Self::InsufficientQuota { .. } => true,
+            Self::Api { kind, .. } => kind.is_quota(),
+            _ => false,
+        }
+    }
+
     /// Creates a network error.
     #[must_use]
     pub fn network(message: impl Into<String>) -> Self {
@@ -404,3 +1015,76 @@ impl RunPodError {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_context_and_hint() {
+        let err: Result<()> = Err(RunPodError::InsufficientQuota {
+            message: String::from("balance too low"),
+        }
+        .into());
+        let err = err
+            .context(|| ErrorContext::new("creating pod").with_resource("demo-dev-llm"))
+            .unwrap_err()
+            .with_context(ErrorContext::new("reconciling").with_attempt(2));
+
+        assert_eq!(
+            err.to_string(),
+            "reconciling (attempt 2): creating pod demo-dev-llm: \
+             RunPod API error: Insufficient quota: balance too low"
+        );
+        assert_eq!(err.contexts().len(), 2);
+        assert!(err.hint().is_some_and(|h| h.contains("balance")));
+        assert!(!err.is_retryable());
+    }
+
+    #[test]
+    fn test_quota_detection() {
+        assert!(
+            RunPodError::from_api_response(400, "Insufficient balance to deploy pod").is_quota()
+        );
+        assert!(
+            RunPodError::from_api_response(400, "You have reached your spend limit of $50/hr")
+                .is_quota()
+        );
+        assert!(RunPodError::from_api_response(400, "Maximum number of pods reached").is_quota());
+        assert!(!RunPodError::from_api_response(400, "Invalid gpuTypeId").is_quota());
+    }
+
+    #[test]
+    fn test_graphql_error_kinds() {
+        let by_code =
+            RunPodError::from_graphql(Some(String::from("POD_LIMIT_EXCEEDED")), "Cannot deploy");
+        assert_eq!(by_code.api_kind(), Some(ApiErrorKind::PodLimitReached));
+        assert!(by_code.is_quota());
+
+        let by_message = RunPodError::from_graphql(None, "Invalid gpuTypeId: NVIDIA Z9000");
+        assert_eq!(by_message.api_kind(), Some(ApiErrorKind::InvalidGpuType));
+        assert!(!by_message.is_quota());
+        assert_eq!(
+            by_message.to_string(),
+            "Invalid GPU type: Invalid gpuTypeId: NVIDIA Z9000"
+        );
+        assert!(
+            HalldyllError::from(by_message)
+                .hint()
+                .is_some_and(|h| h.contains("gpu.type"))
+        );
+
+        let capacity = RunPodError::from_graphql(
+            None,
+            "There are no longer any instances available with the requested specifications.",
+        );
+        assert_eq!(capacity.api_kind(), Some(ApiErrorKind::NoCapacity));
+
+        let unknown =
+            RunPodError::from_graphql(Some(String::from("INTERNAL_SERVER_ERROR")), "boom");
+        assert!(matches!(
+            unknown,
+            RunPodError::ApiRequestFailed { status: 400, .. }
+        ));
+    }
+}
@@ -0,0 +1,250 @@
+//! Active/passive failover to secondary data centers.
+//!
+//! A project with `failover:` configured can bring up standby copies of its
+//! pods in a secondary set of data centers. Standby pods are built from the
+//! same specs as the primary ones but tagged with the `<env>-failover`
+//! environment, so plans and reconciles of the primary never see (or remove)
+//! them. The deployment state records which side is active.
+
+use tracing::{info, warn};
+
+use crate::config::{ConfigHasher, DeployConfig, FailoverConfig, PodConfig, ProjectConfig};
+use crate::error::{ConfigError, HalldyllError, Result};
+use crate::planner::DiffEngine;
+use crate::runpod::{ObservedPod, PodObserver, PodProvisioner};
+use crate::state::{DeploymentState, FailoverSide, StandbyPod};
+
+/// Suffix of the environment standby pods are tagged with.
+pub const STANDBY_ENV_SUFFIX: &str = "failover";
+
+/// Returns the environment standby pods of `environment` are tagged with.
+#[must_use]
+pub fn standby_environment(environment: &str) -> String {
+    format!("{environment}-{STANDBY_ENV_SUFFIX}")
+}
+
+/// Returns the project settings standby pods are provisioned with.
+#[must_use]
+pub fn standby_project(project: &ProjectConfig) -> ProjectConfig {
+    let mut standby = project.clone();
+    standby.environment = standby_environment(&project.environment);
+    standby
+}
+
+/// Outcome of activating or deactivating failover.
+#[derive(Debug, Clone, Default)]
+pub struct FailoverResult {
+    /// Pods created or terminated, with details.
+    pub changed: Vec<String>,
+    /// Pods that were already in the requested state.
+    pub unchanged: Vec<String>,
+    /// Pods that could not be changed, with the error.
+    pub failed: Vec<(String, String)>,
+}
+
+impl FailoverResult {
+    /// Returns true if every pod reached the requested state.
+    #[must_use]
+    pub const fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// Provisions and tears down the standby side of a deployment.
+#[derive(Debug)]
+pub struct FailoverManager<'a> {
+    /// Deployment configuration.
+    config: &'a DeployConfig,
+    /// Failover settings of the project.
+    failover: &'a FailoverConfig,
+    /// Pod provisioner (GPU types must be initialized).
+    provisioner: &'a PodProvisioner,
+    /// Pod observer.
+    observer: &'a PodObserver,
+}
+
+impl<'a> FailoverManager<'a> {
+    /// Creates a failover manager.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the project has no `failover` section.
+    pub fn new(
+        config: &'a DeployConfig,
+        provisioner: &'a PodProvisioner,
+        observer: &'a PodObserver,
+    ) -> Result<Self> {
+        let failover = config.project.failover.as_ref().ok_or_else(|| {
+            HalldyllError::Config(ConfigError::validation(
+                "No failover data centers are configured",
+                "project.failover",
+            ))
+        })?;
+
+        Ok(Self {
+            config,
+            failover,
+            provisioner,
+            observer,
+        })
+    }
+
+    /// Lists the standby pods currently on `RunPod`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pods cannot be listed.
+    pub async fn standby_pods(&self) -> Result<Vec<ObservedPod>> {
+        self.observer
+            .list_project_pods(
+                &self.config.project.name,
+                &standby_environment(&self.config.project.environment),
+            )
+            .await
+    }
+
+    /// Provisions the missing standby pods and makes the secondary side active.
+    ///
+    /// Every pod is placed in the first failover data center that accepts it.
+    /// The active side only changes once all standby pods exist; pods created
+    /// before a failure are still recorded in `state`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the existing standby pods cannot be listed.
+    pub async fn activate(&self, state: &mut DeploymentState) -> Result<FailoverResult> {
+        let project = standby_project(&self.config.project);
+        let existing = self.standby_pods().await?;
+        let hasher = ConfigHasher::new();
+        let mut result = FailoverResult::default();
+
+        for pod in DiffEngine::desired_instances(self.config, Some(state)) {
+            if existing
+                .iter()
+                .any(|p| p.pod_name.as_deref() == Some(pod.name.as_str()))
+            {
+                result.unchanged.push(pod.name.clone());
+                continue;
+            }
+
+            let spec_hash = hasher.hash_pod(&pod);
+            match self.create_standby(&pod, &project, &spec_hash).await {
+                Ok(standby) => {
+                    result
+                        .changed
+                        .push(format!("{} ({})", pod.name, standby.data_center));
+                    state
+                        .failover
+                        .get_or_insert_with(Default::default)
+                        .standby_pods
+                        .insert(pod.name.clone(), standby);
+                }
+                Err(e) => result.failed.push((pod.name.clone(), e.to_string())),
+            }
+        }
+
+        if result.is_success() {
+            state.set_active_side(FailoverSide::Secondary);
+            info!("Failover active: secondary side serving");
+        }
+        Ok(result)
+    }
+
+    /// Terminates the standby pods and makes the primary side active.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the standby pods cannot be listed.
+    pub async fn deactivate(&self, state: &mut DeploymentState) -> Result<FailoverResult> {
+        let mut result = FailoverResult::default();
+
+        for pod in self.standby_pods().await? {
+            let name = pod.pod_name.clone().unwrap_or_else(|| pod.name.clone());
+            match self.provisioner.terminate_pod(&pod.id).await {
+                Ok(()) => result.changed.push(format!("{name} ({})", pod.id)),
+                Err(e) => result.failed.push((name, e.to_string())),
+            }
+        }
+
+        if let Some(failover) = &mut state.failover {
+            failover
+                .standby_pods
+                .retain(|name, _| result.failed.iter().any(|(failed, _)| failed == name));
+        }
+        if result.is_success() {
+            state.set_active_side(FailoverSide::Primary);
+            info!("Failover deactivated: primary side serving");
+        }
+        Ok(result)
+    }
+
+    /// Creates one standby pod, trying the failover data centers in order.
+    async fn create_standby(
+        &self,
+        pod: &PodConfig,
+        project: &ProjectConfig,
+        spec_hash: &str,
+    ) -> Result<StandbyPod> {
+        let mut last_error = None;
+
+        for data_center in &self.failover.data_centers {
+            match self
+                .provisioner
+                .create_pod_in_data_center(pod, project, spec_hash, data_center)
+                .await
+            {
+                Ok(created) => {
+                    return Ok(StandbyPod {
+                        runpod_id: created.id,
+                        data_center: data_center.clone(),
+                        config_hash: spec_hash.to_string(),
+                    });
+                }
+                Err(e) => {
+                    warn!("Could not place standby {} in {data_center}: {e}", pod.name);
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| {
+            HalldyllError::Config(ConfigError::validation(
+                "No failover data centers are configured",
+                "project.failover.data_centers",
+            ))
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_standby_project_and_active_side() {
+        let yaml = r#"
+project:
+  name: proj
+  environment: prod
+  region: EU-RO-1
+  failover:
+    data_centers: ["US-TX-3", "US-GA-1"]
+state:
+  backend: local
+pods: []
+"#;
+        let config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+        let standby = standby_project(&config.project);
+        assert_eq!(standby.environment, "prod-failover");
+        assert_eq!(standby.name, "proj");
+
+        let mut state = DeploymentState::new("proj", "prod");
+        assert_eq!(state.active_side(), FailoverSide::Primary);
+        state.set_active_side(FailoverSide::Secondary);
+
+        let json = serde_json::to_string(&state).unwrap();
+        let restored: DeploymentState = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.active_side(), FailoverSide::Secondary);
+        assert!(restored.failover.is_some_and(|f| f.changed_at.is_some()));
+    }
+}
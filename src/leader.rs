@@ -0,0 +1,203 @@
+//! Leader election for watchers running on several machines.
+//!
+//! With `halldyll watch --leader-election`, every watcher competes for the
+//! state lock. The holder is the leader: it renews the lock as a lease while
+//! it works and is the only watcher that autoscales and reconciles. Followers
+//! keep trying to acquire the lock and take over once the leader stops
+//! renewing it (it crashed, lost connectivity or was stopped) and the lease
+//! expires.
+
+use std::future::Future;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::error::{HalldyllError, Result, StateError};
+use crate::state::{StateStore, generate_holder_id};
+
+/// Role of a watcher in the election.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Role {
+    /// This watcher holds the lease.
+    Leader,
+    /// Another watcher holds the lease.
+    Follower {
+        /// Holder of the lease.
+        leader: String,
+    },
+}
+
+impl std::fmt::Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Leader => write!(f, "leader"),
+            Self::Follower { leader } => write!(f, "follower of {leader}"),
+        }
+    }
+}
+
+/// Competes for the state lock and renews it as a lease while leading.
+#[derive(Debug)]
+pub struct LeaderElection<'a, S: StateStore> {
+    /// State store holding the lock.
+    state_store: &'a S,
+    /// Identifier of this watcher.
+    holder: String,
+    /// Lease duration in seconds.
+    lease_secs: u64,
+    /// Lock held while leading.
+    lock_id: Option<String>,
+}
+
+impl<'a, S: StateStore> LeaderElection<'a, S> {
+    /// Creates an election identified by host, process and a random suffix.
+    #[must_use]
+    pub fn new(state_store: &'a S, lease_secs: u64) -> Self {
+        Self {
+            state_store,
+            holder: generate_holder_id(),
+            lease_secs: lease_secs.max(1),
+            lock_id: None,
+        }
+    }
+
+    /// Sets the identifier this watcher competes as.
+    #[must_use]
+    pub fn with_holder(mut self, holder: impl Into<String>) -> Self {
+        self.holder = holder.into();
+        self
+    }
+
+    /// Returns the identifier this watcher competes as.
+    #[must_use]
+    pub fn holder(&self) -> &str {
+        &self.holder
+    }
+
+    /// Returns true if this watcher held the lease at the last attempt.
+    #[must_use]
+    pub const fn is_leader(&self) -> bool {
+        self.lock_id.is_some()
+    }
+
+    /// Renews the lease if leading, or tries to acquire it otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the state store cannot be reached.
+    pub async fn campaign(&mut self) -> Result<Role> {
+        if self.is_leader() {
+            match self.renew().await {
+                Ok(()) => return Ok(Role::Leader),
+                Err(e) if self.is_leader() => return Err(e),
+                Err(e) => warn!("Lost leadership: {e}"),
+            }
+        }
+
+        match self.state_store.acquire_lock(&self.holder).await {
+            Ok(lock) => {
+                self.lock_id = Some(lock.lock_id);
+                self.renew().await?;
+                info!(
+                    "{} is now the leader (lease {}s)",
+                    self.holder, self.lease_secs
+                );
+                Ok(Role::Leader)
+            }
+            Err(e) => match e.root() {
+                HalldyllError::State(StateError::LockedByOther { holder, .. }) => {
+                    Ok(Role::Follower {
+                        leader: holder.clone(),
+                    })
+                }
+                _ => Err(e),
+            },
+        }
+    }
+
+    /// Runs `work` while renewing the lease in the background.
+    ///
+    /// The work is abandoned if the lease cannot be renewed, so two watchers
+    /// never act at the same time.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this watcher is not the leader or the lease could
+    /// not be renewed before `work` finished.
+    pub async fn lead<F: Future>(&mut self, work: F) -> Result<F::Output> {
+        if !self.is_leader() {
+            return Err(HalldyllError::internal("This watcher is not the leader"));
+        }
+
+        let renew_every = Duration::from_secs((self.lease_secs / 3).max(1));
+        tokio::pin!(work);
+
+        loop {
+            tokio::select! {
+                output = &mut work => return Ok(output),
+                () = tokio::time::sleep(renew_every) => self.renew().await?,
+            }
+        }
+    }
+
+    /// Releases the lease so a follower can take over immediately.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the lock cannot be released.
+    pub async fn resign(&mut self) -> Result<()> {
+        if let Some(lock_id) = self.lock_id.take() {
+            self.state_store.release_lock(&lock_id).await?;
+            info!("{} resigned leadership", self.holder);
+        }
+        Ok(())
+    }
+
+    /// Extends the held lease, forgetting it if it was lost.
+    async fn renew(&mut self) -> Result<()> {
+        let Some(lock_id) = &self.lock_id else {
+            return Ok(());
+        };
+
+        let ttl = i64::try_from(self.lease_secs).unwrap_or(i64::MAX);
+        match self.state_store.renew_lock(lock_id, ttl).await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                if matches!(e.root(), HalldyllError::State(StateError::LockLost { .. })) {
+                    self.lock_id = None;
+                }
+                Err(e)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::LocalStateStore;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_follower_takes_over_after_resign() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = LocalStateStore::with_base_dir(temp_dir.path());
+
+        let mut first = LeaderElection::new(&store, 30).with_holder("watcher-a");
+        let mut second = LeaderElection::new(&store, 30).with_holder("watcher-b");
+
+        assert_eq!(first.campaign().await.unwrap(), Role::Leader);
+        assert_eq!(
+            second.campaign().await.unwrap(),
+            Role::Follower {
+                leader: String::from("watcher-a")
+            }
+        );
+        assert_eq!(first.campaign().await.unwrap(), Role::Leader);
+        assert_eq!(first.lead(async { 7 }).await.unwrap(), 7);
+
+        first.resign().await.unwrap();
+        assert_eq!(second.campaign().await.unwrap(), Role::Leader);
+        assert!(!first.is_leader());
+        assert!(first.lead(async {}).await.is_err());
+    }
+}
@@ -2,42 +2,43 @@
 // Strict linting - Dangerous or non-idiomatic practices are forbidden
 // ============================================================================
 
-#![deny(warnings)]                    // All warnings are treated as errors
-#![deny(unsafe_code)]                 // Unsafe code is forbidden
-#![deny(missing_docs)]                // All public items must be documented
-#![deny(dead_code)]                   // Unused code is forbidden
-#![deny(non_camel_case_types)]        // Types must follow CamelCase convention
+#![deny(warnings)] // All warnings are treated as errors
+#![deny(unsafe_code)] // Unsafe code is forbidden
+#![deny(missing_docs)] // All public items must be documented
+#![deny(dead_code)] // Unused code is forbidden
+#![deny(non_camel_case_types)] // Types must follow CamelCase convention
 
 // Additional strictness - Leave nothing unchecked
-#![deny(unused_imports)]              // Unused imports are forbidden
-#![deny(unused_variables)]            // Unused variables are forbidden
-#![deny(unused_must_use)]             // Must handle Result and Option explicitly
-#![deny(non_snake_case)]              // Variables and functions must be snake_case
-#![deny(non_upper_case_globals)]      // Constants must be UPPER_CASE
-#![deny(nonstandard_style)]           // Non-standard code style is forbidden
-#![forbid(unsafe_op_in_unsafe_fn)]    // Unsafe ops in unsafe fns are forbidden
+#![deny(unused_imports)] // Unused imports are forbidden
+#![deny(unused_variables)] // Unused variables are forbidden
+#![deny(unused_must_use)] // Must handle Result and Option explicitly
+#![deny(non_snake_case)] // Variables and functions must be snake_case
+#![deny(non_upper_case_globals)] // Constants must be UPPER_CASE
+#![deny(nonstandard_style)] // Non-standard code style is forbidden
+#![forbid(unsafe_op_in_unsafe_fn)] // Unsafe ops in unsafe fns are forbidden
 
 // Clippy lints (warnings only)
-#![warn(clippy::all)]                 // All standard Clippy lints
-#![warn(clippy::pedantic)]            // Very strict Clippy lints
-#![warn(clippy::nursery)]             // Experimental lints
-#![warn(clippy::unwrap_used)]         // unwrap() warning
-#![warn(clippy::expect_used)]         // expect() warning
-#![warn(clippy::panic)]               // panic!() warning
-#![warn(clippy::print_stdout)]        // println!() warning
-#![warn(clippy::todo)]                // TODO warning
-#![warn(clippy::unimplemented)]       // unimplemented!() warning
+#![warn(clippy::all)] // All standard Clippy lints
+#![warn(clippy::pedantic)] // Very strict Clippy lints
+#![warn(clippy::nursery)] // Experimental lints
+#![warn(clippy::unwrap_used)] // unwrap() warning
+#![warn(clippy::expect_used)] // expect() warning
+#![warn(clippy::panic)] // panic!() warning
+#![warn(clippy::print_stdout)] // println!() warning
+#![warn(clippy::todo)] // TODO warning
+#![warn(clippy::unimplemented)] // unimplemented!() warning
 #![warn(clippy::missing_const_for_fn)] // Force const when possible
-#![warn(clippy::unwrap_in_result)]    // unwrap() in Result warning
-#![warn(clippy::module_inception)]    // Module with same name as crate warning
-#![warn(clippy::redundant_clone)]     // Useless clones warning
-#![warn(clippy::shadow_unrelated)]    // Shadowing unrelated variables warning
-#![warn(clippy::too_many_arguments)]  // Limit function arguments
+#![warn(clippy::unwrap_in_result)] // unwrap() in Result warning
+#![warn(clippy::module_inception)] // Module with same name as crate warning
+#![warn(clippy::redundant_clone)] // Useless clones warning
+#![warn(clippy::shadow_unrelated)] // Shadowing unrelated variables warning
+#![warn(clippy::too_many_arguments)] // Limit function arguments
 #![warn(clippy::cognitive_complexity)] // Limit cognitive complexity
 
 // Safety and robustness lints
-#![deny(overflowing_literals)]        // Overflowing literals are forbidden
-#![deny(arithmetic_overflow)]         // Arithmetic overflow is forbidden
+#![deny(overflowing_literals)] // Overflowing literals are forbidden
+#![deny(arithmetic_overflow)]
+// Arithmetic overflow is forbidden
 
 // ============================================================================
 // Crate Documentation
@@ -71,6 +72,16 @@
 //! - [`runpod`]: `RunPod` API client and provisioning
 //! - [`planner`]: Diff computation and execution planning
 //! - [`reconciler`]: State reconciliation engine
+//! - [`loadbalancer`]: Load balancer manifests for replicated pods
+//! - [`bundle`]: Auditable deployment bundles
+//! - [`lockfile`]: Locked GPU types, image digests and model revisions
+//! - [`registry`]: Container registry digest lookups
+//! - [`failover`]: Active/passive failover to secondary data centers
+//! - [`release`]: Blue/green releases with an explicit promote step
+//! - [`watch`]: Continuous autoscaling and reconciliation loop
+//! - [`notify`]: Delivery of watch alerts to webhooks
+//! - [`leader`]: Leader election between watchers
+//! - [`version`]: Version information and update checks
 //! - [`cli`]: Command-line interface
 //!
 //! ## Example
@@ -95,13 +106,26 @@
 // Modules
 // ============================================================================
 
+pub mod audit;
+pub mod bundle;
 pub mod cli;
 pub mod config;
+pub mod egress;
 pub mod error;
+pub mod failover;
+pub mod leader;
+pub mod loadbalancer;
+pub mod lockfile;
+pub mod notify;
 pub mod planner;
+pub mod platform;
 pub mod reconciler;
+pub mod registry;
+pub mod release;
 pub mod runpod;
 pub mod state;
+pub mod version;
+pub mod watch;
 
 // ============================================================================
 // Re-exports
@@ -109,11 +133,14 @@ pub mod state;
 
 pub use cli::{Cli, Commands, OutputFormatter};
 pub use config::{ConfigHasher, ConfigParser, ConfigValidator, DeployConfig};
-pub use error::{HalldyllError, Result};
+pub use error::{ApiErrorKind, ErrorContext, HalldyllError, Result, ResultExt};
 pub use planner::{DeploymentPlan, DiffEngine, PlanExecutor};
-pub use reconciler::{DriftReport, ReconciliationResult, Reconciler};
+pub use reconciler::{DriftReport, EndpointDrift, ModelDrift, Reconciler, ReconciliationResult};
 pub use runpod::{
-    HealthChecker, PodObserver, PodProvisioner, RunPodClient,
-    PodExecutor, CommandResult, ModelSetupResult, EngineStartResult, PostProvisionResult,
+    CommandResult, EngineStartResult, HealthChecker, ModelSetupResult, PodExecutor, PodObserver,
+    PodProvisioner, PostProvisionResult, RunPodClient,
 };
-pub use state::{DeploymentState, LocalStateStore, S3StateStore, StateStore};
+#[cfg(feature = "s3")]
+pub use state::S3StateStore;
+pub use state::{DeploymentState, HttpStateStore, LocalStateStore, StateStore};
+pub use watch::{WatchTick, Watcher};
@@ -0,0 +1,375 @@
+//! Load balancer manifest generation.
+//!
+//! This module renders nginx, Caddy, or `HAProxy` configuration listing the
+//! `RunPod` proxy URLs of a pod's running replicas, so clients can hit one
+//! stable address. Manifests are regenerated after every apply/reconcile and
+//! only rewritten when their content changes.
+//!
+//! During replacements the outgoing replica is kept in the manifest marked as
+//! draining, so the proxy stops sending it new requests before it is deleted.
+//!
+//! The `RunPod` proxy routes by Host header and SNI, so every replica is sent
+//! its own host: nginx balances over one local listener per replica, `HAProxy`
+//! names each server after its host and sends that name as `Host`, and Caddy
+//! takes both from the upstream address.
+
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use tracing::{debug, info};
+
+use crate::config::{DeployConfig, LoadBalancerConfig, LoadBalancerFormat, PodConfig};
+use crate::error::Result;
+use crate::planner::DiffEngine;
+use crate::runpod::ObservedPod;
+use crate::state::DeploymentState;
+
+/// A single upstream server in a manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Upstream {
+    /// Replica instance name.
+    pub name: String,
+    /// Proxy host (without scheme).
+    pub host: String,
+    /// Whether the replica is about to go away and takes no new requests.
+    pub draining: bool,
+}
+
+/// Generator for load balancer manifests.
+#[derive(Debug, Default)]
+pub struct ManifestGenerator {
+    /// Replica instance names to mark as draining.
+    draining: Vec<String>,
+}
+
+impl ManifestGenerator {
+    /// Creates a new manifest generator.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            draining: Vec::new(),
+        }
+    }
+
+    /// Marks replicas as draining in the generated manifests.
+    #[must_use]
+    pub fn with_draining(mut self, names: impl IntoIterator<Item = String>) -> Self {
+        self.draining.extend(names);
+        self
+    }
+
+    /// Writes manifests for every pod with a `load_balancer` block.
+    ///
+    /// Returns the paths of manifests whose content changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a manifest cannot be written.
+    pub fn write_all(
+        &self,
+        config: &DeployConfig,
+        state: Option<&DeploymentState>,
+        observed: &[ObservedPod],
+    ) -> Result<Vec<PathBuf>> {
+        let mut written = Vec::new();
+
+        for pod in &config.pods {
+            let Some(lb) = &pod.load_balancer else {
+                continue;
+            };
+
+            let upstreams = self.upstreams(pod, lb, state, observed);
+            let content = self.render(config, pod, lb, &upstreams);
+            let path = Self::output_path(pod, lb);
+
+            if std::fs::read_to_string(&path).is_ok_and(|existing| existing == content) {
+                debug!("Load balancer manifest for {} is up to date", pod.name);
+                continue;
+            }
+
+            if let Some(parent) = path.parent()
+                && !parent.as_os_str().is_empty()
+            {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&path, content)?;
+            info!(
+                "Wrote {} upstream(s) for {} to {}",
+                upstreams.len(),
+                pod.name,
+                path.display()
+            );
+            written.push(path);
+        }
+
+        Ok(written)
+    }
+
+    /// Renders the manifest for a pod.
+    #[must_use]
+    pub fn render(
+        &self,
+        config: &DeployConfig,
+        pod: &PodConfig,
+        lb: &LoadBalancerConfig,
+        upstreams: &[Upstream],
+    ) -> String {
+        let name = format!(
+            "{}_{}_{}",
+            config.project.name, config.project.environment, pod.name
+        )
+        .replace('-', "_");
+        let listen = lb.listen_port;
+
+        let mut out = format!(
+            "# Generated by halldyll for {}/{}. Do not edit by hand.\n",
+            config.qualified_name(),
+            pod.name
+        );
+        if upstreams.is_empty() {
+            out.push_str("# No running replicas were found.\n");
+        }
+
+        match lb.format {
+            LoadBalancerFormat::Nginx => {
+                let socket = |upstream: &Upstream| {
+                    format!("unix:/var/run/halldyll_{name}_{}.sock", upstream.name)
+                };
+                let _ = writeln!(out, "upstream {name} {{");
+                for upstream in upstreams {
+                    if upstream.draining {
+                        let _ = writeln!(
+                            out,
+                            "    server {} down; # {} (draining)",
+                            socket(upstream),
+                            upstream.name
+                        );
+                    } else {
+                        let _ =
+                            writeln!(out, "    server {}; # {}", socket(upstream), upstream.name);
+                    }
+                }
+                let _ = writeln!(out, "}}\n");
+                let _ = writeln!(out, "server {{");
+                let _ = writeln!(out, "    listen {listen};");
+                let _ = writeln!(out, "    location / {{");
+                let _ = writeln!(out, "        proxy_pass http://{name};");
+                let _ = writeln!(out, "        proxy_set_header Host $host;");
+                let _ = writeln!(out, "        proxy_buffering off;");
+                let _ = writeln!(out, "    }}");
+                let _ = writeln!(out, "}}");
+                // One local listener per replica, so each gets its own Host and SNI
+                for upstream in upstreams {
+                    let _ = writeln!(out, "\nserver {{ # {}", upstream.name);
+                    let _ = writeln!(out, "    listen {};", socket(upstream));
+                    let _ = writeln!(out, "    location / {{");
+                    let _ = writeln!(out, "        proxy_pass https://{};", upstream.host);
+                    let _ = writeln!(out, "        proxy_ssl_server_name on;");
+                    let _ = writeln!(out, "        proxy_set_header Host {};", upstream.host);
+                    let _ = writeln!(out, "        proxy_buffering off;");
+                    let _ = writeln!(out, "    }}");
+                    let _ = writeln!(out, "}}");
+                }
+            }
+            LoadBalancerFormat::Caddy => {
+                for upstream in upstreams.iter().filter(|u| u.draining) {
+                    let _ = writeln!(out, "# Draining: {} ({})", upstream.name, upstream.host);
+                }
+                let _ = writeln!(out, ":{listen} {{");
+                let _ = write!(out, "    reverse_proxy");
+                for upstream in upstreams.iter().filter(|u| !u.draining) {
+                    let _ = write!(out, " https://{}", upstream.host);
+                }
+                let _ = writeln!(out, " {{");
+                let _ = writeln!(out, "        lb_policy round_robin");
+                let _ = writeln!(out, "        header_up Host {{upstream_hostport}}");
+                let _ = writeln!(out, "    }}");
+                let _ = writeln!(out, "}}");
+            }
+            LoadBalancerFormat::Haproxy => {
+                let _ = writeln!(out, "frontend {name}_front");
+                let _ = writeln!(out, "    bind *:{listen}");
+                let _ = writeln!(out, "    mode http");
+                let _ = writeln!(out, "    default_backend {name}\n");
+                let _ = writeln!(out, "backend {name}");
+                let _ = writeln!(out, "    mode http");
+                let _ = writeln!(out, "    balance roundrobin");
+                // Servers are named after their host, which is sent as Host
+                let _ = writeln!(out, "    http-send-name-header Host");
+                for upstream in upstreams {
+                    let _ = writeln!(
+                        out,
+                        "    server {host} {host}:443 ssl verify none sni str({host}) check-sni {host} check{} # {}",
+                        if upstream.draining { " weight 0" } else { "" },
+                        if upstream.draining {
+                            format!("{} (draining)", upstream.name)
+                        } else {
+                            upstream.name.clone()
+                        },
+                        host = upstream.host
+                    );
+                }
+            }
+        }
+
+        out
+    }
+
+    /// Collects the running replicas of a pod as upstreams.
+    fn upstreams(
+        &self,
+        pod: &PodConfig,
+        lb: &LoadBalancerConfig,
+        state: Option<&DeploymentState>,
+        observed: &[ObservedPod],
+    ) -> Vec<Upstream> {
+        let Some(port) = lb.port.or_else(|| pod.http_ports().first().copied()) else {
+            return vec![];
+        };
+
+        pod.instances(DiffEngine::replica_count(pod, state))
+            .into_iter()
+            .filter_map(|instance| {
+                let replica = observed.iter().find(|p| {
+                    p.pod_name.as_deref() == Some(instance.name.as_str()) && p.is_running()
+                })?;
+                let url =
+                    replica.endpoints.get(&port).cloned().unwrap_or_else(|| {
+                        format!("https://{}-{port}.proxy.runpod.net", replica.id)
+                    });
+                Some(Upstream {
+                    draining: self.draining.contains(&instance.name),
+                    name: instance.name,
+                    host: url
+                        .trim_start_matches("https://")
+                        .trim_end_matches('/')
+                        .to_string(),
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the output path for a pod's manifest.
+    fn output_path(pod: &PodConfig, lb: &LoadBalancerConfig) -> PathBuf {
+        lb.output.as_ref().map_or_else(
+            || {
+                PathBuf::from(".halldyll").join("lb").join(format!(
+                    "{}.{}",
+                    pod.name,
+                    lb.format.extension()
+                ))
+            },
+            PathBuf::from,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses a config whose pod balances two replicas in `format`.
+    fn config_with_format(format: &str) -> DeployConfig {
+        serde_yaml::from_str(&YAML.replace("format: haproxy", &format!("format: {format}")))
+            .unwrap()
+    }
+
+    /// Two replicas, the second one draining.
+    fn upstreams() -> Vec<Upstream> {
+        vec![
+            Upstream {
+                name: String::from("inference-0"),
+                host: String::from("abc-8000.proxy.runpod.net"),
+                draining: false,
+            },
+            Upstream {
+                name: String::from("inference-1"),
+                host: String::from("def-8000.proxy.runpod.net"),
+                draining: true,
+            },
+        ]
+    }
+
+    const YAML: &str = r#"
+project:
+  name: demo
+  environment: prod
+state:
+  backend: local
+pods:
+  - name: inference
+    replicas: 2
+    gpu:
+      type: "NVIDIA A40"
+    ports:
+      - "8000/http"
+    runtime:
+      image: "vllm/vllm-openai:v0.6.0"
+    load_balancer:
+      format: haproxy
+"#;
+
+    #[test]
+    fn test_render_haproxy_lists_upstreams() {
+        let config = config_with_format("haproxy");
+        let pod = &config.pods[0];
+        let lb = pod.load_balancer.as_ref().unwrap();
+
+        let manifest = ManifestGenerator::new().render(&config, pod, lb, &upstreams());
+
+        assert!(manifest.contains("backend demo_prod_inference"));
+        assert!(manifest.contains("bind *:8080"));
+        assert!(manifest.contains("http-send-name-header Host"));
+        assert!(
+            manifest.contains("server abc-8000.proxy.runpod.net abc-8000.proxy.runpod.net:443")
+        );
+        assert!(
+            manifest.contains("server def-8000.proxy.runpod.net def-8000.proxy.runpod.net:443")
+        );
+        assert!(manifest.contains(
+            "check-sni def-8000.proxy.runpod.net check weight 0 # inference-1 (draining)"
+        ));
+        assert!(manifest.contains("check-sni abc-8000.proxy.runpod.net check # inference-0\n"));
+        assert_eq!(
+            ManifestGenerator::output_path(pod, lb),
+            PathBuf::from(".halldyll/lb/inference.cfg")
+        );
+    }
+
+    #[test]
+    fn test_render_nginx_sends_replica_host() {
+        let config = config_with_format("nginx");
+        let pod = &config.pods[0];
+        let lb = pod.load_balancer.as_ref().unwrap();
+
+        let manifest = ManifestGenerator::new().render(&config, pod, lb, &upstreams());
+
+        let socket = "unix:/var/run/halldyll_demo_prod_inference_inference-0.sock";
+        assert!(manifest.contains(&format!("    server {socket}; # inference-0")));
+        assert!(manifest.contains("_inference-1.sock down; # inference-1 (draining)"));
+        assert!(manifest.contains(
+            "    listen 8080;\n    location / {\n        proxy_pass http://demo_prod_inference;"
+        ));
+        assert!(manifest.contains(&format!("    listen {socket};\n    location / {{\n        proxy_pass https://abc-8000.proxy.runpod.net;")));
+        assert!(manifest.contains("proxy_set_header Host abc-8000.proxy.runpod.net;"));
+        assert!(manifest.contains("proxy_set_header Host def-8000.proxy.runpod.net;"));
+        assert!(!manifest.contains("$proxy_host"));
+    }
+
+    #[test]
+    fn test_render_caddy_skips_draining() {
+        let config = config_with_format("caddy");
+        let pod = &config.pods[0];
+        let lb = pod.load_balancer.as_ref().unwrap();
+
+        let manifest = ManifestGenerator::new().render(&config, pod, lb, &upstreams());
+
+        assert!(manifest.contains(":8080 {"));
+        assert!(manifest.contains("    reverse_proxy https://abc-8000.proxy.runpod.net {"));
+        assert!(manifest.contains("# Draining: inference-1 (def-8000.proxy.runpod.net)"));
+        assert!(manifest.contains("header_up Host {upstream_hostport}"));
+        assert_eq!(
+            ManifestGenerator::output_path(pod, lb),
+            PathBuf::from(".halldyll/lb/inference.caddy")
+        );
+    }
+}
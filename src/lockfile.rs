@@ -0,0 +1,276 @@
+//! Lockfile for resolved external references.
+//!
+//! `halldyll.lock` sits next to the configuration and records what the
+//! configuration's external references resolved to when `halldyll lock` ran:
+//! GPU type names to `RunPod` IDs, image tags to digests, and `HuggingFace`
+//! repositories to commits. Loading a configuration applies the lock, so
+//! plans and applies on any machine deploy the same images and models.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use crate::config::{DeployConfig, ModelProvider};
+use crate::error::{ErrorContext, HalldyllError, Result, ResultExt};
+
+/// Lockfile name, next to the configuration file.
+pub const LOCKFILE_NAME: &str = "halldyll.lock";
+
+/// Lockfile format version.
+pub const LOCKFILE_VERSION: u32 = 1;
+
+/// Resolved external references of a configuration.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lockfile {
+    /// Lockfile format version.
+    pub version: u32,
+    /// When the lock was last written.
+    #[serde(default)]
+    pub generated_at: Option<DateTime<Utc>>,
+    /// GPU type names (as configured) to `RunPod` GPU type IDs.
+    #[serde(default)]
+    pub gpu_types: BTreeMap<String, String>,
+    /// Image references (as configured) to content digests.
+    #[serde(default)]
+    pub images: BTreeMap<String, String>,
+    /// `HuggingFace` repositories to commit hashes.
+    #[serde(default)]
+    pub models: BTreeMap<String, String>,
+}
+
+/// External references of a configuration that can be locked.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LockReferences {
+    /// GPU type names, including fallbacks.
+    pub gpu_types: BTreeSet<String>,
+    /// Image references not already pinned by digest.
+    pub images: BTreeSet<String>,
+    /// `HuggingFace` repositories without an explicit revision.
+    pub models: BTreeSet<String>,
+}
+
+impl LockReferences {
+    /// Collects the lockable references of a configuration.
+    #[must_use]
+    pub fn from_config(config: &DeployConfig) -> Self {
+        let mut references = Self::default();
+
+        for pod in &config.pods {
+            // `type: cheapest` is picked by price at creation, never locked
+            if !pod.gpu.is_auto() {
+                references.gpu_types.insert(pod.gpu.gpu_type.clone());
+            }
+            references
+                .gpu_types
+                .extend(pod.gpu.fallback.iter().cloned());
+
+            if !pod.runtime.image.contains('@') {
+                references.images.insert(pod.runtime.image.clone());
+            }
+
+            references.models.extend(
+                pod.models
+                    .iter()
+                    .filter(|m| m.provider == ModelProvider::Huggingface && m.revision.is_none())
+                    .filter_map(|m| m.repo.clone()),
+            );
+        }
+
+        references
+    }
+
+    /// Returns true if nothing is referenced.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.gpu_types.is_empty() && self.images.is_empty() && self.models.is_empty()
+    }
+
+    /// Lists all references for display.
+    #[must_use]
+    pub fn names(&self) -> Vec<String> {
+        self.gpu_types
+            .iter()
+            .map(|name| format!("gpu {name}"))
+            .chain(self.images.iter().map(|image| format!("image {image}")))
+            .chain(self.models.iter().map(|repo| format!("model {repo}")))
+            .collect()
+    }
+}
+
+impl Lockfile {
+    /// Creates an empty lockfile.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            version: LOCKFILE_VERSION,
+            generated_at: None,
+            gpu_types: BTreeMap::new(),
+            images: BTreeMap::new(),
+            models: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the lockfile path for a configuration file.
+    #[must_use]
+    pub fn path_for(config_file: &Path) -> PathBuf {
+        config_file
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .join(LOCKFILE_NAME)
+    }
+
+    /// Loads a lockfile, returning `None` if it does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed, or was written
+    /// by a newer format version.
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let reading =
+            || ErrorContext::new("reading lockfile").with_resource(path.display().to_string());
+        let content = std::fs::read_to_string(path).context(reading)?;
+        let lock: Self = serde_yaml::from_str(&content)
+            .map_err(|e| HalldyllError::internal(format!("Invalid lockfile: {e}")))
+            .context(reading)?;
+
+        if lock.version > LOCKFILE_VERSION {
+            return Err(HalldyllError::internal(format!(
+                "Lockfile version {} is newer than supported ({LOCKFILE_VERSION}); upgrade halldyll",
+                lock.version
+            )))
+            .context(reading);
+        }
+
+        Ok(Some(lock))
+    }
+
+    /// Writes the lockfile.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let writing =
+            || ErrorContext::new("writing lockfile").with_resource(path.display().to_string());
+        let yaml = serde_yaml::to_string(self)
+            .map_err(|e| HalldyllError::internal(format!("Failed to render lockfile: {e}")))
+            .context(writing)?;
+
+        std::fs::write(
+            path,
+            format!("# Generated by `halldyll lock`. Commit this file; refresh with `halldyll lock --update`.\n{yaml}"),
+        )
+        .context(writing)
+    }
+
+    /// Returns the references of `config` that this lock does not cover.
+    #[must_use]
+    pub fn missing(&self, config: &DeployConfig) -> LockReferences {
+        let mut references = LockReferences::from_config(config);
+        references
+            .gpu_types
+            .retain(|name| !self.gpu_types.contains_key(name));
+        references
+            .images
+            .retain(|image| !self.images.contains_key(image));
+        references
+            .models
+            .retain(|repo| !self.models.contains_key(repo));
+        references
+    }
+
+    /// Drops entries `config` no longer references.
+    pub fn retain_used(&mut self, config: &DeployConfig) {
+        let used = LockReferences::from_config(config);
+        self.gpu_types
+            .retain(|name, _| used.gpu_types.contains(name));
+        self.images.retain(|image, _| used.images.contains(image));
+        self.models.retain(|repo, _| used.models.contains(repo));
+    }
+
+    /// Replaces the configuration's references with their locked values.
+    ///
+    /// Images become `<image>@<digest>`, GPU types their IDs, and models
+    /// without an explicit revision get the locked commit. References the
+    /// lock does not cover are left as configured.
+    pub fn apply(&self, config: &mut DeployConfig) {
+        for pod in &mut config.pods {
+            if let Some(id) = self.gpu_types.get(&pod.gpu.gpu_type) {
+                pod.gpu.gpu_type.clone_from(id);
+            }
+            for fallback in &mut pod.gpu.fallback {
+                if let Some(id) = self.gpu_types.get(fallback.as_str()) {
+                    fallback.clone_from(id);
+                }
+            }
+
+            if let Some(digest) = self.images.get(&pod.runtime.image) {
+                pod.runtime.image = format!("{}@{digest}", pod.runtime.image);
+            }
+
+            for model in &mut pod.models {
+                if model.revision.is_none()
+                    && let Some(revision) =
+                        model.repo.as_ref().and_then(|repo| self.models.get(repo))
+                {
+                    model.revision = Some(revision.clone());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_apply_and_missing() {
+        let yaml = r#"
+project:
+  name: proj
+state:
+  backend: local
+pods:
+  - name: llm
+    gpu:
+      type: A40
+      fallback: ["A6000"]
+    runtime:
+      image: "vllm/vllm-openai:v0.6.0"
+    models:
+      - id: llama
+        provider: huggingface
+        repo: meta-llama/Llama-3
+"#;
+        let mut config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+
+        let mut lock = Lockfile::new();
+        lock.gpu_types
+            .insert(String::from("A40"), String::from("NVIDIA A40"));
+        lock.images.insert(
+            String::from("vllm/vllm-openai:v0.6.0"),
+            String::from("sha256:abc"),
+        );
+        lock.models
+            .insert(String::from("meta-llama/Llama-3"), String::from("0123abcd"));
+        lock.images
+            .insert(String::from("stale:1"), String::from("sha256:old"));
+
+        assert_eq!(lock.missing(&config).names(), ["gpu A6000"]);
+        lock.retain_used(&config);
+        assert!(!lock.images.contains_key("stale:1"));
+
+        lock.apply(&mut config);
+        let pod = &config.pods[0];
+        assert_eq!(pod.gpu.gpu_type, "NVIDIA A40");
+        assert_eq!(pod.gpu.fallback, ["A6000"]);
+        assert_eq!(pod.runtime.image, "vllm/vllm-openai:v0.6.0@sha256:abc");
+        assert_eq!(pod.models[0].revision.as_deref(), Some("0123abcd"));
+    }
+}
@@ -2,22 +2,53 @@
 //!
 //! This is the main entrypoint for the halldyll command-line tool.
 
+use std::collections::{BTreeMap, HashMap};
 use std::io::Write;
 use std::path::PathBuf;
 use std::process::ExitCode;
+use std::sync::OnceLock;
 
-use halldyll_deploy_pods::cli::{Cli, Commands, OutputFormatter, StateCommands};
+use halldyll_deploy_pods::audit::{Workspace, discover_configs, find_orphans};
+use halldyll_deploy_pods::bundle::Bundle;
+use halldyll_deploy_pods::cli::{
+    Cli, Commands, EnvCommands, FailoverCommands, MaintenanceCommands, OutputFormat,
+    OutputFormatter, ReadyPod, StateCommands, TemplateCommands,
+};
 use halldyll_deploy_pods::config::{
-    find_config_file, ConfigHasher, ConfigParser, ConfigValidator, StateBackend,
+    CloudType, ConfigHasher, ConfigOverride, ConfigParser, ConfigValidator, DEFAULT_TAG_NAMESPACE,
+    DeployConfig, ProjectConfig, StateBackend, apply_overrides, find_config_file,
+};
+use halldyll_deploy_pods::egress::command_egress;
+use halldyll_deploy_pods::error::{
+    ConfigError, ErrorContext, HalldyllError, PlanError, ReconcileError, Result,
 };
-use halldyll_deploy_pods::error::Result;
-use halldyll_deploy_pods::planner::{DeploymentPlan, DiffEngine};
+use halldyll_deploy_pods::failover::{FailoverManager, FailoverResult};
+use halldyll_deploy_pods::leader::{LeaderElection, Role};
+use halldyll_deploy_pods::loadbalancer::ManifestGenerator;
+use halldyll_deploy_pods::lockfile::{LOCKFILE_NAME, Lockfile};
+use halldyll_deploy_pods::planner::{
+    ActionType, CostReport, DeadlinePolicy, DeploymentPlan, DiffEngine, ExecutionResult, GpuPrices,
+    PreflightReport, Runway, ScaleDecision, SloReport,
+};
+use halldyll_deploy_pods::platform;
 use halldyll_deploy_pods::reconciler::Reconciler;
-use halldyll_deploy_pods::runpod::{HealthChecker, PodObserver, PodProvisioner, RunPodClient};
-use halldyll_deploy_pods::state::{DeploymentState, LocalStateStore, S3StateStore, StateStore};
+use halldyll_deploy_pods::registry::RegistryClient;
+use halldyll_deploy_pods::release::{ReleaseManager, ReleaseResult};
+use halldyll_deploy_pods::runpod::{
+    EndpointManager, EngineProbe, HealthChecker, ObservedPod, OutputChunk, OutputStream,
+    PodExecutor, PodLogs, PodObserver, PodProvisioner, ProjectStatus, RunPodClient,
+    ServerlessEndpoint, Transcript, keys_fingerprint,
+};
+use halldyll_deploy_pods::state::{
+    DeploymentHistoryEntry, DeploymentOperation, DeploymentState, DeploymentStatus, HealthHistory,
+    HttpStateStore, LocalStateStore, MaintenanceState, POD_STATE_FIELDS, PodState, StateStore,
+    StatusCache,
+};
+use halldyll_deploy_pods::version::{VersionInfo, latest_release};
+use halldyll_deploy_pods::watch::Watcher;
 
 use clap::Parser;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 use tracing_subscriber::EnvFilter;
 
 /// Main entrypoint.
@@ -40,7 +71,10 @@ fn main() -> ExitCode {
         Ok(()) => ExitCode::SUCCESS,
         Err(e) => {
             eprintln!("Error: {e}");
-            ExitCode::FAILURE
+            if let Some(hint) = e.hint() {
+                eprintln!("Hint: {hint}");
+            }
+            ExitCode::from(e.exit_code())
         }
     }
 }
@@ -59,29 +93,201 @@ fn init_logging(verbose: bool) {
         .init();
 }
 
+/// Command-line options that apply to every command.
+#[derive(Debug, Default)]
+struct GlobalOptions {
+    /// Where SSH host keys are recorded; `None` when host key checks are skipped.
+    known_hosts: Option<PathBuf>,
+    /// `--set` overrides applied to the configuration before validation.
+    overrides: Vec<ConfigOverride>,
+    /// `--env` environment, overriding the configured one.
+    environment: Option<String>,
+    /// `--var` values of configuration variables.
+    vars: Vec<(String, String)>,
+}
+
+/// Global options, set once at startup.
+static GLOBAL_OPTIONS: OnceLock<GlobalOptions> = OnceLock::new();
+
+/// Returns the global options (defaults if not set yet).
+fn global_options() -> &'static GlobalOptions {
+    GLOBAL_OPTIONS.get_or_init(GlobalOptions::default)
+}
+
 /// Main async entry point.
 async fn run(cli: Cli) -> Result<()> {
     let formatter = OutputFormatter::new(cli.output);
+    let known_hosts = (!cli.insecure_skip_host_key).then(|| {
+        resolve_config_path(cli.config.as_ref())
+            .ok()
+            .and_then(|file| file.parent().map(std::path::Path::to_path_buf))
+            .unwrap_or_default()
+            .join(".halldyll")
+            .join("known_hosts")
+    });
+    let _ = GLOBAL_OPTIONS.set(GlobalOptions {
+        known_hosts,
+        overrides: cli.overrides.clone(),
+        environment: cli.environment.clone(),
+        vars: cli.vars.clone(),
+    });
 
     match cli.command {
         Commands::Init { path, force } => cmd_init(&path, force),
         Commands::Validate { warnings } => cmd_validate(cli.config.as_ref(), warnings, &formatter),
-        Commands::Plan { detailed } => cmd_plan(cli.config.as_ref(), detailed, &formatter).await,
-        Commands::Apply { yes, continue_on_error } => {
-            cmd_apply(cli.config.as_ref(), yes, continue_on_error, &formatter).await
+        Commands::Check => cmd_check(cli.config.as_ref(), &cli.overrides, &formatter).await,
+        Commands::Plan {
+            detailed,
+            refresh,
+            out_file,
+        } => {
+            cmd_plan(
+                cli.config.as_ref(),
+                detailed,
+                refresh,
+                &formatter.with_out_file(out_file),
+            )
+            .await
         }
-        Commands::Status { detailed, health } => {
-            cmd_status(cli.config.as_ref(), detailed, health, &formatter).await
+        Commands::Apply {
+            yes,
+            blue_green: true,
+            ..
+        } => cmd_release(cli.config.as_ref(), ReleaseStep::Deploy, yes).await,
+        Commands::Apply {
+            yes,
+            continue_on_error,
+            deadline,
+            on_deadline,
+            parallelism,
+            replace,
+            ..
+        } => {
+            let deadline = deadline.map(|d| (tokio::time::Instant::now() + d, on_deadline));
+            cmd_apply(
+                cli.config.as_ref(),
+                yes,
+                continue_on_error,
+                deadline,
+                parallelism.into(),
+                &replace,
+                &formatter,
+            )
+            .await
         }
-        Commands::Reconcile { yes, max_attempts } => {
-            cmd_reconcile(cli.config.as_ref(), yes, max_attempts, &formatter).await
+        Commands::Status {
+            detailed,
+            health,
+            cached,
+            out_file,
+        } => {
+            let formatter = formatter.with_out_file(out_file);
+            if cached {
+                cmd_status_cached(cli.config.as_ref(), &formatter).await
+            } else {
+                cmd_status(cli.config.as_ref(), detailed, health, &formatter).await
+            }
         }
+        Commands::Reconcile {
+            yes,
+            max_attempts,
+            replace,
+        } => cmd_reconcile(cli.config.as_ref(), yes, max_attempts, replace, &formatter).await,
         Commands::Destroy { yes, keep_volumes } => {
             cmd_destroy(cli.config.as_ref(), yes, keep_volumes, &formatter).await
         }
-        Commands::Logs { pod, follow, tail } => cmd_logs(cli.config.as_ref(), pod, follow, tail),
-        Commands::Drift => cmd_drift(cli.config.as_ref(), &formatter).await,
-        Commands::State { command } => cmd_state(cli.config.as_ref(), command, &formatter).await,
+        Commands::Logs { pod, follow, tail } => {
+            cmd_logs(cli.config.as_ref(), pod, follow, tail).await
+        }
+        Commands::Exec {
+            pod,
+            timeout,
+            command,
+        } => cmd_exec(cli.config.as_ref(), &pod, timeout, &command).await,
+        command => run_operations(cli.config.as_ref(), command, &formatter).await,
+    }
+}
+
+/// Runs the day-two commands: inspection, scaling, packaging and state management.
+async fn run_operations(
+    config_path: Option<&PathBuf>,
+    command: Commands,
+    formatter: &OutputFormatter,
+) -> Result<()> {
+    match command {
+        Commands::Cost { out_file } => {
+            cmd_cost(config_path, &formatter.clone().with_out_file(out_file)).await
+        }
+        Commands::Slo {
+            pod,
+            window,
+            out_file,
+        } => {
+            cmd_slo(
+                config_path,
+                &pod,
+                window,
+                &formatter.clone().with_out_file(out_file),
+            )
+            .await
+        }
+        Commands::Drift {
+            probe_endpoints,
+            out_file,
+        } => {
+            cmd_drift(
+                config_path,
+                probe_endpoints,
+                &formatter.clone().with_out_file(out_file),
+            )
+            .await
+        }
+        Commands::Watch {
+            interval,
+            no_reconcile,
+            once,
+            leader_election,
+            lease_secs,
+        } => {
+            let lease_secs = leader_election.then_some(lease_secs);
+            cmd_watch(config_path, interval, no_reconcile, once, lease_secs).await
+        }
+        Commands::Package { file, refresh } => cmd_package(config_path, file, refresh).await,
+        Commands::Lock { update } => cmd_lock(config_path, update).await,
+        Commands::Version { check } => {
+            cmd_version(check, formatter).await;
+            Ok(())
+        }
+        Commands::Endpoints { command } => cmd_endpoints(config_path, &command, formatter),
+        Commands::Inventory { project, out_file } => {
+            cmd_inventory(
+                config_path,
+                project,
+                &formatter.clone().with_out_file(out_file),
+            )
+            .await
+        }
+        Commands::AuditOrphans {
+            search,
+            interactive,
+        } => cmd_audit_orphans(config_path, &search, interactive, formatter).await,
+        Commands::Wake { pod, listen } => cmd_wake(config_path, pod, listen).await,
+        Commands::Scale { pod, count } => cmd_scale(config_path, &pod, count).await,
+        Commands::State { command } => cmd_state(config_path, command, formatter).await,
+        Commands::Template { command } => cmd_template(config_path, command).await,
+        Commands::Env { command } => cmd_env(config_path, &command, formatter),
+        Commands::Maintenance { command } => cmd_maintenance(config_path, command).await,
+        Commands::Annotate {
+            pod,
+            annotations,
+            remove,
+        } => cmd_annotate(config_path, &pod, annotations, &remove).await,
+        Commands::Import { pod, runpod_id } => cmd_import(config_path, &pod, &runpod_id).await,
+        Commands::Failover { command } => cmd_failover(config_path, command).await,
+        Commands::Promote { yes } => cmd_release(config_path, ReleaseStep::Promote, yes).await,
+        Commands::Rollback { yes } => cmd_release(config_path, ReleaseStep::Rollback, yes).await,
+        // Deployment lifecycle commands are handled by `run`
+        _ => Ok(()),
     }
 }
 
@@ -95,7 +301,10 @@ fn cmd_init(path: &PathBuf, force: bool) -> Result<()> {
 
     // Check if files exist
     if !force && config_path.exists() {
-        eprintln!("Configuration file already exists: {}", config_path.display());
+        eprintln!(
+            "Configuration file already exists: {}",
+            config_path.display()
+        );
         eprintln!("Use --force to overwrite.");
         return Ok(());
     }
@@ -120,15 +329,17 @@ fn cmd_init(path: &PathBuf, force: bool) -> Result<()> {
     if gitignore_path.exists() {
         let existing = std::fs::read_to_string(&gitignore_path)?;
         if !existing.contains(".env") || !existing.contains(".halldyll") {
+            // Keep the file's existing (possibly CRLF) line endings
+            let eol = platform::line_ending(&existing);
             let mut file = std::fs::OpenOptions::new()
                 .append(true)
                 .open(&gitignore_path)?;
-            writeln!(file, "\n# Halldyll")?;
+            write!(file, "{eol}# Halldyll{eol}")?;
             if !existing.contains(".env") {
-                writeln!(file, ".env")?;
+                write!(file, ".env{eol}")?;
             }
             if !existing.contains(".halldyll") {
-                writeln!(file, ".halldyll/")?;
+                write!(file, ".halldyll/{eol}")?;
             }
             eprintln!("Updated: {}", gitignore_path.display());
         }
@@ -157,16 +368,15 @@ fn cmd_validate(
     let config_file = resolve_config_path(config_path)?;
     info!("Validating configuration: {}", config_file.display());
 
-    // Load .env
-    let parser = ConfigParser::new().with_base_path(
-        config_file
-            .parent()
-            .unwrap_or_else(|| std::path::Path::new(".")),
-    );
+    // Load .env and .env.<environment>
+    let parser = config_parser(&config_file);
     parser.load_dotenv()?;
 
     // Parse config
-    let config = parser.load_file(&config_file)?;
+    let config = apply_overrides(
+        &parser.load_file(&config_file)?,
+        &global_options().overrides,
+    )?;
 
     // Validate
     let validator = ConfigValidator::new();
@@ -193,43 +403,210 @@ fn cmd_validate(
     Ok(())
 }
 
+/// Simulate configuration overrides and check the result without writing anything.
+async fn cmd_check(
+    config_path: Option<&PathBuf>,
+    overrides: &[ConfigOverride],
+    formatter: &OutputFormatter,
+) -> Result<()> {
+    let config_file = resolve_config_path(config_path)?;
+    let mut baseline = parse_config_file(&config_file)?;
+    apply_lockfile(&config_file, &mut baseline)?;
+    let simulated = apply_overrides(&baseline, overrides)?;
+
+    // Prices and the account balance are optional: without them costs are unknown
+    let lookup = async {
+        let client = create_runpod_client()?;
+        let gpu_types = client.list_gpu_types().await?;
+        let prices = GpuPrices::new(&gpu_types, simulated.project.cloud_type);
+
+        let runway = if simulated
+            .guardrails
+            .as_ref()
+            .is_some_and(|g| g.min_runway_hours.is_some())
+        {
+            let account = client.get_account().await?;
+            let observed = PodObserver::new(client)
+                .with_tag_namespace(simulated.project.tag_namespace())
+                .list_project_pods(&simulated.project.name, &simulated.project.environment)
+                .await?;
+            let desired = DiffEngine::desired_instances(&simulated, None);
+            Some(Runway::estimate(&account, &prices, &desired, &observed))
+        } else {
+            None
+        };
+        Ok::<_, HalldyllError>((prices, runway))
+    };
+    let (prices, runway) = match lookup.await {
+        Ok((prices, runway)) => (Some(prices), runway),
+        Err(e) => {
+            warn!("Costs not estimated: {e}");
+            (None, None)
+        }
+    };
+
+    let mut report = PreflightReport::evaluate(&simulated, &baseline, prices.as_ref())
+        .with_overrides(overrides.iter().map(ToString::to_string).collect());
+    if let Some(runway) = &runway {
+        report = report.with_runway(runway, &simulated);
+    }
+
+    eprintln!("{}", formatter.format_preflight(&report));
+
+    if report.passes() {
+        Ok(())
+    } else {
+        Err(PlanError::PreflightFailed {
+            problems: report.problem_count(),
+        }
+        .into())
+    }
+}
+
 /// Show deployment plan.
 async fn cmd_plan(
     config_path: Option<&PathBuf>,
     detailed: bool,
+    refresh: bool,
     formatter: &OutputFormatter,
 ) -> Result<()> {
     let (config, state_store) = load_config_and_state(config_path).await?;
-    let client = create_runpod_client()?;
-    let observer = PodObserver::new(client);
 
     // Load state
     let state = state_store.load().await?;
+    let plan = compute_plan(config_path, &config, state.as_ref(), refresh).await?;
 
-    // Get observed pods
-    let observed_pods = observer
-        .list_project_pods(&config.project.name, &config.project.environment)
+    // Output
+    formatter.emit(&formatter.format_plan(&plan))?;
+
+    if detailed {
+        eprintln!("\nDetailed changes:");
+        for action in &plan.actions {
+            eprintln!(
+                "  {} {} - {}",
+                action.action_type, action.resource_name, action.reason
+            );
+            for change in &action.changes {
+                eprintln!("      ~ {change}");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Lists the project's serverless endpoints on `RunPod`, if it configures or tracks any.
+async fn observed_endpoints(
+    client: &RunPodClient,
+    config: &DeployConfig,
+    state: Option<&DeploymentState>,
+) -> Result<Option<Vec<ServerlessEndpoint>>> {
+    if config.endpoints.is_empty() && state.is_none_or(|s| s.endpoints.is_empty()) {
+        return Ok(None);
+    }
+    let endpoints = EndpointManager::new(client.clone())
+        .list_project(&config.project)
         .await?;
+    Ok(Some(endpoints))
+}
+
+/// Computes the plan for a configuration, observing `RunPod` if `refresh` is set.
+async fn compute_plan(
+    config_path: Option<&PathBuf>,
+    config: &DeployConfig,
+    state: Option<&DeploymentState>,
+    refresh: bool,
+) -> Result<DeploymentPlan> {
+    // Get observed pods, or reconstruct them from state without refreshing
+    let client = if refresh {
+        Some(create_runpod_client()?)
+    } else {
+        None
+    };
+    let observed_pods = if let Some(client) = &client {
+        let observed_pods = PodObserver::new(client.clone())
+            .with_tag_namespace(config.project.tag_namespace())
+            .with_imported_pods(state)
+            .list_project_pods(&config.project.name, &config.project.environment)
+            .await?;
+        let status = ProjectStatus::from_pods(
+            &config.project.name,
+            &config.project.environment,
+            observed_pods.clone(),
+        );
+        cache_status(config_path, config, &status).await;
+        observed_pods
+    } else {
+        state.map(DiffEngine::recorded_pods).unwrap_or_default()
+    };
 
     // Compute diff
     let hasher = ConfigHasher::new();
-    let config_hash = hasher.hash_config(&config);
+    let config_hash = hasher.hash_config(config);
     let diff_engine = DiffEngine::new();
-    let diff = diff_engine.compute_diff(&config, state.as_ref(), &observed_pods);
+    let mut diff = diff_engine.compute_diff(config, state, &observed_pods);
+    if let Some(client) = &client
+        && let Some(endpoints) = observed_endpoints(client, config, state).await?
+    {
+        diff.set_endpoints(diff_engine.compute_endpoint_diff(config, state, Some(&endpoints)));
+    }
 
     // Generate plan
-    let plan = DeploymentPlan::from_diff(&diff, &config, &config_hash);
+    let mut plan = DeploymentPlan::from_diff(&diff, config, &config_hash)
+        .with_state_warnings(state)
+        .with_duration_estimates(state);
+    match &client {
+        Some(client) => plan = price_plan(client, config, state, &observed_pods, plan).await?,
+        None => plan = plan.without_refresh(),
+    }
 
-    // Output
-    let output = formatter.format_plan(&plan);
-    eprintln!("{output}");
+    Ok(plan)
+}
 
-    if detailed {
-        eprintln!("\nDetailed changes:");
-        for action in &plan.actions {
-            eprintln!("  {} {} - {}", action.action_type, action.resource_name, action.reason);
+/// Write an auditable bundle of the configuration and plan.
+async fn cmd_package(
+    config_path: Option<&PathBuf>,
+    file: Option<PathBuf>,
+    refresh: bool,
+) -> Result<()> {
+    let (config, state_store) = load_config_and_state(config_path).await?;
+    let state = state_store.load().await?;
+    let plan = compute_plan(config_path, &config, state.as_ref(), refresh).await?;
+    let plan_json = OutputFormatter::new(OutputFormat::Json).format_plan(&plan);
+
+    let config_hash = ConfigHasher::new().hash_config(&config);
+    let bundle = Bundle::new(&config, &config_hash, plan_json, refresh);
+
+    // Pin the Hub revisions the models would be downloaded from
+    let mut lock = bundle.lock.clone();
+    if refresh {
+        let probe = EngineProbe::new()?;
+        for model in &mut lock.models {
+            if let Some(repo) = model.repo.as_ref().filter(|_| model.revision.is_none()) {
+                match probe.model_revision(repo).await {
+                    Ok(revision) => model.revision = Some(revision),
+                    Err(e) => warn!("Could not resolve revision of {repo}: {e}"),
+                }
+            }
         }
     }
+    let bundle = bundle.with_lock(lock);
+
+    let path = file.unwrap_or_else(|| PathBuf::from(bundle.file_name()));
+    bundle.write(&path)?;
+
+    let unpinned = bundle
+        .lock
+        .images
+        .iter()
+        .filter(|i| i.digest.is_none())
+        .count();
+    if unpinned > 0 {
+        warn!(
+            "{unpinned} image(s) are referenced by tag, not digest; the bundle records the tag only"
+        );
+    }
+    eprintln!("Wrote bundle: {}", path.display());
 
     Ok(())
 }
@@ -239,12 +616,17 @@ async fn cmd_apply(
     config_path: Option<&PathBuf>,
     auto_approve: bool,
     continue_on_error: bool,
+    deadline: Option<(tokio::time::Instant, DeadlinePolicy)>,
+    parallelism: usize,
+    replace: &[String],
     formatter: &OutputFormatter,
 ) -> Result<()> {
     let (config, state_store) = load_config_and_state(config_path).await?;
+    check_replace_targets(&config, replace)?;
     let client = create_runpod_client()?;
-    let observer = PodObserver::new(client.clone());
-    let mut provisioner = PodProvisioner::new(client);
+    let observer = project_observer(client.clone(), &config.project, &state_store).await?;
+    let mut provisioner = PodProvisioner::new(client.clone())
+        .with_transcript_dir(transcript_dir(config_path, &config)?);
 
     // Initialize GPU types
     provisioner.init_gpu_types().await?;
@@ -259,13 +641,31 @@ async fn cmd_apply(
     let observed_pods = observer
         .list_project_pods(&config.project.name, &config.project.environment)
         .await?;
+    let status = ProjectStatus::from_pods(
+        &config.project.name,
+        &config.project.environment,
+        observed_pods.clone(),
+    );
+    cache_status(config_path, &config, &status).await;
 
     // Compute diff and plan
     let hasher = ConfigHasher::new();
     let config_hash = hasher.hash_config(&config);
     let diff_engine = DiffEngine::new();
-    let diff = diff_engine.compute_diff(&config, Some(&state), &observed_pods);
-    let plan = DeploymentPlan::from_diff(&diff, &config, &config_hash);
+    let mut diff = diff_engine.compute_diff(&config, Some(&state), &observed_pods);
+    if let Some(endpoints) = observed_endpoints(&client, &config, Some(&state)).await? {
+        diff.set_endpoints(diff_engine.compute_endpoint_diff(
+            &config,
+            Some(&state),
+            Some(&endpoints),
+        ));
+    }
+    diff.force_replace(replace);
+    diff.replace_preempted();
+    let plan = DeploymentPlan::from_diff(&diff, &config, &config_hash)
+        .with_state_warnings(Some(&state))
+        .with_duration_estimates(Some(&state));
+    let plan = price_plan(&client, &config, Some(&state), &observed_pods, plan).await?;
 
     if plan.is_empty() {
         eprintln!("No changes to apply.");
@@ -291,17 +691,136 @@ async fn cmd_apply(
     }
 
     // Execute plan
-    let executor = halldyll_deploy_pods::planner::PlanExecutor::new(&provisioner, &config.project)
-        .with_continue_on_error(continue_on_error);
+    let mut executor =
+        halldyll_deploy_pods::planner::PlanExecutor::new(&provisioner, &config.project)
+            .with_continue_on_error(continue_on_error)
+            .with_parallelism(parallelism)
+            .with_load_balancers(&config);
+    if let Some((deadline, policy)) = deadline {
+        executor = executor.with_deadline(deadline, policy);
+    }
 
+    // Keep the state being replaced restorable with `state rollback`
+    state_store.snapshot().await?;
     let result = executor.execute(&plan, &mut state).await?;
+    rotate_ssh_keys(&config, &mut state, &client).await;
 
     // Save state
     state_store.save(&state).await?;
 
     // Show result
-    eprintln!("\n{result}");
+    eprintln!("\n{}", formatter.format_execution(&result));
+
+    write_lb_manifests(&config, Some(&state), &observer).await?;
+
+    let ready = ready_pods(&config, &result, &observer).await;
+    if !ready.is_empty() {
+        eprintln!("\n{}", formatter.format_ready_pods(&ready));
+    }
+
+    if result.deadline_reached {
+        return Err(ReconcileError::DeadlineReached {
+            remaining: result.skipped,
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Collects the endpoints, SSH command and engine status of the pods an apply brought up.
+///
+/// Pods that cannot be looked up are left out; the apply itself succeeded.
+async fn ready_pods(
+    config: &DeployConfig,
+    result: &ExecutionResult,
+    observer: &PodObserver,
+) -> Vec<ReadyPod> {
+    let brought_up: Vec<(&str, &str)> = result
+        .results
+        .iter()
+        .filter(|r| {
+            r.success
+                && matches!(
+                    r.action.action_type,
+                    ActionType::CreatePod | ActionType::UpdatePod | ActionType::ResumePod
+                )
+        })
+        .filter_map(|r| Some((r.action.resource_name.as_str(), r.pod_id.as_deref()?)))
+        .collect();
+    if brought_up.is_empty() {
+        return vec![];
+    }
+
+    let pods = match observer
+        .list_project_pods(&config.project.name, &config.project.environment)
+        .await
+    {
+        Ok(pods) => pods,
+        Err(e) => {
+            warn!("Could not look up the deployed pods: {e}");
+            return vec![];
+        }
+    };
+    let checker = HealthChecker::new().ok();
+
+    let mut ready = Vec::new();
+    for (name, pod_id) in brought_up {
+        let Some(pod) = pods.iter().find(|p| p.id == pod_id) else {
+            continue;
+        };
+        let pod_config = config.pod_for_instance(name);
+        let ssh = match observer.client().get_pod(pod_id).await {
+            Ok(pod) => pod
+                .ssh_endpoint()
+                .map(|(host, port)| format!("ssh root@{host} -p {port}")),
+            Err(e) => {
+                debug!("Could not look up the SSH port of {pod_id}: {e}");
+                None
+            }
+        };
+        let mut engines: Vec<String> = pod_config
+            .iter()
+            .flat_map(|p| &p.models)
+            .filter_map(|m| m.load.as_ref().map(|load| load.engine.clone()))
+            .collect();
+        engines.dedup();
+        let healthy = match &checker {
+            Some(checker) if !pod.endpoints.is_empty() => Some(
+                checker
+                    .check_pod(pod, pod_config.and_then(|p| p.health_check.as_ref()))
+                    .await
+                    .healthy,
+            ),
+            _ => None,
+        };
+
+        ready.push(ReadyPod {
+            name: name.to_string(),
+            pod_id: pod_id.to_string(),
+            endpoints: pod
+                .endpoints
+                .iter()
+                .map(|(port, url)| (*port, url.clone()))
+                .collect(),
+            ssh,
+            engines,
+            healthy,
+        });
+    }
+    ready
+}
 
+/// Checks that every pod passed to `--replace` is configured.
+fn check_replace_targets(config: &DeployConfig, replace: &[String]) -> Result<()> {
+    if let Some(pod) = replace
+        .iter()
+        .find(|pod| config.pod_for_instance(pod).is_none())
+    {
+        return Err(HalldyllError::internal(format!(
+            "Cannot replace '{pod}': no such pod in the configuration"
+        )));
+    }
     Ok(())
 }
 
@@ -312,26 +831,70 @@ async fn cmd_status(
     include_health: bool,
     formatter: &OutputFormatter,
 ) -> Result<()> {
-    let (config, _state_store) = load_config_and_state(config_path).await?;
+    let (config, state_store) = load_config_and_state(config_path).await?;
     let client = create_runpod_client()?;
-    let observer = PodObserver::new(client);
-
-    // Get project status
+    // Get project status, noting pods under maintenance and annotations
+    let state = state_store.load().await?;
+    let observer = PodObserver::new(client)
+        .with_tag_namespace(config.project.tag_namespace())
+        .with_imported_pods(state.as_ref());
+    let notes = config.pods.iter().filter_map(|pod| {
+        let notes = pod.notes.as_ref()?;
+        Some((
+            pod.name.clone(),
+            BTreeMap::from([(String::from("notes"), notes.clone())]),
+        ))
+    });
     let status = observer
         .get_project_status(&config.project.name, &config.project.environment)
-        .await?;
+        .await?
+        .with_maintenance(state.iter().flat_map(|s| {
+            s.maintenance
+                .iter()
+                .map(|(pod, m)| (pod.clone(), m.to_string()))
+        }))
+        .with_annotations(notes)
+        .with_annotations(state.iter().flat_map(|s| s.annotations.clone()));
+    cache_status(config_path, &config, &status).await;
 
     // Optionally check health
     let health = if include_health && !status.pods.is_empty() {
+        let health_checks: HashMap<_, _> = DiffEngine::desired_instances(&config, state.as_ref())
+            .into_iter()
+            .filter_map(|pod| pod.health_check.map(|check| (pod.name, check)))
+            .collect();
+
         let checker = HealthChecker::new()?;
-        Some(checker.check_pods(&status.pods).await)
+        Some(
+            checker
+                .check_pods_with_configs(&status.pods, &health_checks)
+                .await,
+        )
     } else {
         None
     };
 
     // Output
-    let output = formatter.format_status(&status, health.as_deref());
-    eprintln!("{output}");
+    formatter.emit(&formatter.format_status(&status, health.as_deref()))?;
+
+    Ok(())
+}
+
+/// Show the last observed status without contacting `RunPod`.
+async fn cmd_status_cached(
+    config_path: Option<&PathBuf>,
+    formatter: &OutputFormatter,
+) -> Result<()> {
+    let config = load_config(config_path)?;
+    let cache = StatusCache::new(local_state_dir(config_path, &config)?);
+
+    match cache.read().await? {
+        Some(cached) => formatter.emit(&formatter.format_cached_status(&cached))?,
+        None => eprintln!(
+            "No cached status at {}. Run `halldyll status` to record one.",
+            cache.path().display()
+        ),
+    }
 
     Ok(())
 }
@@ -341,19 +904,24 @@ async fn cmd_reconcile(
     config_path: Option<&PathBuf>,
     auto_approve: bool,
     max_attempts: u32,
+    replace: Vec<String>,
     formatter: &OutputFormatter,
 ) -> Result<()> {
     let (config, state_store) = load_config_and_state(config_path).await?;
+    check_replace_targets(&config, &replace)?;
     let client = create_runpod_client()?;
-    let observer = PodObserver::new(client.clone());
-    let mut provisioner = PodProvisioner::new(client);
+    let observer = project_observer(client.clone(), &config.project, &state_store).await?;
+    let mut provisioner =
+        PodProvisioner::new(client).with_transcript_dir(transcript_dir(config_path, &config)?);
 
     // Initialize GPU types
     provisioner.init_gpu_types().await?;
 
     // Confirm
     if !auto_approve {
-        eprint!("This will reconcile your deployment to match the configuration. Continue? [y/N]: ");
+        eprint!(
+            "This will reconcile your deployment to match the configuration. Continue? [y/N]: "
+        );
         std::io::stderr().flush()?;
 
         let mut input = String::new();
@@ -366,16 +934,20 @@ async fn cmd_reconcile(
     }
 
     // Create reconciler
-    let reconciler =
-        Reconciler::new(&config, &state_store, &provisioner, &observer).with_max_attempts(max_attempts);
+    let reconciler = Reconciler::new(&config, &state_store, &provisioner, &observer)
+        .with_max_attempts(max_attempts)
+        .with_replacements(replace);
 
-    // Run reconciliation
+    // Run reconciliation, keeping the state it replaces restorable
+    state_store.snapshot().await?;
     let result = reconciler.reconcile().await?;
 
     // Output
     let output = formatter.format_reconciliation(&result);
     eprintln!("{output}");
 
+    write_lb_manifests(&config, result.final_state.as_ref(), &observer).await?;
+
     Ok(())
 }
 
@@ -383,12 +955,12 @@ async fn cmd_reconcile(
 async fn cmd_destroy(
     config_path: Option<&PathBuf>,
     auto_approve: bool,
-    _keep_volumes: bool,
-    _formatter: &OutputFormatter,
+    keep_volumes: bool,
+    formatter: &OutputFormatter,
 ) -> Result<()> {
     let (config, state_store) = load_config_and_state(config_path).await?;
     let client = create_runpod_client()?;
-    let observer = PodObserver::new(client.clone());
+    let observer = project_observer(client.clone(), &config.project, &state_store).await?;
     let provisioner = PodProvisioner::new(client);
 
     // Get current pods
@@ -396,16 +968,18 @@ async fn cmd_destroy(
         .list_project_pods(&config.project.name, &config.project.environment)
         .await?;
 
-    if pods.is_empty() {
+    let mut state = state_store
+        .load()
+        .await?
+        .unwrap_or_else(|| DeploymentState::new(&config.project.name, &config.project.environment));
+    let config_hash = ConfigHasher::new().hash_config(&config);
+    let plan = DeploymentPlan::destroy(&config, &pods, Some(&state), keep_volumes, &config_hash);
+
+    if plan.is_empty() {
         eprintln!("No pods to destroy.");
         return Ok(());
     }
-
-    eprintln!("The following pods will be destroyed:");
-    for pod in &pods {
-        let name = pod.pod_name.as_deref().unwrap_or(&pod.name);
-        eprintln!("  - {name} ({})", pod.id);
-    }
+    eprintln!("{}", formatter.format_plan(&plan));
 
     // Confirm
     if !auto_approve {
@@ -421,13 +995,31 @@ async fn cmd_destroy(
         }
     }
 
-    // Destroy pods
-    for pod in &pods {
-        let name = pod.pod_name.as_deref().unwrap_or(&pod.name);
-        eprintln!("Destroying {name}...");
-        if let Err(e) = provisioner.terminate_pod(&pod.id).await {
-            error!("Failed to destroy {name}: {e}");
-        }
+    // Destroy pods, dropping them from load balancer manifests
+    let result = halldyll_deploy_pods::planner::PlanExecutor::new(&provisioner, &config.project)
+        .with_continue_on_error(true)
+        .with_load_balancers(&config)
+        .execute(&plan, &mut state)
+        .await?;
+
+    // Keep the state while pods remain, so destroy can be re-run
+    if !result.success {
+        state_store.save(&state).await?;
+        return Err(HalldyllError::internal(format!(
+            "{} of {} destroy actions failed; re-run `halldyll destroy` to retry",
+            result.failed + result.skipped,
+            plan.action_count()
+        )));
+    }
+
+    // Keep the state while it tracks detached volumes, so apply re-attaches them
+    if keep_volumes && !state.volumes.is_empty() {
+        state_store.save(&state).await?;
+        eprintln!(
+            "\nAll pods destroyed; {} volumes kept detached.",
+            state.volumes.len()
+        );
+        return Ok(());
     }
 
     // Clear state
@@ -437,145 +1029,1866 @@ async fn cmd_destroy(
     Ok(())
 }
 
-/// Show logs (placeholder).
+/// Seconds between log polls when following.
+const LOG_FOLLOW_INTERVAL_SECS: u64 = 2;
+
+/// Show the engine logs of running pods.
 ///
 /// # Errors
 ///
-/// Returns an error if the pod is not found (once implemented).
-fn cmd_logs(
-    _config_path: Option<&PathBuf>,
-    _pod: Option<String>,
-    _follow: bool,
-    _tail: u32,
+/// Returns an error if no running pod matches or pods cannot be listed.
+async fn cmd_logs(
+    config_path: Option<&PathBuf>,
+    pod: Option<String>,
+    follow: bool,
+    tail: u32,
 ) -> Result<()> {
-    Err(halldyll_deploy_pods::error::HalldyllError::internal(
-        "Log viewing is not yet implemented. View logs directly in the RunPod dashboard.",
-    ))
-}
-
-/// Check for drift.
-async fn cmd_drift(config_path: Option<&PathBuf>, formatter: &OutputFormatter) -> Result<()> {
-    let (config, state_store) = load_config_and_state(config_path).await?;
+    let config = load_config(config_path)?;
     let client = create_runpod_client()?;
-    let observer = PodObserver::new(client.clone());
-    let provisioner = PodProvisioner::new(client);
+    let observer =
+        PodObserver::new(client.clone()).with_tag_namespace(config.project.tag_namespace());
+    let executor = PodExecutor::new(client);
 
-    let reconciler = Reconciler::new(&config, &state_store, &provisioner, &observer);
-    let report = reconciler.check_drift().await?;
+    let pods: Vec<_> = observer
+        .list_project_pods(&config.project.name, &config.project.environment)
+        .await?
+        .into_iter()
+        .filter(ObservedPod::is_running)
+        .filter(|p| {
+            pod.as_deref().is_none_or(|wanted| {
+                let name = p.pod_name.as_deref().unwrap_or(&p.name);
+                name == wanted
+                    || config
+                        .pod_for_instance(name)
+                        .is_some_and(|c| c.name == wanted)
+            })
+        })
+        .collect();
 
-    let output = formatter.format_drift(&report);
-    eprintln!("{output}");
+    if pods.is_empty() {
+        return Err(HalldyllError::internal(pod.map_or_else(
+            || String::from("No running pods to show logs for"),
+            |name| format!("No running pod named '{name}'"),
+        )));
+    }
 
+    let mut streams = Vec::new();
+    for observed in &pods {
+        let name = observed
+            .pod_name
+            .clone()
+            .unwrap_or_else(|| observed.name.clone());
+        let files = PodExecutor::log_files(config.pod_for_instance(&name));
+        match executor.open_logs(&observed.id, &files, tail).await {
+            Ok(cursors) if cursors.is_empty() => warn!("No engine logs found on {name}"),
+            Ok(cursors) => streams.push(PodLogs {
+                name,
+                pod_id: observed.id.clone(),
+                cursors,
+            }),
+            Err(e) => warn!("Failed to open logs of {name}: {e}"),
+        }
+    }
+
+    // Prefix lines with the pod name when several pods are multiplexed
+    let prefixed = pods.len() > 1;
+    let interval = std::time::Duration::from_secs(LOG_FOLLOW_INTERVAL_SECS);
+    let ctrl_c = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+    executor
+        .follow_logs(
+            &mut streams,
+            follow.then_some(interval),
+            ctrl_c,
+            |name, line| {
+                if prefixed {
+                    println!("[{name}] {line}");
+                } else {
+                    println!("{line}");
+                }
+            },
+        )
+        .await;
     Ok(())
 }
 
-/// State management commands.
-async fn cmd_state(
+/// Run a command on a deployed pod, propagating its exit code.
+///
+/// # Errors
+///
+/// Returns an error if the pod is not in the state, the command cannot be
+/// run, or it exits with a non-zero code.
+async fn cmd_exec(
     config_path: Option<&PathBuf>,
-    command: StateCommands,
-    formatter: &OutputFormatter,
+    pod: &str,
+    timeout: Option<u64>,
+    command: &[String],
 ) -> Result<()> {
-    let (_config, state_store) = load_config_and_state(config_path).await?;
-
-    match command {
-        StateCommands::Show => {
-            if let Some(state) = state_store.load().await? {
-                let output = formatter.format_state(&state);
-                eprintln!("{output}");
-            } else {
-                eprintln!("No state found.");
-            }
-        }
-        StateCommands::Lock { holder } => {
-            let holder_str = holder.as_deref().unwrap_or("");
-            let lock = state_store.acquire_lock(holder_str).await?;
-            eprintln!("State locked: {}", lock.lock_id);
-        }
-        StateCommands::Unlock { lock_id, force } => {
-            if force {
-                // Force unlock by deleting lock file
-                if let Some(lock_info) = state_store.get_lock_info().await? {
-                    state_store.release_lock(&lock_info.lock_id).await?;
-                    eprintln!("State forcefully unlocked.");
+    let (config, state_store) = load_config_and_state(config_path).await?;
+    let state = state_store.load().await?.ok_or_else(|| {
+        HalldyllError::internal("No deployment state found. Run `halldyll apply` first.")
+    })?;
+
+    let pod_state = resolve_state_pod(&config, &state, pod)?;
+
+    let executor = PodExecutor::new(create_runpod_client()?)
+        .with_transcript(Transcript::new(transcript_dir(config_path, &config)?));
+    executor.name_pod(&pod_state.runpod_id, &pod_state.name);
+
+    // Print output as it arrives rather than when the command ends
+    let command_line = command.join(" ");
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel::<OutputChunk>();
+    let print_output = async {
+        while let Some(chunk) = receiver.recv().await {
+            match chunk.stream {
+                OutputStream::Stdout => {
+                    print!("{}", chunk.text);
+                    let _ = std::io::stdout().flush();
                 }
-            } else if let Some(id) = lock_id {
-                state_store.release_lock(&id).await?;
-                eprintln!("State unlocked.");
-            } else {
-                eprintln!("Please provide --lock-id or use --force");
+                OutputStream::Stderr => eprint!("{}", chunk.text),
             }
         }
-        StateCommands::Pull => {
-            eprintln!("State pull is only applicable for remote backends.");
-        }
-        StateCommands::Push { force: _ } => {
-            eprintln!("State push is only applicable for remote backends.");
-        }
+    };
+    let (result, ()) = tokio::join!(
+        executor.execute_command_streaming(&pod_state.runpod_id, &command_line, timeout, sender),
+        print_output
+    );
+    result?.check(&pod_state.name)
+}
+
+/// Finds a pod in the state by name.
+///
+/// A replicated pod can be addressed by its base name if it has one replica.
+fn resolve_state_pod<'a>(
+    config: &DeployConfig,
+    state: &'a DeploymentState,
+    pod: &str,
+) -> Result<&'a PodState> {
+    if let Some(pod_state) = state.get_pod(pod) {
+        return Ok(pod_state);
     }
 
-    Ok(())
+    let mut replicas: Vec<&PodState> = state
+        .pods
+        .values()
+        .filter(|p| {
+            config
+                .pod_for_instance(&p.name)
+                .is_some_and(|c| c.name == pod)
+        })
+        .collect();
+    replicas.sort_by(|a, b| a.name.cmp(&b.name));
+
+    match replicas.as_slice() {
+        [only] => Ok(only),
+        [] => Err(HalldyllError::internal(format!(
+            "Pod '{pod}' is not in the deployment state"
+        ))),
+        _ => {
+            let names: Vec<&str> = replicas.iter().map(|p| p.name.as_str()).collect();
+            Err(HalldyllError::internal(format!(
+                "Pod '{pod}' has several replicas ({}); pick one",
+                names.join(", ")
+            )))
+        }
+    }
 }
 
-// ============================================================================
-// Helper Functions
-// ============================================================================
+/// List a project's pods across all environments.
+async fn cmd_inventory(
+    config_path: Option<&PathBuf>,
+    project: Option<String>,
+    formatter: &OutputFormatter,
+) -> Result<()> {
+    // The configuration is only needed to name the project, cloud type and tag namespace
+    let (project, cloud_type, tag_namespace) = if let Some(project) = project {
+        (
+            project,
+            CloudType::default(),
+            DEFAULT_TAG_NAMESPACE.to_string(),
+        )
+    } else {
+        let config = load_config(config_path)?;
+        let tag_namespace = config.project.tag_namespace().to_string();
+        (
+            config.project.name,
+            config.project.cloud_type,
+            tag_namespace,
+        )
+    };
 
-/// Resolves the configuration file path.
-fn resolve_config_path(config_path: Option<&PathBuf>) -> Result<PathBuf> {
-    config_path.map_or_else(|| find_config_file("."), |path| Ok(path.clone()))
+    let client = create_runpod_client()?;
+    let gpu_types = client.list_gpu_types().await?;
+    let pods = PodObserver::new(client)
+        .with_tag_namespace(&tag_namespace)
+        .list_project_pods_all_envs(&project)
+        .await?;
+
+    let prices = GpuPrices::new(&gpu_types, cloud_type);
+    formatter.emit(&formatter.format_inventory(&project, &pods, &prices))
 }
 
-/// Loads configuration and creates appropriate state store.
-async fn load_config_and_state(
-    config_path: Option<&PathBuf>,
-) -> Result<(halldyll_deploy_pods::config::DeployConfig, Box<dyn StateStore>)> {
-    let config_file = resolve_config_path(config_path)?;
-    debug!("Loading configuration from: {}", config_file.display());
+/// Show the running cost of the project's pods.
+async fn cmd_cost(config_path: Option<&PathBuf>, formatter: &OutputFormatter) -> Result<()> {
+    let (config, state_store) = load_config_and_state(config_path).await?;
+    let state = state_store.load().await?;
+
+    let client = create_runpod_client()?;
+    let gpu_types = client.list_gpu_types().await?;
+    let pods = PodObserver::new(client)
+        .with_tag_namespace(config.project.tag_namespace())
+        .with_imported_pods(state.as_ref())
+        .list_project_pods(&config.project.name, &config.project.environment)
+        .await?;
 
-    let parser = ConfigParser::new().with_base_path(
-        config_file
-            .parent()
-            .unwrap_or_else(|| std::path::Path::new(".")),
+    let prices = GpuPrices::new(&gpu_types, config.project.cloud_type);
+    let report = CostReport::new(
+        &config.project.name,
+        &config.project.environment,
+        &pods,
+        state.as_ref(),
+        &prices,
+        chrono::Utc::now(),
     );
-    parser.load_dotenv()?;
+    formatter.emit(&formatter.format_cost(&report))?;
 
-    let config = parser.load_with_env(&config_file)?;
+    Ok(())
+}
 
-    // Validate
-    let validator = ConfigValidator::new();
-    validator.validate(&config)?;
+/// Report a pod's availability from the recorded health history.
+async fn cmd_slo(
+    config_path: Option<&PathBuf>,
+    pod: &str,
+    window: std::time::Duration,
+    formatter: &OutputFormatter,
+) -> Result<()> {
+    let config = load_config(config_path)?;
+    let history = HealthHistory::new(local_state_dir(config_path, &config)?);
+
+    let now = chrono::Utc::now();
+    let window_start = chrono::Duration::from_std(window)
+        .ok()
+        .and_then(|window| now.checked_sub_signed(window))
+        .unwrap_or(chrono::DateTime::<chrono::Utc>::MIN_UTC);
+    let samples = history.read_since(window_start).await?;
+    if !config.pods.iter().any(|p| p.name == pod) && !samples.iter().any(|s| s.belongs_to(pod)) {
+        return Err(HalldyllError::internal(format!(
+            "Pod '{pod}' is not defined in the configuration and has no health history"
+        )));
+    }
 
-    // Create state store based on config
-    let state_store: Box<dyn StateStore> = match config.state.backend {
-        StateBackend::Local => {
-            let path = config.state.path.as_ref().map_or_else(
-                || {
-                    config_file
-                        .parent()
-                        .unwrap_or_else(|| std::path::Path::new("."))
-                        .join(".halldyll")
-                },
-                PathBuf::from,
-            );
-            Box::new(LocalStateStore::with_base_dir(path))
-        }
-        StateBackend::S3 => {
-            let bucket = config
-                .state
-                .bucket
-                .as_deref()
-                .ok_or_else(|| halldyll_deploy_pods::error::HalldyllError::internal("S3 bucket not configured"))?;
-            let prefix = config.state.prefix.as_deref();
-            let region = config.state.region.as_deref();
-            Box::new(S3StateStore::new(bucket, prefix, region).await?)
-        }
-    };
+    let report = SloReport::new(pod, &samples, window_start, now);
+    formatter.emit(&formatter.format_slo(&report))?;
 
-    Ok((config, state_store))
+    Ok(())
 }
 
-/// Creates a `RunPod` API client.
-fn create_runpod_client() -> Result<RunPodClient> {
-    let api_key = ConfigParser::get_runpod_api_key()?;
-    RunPodClient::new(&api_key)
+/// Resolve external references into the lockfile.
+async fn cmd_lock(config_path: Option<&PathBuf>, update: bool) -> Result<()> {
+    let config_file = resolve_config_path(config_path)?;
+    let config = load_unlocked_config(&config_file)?;
+    let lock_path = Lockfile::path_for(&config_file);
+
+    let previous = Lockfile::load(&lock_path)?;
+    let mut lock = match (&previous, update) {
+        (Some(existing), false) => existing.clone(),
+        _ => Lockfile::new(),
+    };
+    lock.retain_used(&config);
+
+    let missing = lock.missing(&config);
+    let mut unresolved = Vec::new();
+
+    if !missing.gpu_types.is_empty() {
+        let gpu_types = create_runpod_client()?.list_gpu_types().await?;
+        for name in &missing.gpu_types {
+            match gpu_types
+                .iter()
+                .find(|g| &g.id == name || &g.display_name == name)
+            {
+                Some(gpu) => {
+                    lock.gpu_types.insert(name.clone(), gpu.id.clone());
+                }
+                None => unresolved.push(format!("gpu {name}: unknown GPU type")),
+            }
+        }
+    }
+
+    if !missing.images.is_empty() {
+        let registry = RegistryClient::new()?;
+        for image in &missing.images {
+            match registry.resolve_digest(image).await {
+                Ok(digest) => {
+                    lock.images.insert(image.clone(), digest);
+                }
+                Err(e) => unresolved.push(format!("image {image}: {e}")),
+            }
+        }
+    }
+
+    if !missing.models.is_empty() {
+        let probe = EngineProbe::new()?;
+        for repo in &missing.models {
+            match probe.model_revision(repo).await {
+                Ok(revision) => {
+                    lock.models.insert(repo.clone(), revision);
+                }
+                Err(e) => unresolved.push(format!("model {repo}: {e}")),
+            }
+        }
+    }
+
+    let changed = previous.as_ref().is_none_or(|p| {
+        p.gpu_types != lock.gpu_types || p.images != lock.images || p.models != lock.models
+    });
+    if changed {
+        lock.generated_at = Some(chrono::Utc::now());
+        lock.save(&lock_path)?;
+        eprintln!(
+            "Wrote {}: {} GPU types, {} images, {} models",
+            lock_path.display(),
+            lock.gpu_types.len(),
+            lock.images.len(),
+            lock.models.len()
+        );
+    } else {
+        eprintln!("{} is up to date", lock_path.display());
+    }
+
+    if unresolved.is_empty() {
+        return Ok(());
+    }
+    for reference in &unresolved {
+        error!("Could not lock {reference}");
+    }
+    Err(HalldyllError::internal(format!(
+        "{} reference(s) could not be locked",
+        unresolved.len()
+    )))
+}
+
+/// Show version details, optionally checking for a newer release.
+async fn cmd_version(check: bool, formatter: &OutputFormatter) {
+    let mut info = VersionInfo::current();
+    if check {
+        match latest_release().await {
+            Ok(latest) => info.latest = Some(latest),
+            Err(e) => warn!("{e}"),
+        }
+    }
+
+    eprintln!("{}", formatter.format_version(&info));
+}
+
+/// List the external hosts a command may contact.
+fn cmd_endpoints(
+    config_path: Option<&PathBuf>,
+    command: &str,
+    formatter: &OutputFormatter,
+) -> Result<()> {
+    let config = match resolve_config_path(config_path) {
+        Ok(file) if file.exists() => Some(load_config(config_path)?),
+        _ => {
+            warn!("No configuration found; hosts that depend on it are not listed");
+            None
+        }
+    };
+
+    let hosts = command_egress(command, config.as_ref());
+    eprintln!("{}", formatter.format_egress(command, &hosts));
+    Ok(())
+}
+
+/// Find tagged pods that no local workspace accounts for.
+async fn cmd_audit_orphans(
+    config_path: Option<&PathBuf>,
+    search: &[PathBuf],
+    interactive: bool,
+    formatter: &OutputFormatter,
+) -> Result<()> {
+    let mut paths = discover_configs(search);
+    if let Ok(current) = resolve_config_path(config_path) {
+        paths.push(current);
+    }
+    paths.sort();
+    paths.dedup();
+
+    let mut workspaces = Vec::new();
+    let mut stores = Vec::new();
+    for path in paths {
+        match load_config_and_state(Some(&path)).await {
+            Ok((config, store)) => {
+                let state = store.load().await?;
+                workspaces.push(Workspace {
+                    path,
+                    config,
+                    state,
+                });
+                stores.push(store);
+            }
+            Err(e) => warn!(
+                "Skipping {}: {e}. Its pods may be reported as orphans.",
+                path.display()
+            ),
+        }
+    }
+
+    let client = create_runpod_client()?;
+    let pods = PodObserver::new(client.clone()).list_all_pods().await?;
+    let orphans = find_orphans(&pods, &workspaces);
+    eprintln!("{}", formatter.format_orphans(&orphans, workspaces.len()));
+
+    if !interactive {
+        return Ok(());
+    }
+
+    let provisioner = PodProvisioner::new(client);
+    for orphan in &orphans {
+        let name = orphan.pod.pod_name.as_deref().unwrap_or(&orphan.pod.name);
+        let adoptable = orphan.workspace.is_some();
+        eprint!(
+            "{name} ({}, {}): [t]erminate, {}[s]kip? ",
+            orphan.pod.id,
+            orphan.reason,
+            if adoptable {
+                "[a]dopt into state, "
+            } else {
+                ""
+            }
+        );
+        std::io::stderr().flush()?;
+
+        let mut input = String::new();
+        std::io::stdin().read_line(&mut input)?;
+
+        match (input.trim(), orphan.workspace) {
+            ("t" | "terminate", _) => match provisioner.terminate_pod(&orphan.pod.id).await {
+                Ok(()) => eprintln!("  Terminated {name}"),
+                Err(e) => error!("Failed to terminate {name}: {e}"),
+            },
+            ("a" | "adopt", Some(index)) => {
+                let workspace = &mut workspaces[index];
+                let state = workspace.state.get_or_insert_with(|| {
+                    DeploymentState::new(
+                        &workspace.config.project.name,
+                        &workspace.config.project.environment,
+                    )
+                });
+                let mut pod_state = PodState::new(
+                    name,
+                    &orphan.pod.id,
+                    orphan.pod.spec_hash.as_deref().unwrap_or(""),
+                );
+                pod_state.gpu_type = orphan.pod.gpu_type.clone().unwrap_or_default();
+                pod_state.gpu_count = orphan.pod.gpu_count;
+                pod_state.image.clone_from(&orphan.pod.image);
+                state.set_pod(pod_state);
+                stores[index].save(state).await?;
+                eprintln!("  Recorded {name} in {}", workspace.path.display());
+            }
+            _ => eprintln!("  Skipped {name}"),
+        }
+    }
+
+    Ok(())
+}
+
+/// Check for drift.
+async fn cmd_drift(
+    config_path: Option<&PathBuf>,
+    probe_endpoints: bool,
+    formatter: &OutputFormatter,
+) -> Result<()> {
+    let (config, state_store) = load_config_and_state(config_path).await?;
+    let client = create_runpod_client()?;
+    let observer = project_observer(client.clone(), &config.project, &state_store).await?;
+    let provisioner = PodProvisioner::new(client);
+
+    let reconciler = Reconciler::new(&config, &state_store, &provisioner, &observer)
+        .with_endpoint_probes(probe_endpoints);
+    let report = reconciler.check_drift().await?;
+
+    formatter.emit(&formatter.format_drift(&report))?;
+
+    Ok(())
+}
+
+/// Days of health history kept for `halldyll slo`.
+const HEALTH_HISTORY_RETENTION_DAYS: i64 = 90;
+
+/// Watch the deployment, autoscaling and reconciling on an interval.
+async fn cmd_watch(
+    config_path: Option<&PathBuf>,
+    interval_secs: u64,
+    no_reconcile: bool,
+    once: bool,
+    lease_secs: Option<u64>,
+) -> Result<()> {
+    let (config, state_store) = load_config_and_state(config_path).await?;
+    let client = create_runpod_client()?;
+    let observer = project_observer(client.clone(), &config.project, &state_store).await?;
+    let mut provisioner = PodProvisioner::new(client);
+
+    if !no_reconcile {
+        provisioner.init_gpu_types().await?;
+    }
+
+    let health_history = HealthHistory::new(local_state_dir(config_path, &config)?);
+    if let Err(e) = health_history
+        .prune(chrono::Utc::now() - chrono::Duration::days(HEALTH_HISTORY_RETENTION_DAYS))
+        .await
+    {
+        warn!("Failed to prune health history: {e}");
+    }
+
+    let watcher = Watcher::new(&config, &state_store, &provisioner, &observer)?
+        .with_reconcile(!no_reconcile)
+        .with_health_history(health_history);
+    let interval = std::time::Duration::from_secs(interval_secs.max(1));
+    let mut election = lease_secs.map(|secs| LeaderElection::new(&state_store, secs));
+
+    eprintln!(
+        "Watching {}/{} every {}s (Ctrl+C to stop)",
+        config.project.name,
+        config.project.environment,
+        interval.as_secs()
+    );
+    if let (Some(election), Some(secs)) = (&election, lease_secs) {
+        eprintln!(
+            "Leader election enabled as {} (lease {secs}s)",
+            election.holder()
+        );
+    }
+
+    let mut last_role = None;
+    loop {
+        let outcome = match election.as_mut() {
+            None => Some(watcher.tick().await),
+            Some(election) => match election.campaign().await {
+                Ok(role) => {
+                    if last_role.as_ref() != Some(&role) {
+                        eprintln!(
+                            "[{}] Acting as {role}",
+                            chrono::Utc::now().format("%H:%M:%S")
+                        );
+                    }
+                    let leading = role == Role::Leader;
+                    last_role = Some(role);
+                    if leading {
+                        Some(election.lead(watcher.tick()).await.and_then(|tick| tick))
+                    } else {
+                        None
+                    }
+                }
+                Err(e) => Some(Err(e)),
+            },
+        };
+
+        match outcome {
+            Some(Ok(tick)) => {
+                let summary = tick.to_string();
+                if !summary.is_empty() {
+                    eprintln!("[{}] {summary}", chrono::Utc::now().format("%H:%M:%S"));
+                }
+            }
+            Some(Err(e)) if once => {
+                if let Some(election) = election.as_mut()
+                    && let Err(resign_error) = election.resign().await
+                {
+                    warn!("Failed to release leader lease: {resign_error}");
+                }
+                return Err(e);
+            }
+            Some(Err(e)) => error!("Watch iteration failed: {e}"),
+            None => {}
+        }
+
+        if once {
+            break;
+        }
+
+        // Keep renewing the lease between iterations
+        let pause = async {
+            match election.as_mut() {
+                Some(election) if election.is_leader() => {
+                    if let Err(e) = election.lead(tokio::time::sleep(interval)).await {
+                        warn!("Failed to renew leader lease: {e}");
+                    }
+                }
+                _ => tokio::time::sleep(interval).await,
+            }
+        };
+        let stop = tokio::select! {
+            () = pause => false,
+            _ = tokio::signal::ctrl_c() => true,
+        };
+        if stop {
+            eprintln!("\nStopping watch.");
+            break;
+        }
+    }
+
+    if let Some(election) = election.as_mut() {
+        election.resign().await?;
+    }
+    Ok(())
+}
+
+/// Wake a sleeping pod, or serve a wake webhook.
+async fn cmd_wake(
+    config_path: Option<&PathBuf>,
+    pod: Option<String>,
+    listen: Option<String>,
+) -> Result<()> {
+    let (config, state_store) = load_config_and_state(config_path).await?;
+    let client = create_runpod_client()?;
+    let observer = project_observer(client.clone(), &config.project, &state_store).await?;
+    let provisioner = PodProvisioner::new(client);
+
+    let watcher = Watcher::new(&config, &state_store, &provisioner, &observer)?;
+
+    let Some(addr) = listen else {
+        let pod = pod.unwrap_or_default();
+        let decision = watcher.wake(&pod).await?;
+        eprintln!("{decision}");
+        return Ok(());
+    };
+
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    let token = std::env::var("HALLDYLL_WAKE_TOKEN")
+        .ok()
+        .filter(|t| !t.is_empty());
+    eprintln!("Listening for wake requests on http://{addr}/wake/<pod> (Ctrl+C to stop)");
+    if token.is_none() {
+        eprintln!("Warning: HALLDYLL_WAKE_TOKEN is not set, the webhook is unauthenticated.");
+    }
+
+    // Requests are read concurrently, each in its own task, and the valid
+    // ones are handed back here so wakes (which update state) run one at a time
+    let (requests, mut pending) = tokio::sync::mpsc::channel(WAKE_MAX_PENDING);
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let (stream, peer) = accepted?;
+                tokio::spawn(accept_wake_request(stream, peer, token.clone(), requests.clone()));
+            }
+            Some((mut stream, peer, pod_name)) = pending.recv() => {
+                let (status, body) = match watcher.wake(&pod_name).await {
+                    Ok(decision) => ("200 OK", decision.to_string()),
+                    Err(e) => ("500 Internal Server Error", e.to_string()),
+                };
+                debug!("Wake request from {peer}: {status}");
+                tokio::spawn(async move { answer_wake_request(&mut stream, peer, status, &body).await });
+            }
+            _ = tokio::signal::ctrl_c() => {
+                eprintln!("\nStopping wake listener.");
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Seconds a wake webhook client has to send its request line and headers.
+const WAKE_REQUEST_TIMEOUT_SECS: u64 = 10;
+
+/// Largest wake webhook request line and headers accepted, in bytes.
+const WAKE_MAX_HEADER_BYTES: u64 = 8 * 1024;
+
+/// Most headers accepted in a wake webhook request.
+const WAKE_MAX_HEADERS: usize = 64;
+
+/// Wake requests read and waiting for their pod to be woken.
+const WAKE_MAX_PENDING: usize = 16;
+
+/// A wake request that passed authentication: stream, peer and pod name.
+type WakeRequest = (tokio::net::TcpStream, std::net::SocketAddr, String);
+
+/// Reads one wake webhook connection, answering it directly if it is rejected.
+///
+/// A client that is too slow or sends too much is dropped without blocking
+/// other connections.
+async fn accept_wake_request(
+    mut stream: tokio::net::TcpStream,
+    peer: std::net::SocketAddr,
+    token: Option<String>,
+    requests: tokio::sync::mpsc::Sender<WakeRequest>,
+) {
+    let read = tokio::time::timeout(
+        std::time::Duration::from_secs(WAKE_REQUEST_TIMEOUT_SECS),
+        read_wake_request(&mut stream, token.as_deref()),
+    )
+    .await;
+
+    let (status, body) = match read {
+        Ok(Ok(pod_name)) => {
+            if let Err(e) = requests.send((stream, peer, pod_name)).await {
+                debug!("Wake listener stopped before handling {peer}: {e}");
+            }
+            return;
+        }
+        Ok(Err(rejection)) => rejection,
+        Err(_) => (
+            "408 Request Timeout",
+            format!("No request within {WAKE_REQUEST_TIMEOUT_SECS}s"),
+        ),
+    };
+    debug!("Wake request from {peer}: {status}");
+    answer_wake_request(&mut stream, peer, status, &body).await;
+}
+
+/// Writes the response to a wake webhook request.
+async fn answer_wake_request(
+    stream: &mut tokio::net::TcpStream,
+    peer: std::net::SocketAddr,
+    status: &str,
+    body: &str,
+) {
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    if let Err(e) = tokio::io::AsyncWriteExt::write_all(stream, response.as_bytes()).await {
+        error!("Failed to answer wake request from {peer}: {e}");
+    }
+}
+
+/// Reads a wake webhook request and returns the pod name to wake.
+///
+/// The request line and headers are capped at [`WAKE_MAX_HEADER_BYTES`] and
+/// [`WAKE_MAX_HEADERS`]. Rejections are returned as an HTTP status line and
+/// body.
+async fn read_wake_request(
+    stream: &mut tokio::net::TcpStream,
+    token: Option<&str>,
+) -> std::result::Result<String, (&'static str, String)> {
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt};
+
+    let too_large = || {
+        (
+            "431 Request Header Fields Too Large",
+            format!(
+                "Request headers are limited to {WAKE_MAX_HEADER_BYTES} bytes and {WAKE_MAX_HEADERS} fields"
+            ),
+        )
+    };
+    // Reading stops at the byte cap, leaving a line without its newline
+    let mut reader = tokio::io::BufReader::new(stream.take(WAKE_MAX_HEADER_BYTES));
+    let mut request_line = String::new();
+    reader
+        .read_line(&mut request_line)
+        .await
+        .map_err(|e| ("400 Bad Request", e.to_string()))?;
+    if !request_line.ends_with('\n') {
+        return Err(too_large());
+    }
+
+    let mut authorized = token.is_none();
+    for count in 0.. {
+        let mut header = String::new();
+        let read = reader
+            .read_line(&mut header)
+            .await
+            .map_err(|e| ("400 Bad Request", e.to_string()))?;
+        if read > 0 && !header.ends_with('\n') {
+            return Err(too_large());
+        }
+        let header = header.trim();
+        if read == 0 || header.is_empty() {
+            break;
+        }
+        if count == WAKE_MAX_HEADERS {
+            return Err(too_large());
+        }
+        if let (Some(expected), Some((name, value))) = (token, header.split_once(':'))
+            && name.trim().eq_ignore_ascii_case("authorization")
+            && value.trim().strip_prefix("Bearer ") == Some(expected)
+        {
+            authorized = true;
+        }
+    }
+
+    if !authorized {
+        return Err((
+            "401 Unauthorized",
+            String::from("Missing or invalid bearer token"),
+        ));
+    }
+
+    let mut parts = request_line.split_whitespace();
+    match (
+        parts.next(),
+        parts.next().and_then(|path| path.strip_prefix("/wake/")),
+    ) {
+        (Some("POST"), Some(pod)) if !pod.is_empty() => Ok(pod.trim_end_matches('/').to_string()),
+        _ => Err(("404 Not Found", String::from("Use POST /wake/<pod>"))),
+    }
+}
+
+/// State management commands.
+async fn cmd_state(
+    config_path: Option<&PathBuf>,
+    command: StateCommands,
+    formatter: &OutputFormatter,
+) -> Result<()> {
+    let (_config, state_store) = load_config_and_state(config_path).await?;
+
+    match command {
+        StateCommands::Show { pod, fields } => {
+            if let Some(unknown) = fields
+                .iter()
+                .find(|f| !POD_STATE_FIELDS.contains(&f.as_str()))
+            {
+                return Err(HalldyllError::internal(format!(
+                    "Unknown pod field '{unknown}'; available: {}",
+                    POD_STATE_FIELDS.join(", ")
+                )));
+            }
+            match state_store.load().await? {
+                Some(state) if pod.is_some() || !fields.is_empty() => {
+                    let pods = state.select_pods(pod.as_deref());
+                    if let Some(pod) = pod.as_deref().filter(|_| pods.is_empty()) {
+                        return Err(HalldyllError::internal(format!(
+                            "Pod '{pod}' is not in the state"
+                        )));
+                    }
+                    eprintln!("{}", formatter.format_pod_states(&pods, &fields));
+                }
+                Some(state) => eprintln!("{}", formatter.format_state(&state)),
+                None => eprintln!("No state found."),
+            }
+        }
+        StateCommands::Lock { holder } => {
+            let holder_str = holder.as_deref().unwrap_or("");
+            let lock = state_store.acquire_lock(holder_str).await?;
+            eprintln!("State locked: {}", lock.lock_id);
+        }
+        StateCommands::Unlock { lock_id, force } => {
+            if force {
+                // Force unlock by deleting lock file
+                if let Some(lock_info) = state_store.get_lock_info().await? {
+                    state_store.release_lock(&lock_info.lock_id).await?;
+                    eprintln!("State forcefully unlocked.");
+                }
+            } else if let Some(id) = lock_id {
+                state_store.release_lock(&id).await?;
+                eprintln!("State unlocked.");
+            } else {
+                eprintln!("Please provide --lock-id or use --force");
+            }
+        }
+        StateCommands::Pull => {
+            eprintln!("State pull is only applicable for remote backends.");
+        }
+        StateCommands::Push { force: _ } => {
+            eprintln!("State push is only applicable for remote backends.");
+        }
+        StateCommands::History { versions } => {
+            if versions {
+                let versions = state_store.list_versions().await?;
+                eprintln!("{}", formatter.format_state_versions(&versions));
+            } else if let Some(state) = state_store.load().await? {
+                eprintln!("{}", formatter.format_history(&state));
+            } else {
+                eprintln!("No state found.");
+            }
+        }
+        StateCommands::Rollback { version_id, yes } => {
+            cmd_state_rollback(&state_store, &version_id, yes).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// `RunPod` template commands.
+async fn cmd_template(config_path: Option<&PathBuf>, command: TemplateCommands) -> Result<()> {
+    let client = create_runpod_client()?;
+
+    match command {
+        TemplateCommands::Push { pod } => {
+            let config = load_config(config_path)?;
+            let pod_config = config.pods.iter().find(|p| p.name == pod).ok_or_else(|| {
+                HalldyllError::internal(format!("Pod '{pod}' is not defined in the configuration"))
+            })?;
+
+            let provisioner = PodProvisioner::new(client);
+            let template = provisioner
+                .push_template(pod_config, &config.project)
+                .await?;
+            eprintln!(
+                "Saved template '{}' (ID: {})",
+                template.name,
+                template.id.as_deref().unwrap_or("-")
+            );
+            if !pod_config.runtime.env.is_empty() {
+                eprintln!(
+                    "Note: environment values are stored in the template as written; keep secrets out of it."
+                );
+            }
+        }
+        TemplateCommands::Pull {
+            template,
+            name,
+            gpu,
+        } => {
+            let found = client
+                .list_templates()
+                .await?
+                .into_iter()
+                .find(|t| t.name == template || t.id.as_deref() == Some(template.as_str()))
+                .ok_or_else(|| {
+                    HalldyllError::internal(format!("Template '{template}' not found on RunPod"))
+                })?;
+
+            if found.docker_args.as_deref().is_some_and(|a| !a.is_empty()) {
+                warn!(
+                    "Template '{}' sets docker arguments, which are not carried over",
+                    found.name
+                );
+            }
+
+            let name = name.unwrap_or_else(|| found.name.to_lowercase().replace([' ', '_'], "-"));
+            let pod = PodProvisioner::pod_config_from_template(&found, &name, &gpu)?;
+            let yaml = serde_yaml::to_value(vec![pod])
+                .map(prune_yaml)
+                .and_then(|value| serde_yaml::to_string(&value))
+                .map_err(|e| {
+                    HalldyllError::internal(format!("Failed to render pod configuration: {e}"))
+                })?;
+            println!("{yaml}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Env commands.
+fn cmd_env(
+    config_path: Option<&PathBuf>,
+    command: &EnvCommands,
+    formatter: &OutputFormatter,
+) -> Result<()> {
+    let config_file = resolve_config_path(config_path)?;
+    let parser = config_parser(&config_file);
+
+    match command {
+        EnvCommands::Print => {
+            let vars = parser.resolve_env()?;
+            eprintln!("{}", formatter.format_env(&vars));
+        }
+    }
+
+    Ok(())
+}
+
+/// Maintenance commands.
+async fn cmd_maintenance(
+    config_path: Option<&PathBuf>,
+    command: MaintenanceCommands,
+) -> Result<()> {
+    let (config, state_store) = load_config_and_state(config_path).await?;
+    let mut state = state_store
+        .load()
+        .await?
+        .unwrap_or_else(|| DeploymentState::new(&config.project.name, &config.project.environment));
+
+    match command {
+        MaintenanceCommands::On { pod, reason, owner } => {
+            let known = config.pods.iter().any(|p| p.name == pod)
+                || DiffEngine::desired_instances(&config, Some(&state))
+                    .iter()
+                    .any(|p| p.name == pod);
+            if !known {
+                return Err(HalldyllError::internal(format!(
+                    "Pod '{pod}' is not defined in the configuration"
+                )));
+            }
+
+            let owner = owner
+                .or_else(|| std::env::var("USER").ok())
+                .or_else(|| std::env::var("USERNAME").ok())
+                .unwrap_or_else(|| String::from("unknown"));
+            let maintenance = MaintenanceState::new(&reason, &owner);
+            eprintln!("{pod} is under maintenance: {maintenance}");
+            eprintln!(
+                "Reconcile and watch leave it alone, and drift checks skip it, until `halldyll maintenance off {pod}`."
+            );
+            state.set_maintenance(&pod, maintenance);
+        }
+        MaintenanceCommands::Off { pod } => {
+            let Some(maintenance) = state.clear_maintenance(&pod) else {
+                eprintln!("{pod} is not under maintenance.");
+                return Ok(());
+            };
+            eprintln!("Ended maintenance of {pod}: {maintenance}");
+        }
+    }
+
+    state_store.save(&state).await
+}
+
+/// Set, remove or list a pod's annotations.
+async fn cmd_annotate(
+    config_path: Option<&PathBuf>,
+    pod: &str,
+    annotations: Vec<(String, String)>,
+    remove: &[String],
+) -> Result<()> {
+    let (config, state_store) = load_config_and_state(config_path).await?;
+    let mut state = state_store
+        .load()
+        .await?
+        .unwrap_or_else(|| DeploymentState::new(&config.project.name, &config.project.environment));
+
+    let known = config.pods.iter().any(|p| p.name == pod)
+        || DiffEngine::desired_instances(&config, Some(&state))
+            .iter()
+            .any(|p| p.name == pod)
+        || state.annotations.contains_key(pod);
+    if !known {
+        return Err(HalldyllError::internal(format!(
+            "Pod '{pod}' is not defined in the configuration"
+        )));
+    }
+
+    if annotations.is_empty() && remove.is_empty() {
+        let notes = config
+            .pods
+            .iter()
+            .find(|p| p.name == pod)
+            .and_then(|p| p.notes.as_ref());
+        let current = state.annotations.get(pod);
+        if notes.is_none() && current.is_none_or(BTreeMap::is_empty) {
+            eprintln!("{pod} has no annotations.");
+        }
+        if let Some(notes) = notes {
+            eprintln!("notes={notes} (from config)");
+        }
+        for (key, value) in current.into_iter().flatten() {
+            eprintln!("{key}={value}");
+        }
+        return Ok(());
+    }
+
+    for key in remove {
+        match state.remove_annotation(pod, key) {
+            Some(value) => eprintln!("Removed {key}={value} from {pod}"),
+            None => eprintln!("{pod} has no annotation '{key}'"),
+        }
+    }
+    for (key, value) in &annotations {
+        state.annotate(pod, key, value);
+        eprintln!("Annotated {pod}: {key}={value}");
+    }
+
+    state_store.save(&state).await
+}
+
+/// Adopt an existing `RunPod` pod into state as a configured pod.
+async fn cmd_import(config_path: Option<&PathBuf>, pod: &str, runpod_id: &str) -> Result<()> {
+    let (config, state_store) = load_config_and_state(config_path).await?;
+    let mut state = state_store
+        .load()
+        .await?
+        .unwrap_or_else(|| DeploymentState::new(&config.project.name, &config.project.environment));
+
+    if let Some(existing) = state.get_pod(pod) {
+        return Err(HalldyllError::internal(format!(
+            "Pod '{pod}' is already in state (ID: {}); destroy it before importing another pod",
+            existing.runpod_id
+        )));
+    }
+
+    let observed = PodObserver::new(create_runpod_client()?)
+        .with_tag_namespace(config.project.tag_namespace())
+        .get_pod(runpod_id)
+        .await?;
+    if let Some(project) = &observed.project {
+        return Err(HalldyllError::internal(format!(
+            "Pod {runpod_id} is already managed by halldyll (project '{project}')"
+        )));
+    }
+
+    let (pod_state, details) = DiffEngine::new()
+        .import_pod(&config, Some(&state), pod, &observed)
+        .ok_or_else(|| {
+            HalldyllError::internal(format!("Pod '{pod}' is not defined in the configuration"))
+        })?;
+    state.set_pod(pod_state);
+    state_store.save(&state).await?;
+
+    eprintln!("Imported {runpod_id} ({}) as '{pod}'.", observed.name);
+    if details.is_empty() {
+        eprintln!("It matches the configuration; apply will leave it running.");
+    } else {
+        eprintln!("It differs from the configuration, so the next apply will replace it:");
+        for detail in &details {
+            eprintln!("  {detail}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Set the replica count of a pod for the next apply.
+async fn cmd_scale(config_path: Option<&PathBuf>, pod_name: &str, count: u32) -> Result<()> {
+    let (config, state_store) = load_config_and_state(config_path).await?;
+    let pod = config
+        .pods
+        .iter()
+        .find(|p| p.name == pod_name)
+        .ok_or_else(|| {
+            HalldyllError::internal(format!(
+                "Pod '{pod_name}' is not defined in the configuration"
+            ))
+        })?;
+
+    // Only replicated pods have a replica count to override
+    if !pod.is_replicated() {
+        return Err(HalldyllError::Config(ConfigError::validation(
+            format!(
+                "Pod '{pod_name}' runs as a single pod; set `replicas` to 2 or more to scale it"
+            ),
+            format!("pods.{pod_name}.replicas"),
+        )));
+    }
+    if let Some(autoscale) = &pod.autoscale
+        && !(autoscale.min..=autoscale.max).contains(&count)
+    {
+        return Err(HalldyllError::Config(ConfigError::validation(
+            format!(
+                "{count} replicas is outside the autoscale bounds [{}, {}]",
+                autoscale.min, autoscale.max
+            ),
+            format!("pods.{pod_name}.autoscale"),
+        )));
+    }
+
+    let mut state = state_store
+        .load()
+        .await?
+        .unwrap_or_else(|| DeploymentState::new(&config.project.name, &config.project.environment));
+
+    let current = DiffEngine::replica_count(pod, Some(&state));
+    if current == count {
+        eprintln!("{pod_name} already runs {count} replicas.");
+        return Ok(());
+    }
+
+    let decision = ScaleDecision {
+        pod: pod.name.clone(),
+        current,
+        desired: count,
+        reason: String::from("scale requested"),
+    };
+    state.scale_replicas(&pod.name, count, pod.replicas);
+    state.add_history(
+        DeploymentHistoryEntry::new(
+            DeploymentOperation::Scale,
+            &ConfigHasher::new().hash_config(&config),
+            vec![pod.name.clone()],
+        )
+        .with_details(decision.to_string()),
+    );
+    state_store.save(&state).await?;
+
+    eprintln!("{decision}");
+    eprintln!(
+        "Run `halldyll apply` to create or remove replicas. Changing `replicas` in the configuration overrides this count."
+    );
+    Ok(())
+}
+
+/// Failover commands.
+async fn cmd_failover(config_path: Option<&PathBuf>, command: FailoverCommands) -> Result<()> {
+    let (config, state_store) = load_config_and_state(config_path).await?;
+    let client = create_runpod_client()?;
+    let observer =
+        PodObserver::new(client.clone()).with_tag_namespace(config.project.tag_namespace());
+    let mut provisioner = PodProvisioner::new(client);
+
+    let mut state = state_store
+        .load()
+        .await?
+        .unwrap_or_else(|| DeploymentState::new(&config.project.name, &config.project.environment));
+
+    let (auto_approve, prompt, activate) = match command {
+        FailoverCommands::Status => {
+            let manager = FailoverManager::new(&config, &provisioner, &observer)?;
+            let standby = manager.standby_pods().await?;
+            eprintln!("Active side: {}", state.active_side());
+            if let Some(changed_at) = state.failover.as_ref().and_then(|f| f.changed_at) {
+                eprintln!(
+                    "Last switched: {}",
+                    changed_at.format("%Y-%m-%d %H:%M:%S UTC")
+                );
+            }
+            if standby.is_empty() {
+                eprintln!("No standby pods.");
+            }
+            for pod in &standby {
+                let name = pod.pod_name.as_deref().unwrap_or(&pod.name);
+                let data_center = state
+                    .failover
+                    .as_ref()
+                    .and_then(|f| f.standby_pods.get(name))
+                    .map_or("-", |p| p.data_center.as_str());
+                eprintln!("  - {name} ({}, {data_center}): {}", pod.id, pod.status);
+            }
+            return Ok(());
+        }
+        FailoverCommands::Activate { yes } => (
+            yes,
+            "Provision standby pods and switch to the secondary side?",
+            true,
+        ),
+        FailoverCommands::Deactivate { yes } => (
+            yes,
+            "Terminate standby pods and switch back to the primary side?",
+            false,
+        ),
+    };
+
+    if !auto_approve {
+        eprint!("{prompt} [y/N]: ");
+        std::io::stderr().flush()?;
+
+        let mut input = String::new();
+        std::io::stdin().read_line(&mut input)?;
+
+        if !input.trim().eq_ignore_ascii_case("y") {
+            eprintln!("Failover cancelled.");
+            return Ok(());
+        }
+    }
+
+    if activate {
+        provisioner.init_gpu_types().await?;
+    }
+    let manager = FailoverManager::new(&config, &provisioner, &observer)?;
+    let result = if activate {
+        manager.activate(&mut state).await?
+    } else {
+        manager.deactivate(&mut state).await?
+    };
+
+    // Record standby pods even if some failed
+    state_store.save(&state).await?;
+    report_failover(&result, activate);
+
+    if result.is_success() {
+        eprintln!("\nActive side: {}", state.active_side());
+        Ok(())
+    } else {
+        Err(HalldyllError::internal(format!(
+            "Failover incomplete: {} pod(s) failed; active side is still {}",
+            result.failed.len(),
+            state.active_side()
+        )))
+    }
+}
+
+/// Blue/green release step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReleaseStep {
+    /// Create the release pods.
+    Deploy,
+    /// Make the release live.
+    Promote,
+    /// Discard the release.
+    Rollback,
+}
+
+/// Blue/green release commands.
+async fn cmd_release(
+    config_path: Option<&PathBuf>,
+    step: ReleaseStep,
+    auto_approve: bool,
+) -> Result<()> {
+    let (config, state_store) = load_config_and_state(config_path).await?;
+    let client = create_runpod_client()?;
+    let mut provisioner = PodProvisioner::new(client.clone());
+    let observer = project_observer(client, &config.project, &state_store).await?;
+
+    let mut state = state_store
+        .load()
+        .await?
+        .unwrap_or_else(|| DeploymentState::new(&config.project.name, &config.project.environment));
+
+    let prompt = match (step, &state.release) {
+        (ReleaseStep::Deploy, Some(release)) => format!(
+            "Continue the {} release next to the live pods?",
+            release.color
+        ),
+        (ReleaseStep::Deploy, None) => {
+            format!(
+                "Create a {} release next to the live pods?",
+                state.live_color().other()
+            )
+        }
+        (ReleaseStep::Promote, Some(release)) => format!(
+            "Terminate {} live pod(s) and make the {} release ({} pods) live?",
+            state.pods.len(),
+            release.color,
+            release.pods.len()
+        ),
+        (ReleaseStep::Rollback, Some(release)) => format!(
+            "Terminate the {} release and keep the live pods?",
+            release.color
+        ),
+        (_, None) => {
+            eprintln!(
+                "No blue/green release in progress. Start one with `halldyll deploy --blue-green`."
+            );
+            return Ok(());
+        }
+    };
+
+    if !auto_approve {
+        eprint!("{prompt} [y/N]: ");
+        std::io::stderr().flush()?;
+
+        let mut input = String::new();
+        std::io::stdin().read_line(&mut input)?;
+
+        if !input.trim().eq_ignore_ascii_case("y") {
+            eprintln!("Release cancelled.");
+            return Ok(());
+        }
+    }
+
+    if step == ReleaseStep::Deploy {
+        provisioner.init_gpu_types().await?;
+    }
+    state_store.snapshot().await?;
+    let manager = ReleaseManager::new(&config, &provisioner, &observer);
+    let result = match step {
+        ReleaseStep::Deploy => manager.deploy(&mut state).await?,
+        ReleaseStep::Promote => manager.promote(&mut state).await?,
+        ReleaseStep::Rollback => manager.rollback(&mut state).await?,
+    };
+
+    // Record the pods that changed even if some failed
+    state_store.save(&state).await?;
+    report_release(&result, step);
+
+    if !result.is_success() {
+        return Err(HalldyllError::internal(format!(
+            "Release step incomplete: {} pod(s) failed",
+            result.failed.len()
+        )));
+    }
+    match step {
+        ReleaseStep::Deploy => eprintln!(
+            "\nRun `halldyll promote` to make the release live, or `halldyll rollback` to discard it."
+        ),
+        ReleaseStep::Promote => eprintln!("\nThe {} release is live.", state.live_color()),
+        ReleaseStep::Rollback => eprintln!("\nThe live pods were kept."),
+    }
+    Ok(())
+}
+
+/// Prints the pods a release step changed.
+fn report_release(result: &ReleaseResult, step: ReleaseStep) {
+    let verb = match step {
+        ReleaseStep::Deploy => "Created release pod",
+        ReleaseStep::Promote => "Terminated live pod",
+        ReleaseStep::Rollback => "Terminated release pod",
+    };
+    for pod in &result.changed {
+        eprintln!("{verb} {pod}");
+    }
+    for pod in &result.unchanged {
+        eprintln!("Release pod {pod} already exists");
+    }
+    for (pod, error) in &result.failed {
+        eprintln!("Failed: {pod}: {error}");
+    }
+}
+
+/// Prints the pods a failover changed.
+fn report_failover(result: &FailoverResult, activate: bool) {
+    let verb = if activate { "Created" } else { "Terminated" };
+    for pod in &result.changed {
+        eprintln!("{verb} standby {pod}");
+    }
+    for pod in &result.unchanged {
+        eprintln!("Standby {pod} already exists");
+    }
+    for (pod, error) in &result.failed {
+        eprintln!("Failed: {pod}: {error}");
+    }
+}
+
+/// Drops null and empty values so rendered configuration only shows what is set.
+fn prune_yaml(value: serde_yaml::Value) -> serde_yaml::Value {
+    use serde_yaml::Value;
+
+    let is_empty = |v: &Value| match v {
+        Value::Null => true,
+        Value::Sequence(seq) => seq.is_empty(),
+        Value::Mapping(map) => map.is_empty(),
+        _ => false,
+    };
+
+    match value {
+        Value::Sequence(seq) => Value::Sequence(seq.into_iter().map(prune_yaml).collect()),
+        Value::Mapping(map) => Value::Mapping(
+            map.into_iter()
+                .map(|(k, v)| (k, prune_yaml(v)))
+                .filter(|(_, v)| !is_empty(v))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+/// Restores a stored version of the state object.
+async fn cmd_state_rollback(
+    state_store: &dyn StateStore,
+    version_id: &str,
+    auto_approve: bool,
+) -> Result<()> {
+    let restored = state_store.load_version(version_id).await?;
+    // Loading the current state records its version, so the restore fails
+    // cleanly if another writer updates the state meanwhile.
+    let current = state_store.load().await?;
+
+    eprintln!(
+        "Restoring state version {version_id} (last updated {}, {} pods)",
+        restored.last_updated.format("%Y-%m-%d %H:%M"),
+        restored.pods.len()
+    );
+    if let Some(current) = &current {
+        eprintln!(
+            "Current state: last updated {}, {} pods",
+            current.last_updated.format("%Y-%m-%d %H:%M"),
+            current.pods.len()
+        );
+    }
+
+    if !auto_approve {
+        eprint!("Do you want to restore this version? [y/N]: ");
+        std::io::stderr().flush()?;
+
+        let mut input = String::new();
+        std::io::stdin().read_line(&mut input)?;
+
+        if !input.trim().eq_ignore_ascii_case("y") {
+            eprintln!("Rollback cancelled.");
+            return Ok(());
+        }
+    }
+
+    state_store.snapshot().await?;
+    state_store.save(&restored).await?;
+    eprintln!("State restored. Run `plan` to see how the deployment differs from it.");
+
+    Ok(())
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Adds costs and the account runway to a plan.
+///
+/// A failed pricing or account lookup only costs the plan those lines,
+/// unless `guardrails.max_hourly_cost` or `min_runway_hours` requires them.
+async fn price_plan(
+    client: &RunPodClient,
+    config: &halldyll_deploy_pods::config::DeployConfig,
+    state: Option<&DeploymentState>,
+    observed: &[halldyll_deploy_pods::runpod::ObservedPod],
+    plan: DeploymentPlan,
+) -> Result<DeploymentPlan> {
+    let guardrails = config.guardrails.as_ref();
+    let prices = match client.list_gpu_types().await {
+        Ok(gpu_types) => GpuPrices::new(&gpu_types, config.project.cloud_type),
+        Err(e)
+            if guardrails
+                .is_some_and(|g| g.max_hourly_cost.is_some() || g.min_runway_hours.is_some()) =>
+        {
+            return Err(
+                e.with_context(ErrorContext::new("fetching GPU prices for cost guardrails"))
+            );
+        }
+        Err(e) => {
+            debug!("Skipping cost estimate: {e}");
+            return Ok(plan);
+        }
+    };
+    let plan = plan.with_costs(&prices, observed, guardrails);
+
+    match client.get_account().await {
+        Ok(account) => {
+            let desired = DiffEngine::desired_instances(config, state);
+            let runway = Runway::estimate(&account, &prices, &desired, observed);
+            Ok(plan.with_runway(runway, guardrails))
+        }
+        Err(e) if guardrails.is_some_and(|g| g.min_runway_hours.is_some()) => Err(e.with_context(
+            ErrorContext::new("checking account balance for min_runway_hours"),
+        )),
+        Err(e) => {
+            debug!("Skipping runway estimate: {e}");
+            Ok(plan)
+        }
+    }
+}
+
+/// Resolves the configuration file path.
+fn resolve_config_path(config_path: Option<&PathBuf>) -> Result<PathBuf> {
+    config_path.map_or_else(|| find_config_file("."), |path| Ok(path.clone()))
+}
+
+/// Loads configuration and creates appropriate state store.
+async fn load_config_and_state(
+    config_path: Option<&PathBuf>,
+) -> Result<(DeployConfig, Box<dyn StateStore>)> {
+    let config = load_config(config_path)?;
+
+    // Create state store based on config
+    let state_store: Box<dyn StateStore> = match config.state.backend {
+        StateBackend::Local => Box::new(
+            LocalStateStore::with_base_dir(local_state_dir(config_path, &config)?)
+                .with_keep_snapshots(config.state.keep_snapshots),
+        ),
+        StateBackend::S3 => s3_state_store(&config).await?,
+        StateBackend::Http => Box::new(HttpStateStore::from_config(&config.state)?),
+    };
+
+    Ok((config, state_store))
+}
+
+/// Creates the S3 state store.
+#[cfg(feature = "s3")]
+async fn s3_state_store(config: &DeployConfig) -> Result<Box<dyn StateStore>> {
+    Ok(Box::new(
+        halldyll_deploy_pods::state::S3StateStore::from_config(&config.state).await?,
+    ))
+}
+
+/// Without the `s3` feature, the S3 state backend is unavailable.
+#[cfg(not(feature = "s3"))]
+#[allow(clippy::unused_async)]
+async fn s3_state_store(_config: &DeployConfig) -> Result<Box<dyn StateStore>> {
+    Err(ConfigError::feature_disabled("s3", "The S3 state backend").into())
+}
+
+/// Loads and validates the configuration.
+fn load_config(config_path: Option<&PathBuf>) -> Result<DeployConfig> {
+    let config_file = resolve_config_path(config_path)?;
+    let mut config = load_unlocked_config(&config_file)?;
+    apply_lockfile(&config_file, &mut config)?;
+    Ok(config)
+}
+
+/// Replaces references with the values locked in `halldyll.lock`, if any.
+fn apply_lockfile(config_file: &std::path::Path, config: &mut DeployConfig) -> Result<()> {
+    // Use locked GPU types, image digests and model revisions
+    if std::env::var_os("HALLDYLL_NO_LOCK").is_none()
+        && let Some(lock) = Lockfile::load(&Lockfile::path_for(config_file))?
+    {
+        let missing = lock.missing(config);
+        if !missing.is_empty() {
+            warn!(
+                "Not in {LOCKFILE_NAME} (run `halldyll lock`): {}",
+                missing.names().join(", ")
+            );
+        }
+        lock.apply(config);
+    }
+
+    Ok(())
+}
+
+/// Load and validate a configuration file as written (plus `--set` overrides),
+/// without applying the lockfile.
+fn load_unlocked_config(config_file: &std::path::Path) -> Result<DeployConfig> {
+    let config = apply_overrides(
+        &parse_config_file(config_file)?,
+        &global_options().overrides,
+    )?;
+
+    // Validate
+    let validator = ConfigValidator::new();
+    validator.validate(&config)?;
+
+    Ok(config)
+}
+
+/// Returns the parser for a configuration file, following `--env` and `--var` if given.
+fn config_parser(config_file: &std::path::Path) -> ConfigParser {
+    let options = global_options();
+    let mut parser = ConfigParser::for_config_file(config_file);
+    if let Some(environment) = &options.environment {
+        parser = parser.with_environment(environment);
+    }
+    options
+        .vars
+        .iter()
+        .fold(parser, |parser, (name, value)| parser.with_var(name, value))
+}
+
+/// Parses a configuration file (after loading its dotenv files) without validating it.
+fn parse_config_file(config_file: &std::path::Path) -> Result<DeployConfig> {
+    debug!("Loading configuration from: {}", config_file.display());
+
+    let parser = config_parser(config_file);
+    parser.load_dotenv()?;
+    let parser = prompt_for_variables(parser, config_file)?;
+
+    parser.load_with_env(config_file)
+}
+
+/// Asks on the terminal for the variables the configuration references but
+/// nothing sets, hiding the input of sensitive ones.
+///
+/// Without a terminal nothing is asked, and loading fails with the list.
+fn prompt_for_variables(
+    mut parser: ConfigParser,
+    config_file: &std::path::Path,
+) -> Result<ConfigParser> {
+    use std::io::IsTerminal;
+
+    if !std::io::stdin().is_terminal() {
+        return Ok(parser);
+    }
+    for variable in parser.missing_variables(config_file)? {
+        let label = match &variable.description {
+            Some(description) => format!("{} ({description})", variable.name),
+            None => variable.name.clone(),
+        };
+        let value = prompt_value(&label, variable.sensitive)?;
+        parser = parser.with_var(variable.name, value);
+    }
+    Ok(parser)
+}
+
+/// Returns the local state directory (`state.path`, else the platform default).
+fn local_state_dir(config_path: Option<&PathBuf>, config: &DeployConfig) -> Result<PathBuf> {
+    let config_file = resolve_config_path(config_path)?;
+    let config_dir = config_file
+        .parent()
+        .unwrap_or_else(|| std::path::Path::new("."));
+
+    Ok(
+        match (&config.state.backend, config.state.path.as_deref()) {
+            // Relative to the working directory, as state.path has always been
+            (StateBackend::Local, Some(raw)) => platform::absolute(&platform::expand_path(raw)),
+            _ => platform::default_state_dir(config_dir, &config.project.name),
+        },
+    )
+}
+
+/// Returns the directory of pod command transcripts.
+fn transcript_dir(config_path: Option<&PathBuf>, config: &DeployConfig) -> Result<PathBuf> {
+    Ok(local_state_dir(config_path, config)?.join("transcripts"))
+}
+
+/// Records observed pods for `status --cached`; failures only warn.
+async fn cache_status(
+    config_path: Option<&PathBuf>,
+    config: &DeployConfig,
+    status: &ProjectStatus,
+) {
+    let result = match local_state_dir(config_path, config) {
+        Ok(dir) => StatusCache::new(dir).write(status).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        warn!("Failed to cache project status: {e}");
+    }
+}
+
+/// Rewrites `authorized_keys` on running pods whose configured SSH keys
+/// changed since they were installed.
+async fn rotate_ssh_keys(
+    config: &halldyll_deploy_pods::config::DeployConfig,
+    state: &mut DeploymentState,
+    client: &RunPodClient,
+) {
+    let executor = PodExecutor::new(client.clone());
+
+    for pod in DiffEngine::desired_instances(config, Some(state)) {
+        let Some(ssh) = pod.ssh_config(&config.project) else {
+            continue;
+        };
+        let keys = match ssh.public_keys() {
+            Ok(keys) if !keys.is_empty() => keys,
+            Ok(_) => continue,
+            Err(e) => {
+                warn!("Skipping SSH key rotation for {}: {e}", pod.name);
+                continue;
+            }
+        };
+        let fingerprint = keys_fingerprint(&keys);
+        let Some(pod_state) = state.pods.get_mut(&pod.name) else {
+            continue;
+        };
+        if !matches!(
+            pod_state.status,
+            DeploymentStatus::Creating | DeploymentStatus::Running
+        ) || pod_state.ssh_keys.as_deref() == Some(fingerprint.as_str())
+        {
+            continue;
+        }
+
+        match executor
+            .install_authorized_keys(&pod_state.runpod_id, &keys)
+            .await
+        {
+            Ok(()) => {
+                eprintln!("Updated SSH keys on pod {}", pod.name);
+                pod_state.ssh_keys = Some(fingerprint);
+            }
+            Err(e) => warn!("Failed to update SSH keys on pod {}: {e}", pod.name),
+        }
+    }
+}
+
+/// Regenerates load balancer manifests for pods that declare one.
+async fn write_lb_manifests(
+    config: &halldyll_deploy_pods::config::DeployConfig,
+    state: Option<&DeploymentState>,
+    observer: &PodObserver,
+) -> Result<()> {
+    if !config.pods.iter().any(|p| p.load_balancer.is_some()) {
+        return Ok(());
+    }
+
+    let pods = observer
+        .list_project_pods(&config.project.name, &config.project.environment)
+        .await?;
+
+    for path in ManifestGenerator::new().write_all(config, state, &pods)? {
+        eprintln!("Updated load balancer manifest: {}", path.display());
+    }
+
+    Ok(())
+}
+
+/// Creates an observer that also sees the pods imported into the saved state.
+async fn project_observer(
+    client: RunPodClient,
+    project: &ProjectConfig,
+    state_store: &dyn StateStore,
+) -> Result<PodObserver> {
+    let state = state_store.load().await?;
+    Ok(PodObserver::new(client)
+        .with_tag_namespace(project.tag_namespace())
+        .with_imported_pods(state.as_ref()))
+}
+
+/// Creates a `RunPod` API client.
+fn create_runpod_client() -> Result<RunPodClient> {
+    let client = RunPodClient::new(&runpod_api_key()?)?;
+    with_ssh_client(client)
+}
+
+/// API key typed at the prompt, so it is asked for at most once per run.
+static PROMPTED_API_KEY: OnceLock<String> = OnceLock::new();
+
+/// Returns `RUNPOD_API_KEY`, asking for it on a terminal if it is unset.
+///
+/// Without a terminal a missing key is an error.
+fn runpod_api_key() -> Result<String> {
+    use std::io::IsTerminal;
+
+    if let Some(key) = PROMPTED_API_KEY.get() {
+        return Ok(key.clone());
+    }
+    match ConfigParser::get_runpod_api_key() {
+        Err(e) if !std::io::stdin().is_terminal() => Err(e),
+        Err(_) => {
+            let key = prompt_value("RUNPOD_API_KEY (RunPod API key)", true)?;
+            Ok(PROMPTED_API_KEY.get_or_init(|| key).clone())
+        }
+        key => key,
+    }
+}
+
+/// Asks for a value on the terminal, without echoing `sensitive` input.
+fn prompt_value(label: &str, sensitive: bool) -> Result<String> {
+    eprint!("{label}: ");
+    std::io::stderr().flush()?;
+
+    let value = if sensitive {
+        read_hidden_line()?
+    } else {
+        let mut input = String::new();
+        std::io::stdin().read_line(&mut input)?;
+        input
+    };
+    Ok(value.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Leaves terminal raw mode when dropped.
+struct RawModeGuard;
+
+impl Drop for RawModeGuard {
+    fn drop(&mut self) {
+        let _ = crossterm::terminal::disable_raw_mode();
+    }
+}
+
+/// Reads a line from the terminal without echoing it.
+///
+/// The terminal is restored however the read ends; Ctrl+C cancels it.
+fn read_hidden_line() -> Result<String> {
+    use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+
+    crossterm::terminal::enable_raw_mode()?;
+    let _raw_mode = RawModeGuard;
+
+    let mut input = String::new();
+    loop {
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind == KeyEventKind::Release {
+            continue;
+        }
+        match key.code {
+            KeyCode::Enter => break,
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                eprint!("\r\n");
+                return Err(std::io::Error::from(std::io::ErrorKind::Interrupted).into());
+            }
+            KeyCode::Char(c) => input.push(c),
+            _ => {}
+        }
+    }
+    eprint!("\r\n");
+    Ok(input)
+}
+
+/// Runs pod commands over SSH when `RUNPOD_SSH_KEY` is set.
+#[cfg(feature = "ssh")]
+fn with_ssh_client(client: RunPodClient) -> Result<RunPodClient> {
+    use halldyll_deploy_pods::runpod::{KnownHosts, SshClient};
+
+    Ok(match SshClient::from_env()? {
+        Some(ssh) => match &global_options().known_hosts {
+            Some(path) => client.with_ssh(ssh.with_known_hosts(KnownHosts::new(path))),
+            None => client.with_ssh(ssh),
+        },
+        None => client,
+    })
+}
+
+/// Without the `ssh` feature, pod commands always go through the exec API.
+#[cfg(not(feature = "ssh"))]
+#[allow(clippy::unnecessary_wraps)]
+fn with_ssh_client(client: RunPodClient) -> Result<RunPodClient> {
+    if std::env::var_os(halldyll_deploy_pods::runpod::SSH_KEY_ENV).is_some()
+        || global_options().known_hosts.is_some()
+    {
+        warn!("RUNPOD_SSH_KEY is ignored: halldyll was built without the `ssh` feature");
+    }
+    Ok(client)
 }
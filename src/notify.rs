@@ -0,0 +1,84 @@
+//! Delivery of watch alerts to webhooks.
+//!
+//! Alerts are posted as `{"text": "..."}`, which Slack, Mattermost and most
+//! chat incoming webhooks accept as-is. Delivery failures are logged and do
+//! not stop the watch loop. A webhook written as `${VAR}` is read from that
+//! environment variable, so its credentials stay out of the configuration.
+
+use reqwest::Client;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+use crate::error::{ConfigError, HalldyllError, Result};
+use crate::planner::Alert;
+use crate::version::USER_AGENT;
+
+/// Timeout of a webhook request in seconds.
+const WEBHOOK_TIMEOUT_SECS: u64 = 10;
+
+/// Posts alerts to the configured webhooks.
+#[derive(Debug, Clone)]
+pub struct WebhookNotifier {
+    /// HTTP client.
+    client: Client,
+    /// Webhook URLs.
+    webhooks: Vec<String>,
+}
+
+impl WebhookNotifier {
+    /// Creates a notifier for `webhooks`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a referenced environment variable is not set or the
+    /// HTTP client cannot be created.
+    pub fn new(webhooks: &[String]) -> Result<Self> {
+        let webhooks = webhooks
+            .iter()
+            .map(|webhook| {
+                webhook
+                    .trim()
+                    .strip_prefix("${")
+                    .and_then(|v| v.strip_suffix('}'))
+                    .map_or_else(
+                        || Ok(webhook.clone()),
+                        |name| {
+                            std::env::var(name).map_err(|_| {
+                                HalldyllError::Config(ConfigError::MissingEnvVar {
+                                    name: name.to_string(),
+                                })
+                            })
+                        },
+                    )
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let client = Client::builder()
+            .timeout(Duration::from_secs(WEBHOOK_TIMEOUT_SECS))
+            .user_agent(USER_AGENT)
+            .build()
+            .map_err(|e| HalldyllError::internal(format!("Failed to create HTTP client: {e}")))?;
+
+        Ok(Self { client, webhooks })
+    }
+
+    /// Sends each alert to every webhook, prefixed with the project.
+    pub async fn send(&self, project: &str, alerts: &[Alert]) {
+        for alert in alerts {
+            let body = serde_json::json!({ "text": format!("[halldyll {project}] {alert}") });
+            for webhook in &self.webhooks {
+                match self.client.post(webhook).json(&body).send().await {
+                    Ok(response) if response.status().is_success() => {
+                        debug!("Sent alert {} to webhook", alert.key);
+                    }
+                    Ok(response) => warn!(
+                        "Alert webhook answered {} for {}",
+                        response.status(),
+                        alert.key
+                    ),
+                    Err(e) => warn!("Failed to send alert {}: {e}", alert.key),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,164 @@
+//! Alert evaluation for watch mode.
+//!
+//! Every watch tick reports which alert conditions hold (hourly cost above
+//! the threshold, unhealthy pods, drifted pods). A condition raises an alert
+//! once it has held for its configured duration, and again every
+//! `cooldown_minutes` while it keeps holding. Conditions are tracked in the
+//! deployment state, so restarting watch neither resets how long a condition
+//! has held nor re-sends an alert inside its cooldown.
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::config::AlertsConfig;
+use crate::state::{AlertState, DeploymentState};
+
+/// Alert conditions observed during one watch tick.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AlertInputs {
+    /// Current cost of the project's pods in USD per hour, if known.
+    pub cost_per_hour: Option<f64>,
+    /// Pods failing their health check.
+    pub unhealthy: Vec<String>,
+    /// Pods that differ from the configuration.
+    pub drifted: Vec<String>,
+}
+
+/// An alert to send.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alert {
+    /// Condition key (e.g. `unhealthy/llm-0`).
+    pub key: String,
+    /// Human-readable message.
+    pub message: String,
+}
+
+impl std::fmt::Display for Alert {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Updates the conditions tracked in `state` and returns the alerts due at `now`.
+///
+/// Conditions that no longer hold are dropped, so they start over if they return.
+#[must_use]
+pub fn evaluate_alerts(
+    config: &AlertsConfig,
+    inputs: &AlertInputs,
+    state: &mut DeploymentState,
+    now: DateTime<Utc>,
+) -> Vec<Alert> {
+    // Conditions that hold now: key, how long they must hold, message
+    let mut holding: Vec<(String, Duration, String)> = Vec::new();
+    if let (Some(threshold), Some(cost)) = (config.hourly_cost_above, inputs.cost_per_hour)
+        && cost > threshold
+    {
+        holding.push((
+            String::from("cost"),
+            Duration::zero(),
+            format!("Hourly cost is ${cost:.2}, above the ${threshold:.2} alert threshold"),
+        ));
+    }
+    if let Some(minutes) = config.unhealthy_minutes {
+        for pod in &inputs.unhealthy {
+            holding.push((
+                format!("unhealthy/{pod}"),
+                minutes_duration(minutes),
+                format!("Pod {pod} has been unhealthy for {minutes}+ minutes"),
+            ));
+        }
+    }
+    if let Some(minutes) = config.drift_minutes {
+        for pod in &inputs.drifted {
+            holding.push((
+                format!("drift/{pod}"),
+                minutes_duration(minutes),
+                format!("Pod {pod} has drifted from the configuration for {minutes}+ minutes"),
+            ));
+        }
+    }
+
+    let tracked = &mut state.alerts;
+    tracked.retain(|key, _| holding.iter().any(|(k, _, _)| k == key));
+
+    let cooldown = minutes_duration(config.cooldown_minutes);
+    let mut alerts = Vec::new();
+    for (key, after, message) in holding {
+        let condition = tracked.entry(key.clone()).or_insert(AlertState {
+            since: now,
+            notified_at: None,
+        });
+        let due = now - condition.since >= after
+            && condition
+                .notified_at
+                .is_none_or(|sent| now - sent >= cooldown);
+        if due {
+            condition.notified_at = Some(now);
+            alerts.push(Alert { key, message });
+        }
+    }
+    alerts
+}
+
+/// Converts configured minutes to a duration.
+fn minutes_duration(minutes: u64) -> Duration {
+    Duration::minutes(i64::try_from(minutes).unwrap_or(i64::MAX / 60_000))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alerts_wait_for_duration_and_cooldown() {
+        let config = AlertsConfig {
+            hourly_cost_above: Some(5.0),
+            unhealthy_minutes: Some(10),
+            drift_minutes: None,
+            cooldown_minutes: 60,
+            webhooks: vec![],
+        };
+        let inputs = AlertInputs {
+            cost_per_hour: Some(6.5),
+            unhealthy: vec![String::from("llm-0")],
+            drifted: vec![String::from("cache")],
+        };
+        let start = Utc::now();
+        let mut state = DeploymentState::new("proj", "prod");
+        let keys = |alerts: Vec<Alert>| alerts.into_iter().map(|a| a.key).collect::<Vec<_>>();
+
+        // Cost alerts at once; the pod must stay unhealthy for 10 minutes; drift is not configured
+        assert_eq!(
+            keys(evaluate_alerts(&config, &inputs, &mut state, start)),
+            ["cost"]
+        );
+        let later = start + Duration::minutes(10);
+        assert_eq!(
+            keys(evaluate_alerts(&config, &inputs, &mut state, later)),
+            ["unhealthy/llm-0"]
+        );
+
+        // Both are in their cooldown until an hour after they were sent
+        assert!(
+            evaluate_alerts(&config, &inputs, &mut state, later + Duration::minutes(5)).is_empty()
+        );
+        let after_cooldown = start + Duration::minutes(61);
+        assert_eq!(
+            keys(evaluate_alerts(
+                &config,
+                &inputs,
+                &mut state,
+                after_cooldown
+            )),
+            ["cost"]
+        );
+
+        // A recovered pod starts over
+        let recovered = AlertInputs {
+            unhealthy: vec![],
+            ..inputs
+        };
+        assert!(evaluate_alerts(&config, &recovered, &mut state, after_cooldown).is_empty());
+        assert_eq!(state.alerts.keys().collect::<Vec<_>>(), ["cost"]);
+    }
+}
@@ -0,0 +1,225 @@
+//! Autoscaling decisions for replicated pods.
+//!
+//! This module turns observed load (engine metrics, GPU utilization) into a
+//! desired replica count, honouring the bounds and cooldowns configured in
+//! the pod's `autoscale` block. The resulting count is recorded in state and
+//! picked up by the diff engine on the next plan.
+
+use chrono::{DateTime, Utc};
+use tracing::debug;
+
+use crate::config::{AutoscaleConfig, PodConfig};
+
+/// Relative deviation from the target that is tolerated without scaling.
+const SCALE_TOLERANCE: f64 = 0.1;
+
+/// Average load observed across the replicas of a pod.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ReplicaLoad {
+    /// Average in-flight requests per replica (if scraped).
+    pub concurrent_requests: Option<f64>,
+    /// Average GPU utilization in percent (if reported).
+    pub gpu_util: Option<f64>,
+    /// Number of replicas that contributed samples.
+    pub sampled: u32,
+}
+
+/// A scaling decision for a single pod.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct ScaleDecision {
+    /// Pod name (from config).
+    pub pod: String,
+    /// Replica count before scaling.
+    pub current: u32,
+    /// Replica count after scaling.
+    pub desired: u32,
+    /// Human-readable reason.
+    pub reason: String,
+}
+
+/// Autoscaler computing replica counts from observed load.
+#[derive(Debug, Default)]
+pub struct Autoscaler;
+
+impl Autoscaler {
+    /// Creates a new autoscaler.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self
+    }
+
+    /// Evaluates a pod's load and returns a scaling decision, if any.
+    ///
+    /// Returns `None` when the pod is not autoscaled, no samples are
+    /// available, the load is within tolerance, or a cooldown is active.
+    #[must_use]
+    pub fn evaluate(
+        &self,
+        pod: &PodConfig,
+        current: u32,
+        load: &ReplicaLoad,
+        last_scaled_at: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> Option<ScaleDecision> {
+        let autoscale = pod.autoscale.as_ref()?;
+        if load.sampled == 0 {
+            debug!("No load samples for {}, skipping autoscale", pod.name);
+            return None;
+        }
+
+        let mut proposals = Vec::new();
+        if let (Some(target), Some(observed)) = (
+            autoscale.target_concurrent_requests,
+            load.concurrent_requests,
+        ) {
+            proposals.push((
+                Self::proposed_replicas(current, observed, f64::from(target)),
+                format!("{observed:.1} concurrent requests/replica (target {target})"),
+            ));
+        }
+        if let (Some(target), Some(observed)) = (autoscale.target_gpu_util, load.gpu_util) {
+            proposals.push((
+                Self::proposed_replicas(current, observed, f64::from(target)),
+                format!("{observed:.0}% GPU utilization (target {target}%)"),
+            ));
+        }
+
+        let (proposed, reason) = proposals.into_iter().max_by_key(|(count, _)| *count)?;
+        let desired = pod.clamp_replicas(proposed);
+
+        if desired == current {
+            return None;
+        }
+
+        if let Some(last) = last_scaled_at {
+            let cooldown = Self::cooldown_secs(autoscale, desired > current);
+            let elapsed = (now - last).num_seconds();
+            if elapsed < i64::try_from(cooldown).unwrap_or(i64::MAX) {
+                debug!(
+                    "Autoscale for {} wants {current} -> {desired} but cooldown is active ({elapsed}s/{cooldown}s)",
+                    pod.name
+                );
+                return None;
+            }
+        }
+
+        Some(ScaleDecision {
+            pod: pod.name.clone(),
+            current,
+            desired,
+            reason,
+        })
+    }
+
+    /// Computes the replica count that brings the observed metric to target.
+    fn proposed_replicas(current: u32, observed: f64, target: f64) -> u32 {
+        let ratio = observed / target;
+        if (ratio - 1.0).abs() <= SCALE_TOLERANCE {
+            return current;
+        }
+
+        let raw = (f64::from(current.max(1)) * ratio).ceil();
+        if raw <= 0.0 {
+            0
+        } else if raw >= f64::from(u32::MAX) {
+            u32::MAX
+        } else {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let count = raw as u32;
+            count
+        }
+    }
+
+    /// Returns the cooldown for the given scaling direction.
+    const fn cooldown_secs(autoscale: &AutoscaleConfig, scaling_up: bool) -> u64 {
+        if scaling_up {
+            autoscale.scale_up_cooldown_secs
+        } else {
+            autoscale.scale_down_cooldown_secs
+        }
+    }
+}
+
+impl std::fmt::Display for ScaleDecision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let direction = if self.desired > self.current {
+            "up"
+        } else {
+            "down"
+        };
+        write!(
+            f,
+            "Scale {direction} '{}': {} -> {} replicas ({})",
+            self.pod, self.current, self.desired, self.reason
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn autoscaled_pod() -> PodConfig {
+        let yaml = r#"
+name: inference
+gpu:
+  type: "NVIDIA A40"
+runtime:
+  image: "vllm/vllm-openai:v0.6.0"
+autoscale:
+  min: 1
+  max: 4
+  target_concurrent_requests: 10
+"#;
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    fn load(concurrent: f64) -> ReplicaLoad {
+        ReplicaLoad {
+            concurrent_requests: Some(concurrent),
+            gpu_util: None,
+            sampled: 1,
+        }
+    }
+
+    #[test]
+    fn test_scale_up_and_clamp() {
+        let pod = autoscaled_pod();
+        let now = Utc::now();
+
+        let up = Autoscaler::new()
+            .evaluate(&pod, 2, &load(15.0), None, now)
+            .unwrap();
+        assert_eq!(up.desired, 3);
+
+        let capped = Autoscaler::new()
+            .evaluate(&pod, 2, &load(100.0), None, now)
+            .unwrap();
+        assert_eq!(capped.desired, 4);
+    }
+
+    #[test]
+    fn test_within_tolerance_and_cooldown() {
+        let pod = autoscaled_pod();
+        let now = Utc::now();
+
+        assert!(
+            Autoscaler::new()
+                .evaluate(&pod, 2, &load(10.5), None, now)
+                .is_none()
+        );
+
+        let recent = Some(now - Duration::seconds(30));
+        assert!(
+            Autoscaler::new()
+                .evaluate(&pod, 2, &load(15.0), recent, now)
+                .is_none()
+        );
+
+        let down = Autoscaler::new()
+            .evaluate(&pod, 3, &load(2.0), None, now)
+            .unwrap();
+        assert_eq!(down.desired, 1);
+    }
+}
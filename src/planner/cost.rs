@@ -0,0 +1,177 @@
+//! Running cost report for a project's pods.
+//!
+//! Combines observed pods with GPU prices and creation times from the state
+//! to show what each pod costs per hour now and has cost since it was created.
+
+use chrono::{DateTime, Utc};
+
+use crate::runpod::{ObservedPod, PodStatus};
+use crate::state::DeploymentState;
+
+use super::runway::GpuPrices;
+
+/// Cost of one pod.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PodCost {
+    /// Local pod name (or the `RunPod` name if untagged).
+    pub name: String,
+    /// `RunPod` pod ID.
+    pub pod_id: String,
+    /// Current status.
+    pub status: PodStatus,
+    /// GPU type, if known.
+    pub gpu_type: Option<String>,
+    /// Number of GPUs.
+    pub gpu_count: u32,
+    /// Current cost in USD per hour (GPUs when running, volume when stopped).
+    pub cost_per_hour: Option<f64>,
+    /// When the pod was created, or last started if it is not in the state.
+    pub since: Option<DateTime<Utc>>,
+    /// GPU cost in USD accumulated since `since`, assuming the pod ran throughout.
+    pub accumulated: Option<f64>,
+}
+
+/// Costs of all pods of a project environment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostReport {
+    /// Project name.
+    pub project: String,
+    /// Environment name.
+    pub environment: String,
+    /// Per-pod costs.
+    pub pods: Vec<PodCost>,
+}
+
+impl CostReport {
+    /// Builds the report at time `now`.
+    ///
+    /// Stopped pods are billed for their volume only and accumulate no GPU
+    /// cost, since how long they ran before stopping is not known.
+    #[must_use]
+    pub fn new(
+        project: &str,
+        environment: &str,
+        observed: &[ObservedPod],
+        state: Option<&DeploymentState>,
+        prices: &GpuPrices,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let pods = observed
+            .iter()
+            .map(|pod| {
+                let gpu_hourly =
+                    prices.hourly(pod.gpu_type.as_deref().unwrap_or_default(), pod.gpu_count);
+                let created_at = state
+                    .and_then(|s| s.pods.values().find(|p| p.runpod_id == pod.id))
+                    .map(|p| p.created_at);
+                let since = created_at.or_else(|| {
+                    (pod.uptime_secs > 0).then(|| {
+                        now - chrono::Duration::seconds(
+                            i64::try_from(pod.uptime_secs).unwrap_or(i64::MAX),
+                        )
+                    })
+                });
+
+                let (cost_per_hour, accumulated) = if pod.is_stopped() {
+                    (pod.stopped_storage_cost_per_hour(), None)
+                } else {
+                    #[allow(clippy::cast_precision_loss)]
+                    let accumulated = since.zip(gpu_hourly).map(|(since, hourly)| {
+                        hourly * (now - since).num_seconds().max(0) as f64 / 3600.0
+                    });
+                    (gpu_hourly, accumulated)
+                };
+
+                PodCost {
+                    name: pod.pod_name.clone().unwrap_or_else(|| pod.name.clone()),
+                    pod_id: pod.id.clone(),
+                    status: pod.status,
+                    gpu_type: pod.gpu_type.clone(),
+                    gpu_count: pod.gpu_count,
+                    cost_per_hour,
+                    since,
+                    accumulated,
+                }
+            })
+            .collect();
+
+        Self {
+            project: project.to_string(),
+            environment: environment.to_string(),
+            pods,
+        }
+    }
+
+    /// Returns the project's current cost in USD per hour.
+    #[must_use]
+    pub fn cost_per_hour(&self) -> f64 {
+        self.pods.iter().filter_map(|p| p.cost_per_hour).sum()
+    }
+
+    /// Returns the GPU cost in USD the project's pods have accumulated.
+    #[must_use]
+    pub fn accumulated(&self) -> f64 {
+        self.pods.iter().filter_map(|p| p.accumulated).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CloudType;
+    use crate::runpod::GpuType;
+    use crate::state::PodState;
+
+    #[test]
+    fn test_cost_report() {
+        let gpu: GpuType = serde_json::from_value(serde_json::json!({
+            "id": "NVIDIA A40",
+            "displayName": "A40",
+            "memoryInGb": 48,
+            "securePrice": 0.5
+        }))
+        .unwrap();
+        let prices = GpuPrices::new(&[gpu], CloudType::Secure);
+        let now = Utc::now();
+
+        let pod = |id: &str, name: &str, status: PodStatus| ObservedPod {
+            id: id.to_string(),
+            name: format!("demo-prod-{name}"),
+            project: Some(String::from("demo")),
+            environment: Some(String::from("prod")),
+            pod_name: Some(name.to_string()),
+            spec_hash: None,
+            status,
+            gpu_type: Some(String::from("NVIDIA A40")),
+            gpu_count: 2,
+            image: String::new(),
+            endpoints: std::collections::HashMap::new(),
+            gpu_utilization: None,
+            tags: std::collections::HashMap::new(),
+            volume_gb: 100,
+            uptime_secs: 1800,
+            env: None,
+        };
+
+        let mut state = DeploymentState::new("demo", "prod");
+        let mut llm = PodState::new("llm", "abc", "hash");
+        llm.created_at = now - chrono::Duration::hours(3);
+        state.set_pod(llm);
+
+        let observed = [
+            pod("abc", "llm", PodStatus::Running),
+            pod("def", "cache", PodStatus::Stopped),
+        ];
+        let report = CostReport::new("demo", "prod", &observed, Some(&state), &prices, now);
+
+        assert_eq!(report.pods[0].cost_per_hour, Some(1.0));
+        assert!(
+            report.pods[0]
+                .accumulated
+                .is_some_and(|cost| (cost - 3.0).abs() < 1e-6)
+        );
+        assert_eq!(report.pods[1].accumulated, None);
+        assert!(report.cost_per_hour() > 1.0);
+        assert!((report.accumulated() - 3.0).abs() < 1e-6);
+    }
+}
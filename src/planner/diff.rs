@@ -3,18 +3,41 @@
 //! This module computes the difference between the desired configuration
 //! and the observed state on `RunPod`.
 
-use std::collections::HashMap;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use tracing::debug;
 
-use crate::config::{ConfigHasher, DeployConfig, PodConfig};
-use crate::runpod::ObservedPod;
-use crate::state::DeploymentState;
+use crate::config::{
+    ConfigHasher, DeployConfig, EndpointConfig, PodConfig, TemplateConfig, VolumeConfig,
+    is_secret_env_key,
+};
+use crate::runpod::{ObservedPod, PodStatus, ServerlessEndpoint, endpoint_name};
+use crate::state::{DeploymentState, DeploymentStatus, PodState, StopReason};
+
+/// Env keys the provisioner adds on its own when they are not configured.
+const INJECTED_ENV_KEYS: &[&str] = &["HF_TOKEN"];
+
+/// Diff fields `RunPod` sets on its own, never reported as changes.
+///
+/// Patterns match a field exactly or, ending in `*`, by prefix.
+pub const BUILTIN_IGNORED_FIELDS: &[&str] = &["env.RUNPOD_*", "env.PUBLIC_KEY"];
+
+/// Diff field marking a replacement requested with `--replace`.
+pub const FORCED_REPLACEMENT_FIELD: &str = "replace";
+
+/// Diff field marking a pod stopped outside halldyll, resumed in place.
+pub const STOPPED_FIELD: &str = "status";
+
+/// Diff field marking a running pod disabled in the configuration, stopped in place.
+pub const DISABLED_FIELD: &str = "enabled";
 
 /// Engine for computing diffs between desired and observed states.
 #[derive(Debug, Default)]
 pub struct DiffEngine {
     /// Configuration hasher.
     hasher: ConfigHasher,
+    /// Extra field patterns to ignore, on top of the built-in list and config.
+    ignored_fields: Vec<String>,
 }
 
 /// Difference for a single resource.
@@ -30,6 +53,61 @@ pub struct ResourceDiff {
     pub old_hash: Option<String>,
     /// New hash (if applicable).
     pub new_hash: Option<String>,
+    /// Desired configuration for this resource (replica instances are expanded).
+    pub pod_config: Option<PodConfig>,
+    /// ID of the pod currently running on `RunPod` (if any).
+    pub runpod_id: Option<String>,
+    /// Status of the pod on `RunPod` (if observed).
+    pub observed_status: Option<PodStatus>,
+    /// Whether this resource is a stopped warm pool clone.
+    pub warm: bool,
+    /// Warm pod resumed in place of provisioning a new pod (if any).
+    pub warm_claim: Option<WarmClaim>,
+    /// Managed network volumes to create before the pod is provisioned.
+    pub volumes: Vec<VolumeConfig>,
+}
+
+/// Difference for a serverless endpoint.
+#[derive(Debug, Clone)]
+pub struct EndpointDiff {
+    /// Endpoint name.
+    pub name: String,
+    /// Type of difference (endpoints are updated in place, never drift).
+    pub diff_type: DiffType,
+    /// Desired configuration (`None` for an endpoint to delete).
+    pub config: Option<EndpointConfig>,
+    /// ID of the endpoint on `RunPod` (if it exists).
+    pub runpod_id: Option<String>,
+    /// New configuration hash (if applicable).
+    pub new_hash: Option<String>,
+    /// Why the endpoint changes.
+    pub reason: String,
+}
+
+/// Difference for a configured template.
+#[derive(Debug, Clone)]
+pub struct TemplateDiff {
+    /// Template name.
+    pub name: String,
+    /// Type of difference (templates are updated in place, never drift).
+    pub diff_type: DiffType,
+    /// Desired configuration (`None` for a template to delete).
+    pub config: Option<TemplateConfig>,
+    /// ID of the template on `RunPod` (if recorded).
+    pub runpod_id: Option<String>,
+    /// New configuration hash (if applicable).
+    pub new_hash: Option<String>,
+    /// Why the template changes.
+    pub reason: String,
+}
+
+/// A stopped warm pool pod taken over by another resource.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WarmClaim {
+    /// Warm pool instance name the pod was created under.
+    pub name: String,
+    /// `RunPod` pod ID of the warm pod.
+    pub runpod_id: String,
 }
 
 /// Type of difference detected.
@@ -56,6 +134,8 @@ pub struct DiffDetail {
     pub old_value: Option<String>,
     /// New value.
     pub new_value: Option<String>,
+    /// Whether applying this change requires recreating the pod.
+    pub forces_replacement: bool,
 }
 
 /// Complete diff result.
@@ -71,6 +151,10 @@ pub struct DiffResult {
     pub deletes: usize,
     /// Number of unchanged resources.
     pub unchanged: usize,
+    /// Serverless endpoint diffs.
+    pub endpoints: Vec<EndpointDiff>,
+    /// Template diffs.
+    pub templates: Vec<TemplateDiff>,
 }
 
 impl DiffEngine {
@@ -79,9 +163,41 @@ impl DiffEngine {
     pub const fn new() -> Self {
         Self {
             hasher: ConfigHasher::new(),
+            ignored_fields: Vec::new(),
         }
     }
 
+    /// Ignores diff fields matching `pattern` (exact, or a prefix ending in `*`).
+    #[must_use]
+    pub fn with_ignored_field(mut self, pattern: impl Into<String>) -> Self {
+        self.ignored_fields.push(pattern.into());
+        self
+    }
+
+    /// Returns every ignored field pattern: built-in, engine and config.
+    fn ignored_patterns<'a>(&'a self, config: &'a DeployConfig) -> Vec<&'a str> {
+        BUILTIN_IGNORED_FIELDS
+            .iter()
+            .copied()
+            .chain(self.ignored_fields.iter().map(String::as_str))
+            .chain(
+                config
+                    .diff
+                    .iter()
+                    .flat_map(|d| d.ignore.iter().map(String::as_str)),
+            )
+            .collect()
+    }
+
+    /// Returns true if a diff field matches one of the ignore patterns.
+    fn is_ignored(field: &str, ignored: &[&str]) -> bool {
+        ignored.iter().any(|pattern| {
+            pattern
+                .strip_suffix('*')
+                .map_or_else(|| field == *pattern, |prefix| field.starts_with(prefix))
+        })
+    }
+
     /// Computes the diff between desired config and observed state.
     pub fn compute_diff(
         &self,
@@ -91,6 +207,17 @@ impl DiffEngine {
     ) -> DiffResult {
         let mut diffs = Vec::new();
 
+        // Warm pool clones are desired alongside the serving instances
+        let warm: Vec<PodConfig> = config
+            .pods
+            .iter()
+            .flat_map(PodConfig::warm_instances)
+            .collect();
+        let warm_names: HashSet<String> = warm.iter().map(|p| p.name.clone()).collect();
+
+        // Resumed warm pods keep their warm tags; state knows what they became
+        let observed = &Self::adopt_claimed_pods(observed, state, &warm_names);
+
         // Build a map of observed pods by name
         let observed_by_name: HashMap<&str, &ObservedPod> = observed
             .iter()
@@ -102,20 +229,40 @@ impl DiffEngine {
             .map(|s| s.pods.iter().map(|(k, v)| (k.as_str(), v)).collect())
             .unwrap_or_default();
 
+        // Expand replicated pods into their instances
+        let mut desired = Self::desired_instances(config, state);
+        desired.extend(warm);
+        let desired_names: HashSet<&str> = desired.iter().map(|p| p.name.as_str()).collect();
+        let ignored = self.ignored_patterns(config);
+
         // Check each desired pod
-        for pod_config in &config.pods {
+        for pod_config in &desired {
             let new_hash = self.hasher.hash_pod(pod_config);
             let observed_pod = observed_by_name.get(pod_config.name.as_str());
             let state_pod = state_pods.get(pod_config.name.as_str());
 
-            let diff = Self::compute_pod_diff(pod_config, observed_pod.copied(), state_pod.copied(), &new_hash);
+            let mut diff = Self::compute_pod_diff(
+                pod_config,
+                observed_pod.copied(),
+                state_pod.copied(),
+                &new_hash,
+                &ignored,
+            );
+            diff.warm = warm_names.contains(&pod_config.name);
+            diff.volumes = Self::missing_volumes(pod_config, state);
             diffs.push(diff);
         }
 
+        Self::claim_warm_pods(config, state, &mut diffs);
+        Self::resume_stopped_pods(config, state, &mut diffs);
+        let templates = self.compute_template_diff(config, state);
+        Self::update_template_pods(&templates, &mut diffs);
+        Self::stop_disabled_pods(&mut diffs);
+
         // Check for pods that exist but are not in config (should be deleted)
         for observed_pod in observed {
             if let Some(pod_name) = &observed_pod.pod_name {
-                let in_config = config.pods.iter().any(|p| p.name == *pod_name);
+                let in_config = desired_names.contains(pod_name.as_str());
                 if !in_config {
                     debug!("Found orphaned pod: {pod_name}");
                     diffs.push(ResourceDiff {
@@ -125,30 +272,508 @@ impl DiffEngine {
                             field: String::from("pod"),
                             old_value: Some(observed_pod.id.clone()),
                             new_value: None,
+                            forces_replacement: false,
                         }],
                         old_hash: observed_pod.spec_hash.clone(),
                         new_hash: None,
+                        pod_config: None,
+                        runpod_id: Some(observed_pod.id.clone()),
+                        observed_status: Some(observed_pod.status),
+                        warm: false,
+                        warm_claim: None,
+                        volumes: vec![],
                     });
                 }
             }
         }
 
-        // Compute summary
-        let creates = diffs.iter().filter(|d| d.diff_type == DiffType::Create).count();
-        let updates = diffs
-            .iter()
-            .filter(|d| matches!(d.diff_type, DiffType::Update | DiffType::Drift))
-            .count();
-        let deletes = diffs.iter().filter(|d| d.diff_type == DiffType::Delete).count();
-        let unchanged = diffs.iter().filter(|d| d.diff_type == DiffType::NoChange).count();
+        // Sort by name so identical inputs always produce identical plans
+        diffs.sort_by(|a, b| {
+            a.name
+                .cmp(&b.name)
+                .then_with(|| a.runpod_id.cmp(&b.runpod_id))
+        });
 
-        DiffResult {
+        DiffResult::from_diffs(
             diffs,
-            creates,
-            updates,
-            deletes,
-            unchanged,
+            self.compute_endpoint_diff(config, state, None),
+            templates,
+        )
+    }
+
+    /// Plans to resume configured pods that were stopped outside halldyll.
+    ///
+    /// Warm pool clones and replicas put to sleep while their pod still has
+    /// an idle timeout were stopped on purpose and count as converged.
+    fn resume_stopped_pods(
+        config: &DeployConfig,
+        state: Option<&DeploymentState>,
+        diffs: &mut [ResourceDiff],
+    ) {
+        let enabled = |d: &ResourceDiff| d.pod_config.as_ref().is_some_and(|p| p.enabled);
+        for diff in diffs
+            .iter_mut()
+            .filter(|d| !d.warm && d.diff_type == DiffType::NoChange && enabled(d))
+        {
+            let Some(status) = diff
+                .observed_status
+                .filter(|s| matches!(s, PodStatus::Stopped | PodStatus::Exited))
+            else {
+                continue;
+            };
+            let Some(pod) = config.pod_for_instance(&diff.name) else {
+                continue;
+            };
+
+            let may_sleep = pod
+                .autoscale
+                .as_ref()
+                .is_some_and(|a| a.idle_timeout_secs.is_some());
+            let asleep = state.is_some_and(|s| {
+                s.is_sleeping(&pod.name)
+                    || s.get_pod(&diff.name).and_then(|p| p.stop_reason) == Some(StopReason::Idle)
+            });
+            if may_sleep && asleep {
+                continue;
+            }
+
+            debug!(
+                "Pod {} was stopped outside halldyll, resuming it",
+                diff.name
+            );
+            diff.diff_type = DiffType::Drift;
+            diff.details.push(DiffDetail {
+                field: STOPPED_FIELD.to_string(),
+                old_value: Some(status.to_string()),
+                new_value: Some(String::from("running")),
+                forces_replacement: false,
+            });
+        }
+    }
+
+    /// Keeps the instances of disabled pods stopped.
+    ///
+    /// Missing instances are not created and stopped ones are left as they
+    /// are, whatever their spec; running ones are planned to stop.
+    fn stop_disabled_pods(diffs: &mut Vec<ResourceDiff>) {
+        let disabled = |d: &ResourceDiff| d.pod_config.as_ref().is_some_and(|p| !p.enabled);
+        diffs.retain(|d| d.runpod_id.is_some() || !disabled(d));
+
+        for diff in diffs.iter_mut().filter(|d| disabled(d)) {
+            diff.volumes.clear();
+            if matches!(
+                diff.observed_status,
+                Some(PodStatus::Stopped | PodStatus::Exited)
+            ) {
+                diff.diff_type = DiffType::NoChange;
+                diff.details.clear();
+            } else {
+                debug!("Pod {} is disabled, stopping it", diff.name);
+                diff.diff_type = DiffType::Update;
+                diff.details = vec![DiffDetail {
+                    field: DISABLED_FIELD.to_string(),
+                    old_value: Some(String::from("true")),
+                    new_value: Some(String::from("false")),
+                    forces_replacement: false,
+                }];
+            }
+        }
+    }
+
+    /// Computes the diff of the configured templates against state.
+    #[must_use]
+    pub fn compute_template_diff(
+        &self,
+        config: &DeployConfig,
+        state: Option<&DeploymentState>,
+    ) -> Vec<TemplateDiff> {
+        let mut diffs = Vec::new();
+
+        for template in &config.templates {
+            let new_hash = self.hasher.hash_template(template);
+            let recorded = state.and_then(|s| s.templates.get(&template.name));
+            let (diff_type, reason) = match recorded {
+                None => (
+                    DiffType::Create,
+                    String::from("Template defined in configuration"),
+                ),
+                Some(recorded) if recorded.config_hash != new_hash => (
+                    DiffType::Update,
+                    String::from("Template configuration changed"),
+                ),
+                Some(_) => (DiffType::NoChange, String::new()),
+            };
+            diffs.push(TemplateDiff {
+                name: template.name.clone(),
+                diff_type,
+                config: Some(template.clone()),
+                runpod_id: recorded.map(|r| r.runpod_id.clone()),
+                new_hash: Some(new_hash),
+                reason,
+            });
+        }
+
+        for recorded in state.iter().flat_map(|s| s.templates.values()) {
+            if config.template(&recorded.name).is_none() {
+                diffs.push(TemplateDiff {
+                    name: recorded.name.clone(),
+                    diff_type: DiffType::Delete,
+                    config: None,
+                    runpod_id: Some(recorded.runpod_id.clone()),
+                    new_hash: None,
+                    reason: String::from("Template removed from configuration"),
+                });
+            }
+        }
+
+        diffs.sort_by(|a, b| a.name.cmp(&b.name));
+        diffs
+    }
+
+    /// Recreates the existing pods deployed from a changed template.
+    fn update_template_pods(templates: &[TemplateDiff], diffs: &mut [ResourceDiff]) {
+        for template in templates.iter().filter(|t| t.diff_type == DiffType::Update) {
+            let deployed_from = |diff: &ResourceDiff| {
+                diff.pod_config.as_ref().and_then(|p| p.template.as_deref())
+                    == Some(template.name.as_str())
+            };
+            for diff in diffs.iter_mut().filter(|d| deployed_from(d)) {
+                if !matches!(
+                    diff.diff_type,
+                    DiffType::NoChange | DiffType::Update | DiffType::Drift
+                ) {
+                    continue;
+                }
+                diff.diff_type = DiffType::Update;
+                diff.details.push(DiffDetail {
+                    field: String::from("template"),
+                    old_value: None,
+                    new_value: Some(template.name.clone()),
+                    forces_replacement: true,
+                });
+            }
+        }
+    }
+
+    /// Computes the diff of the configured serverless endpoints.
+    ///
+    /// Endpoints are compared with the configuration hash recorded in state.
+    /// With `observed` (the project's endpoints on `RunPod`), endpoints
+    /// deleted outside halldyll are recreated and existing endpoints of the
+    /// same name are adopted. Only endpoints recorded in state are ever
+    /// deleted.
+    #[must_use]
+    pub fn compute_endpoint_diff(
+        &self,
+        config: &DeployConfig,
+        state: Option<&DeploymentState>,
+        observed: Option<&[ServerlessEndpoint]>,
+    ) -> Vec<EndpointDiff> {
+        let observed_id = |name: &str| {
+            let name = endpoint_name(&config.project, name);
+            observed?
+                .iter()
+                .find(|e| e.name == name)
+                .and_then(|e| e.id.clone())
+        };
+        let changed_templates: HashSet<String> = self
+            .compute_template_diff(config, state)
+            .into_iter()
+            .filter(|t| t.diff_type == DiffType::Update)
+            .map(|t| t.name)
+            .collect();
+        let mut diffs = Vec::new();
+
+        for endpoint in &config.endpoints {
+            let new_hash = self.hasher.hash_endpoint(endpoint);
+            let recorded = state.and_then(|s| s.endpoints.get(&endpoint.name));
+            let runpod_id = match (recorded, observed) {
+                (Some(recorded), None) => Some(recorded.runpod_id.clone()),
+                (Some(recorded), Some(_)) => {
+                    observed_id(&endpoint.name).filter(|id| *id == recorded.runpod_id)
+                }
+                (None, _) => observed_id(&endpoint.name),
+            };
+            let (diff_type, reason) = match (recorded, &runpod_id) {
+                (_, None) if recorded.is_some() => {
+                    (DiffType::Create, String::from("Endpoint missing on RunPod"))
+                }
+                (_, None) => (
+                    DiffType::Create,
+                    String::from("Endpoint defined in configuration"),
+                ),
+                (None, Some(_)) => (DiffType::Update, String::from("Adopting existing endpoint")),
+                (Some(recorded), Some(_)) if recorded.config_hash != new_hash => (
+                    DiffType::Update,
+                    String::from("Endpoint configuration changed"),
+                ),
+                (Some(_), Some(_))
+                    if endpoint
+                        .template
+                        .as_ref()
+                        .is_some_and(|t| changed_templates.contains(t)) =>
+                {
+                    (DiffType::Update, String::from("Endpoint template changed"))
+                }
+                (Some(_), Some(_)) => (DiffType::NoChange, String::new()),
+            };
+            diffs.push(EndpointDiff {
+                name: endpoint.name.clone(),
+                diff_type,
+                config: Some(endpoint.clone()),
+                runpod_id,
+                new_hash: Some(new_hash),
+                reason,
+            });
+        }
+
+        for recorded in state.iter().flat_map(|s| s.endpoints.values()) {
+            if config.endpoint(&recorded.name).is_none() {
+                diffs.push(EndpointDiff {
+                    name: recorded.name.clone(),
+                    diff_type: DiffType::Delete,
+                    config: None,
+                    runpod_id: Some(recorded.runpod_id.clone()),
+                    new_hash: None,
+                    reason: String::from("Endpoint removed from configuration"),
+                });
+            }
         }
+
+        diffs.sort_by(|a, b| a.name.cmp(&b.name));
+        diffs
+    }
+
+    /// Builds the observed pods implied by saved state, without calling `RunPod`.
+    ///
+    /// Used for offline plans; anything that changed on `RunPod` since the
+    /// last apply (drift, deleted pods) is invisible to such a plan.
+    #[must_use]
+    pub fn recorded_pods(state: &DeploymentState) -> Vec<ObservedPod> {
+        let mut pods: Vec<_> = state.pods.values().collect();
+        pods.sort_by(|a, b| a.name.cmp(&b.name));
+
+        pods.into_iter()
+            .filter(|pod| pod.status != DeploymentStatus::Deleted)
+            .map(|pod| ObservedPod {
+                id: pod.runpod_id.clone(),
+                name: format!("{}-{}-{}", state.project, state.environment, pod.name),
+                project: Some(state.project.clone()),
+                environment: Some(state.environment.clone()),
+                pod_name: Some(pod.name.clone()),
+                spec_hash: Some(pod.config_hash.clone()),
+                status: match pod.status {
+                    DeploymentStatus::Creating => PodStatus::Creating,
+                    DeploymentStatus::Running => PodStatus::Running,
+                    DeploymentStatus::Stopped => PodStatus::Stopped,
+                    _ => PodStatus::Unknown,
+                },
+                gpu_type: (!pod.gpu_type.is_empty()).then(|| pod.gpu_type.clone()),
+                gpu_count: pod.gpu_count,
+                image: pod.image.clone(),
+                endpoints: pod.endpoints.clone(),
+                gpu_utilization: None,
+                tags: pod.tags.clone(),
+                volume_gb: 0,
+                uptime_secs: 0,
+                env: None,
+            })
+            .collect()
+    }
+
+    /// Returns the managed network volumes of a pod instance not recorded in state.
+    ///
+    /// A volume recorded in another data center counts as missing.
+    #[must_use]
+    pub fn missing_volumes(
+        pod_config: &PodConfig,
+        state: Option<&DeploymentState>,
+    ) -> Vec<VolumeConfig> {
+        pod_config
+            .volumes
+            .iter()
+            .filter(|volume| {
+                volume.is_managed()
+                    && state
+                        .and_then(|s| s.volume_for(&pod_config.name, &volume.name))
+                        .is_none_or(|recorded| recorded.data_center != volume.data_center)
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Expands the configured pods into one entry per desired instance.
+    ///
+    /// Each pod expands to [`Self::replica_count`] instances.
+    #[must_use]
+    pub fn desired_instances(
+        config: &DeployConfig,
+        state: Option<&DeploymentState>,
+    ) -> Vec<PodConfig> {
+        config
+            .pods
+            .iter()
+            .flat_map(|pod| pod.instances(Self::replica_count(pod, state)))
+            .collect()
+    }
+
+    /// Returns the number of replicas currently desired for a pod.
+    ///
+    /// A count recorded by the autoscaler always applies; one set with
+    /// `halldyll scale` applies until `replicas` changes in the configuration.
+    #[must_use]
+    pub fn replica_count(pod: &PodConfig, state: Option<&DeploymentState>) -> u32 {
+        let recorded = state
+            .and_then(|s| s.replicas.get(&pod.name))
+            .filter(|r| pod.autoscale.is_some() || r.configured == Some(pod.replicas))
+            .map(|r| r.desired);
+        pod.clamp_replicas(recorded.unwrap_or(pod.replicas))
+    }
+
+    /// Renames observed warm pods that state records under another name.
+    ///
+    /// A resumed warm pod keeps the tags it was created with, so it is
+    /// matched by its `RunPod` ID and takes the name and spec hash recorded
+    /// when it was resumed.
+    fn adopt_claimed_pods(
+        observed: &[ObservedPod],
+        state: Option<&DeploymentState>,
+        warm_names: &HashSet<String>,
+    ) -> Vec<ObservedPod> {
+        let recorded: HashMap<&str, &crate::state::PodState> = state
+            .map(|s| s.pods.values().map(|p| (p.runpod_id.as_str(), p)).collect())
+            .unwrap_or_default();
+
+        observed
+            .iter()
+            .cloned()
+            .map(|mut pod| {
+                if let Some(name) = &pod.pod_name
+                    && warm_names.contains(name)
+                    && let Some(claimed) = recorded.get(pod.id.as_str())
+                    && claimed.name != *name
+                {
+                    debug!("Warm pod {name} now serves as {}", claimed.name);
+                    pod.pod_name = Some(claimed.name.clone());
+                    pod.spec_hash = Some(claimed.config_hash.clone());
+                }
+                pod
+            })
+            .collect()
+    }
+
+    /// Hands stopped, up-to-date warm pods to instances that need a new pod.
+    ///
+    /// Each claimed warm pod is resumed in place of a create, and its slot
+    /// in the pool becomes a create so the pool is refilled.
+    fn claim_warm_pods(
+        config: &DeployConfig,
+        state: Option<&DeploymentState>,
+        diffs: &mut [ResourceDiff],
+    ) {
+        for pod in config
+            .pods
+            .iter()
+            .filter(|p| p.enabled && p.warm_pool.is_some())
+        {
+            let warm_names: HashSet<String> =
+                pod.warm_instances().into_iter().map(|p| p.name).collect();
+            let instance_names: HashSet<String> = pod
+                .instances(Self::replica_count(pod, state))
+                .into_iter()
+                .map(|p| p.name)
+                .collect();
+
+            let mut available = diffs
+                .iter()
+                .enumerate()
+                .filter(|(_, d)| {
+                    warm_names.contains(&d.name)
+                        && d.diff_type == DiffType::NoChange
+                        && matches!(
+                            d.observed_status,
+                            Some(PodStatus::Stopped | PodStatus::Exited)
+                        )
+                })
+                .map(|(i, _)| i)
+                .collect::<Vec<_>>()
+                .into_iter();
+            let targets: Vec<usize> = diffs
+                .iter()
+                .enumerate()
+                .filter(|(_, d)| {
+                    instance_names.contains(&d.name)
+                        && matches!(
+                            d.diff_type,
+                            DiffType::Create | DiffType::Update | DiffType::Drift
+                        )
+                })
+                .map(|(i, _)| i)
+                .collect();
+
+            for target in targets {
+                let Some(slot) = available.next() else {
+                    break;
+                };
+
+                let target_name = diffs[target].name.clone();
+                let warm = &mut diffs[slot];
+                let claim = WarmClaim {
+                    name: warm.name.clone(),
+                    runpod_id: warm.runpod_id.take().unwrap_or_default(),
+                };
+                debug!("Warm pod {} will be resumed for {target_name}", claim.name);
+                warm.diff_type = DiffType::Create;
+                warm.details = vec![DiffDetail {
+                    field: String::from("pod"),
+                    old_value: Some(format!("resumed as {target_name}")),
+                    new_value: Some(claim.name.clone()),
+                    forces_replacement: false,
+                }];
+                warm.observed_status = None;
+                diffs[target].warm_claim = Some(claim);
+            }
+        }
+    }
+
+    /// Builds the state of an existing pod adopted as instance `name`.
+    ///
+    /// The pod gets the spec hash of its configuration when its image, GPUs
+    /// and env match it, so the planner leaves it alone. Otherwise no hash
+    /// is recorded and the returned differences are what the next apply
+    /// replaces it for. Returns `None` if `name` is not a configured instance.
+    #[must_use]
+    pub fn import_pod(
+        &self,
+        config: &DeployConfig,
+        state: Option<&DeploymentState>,
+        name: &str,
+        observed: &ObservedPod,
+    ) -> Option<(PodState, Vec<DiffDetail>)> {
+        let pod_config = Self::desired_instances(config, state)
+            .into_iter()
+            .find(|p| p.name == name)?;
+        let spec_hash = self.hasher.hash_pod(&pod_config);
+
+        let details =
+            Self::compute_field_diff(&pod_config, observed, &self.ignored_patterns(config));
+
+        let mut pod_state = PodState::new(
+            name,
+            &observed.id,
+            if details.is_empty() { &spec_hash } else { "" },
+        );
+        pod_state.gpu_type = observed.gpu_type.clone().unwrap_or_default();
+        pod_state.gpu_count = observed.gpu_count;
+        pod_state.image.clone_from(&observed.image);
+        pod_state.endpoints.clone_from(&observed.endpoints);
+        pod_state.imported = true;
+        pod_state.set_status(match observed.status {
+            PodStatus::Stopped | PodStatus::Exited => DeploymentStatus::Stopped,
+            _ => DeploymentStatus::Running,
+        });
+
+        Some((pod_state, details))
     }
 
     /// Computes the diff for a single pod.
@@ -157,6 +782,7 @@ impl DiffEngine {
         observed: Option<&ObservedPod>,
         state: Option<&crate::state::PodState>,
         new_hash: &str,
+        ignored: &[&str],
     ) -> ResourceDiff {
         match (observed, state) {
             // Pod doesn't exist at all - create
@@ -169,30 +795,57 @@ impl DiffEngine {
                         field: String::from("pod"),
                         old_value: None,
                         new_value: Some(config.name.clone()),
+                        forces_replacement: false,
                     }],
                     old_hash: None,
                     new_hash: Some(new_hash.to_string()),
+                    pod_config: Some(config.clone()),
+                    runpod_id: None,
+                    observed_status: None,
+                    warm: false,
+                    warm_claim: None,
+                    volumes: vec![],
                 }
             }
 
             // Pod exists on RunPod
-            (Some(obs), _) => {
-                // Check if spec hash matches
-                let old_hash = obs.spec_hash.as_deref();
+            (Some(obs), state) => {
+                // Check if spec hash matches; untagged imported pods have it in state
+                let old_hash = obs.spec_hash.as_deref().or_else(|| {
+                    state
+                        .filter(|s| s.runpod_id == obs.id)
+                        .map(|s| s.config_hash.as_str())
+                });
 
-                if old_hash == Some(new_hash) {
+                // Hash matches, but the env may have been edited on RunPod
+                let env_details = Self::compute_env_diff(config, obs, ignored);
+                let legacy = old_hash.is_some_and(|hash| !ConfigHasher::is_current(hash));
+                let (diff_type, details) = if old_hash == Some(new_hash) && !env_details.is_empty()
+                {
+                    debug!("Pod {} env drifted on RunPod", config.name);
+                    (DiffType::Drift, env_details)
+                } else if old_hash == Some(new_hash) {
                     // Hash matches - no change needed
                     debug!("Pod {} is up to date", config.name);
-                    ResourceDiff {
-                        name: config.name.clone(),
-                        diff_type: DiffType::NoChange,
-                        details: vec![],
-                        old_hash: old_hash.map(String::from),
-                        new_hash: Some(new_hash.to_string()),
-                    }
+                    (DiffType::NoChange, vec![])
+                } else if legacy {
+                    // Hashed by an older halldyll: managed, but only its fields
+                    // can be compared; it is re-tagged when next replaced
+                    let details = Self::compute_field_diff(config, obs, ignored);
+                    debug!(
+                        "Pod {} has an older spec hash, {} fields differ",
+                        config.name,
+                        details.len()
+                    );
+                    let diff_type = if details.is_empty() {
+                        DiffType::NoChange
+                    } else {
+                        DiffType::Update
+                    };
+                    (diff_type, details)
                 } else {
                     // Hash differs - compute detailed diff
-                    let details = Self::compute_detailed_diff(config, obs);
+                    let details = Self::compute_detailed_diff(config, obs, new_hash, ignored);
                     let diff_type = if old_hash.is_some() {
                         DiffType::Update
                     } else {
@@ -200,19 +853,30 @@ impl DiffEngine {
                     };
 
                     debug!("Pod {} needs update ({:?})", config.name, diff_type);
-                    ResourceDiff {
-                        name: config.name.clone(),
-                        diff_type,
-                        details,
-                        old_hash: old_hash.map(String::from),
-                        new_hash: Some(new_hash.to_string()),
-                    }
+                    (diff_type, details)
+                };
+
+                ResourceDiff {
+                    name: config.name.clone(),
+                    diff_type,
+                    details,
+                    old_hash: old_hash.map(String::from),
+                    new_hash: Some(new_hash.to_string()),
+                    pod_config: Some(config.clone()),
+                    runpod_id: Some(obs.id.clone()),
+                    observed_status: Some(obs.status),
+                    warm: false,
+                    warm_claim: None,
+                    volumes: vec![],
                 }
             }
 
             // Pod exists in state but not on RunPod - recreate
             (None, Some(st)) => {
-                debug!("Pod {} exists in state but not on RunPod, recreating", config.name);
+                debug!(
+                    "Pod {} exists in state but not on RunPod, recreating",
+                    config.name
+                );
                 ResourceDiff {
                     name: config.name.clone(),
                     diff_type: DiffType::Create,
@@ -220,16 +884,52 @@ impl DiffEngine {
                         field: String::from("pod"),
                         old_value: Some(format!("missing (was {})", st.runpod_id)),
                         new_value: Some(config.name.clone()),
+                        forces_replacement: false,
                     }],
                     old_hash: Some(st.config_hash.clone()),
                     new_hash: Some(new_hash.to_string()),
+                    pod_config: Some(config.clone()),
+                    runpod_id: None,
+                    observed_status: None,
+                    warm: false,
+                    warm_claim: None,
+                    volumes: vec![],
                 }
             }
         }
     }
 
     /// Computes detailed differences between config and observed state.
-    fn compute_detailed_diff(config: &PodConfig, observed: &ObservedPod) -> Vec<DiffDetail> {
+    ///
+    /// `RunPod` cannot change a pod's image or GPUs in place, so every
+    /// difference currently forces a replacement. Ignored fields are dropped.
+    fn compute_detailed_diff(
+        config: &PodConfig,
+        observed: &ObservedPod,
+        new_hash: &str,
+        ignored: &[&str],
+    ) -> Vec<DiffDetail> {
+        let mut details = Self::compute_field_diff(config, observed, ignored);
+
+        // Other spec changes (ports, models, ...) are only visible in the hash
+        if details.is_empty() {
+            details.push(DiffDetail {
+                field: String::from("spec_hash"),
+                old_value: observed.spec_hash.clone(),
+                new_value: Some(new_hash.to_string()),
+                forces_replacement: true,
+            });
+        }
+
+        details
+    }
+
+    /// Compares the fields of a pod visible on `RunPod` (image, GPU, env) with its configuration.
+    fn compute_field_diff(
+        config: &PodConfig,
+        observed: &ObservedPod,
+        ignored: &[&str],
+    ) -> Vec<DiffDetail> {
         let mut details = Vec::new();
 
         // Check image
@@ -238,18 +938,22 @@ impl DiffEngine {
                 field: String::from("image"),
                 old_value: Some(observed.image.clone()),
                 new_value: Some(config.runtime.image.clone()),
+                forces_replacement: true,
             });
         }
 
-        // Check GPU type
+        // Check GPU type (a type picked by price is whatever was cheapest)
         if let Some(obs_gpu) = &observed.gpu_type
-            && config.gpu.gpu_type != *obs_gpu {
-                details.push(DiffDetail {
-                    field: String::from("gpu_type"),
-                    old_value: Some(obs_gpu.clone()),
-                    new_value: Some(config.gpu.gpu_type.clone()),
-                });
-            }
+            && !config.gpu.is_auto()
+            && config.gpu.gpu_type != *obs_gpu
+        {
+            details.push(DiffDetail {
+                field: String::from("gpu_type"),
+                old_value: Some(obs_gpu.clone()),
+                new_value: Some(config.gpu.gpu_type.clone()),
+                forces_replacement: true,
+            });
+        }
 
         // Check GPU count
         if config.gpu.count != observed.gpu_count {
@@ -257,14 +961,203 @@ impl DiffEngine {
                 field: String::from("gpu_count"),
                 old_value: Some(observed.gpu_count.to_string()),
                 new_value: Some(config.gpu.count.to_string()),
+                forces_replacement: true,
             });
         }
 
+        details.extend(Self::compute_env_diff(config, observed, ignored));
+        details.retain(|d| !Self::is_ignored(&d.field, ignored));
         details
     }
+
+    /// Compares the configured env with the env observed on `RunPod`.
+    ///
+    /// Keys only present on the pod because the provisioner injected them,
+    /// and ignored fields, are skipped. Secret values are compared and shown
+    /// by hash. Returns nothing if the pod's env was not observed.
+    fn compute_env_diff(
+        config: &PodConfig,
+        observed: &ObservedPod,
+        ignored: &[&str],
+    ) -> Vec<DiffDetail> {
+        let Some(observed_env) = &observed.env else {
+            return Vec::new();
+        };
+        let desired_env = &config.resolved_env();
+
+        let keys: BTreeSet<&String> = desired_env.keys().chain(observed_env.keys()).collect();
+        keys.into_iter()
+            .filter(|key| {
+                desired_env.contains_key(*key) || !INJECTED_ENV_KEYS.contains(&key.as_str())
+            })
+            .filter(|key| !Self::is_ignored(&format!("env.{key}"), ignored))
+            .filter_map(|key| {
+                let secret = is_secret_env_key(key);
+                let show = |value: &String| {
+                    if secret {
+                        Self::fingerprint(value)
+                    } else {
+                        value.clone()
+                    }
+                };
+                let old_value = observed_env.get(key).map(show);
+                let new_value = desired_env.get(key).map(show);
+
+                (old_value != new_value).then(|| DiffDetail {
+                    field: format!("env.{key}"),
+                    old_value,
+                    new_value,
+                    forces_replacement: true,
+                })
+            })
+            .collect()
+    }
+
+    /// Short, stable fingerprint of a secret value.
+    fn fingerprint(value: &str) -> String {
+        let digest = hex::encode(Sha256::digest(value.as_bytes()));
+        format!("sha256:{}", &digest[..12])
+    }
 }
 
 impl DiffResult {
+    /// Builds a result from resource diffs, computing the summary counts.
+    fn from_diffs(
+        diffs: Vec<ResourceDiff>,
+        endpoints: Vec<EndpointDiff>,
+        templates: Vec<TemplateDiff>,
+    ) -> Self {
+        let types: Vec<DiffType> = diffs
+            .iter()
+            .map(|d| d.diff_type)
+            .chain(endpoints.iter().map(|e| e.diff_type))
+            .chain(templates.iter().map(|t| t.diff_type))
+            .collect();
+        let count = |wanted: &[DiffType]| types.iter().filter(|t| wanted.contains(t)).count();
+
+        Self {
+            creates: count(&[DiffType::Create]),
+            updates: count(&[DiffType::Update, DiffType::Drift]),
+            deletes: count(&[DiffType::Delete]),
+            unchanged: count(&[DiffType::NoChange]),
+            diffs,
+            endpoints,
+            templates,
+        }
+    }
+
+    /// Replaces the endpoint diffs, e.g. with ones computed against the endpoints on `RunPod`.
+    pub fn set_endpoints(&mut self, endpoints: Vec<EndpointDiff>) {
+        *self = Self::from_diffs(
+            std::mem::take(&mut self.diffs),
+            endpoints,
+            std::mem::take(&mut self.templates),
+        );
+    }
+
+    /// Drops the actions on resources matching `held`, returning their names.
+    ///
+    /// Held resources are reported as unchanged, e.g. pods under maintenance.
+    pub fn hold(&mut self, held: impl Fn(&str) -> bool) -> Vec<String> {
+        let mut names = Vec::new();
+        for diff in &mut self.diffs {
+            if diff.diff_type != DiffType::NoChange && held(&diff.name) {
+                diff.diff_type = DiffType::NoChange;
+                diff.details.clear();
+                diff.warm_claim = None;
+                names.push(diff.name.clone());
+            }
+        }
+
+        if !names.is_empty() {
+            *self = Self::from_diffs(
+                std::mem::take(&mut self.diffs),
+                std::mem::take(&mut self.endpoints),
+                std::mem::take(&mut self.templates),
+            );
+        }
+        names
+    }
+
+    /// Replaces the existing pods named in `pods` (or their replicas) whatever their diff.
+    ///
+    /// The replacement is marked with a [`FORCED_REPLACEMENT_FIELD`] detail,
+    /// e.g. for a wedged pod whose spec matches. Returns the names matching no
+    /// resource.
+    pub fn force_replace(&mut self, pods: &[String]) -> Vec<String> {
+        let matches = |pod: &str, name: &str| {
+            name == pod
+                || name
+                    .rsplit_once('-')
+                    .is_some_and(|(base, index)| base == pod && index.parse::<u32>().is_ok())
+        };
+        let unknown = pods
+            .iter()
+            .filter(|pod| !self.diffs.iter().any(|diff| matches(pod, &diff.name)))
+            .cloned()
+            .collect();
+
+        for diff in &mut self.diffs {
+            let existing = diff.runpod_id.is_some()
+                && matches!(
+                    diff.diff_type,
+                    DiffType::NoChange | DiffType::Update | DiffType::Drift
+                );
+            let enabled = diff.pod_config.as_ref().is_some_and(|p| p.enabled);
+            if existing && enabled && pods.iter().any(|pod| matches(pod, &diff.name)) {
+                if diff.diff_type == DiffType::NoChange {
+                    diff.diff_type = DiffType::Update;
+                }
+                diff.details.push(DiffDetail {
+                    field: FORCED_REPLACEMENT_FIELD.to_string(),
+                    old_value: None,
+                    new_value: Some(String::from("requested with --replace")),
+                    forces_replacement: true,
+                });
+            }
+        }
+
+        *self = Self::from_diffs(
+            std::mem::take(&mut self.diffs),
+            std::mem::take(&mut self.endpoints),
+            std::mem::take(&mut self.templates),
+        );
+        unknown
+    }
+
+    /// Recreates spot pods that `RunPod` stopped to serve a higher bid, returning their names.
+    ///
+    /// A preempted pod is one stopped outside halldyll; resuming it would
+    /// wait for its GPU to be free again, so a new pod is rented instead.
+    pub fn replace_preempted(&mut self) -> Vec<String> {
+        let mut names = Vec::new();
+        for diff in &mut self.diffs {
+            let spot = diff
+                .pod_config
+                .as_ref()
+                .is_some_and(|pod| pod.pricing.and_then(|p| p.spot_bid()).is_some());
+            if spot && diff.needs_resume() {
+                diff.diff_type = DiffType::Update;
+                diff.details = vec![DiffDetail {
+                    field: String::from("preempted"),
+                    old_value: diff.observed_status.map(|s| s.to_string()),
+                    new_value: Some(String::from("recreated at bid")),
+                    forces_replacement: true,
+                }];
+                names.push(diff.name.clone());
+            }
+        }
+
+        if !names.is_empty() {
+            *self = Self::from_diffs(
+                std::mem::take(&mut self.diffs),
+                std::mem::take(&mut self.endpoints),
+                std::mem::take(&mut self.templates),
+            );
+        }
+        names
+    }
+
     /// Returns true if there are any changes.
     #[must_use]
     pub const fn has_changes(&self) -> bool {
@@ -300,6 +1193,44 @@ impl std::fmt::Display for DiffType {
     }
 }
 
+impl ResourceDiff {
+    /// Returns true if the replacement was requested with `--replace` rather than detected.
+    #[must_use]
+    pub fn is_forced(&self) -> bool {
+        self.details
+            .iter()
+            .any(|d| d.field == FORCED_REPLACEMENT_FIELD)
+    }
+
+    /// Returns true if the pod only needs resuming after being stopped outside halldyll.
+    #[must_use]
+    pub fn needs_resume(&self) -> bool {
+        !self.details.is_empty() && self.details.iter().all(|d| d.field == STOPPED_FIELD)
+    }
+
+    /// Returns true if the pod only needs stopping because it was disabled.
+    #[must_use]
+    pub fn needs_stop(&self) -> bool {
+        !self.details.is_empty() && self.details.iter().all(|d| d.field == DISABLED_FIELD)
+    }
+}
+
+impl std::fmt::Display for DiffDetail {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} -> {}",
+            self.field,
+            self.old_value.as_deref().unwrap_or("(none)"),
+            self.new_value.as_deref().unwrap_or("(none)")
+        )?;
+        if self.forces_replacement {
+            write!(f, "  # forces replacement")?;
+        }
+        Ok(())
+    }
+}
+
 impl std::fmt::Display for ResourceDiff {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}: {}", self.name, self.diff_type)?;
@@ -316,3 +1247,555 @@ impl std::fmt::Display for ResourceDiff {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_imported_pod_is_left_alone() {
+        let yaml = "project:\n  name: proj\nstate:\n  backend: local\npods:\n  - name: web\n    gpu:\n      type: A40\n    runtime:\n      image: img\n";
+        let config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+        // Created in the dashboard: no halldyll tags, hence no spec hash
+        let mut observed = ObservedPod {
+            id: String::from("manual-1"),
+            name: String::from("my-web-pod"),
+            project: Some(String::from("proj")),
+            environment: Some(String::from("dev")),
+            pod_name: Some(String::from("web")),
+            spec_hash: None,
+            status: PodStatus::Running,
+            gpu_type: Some(String::from("A40")),
+            gpu_count: 1,
+            image: String::from("img"),
+            endpoints: HashMap::new(),
+            gpu_utilization: None,
+            tags: HashMap::new(),
+            volume_gb: 0,
+            uptime_secs: 0,
+            env: None,
+        };
+        let engine = DiffEngine::new();
+        assert!(engine.import_pod(&config, None, "api", &observed).is_none());
+
+        let (pod_state, details) = engine.import_pod(&config, None, "web", &observed).unwrap();
+        assert!(details.is_empty());
+        assert!(pod_state.imported);
+        let mut state = DeploymentState::new("proj", "dev");
+        state.set_pod(pod_state);
+        let diff = engine.compute_diff(&config, Some(&state), std::slice::from_ref(&observed));
+        assert!(!diff.has_changes());
+
+        // A pod that differs from its configuration is replaced on the next apply
+        observed.image = String::from("other");
+        let (outdated, changes) = engine.import_pod(&config, None, "web", &observed).unwrap();
+        assert_eq!(changes[0].field, "image");
+        state.set_pod(outdated);
+        let replaced = engine.compute_diff(&config, Some(&state), &[observed]);
+        assert_eq!(replaced.diffs[0].diff_type, DiffType::Update);
+    }
+
+    #[test]
+    fn test_older_spec_hash_compared_by_fields() {
+        let yaml = "project:\n  name: proj\nstate:\n  backend: local\npods:\n  - name: web\n    gpu:\n      type: A40\n    runtime:\n      image: img\n";
+        let config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+        // Tagged by a halldyll release before spec hashes were versioned
+        let mut observed = ObservedPod {
+            id: String::from("id-web"),
+            name: String::from("proj-dev-web"),
+            project: Some(String::from("proj")),
+            environment: Some(String::from("dev")),
+            pod_name: Some(String::from("web")),
+            spec_hash: Some(String::from(
+                "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08",
+            )),
+            status: PodStatus::Running,
+            gpu_type: Some(String::from("A40")),
+            gpu_count: 1,
+            image: String::from("img"),
+            endpoints: HashMap::new(),
+            gpu_utilization: None,
+            tags: HashMap::new(),
+            volume_gb: 0,
+            uptime_secs: 0,
+            env: None,
+        };
+        let engine = DiffEngine::new();
+        let unchanged = engine.compute_diff(&config, None, std::slice::from_ref(&observed));
+        assert_eq!(unchanged.diffs[0].diff_type, DiffType::NoChange);
+
+        observed.image = String::from("old-img");
+        let changed = engine.compute_diff(&config, None, &[observed]);
+        assert_eq!(changed.diffs[0].diff_type, DiffType::Update);
+        let fields: Vec<_> = changed.diffs[0]
+            .details
+            .iter()
+            .map(|d| d.field.as_str())
+            .collect();
+        assert_eq!(fields, ["image"]);
+    }
+
+    #[test]
+    fn test_env_drift_detected() {
+        let yaml = "project:\n  name: proj\nstate:\n  backend: local\npods:\n  - name: web\n    gpu:\n      type: A40\n    runtime:\n      image: img\n      env:\n        MODE: prod\n        API_KEY: new-secret\n";
+        let config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+        let hash = ConfigHasher::new().hash_pod(&config.pods[0]);
+
+        let env = [
+            ("MODE", "debug"),
+            ("API_KEY", "old-secret"),
+            ("HF_TOKEN", "hf"),
+            ("RUNPOD_POD_ID", "id"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        let observed = ObservedPod {
+            id: String::from("id-web"),
+            name: String::from("proj-dev-web"),
+            project: Some(String::from("proj")),
+            environment: Some(String::from("dev")),
+            pod_name: Some(String::from("web")),
+            spec_hash: Some(hash),
+            status: PodStatus::Running,
+            gpu_type: Some(String::from("A40")),
+            gpu_count: 1,
+            image: String::from("img"),
+            endpoints: HashMap::new(),
+            gpu_utilization: None,
+            tags: HashMap::new(),
+            volume_gb: 0,
+            uptime_secs: 0,
+            env: Some(env),
+        };
+
+        let diff = DiffEngine::new().compute_diff(&config, None, std::slice::from_ref(&observed));
+        let web = &diff.diffs[0];
+        assert_eq!(web.diff_type, DiffType::Drift);
+
+        let fields: Vec<_> = web.details.iter().map(|d| d.field.as_str()).collect();
+        assert_eq!(fields, ["env.API_KEY", "env.MODE"]);
+
+        let secret = &web.details[0];
+        assert!(
+            secret
+                .old_value
+                .as_deref()
+                .is_some_and(|v| v.starts_with("sha256:"))
+        );
+        assert!(!secret.to_string().contains("secret"));
+        assert_eq!(web.details[1].old_value.as_deref(), Some("debug"));
+
+        // Under maintenance, the drift is held back
+        let mut state = DeploymentState::new("proj", "dev");
+        state.set_maintenance(
+            "web",
+            crate::state::MaintenanceState::new("debugging", "ops"),
+        );
+        assert!(state.maintenance_for("web-1").is_some());
+        assert!(state.maintenance_for("webapp").is_none());
+        let mut held =
+            DiffEngine::new().compute_diff(&config, Some(&state), std::slice::from_ref(&observed));
+        assert_eq!(
+            held.hold(|name| state.maintenance_for(name).is_some()),
+            ["web"]
+        );
+        assert!(!held.has_changes());
+
+        // Ignored through config and the engine, the env no longer drifts
+        let mut config = config;
+        config.diff = Some(crate::config::DiffConfig {
+            ignore: vec![String::from("env.API_*")],
+        });
+        let engine = DiffEngine::new().with_ignored_field("env.MODE");
+        let quiet = engine.compute_diff(&config, None, &[observed]);
+        assert_eq!(quiet.diffs[0].diff_type, DiffType::NoChange);
+    }
+
+    #[test]
+    fn test_observation_only_fields_ignored() {
+        let yaml = "project:\n  name: proj\nstate:\n  backend: local\npods:\n  - name: web\n    gpu:\n      type: A40\n    ports: [\"8000/http\", \"22/tcp\"]\n    runtime:\n      image: img\n      env:\n        MODE: prod\n";
+        let mut config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+        let hash = ConfigHasher::new().hash_pod(&config.pods[0]);
+
+        // RunPod sets its own env, and picks the public ports
+        let env = [
+            ("MODE", "prod"),
+            ("RUNPOD_POD_ID", "id-web"),
+            ("RUNPOD_PUBLIC_IP", "203.0.113.7"),
+            ("RUNPOD_TCP_PORT_22", "40123"),
+            ("PUBLIC_KEY", "ssh-ed25519 AAAA"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        let mut observed = ObservedPod {
+            id: String::from("id-web"),
+            name: String::from("proj-dev-web"),
+            project: Some(String::from("proj")),
+            environment: Some(String::from("dev")),
+            pod_name: Some(String::from("web")),
+            spec_hash: Some(hash),
+            status: PodStatus::Running,
+            gpu_type: Some(String::from("A40")),
+            gpu_count: 1,
+            image: String::from("img"),
+            endpoints: HashMap::from([
+                (8000, String::from("https://id-web-8000.proxy.runpod.net")),
+                (22, String::from("tcp://203.0.113.7:40123")),
+            ]),
+            gpu_utilization: None,
+            tags: HashMap::new(),
+            volume_gb: 0,
+            uptime_secs: 0,
+            env: Some(env),
+        };
+        let engine = DiffEngine::new();
+        let quiet = engine.compute_diff(&config, None, std::slice::from_ref(&observed));
+        assert_eq!(quiet.diffs[0].diff_type, DiffType::NoChange);
+
+        // A sidecar's env drifts until diff.ignore lists it
+        observed.env.as_mut().unwrap().extend([
+            (String::from("OTEL_SERVICE_NAME"), String::from("web")),
+            (
+                String::from("OTEL_TRACES_SAMPLER"),
+                String::from("always_on"),
+            ),
+        ]);
+        let drift = engine.compute_diff(&config, None, std::slice::from_ref(&observed));
+        let fields: Vec<_> = drift.diffs[0]
+            .details
+            .iter()
+            .map(|d| d.field.as_str())
+            .collect();
+        assert_eq!(fields, ["env.OTEL_SERVICE_NAME", "env.OTEL_TRACES_SAMPLER"]);
+
+        config.diff = Some(crate::config::DiffConfig {
+            ignore: vec![String::from("env.OTEL_*")],
+        });
+        let suppressed = engine.compute_diff(&config, None, &[observed]);
+        assert_eq!(suppressed.diffs[0].diff_type, DiffType::NoChange);
+    }
+
+    #[test]
+    fn test_manual_scale_until_config_changes() {
+        let yaml = "project:\n  name: proj\nstate:\n  backend: local\npods:\n  - name: llm\n    replicas: 2\n    gpu:\n      type: A40\n    runtime:\n      image: img\n";
+        let mut config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+        let mut state = DeploymentState::new("proj", "dev");
+        state.scale_replicas("llm", 3, 2);
+
+        let names: Vec<_> = DiffEngine::desired_instances(&config, Some(&state))
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        assert_eq!(names, ["llm", "llm-1", "llm-2"]);
+
+        // Editing `replicas` takes precedence over the scaled count
+        config.pods[0].replicas = 4;
+        assert_eq!(DiffEngine::replica_count(&config.pods[0], Some(&state)), 4);
+    }
+
+    #[test]
+    fn test_scaling_out_keeps_running_pod() {
+        let yaml = "project:\n  name: proj\nstate:\n  backend: local\npods:\n  - name: llm\n    gpu:\n      type: A40\n    runtime:\n      image: img\n";
+        let mut config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+        let engine = DiffEngine::new();
+        let observed = ObservedPod {
+            id: String::from("id-llm"),
+            name: String::from("proj-dev-llm"),
+            project: Some(String::from("proj")),
+            environment: Some(String::from("dev")),
+            pod_name: Some(String::from("llm")),
+            spec_hash: Some(engine.hasher.hash_pod(&config.pods[0])),
+            status: PodStatus::Running,
+            gpu_type: Some(String::from("A40")),
+            gpu_count: 1,
+            image: String::from("img"),
+            endpoints: HashMap::new(),
+            gpu_utilization: None,
+            tags: HashMap::new(),
+            volume_gb: 0,
+            uptime_secs: 0,
+            env: None,
+        };
+        let observed = std::slice::from_ref(&observed);
+        assert!(!engine.compute_diff(&config, None, observed).has_changes());
+
+        // Going from 1 to 2 replicas adds llm-1 next to the running pod
+        config.pods[0].replicas = 2;
+        let scaled = engine.compute_diff(&config, None, observed);
+        let changes: Vec<_> = scaled
+            .diffs
+            .iter()
+            .map(|d| (d.name.as_str(), d.diff_type))
+            .collect();
+        assert_eq!(
+            changes,
+            [("llm", DiffType::NoChange), ("llm-1", DiffType::Create)]
+        );
+        assert_eq!(scaled.deletes, 0);
+
+        // So does adding an autoscale block
+        config.pods[0].replicas = 1;
+        config.pods[0].autoscale = Some(serde_yaml::from_str("min: 1\nmax: 4").unwrap());
+        let autoscaled = engine.compute_diff(&config, None, observed);
+        assert!(!autoscaled.has_changes());
+        assert_eq!(autoscaled.diffs[0].runpod_id.as_deref(), Some("id-llm"));
+    }
+
+    #[test]
+    fn test_endpoint_diff() {
+        let yaml = "project:\n  name: proj\nstate:\n  backend: local\npods: []\nendpoints:\n  - name: embed\n    template_id: tpl-1\n    gpu:\n      pools: [AMPERE_24]\n  - name: rerank\n    template_id: tpl-2\n    gpu:\n      pools: [ADA_24]\n    workers:\n      max: 2\n";
+        let mut config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+        let engine = DiffEngine::new();
+
+        // Endpoints not in state are created, unless one of the same name exists
+        let existing = ServerlessEndpoint {
+            id: Some(String::from("ep-2")),
+            name: String::from("proj-dev-rerank"),
+            template_id: String::from("tpl-2"),
+            gpu_ids: String::from("ADA_24"),
+            gpu_count: 1,
+            workers_min: 0,
+            workers_max: 2,
+            idle_timeout: 5,
+        };
+        let diff = engine.compute_diff(&config, None, &[]);
+        assert_eq!(diff.creates, 2);
+        let adopted =
+            engine.compute_endpoint_diff(&config, None, Some(std::slice::from_ref(&existing)));
+        assert_eq!(adopted[1].diff_type, DiffType::Update);
+        assert_eq!(adopted[1].runpod_id.as_deref(), Some("ep-2"));
+
+        // Recorded endpoints are unchanged until their configuration changes
+        let mut state = DeploymentState::new("proj", "dev");
+        for (endpoint, id) in config.endpoints.iter().zip(["ep-1", "ep-2"]) {
+            state.set_endpoint(crate::state::EndpointState {
+                name: endpoint.name.clone(),
+                runpod_id: id.to_string(),
+                config_hash: engine.hasher.hash_endpoint(endpoint),
+                created_at: chrono::Utc::now(),
+                updated_at: chrono::Utc::now(),
+            });
+        }
+        assert!(
+            !engine
+                .compute_diff(&config, Some(&state), &[])
+                .has_changes()
+        );
+
+        // A recorded endpoint missing on RunPod is recreated; a removed one is deleted
+        config.endpoints[0].workers.max = 5;
+        let changed = engine.compute_endpoint_diff(&config, Some(&state), None);
+        assert_eq!(changed[0].diff_type, DiffType::Update);
+        let missing = engine.compute_endpoint_diff(&config, Some(&state), Some(&[existing]));
+        assert_eq!(missing[0].diff_type, DiffType::Create);
+        config.endpoints.remove(1);
+        let removed = engine.compute_endpoint_diff(&config, Some(&state), None);
+        assert_eq!(
+            (removed[1].diff_type, removed[1].runpod_id.as_deref()),
+            (DiffType::Delete, Some("ep-2"))
+        );
+    }
+
+    #[test]
+    fn test_template_change_recreates_its_pods() {
+        let yaml = "project:\n  name: proj\nstate:\n  backend: local\ntemplates:\n  - name: base\n    image: img\n    env:\n      MODE: prod\npods:\n  - name: web\n    template: base\n    gpu:\n      type: A40\n";
+        let mut config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+        config.apply_templates();
+        assert_eq!(config.pods[0].runtime.image, "img");
+        let engine = DiffEngine::new();
+
+        let observed = ObservedPod {
+            id: String::from("id-web"),
+            name: String::from("proj-dev-web"),
+            project: Some(String::from("proj")),
+            environment: Some(String::from("dev")),
+            pod_name: Some(String::from("web")),
+            spec_hash: Some(engine.hasher.hash_pod(&config.pods[0])),
+            status: PodStatus::Running,
+            gpu_type: Some(String::from("A40")),
+            gpu_count: 1,
+            image: String::from("img"),
+            endpoints: HashMap::new(),
+            gpu_utilization: None,
+            tags: HashMap::new(),
+            volume_gb: 0,
+            uptime_secs: 0,
+            env: None,
+        };
+        let mut state = DeploymentState::new("proj", "dev");
+        state.set_template(crate::state::TemplateState {
+            name: String::from("base"),
+            runpod_id: String::from("tpl-1"),
+            config_hash: engine.hasher.hash_template(&config.templates[0]),
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        });
+        let observed = std::slice::from_ref(&observed);
+        assert!(
+            !engine
+                .compute_diff(&config, Some(&state), observed)
+                .has_changes()
+        );
+
+        // A disk size change keeps the pod's hash but recreates it from the updated template
+        config.templates[0].container_disk_gb = 50;
+        let diff = engine.compute_diff(&config, Some(&state), observed);
+        assert_eq!(diff.templates[0].diff_type, DiffType::Update);
+        assert_eq!(diff.diffs[0].diff_type, DiffType::Update);
+        assert!(
+            diff.diffs[0]
+                .details
+                .iter()
+                .any(|d| d.field == "template" && d.forces_replacement)
+        );
+
+        // Removed from the configuration, the recorded template is deleted
+        config.templates.clear();
+        config.pods[0].template = None;
+        let removed = engine.compute_template_diff(&config, Some(&state));
+        assert_eq!(
+            (removed[0].diff_type, removed[0].runpod_id.as_deref()),
+            (DiffType::Delete, Some("tpl-1"))
+        );
+    }
+
+    #[test]
+    fn test_stopped_pod_resumed_or_recreated() {
+        let yaml = "project:\n  name: proj\nstate:\n  backend: local\npods:\n  - name: web\n    gpu:\n      type: A40\n    pricing:\n      mode: spot\n      max_bid: 0.4\n    runtime:\n      image: img\n";
+        let config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+        let engine = DiffEngine::new();
+        let observed = ObservedPod {
+            id: String::from("id-web"),
+            name: String::from("proj-dev-web"),
+            project: Some(String::from("proj")),
+            environment: Some(String::from("dev")),
+            pod_name: Some(String::from("web")),
+            spec_hash: Some(engine.hasher.hash_pod(&config.pods[0])),
+            status: PodStatus::Exited,
+            gpu_type: Some(String::from("A40")),
+            gpu_count: 1,
+            image: String::from("img"),
+            endpoints: HashMap::new(),
+            gpu_utilization: None,
+            tags: HashMap::new(),
+            volume_gb: 0,
+            uptime_secs: 0,
+            env: None,
+        };
+        let observed = std::slice::from_ref(&observed);
+
+        // Stopped outside halldyll: resumed, or for a spot pod, rented again
+        let mut diff = engine.compute_diff(&config, None, observed);
+        assert!(diff.diffs[0].needs_resume());
+        assert_eq!(diff.replace_preempted(), ["web"]);
+        assert_eq!(diff.updates, 1);
+        assert!(
+            diff.diffs[0]
+                .details
+                .iter()
+                .any(|d| d.field == "preempted" && d.forces_replacement)
+        );
+
+        // Idle replicas stopped by `watch` stay stopped while the pod may sleep
+        let mut config = config;
+        config.pods[0].pricing = None;
+        config.pods[0].autoscale =
+            Some(serde_yaml::from_str("max: 2\nidle_timeout_secs: 600").unwrap());
+        let instance = &DiffEngine::desired_instances(&config, None)[0];
+        let mut observed = observed[0].clone();
+        observed.pod_name = Some(instance.name.clone());
+        observed.spec_hash = Some(engine.hasher.hash_pod(instance));
+        let mut state = DeploymentState::new("proj", "dev");
+        let mut pod_state = PodState::new(&instance.name, "id-web", "");
+        pod_state.stop(StopReason::Idle);
+        state.set_pod(pod_state);
+        assert!(
+            !engine
+                .compute_diff(&config, Some(&state), std::slice::from_ref(&observed))
+                .has_changes()
+        );
+
+        // Without an idle timeout the pod must run again
+        config.pods[0].autoscale.as_mut().unwrap().idle_timeout_secs = None;
+        let awake = engine.compute_diff(&config, Some(&state), std::slice::from_ref(&observed));
+        assert!(awake.diffs.iter().any(ResourceDiff::needs_resume));
+    }
+
+    #[test]
+    fn test_only_external_stops_are_resumed() {
+        let yaml = "project:\n  name: proj\nstate:\n  backend: local\npods:\n  - name: web\n    gpu:\n      type: A40\n    runtime:\n      image: img\n";
+        let mut config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+        let engine = DiffEngine::new();
+        let observed = ObservedPod {
+            id: String::from("id-web"),
+            name: String::from("proj-dev-web"),
+            project: Some(String::from("proj")),
+            environment: Some(String::from("dev")),
+            pod_name: Some(String::from("web")),
+            spec_hash: Some(engine.hasher.hash_pod(&config.pods[0])),
+            status: PodStatus::Exited,
+            gpu_type: Some(String::from("A40")),
+            gpu_count: 1,
+            image: String::from("img"),
+            endpoints: HashMap::new(),
+            gpu_utilization: None,
+            tags: HashMap::new(),
+            volume_gb: 0,
+            uptime_secs: 0,
+            env: None,
+        };
+        let observed = std::slice::from_ref(&observed);
+        let state_with = |name: &str, reason: Option<StopReason>| {
+            let mut state = DeploymentState::new("proj", "dev");
+            let mut pod_state = PodState::new(name, "id-web", "");
+            if let Some(reason) = reason {
+                pod_state.stop(reason);
+            }
+            state.set_pod(pod_state);
+            state
+        };
+
+        // Stopped in the dashboard: state records no reason, so it runs again
+        let external = state_with("web", None);
+        let resumed = engine.compute_diff(&config, Some(&external), observed);
+        assert_eq!(resumed.updates, 1);
+        assert!(resumed.diffs[0].needs_resume());
+
+        // Stopped by halldyll because the pod is disabled: left stopped
+        let disabled = state_with("web", Some(StopReason::Disabled));
+        config.pods[0].enabled = false;
+        let kept = engine.compute_diff(&config, Some(&disabled), observed);
+        assert!(!kept.has_changes());
+        assert!(!kept.diffs.iter().any(ResourceDiff::needs_resume));
+
+        // Enabled again, the config says it must run
+        config.pods[0].enabled = true;
+        let reenabled = engine.compute_diff(&config, Some(&disabled), observed);
+        assert!(reenabled.diffs[0].needs_resume());
+
+        // Put to sleep by `watch` while the pod has an idle timeout: left stopped
+        config.pods[0].autoscale =
+            Some(serde_yaml::from_str("max: 1\nidle_timeout_secs: 600").unwrap());
+        let instance = &DiffEngine::desired_instances(&config, None)[0];
+        let mut sleeping = observed[0].clone();
+        sleeping.pod_name = Some(instance.name.clone());
+        sleeping.spec_hash = Some(engine.hasher.hash_pod(instance));
+        let idle = state_with(&instance.name, Some(StopReason::Idle));
+        assert!(
+            !engine
+                .compute_diff(&config, Some(&idle), std::slice::from_ref(&sleeping))
+                .has_changes()
+        );
+
+        // The same pod stopped externally is still resumed
+        let stopped_replica = state_with(&instance.name, None);
+        let woken = engine.compute_diff(
+            &config,
+            Some(&stopped_replica),
+            std::slice::from_ref(&sleeping),
+        );
+        assert!(woken.diffs[0].needs_resume());
+    }
+}
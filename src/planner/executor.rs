@@ -3,16 +3,55 @@
 //! This module handles the execution of deployment plans, including
 //! error handling, rollback, and progress tracking.
 
-use std::collections::HashSet;
+use chrono::{DateTime, Utc};
+use futures::stream::{FuturesUnordered, StreamExt};
+use std::collections::{HashMap, HashSet};
+use tokio::sync::Mutex;
 use tracing::{debug, error, info, warn};
 
-use crate::config::ProjectConfig;
+use crate::config::DrainConfig;
+use crate::config::{DeployConfig, PodConfig, ProjectConfig};
 use crate::error::{HalldyllError, ReconcileError, Result};
-use crate::runpod::PodProvisioner;
-use crate::state::{DeploymentHistoryEntry, DeploymentOperation, DeploymentState, PodState};
+use crate::loadbalancer::ManifestGenerator;
+use crate::runpod::{
+    EndpointManager, EngineDrainer, HealthChecker, Pod, PodObserver, PodProvisioner, PodStatus,
+    TemplateManager, VolumeManager, keys_fingerprint,
+};
+use crate::state::{
+    DeploymentHistoryEntry, DeploymentOperation, DeploymentState, EndpointState, PodState,
+    TemplateState, VolumeState,
+};
 
 use super::plan::{ActionType, DeploymentPlan, PlannedAction};
 
+/// Error recorded for actions skipped because a dependency failed.
+const SKIPPED_DEPENDENCY: &str = "Skipped due to dependency failure";
+
+/// Error recorded for actions skipped after hitting a `RunPod` account limit.
+const SKIPPED_QUOTA: &str = "Skipped after RunPod account limit was reached";
+
+/// Error recorded for actions not started because the apply deadline was near.
+const SKIPPED_DEADLINE: &str = "Not started: apply deadline reached";
+
+/// Error recorded for an action interrupted at the apply deadline.
+const CANCELLED_DEADLINE: &str = "Cancelled at apply deadline";
+
+/// How long a new pod may take to become healthy when something waits on it.
+const HEALTHY_TIMEOUT_SECS: u64 = 600;
+
+/// What to do with an action still running when the apply deadline passes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum DeadlinePolicy {
+    /// Let it finish, then stop.
+    #[default]
+    Complete,
+    /// Stop waiting at the deadline. Actions not yet sent to `RunPod` are
+    /// cancelled, and waits for dependencies or health and drains are cut
+    /// short; a call already sent to `RunPod` is finished and recorded, so
+    /// nothing is left running outside the state.
+    Cancel,
+}
+
 /// Executor for deployment plans.
 #[derive(Debug)]
 pub struct PlanExecutor<'a> {
@@ -22,6 +61,14 @@ pub struct PlanExecutor<'a> {
     project: &'a ProjectConfig,
     /// Whether to continue on errors.
     continue_on_error: bool,
+    /// When to stop starting new actions.
+    deadline: Option<tokio::time::Instant>,
+    /// How to treat an action still running at the deadline.
+    deadline_policy: DeadlinePolicy,
+    /// Configuration whose load balancer manifests follow replacements.
+    load_balancing: Option<&'a DeployConfig>,
+    /// Maximum number of actions running at once.
+    parallelism: usize,
 }
 
 /// Result of executing a single action.
@@ -37,6 +84,23 @@ pub struct ActionResult {
     pub pod_id: Option<String>,
     /// Error message (if failed).
     pub error: Option<String>,
+    /// Suggested remediation for the error (if known).
+    pub hint: Option<String>,
+    /// Whether the action failed on a `RunPod` account limit.
+    pub quota_exceeded: bool,
+    /// When the action ran and how long it took (not set for skipped actions).
+    pub timing: Option<ActionTimes>,
+}
+
+/// Start, end and duration of an executed action.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ActionTimes {
+    /// When the action started.
+    pub started_at: DateTime<Utc>,
+    /// When the action finished.
+    pub finished_at: DateTime<Utc>,
+    /// Duration in seconds.
+    pub duration_secs: f64,
 }
 
 /// Result of executing the entire plan.
@@ -54,6 +118,8 @@ pub struct ExecutionResult {
     pub skipped: usize,
     /// Whether the entire plan succeeded.
     pub success: bool,
+    /// Whether execution stopped early at the apply deadline.
+    pub deadline_reached: bool,
 }
 
 impl<'a> PlanExecutor<'a> {
@@ -64,9 +130,55 @@ impl<'a> PlanExecutor<'a> {
             provisioner,
             project,
             continue_on_error: false,
+            deadline: None,
+            deadline_policy: DeadlinePolicy::Complete,
+            load_balancing: None,
+            parallelism: 1,
         }
     }
 
+    /// Runs up to `parallelism` independent actions at once.
+    ///
+    /// An action still starts only after all its dependencies succeeded.
+    #[must_use]
+    pub const fn with_parallelism(mut self, parallelism: usize) -> Self {
+        self.parallelism = if parallelism == 0 { 1 } else { parallelism };
+        self
+    }
+
+    /// Keeps load balancer manifests in step with replacements.
+    ///
+    /// A replica is marked as draining in its manifest before it is deleted,
+    /// and a new replica is only added once it is healthy.
+    #[must_use]
+    pub const fn with_load_balancers(mut self, config: &'a DeployConfig) -> Self {
+        self.load_balancing = Some(config);
+        self
+    }
+
+    /// Stops starting actions that would not finish before `deadline`.
+    ///
+    /// An action's recorded duration estimate counts against the time left.
+    #[must_use]
+    pub const fn with_deadline(
+        mut self,
+        deadline: tokio::time::Instant,
+        policy: DeadlinePolicy,
+    ) -> Self {
+        self.deadline = Some(deadline);
+        self.deadline_policy = policy;
+        self
+    }
+
+    /// Returns true if `action` should not be started this close to the deadline.
+    fn past_deadline(&self, action: &PlannedAction) -> bool {
+        self.deadline.is_some_and(|deadline| {
+            let needed =
+                std::time::Duration::from_secs_f64(action.estimated_secs.unwrap_or(0.0).max(0.0));
+            deadline.saturating_duration_since(tokio::time::Instant::now()) <= needed
+        })
+    }
+
     /// Sets whether to continue on errors.
     #[must_use]
     pub const fn with_continue_on_error(mut self, continue_on_error: bool) -> Self {
@@ -84,17 +196,13 @@ impl<'a> PlanExecutor<'a> {
         plan: &DeploymentPlan,
         state: &mut DeploymentState,
     ) -> Result<ExecutionResult> {
-        info!("Executing deployment plan with {} actions", plan.actions.len());
+        info!(
+            "Executing deployment plan with {} actions",
+            plan.actions.len()
+        );
 
         if plan.actions.is_empty() {
-            return Ok(ExecutionResult {
-                results: vec![],
-                total_executed: 0,
-                successful: 0,
-                failed: 0,
-                skipped: 0,
-                success: true,
-            });
+            return Ok(ExecutionResult::summarize(vec![], false));
         }
 
         // Check guardrails
@@ -108,112 +216,290 @@ impl<'a> PlanExecutor<'a> {
             }));
         }
 
+        let shared = Mutex::new(state.clone());
         let mut results = Vec::new();
         let mut completed: HashSet<usize> = HashSet::new();
         let mut failed_indices: HashSet<usize> = HashSet::new();
+        let mut quota_error: Option<String> = None;
+        let mut deadline_reached = false;
+        let mut stopping = false;
+        let mut pending: Vec<usize> = (0..plan.actions.len()).collect();
+        let mut running = FuturesUnordered::new();
 
-        // Execute actions in dependency order
-        for (idx, action) in plan.actions.iter().enumerate() {
-            // Check if dependencies are met
-            let deps_failed = action
-                .dependencies
-                .iter()
-                .any(|dep| failed_indices.contains(dep));
-
-            if deps_failed {
-                warn!("Skipping action {} due to failed dependencies", idx);
-                results.push(ActionResult {
-                    index: idx,
-                    action: action.clone(),
-                    success: false,
-                    pod_id: None,
-                    error: Some(String::from("Skipped due to dependency failure")),
-                });
-                failed_indices.insert(idx);
-                continue;
-            }
+        // Start actions in dependency order as slots free up
+        loop {
+            let mut i = 0;
+            while !stopping && i < pending.len() && running.len() < self.parallelism {
+                let idx = pending[i];
+                let action = &plan.actions[idx];
 
-            // Wait for dependencies to complete
-            let deps_complete = action.dependencies.iter().all(|dep| completed.contains(dep));
-            if !deps_complete {
-                // This shouldn't happen with proper ordering, but handle it gracefully
-                warn!("Action {} has incomplete dependencies, waiting...", idx);
+                if let Some(reason) = Self::skip_reason(
+                    action,
+                    &failed_indices,
+                    quota_error.is_some(),
+                    deadline_reached,
+                )
+                .or_else(|| self.past_deadline(action).then_some(SKIPPED_DEADLINE))
+                {
+                    if reason == SKIPPED_DEADLINE {
+                        if !deadline_reached {
+                            warn!(
+                                "Apply deadline reached, not starting action {} or later",
+                                idx
+                            );
+                        }
+                        deadline_reached = true;
+                    } else {
+                        warn!("Skipping action {}: {}", idx, reason);
+                        failed_indices.insert(idx);
+                    }
+                    results.push(ActionResult::skipped(idx, action, reason));
+                    pending.remove(i);
+                    continue;
+                }
+
+                // Wait for dependencies still running
+                let deps_complete = action
+                    .dependencies
+                    .iter()
+                    .all(|dep| completed.contains(dep));
+                if !deps_complete {
+                    if !running.is_empty() {
+                        i += 1;
+                        continue;
+                    }
+                    // This shouldn't happen with proper ordering, but handle it gracefully
+                    warn!(
+                        "Action {} has incomplete dependencies, starting it anyway",
+                        idx
+                    );
+                }
+
+                let dependency_pods: HashMap<usize, String> = results
+                    .iter()
+                    .filter(|r| action.dependencies.contains(&r.index))
+                    .filter_map(|r| Some((r.index, r.pod_id.clone()?)))
+                    .collect();
+                running.push(self.run_scheduled(idx, plan, dependency_pods, &shared));
+                pending.remove(i);
             }
 
-            // Execute the action
-            let result = self.execute_action(idx, action, state).await;
+            let Some(result) = running.next().await else {
+                break;
+            };
+            let idx = result.index;
+            deadline_reached |= result.error.as_deref() == Some(CANCELLED_DEADLINE);
+
+            if result.quota_exceeded && quota_error.is_none() {
+                quota_error.clone_from(&result.error);
+            }
 
             if result.success {
                 completed.insert(idx);
             } else {
                 failed_indices.insert(idx);
 
-                if !self.continue_on_error {
-                    // Add result and return early
-                    results.push(result);
-                    break;
-                }
+                // Let running actions finish, but start nothing new
+                stopping |= !self.continue_on_error;
             }
 
             results.push(result);
         }
+        drop(running);
+        *state = shared.into_inner();
+        results.sort_by_key(|r| r.index);
 
-        // Compute summary
-        let successful = results.iter().filter(|r| r.success).count();
-        let failed = results.iter().filter(|r| !r.success && r.error.as_deref() != Some("Skipped due to dependency failure")).count();
-        let skipped = results.iter().filter(|r| r.error.as_deref() == Some("Skipped due to dependency failure")).count();
+        let execution_result = ExecutionResult::summarize(results, deadline_reached);
 
-        let execution_result = ExecutionResult {
-            total_executed: results.len(),
-            successful,
-            failed,
-            skipped,
-            success: failed == 0,
-            results,
-        };
+        Self::record_history(plan, state, &execution_result, quota_error);
 
-        // Add to history
-        let history_entry = if execution_result.success {
+        Ok(execution_result)
+    }
+
+    /// Adds the outcome of a plan to the deployment history.
+    fn record_history(
+        plan: &DeploymentPlan,
+        state: &mut DeploymentState,
+        result: &ExecutionResult,
+        quota_error: Option<String>,
+    ) {
+        let history_entry = if result.success {
             DeploymentHistoryEntry::new(
                 DeploymentOperation::Create,
                 &plan.config_hash,
-                plan.actions.iter().map(|a| a.resource_name.clone()).collect(),
+                plan.actions
+                    .iter()
+                    .map(|a| a.resource_name.clone())
+                    .collect(),
             )
         } else {
             DeploymentHistoryEntry::failed(
                 DeploymentOperation::Create,
                 &plan.config_hash,
-                plan.actions.iter().map(|a| a.resource_name.clone()).collect(),
-                &format!("{} actions failed", execution_result.failed),
+                plan.actions
+                    .iter()
+                    .map(|a| a.resource_name.clone())
+                    .collect(),
+                &quota_error.unwrap_or_else(|| {
+                    if result.deadline_reached {
+                        format!(
+                            "Apply deadline reached with {} actions skipped",
+                            result.skipped
+                        )
+                    } else {
+                        format!("{} actions failed", result.failed)
+                    }
+                }),
             )
         };
         state.add_history(history_entry);
         state.config_hash.clone_from(&plan.config_hash);
+    }
 
-        Ok(execution_result)
+    /// Returns why `action` must be skipped without being attempted.
+    fn skip_reason(
+        action: &PlannedAction,
+        failed_indices: &HashSet<usize>,
+        quota_reached: bool,
+        deadline_reached: bool,
+    ) -> Option<&'static str> {
+        if action
+            .dependencies
+            .iter()
+            .any(|dep| failed_indices.contains(dep))
+        {
+            return Some(SKIPPED_DEPENDENCY);
+        }
+
+        // Further pods would hit the same account limit
+        if quota_reached
+            && matches!(
+                action.action_type,
+                ActionType::CreatePod | ActionType::UpdatePod | ActionType::ResumePod
+            )
+        {
+            return Some(SKIPPED_QUOTA);
+        }
+
+        // Nothing new starts once the deadline is near
+        deadline_reached.then_some(SKIPPED_DEADLINE)
+    }
+
+    /// Runs a scheduled action once the pods from its `depends_on` are healthy.
+    async fn run_scheduled(
+        &self,
+        index: usize,
+        plan: &DeploymentPlan,
+        dependency_pods: HashMap<usize, String>,
+        state: &Mutex<DeploymentState>,
+    ) -> ActionResult {
+        let action = &plan.actions[index];
+        match self
+            .until_deadline(self.await_dependencies(plan, action, &dependency_pods))
+            .await
+        {
+            None => {
+                warn!("Cancelled action {} at the apply deadline", index);
+                ActionResult::skipped(index, action, CANCELLED_DEADLINE)
+            }
+            Some(Some(reason)) => ActionResult::skipped(index, action, &reason),
+            Some(None) => self.execute_within_deadline(index, action, state).await,
+        }
+    }
+
+    /// Executes an action unless [`DeadlinePolicy::Cancel`]'s deadline has
+    /// passed. Once started, an action is never dropped: only its waits are
+    /// cut short (see [`Self::until_deadline`]).
+    async fn execute_within_deadline(
+        &self,
+        index: usize,
+        action: &PlannedAction,
+        state: &Mutex<DeploymentState>,
+    ) -> ActionResult {
+        if let (Some(deadline), DeadlinePolicy::Cancel) = (self.deadline, self.deadline_policy)
+            && tokio::time::Instant::now() >= deadline
+        {
+            warn!("Cancelled action {} at the apply deadline", index);
+            return ActionResult::skipped(index, action, CANCELLED_DEADLINE);
+        }
+        self.execute_action(index, action, state).await
     }
 
-    /// Executes a single action.
+    /// Runs a wait that is safe to abandon, giving up at the deadline under
+    /// [`DeadlinePolicy::Cancel`].
+    ///
+    /// Returns `None` if the deadline cut it short.
+    async fn until_deadline<T>(&self, wait: impl std::future::Future<Output = T>) -> Option<T> {
+        match (self.deadline, self.deadline_policy) {
+            (Some(deadline), DeadlinePolicy::Cancel) => {
+                tokio::time::timeout_at(deadline, wait).await.ok()
+            }
+            _ => Some(wait.await),
+        }
+    }
+
+    /// Executes a single action, recording how long it took for future estimates.
     async fn execute_action(
         &self,
         index: usize,
         action: &PlannedAction,
-        state: &mut DeploymentState,
+        state: &Mutex<DeploymentState>,
     ) -> ActionResult {
         info!("Executing action {}: {}", index, action.description());
 
+        let started_at = Utc::now();
+        let started = std::time::Instant::now();
+        let mut result = self.run_action(index, action, state).await;
+        let duration_secs = started.elapsed().as_secs_f64();
+        if result.success && action.action_type != ActionType::Noop {
+            state.lock().await.record_timing(
+                &action.action_type.to_string(),
+                &action.resource_name,
+                duration_secs,
+            );
+        }
+        result.timing = Some(ActionTimes {
+            started_at,
+            finished_at: Utc::now(),
+            duration_secs,
+        });
+        result
+    }
+
+    /// Dispatches a single action to its handler.
+    async fn run_action(
+        &self,
+        index: usize,
+        action: &PlannedAction,
+        state: &Mutex<DeploymentState>,
+    ) -> ActionResult {
         match action.action_type {
             ActionType::CreatePod => self.execute_create(index, action, state).await,
-            ActionType::DeletePod => self.execute_delete(index, action, state).await,
+            ActionType::DeletePod | ActionType::DestroyPod => {
+                self.execute_delete(index, action, state).await
+            }
             ActionType::UpdatePod => self.execute_update(index, action, state).await,
             ActionType::StopPod => self.execute_stop(index, action, state).await,
             ActionType::ResumePod => self.execute_resume(index, action, state).await,
+            ActionType::CreateVolume => self.execute_create_volume(index, action, state).await,
+            ActionType::DeleteVolume => self.execute_delete_volume(index, action, state).await,
+            ActionType::CreateEndpoint | ActionType::UpdateEndpoint => {
+                self.execute_save_endpoint(index, action, state).await
+            }
+            ActionType::DeleteEndpoint => self.execute_delete_endpoint(index, action, state).await,
+            ActionType::CreateTemplate | ActionType::UpdateTemplate => {
+                self.execute_save_template(index, action, state).await
+            }
+            ActionType::DeleteTemplate => self.execute_delete_template(index, action, state).await,
             ActionType::Noop => ActionResult {
                 index,
                 action: action.clone(),
                 success: true,
                 pod_id: None,
                 error: None,
+                hint: None,
+                quota_exceeded: false,
+                timing: None,
             },
         }
     }
@@ -223,7 +509,7 @@ impl<'a> PlanExecutor<'a> {
         &self,
         index: usize,
         action: &PlannedAction,
-        state: &mut DeploymentState,
+        state: &Mutex<DeploymentState>,
     ) -> ActionResult {
         let Some(pod_config) = &action.pod_config else {
             return ActionResult {
@@ -232,34 +518,70 @@ impl<'a> PlanExecutor<'a> {
                 success: false,
                 pod_id: None,
                 error: Some(String::from("Missing pod configuration")),
+                hint: None,
+                quota_exceeded: false,
+                timing: None,
             };
         };
 
         let spec_hash = action.new_hash.as_deref().unwrap_or("");
+        let pod_config =
+            &Self::reattach_volumes(pod_config, &action.resource_name, &*state.lock().await);
 
-        match self.provisioner.create_pod(pod_config, self.project, spec_hash).await {
+        match self
+            .provisioner
+            .create_pod(pod_config, self.project, spec_hash)
+            .await
+        {
             Ok(pod) => {
                 // Update state
-                let mut pod_state = PodState::new(
-                    &action.resource_name,
-                    &pod.id,
-                    spec_hash,
-                );
+                let mut pod_state = PodState::new(&action.resource_name, &pod.id, spec_hash);
                 pod_state.gpu_type = pod.gpu_type_name().unwrap_or("").to_string();
                 pod_state.gpu_count = pod.gpu_count;
                 pod_state.image.clone_from(&pod.image_name);
+                pod_state.region.clone_from(&pod.data_center_id);
+                pod_state.ssh_keys = pod_config
+                    .ssh_config(self.project)
+                    .and_then(|ssh| ssh.public_keys().ok())
+                    .filter(|keys| !keys.is_empty())
+                    .map(|keys| keys_fingerprint(&keys));
                 pod_state.set_status(crate::state::DeploymentStatus::Creating);
 
-                state.set_pod(pod_state);
+                state.lock().await.set_pod(pod_state);
+                Self::record_volumes(&mut *state.lock().await, &action.resource_name, pod_config);
 
                 info!("Created pod: {} (ID: {})", action.resource_name, pod.id);
 
+                if let Some(balanced) = self.balanced_pod(&action.resource_name) {
+                    // The pod is recorded, so the wait may end at the deadline
+                    match self
+                        .until_deadline(self.wait_until_healthy(&pod.id, balanced))
+                        .await
+                    {
+                        Some(Ok(())) => {}
+                        Some(Err(e)) => {
+                            warn!(
+                                "Pod {} is not healthy yet, updating the load balancer anyway: {e}",
+                                pod.id
+                            );
+                        }
+                        None => warn!(
+                            "Stopped waiting for pod {} to be healthy at the apply deadline",
+                            pod.id
+                        ),
+                    }
+                    self.write_lb_manifests(state, &[]).await;
+                }
+
                 ActionResult {
                     index,
                     action: action.clone(),
                     success: true,
                     pod_id: Some(pod.id),
                     error: None,
+                    hint: None,
+                    quota_exceeded: false,
+                    timing: None,
                 }
             }
             Err(e) => {
@@ -270,6 +592,9 @@ impl<'a> PlanExecutor<'a> {
                     success: false,
                     pod_id: None,
                     error: Some(e.to_string()),
+                    hint: e.hint(),
+                    quota_exceeded: e.is_quota(),
+                    timing: None,
                 }
             }
         }
@@ -280,28 +605,57 @@ impl<'a> PlanExecutor<'a> {
         &self,
         index: usize,
         action: &PlannedAction,
-        state: &mut DeploymentState,
+        state: &Mutex<DeploymentState>,
     ) -> ActionResult {
-        // Find the pod ID
-        let pod_id = action.runpod_id.clone().or_else(|| {
-            state.get_pod(&action.resource_name).map(|p| p.runpod_id.clone())
-        });
-
-        let Some(pod_id) = pod_id else {
-            debug!("No pod ID found for {}, considering delete successful", action.resource_name);
-            state.remove_pod(&action.resource_name);
+        let Some(pod_id) = Self::known_pod_id(action, state).await else {
+            debug!(
+                "No pod ID found for {}, considering delete successful",
+                action.resource_name
+            );
+            state.lock().await.remove_pod(&action.resource_name);
             return ActionResult {
                 index,
                 action: action.clone(),
                 success: true,
                 pod_id: None,
                 error: None,
+                hint: None,
+                quota_exceeded: false,
+                timing: None,
             };
         };
 
+        // A create-first replacement may already have recorded the new pod
+        let forget = |current: &mut DeploymentState| {
+            if current
+                .get_pod(&action.resource_name)
+                .is_none_or(|p| p.runpod_id == pod_id)
+            {
+                current.remove_pod(&action.resource_name);
+                current.detach_volumes(&action.resource_name);
+            }
+        };
+
+        if self.balanced_pod(&action.resource_name).is_some() {
+            self.write_lb_manifests(state, std::slice::from_ref(&action.resource_name))
+                .await;
+        }
+
+        if let Some(drain) = &action.drain
+            && self
+                .until_deadline(self.drain(&pod_id, drain))
+                .await
+                .is_none()
+        {
+            warn!(
+                "Cut the drain of {} short at the apply deadline",
+                action.resource_name
+            );
+        }
+
         match self.provisioner.terminate_pod(&pod_id).await {
             Ok(()) => {
-                state.remove_pod(&action.resource_name);
+                forget(&mut *state.lock().await);
                 info!("Deleted pod: {} (ID: {})", action.resource_name, pod_id);
 
                 ActionResult {
@@ -310,12 +664,18 @@ impl<'a> PlanExecutor<'a> {
                     success: true,
                     pod_id: Some(pod_id),
                     error: None,
+                    hint: None,
+                    quota_exceeded: false,
+                    timing: None,
                 }
             }
             Err(e) => {
                 // Check if pod was already deleted
-                if matches!(e, HalldyllError::RunPod(crate::error::RunPodError::PodNotFound { .. })) {
-                    state.remove_pod(&action.resource_name);
+                if matches!(
+                    e.root(),
+                    HalldyllError::RunPod(crate::error::RunPodError::PodNotFound { .. })
+                ) {
+                    forget(&mut *state.lock().await);
                     info!("Pod {} was already deleted", action.resource_name);
                     return ActionResult {
                         index,
@@ -323,6 +683,9 @@ impl<'a> PlanExecutor<'a> {
                         success: true,
                         pod_id: Some(pod_id),
                         error: None,
+                        hint: None,
+                        quota_exceeded: false,
+                        timing: None,
                     };
                 }
 
@@ -333,17 +696,542 @@ impl<'a> PlanExecutor<'a> {
                     success: false,
                     pod_id: Some(pod_id),
                     error: Some(e.to_string()),
+                    hint: e.hint(),
+                    quota_exceeded: e.is_quota(),
+                    timing: None,
+                }
+            }
+        }
+    }
+
+    /// Executes a delete volume action.
+    ///
+    /// `RunPod` deletes a pod volume along with its pod, so only network
+    /// volumes tracked in state need deleting.
+    async fn execute_delete_volume(
+        &self,
+        index: usize,
+        action: &PlannedAction,
+        state: &Mutex<DeploymentState>,
+    ) -> ActionResult {
+        let network_volume = state
+            .lock()
+            .await
+            .volumes
+            .values()
+            .find(|v| action.runpod_id.as_ref() == Some(&v.runpod_id))
+            .map(|v| v.runpod_id.clone());
+
+        let outcome = match &network_volume {
+            Some(volume_id) => {
+                VolumeManager::new(self.provisioner.client().clone())
+                    .delete(volume_id)
+                    .await
+            }
+            None => Ok(()),
+        };
+
+        match outcome {
+            Ok(()) => {
+                if let Some(volume_id) = &network_volume {
+                    state
+                        .lock()
+                        .await
+                        .volumes
+                        .retain(|_, v| &v.runpod_id != volume_id);
+                    info!("Deleted network volume: {}", action.resource_name);
+                }
+                ActionResult {
+                    index,
+                    action: action.clone(),
+                    success: true,
+                    pod_id: action.runpod_id.clone(),
+                    error: None,
+                    hint: None,
+                    quota_exceeded: false,
+                    timing: None,
+                }
+            }
+            Err(e) => {
+                error!("Failed to delete volume {}: {}", action.resource_name, e);
+                ActionResult {
+                    index,
+                    action: action.clone(),
+                    success: false,
+                    pod_id: action.runpod_id.clone(),
+                    error: Some(e.to_string()),
+                    hint: e.hint(),
+                    quota_exceeded: e.is_quota(),
+                    timing: None,
+                }
+            }
+        }
+    }
+
+    /// Executes a create volume action.
+    ///
+    /// The volume is recorded detached, so the create of the pod mounting it
+    /// re-attaches it like any volume its pod left behind.
+    async fn execute_create_volume(
+        &self,
+        index: usize,
+        action: &PlannedAction,
+        state: &Mutex<DeploymentState>,
+    ) -> ActionResult {
+        let (instance, name) = action.resource_name.rsplit_once('/').unwrap_or_default();
+        let Some(volume) = action
+            .pod_config
+            .iter()
+            .flat_map(|p| &p.volumes)
+            .find(|v| v.name == name)
+        else {
+            return ActionResult {
+                index,
+                action: action.clone(),
+                success: false,
+                pod_id: None,
+                error: Some(String::from("Missing volume configuration")),
+                hint: None,
+                quota_exceeded: false,
+                timing: None,
+            };
+        };
+
+        let manager = VolumeManager::new(self.provisioner.client().clone());
+        match manager.ensure(self.project, instance, volume).await {
+            Ok(created) => {
+                state.lock().await.set_volume(VolumeState {
+                    name: volume.name.clone(),
+                    runpod_id: created.id.clone(),
+                    mount_path: volume.mount.clone(),
+                    size_gb: created.size,
+                    created_at: chrono::Utc::now(),
+                    pod: instance.to_string(),
+                    detached: true,
+                    data_center: Some(created.data_center_id),
+                    managed: true,
+                });
+                ActionResult {
+                    index,
+                    action: action.clone(),
+                    success: true,
+                    pod_id: Some(created.id),
+                    error: None,
+                    hint: None,
+                    quota_exceeded: false,
+                    timing: None,
+                }
+            }
+            Err(e) => {
+                error!("Failed to create volume {}: {}", action.resource_name, e);
+                ActionResult {
+                    index,
+                    action: action.clone(),
+                    success: false,
+                    pod_id: None,
+                    error: Some(e.to_string()),
+                    hint: e.hint(),
+                    quota_exceeded: e.is_quota(),
+                    timing: None,
+                }
+            }
+        }
+    }
+
+    /// Executes a create or update serverless endpoint action.
+    async fn execute_save_endpoint(
+        &self,
+        index: usize,
+        action: &PlannedAction,
+        state: &Mutex<DeploymentState>,
+    ) -> ActionResult {
+        let Some(endpoint) = &action.endpoint_config else {
+            return ActionResult {
+                index,
+                action: action.clone(),
+                success: false,
+                pod_id: None,
+                error: Some(String::from("Missing endpoint configuration")),
+                hint: None,
+                quota_exceeded: false,
+                timing: None,
+            };
+        };
+
+        let manager = EndpointManager::new(self.provisioner.client().clone());
+        match manager
+            .save(self.project, endpoint, action.runpod_id.as_deref())
+            .await
+        {
+            Ok(saved) => {
+                let runpod_id = saved.id.unwrap_or_default();
+                {
+                    let mut state = state.lock().await;
+                    let created_at = state
+                        .endpoints
+                        .get(&endpoint.name)
+                        .filter(|e| e.runpod_id == runpod_id)
+                        .map_or_else(chrono::Utc::now, |e| e.created_at);
+                    state.set_endpoint(EndpointState {
+                        name: endpoint.name.clone(),
+                        runpod_id: runpod_id.clone(),
+                        config_hash: action.new_hash.clone().unwrap_or_default(),
+                        created_at,
+                        updated_at: chrono::Utc::now(),
+                    });
+                }
+                ActionResult {
+                    index,
+                    action: action.clone(),
+                    success: true,
+                    pod_id: Some(runpod_id),
+                    error: None,
+                    hint: None,
+                    quota_exceeded: false,
+                    timing: None,
+                }
+            }
+            Err(e) => {
+                error!("Failed to save endpoint {}: {}", action.resource_name, e);
+                ActionResult {
+                    index,
+                    action: action.clone(),
+                    success: false,
+                    pod_id: action.runpod_id.clone(),
+                    error: Some(e.to_string()),
+                    hint: e.hint(),
+                    quota_exceeded: e.is_quota(),
+                    timing: None,
+                }
+            }
+        }
+    }
+
+    /// Executes a delete serverless endpoint action.
+    async fn execute_delete_endpoint(
+        &self,
+        index: usize,
+        action: &PlannedAction,
+        state: &Mutex<DeploymentState>,
+    ) -> ActionResult {
+        let manager = EndpointManager::new(self.provisioner.client().clone());
+        let deleted = match &action.runpod_id {
+            Some(id) => manager.delete(id).await,
+            None => Ok(()),
+        };
+        match deleted {
+            Ok(()) => {
+                state.lock().await.remove_endpoint(&action.resource_name);
+                ActionResult {
+                    index,
+                    action: action.clone(),
+                    success: true,
+                    pod_id: action.runpod_id.clone(),
+                    error: None,
+                    hint: None,
+                    quota_exceeded: false,
+                    timing: None,
+                }
+            }
+            Err(e) => {
+                error!("Failed to delete endpoint {}: {}", action.resource_name, e);
+                ActionResult {
+                    index,
+                    action: action.clone(),
+                    success: false,
+                    pod_id: action.runpod_id.clone(),
+                    error: Some(e.to_string()),
+                    hint: e.hint(),
+                    quota_exceeded: e.is_quota(),
+                    timing: None,
+                }
+            }
+        }
+    }
+
+    /// Executes a create or update template action.
+    async fn execute_save_template(
+        &self,
+        index: usize,
+        action: &PlannedAction,
+        state: &Mutex<DeploymentState>,
+    ) -> ActionResult {
+        let Some(template) = &action.template_config else {
+            return ActionResult {
+                index,
+                action: action.clone(),
+                success: false,
+                pod_id: None,
+                error: Some(String::from("Missing template configuration")),
+                hint: None,
+                quota_exceeded: false,
+                timing: None,
+            };
+        };
+
+        let manager = TemplateManager::new(self.provisioner.client().clone());
+        match manager
+            .save(self.project, template, action.runpod_id.as_deref())
+            .await
+        {
+            Ok(saved) => {
+                let runpod_id = saved.id.unwrap_or_default();
+                {
+                    let mut state = state.lock().await;
+                    let created_at = state
+                        .templates
+                        .get(&template.name)
+                        .filter(|t| t.runpod_id == runpod_id)
+                        .map_or_else(chrono::Utc::now, |t| t.created_at);
+                    state.set_template(TemplateState {
+                        name: template.name.clone(),
+                        runpod_id: runpod_id.clone(),
+                        config_hash: action.new_hash.clone().unwrap_or_default(),
+                        created_at,
+                        updated_at: chrono::Utc::now(),
+                    });
+                }
+                ActionResult {
+                    index,
+                    action: action.clone(),
+                    success: true,
+                    pod_id: Some(runpod_id),
+                    error: None,
+                    hint: None,
+                    quota_exceeded: false,
+                    timing: None,
+                }
+            }
+            Err(e) => {
+                error!("Failed to save template {}: {}", action.resource_name, e);
+                ActionResult {
+                    index,
+                    action: action.clone(),
+                    success: false,
+                    pod_id: action.runpod_id.clone(),
+                    error: Some(e.to_string()),
+                    hint: e.hint(),
+                    quota_exceeded: e.is_quota(),
+                    timing: None,
+                }
+            }
+        }
+    }
+
+    /// Executes a delete template action.
+    async fn execute_delete_template(
+        &self,
+        index: usize,
+        action: &PlannedAction,
+        state: &Mutex<DeploymentState>,
+    ) -> ActionResult {
+        let manager = TemplateManager::new(self.provisioner.client().clone());
+        match manager.delete(self.project, &action.resource_name).await {
+            Ok(()) => {
+                state.lock().await.remove_template(&action.resource_name);
+                ActionResult {
+                    index,
+                    action: action.clone(),
+                    success: true,
+                    pod_id: action.runpod_id.clone(),
+                    error: None,
+                    hint: None,
+                    quota_exceeded: false,
+                    timing: None,
+                }
+            }
+            Err(e) => {
+                error!("Failed to delete template {}: {}", action.resource_name, e);
+                ActionResult {
+                    index,
+                    action: action.clone(),
+                    success: false,
+                    pod_id: action.runpod_id.clone(),
+                    error: Some(e.to_string()),
+                    hint: e.hint(),
+                    quota_exceeded: e.is_quota(),
+                    timing: None,
                 }
             }
         }
     }
 
+    /// Returns the pod configuration with detached network volumes re-attached.
+    ///
+    /// A persistent volume without a configured network volume mounts the
+    /// detached volume of the same name that this pod instance left behind,
+    /// unless it lives in another data center than the one configured.
+    fn reattach_volumes(
+        pod_config: &PodConfig,
+        instance: &str,
+        state: &DeploymentState,
+    ) -> PodConfig {
+        let mut pod_config = pod_config.clone();
+        for volume in pod_config
+            .volumes
+            .iter_mut()
+            .filter(|v| v.persistent && v.network_volume_id.is_none())
+        {
+            if let Some(detached) = state.volume_for(instance, &volume.name).filter(|v| {
+                v.detached && (v.data_center.is_none() || v.data_center == volume.data_center)
+            }) {
+                info!(
+                    "Re-attaching volume {} ({}) to {instance}",
+                    volume.name, detached.runpod_id
+                );
+                volume.network_volume_id = Some(detached.runpod_id.clone());
+            }
+        }
+        pod_config
+    }
+
+    /// Records the network volumes mounted by a newly created pod.
+    fn record_volumes(state: &mut DeploymentState, instance: &str, pod_config: &PodConfig) {
+        for volume in &pod_config.volumes {
+            let Some(volume_id) = &volume.network_volume_id else {
+                continue;
+            };
+            let recorded = state
+                .volume_for(instance, &volume.name)
+                .filter(|v| &v.runpod_id == volume_id);
+            let created_at = recorded.map_or_else(chrono::Utc::now, |v| v.created_at);
+            let managed = recorded.is_some_and(|v| v.managed);
+            state.set_volume(VolumeState {
+                name: volume.name.clone(),
+                runpod_id: volume_id.clone(),
+                mount_path: volume.mount.clone(),
+                size_gb: volume.size_gb.unwrap_or_default(),
+                created_at,
+                pod: instance.to_string(),
+                detached: false,
+                data_center: volume.data_center.clone(),
+                managed,
+            });
+        }
+    }
+
+    /// Drains a running pod's engine before it is terminated.
+    ///
+    /// Problems are logged; the termination goes ahead regardless.
+    async fn drain(&self, pod_id: &str, drain: &DrainConfig) {
+        let observer = PodObserver::new(self.provisioner.client().clone())
+            .with_tag_namespace(self.project.tag_namespace());
+        let pod = match observer.get_pod(pod_id).await {
+            Ok(pod) if pod.status == PodStatus::Running => pod,
+            Ok(_) => return,
+            Err(e) => {
+                warn!("Skipping drain of {pod_id}: {e}");
+                return;
+            }
+        };
+
+        match EngineDrainer::new() {
+            Ok(drainer) => {
+                drainer.drain(&pod, drain).await;
+            }
+            Err(e) => warn!("Skipping drain of {pod_id}: {e}"),
+        }
+    }
+
+    /// Returns the configuration of a pod instance that has a load balancer.
+    fn balanced_pod(&self, instance: &str) -> Option<&'a PodConfig> {
+        self.load_balancing?
+            .pod_for_instance(instance)
+            .filter(|pod| pod.load_balancer.is_some())
+    }
+
+    /// Waits for a new pod to run and pass its health checks.
+    async fn wait_until_healthy(&self, pod_id: &str, pod_config: &PodConfig) -> Result<()> {
+        let started = std::time::Instant::now();
+        self.provisioner
+            .wait_for_status(pod_id, PodStatus::Running, HEALTHY_TIMEOUT_SECS)
+            .await?;
+
+        let observer = PodObserver::new(self.provisioner.client().clone())
+            .with_tag_namespace(self.project.tag_namespace());
+        let pod = observer.get_pod(pod_id).await?;
+        let remaining = HEALTHY_TIMEOUT_SECS.saturating_sub(started.elapsed().as_secs());
+        HealthChecker::new()?
+            .wait_for_healthy(&pod, pod_config.health_check.as_ref(), remaining)
+            .await
+            .map(|_| ())
+    }
+
+    /// Waits for the pods `action` depends on that this plan brought up.
+    ///
+    /// Returns the reason to skip `action` if one of them never got healthy.
+    async fn await_dependencies(
+        &self,
+        plan: &DeploymentPlan,
+        action: &PlannedAction,
+        dependency_pods: &HashMap<usize, String>,
+    ) -> Option<String> {
+        for &dep in &action.dependencies {
+            let dependency = &plan.actions[dep];
+            // Replacements and warm stops depend on actions on the same pod
+            if dependency.resource_name == action.resource_name {
+                continue;
+            }
+            let (Some(pod_config), Some(pod_id)) =
+                (&dependency.pod_config, dependency_pods.get(&dep))
+            else {
+                continue;
+            };
+
+            info!(
+                "Waiting for {} to be healthy before {}",
+                dependency.resource_name, action.resource_name
+            );
+            if let Err(e) = self.wait_until_healthy(pod_id, pod_config).await {
+                warn!(
+                    "Dependency {} did not become healthy: {e}",
+                    dependency.resource_name
+                );
+                return Some(format!(
+                    "Dependency {} did not become healthy",
+                    dependency.resource_name
+                ));
+            }
+        }
+        None
+    }
+
+    /// Regenerates load balancer manifests, marking `draining` replicas.
+    ///
+    /// Failures are logged; the plan goes ahead regardless.
+    async fn write_lb_manifests(&self, state: &Mutex<DeploymentState>, draining: &[String]) {
+        let Some(config) = self.load_balancing else {
+            return;
+        };
+
+        let observer = PodObserver::new(self.provisioner.client().clone())
+            .with_tag_namespace(self.project.tag_namespace());
+        let result = match observer
+            .list_project_pods(&config.project.name, &config.project.environment)
+            .await
+        {
+            Ok(pods) => ManifestGenerator::new()
+                .with_draining(draining.iter().cloned())
+                .write_all(config, Some(&*state.lock().await), &pods),
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(paths) => {
+                for path in paths {
+                    info!("Updated load balancer manifest: {}", path.display());
+                }
+            }
+            Err(e) => warn!("Failed to update load balancer manifests: {e}"),
+        }
+    }
+
     /// Executes an update pod action (currently just recreates).
     async fn execute_update(
         &self,
         index: usize,
         action: &PlannedAction,
-        state: &mut DeploymentState,
+        state: &Mutex<DeploymentState>,
     ) -> ActionResult {
         // For now, update is handled as delete + create in the plan
         // This is a fallback in case it's called directly
@@ -355,26 +1243,31 @@ impl<'a> PlanExecutor<'a> {
         &self,
         index: usize,
         action: &PlannedAction,
-        state: &mut DeploymentState,
+        state: &Mutex<DeploymentState>,
     ) -> ActionResult {
-        let pod_id = action.runpod_id.clone().or_else(|| {
-            state.get_pod(&action.resource_name).map(|p| p.runpod_id.clone())
-        });
-
-        let Some(pod_id) = pod_id else {
+        let Some(pod_id) = Self::known_pod_id(action, state).await else {
             return ActionResult {
                 index,
                 action: action.clone(),
                 success: false,
                 pod_id: None,
                 error: Some(String::from("Pod not found")),
+                hint: None,
+                quota_exceeded: false,
+                timing: None,
             };
         };
 
         match self.provisioner.stop_pod(&pod_id).await {
             Ok(()) => {
-                if let Some(pod_state) = state.get_pod_mut(&action.resource_name) {
-                    pod_state.set_status(crate::state::DeploymentStatus::Stopped);
+                // Warm pool stops carry no pod config
+                let reason = if action.pod_config.as_ref().is_some_and(|p| !p.enabled) {
+                    crate::state::StopReason::Disabled
+                } else {
+                    crate::state::StopReason::WarmPool
+                };
+                if let Some(pod_state) = state.lock().await.get_pod_mut(&action.resource_name) {
+                    pod_state.stop(reason);
                 }
 
                 ActionResult {
@@ -383,6 +1276,9 @@ impl<'a> PlanExecutor<'a> {
                     success: true,
                     pod_id: Some(pod_id),
                     error: None,
+                    hint: None,
+                    quota_exceeded: false,
+                    timing: None,
                 }
             }
             Err(e) => {
@@ -393,6 +1289,9 @@ impl<'a> PlanExecutor<'a> {
                     success: false,
                     pod_id: Some(pod_id),
                     error: Some(e.to_string()),
+                    hint: e.hint(),
+                    quota_exceeded: e.is_quota(),
+                    timing: None,
                 }
             }
         }
@@ -403,27 +1302,30 @@ impl<'a> PlanExecutor<'a> {
         &self,
         index: usize,
         action: &PlannedAction,
-        state: &mut DeploymentState,
+        state: &Mutex<DeploymentState>,
     ) -> ActionResult {
-        let pod_id = action.runpod_id.clone().or_else(|| {
-            state.get_pod(&action.resource_name).map(|p| p.runpod_id.clone())
-        });
-
-        let Some(pod_id) = pod_id else {
+        let Some(pod_id) = Self::known_pod_id(action, state).await else {
             return ActionResult {
                 index,
                 action: action.clone(),
                 success: false,
                 pod_id: None,
                 error: Some(String::from("Pod not found")),
+                hint: None,
+                quota_exceeded: false,
+                timing: None,
             };
         };
 
         match self.provisioner.resume_pod(&pod_id).await {
-            Ok(_) => {
-                if let Some(pod_state) = state.get_pod_mut(&action.resource_name) {
+            Ok(pod) => {
+                let mut state = state.lock().await;
+                if let Some(spec_hash) = &action.new_hash {
+                    Self::record_claimed_pod(&mut state, &action.resource_name, &pod, spec_hash);
+                } else if let Some(pod_state) = state.get_pod_mut(&action.resource_name) {
                     pod_state.set_status(crate::state::DeploymentStatus::Running);
                 }
+                drop(state);
 
                 ActionResult {
                     index,
@@ -431,6 +1333,9 @@ impl<'a> PlanExecutor<'a> {
                     success: true,
                     pod_id: Some(pod_id),
                     error: None,
+                    hint: None,
+                    quota_exceeded: false,
+                    timing: None,
                 }
             }
             Err(e) => {
@@ -441,18 +1346,129 @@ impl<'a> PlanExecutor<'a> {
                     success: false,
                     pod_id: Some(pod_id),
                     error: Some(e.to_string()),
+                    hint: e.hint(),
+                    quota_exceeded: e.is_quota(),
+                    timing: None,
                 }
             }
         }
     }
 }
 
+impl PlanExecutor<'_> {
+    /// Returns the `RunPod` ID of the pod an action targets.
+    async fn known_pod_id(
+        action: &PlannedAction,
+        state: &Mutex<DeploymentState>,
+    ) -> Option<String> {
+        if let Some(pod_id) = &action.runpod_id {
+            return Some(pod_id.clone());
+        }
+        state
+            .lock()
+            .await
+            .get_pod(&action.resource_name)
+            .map(|p| p.runpod_id.clone())
+    }
+
+    /// Records a resumed warm pod under the instance it now serves.
+    ///
+    /// The pod keeps its warm pool tags on `RunPod`; the diff engine matches
+    /// it back to `name` through its ID in state.
+    fn record_claimed_pod(state: &mut DeploymentState, name: &str, pod: &Pod, spec_hash: &str) {
+        let warm_name = state
+            .pods
+            .values()
+            .find(|p| p.runpod_id == pod.id && p.name != name)
+            .map(|p| p.name.clone());
+        let warm = warm_name.and_then(|warm_name| state.remove_pod(&warm_name));
+
+        let mut pod_state = PodState::new(name, &pod.id, spec_hash);
+        pod_state.gpu_type = pod
+            .gpu_type_name()
+            .map(String::from)
+            .or_else(|| warm.map(|w| w.gpu_type))
+            .unwrap_or_default();
+        pod_state.gpu_count = pod.gpu_count;
+        pod_state.image.clone_from(&pod.image_name);
+        pod_state.set_status(crate::state::DeploymentStatus::Running);
+        state.set_pod(pod_state);
+
+        info!("Resumed warm pod {} as {name}", pod.id);
+    }
+}
+
 impl ExecutionResult {
     /// Returns true if all actions succeeded.
     #[must_use]
     pub const fn all_successful(&self) -> bool {
         self.success && self.failed == 0 && self.skipped == 0
     }
+
+    /// Returns the wall-clock time from the first action's start to the last one's end.
+    #[must_use]
+    pub fn duration_secs(&self) -> Option<f64> {
+        let started = self
+            .results
+            .iter()
+            .filter_map(|r| r.timing)
+            .map(|t| t.started_at)
+            .min()?;
+        let finished = self
+            .results
+            .iter()
+            .filter_map(|r| r.timing)
+            .map(|t| t.finished_at)
+            .max()?;
+        // Millisecond precision is plenty for a summary
+        #[allow(clippy::cast_precision_loss)]
+        let secs = (finished - started).num_milliseconds() as f64 / 1000.0;
+        Some(secs)
+    }
+}
+
+impl ActionResult {
+    /// Creates the result of an action that was not attempted.
+    fn skipped(index: usize, action: &PlannedAction, reason: &str) -> Self {
+        Self {
+            index,
+            action: action.clone(),
+            success: false,
+            pod_id: None,
+            error: Some(reason.to_string()),
+            hint: None,
+            quota_exceeded: false,
+            timing: None,
+        }
+    }
+}
+
+impl ExecutionResult {
+    /// Tallies the individual action results.
+    fn summarize(results: Vec<ActionResult>, deadline_reached: bool) -> Self {
+        let successful = results.iter().filter(|r| r.success).count();
+        let is_skipped = |r: &&ActionResult| {
+            matches!(
+                r.error.as_deref(),
+                Some(SKIPPED_DEPENDENCY | SKIPPED_QUOTA | SKIPPED_DEADLINE | CANCELLED_DEADLINE)
+            )
+        };
+        let failed = results
+            .iter()
+            .filter(|r| !r.success && !is_skipped(r))
+            .count();
+        let skipped = results.iter().filter(is_skipped).count();
+
+        Self {
+            total_executed: results.len(),
+            successful,
+            failed,
+            skipped,
+            success: failed == 0 && !deadline_reached,
+            deadline_reached,
+            results,
+        }
+    }
 }
 
 impl std::fmt::Display for ExecutionResult {
@@ -461,6 +1477,113 @@ impl std::fmt::Display for ExecutionResult {
             f,
             "Executed {} actions: {} successful, {} failed, {} skipped",
             self.total_executed, self.successful, self.failed, self.skipped
-        )
+        )?;
+
+        for result in self.results.iter().filter(|r| !r.success) {
+            if let Some(error) = &result.error {
+                write!(f, "\n  ✗ {}: {error}", result.action.resource_name)?;
+            }
+            if let Some(hint) = &result.hint {
+                write!(f, "\n    hint: {hint}")?;
+            }
+        }
+
+        if self.deadline_reached {
+            write!(f, "\n  Stopped at the apply deadline")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::DeployConfig;
+    use crate::planner::DiffEngine;
+    use crate::runpod::RunPodClient;
+
+    #[tokio::test]
+    async fn test_deadline_stops_scheduling() {
+        let yaml = "project:\n  name: proj\nstate:\n  backend: local\npods:\n  - name: llm\n    gpu:\n      type: A40\n    runtime:\n      image: img\n";
+        let config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+        let diff = DiffEngine::new().compute_diff(&config, None, &[]);
+        let plan = DeploymentPlan::from_diff(&diff, &config, "hash");
+
+        let provisioner = PodProvisioner::new(RunPodClient::new("test-key").unwrap());
+        let executor = PlanExecutor::new(&provisioner, &config.project)
+            .with_deadline(tokio::time::Instant::now(), DeadlinePolicy::Complete);
+
+        let mut state = DeploymentState::new("proj", "dev");
+        let result = executor.execute(&plan, &mut state).await.unwrap();
+
+        assert!(result.deadline_reached);
+        assert!(!result.success);
+        assert_eq!(result.skipped, 1);
+        assert_eq!(result.results[0].error.as_deref(), Some(SKIPPED_DEADLINE));
+        assert!(state.pods.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cancel_deadline_only_cuts_waits() {
+        let yaml = "project:\n  name: proj\nstate:\n  backend: local\npods:\n  - name: llm\n    gpu:\n      type: A40\n    runtime:\n      image: img\n";
+        let config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+        let diff = DiffEngine::new().compute_diff(&config, None, &[]);
+        let plan = DeploymentPlan::from_diff(&diff, &config, "hash");
+
+        let provisioner = PodProvisioner::new(RunPodClient::new("test-key").unwrap());
+        let deadline = tokio::time::Instant::now() + std::time::Duration::from_millis(20);
+        let cancel = PlanExecutor::new(&provisioner, &config.project)
+            .with_deadline(deadline, DeadlinePolicy::Cancel);
+        tokio::time::sleep_until(deadline).await;
+
+        // Waits are abandoned at the deadline, but only under Cancel
+        assert_eq!(
+            cancel.until_deadline(std::future::pending::<()>()).await,
+            None
+        );
+        let complete = PlanExecutor::new(&provisioner, &config.project)
+            .with_deadline(deadline, DeadlinePolicy::Complete);
+        assert_eq!(complete.until_deadline(async { 1 }).await, Some(1));
+
+        // An action not yet sent to RunPod is cancelled before its call
+        let state = Mutex::new(DeploymentState::new("proj", "dev"));
+        let result = cancel
+            .execute_within_deadline(0, &plan.actions[0], &state)
+            .await;
+        assert_eq!(result.error.as_deref(), Some(CANCELLED_DEADLINE));
+        assert!(state.into_inner().pods.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_parallel_execution_respects_dependencies() {
+        let yaml = "project:\n  name: proj\nstate:\n  backend: local\npods:\n  - name: db\n    gpu:\n      type: A40\n    runtime:\n      image: img\n  - name: web\n    gpu:\n      type: A40\n    runtime:\n      image: img\n  - name: api\n    depends_on: [db]\n    gpu:\n      type: A40\n    runtime:\n      image: img\n";
+        let config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+        let diff = DiffEngine::new().compute_diff(&config, None, &[]);
+        let mut plan = DeploymentPlan::from_diff(&diff, &config, "hash");
+
+        // Stopping a pod that was never created fails without contacting RunPod
+        for action in &mut plan.actions {
+            action.action_type = if action.resource_name == "db" {
+                ActionType::StopPod
+            } else {
+                ActionType::Noop
+            };
+        }
+        let provisioner = PodProvisioner::new(RunPodClient::new("test-key").unwrap());
+        let executor = PlanExecutor::new(&provisioner, &config.project).with_parallelism(2);
+
+        let mut state = DeploymentState::new("proj", "dev");
+        let result = executor.execute(&plan, &mut state).await.unwrap();
+
+        // web ran alongside db; api waited for db and never started
+        let names: Vec<_> = result
+            .results
+            .iter()
+            .map(|r| r.action.resource_name.as_str())
+            .collect();
+        assert_eq!(names, ["db", "web"]);
+        assert!(!result.results[0].success);
+        assert!(result.results[1].success);
     }
 }
@@ -3,10 +3,25 @@
 //! This module handles the comparison between desired and observed states,
 //! generating execution plans for applying changes.
 
+mod alerts;
+mod autoscale;
+mod cost;
 mod diff;
-mod plan;
 mod executor;
+mod plan;
+mod preflight;
+mod runway;
+mod slo;
 
-pub use diff::{DiffEngine, ResourceDiff, DiffType};
-pub use plan::{DeploymentPlan, PlannedAction, ActionType};
-pub use executor::PlanExecutor;
+pub use alerts::{Alert, AlertInputs, evaluate_alerts};
+pub use autoscale::{Autoscaler, ReplicaLoad, ScaleDecision};
+pub use cost::{CostReport, PodCost};
+pub use diff::{
+    BUILTIN_IGNORED_FIELDS, DISABLED_FIELD, DiffDetail, DiffEngine, DiffType, EndpointDiff,
+    FORCED_REPLACEMENT_FIELD, ResourceDiff, STOPPED_FIELD, TemplateDiff, WarmClaim,
+};
+pub use executor::{ActionResult, ActionTimes, DeadlinePolicy, ExecutionResult, PlanExecutor};
+pub use plan::{ActionType, DeploymentPlan, PlannedAction};
+pub use preflight::PreflightReport;
+pub use runway::{GpuPrices, Runway};
+pub use slo::{MAX_SAMPLE_GAP_SECS, Outage, SloReport};
@@ -5,9 +5,16 @@
 
 use chrono::{DateTime, Utc};
 
-use crate::config::{DeployConfig, GuardrailsConfig, PodConfig};
+use crate::config::{
+    DeployConfig, DrainConfig, EndpointConfig, GuardrailsConfig, PodConfig, ReplaceStrategy,
+    TemplateConfig,
+};
+use crate::error::RunPodError;
+use crate::runpod::{ObservedPod, PodStatus};
+use crate::state::{DeploymentState, EndpointState, TemplateState, VolumeState};
 
-use super::diff::{DiffResult, DiffType};
+use super::diff::{DiffDetail, DiffResult, DiffType, ResourceDiff};
+use super::runway::{GpuPrices, Runway};
 
 /// A complete deployment plan.
 #[derive(Debug)]
@@ -20,10 +27,18 @@ pub struct DeploymentPlan {
     pub actions: Vec<PlannedAction>,
     /// Estimated hourly cost delta (positive = increase).
     pub estimated_cost_delta: Option<f64>,
+    /// Estimated hourly cost of the project's running pods once applied.
+    pub estimated_hourly_cost: Option<f64>,
     /// Whether the plan passes guardrails.
     pub passes_guardrails: bool,
     /// Guardrail violations (if any).
     pub guardrail_violations: Vec<String>,
+    /// Non-blocking warnings about the plan.
+    pub warnings: Vec<String>,
+    /// Account runway at the planned spend rate (if the account was checked).
+    pub runway: Option<Runway>,
+    /// Whether pods were observed on `RunPod` (false for saved-state-only plans).
+    pub refreshed: bool,
 }
 
 /// A single planned action.
@@ -35,6 +50,10 @@ pub struct PlannedAction {
     pub resource_name: String,
     /// Pod configuration (if applicable).
     pub pod_config: Option<PodConfig>,
+    /// Serverless endpoint configuration (for endpoint actions).
+    pub endpoint_config: Option<EndpointConfig>,
+    /// Template configuration (for template saves).
+    pub template_config: Option<TemplateConfig>,
     /// `RunPod` pod ID (if applicable).
     pub runpod_id: Option<String>,
     /// Reason for this action.
@@ -43,6 +62,15 @@ pub struct PlannedAction {
     pub new_hash: Option<String>,
     /// Dependencies (action indices that must complete first).
     pub dependencies: Vec<usize>,
+    /// Field changes applied by this action (on a replacement's create, or a
+    /// pod stopped or resumed in place).
+    pub changes: Vec<DiffDetail>,
+    /// Expected duration in seconds, from timings recorded in state.
+    pub estimated_secs: Option<f64>,
+    /// Engine drain to run before deleting the pod (if configured).
+    pub drain: Option<DrainConfig>,
+    /// Change in hourly cost once the action is applied (if priced).
+    pub hourly_cost_delta: Option<f64>,
 }
 
 /// Types of actions in a plan.
@@ -58,35 +86,41 @@ pub enum ActionType {
     StopPod,
     /// Resume a stopped pod.
     ResumePod,
+    /// Terminate a pod as part of `halldyll destroy`.
+    DestroyPod,
+    /// Create a managed network volume before the pod mounting it.
+    CreateVolume,
+    /// Delete a pod's volume (removed together with the pod).
+    DeleteVolume,
+    /// Create a serverless endpoint.
+    CreateEndpoint,
+    /// Update a serverless endpoint in place.
+    UpdateEndpoint,
+    /// Delete a serverless endpoint.
+    DeleteEndpoint,
+    /// Create a template before the pods and endpoints using it.
+    CreateTemplate,
+    /// Update a template in place.
+    UpdateTemplate,
+    /// Delete a template once nothing uses it.
+    DeleteTemplate,
     /// No operation (for tracking).
     Noop,
 }
 
 impl DeploymentPlan {
     /// Creates a new plan from a diff result.
+    ///
+    /// Actions are grouped as deletes, creates, then replacements, each in
+    /// the diff's name order, so equal inputs always number actions the same.
     #[must_use]
-    pub fn from_diff(
-        diff: &DiffResult,
-        config: &DeployConfig,
-        config_hash: &str,
-    ) -> Self {
+    pub fn from_diff(diff: &DiffResult, config: &DeployConfig, config_hash: &str) -> Self {
         let mut actions = Vec::new();
 
         // Process deletes first
         for resource_diff in &diff.diffs {
             if resource_diff.diff_type == DiffType::Delete {
-                actions.push(PlannedAction {
-                    action_type: ActionType::DeletePod,
-                    resource_name: resource_diff.name.clone(),
-                    pod_config: None,
-                    runpod_id: resource_diff
-                        .details
-                        .first()
-                        .and_then(|d| d.old_value.clone()),
-                    reason: String::from("Pod removed from configuration"),
-                    new_hash: None,
-                    dependencies: vec![],
-                });
+                actions.push(Self::delete_action(resource_diff, config));
             }
         }
 
@@ -95,50 +129,87 @@ impl DeploymentPlan {
         // Process creates
         for resource_diff in &diff.diffs {
             if resource_diff.diff_type == DiffType::Create
-                && let Some(pod_config) = config.pods.iter().find(|p| p.name == resource_diff.name) {
-                    actions.push(PlannedAction {
-                        action_type: ActionType::CreatePod,
-                        resource_name: resource_diff.name.clone(),
-                        pod_config: Some(pod_config.clone()),
-                        runpod_id: None,
-                        reason: String::from("Pod defined in configuration"),
-                        new_hash: resource_diff.new_hash.clone(),
-                        dependencies: vec![], // Creates can run in parallel
-                    });
-                }
+                && let Some(pod_config) = &resource_diff.pod_config
+            {
+                // Creates can run in parallel
+                actions.push(Self::provision_action(
+                    resource_diff,
+                    pod_config,
+                    String::from("Pod defined in configuration"),
+                ));
+                Self::push_warm_stop(&mut actions, resource_diff);
+            }
         }
 
-        // Process updates (recreate strategy)
-        for (i, resource_diff) in diff.diffs.iter().enumerate() {
+        // Process updates (recreate with the pod's replace strategy)
+        let mut warnings = Vec::new();
+        for resource_diff in &diff.diffs {
             if matches!(resource_diff.diff_type, DiffType::Update | DiffType::Drift)
-                && let Some(pod_config) = config.pods.iter().find(|p| p.name == resource_diff.name) {
-                    // Add delete action
-                    let delete_idx = actions.len();
-                    actions.push(PlannedAction {
-                        action_type: ActionType::DeletePod,
-                        resource_name: resource_diff.name.clone(),
-                        pod_config: None,
-                        runpod_id: diff.diffs.get(i).and_then(|d| {
-                            d.details.first().and_then(|det| det.old_value.clone())
-                        }),
-                        reason: format!("Recreating pod due to {}", resource_diff.diff_type),
-                        new_hash: None,
-                        dependencies: vec![], // Can start immediately
-                    });
+                && let Some(pod_config) = &resource_diff.pod_config
+            {
+                // Pods stopped outside halldyll are resumed, disabled ones stopped
+                if resource_diff.needs_resume() || resource_diff.needs_stop() {
+                    actions.push(Self::status_action(resource_diff, pod_config));
+                    continue;
+                }
 
-                    // Add create action (depends on delete)
-                    actions.push(PlannedAction {
-                        action_type: ActionType::CreatePod,
-                        resource_name: resource_diff.name.clone(),
-                        pod_config: Some(pod_config.clone()),
-                        runpod_id: None,
-                        reason: format!("Recreating pod due to {}", resource_diff.diff_type),
-                        new_hash: resource_diff.new_hash.clone(),
-                        dependencies: vec![delete_idx],
-                    });
+                // Warm pods serve no traffic, so they are simply rebuilt
+                if resource_diff.warm {
+                    Self::push_replacement(
+                        &mut actions,
+                        resource_diff,
+                        pod_config,
+                        ReplaceStrategy::DeleteBeforeCreate,
+                    );
+                    Self::push_warm_stop(&mut actions, resource_diff);
+                    continue;
                 }
+
+                // Requested replacements go ahead even with replace_strategy: manual
+                match pod_config.replace_strategy(&config.project) {
+                    ReplaceStrategy::Manual if !resource_diff.is_forced() => {
+                        warnings.push(format!(
+                            "Pod '{}' needs replacement ({}) but uses replace_strategy: manual; \
+                             delete it to apply the new spec",
+                            resource_diff.name, resource_diff.diff_type
+                        ));
+                    }
+                    strategy => {
+                        Self::push_replacement(&mut actions, resource_diff, pod_config, strategy);
+                    }
+                }
+            }
         }
 
+        // Warm pods left running (e.g. by an interrupted apply) are stopped
+        for resource_diff in diff.diffs.iter().filter(|d| {
+            d.warm
+                && d.diff_type == DiffType::NoChange
+                && d.observed_status == Some(PodStatus::Running)
+        }) {
+            Self::push_warm_stop(&mut actions, resource_diff);
+        }
+
+        // Model setup after provisioning runs commands over SSH
+        for action in &actions {
+            if action.action_type == ActionType::CreatePod
+                && let Some(pod_config) = &action.pod_config
+                && !pod_config.models.is_empty()
+                && !pod_config.exposes_ssh()
+            {
+                warnings.push(format!(
+                    "Pod '{}' declares models but exposes no SSH port, so model setup will fail \
+                     after provisioning; add \"22/tcp\" to its ports or use an image with exec support",
+                    action.resource_name
+                ));
+            }
+        }
+
+        Self::push_volume_creates(&mut actions, diff);
+        Self::push_endpoint_actions(&mut actions, diff);
+        Self::push_template_actions(&mut actions, diff);
+        Self::order_by_dependencies(&mut actions, config);
+
         // Check guardrails
         let (passes_guardrails, guardrail_violations) =
             Self::check_guardrails(config, &actions, delete_count);
@@ -148,8 +219,602 @@ impl DeploymentPlan {
             config_hash: config_hash.to_string(),
             actions,
             estimated_cost_delta: None,
+            estimated_hourly_cost: None,
             passes_guardrails,
             guardrail_violations,
+            warnings,
+            runway: None,
+            refreshed: true,
+        }
+    }
+
+    /// Appends the delete and create actions replacing a pod.
+    ///
+    /// The second action depends on the first, so a failure leaves the
+    /// pod in place (create-first) or merely absent (delete-first).
+    fn push_replacement(
+        actions: &mut Vec<PlannedAction>,
+        resource_diff: &ResourceDiff,
+        pod_config: &PodConfig,
+        strategy: ReplaceStrategy,
+    ) {
+        let reason = if resource_diff.is_forced() {
+            format!("Recreating pod as requested with --replace ({strategy})")
+        } else {
+            format!(
+                "Recreating pod due to {} ({strategy})",
+                resource_diff.diff_type
+            )
+        };
+        let delete = PlannedAction {
+            action_type: ActionType::DeletePod,
+            resource_name: resource_diff.name.clone(),
+            pod_config: None,
+            endpoint_config: None,
+            template_config: None,
+            runpod_id: resource_diff.runpod_id.clone(),
+            reason: reason.clone(),
+            new_hash: None,
+            dependencies: vec![],
+            changes: vec![],
+            estimated_secs: None,
+            drain: pod_config.resolved_drain().filter(|_| !resource_diff.warm),
+            hourly_cost_delta: None,
+        };
+        let mut create = Self::provision_action(resource_diff, pod_config, reason);
+        create.changes.clone_from(&resource_diff.details);
+
+        let (first, mut second) = match strategy {
+            ReplaceStrategy::CreateBeforeDelete => (create, delete),
+            ReplaceStrategy::DeleteBeforeCreate | ReplaceStrategy::Manual => (delete, create),
+        };
+        second.dependencies = vec![actions.len()];
+        actions.push(first);
+        actions.push(second);
+    }
+
+    /// Makes actions bringing up a pod depend on those bringing up the pods
+    /// it `depends_on`, and reorders the plan (stably) so they run first.
+    fn order_by_dependencies(actions: &mut Vec<PlannedAction>, config: &DeployConfig) {
+        let provisions = |action: &PlannedAction| {
+            matches!(
+                action.action_type,
+                ActionType::CreatePod | ActionType::ResumePod
+            )
+        };
+        let count = actions.len();
+
+        for i in 0..count {
+            let Some(pod) = config
+                .pod_for_instance(&actions[i].resource_name)
+                .filter(|pod| provisions(&actions[i]) && !pod.depends_on.is_empty())
+            else {
+                continue;
+            };
+            let required: Vec<usize> = (0..count)
+                .filter(|&j| {
+                    j != i
+                        && provisions(&actions[j])
+                        && config
+                            .pod_for_instance(&actions[j].resource_name)
+                            .is_some_and(|dependency| pod.depends_on.contains(&dependency.name))
+                })
+                .collect();
+            for j in required {
+                if !actions[i].dependencies.contains(&j) {
+                    actions[i].dependencies.push(j);
+                }
+            }
+        }
+
+        // Take the first action whose dependencies are placed; validation
+        // rejects cycles, but any left keep their original order
+        let mut placed = vec![false; count];
+        let mut order = Vec::with_capacity(count);
+        while order.len() < count {
+            let ready = (0..count)
+                .find(|&i| !placed[i] && actions[i].dependencies.iter().all(|&d| placed[d]))
+                .or_else(|| (0..count).find(|&i| !placed[i]));
+            let Some(next) = ready else { break };
+            placed[next] = true;
+            order.push(next);
+        }
+        if order
+            .iter()
+            .enumerate()
+            .all(|(position, &index)| position == index)
+        {
+            return;
+        }
+
+        let mut new_index = vec![0; count];
+        for (position, &index) in order.iter().enumerate() {
+            new_index[index] = position;
+        }
+        let mut previous: Vec<Option<PlannedAction>> =
+            std::mem::take(actions).into_iter().map(Some).collect();
+        for index in order {
+            if let Some(mut action) = previous[index].take() {
+                action.dependencies = action.dependencies.iter().map(|&d| new_index[d]).collect();
+                actions.push(action);
+            }
+        }
+    }
+
+    /// Returns the action bringing up a pod: resuming a claimed warm pod, or a create.
+    fn provision_action(
+        resource_diff: &ResourceDiff,
+        pod_config: &PodConfig,
+        reason: String,
+    ) -> PlannedAction {
+        let (action_type, runpod_id, reason) = match &resource_diff.warm_claim {
+            Some(claim) => (
+                ActionType::ResumePod,
+                Some(claim.runpod_id.clone()),
+                format!("{reason}, resuming warm pod '{}'", claim.name),
+            ),
+            None => (ActionType::CreatePod, None, reason),
+        };
+
+        PlannedAction {
+            action_type,
+            resource_name: resource_diff.name.clone(),
+            pod_config: Some(pod_config.clone()),
+            endpoint_config: None,
+            template_config: None,
+            runpod_id,
+            reason,
+            new_hash: resource_diff.new_hash.clone(),
+            dependencies: vec![],
+            changes: vec![],
+            estimated_secs: None,
+            drain: None,
+            hourly_cost_delta: None,
+        }
+    }
+
+    /// Builds the action deleting a pod removed from the configuration.
+    fn delete_action(resource_diff: &ResourceDiff, config: &DeployConfig) -> PlannedAction {
+        PlannedAction {
+            action_type: ActionType::DeletePod,
+            resource_name: resource_diff.name.clone(),
+            pod_config: None,
+            endpoint_config: None,
+            template_config: None,
+            runpod_id: resource_diff.runpod_id.clone(),
+            reason: String::from("Pod removed from configuration"),
+            new_hash: None,
+            dependencies: vec![],
+            changes: vec![],
+            estimated_secs: None,
+            // Replicas removed by a scale-down still belong to a configured pod
+            drain: config
+                .pod_for_instance(&resource_diff.name)
+                .filter(|_| !resource_diff.warm)
+                .and_then(PodConfig::resolved_drain),
+            hourly_cost_delta: None,
+        }
+    }
+
+    /// Builds the action resuming a pod stopped outside halldyll, or stopping a disabled one.
+    fn status_action(resource_diff: &ResourceDiff, pod_config: &PodConfig) -> PlannedAction {
+        let (action_type, reason) = if resource_diff.needs_stop() {
+            (ActionType::StopPod, "Pod is disabled in configuration")
+        } else {
+            (ActionType::ResumePod, "Pod was stopped outside halldyll")
+        };
+
+        PlannedAction {
+            action_type,
+            resource_name: resource_diff.name.clone(),
+            pod_config: Some(pod_config.clone()),
+            endpoint_config: None,
+            template_config: None,
+            runpod_id: resource_diff.runpod_id.clone(),
+            reason: String::from(reason),
+            new_hash: None,
+            dependencies: vec![],
+            changes: resource_diff.details.clone(),
+            estimated_secs: None,
+            drain: None,
+            hourly_cost_delta: None,
+        }
+    }
+
+    /// Appends the creation of each missing network volume, which the create
+    /// of the pod mounting it then depends on.
+    fn push_volume_creates(actions: &mut Vec<PlannedAction>, diff: &DiffResult) {
+        for resource_diff in diff.diffs.iter().filter(|d| !d.volumes.is_empty()) {
+            let Some(create) = actions.iter().position(|a| {
+                a.action_type == ActionType::CreatePod && a.resource_name == resource_diff.name
+            }) else {
+                continue;
+            };
+            for volume in &resource_diff.volumes {
+                let index = actions.len();
+                actions[create].dependencies.push(index);
+                actions.push(PlannedAction {
+                    action_type: ActionType::CreateVolume,
+                    resource_name: format!("{}/{}", resource_diff.name, volume.name),
+                    pod_config: resource_diff.pod_config.clone(),
+                    endpoint_config: None,
+                    template_config: None,
+                    runpod_id: None,
+                    reason: format!(
+                        "{} GB network volume at {} in {}",
+                        volume.size_gb.unwrap_or_default(),
+                        volume.mount,
+                        volume.data_center.as_deref().unwrap_or_default()
+                    ),
+                    new_hash: None,
+                    dependencies: vec![],
+                    changes: vec![],
+                    estimated_secs: None,
+                    drain: None,
+                    hourly_cost_delta: None,
+                });
+            }
+        }
+    }
+
+    /// Appends the actions creating, updating and deleting serverless endpoints.
+    fn push_endpoint_actions(actions: &mut Vec<PlannedAction>, diff: &DiffResult) {
+        for endpoint in &diff.endpoints {
+            let action_type = match endpoint.diff_type {
+                DiffType::Create => ActionType::CreateEndpoint,
+                DiffType::Update | DiffType::Drift => ActionType::UpdateEndpoint,
+                DiffType::Delete => ActionType::DeleteEndpoint,
+                DiffType::NoChange => continue,
+            };
+            actions.push(PlannedAction {
+                action_type,
+                resource_name: endpoint.name.clone(),
+                pod_config: None,
+                endpoint_config: endpoint.config.clone(),
+                template_config: None,
+                runpod_id: endpoint.runpod_id.clone(),
+                reason: endpoint.reason.clone(),
+                new_hash: endpoint.new_hash.clone(),
+                dependencies: vec![],
+                changes: vec![],
+                estimated_secs: None,
+                drain: None,
+                hourly_cost_delta: None,
+            });
+        }
+    }
+
+    /// Appends the actions saving and deleting templates.
+    ///
+    /// Pods and endpoints deployed from a template wait for it to be saved,
+    /// and a removed template is deleted after the pods and endpoints going
+    /// away, which may still use it.
+    fn push_template_actions(actions: &mut Vec<PlannedAction>, diff: &DiffResult) {
+        for template in &diff.templates {
+            let action_type = match template.diff_type {
+                DiffType::Create => ActionType::CreateTemplate,
+                DiffType::Update | DiffType::Drift => ActionType::UpdateTemplate,
+                DiffType::Delete => ActionType::DeleteTemplate,
+                DiffType::NoChange => continue,
+            };
+            let index = actions.len();
+            let uses_template = |action: &PlannedAction| {
+                let used = action
+                    .pod_config
+                    .as_ref()
+                    .and_then(|p| p.template.as_deref())
+                    .or_else(|| {
+                        action
+                            .endpoint_config
+                            .as_ref()
+                            .and_then(|e| e.template.as_deref())
+                    });
+                used == Some(template.name.as_str())
+            };
+            let mut dependencies = vec![];
+            for (i, action) in actions.iter_mut().enumerate() {
+                match action.action_type {
+                    ActionType::CreatePod
+                    | ActionType::CreateEndpoint
+                    | ActionType::UpdateEndpoint
+                        if action_type != ActionType::DeleteTemplate && uses_template(action) =>
+                    {
+                        action.dependencies.push(index);
+                    }
+                    ActionType::DeletePod | ActionType::DeleteEndpoint
+                        if action_type == ActionType::DeleteTemplate =>
+                    {
+                        dependencies.push(i);
+                    }
+                    _ => {}
+                }
+            }
+            actions.push(PlannedAction {
+                action_type,
+                resource_name: template.name.clone(),
+                pod_config: None,
+                endpoint_config: None,
+                template_config: template.config.clone(),
+                runpod_id: template.runpod_id.clone(),
+                reason: template.reason.clone(),
+                new_hash: template.new_hash.clone(),
+                dependencies,
+                changes: vec![],
+                estimated_secs: None,
+                drain: None,
+                hourly_cost_delta: None,
+            });
+        }
+    }
+
+    /// Appends the stop keeping a warm pool clone idle, after the action just pushed.
+    fn push_warm_stop(actions: &mut Vec<PlannedAction>, resource_diff: &ResourceDiff) {
+        if !resource_diff.warm {
+            return;
+        }
+
+        let dependencies = match resource_diff.diff_type {
+            DiffType::NoChange => vec![],
+            _ => vec![actions.len() - 1],
+        };
+        actions.push(PlannedAction {
+            action_type: ActionType::StopPod,
+            resource_name: resource_diff.name.clone(),
+            pod_config: None,
+            endpoint_config: None,
+            template_config: None,
+            runpod_id: resource_diff
+                .runpod_id
+                .clone()
+                .filter(|_| dependencies.is_empty()),
+            reason: String::from("Keeping warm pool pod stopped"),
+            new_hash: None,
+            dependencies,
+            changes: vec![],
+            estimated_secs: None,
+            drain: None,
+            hourly_cost_delta: None,
+        });
+    }
+
+    /// Creates the plan tearing down every observed pod of the project.
+    ///
+    /// Each pod with a volume gets a [`ActionType::DeleteVolume`] after its
+    /// [`ActionType::DestroyPod`], so the plan shows the data that goes
+    /// with it. Load balancer manifests left without backends and the state
+    /// history that is deleted are listed as warnings.
+    ///
+    /// Serverless endpoints tracked in state are deleted as well.
+    ///
+    /// Network volumes tracked in state are deleted too, unless
+    /// `keep_volumes` is set: then they are kept, detached, for the next
+    /// apply to re-attach.
+    #[must_use]
+    pub fn destroy(
+        config: &DeployConfig,
+        observed: &[ObservedPod],
+        state: Option<&DeploymentState>,
+        keep_volumes: bool,
+        config_hash: &str,
+    ) -> Self {
+        let mut actions = Vec::new();
+        let mut balanced = Vec::new();
+        let mut warnings = Vec::new();
+        let network_volumes: Vec<&VolumeState> =
+            state.iter().flat_map(|s| s.volumes.values()).collect();
+
+        for pod in observed {
+            let name = pod.pod_name.clone().unwrap_or_else(|| pod.name.clone());
+            let pod_config = config.pod_for_instance(&name);
+            if let Some(lb_pod) = pod_config.filter(|p| p.load_balancer.is_some())
+                && !balanced.contains(&lb_pod.name)
+            {
+                balanced.push(lb_pod.name.clone());
+            }
+
+            actions.push(Self::destroy_pod(pod, &name));
+
+            if keep_volumes
+                && let Some(lost) = pod_config.iter().flat_map(|p| &p.volumes).find(|v| {
+                    v.persistent
+                        && v.network_volume_id.is_none()
+                        && !network_volumes.iter().any(|n| n.name == v.name)
+                })
+            {
+                warnings.push(format!(
+                    "Volume '{}' of '{name}' is a pod volume and is deleted with the pod; only network volumes can be kept",
+                    lost.name
+                ));
+            }
+
+            if pod.volume_gb > 0 {
+                actions.push(Self::delete_pod_volume(
+                    pod,
+                    name,
+                    pod_config,
+                    actions.len() - 1,
+                ));
+            }
+        }
+
+        for volume in &network_volumes {
+            if keep_volumes {
+                warnings.push(format!(
+                    "Network volume '{}' ({}) will be kept detached and re-attached by the next apply",
+                    volume.name, volume.runpod_id
+                ));
+                continue;
+            }
+            actions.push(Self::delete_network_volume(volume, &actions));
+        }
+
+        let mut endpoints: Vec<_> = state.iter().flat_map(|s| s.endpoints.values()).collect();
+        endpoints.sort_by(|a, b| a.name.cmp(&b.name));
+        actions.extend(endpoints.into_iter().map(Self::delete_endpoint));
+
+        let mut templates: Vec<_> = state.iter().flat_map(|s| s.templates.values()).collect();
+        templates.sort_by(|a, b| a.name.cmp(&b.name));
+        let in_use: Vec<usize> = (0..actions.len()).collect();
+        actions.extend(
+            templates
+                .into_iter()
+                .map(|t| Self::delete_template(t, &in_use)),
+        );
+
+        warnings.extend(balanced.iter().map(|pod| {
+            format!("The load balancer manifest of '{pod}' will be left without backends")
+        }));
+        if keep_volumes && !network_volumes.is_empty() {
+            warnings.push(String::from(
+                "The deployment state will be kept to track the detached volumes",
+            ));
+        } else if let Some(state) = state {
+            warnings.push(format!(
+                "The deployment state will be deleted, with its {} history entries{}",
+                state.history.len(),
+                if state.annotations.is_empty() {
+                    ""
+                } else {
+                    " and pod annotations"
+                }
+            ));
+        }
+
+        Self {
+            created_at: Utc::now(),
+            config_hash: config_hash.to_string(),
+            actions,
+            estimated_cost_delta: None,
+            estimated_hourly_cost: None,
+            passes_guardrails: true,
+            guardrail_violations: vec![],
+            warnings,
+            runway: None,
+            refreshed: true,
+        }
+    }
+
+    /// Plans destroying an observed pod.
+    fn destroy_pod(pod: &ObservedPod, name: &str) -> PlannedAction {
+        PlannedAction {
+            action_type: ActionType::DestroyPod,
+            resource_name: name.to_string(),
+            pod_config: None,
+            endpoint_config: None,
+            template_config: None,
+            runpod_id: Some(pod.id.clone()),
+            reason: format!(
+                "{} {}x {}",
+                pod.status,
+                pod.gpu_count,
+                pod.gpu_type.as_deref().unwrap_or("unknown GPU")
+            ),
+            new_hash: None,
+            dependencies: vec![],
+            changes: vec![],
+            estimated_secs: None,
+            drain: None,
+            hourly_cost_delta: None,
+        }
+    }
+
+    /// Plans deleting a pod volume, which goes with its pod.
+    fn delete_pod_volume(
+        pod: &ObservedPod,
+        name: String,
+        pod_config: Option<&PodConfig>,
+        destroy_index: usize,
+    ) -> PlannedAction {
+        let mounts: Vec<&str> = pod_config
+            .iter()
+            .flat_map(|p| p.volumes.iter().map(|v| v.mount.as_str()))
+            .collect();
+        let reason = if mounts.is_empty() {
+            format!("{} GB volume", pod.volume_gb)
+        } else {
+            format!("{} GB volume at {}", pod.volume_gb, mounts.join(", "))
+        };
+
+        PlannedAction {
+            action_type: ActionType::DeleteVolume,
+            resource_name: name,
+            pod_config: None,
+            endpoint_config: None,
+            template_config: None,
+            runpod_id: Some(pod.id.clone()),
+            reason,
+            new_hash: None,
+            dependencies: vec![destroy_index],
+            changes: vec![],
+            estimated_secs: None,
+            drain: None,
+            hourly_cost_delta: None,
+        }
+    }
+
+    /// Plans deleting a network volume after the pod mounting it is destroyed.
+    fn delete_network_volume(volume: &VolumeState, actions: &[PlannedAction]) -> PlannedAction {
+        let dependencies = actions
+            .iter()
+            .position(|a| a.action_type == ActionType::DestroyPod && a.resource_name == volume.pod)
+            .into_iter()
+            .collect();
+
+        PlannedAction {
+            action_type: ActionType::DeleteVolume,
+            resource_name: volume.name.clone(),
+            pod_config: None,
+            endpoint_config: None,
+            template_config: None,
+            runpod_id: Some(volume.runpod_id.clone()),
+            reason: format!(
+                "{} GB network volume at {}",
+                volume.size_gb, volume.mount_path
+            ),
+            new_hash: None,
+            dependencies,
+            changes: vec![],
+            estimated_secs: None,
+            drain: None,
+            hourly_cost_delta: None,
+        }
+    }
+
+    /// Plans deleting a template tracked in state, after the actions in `in_use`.
+    fn delete_template(template: &TemplateState, in_use: &[usize]) -> PlannedAction {
+        PlannedAction {
+            action_type: ActionType::DeleteTemplate,
+            resource_name: template.name.clone(),
+            pod_config: None,
+            endpoint_config: None,
+            template_config: None,
+            runpod_id: Some(template.runpod_id.clone()),
+            reason: String::from("Template"),
+            new_hash: None,
+            dependencies: in_use.to_vec(),
+            changes: vec![],
+            estimated_secs: None,
+            drain: None,
+            hourly_cost_delta: None,
+        }
+    }
+
+    /// Plans deleting a serverless endpoint tracked in state.
+    fn delete_endpoint(endpoint: &EndpointState) -> PlannedAction {
+        PlannedAction {
+            action_type: ActionType::DeleteEndpoint,
+            resource_name: endpoint.name.clone(),
+            pod_config: None,
+            endpoint_config: None,
+            template_config: None,
+            runpod_id: Some(endpoint.runpod_id.clone()),
+            reason: String::from("Serverless endpoint"),
+            new_hash: None,
+            dependencies: vec![],
+            changes: vec![],
+            estimated_secs: None,
+            drain: None,
+            hourly_cost_delta: None,
         }
     }
 
@@ -161,8 +826,12 @@ impl DeploymentPlan {
             config_hash: config_hash.to_string(),
             actions: vec![],
             estimated_cost_delta: Some(0.0),
+            estimated_hourly_cost: None,
             passes_guardrails: true,
             guardrail_violations: vec![],
+            warnings: vec![],
+            runway: None,
+            refreshed: true,
         }
     }
 
@@ -189,27 +858,230 @@ impl DeploymentPlan {
                     ));
                 }
             }
-
-            // Additional guardrail checks can be added here
-            Self::check_cost_guardrails(guardrails, actions, &violations);
         }
 
         (violations.is_empty(), violations)
     }
 
-    /// Checks cost-related guardrails.
-    const fn check_cost_guardrails(
-        guardrails: &GuardrailsConfig,
-        _actions: &[PlannedAction],
-        violations: &Vec<String>,
-    ) {
-        // Placeholder for cost estimation
-        // In a real implementation, this would query GPU prices
-        if guardrails.max_hourly_cost.is_some() {
-            // Cost checking would go here
-            // For now, we don't have pricing data
-            let _ = violations; // Suppress unused warning
+    /// Adds warnings derived from the recorded deployment state.
+    ///
+    /// If the last apply failed on a `RunPod` account limit and this plan
+    /// starts pods again, it will most likely fail the same way. Actions on
+    /// pods under maintenance are flagged as well.
+    #[must_use]
+    pub fn with_state_warnings(mut self, state: Option<&DeploymentState>) -> Self {
+        if self.starts_pods()
+            && let Some(last) = state.and_then(|s| s.history.last())
+            && !last.success
+            && let Some(error) = &last.error
+            && RunPodError::from_api_response(0, error.as_str()).is_quota()
+        {
+            self.warnings.push(format!(
+                "The last apply ({}) hit a RunPod account limit: {error}. \
+                 Add funds or raise the limit before applying.",
+                last.timestamp.format("%Y-%m-%d %H:%M")
+            ));
+        }
+
+        if let Some(state) = state {
+            for action in &self.actions {
+                if let Some(maintenance) = state.maintenance_for(&action.resource_name) {
+                    let warning = format!(
+                        "{} is under maintenance: {maintenance}. Applying will change it anyway.",
+                        action.resource_name
+                    );
+                    if !self.warnings.contains(&warning) {
+                        self.warnings.push(warning);
+                    }
+                }
+            }
         }
+
+        self
+    }
+
+    /// Marks the plan as computed from saved state without observing `RunPod`.
+    #[must_use]
+    pub const fn without_refresh(mut self) -> Self {
+        self.refreshed = false;
+        self
+    }
+
+    /// Estimates each action's duration from timings recorded in state.
+    #[must_use]
+    pub fn with_duration_estimates(mut self, state: Option<&DeploymentState>) -> Self {
+        if let Some(state) = state {
+            for action in &mut self.actions {
+                action.estimated_secs = state
+                    .timing(&action.action_type.to_string(), &action.resource_name)
+                    .map(|t| t.mean_secs);
+            }
+        }
+        self
+    }
+
+    /// Returns the estimated wall-clock duration of the plan in seconds.
+    ///
+    /// Actions run one after another, so this is the sum over actions with
+    /// recorded timings; `None` if no action has any history.
+    #[must_use]
+    pub fn estimated_duration_secs(&self) -> Option<f64> {
+        self.actions
+            .iter()
+            .filter_map(|a| a.estimated_secs)
+            .reduce(|total, secs| total + secs)
+    }
+
+    /// Returns the number of actions without a duration estimate.
+    #[must_use]
+    pub fn unestimated_count(&self) -> usize {
+        self.actions
+            .iter()
+            .filter(|a| a.estimated_secs.is_none())
+            .count()
+    }
+
+    /// Prices the plan's actions and enforces `guardrails.max_hourly_cost`.
+    ///
+    /// Each action gets the change in hourly cost it causes, and the
+    /// project's cost after the plan is what its running pods cost now plus
+    /// those changes. GPUs without a known price are counted as free. A warm
+    /// pool pod stopped right after it is provisioned never runs, so its stop
+    /// takes back what its create added.
+    #[must_use]
+    pub fn with_costs(
+        mut self,
+        prices: &GpuPrices,
+        observed: &[ObservedPod],
+        guardrails: Option<&GuardrailsConfig>,
+    ) -> Self {
+        let is_running = |pod: &ObservedPod| {
+            matches!(
+                pod.status,
+                PodStatus::Running | PodStatus::Starting | PodStatus::Creating
+            )
+        };
+        let observed_price =
+            |pod: &ObservedPod| prices.hourly(pod.gpu_type.as_deref()?, pod.gpu_count);
+
+        let mut unpriced = Vec::new();
+        let mut provisioned: Vec<(String, Option<f64>)> = Vec::new();
+        for action in &mut self.actions {
+            let target = observed.iter().find(|pod| match &action.runpod_id {
+                Some(id) => &pod.id == id,
+                None => pod.pod_name.as_deref() == Some(action.resource_name.as_str()),
+            });
+            // Spot pods cost at most their bid
+            let configured = action.pod_config.as_ref().map(|pod| {
+                pod.pricing.and_then(|p| p.spot_bid()).map_or_else(
+                    || prices.pod_hourly(&pod.gpu),
+                    |bid| Some(bid * f64::from(pod.gpu.count)),
+                )
+            });
+
+            let refill = provisioned.iter().find(|(name, _)| {
+                action.action_type == ActionType::StopPod && *name == action.resource_name
+            });
+
+            action.hourly_cost_delta = match action.action_type {
+                ActionType::CreatePod | ActionType::UpdatePod => configured.flatten(),
+                ActionType::ResumePod => {
+                    configured.unwrap_or_else(|| target.and_then(observed_price))
+                }
+                ActionType::StopPod if refill.is_some() => {
+                    refill.and_then(|(_, delta)| *delta).map(|d| -d)
+                }
+                ActionType::DeletePod | ActionType::StopPod | ActionType::DestroyPod => {
+                    match target {
+                        Some(pod) if is_running(pod) => observed_price(pod).map(|price| -price),
+                        _ => Some(0.0),
+                    }
+                }
+                // Idle endpoints cost nothing; workers kept warm are not priced
+                ActionType::CreateEndpoint | ActionType::UpdateEndpoint => action
+                    .endpoint_config
+                    .as_ref()
+                    .is_none_or(|e| e.workers.min == 0)
+                    .then_some(0.0),
+                ActionType::CreateVolume
+                | ActionType::DeleteVolume
+                | ActionType::DeleteEndpoint
+                | ActionType::CreateTemplate
+                | ActionType::UpdateTemplate
+                | ActionType::DeleteTemplate
+                | ActionType::Noop => Some(0.0),
+            };
+            if matches!(
+                action.action_type,
+                ActionType::CreatePod | ActionType::UpdatePod
+            ) {
+                provisioned.push((action.resource_name.clone(), action.hourly_cost_delta));
+            }
+            if action.hourly_cost_delta.is_none() && !unpriced.contains(&action.resource_name) {
+                unpriced.push(action.resource_name.clone());
+            }
+        }
+
+        let current: f64 = observed
+            .iter()
+            .filter(|pod| is_running(pod))
+            .filter_map(observed_price)
+            .sum();
+        let delta: f64 = self
+            .actions
+            .iter()
+            .filter_map(|a| a.hourly_cost_delta)
+            .sum();
+        let hourly_cost = (current + delta).max(0.0);
+
+        if let Some(limit) = guardrails.and_then(|g| g.max_hourly_cost)
+            && hourly_cost > limit
+            && self.starts_pods()
+        {
+            self.guardrail_violations.push(format!(
+                "Plan costs ${hourly_cost:.2}/hr once applied but max_hourly_cost is ${limit:.2}/hr"
+            ));
+            self.passes_guardrails = false;
+        }
+        if !unpriced.is_empty() {
+            self.warnings.push(format!(
+                "No GPU price known for {}; not counted in the cost estimate",
+                unpriced.join(", ")
+            ));
+        }
+
+        self.estimated_cost_delta = Some(delta);
+        self.estimated_hourly_cost = Some(hourly_cost);
+        self
+    }
+
+    /// Attaches the account runway and enforces `guardrails.min_runway_hours`.
+    #[must_use]
+    pub fn with_runway(mut self, runway: Runway, guardrails: Option<&GuardrailsConfig>) -> Self {
+        if let (Some(min_hours), Some(hours)) =
+            (guardrails.and_then(|g| g.min_runway_hours), runway.hours())
+            && hours < min_hours
+            && self.starts_pods()
+        {
+            self.guardrail_violations.push(format!(
+                "Balance ${:.2} covers {hours:.1}h at ${:.2}/hr, below min_runway_hours {min_hours}",
+                runway.balance, runway.planned_spend_per_hour
+            ));
+            self.passes_guardrails = false;
+        }
+
+        self.runway = Some(runway);
+        self
+    }
+
+    /// Returns true if the plan creates, replaces, or resumes pods.
+    fn starts_pods(&self) -> bool {
+        self.actions.iter().any(|a| {
+            matches!(
+                a.action_type,
+                ActionType::CreatePod | ActionType::UpdatePod | ActionType::ResumePod
+            )
+        })
     }
 
     /// Returns true if the plan is empty (no changes).
@@ -229,7 +1101,15 @@ impl DeploymentPlan {
     pub fn create_count(&self) -> usize {
         self.actions
             .iter()
-            .filter(|a| a.action_type == ActionType::CreatePod)
+            .filter(|a| {
+                matches!(
+                    a.action_type,
+                    ActionType::CreatePod
+                        | ActionType::CreateVolume
+                        | ActionType::CreateEndpoint
+                        | ActionType::CreateTemplate
+                )
+            })
             .count()
     }
 
@@ -238,7 +1118,16 @@ impl DeploymentPlan {
     pub fn delete_count(&self) -> usize {
         self.actions
             .iter()
-            .filter(|a| a.action_type == ActionType::DeletePod)
+            .filter(|a| {
+                matches!(
+                    a.action_type,
+                    ActionType::DeletePod
+                        | ActionType::DestroyPod
+                        | ActionType::DeleteVolume
+                        | ActionType::DeleteEndpoint
+                        | ActionType::DeleteTemplate
+                )
+            })
             .count()
     }
 
@@ -272,6 +1161,15 @@ impl PlannedAction {
             ActionType::DeletePod => format!("Delete pod '{}'", self.resource_name),
             ActionType::StopPod => format!("Stop pod '{}'", self.resource_name),
             ActionType::ResumePod => format!("Resume pod '{}'", self.resource_name),
+            ActionType::DestroyPod => format!("Destroy pod '{}'", self.resource_name),
+            ActionType::CreateVolume => format!("Create volume '{}'", self.resource_name),
+            ActionType::DeleteVolume => format!("Delete volume of '{}'", self.resource_name),
+            ActionType::CreateEndpoint => format!("Create endpoint '{}'", self.resource_name),
+            ActionType::UpdateEndpoint => format!("Update endpoint '{}'", self.resource_name),
+            ActionType::DeleteEndpoint => format!("Delete endpoint '{}'", self.resource_name),
+            ActionType::CreateTemplate => format!("Create template '{}'", self.resource_name),
+            ActionType::UpdateTemplate => format!("Update template '{}'", self.resource_name),
+            ActionType::DeleteTemplate => format!("Delete template '{}'", self.resource_name),
             ActionType::Noop => format!("No change for '{}'", self.resource_name),
         }
     }
@@ -285,6 +1183,15 @@ impl std::fmt::Display for ActionType {
             Self::DeletePod => "delete",
             Self::StopPod => "stop",
             Self::ResumePod => "resume",
+            Self::DestroyPod => "destroy",
+            Self::CreateVolume => "create-volume",
+            Self::DeleteVolume => "delete-volume",
+            Self::CreateEndpoint => "create-endpoint",
+            Self::UpdateEndpoint => "update-endpoint",
+            Self::DeleteEndpoint => "delete-endpoint",
+            Self::CreateTemplate => "create-template",
+            Self::UpdateTemplate => "update-template",
+            Self::DeleteTemplate => "delete-template",
             Self::Noop => "noop",
         };
         write!(f, "{s}")
@@ -308,6 +1215,12 @@ impl std::fmt::Display for DeploymentPlan {
         }
 
         writeln!(f, "Deployment Plan ({} actions):", self.actions.len())?;
+        if let Some(cost) = self.estimated_hourly_cost {
+            writeln!(f, "  Estimated cost: ${cost:.2}/hr")?;
+        }
+        if let Some(runway) = &self.runway {
+            writeln!(f, "  {runway}")?;
+        }
         for (i, action) in self.actions.iter().enumerate() {
             writeln!(f, "  {i}. {action}")?;
         }
@@ -319,6 +1232,641 @@ impl std::fmt::Display for DeploymentPlan {
             }
         }
 
+        if !self.warnings.is_empty() {
+            writeln!(f, "\nWarnings:")?;
+            for warning in &self.warnings {
+                writeln!(f, "  - {warning}")?;
+            }
+        }
+
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::planner::{DISABLED_FIELD, DiffEngine, STOPPED_FIELD};
+    use crate::runpod::{ObservedPod, PodStatus};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_replace_strategy_ordering() {
+        let yaml = r#"
+project:
+  name: proj
+  replace_strategy: create_before_delete
+state:
+  backend: local
+pods:
+  - name: llm
+    gpu:
+      type: "NVIDIA A40"
+    ports: ["8000/http"]
+    drain:
+      timeout_secs: 30
+    runtime:
+      image: "vllm/vllm-openai:v0.6.0"
+  - name: batch
+    replace_strategy: manual
+    gpu:
+      type: "NVIDIA A40"
+    runtime:
+      image: "vllm/vllm-openai:v0.6.0"
+"#;
+        let config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+        let observed: Vec<ObservedPod> = ["llm", "batch"]
+            .iter()
+            .map(|name| ObservedPod {
+                id: format!("id-{name}"),
+                name: format!("proj-dev-{name}"),
+                project: Some(String::from("proj")),
+                environment: Some(String::from("dev")),
+                pod_name: Some((*name).to_string()),
+                spec_hash: Some(String::from("stale")),
+                status: PodStatus::Running,
+                gpu_type: None,
+                gpu_count: 1,
+                image: String::from("vllm/vllm-openai:v0.5.0"),
+                endpoints: HashMap::new(),
+                gpu_utilization: None,
+                tags: HashMap::new(),
+                volume_gb: 0,
+                uptime_secs: 0,
+                env: None,
+            })
+            .collect();
+
+        let diff = DiffEngine::new().compute_diff(&config, None, &observed);
+        let plan = DeploymentPlan::from_diff(&diff, &config, "hash");
+
+        let kinds: Vec<_> = plan.actions.iter().map(|a| a.action_type).collect();
+        assert_eq!(kinds, [ActionType::CreatePod, ActionType::DeletePod]);
+        assert_eq!(plan.actions[1].runpod_id.as_deref(), Some("id-llm"));
+        assert_eq!(plan.actions[1].dependencies, [0]);
+        let change = &plan.actions[0].changes[0];
+        assert_eq!(change.field, "image");
+        assert!(change.forces_replacement);
+        assert!(change.to_string().ends_with("# forces replacement"));
+        assert!(plan.actions[1].changes.is_empty());
+        assert_eq!(
+            plan.actions[1].drain.as_ref().and_then(|d| d.port),
+            Some(8000)
+        );
+        assert!(plan.actions[0].drain.is_none());
+        assert!(plan.warnings.iter().any(|w| w.contains("'batch'")));
+    }
+
+    #[test]
+    fn test_plan_from_recorded_state() {
+        let yaml = "project:\n  name: proj\nstate:\n  backend: local\npods:\n  - name: llm\n    gpu:\n      type: A40\n    runtime:\n      image: img\n";
+        let config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+        let hash = crate::config::ConfigHasher::new().hash_pod(&config.pods[0]);
+
+        let mut state = DeploymentState::new("proj", "dev");
+        state.set_pod(crate::state::PodState::new("llm", "id-llm", &hash));
+
+        let observed = DiffEngine::recorded_pods(&state);
+        let diff = DiffEngine::new().compute_diff(&config, Some(&state), &observed);
+        let plan = DeploymentPlan::from_diff(&diff, &config, "hash").without_refresh();
+
+        assert!(plan.is_empty());
+        assert!(!plan.refreshed);
+    }
+
+    #[test]
+    fn test_forced_replacement() {
+        let yaml = "project:\n  name: proj\n  replace_strategy: manual\nstate:\n  backend: local\npods:\n  - name: llm\n    replicas: 2\n    gpu:\n      type: A40\n    runtime:\n      image: img\n";
+        let config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+        let mut state = DeploymentState::new("proj", "dev");
+        for pod in DiffEngine::desired_instances(&config, None) {
+            let hash = crate::config::ConfigHasher::new().hash_pod(&pod);
+            state.set_pod(crate::state::PodState::new(
+                &pod.name,
+                &format!("id-{}", pod.name),
+                &hash,
+            ));
+        }
+
+        let observed = DiffEngine::recorded_pods(&state);
+        let mut diff = DiffEngine::new().compute_diff(&config, Some(&state), &observed);
+        assert!(!diff.has_changes());
+        assert_eq!(
+            diff.force_replace(&[String::from("llm-1"), String::from("api")]),
+            ["api"]
+        );
+
+        // Replaced even though the spec matches and the strategy is manual
+        let plan = DeploymentPlan::from_diff(&diff, &config, "hash");
+        let kinds: Vec<_> = plan.actions.iter().map(|a| a.action_type).collect();
+        assert_eq!(kinds, [ActionType::DeletePod, ActionType::CreatePod]);
+        assert_eq!(plan.actions[0].runpod_id.as_deref(), Some("id-llm-1"));
+        assert!(plan.actions[1].reason.contains("--replace"));
+        assert!(plan.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_changes_marked_in_place_or_replacing() {
+        let yaml = "project:\n  name: proj\nstate:\n  backend: local\npods:\n  - name: api\n    gpu:\n      type: A40\n    runtime:\n      image: img\n  - name: batch\n    enabled: false\n    gpu:\n      type: A40\n    runtime:\n      image: img\n  - name: web\n    gpu:\n      type: A40\n    runtime:\n      image: img\n";
+        let mut config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+        let mut state = DeploymentState::new("proj", "dev");
+        for pod in DiffEngine::desired_instances(&config, None) {
+            let hash = crate::config::ConfigHasher::new().hash_pod(&pod);
+            let mut pod_state =
+                crate::state::PodState::new(&pod.name, &format!("id-{}", pod.name), &hash);
+            pod_state.image = String::from("img");
+            state.set_pod(pod_state);
+        }
+        let mut observed = DiffEngine::recorded_pods(&state);
+        // api was stopped in the dashboard; web gets a new image
+        observed[0].status = PodStatus::Exited;
+        config.pods[2].runtime.image = String::from("img:2");
+
+        let diff = DiffEngine::new().compute_diff(&config, Some(&state), &observed);
+        let plan = DeploymentPlan::from_diff(&diff, &config, "hash");
+        let action = |kind: ActionType, name: &str| {
+            plan.actions
+                .iter()
+                .find(|a| a.action_type == kind && a.resource_name == name)
+                .unwrap()
+        };
+
+        // Stopping a disabled pod and resuming a stopped one happen in place
+        for (kind, name, field) in [
+            (ActionType::ResumePod, "api", STOPPED_FIELD),
+            (ActionType::StopPod, "batch", DISABLED_FIELD),
+        ] {
+            let changes = &action(kind, name).changes;
+            assert_eq!(changes.len(), 1);
+            assert_eq!(changes[0].field, field);
+            assert!(!changes[0].forces_replacement);
+            assert!(!changes[0].to_string().contains("forces replacement"));
+        }
+
+        // A new image needs a new pod
+        let create = action(ActionType::CreatePod, "web");
+        assert_eq!(create.changes[0].field, "image");
+        assert!(create.changes.iter().all(|c| c.forces_replacement));
+        assert!(
+            create.changes[0]
+                .to_string()
+                .ends_with("# forces replacement")
+        );
+        assert!(action(ActionType::DeletePod, "web").changes.is_empty());
+    }
+
+    #[test]
+    fn test_network_volumes_created_before_pods() {
+        let yaml = "project:\n  name: proj\nstate:\n  backend: local\npods:\n  - name: llm\n    replicas: 2\n    volumes:\n      - name: data\n        mount: /data\n        size_gb: 50\n        data_center: EU-RO-1\n    gpu:\n      type: A40\n    runtime:\n      image: img\n";
+        let config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+
+        let diff = DiffEngine::new().compute_diff(&config, None, &[]);
+        let plan = DeploymentPlan::from_diff(&diff, &config, "hash");
+        let actions: Vec<_> = plan
+            .actions
+            .iter()
+            .map(|a| (a.action_type, a.resource_name.as_str()))
+            .collect();
+        assert_eq!(
+            actions,
+            [
+                (ActionType::CreateVolume, "llm/data"),
+                (ActionType::CreatePod, "llm"),
+                (ActionType::CreateVolume, "llm-1/data"),
+                (ActionType::CreatePod, "llm-1"),
+            ]
+        );
+        assert_eq!(plan.actions[1].dependencies, [0]);
+        assert_eq!(
+            plan.actions[2].reason,
+            "50 GB network volume at /data in EU-RO-1"
+        );
+
+        // A volume left detached by the previous pod is re-attached, not created again
+        let mut state = DeploymentState::new("proj", "dev");
+        state.set_volume(VolumeState {
+            name: String::from("data"),
+            runpod_id: String::from("vol-0"),
+            mount_path: String::from("/data"),
+            size_gb: 50,
+            created_at: Utc::now(),
+            pod: String::from("llm"),
+            detached: true,
+            data_center: Some(String::from("EU-RO-1")),
+            managed: true,
+        });
+        assert!(state.get_volume("llm/data").is_some());
+        let recorded = DiffEngine::new().compute_diff(&config, Some(&state), &[]);
+        let replan = DeploymentPlan::from_diff(&recorded, &config, "hash");
+        let volumes: Vec<_> = replan
+            .actions
+            .iter()
+            .filter(|a| a.action_type == ActionType::CreateVolume)
+            .map(|a| a.resource_name.as_str())
+            .collect();
+        assert_eq!(volumes, ["llm-1/data"]);
+    }
+
+    #[test]
+    fn test_duration_estimates_from_history() {
+        let yaml = "project:\n  name: proj\nstate:\n  backend: local\npods:\n  - name: a\n    gpu:\n      type: A40\n    runtime:\n      image: img\n  - name: b\n    gpu:\n      type: A40\n    runtime:\n      image: img\n";
+        let config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+
+        let mut state = DeploymentState::new("proj", "dev");
+        state.record_timing("create", "a", 100.0);
+        state.record_timing("create", "a", 200.0);
+        state.record_timing("create", "other", 30.0);
+        let recorded = state.timing("create", "a").unwrap();
+        assert_eq!(
+            (recorded.min_secs, recorded.max_secs, recorded.last_secs),
+            (100.0, 200.0, 200.0)
+        );
+
+        let diff = DiffEngine::new().compute_diff(&config, Some(&state), &[]);
+        let plan =
+            DeploymentPlan::from_diff(&diff, &config, "hash").with_duration_estimates(Some(&state));
+
+        // "a" uses its own history, "b" the average over all creates
+        assert_eq!(plan.actions[0].estimated_secs, Some(150.0));
+        assert_eq!(plan.actions[1].estimated_secs, Some(110.0));
+        assert_eq!(plan.estimated_duration_secs(), Some(260.0));
+        assert_eq!(plan.unestimated_count(), 0);
+    }
+
+    #[test]
+    fn test_plan_order_is_deterministic() {
+        let yaml = "project:\n  name: proj\nstate:\n  backend: local\npods:\n  - name: web\n    gpu:\n      type: A40\n    runtime:\n      image: img\n";
+        let config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+        let orphan = |name: &str| ObservedPod {
+            id: format!("id-{name}"),
+            name: format!("proj-dev-{name}"),
+            project: Some(String::from("proj")),
+            environment: Some(String::from("dev")),
+            pod_name: Some(name.to_string()),
+            spec_hash: None,
+            status: PodStatus::Running,
+            gpu_type: None,
+            gpu_count: 1,
+            image: String::from("img"),
+            endpoints: HashMap::new(),
+            gpu_utilization: None,
+            tags: HashMap::new(),
+            volume_gb: 0,
+            uptime_secs: 0,
+            env: None,
+        };
+
+        let names = |observed: &[ObservedPod]| {
+            let diff = DiffEngine::new().compute_diff(&config, None, observed);
+            DeploymentPlan::from_diff(&diff, &config, "hash")
+                .actions
+                .into_iter()
+                .map(|a| a.resource_name)
+                .collect::<Vec<_>>()
+        };
+
+        let forward = names(&[orphan("b"), orphan("a")]);
+        assert_eq!(forward, names(&[orphan("a"), orphan("b")]));
+        assert_eq!(forward, ["a", "b", "web"]);
+    }
+
+    #[test]
+    fn test_destroy_plan_lists_volumes_and_state() {
+        let yaml = "project:\n  name: proj\nstate:\n  backend: local\npods:\n  - name: llm\n    volumes:\n      - name: data\n        mount: /data\n    gpu:\n      type: A40\n    runtime:\n      image: img\n";
+        let config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+        let pod = |name: &str, volume_gb: u32| ObservedPod {
+            id: format!("id-{name}"),
+            name: format!("proj-dev-{name}"),
+            project: Some(String::from("proj")),
+            environment: Some(String::from("dev")),
+            pod_name: Some(name.to_string()),
+            spec_hash: None,
+            status: PodStatus::Running,
+            gpu_type: Some(String::from("A40")),
+            gpu_count: 1,
+            image: String::from("img"),
+            endpoints: HashMap::new(),
+            gpu_utilization: None,
+            tags: HashMap::new(),
+            volume_gb,
+            uptime_secs: 0,
+            env: None,
+        };
+        let state = DeploymentState::new("proj", "dev");
+
+        let plan = DeploymentPlan::destroy(
+            &config,
+            &[pod("llm", 50), pod("old", 0)],
+            Some(&state),
+            false,
+            "hash",
+        );
+
+        let kinds: Vec<_> = plan.actions.iter().map(|a| a.action_type).collect();
+        assert_eq!(
+            kinds,
+            [
+                ActionType::DestroyPod,
+                ActionType::DeleteVolume,
+                ActionType::DestroyPod
+            ]
+        );
+        assert_eq!(plan.actions[1].dependencies, [0]);
+        assert_eq!(plan.actions[1].reason, "50 GB volume at /data");
+        assert_eq!(plan.delete_count(), 3);
+        assert!(plan.warnings.iter().any(|w| w.contains("history entries")));
+    }
+
+    #[test]
+    fn test_destroy_keep_volumes_keeps_network_volumes() {
+        let yaml = "project:\n  name: proj\nstate:\n  backend: local\npods:\n  - name: llm\n    volumes:\n      - name: data\n        mount: /data\n        network_volume_id: vol-1\n      - name: scratch\n        mount: /scratch\n    gpu:\n      type: A40\n    runtime:\n      image: img\n";
+        let config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+        let observed = ObservedPod {
+            id: String::from("id-llm"),
+            name: String::from("proj-dev-llm"),
+            project: Some(String::from("proj")),
+            environment: Some(String::from("dev")),
+            pod_name: Some(String::from("llm")),
+            spec_hash: None,
+            status: PodStatus::Running,
+            gpu_type: Some(String::from("A40")),
+            gpu_count: 1,
+            image: String::from("img"),
+            endpoints: HashMap::new(),
+            gpu_utilization: None,
+            tags: HashMap::new(),
+            volume_gb: 0,
+            uptime_secs: 0,
+            env: None,
+        };
+        let mut state = DeploymentState::new("proj", "dev");
+        state.set_volume(VolumeState {
+            name: String::from("data"),
+            runpod_id: String::from("vol-1"),
+            mount_path: String::from("/data"),
+            size_gb: 100,
+            created_at: Utc::now(),
+            pod: String::from("llm"),
+            detached: false,
+            data_center: None,
+            managed: false,
+        });
+
+        let plan = DeploymentPlan::destroy(
+            &config,
+            std::slice::from_ref(&observed),
+            Some(&state),
+            false,
+            "hash",
+        );
+        let kinds: Vec<_> = plan.actions.iter().map(|a| a.action_type).collect();
+        assert_eq!(kinds, [ActionType::DestroyPod, ActionType::DeleteVolume]);
+        assert_eq!(plan.actions[1].runpod_id.as_deref(), Some("vol-1"));
+        assert_eq!(plan.actions[1].dependencies, [0]);
+
+        let kept = DeploymentPlan::destroy(&config, &[observed], Some(&state), true, "hash");
+        assert_eq!(kept.action_count(), 1);
+        assert!(
+            kept.warnings
+                .iter()
+                .any(|w| w.contains("'data' (vol-1) will be kept"))
+        );
+        assert!(
+            kept.warnings
+                .iter()
+                .any(|w| w.contains("'scratch' of 'llm' is a pod volume"))
+        );
+        assert!(
+            kept.warnings
+                .iter()
+                .any(|w| w.contains("state will be kept"))
+        );
+    }
+
+    #[test]
+    fn test_depends_on_orders_creates() {
+        let yaml = "project:\n  name: proj\nstate:\n  backend: local\npods:\n  - name: api\n    depends_on: [vector-db]\n    gpu:\n      type: A40\n    runtime:\n      image: img\n  - name: vector-db\n    replicas: 2\n    gpu:\n      type: A40\n    runtime:\n      image: qdrant\n";
+        let config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+
+        let diff = DiffEngine::new().compute_diff(&config, None, &[]);
+        let plan = DeploymentPlan::from_diff(&diff, &config, "hash");
+
+        let names: Vec<&str> = plan
+            .actions
+            .iter()
+            .map(|a| a.resource_name.as_str())
+            .collect();
+        assert_eq!(names, ["vector-db", "vector-db-1", "api"]);
+        assert_eq!(plan.actions[2].dependencies, [0, 1]);
+        assert!(plan.actions[0].dependencies.is_empty());
+    }
+
+    #[test]
+    fn test_warm_pool_resumed_and_refilled() {
+        let yaml = "project:\n  name: proj\nstate:\n  backend: local\npods:\n  - name: llm\n    replicas: 2\n    warm_pool:\n      size: 1\n    gpu:\n      type: A40\n    runtime:\n      image: img\n";
+        let config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+        let hasher = crate::config::ConfigHasher::new();
+        let replicas = config.pods[0].instances(2);
+        let warm = config.pods[0].warm_instances();
+        let pod = |name: &str, hash: String, status: PodStatus| ObservedPod {
+            id: format!("id-{name}"),
+            name: format!("proj-dev-{name}"),
+            project: Some(String::from("proj")),
+            environment: Some(String::from("dev")),
+            pod_name: Some(name.to_string()),
+            spec_hash: Some(hash),
+            status,
+            gpu_type: None,
+            gpu_count: 1,
+            image: String::from("img"),
+            endpoints: HashMap::new(),
+            gpu_utilization: None,
+            tags: HashMap::new(),
+            volume_gb: 0,
+            uptime_secs: 0,
+            env: None,
+        };
+        let observed = vec![
+            pod("llm", hasher.hash_pod(&replicas[0]), PodStatus::Running),
+            pod("llm-warm-0", hasher.hash_pod(&warm[0]), PodStatus::Exited),
+        ];
+
+        let diff = DiffEngine::new().compute_diff(&config, None, &observed);
+        let plan = DeploymentPlan::from_diff(&diff, &config, "hash");
+
+        let kinds: Vec<_> = plan
+            .actions
+            .iter()
+            .map(|a| (a.action_type, a.resource_name.as_str()))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                (ActionType::ResumePod, "llm-1"),
+                (ActionType::CreatePod, "llm-warm-0"),
+                (ActionType::StopPod, "llm-warm-0"),
+            ]
+        );
+        assert_eq!(plan.actions[0].runpod_id.as_deref(), Some("id-llm-warm-0"));
+        assert_eq!(plan.actions[2].dependencies, [1]);
+
+        // Once resumed, state maps the warm-tagged pod to its new name
+        let mut state = DeploymentState::new("proj", "dev");
+        state.set_pod(crate::state::PodState::new(
+            "llm-1",
+            "id-llm-warm-0",
+            &hasher.hash_pod(&replicas[1]),
+        ));
+        let observed = vec![
+            observed[0].clone(),
+            pod("llm-warm-0", String::from("stale"), PodStatus::Running),
+        ];
+
+        let refilled = DiffEngine::new().compute_diff(&config, Some(&state), &observed);
+        let refill_plan = DeploymentPlan::from_diff(&refilled, &config, "hash");
+
+        let refill_kinds: Vec<_> = refill_plan.actions.iter().map(|a| a.action_type).collect();
+        assert_eq!(refill_kinds, [ActionType::CreatePod, ActionType::StopPod]);
+    }
+
+    #[test]
+    fn test_disabled_pod_kept_stopped() {
+        let yaml = "project:\n  name: proj\nstate:\n  backend: local\npods:\n  - name: llm\n    enabled: false\n    replicas: 2\n    gpu:\n      type: A40\n    runtime:\n      image: img\n";
+        let mut config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+        let hasher = crate::config::ConfigHasher::new();
+        let replicas = config.pods[0].instances(2);
+        let mut observed = ObservedPod {
+            id: String::from("id-llm"),
+            name: String::from("proj-dev-llm"),
+            project: Some(String::from("proj")),
+            environment: Some(String::from("dev")),
+            pod_name: Some(String::from("llm")),
+            spec_hash: Some(hasher.hash_pod(&replicas[0])),
+            status: PodStatus::Running,
+            gpu_type: None,
+            gpu_count: 1,
+            image: String::from("img"),
+            endpoints: HashMap::new(),
+            gpu_utilization: None,
+            tags: HashMap::new(),
+            volume_gb: 0,
+            uptime_secs: 0,
+            env: None,
+        };
+
+        // The running replica is stopped in place and the missing one is not created
+        let diff = DiffEngine::new().compute_diff(&config, None, std::slice::from_ref(&observed));
+        let plan = DeploymentPlan::from_diff(&diff, &config, "hash");
+        let kinds: Vec<_> = plan
+            .actions
+            .iter()
+            .map(|a| (a.action_type, a.resource_name.as_str()))
+            .collect();
+        assert_eq!(kinds, [(ActionType::StopPod, "llm")]);
+        assert_eq!(plan.actions[0].runpod_id.as_deref(), Some("id-llm"));
+
+        // Once stopped it is left alone, even with a new spec
+        observed.status = PodStatus::Exited;
+        config.pods[0].runtime.image = String::from("img:2");
+        assert!(
+            !DiffEngine::new()
+                .compute_diff(&config, None, std::slice::from_ref(&observed))
+                .has_changes()
+        );
+
+        // Enabled again, it is recreated with the new spec and the other replica created
+        config.pods[0].enabled = true;
+        let enabled =
+            DiffEngine::new().compute_diff(&config, None, std::slice::from_ref(&observed));
+        assert_eq!((enabled.creates, enabled.updates), (1, 1));
+    }
+
+    #[test]
+    fn test_costs_enforce_max_hourly_cost() {
+        let yaml = "project:\n  name: proj\nstate:\n  backend: local\nguardrails:\n  max_hourly_cost: 1.0\npods:\n  - name: llm\n    gpu:\n      type: A40\n      count: 2\n    runtime:\n      image: img\n  - name: embed\n    gpu:\n      type: L4\n    runtime:\n      image: img\n";
+        let config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+        let diff = crate::planner::DiffEngine::new().compute_diff(&config, None, &[]);
+        let gpu: crate::runpod::GpuType = serde_json::from_value(serde_json::json!({
+            "id": "NVIDIA A40",
+            "displayName": "A40",
+            "memoryInGb": 48,
+            "securePrice": 0.4
+        }))
+        .unwrap();
+        let prices = GpuPrices::new(&[gpu], crate::config::CloudType::Secure);
+
+        let plan = DeploymentPlan::from_diff(&diff, &config, "hash").with_costs(
+            &prices,
+            &[],
+            config.guardrails.as_ref(),
+        );
+
+        let llm = plan
+            .actions
+            .iter()
+            .find(|a| a.resource_name == "llm")
+            .unwrap();
+        assert!(
+            llm.hourly_cost_delta
+                .is_some_and(|d| (d - 0.8).abs() < 1e-9)
+        );
+        assert!(
+            plan.estimated_hourly_cost
+                .is_some_and(|c| (c - 0.8).abs() < 1e-9)
+        );
+        assert!(plan.passes_guardrails);
+        assert!(plan.warnings.iter().any(|w| w.contains("embed")));
+
+        let strict = GuardrailsConfig {
+            max_hourly_cost: Some(0.5),
+            ..config.guardrails.clone().unwrap()
+        };
+        let over_budget = DeploymentPlan::from_diff(&diff, &config, "hash").with_costs(
+            &prices,
+            &[],
+            Some(&strict),
+        );
+        assert!(!over_budget.passes_guardrails);
+        assert!(over_budget.guardrail_violations[0].contains("max_hourly_cost"));
+    }
+
+    #[test]
+    fn test_costs_net_warm_pool_refill() {
+        let yaml = "project:\n  name: proj\nstate:\n  backend: local\nguardrails:\n  max_hourly_cost: 1.0\npods:\n  - name: llm\n    warm_pool:\n      size: 1\n    gpu:\n      type: A40\n      count: 2\n    runtime:\n      image: img\n";
+        let config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+        let gpu: crate::runpod::GpuType = serde_json::from_value(serde_json::json!({
+            "id": "NVIDIA A40",
+            "displayName": "A40",
+            "memoryInGb": 48,
+            "securePrice": 0.4
+        }))
+        .unwrap();
+        let prices = GpuPrices::new(&[gpu], crate::config::CloudType::Secure);
+        let diff = crate::planner::DiffEngine::new().compute_diff(&config, None, &[]);
+        let plan = DeploymentPlan::from_diff(&diff, &config, "hash").with_costs(
+            &prices,
+            &[],
+            config.guardrails.as_ref(),
+        );
+
+        // The warm clone is created and stopped: only the serving pod costs
+        let deltas: Vec<_> = plan
+            .actions
+            .iter()
+            .map(|a| (a.action_type, a.resource_name.as_str(), a.hourly_cost_delta))
+            .collect();
+        assert_eq!(
+            deltas,
+            [
+                (ActionType::CreatePod, "llm", Some(0.8)),
+                (ActionType::CreatePod, "llm-warm-0", Some(0.8)),
+                (ActionType::StopPod, "llm-warm-0", Some(-0.8)),
+            ]
+        );
+        assert!(
+            plan.estimated_hourly_cost
+                .is_some_and(|c| (c - 0.8).abs() < 1e-9)
+        );
+        assert!(plan.passes_guardrails);
+    }
+}
@@ -0,0 +1,223 @@
+//! Pre-flight checks of hypothetical configurations.
+//!
+//! `halldyll check --set ...` applies overrides to the configuration in
+//! memory and evaluates the result as a fresh deployment: validation,
+//! GPU and cost guardrails, and account runway. Nothing is written.
+
+use serde::Serialize;
+
+use crate::config::{ConfigValidator, DeployConfig};
+
+use super::diff::DiffEngine;
+use super::runway::{GpuPrices, Runway};
+
+/// Outcome of evaluating a (possibly overridden) configuration.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PreflightReport {
+    /// Overrides that were applied, as `path=value`.
+    pub overrides: Vec<String>,
+    /// Validation errors.
+    pub errors: Vec<String>,
+    /// Validation warnings.
+    pub warnings: Vec<String>,
+    /// Guardrail violations.
+    pub violations: Vec<String>,
+    /// GPUs the configuration deploys.
+    pub total_gpus: u32,
+    /// GPUs the configuration deploys without the overrides.
+    pub baseline_gpus: u32,
+    /// Estimated hourly cost in USD (if prices are known).
+    pub hourly_cost: Option<f64>,
+    /// Estimated hourly cost without the overrides.
+    pub baseline_hourly_cost: Option<f64>,
+    /// Pods whose GPU type has no known price.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unpriced: Vec<String>,
+    /// Hours the account balance covers after the change.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub runway_hours: Option<f64>,
+}
+
+impl PreflightReport {
+    /// Evaluates `config`, comparing it with the unmodified `baseline`.
+    ///
+    /// Pods are counted at their configured replica counts. Without
+    /// `prices`, costs are unknown and `max_hourly_cost` is not checked.
+    #[must_use]
+    pub fn evaluate(
+        config: &DeployConfig,
+        baseline: &DeployConfig,
+        prices: Option<&GpuPrices>,
+    ) -> Self {
+        let validation = ConfigValidator::new().check(config);
+        let desired = DiffEngine::desired_instances(config, None);
+        let baseline_desired = DiffEngine::desired_instances(baseline, None);
+
+        let mut report = Self {
+            errors: validation
+                .errors
+                .iter()
+                .map(|e| format!("{}: {}", e.field, e.message))
+                .collect(),
+            warnings: validation.warnings,
+            total_gpus: desired.iter().map(|p| p.gpu.count).sum(),
+            baseline_gpus: baseline_desired.iter().map(|p| p.gpu.count).sum(),
+            ..Self::default()
+        };
+
+        if let Some(prices) = prices {
+            let price = |pod: &crate::config::PodConfig| prices.pod_hourly(&pod.gpu);
+            report.hourly_cost = Some(desired.iter().filter_map(price).sum());
+            report.baseline_hourly_cost = Some(baseline_desired.iter().filter_map(price).sum());
+            report.unpriced = desired
+                .iter()
+                .filter(|pod| price(pod).is_none())
+                .map(|pod| pod.name.clone())
+                .collect();
+        }
+
+        if let Some(guardrails) = &config.guardrails {
+            if let Some(max_gpus) = guardrails.max_gpus
+                && report.total_gpus > max_gpus
+            {
+                report.violations.push(format!(
+                    "Deploys {} GPUs but max_gpus is {max_gpus}",
+                    report.total_gpus
+                ));
+            }
+            if let (Some(limit), Some(cost)) = (guardrails.max_hourly_cost, report.hourly_cost)
+                && cost > limit
+            {
+                report.violations.push(format!(
+                    "Costs ${cost:.2}/hr but max_hourly_cost is ${limit:.2}/hr"
+                ));
+            }
+        }
+
+        report
+    }
+
+    /// Records the account runway and checks `min_runway_hours`.
+    #[must_use]
+    pub fn with_runway(mut self, runway: &Runway, config: &DeployConfig) -> Self {
+        self.runway_hours = runway.hours();
+        if let (Some(min_hours), Some(hours)) = (
+            config.guardrails.as_ref().and_then(|g| g.min_runway_hours),
+            self.runway_hours,
+        ) && hours < min_hours
+        {
+            self.violations.push(format!(
+                "Balance ${:.2} covers {hours:.1}h at ${:.2}/hr, below min_runway_hours {min_hours}",
+                runway.balance, runway.planned_spend_per_hour
+            ));
+        }
+        self
+    }
+
+    /// Records the overrides that produced the evaluated configuration.
+    #[must_use]
+    pub fn with_overrides(mut self, overrides: Vec<String>) -> Self {
+        self.overrides = overrides;
+        self
+    }
+
+    /// Returns true if the configuration is valid and within its guardrails.
+    #[must_use]
+    pub const fn passes(&self) -> bool {
+        self.errors.is_empty() && self.violations.is_empty()
+    }
+
+    /// Returns the number of errors and guardrail violations.
+    #[must_use]
+    pub const fn problem_count(&self) -> usize {
+        self.errors.len() + self.violations.len()
+    }
+}
+
+impl std::fmt::Display for PreflightReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if !self.overrides.is_empty() {
+            writeln!(f, "Simulated overrides:")?;
+            for item in &self.overrides {
+                writeln!(f, "  {item}")?;
+            }
+            writeln!(f)?;
+        }
+
+        writeln!(
+            f,
+            "GPUs: {} (currently configured: {})",
+            self.total_gpus, self.baseline_gpus
+        )?;
+        match (self.hourly_cost, self.baseline_hourly_cost) {
+            (Some(cost), Some(baseline)) => {
+                writeln!(
+                    f,
+                    "Hourly cost: ${cost:.2} (currently configured: ${baseline:.2}, {:+.2})",
+                    cost - baseline
+                )?;
+            }
+            _ => writeln!(f, "Hourly cost: unknown (GPU prices unavailable)")?,
+        }
+        if !self.unpriced.is_empty() {
+            writeln!(f, "No price known for: {}", self.unpriced.join(", "))?;
+        }
+        if let Some(hours) = self.runway_hours {
+            writeln!(f, "Runway: ~{hours:.1}h")?;
+        }
+
+        for (title, items) in [
+            ("Validation errors", &self.errors),
+            ("Guardrail violations", &self.violations),
+            ("Warnings", &self.warnings),
+        ] {
+            if !items.is_empty() {
+                writeln!(f, "\n{title}:")?;
+                for item in items {
+                    writeln!(f, "  - {item}")?;
+                }
+            }
+        }
+
+        if self.passes() {
+            write!(f, "\nThe change would pass.")
+        } else {
+            write!(
+                f,
+                "\nThe change would fail ({} problems).",
+                self.problem_count()
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ConfigOverride, apply_overrides};
+    use crate::runpod::GpuType;
+
+    #[test]
+    fn test_preflight_guardrails() {
+        let yaml = "project:\n  name: proj\nstate:\n  backend: local\nguardrails:\n  max_gpus: 2\n  max_hourly_cost: 1.0\npods:\n  - name: llm\n    gpu:\n      type: A40\n    runtime:\n      image: img\n";
+        let config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+        let gpu: GpuType = serde_json::from_value(serde_json::json!({
+            "id": "NVIDIA A40",
+            "displayName": "A40",
+            "memoryInGb": 48,
+            "securePrice": 0.4
+        }))
+        .unwrap();
+        let prices = GpuPrices::new(&[gpu], crate::config::CloudType::Secure);
+
+        assert!(PreflightReport::evaluate(&config, &config, Some(&prices)).passes());
+
+        let overrides = [ConfigOverride::parse("pods[0].gpu.count=4").unwrap()];
+        let simulated = apply_overrides(&config, &overrides).unwrap();
+        let report = PreflightReport::evaluate(&simulated, &config, Some(&prices));
+        assert_eq!(report.total_gpus, 4);
+        assert!(report.hourly_cost.is_some_and(|c| (c - 1.6).abs() < 1e-9));
+        assert_eq!(report.violations.len(), 2);
+        assert!(!report.passes());
+    }
+}
@@ -0,0 +1,174 @@
+//! Account runway estimation.
+//!
+//! Compares the `RunPod` account balance with the spend rate the account
+//! would have once a plan is applied, so applies that would exhaust funds
+//! quickly can be flagged (or blocked with `guardrails.min_runway_hours`).
+
+use std::collections::HashMap;
+
+use crate::config::{CloudType, GpuConfig, PodConfig};
+use crate::runpod::{AccountInfo, GpuType, ObservedPod, PodStatus};
+
+/// Hourly GPU prices for one cloud type, keyed by GPU ID and display name.
+#[derive(Debug, Clone, Default)]
+pub struct GpuPrices {
+    /// Price of one GPU per hour.
+    prices: HashMap<String, f64>,
+    /// Cloud type the prices are for (`SECURE` or `COMMUNITY`).
+    cloud: &'static str,
+    /// GPU types the cheapest one is picked from for `type: cheapest`.
+    gpu_types: Vec<GpuType>,
+}
+
+impl GpuPrices {
+    /// Builds the price table for a cloud type.
+    #[must_use]
+    pub fn new(gpu_types: &[GpuType], cloud_type: CloudType) -> Self {
+        let cloud = match cloud_type {
+            CloudType::Secure => "SECURE",
+            CloudType::Community => "COMMUNITY",
+        };
+
+        let mut prices = HashMap::new();
+        for gpu in gpu_types {
+            if let Some(price) = gpu.hourly_price(cloud) {
+                prices.insert(gpu.id.clone(), price);
+                prices.insert(gpu.display_name.clone(), price);
+            }
+        }
+
+        Self {
+            prices,
+            cloud,
+            gpu_types: gpu_types.to_vec(),
+        }
+    }
+
+    /// Returns the hourly price of `count` GPUs of a type, if known.
+    #[must_use]
+    pub fn hourly(&self, gpu_type: &str, count: u32) -> Option<f64> {
+        self.prices
+            .get(gpu_type)
+            .map(|price| price * f64::from(count))
+    }
+
+    /// Returns the hourly price of a pod's GPUs, if known.
+    ///
+    /// With `type: cheapest`, this is the price of the type the provisioner
+    /// would pick today.
+    #[must_use]
+    pub fn pod_hourly(&self, gpu: &GpuConfig) -> Option<f64> {
+        if gpu.is_auto() {
+            let (_, price) = GpuType::cheapest(
+                &self.gpu_types,
+                self.cloud,
+                gpu.min_vram_gb.unwrap_or_default(),
+            )?;
+            return Some(price * f64::from(gpu.count));
+        }
+        self.hourly(&gpu.gpu_type, gpu.count)
+    }
+}
+
+/// Account funds compared with the spend rate after applying a plan.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Runway {
+    /// Account balance in USD.
+    pub balance: f64,
+    /// Current account spend in USD per hour.
+    pub current_spend_per_hour: f64,
+    /// Estimated account spend in USD per hour once the plan is applied.
+    pub planned_spend_per_hour: f64,
+}
+
+impl Runway {
+    /// Estimates the runway for a desired configuration.
+    ///
+    /// The account's current spend is adjusted by replacing what this
+    /// project's running pods cost today with what the desired pods will
+    /// cost. GPUs without a known price are counted as free.
+    #[must_use]
+    pub fn estimate(
+        account: &AccountInfo,
+        prices: &GpuPrices,
+        desired: &[PodConfig],
+        observed: &[ObservedPod],
+    ) -> Self {
+        let current_project: f64 = observed
+            .iter()
+            .filter(|pod| {
+                matches!(
+                    pod.status,
+                    PodStatus::Running | PodStatus::Starting | PodStatus::Creating
+                )
+            })
+            .filter_map(|pod| prices.hourly(pod.gpu_type.as_deref()?, pod.gpu_count))
+            .sum();
+
+        let planned_project: f64 = desired
+            .iter()
+            .filter_map(|pod| prices.pod_hourly(&pod.gpu))
+            .sum();
+
+        let planned = (account.current_spend_per_hr - current_project).max(0.0) + planned_project;
+
+        Self {
+            balance: account.client_balance,
+            current_spend_per_hour: account.current_spend_per_hr,
+            planned_spend_per_hour: planned,
+        }
+    }
+
+    /// Returns how many hours the balance covers at the planned spend rate.
+    ///
+    /// `None` if nothing would be running.
+    #[must_use]
+    pub fn hours(&self) -> Option<f64> {
+        (self.planned_spend_per_hour > 0.0).then(|| self.balance / self.planned_spend_per_hour)
+    }
+}
+
+impl std::fmt::Display for Runway {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Balance ${:.2}", self.balance)?;
+        match self.hours() {
+            Some(hours) => write!(
+                f,
+                ", ~{hours:.1}h at ${:.2}/hr planned spend",
+                self.planned_spend_per_hour
+            ),
+            None => write!(f, ", no planned spend"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_runway_estimate() {
+        let gpu: GpuType = serde_json::from_value(serde_json::json!({
+            "id": "NVIDIA A40",
+            "displayName": "A40",
+            "memoryInGb": 48,
+            "securePrice": 0.4
+        }))
+        .unwrap();
+        let prices = GpuPrices::new(&[gpu], CloudType::Secure);
+
+        let pod: PodConfig = serde_yaml::from_str(
+            "name: llm\ngpu:\n  type: A40\n  count: 2\nruntime:\n  image: vllm/vllm-openai\n",
+        )
+        .unwrap();
+        let account = AccountInfo {
+            client_balance: 12.0,
+            current_spend_per_hr: 0.2,
+            spend_limit: None,
+        };
+
+        let runway = Runway::estimate(&account, &prices, &[pod], &[]);
+        assert!((runway.planned_spend_per_hour - 1.0).abs() < 1e-9);
+        assert!(runway.hours().is_some_and(|h| (h - 12.0).abs() < 1e-9));
+    }
+}
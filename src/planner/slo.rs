@@ -0,0 +1,177 @@
+//! Availability report derived from the health history.
+//!
+//! Samples taken in the same watch tick are merged: a replicated pod counts
+//! as up while any of its replicas is healthy. Each tick's outcome holds
+//! until the next one, except across gaps longer than
+//! [`MAX_SAMPLE_GAP_SECS`] (watch not running), which count as unmonitored.
+
+use chrono::{DateTime, Duration, Utc};
+use std::collections::BTreeMap;
+
+use crate::state::HealthSample;
+
+/// Longest gap between samples still counted as monitored time.
+pub const MAX_SAMPLE_GAP_SECS: i64 = 600;
+
+/// A period during which the pod was unhealthy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Outage {
+    /// First unhealthy sample.
+    pub start: DateTime<Utc>,
+    /// First healthy sample after it, or `None` if the outage is ongoing.
+    pub end: Option<DateTime<Utc>>,
+}
+
+impl Outage {
+    /// Returns how long the outage lasted (until `now` if ongoing).
+    #[must_use]
+    pub fn duration(&self, now: DateTime<Utc>) -> Duration {
+        self.end.unwrap_or(now) - self.start
+    }
+}
+
+/// Availability of a pod over a time window.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SloReport {
+    /// Pod name.
+    pub pod: String,
+    /// Start of the window.
+    pub window_start: DateTime<Utc>,
+    /// End of the window (when the report was built).
+    pub window_end: DateTime<Utc>,
+    /// Number of watch ticks with samples of the pod.
+    pub samples: usize,
+    /// Seconds covered by samples.
+    pub monitored_secs: i64,
+    /// Seconds the pod was healthy.
+    pub healthy_secs: i64,
+    /// Outages, oldest first.
+    pub outages: Vec<Outage>,
+}
+
+impl SloReport {
+    /// Builds the report for `pod` from the samples taken since `window_start`.
+    #[must_use]
+    pub fn new(
+        pod: &str,
+        samples: &[HealthSample],
+        window_start: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let mut ticks: BTreeMap<DateTime<Utc>, bool> = BTreeMap::new();
+        for sample in samples
+            .iter()
+            .filter(|s| s.belongs_to(pod) && s.at >= window_start)
+        {
+            *ticks.entry(sample.at).or_default() |= sample.healthy;
+        }
+
+        let max_gap = Duration::seconds(MAX_SAMPLE_GAP_SECS);
+        let mut monitored_secs = 0;
+        let mut healthy_secs = 0;
+        let mut outages: Vec<Outage> = Vec::new();
+
+        let ends = ticks.keys().skip(1).copied().chain(std::iter::once(now));
+        for ((&at, &healthy), next) in ticks.iter().zip(ends) {
+            let covered = (next - at).min(max_gap).num_seconds().max(0);
+            monitored_secs += covered;
+            if healthy {
+                healthy_secs += covered;
+                if let Some(outage) = outages.last_mut().filter(|o| o.end.is_none()) {
+                    outage.end = Some(at);
+                }
+            } else if outages.last().is_none_or(|o| o.end.is_some()) {
+                outages.push(Outage {
+                    start: at,
+                    end: None,
+                });
+            }
+        }
+
+        Self {
+            pod: pod.to_string(),
+            window_start,
+            window_end: now,
+            samples: ticks.len(),
+            monitored_secs,
+            healthy_secs,
+            outages,
+        }
+    }
+
+    /// Returns the percentage of monitored time the pod was healthy.
+    #[must_use]
+    pub fn availability(&self) -> Option<f64> {
+        #[allow(clippy::cast_precision_loss)]
+        (self.monitored_secs > 0)
+            .then(|| self.healthy_secs as f64 * 100.0 / self.monitored_secs as f64)
+    }
+
+    /// Returns the longest outage, ongoing ones included.
+    #[must_use]
+    pub fn longest_outage(&self) -> Option<Duration> {
+        self.outages
+            .iter()
+            .map(|o| o.duration(self.window_end))
+            .max()
+    }
+
+    /// Returns the mean time to recovery of the resolved outages.
+    #[must_use]
+    pub fn mttr(&self) -> Option<Duration> {
+        let resolved: Vec<Duration> = self
+            .outages
+            .iter()
+            .filter(|o| o.end.is_some())
+            .map(|o| o.duration(self.window_end))
+            .collect();
+        let count = i32::try_from(resolved.len()).ok().filter(|&n| n > 0)?;
+        Some(resolved.into_iter().sum::<Duration>() / count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slo_report() {
+        let start = Utc::now() - Duration::hours(1);
+        let sample = |mins: i64, pod: &str, healthy: bool| HealthSample {
+            at: start + Duration::minutes(mins),
+            pod: pod.to_string(),
+            healthy,
+            response_time_ms: None,
+            error: None,
+        };
+
+        // One replica down at 5m is masked by the other; both down 10m-20m,
+        // then an unmonitored gap, then a second outage 50m-52m
+        let samples = [
+            sample(0, "llm-0", true),
+            sample(5, "llm-0", false),
+            sample(5, "llm-1", true),
+            sample(10, "llm-0", false),
+            sample(10, "llm-1", false),
+            sample(20, "llm-0", true),
+            sample(50, "llm-0", false),
+            sample(52, "llm-0", true),
+            sample(52, "cache", false),
+        ];
+        let now = start + Duration::minutes(55);
+        let report = SloReport::new("llm", &samples, start, now);
+
+        assert_eq!(report.samples, 6);
+        assert_eq!(report.outages.len(), 2);
+        assert_eq!(report.longest_outage(), Some(Duration::minutes(10)));
+        assert_eq!(report.mttr(), Some(Duration::minutes(6)));
+        // 20m + 10m (gap capped) + 5m monitored, of which 12m down
+        assert_eq!(report.monitored_secs, 35 * 60);
+        assert_eq!(report.healthy_secs, 23 * 60);
+        assert!(
+            report
+                .availability()
+                .is_some_and(|a| (a - 65.71).abs() < 0.01)
+        );
+    }
+}
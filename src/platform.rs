@@ -0,0 +1,203 @@
+//! Platform-specific paths and file operations.
+//!
+//! This module keeps OS differences out of the rest of the code base:
+//! expansion of `~` and environment variables (`%APPDATA%` on Windows) in
+//! configured paths, the default local state directory (`%APPDATA%` on
+//! Windows), and replacing files in a way that tolerates Windows sharing
+//! violations.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Attempts made when replacing a file that is briefly held open elsewhere.
+const REPLACE_ATTEMPTS: u32 = 5;
+
+/// Delay between file replacement attempts.
+const REPLACE_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// Directory kept next to the config file for local state.
+const LOCAL_STATE_DIR: &str = ".halldyll";
+
+/// Directory under the user's data directory (`%APPDATA%`) for local state.
+const APP_DIR: &str = "halldyll";
+
+/// Expands a configured path.
+///
+/// Supports a leading `~`, `${VAR}` references, and `%VAR%` references on
+/// Windows. Forward slashes are converted to the native separator so paths
+/// written in a shared config file work on every platform. Unset variables
+/// are left as-is.
+#[must_use]
+pub fn expand_path(raw: &str) -> PathBuf {
+    expand_path_with(raw, dirs::home_dir(), &|name| std::env::var(name).ok())
+}
+
+/// Makes a path absolute without resolving symlinks.
+///
+/// Unlike `canonicalize`, this never produces `\\?\` verbatim paths on Windows.
+#[must_use]
+pub fn absolute(path: &Path) -> PathBuf {
+    std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Returns the default local state directory of a project.
+///
+/// On Windows this is `%APPDATA%\halldyll\<project>`, so state stays in the
+/// user profile instead of next to a config that often lives in a synced or
+/// shared folder. Elsewhere it is `.halldyll` next to the config. A `.halldyll`
+/// directory that already exists next to the config (state written before
+/// the Windows default moved) keeps being used.
+#[must_use]
+pub fn default_state_dir(config_dir: &Path, project: &str) -> PathBuf {
+    let data_dir = if cfg!(windows) {
+        dirs::data_dir()
+    } else {
+        None
+    };
+    default_state_dir_with(config_dir, project, data_dir)
+}
+
+/// Atomically replaces `dest` with `src`.
+///
+/// On Windows a rename fails while another process (an editor, antivirus,
+/// a concurrent halldyll) has the destination open, so the rename is retried
+/// briefly before giving up.
+///
+/// # Errors
+///
+/// Returns the last I/O error if the file could not be replaced.
+pub async fn replace_file(src: &Path, dest: &Path) -> std::io::Result<()> {
+    let mut attempt = 1;
+    loop {
+        match tokio::fs::rename(src, dest).await {
+            Ok(()) => return Ok(()),
+            Err(e)
+                if e.kind() == std::io::ErrorKind::PermissionDenied
+                    && attempt < REPLACE_ATTEMPTS =>
+            {
+                attempt += 1;
+                tokio::time::sleep(REPLACE_RETRY_DELAY).await;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Returns the line ending used by existing text, defaulting to `\n`.
+#[must_use]
+pub fn line_ending(text: &str) -> &'static str {
+    if text.contains("\r\n") { "\r\n" } else { "\n" }
+}
+
+/// Picks the default state directory given the user's data directory, if any.
+fn default_state_dir_with(config_dir: &Path, project: &str, data_dir: Option<PathBuf>) -> PathBuf {
+    let beside_config = config_dir.join(LOCAL_STATE_DIR);
+    match data_dir {
+        Some(data_dir) if !beside_config.exists() => data_dir.join(APP_DIR).join(project),
+        _ => beside_config,
+    }
+}
+
+/// Expands a path using the given home directory and environment lookup.
+fn expand_path_with(
+    raw: &str,
+    home: Option<PathBuf>,
+    env: &dyn Fn(&str) -> Option<String>,
+) -> PathBuf {
+    let mut expanded = expand_vars(raw, "${", "}", env);
+    if cfg!(windows) {
+        expanded = expand_vars(&expanded, "%", "%", env);
+    }
+
+    if (expanded == "~" || expanded.starts_with("~/") || expanded.starts_with("~\\"))
+        && let Some(home) = home
+    {
+        expanded = format!("{}{}", home.display(), &expanded[1..]);
+    }
+
+    if cfg!(windows) {
+        expanded = expanded.replace('/', "\\");
+    }
+
+    PathBuf::from(expanded)
+}
+
+/// Replaces `open`NAME`close` references with environment values.
+fn expand_vars(raw: &str, open: &str, close: &str, env: &dyn Fn(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(raw.len());
+    let mut rest = raw;
+
+    while let Some(start) = rest.find(open) {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + open.len()..];
+        let Some(end) = after.find(close) else {
+            out.push_str(&rest[start..]);
+            return out;
+        };
+
+        let name = &after[..end];
+        match env(name) {
+            Some(value) if !name.is_empty() => out.push_str(&value),
+            _ => out.push_str(&rest[start..start + open.len() + end + close.len()]),
+        }
+        rest = &after[end + close.len()..];
+    }
+
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(name: &str) -> Option<String> {
+        (name == "STATE_ROOT").then(|| String::from("/srv/state"))
+    }
+
+    #[test]
+    fn test_expand_path() {
+        let sep = std::path::MAIN_SEPARATOR;
+
+        let home = expand_path_with("~/.halldyll", Some(PathBuf::from("/home/dev")), &env);
+        assert!(
+            home.to_string_lossy()
+                .ends_with(&format!("dev{sep}.halldyll"))
+        );
+
+        let var = expand_path_with("${STATE_ROOT}/prod", None, &env);
+        assert!(var.to_string_lossy().ends_with(&format!("state{sep}prod")));
+
+        let unset = expand_path_with("${MISSING}/x", None, &env);
+        assert!(unset.to_string_lossy().starts_with("${MISSING}"));
+
+        assert_eq!(line_ending("a\r\nb"), "\r\n");
+        assert_eq!(line_ending("a\nb"), "\n");
+    }
+
+    #[test]
+    fn test_default_state_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_dir = dir.path().join("repo");
+        let appdata = dir.path().join("AppData").join("Roaming");
+
+        // Without a data directory (every platform but Windows): next to the config
+        assert_eq!(
+            default_state_dir_with(&config_dir, "my-project", None),
+            config_dir.join(".halldyll")
+        );
+
+        // With one: under it, keyed by project
+        assert_eq!(
+            default_state_dir_with(&config_dir, "my-project", Some(appdata.clone())),
+            appdata.join("halldyll").join("my-project")
+        );
+
+        // Existing state next to the config keeps being used
+        std::fs::create_dir_all(config_dir.join(".halldyll")).unwrap();
+        assert_eq!(
+            default_state_dir_with(&config_dir, "my-project", Some(appdata)),
+            config_dir.join(".halldyll")
+        );
+    }
+}
@@ -7,9 +7,11 @@
 use tracing::{debug, error, info, warn};
 
 use crate::config::{ConfigHasher, DeployConfig};
-use crate::error::{HalldyllError, ReconcileError, Result};
-use crate::planner::{DeploymentPlan, DiffEngine, PlanExecutor};
-use crate::runpod::{ObservedPod, PodObserver, PodProvisioner};
+use crate::error::{ErrorContext, HalldyllError, ReconcileError, Result};
+use crate::planner::{DeploymentPlan, DiffEngine, GpuPrices, PlanExecutor};
+use crate::runpod::{
+    EngineProbe, HealthChecker, ObservedPod, PodObserver, PodProvisioner, model_matches,
+};
 use crate::state::{DeploymentState, StateStore};
 
 /// Reconciler for maintaining desired state.
@@ -28,6 +30,10 @@ pub struct Reconciler<'a, S: StateStore> {
     diff_engine: DiffEngine,
     /// Maximum reconciliation attempts.
     max_attempts: u32,
+    /// Whether drift checks probe recorded endpoints.
+    probe_endpoints: bool,
+    /// Pods replaced on the first attempt whatever their diff.
+    replace: Vec<String>,
 }
 
 /// Result of a reconciliation run.
@@ -67,9 +73,25 @@ impl<'a, S: StateStore> Reconciler<'a, S> {
             hasher: ConfigHasher::new(),
             diff_engine: DiffEngine::new(),
             max_attempts: 3,
+            probe_endpoints: false,
+            replace: Vec::new(),
         }
     }
 
+    /// Replaces `pods` (or their replicas) even if they match the configuration.
+    #[must_use]
+    pub fn with_replacements(mut self, pods: Vec<String>) -> Self {
+        self.replace = pods;
+        self
+    }
+
+    /// Enables probing each recorded endpoint during drift checks.
+    #[must_use]
+    pub const fn with_endpoint_probes(mut self, enabled: bool) -> Self {
+        self.probe_endpoints = enabled;
+        self
+    }
+
     /// Sets the maximum reconciliation attempts.
     #[must_use]
     pub const fn with_max_attempts(mut self, max_attempts: u32) -> Self {
@@ -91,13 +113,9 @@ impl<'a, S: StateStore> Reconciler<'a, S> {
         let config_hash = self.hasher.hash_config(self.config);
 
         // Load current state
-        let mut state = self
-            .state_store
-            .load()
-            .await?
-            .unwrap_or_else(|| {
-                DeploymentState::new(&self.config.project.name, &self.config.project.environment)
-            });
+        let mut state = self.state_store.load().await?.unwrap_or_else(|| {
+            DeploymentState::new(&self.config.project.name, &self.config.project.environment)
+        });
 
         // Observe current pods on RunPod
         let observed = self
@@ -122,8 +140,14 @@ impl<'a, S: StateStore> Reconciler<'a, S> {
         for attempt in 1..=self.max_attempts {
             debug!("Reconciliation attempt {}/{}", attempt, self.max_attempts);
 
+            // Forced replacements happen once, not on every retry
+            let replace = if attempt == 1 {
+                self.replace.as_slice()
+            } else {
+                &[]
+            };
             match self
-                .reconcile_once(&mut state, &observed, &config_hash)
+                .reconcile_once(&mut state, &observed, &config_hash, replace)
                 .await
             {
                 Ok(r) => {
@@ -137,8 +161,16 @@ impl<'a, S: StateStore> Reconciler<'a, S> {
                     }
                 }
                 Err(err) => {
-                    error!("Reconciliation attempt {} failed: {}", attempt, err);
-                    result.errors.push(format!("Attempt {attempt}: {err}"));
+                    let err = err.with_context(
+                        ErrorContext::new("reconciling")
+                            .with_resource(format!(
+                                "{}/{}",
+                                self.config.project.name, self.config.project.environment
+                            ))
+                            .with_attempt(attempt),
+                    );
+                    error!("{err}");
+                    result.errors.push(err.to_string());
                     last_error = Some(err);
 
                     if attempt < self.max_attempts {
@@ -157,7 +189,9 @@ impl<'a, S: StateStore> Reconciler<'a, S> {
 
         result.final_state = Some(state);
 
-        if !result.success && let Some(err) = last_error {
+        if !result.success
+            && let Some(err) = last_error
+        {
             return Err(err);
         }
 
@@ -170,11 +204,19 @@ impl<'a, S: StateStore> Reconciler<'a, S> {
         state: &mut DeploymentState,
         observed: &[ObservedPod],
         config_hash: &str,
+        replace: &[String],
     ) -> Result<ReconciliationResult> {
-        // Compute diff
-        let diff = self
+        // Compute diff, leaving pods under maintenance alone
+        let mut diff = self
             .diff_engine
             .compute_diff(self.config, Some(state), observed);
+        diff.force_replace(replace);
+        for name in diff.replace_preempted() {
+            info!("Recreating spot pod {name}: preempted by RunPod");
+        }
+        for name in diff.hold(|name| state.maintenance_for(name).is_some()) {
+            info!("Skipping {name}: under maintenance");
+        }
 
         info!(
             "Diff: {} creates, {} updates, {} deletes, {} unchanged",
@@ -195,7 +237,17 @@ impl<'a, S: StateStore> Reconciler<'a, S> {
         }
 
         // Generate plan
-        let plan = DeploymentPlan::from_diff(&diff, self.config, config_hash);
+        let mut plan = DeploymentPlan::from_diff(&diff, self.config, config_hash);
+        if let Some(guardrails) = self
+            .config
+            .guardrails
+            .as_ref()
+            .filter(|g| g.max_hourly_cost.is_some())
+        {
+            let gpu_types = self.provisioner.client().list_gpu_types().await?;
+            let prices = GpuPrices::new(&gpu_types, self.config.project.cloud_type);
+            plan = plan.with_costs(&prices, observed, Some(guardrails));
+        }
 
         if !plan.passes_guardrails {
             return Err(HalldyllError::Reconcile(ReconcileError::Aborted {
@@ -208,7 +260,8 @@ impl<'a, S: StateStore> Reconciler<'a, S> {
 
         // Execute plan
         let executor = PlanExecutor::new(self.provisioner, &self.config.project)
-            .with_continue_on_error(true);
+            .with_continue_on_error(true)
+            .with_load_balancers(self.config);
 
         let execution_result = executor.execute(&plan, state).await?;
 
@@ -258,11 +311,19 @@ impl<'a, S: StateStore> Reconciler<'a, S> {
             .list_project_pods(&self.config.project.name, &self.config.project.environment)
             .await?;
 
-        let diff = self
+        let mut diff = self
             .diff_engine
             .compute_diff(self.config, state.as_ref(), &observed);
 
-        let drifted_resources: Vec<String> = diff
+        // Pods under maintenance are expected to differ; don't report them
+        let in_maintenance = |pod: &str| {
+            state
+                .as_ref()
+                .is_some_and(|s| s.maintenance_for(pod).is_some())
+        };
+        diff.hold(in_maintenance);
+
+        let mut drifted_resources: Vec<String> = diff
             .diffs
             .iter()
             .filter(|d| {
@@ -277,13 +338,196 @@ impl<'a, S: StateStore> Reconciler<'a, S> {
             .map(|d| d.name.clone())
             .collect();
 
+        let mut model_drift = self.check_served_models(state.as_ref(), &observed).await?;
+        model_drift.retain(|drift| !in_maintenance(&drift.pod));
+        for drift in &model_drift {
+            if !drifted_resources.contains(&drift.pod) {
+                drifted_resources.push(drift.pod.clone());
+            }
+        }
+
+        let mut endpoint_drift = if self.probe_endpoints {
+            self.check_endpoints(state.as_ref(), &observed).await?
+        } else {
+            Vec::new()
+        };
+        endpoint_drift.retain(|drift| !in_maintenance(&drift.pod));
+        for drift in &endpoint_drift {
+            if !drifted_resources.contains(&drift.pod) {
+                drifted_resources.push(drift.pod.clone());
+            }
+        }
+
+        let mut maintenance: Vec<String> = state
+            .iter()
+            .flat_map(|s| s.maintenance.keys().cloned())
+            .collect();
+        maintenance.sort();
+
         Ok(DriftReport {
-            has_drift: diff.has_changes(),
+            has_drift: diff.has_changes() || !model_drift.is_empty() || !endpoint_drift.is_empty(),
             drifted_resources,
+            model_drift,
+            endpoint_drift,
+            maintenance,
             total_resources: self.config.pods.len(),
             observed_count: observed.len(),
         })
     }
+
+    /// Compares the models served by running engines with the configured ones.
+    ///
+    /// Pods whose engine cannot be reached (e.g. still loading) are skipped.
+    async fn check_served_models(
+        &self,
+        state: Option<&DeploymentState>,
+        observed: &[ObservedPod],
+    ) -> Result<Vec<ModelDrift>> {
+        let mut drift = Vec::new();
+        let mut probe = None;
+
+        for pod in DiffEngine::desired_instances(self.config, state) {
+            let expected: Vec<String> = pod
+                .models
+                .iter()
+                .filter_map(crate::config::ModelConfig::served_model_name)
+                .collect();
+            if expected.is_empty() {
+                continue;
+            }
+
+            let Some(running) = observed
+                .iter()
+                .find(|p| p.pod_name.as_deref() == Some(pod.name.as_str()) && p.is_running())
+            else {
+                continue;
+            };
+
+            // Engines are started on the pod's primary HTTP port
+            let port = pod.http_ports().first().copied().unwrap_or(8000);
+            let probe = match &mut probe {
+                Some(probe) => probe,
+                None => probe.insert(EngineProbe::new()?),
+            };
+
+            let served = match probe.served_models(running, port).await {
+                Ok(served) => served,
+                Err(e) => {
+                    warn!("Could not list models served by {}: {}", pod.name, e);
+                    continue;
+                }
+            };
+
+            let missing = expected
+                .iter()
+                .any(|e| !served.iter().any(|s| model_matches(s, e)));
+            let unexpected = served
+                .iter()
+                .any(|s| !expected.iter().any(|e| model_matches(s, e)));
+
+            if missing || unexpected {
+                drift.push(ModelDrift {
+                    pod: pod.name.clone(),
+                    expected,
+                    served,
+                });
+            }
+        }
+
+        Ok(drift)
+    }
+
+    /// Probes the endpoints recorded for running pods.
+    ///
+    /// Catches pods that are Running on `RunPod` while the service behind
+    /// a port (typically the inference engine) has died.
+    async fn check_endpoints(
+        &self,
+        state: Option<&DeploymentState>,
+        observed: &[ObservedPod],
+    ) -> Result<Vec<EndpointDrift>> {
+        let checker = HealthChecker::new()?;
+        let mut drift = Vec::new();
+
+        for pod in DiffEngine::desired_instances(self.config, state) {
+            let Some(running) = observed
+                .iter()
+                .find(|p| p.pod_name.as_deref() == Some(pod.name.as_str()) && p.is_running())
+            else {
+                continue;
+            };
+
+            // Prefer what apply recorded; fall back to what RunPod reports now
+            let endpoints = state
+                .and_then(|s| s.get_pod(&pod.name))
+                .map(|p| &p.endpoints)
+                .filter(|endpoints| !endpoints.is_empty())
+                .unwrap_or(&running.endpoints);
+
+            let mut ports: Vec<_> = endpoints.iter().collect();
+            ports.sort_by_key(|(port, _)| **port);
+            for (port, url) in ports {
+                let check = checker.probe_endpoint(*port, url).await;
+                if !check.healthy {
+                    debug!("Endpoint {url} of {} is unreachable", pod.name);
+                    drift.push(EndpointDrift {
+                        pod: pod.name.clone(),
+                        port: *port,
+                        url: url.clone(),
+                        error: check.error.unwrap_or_else(|| String::from("unreachable")),
+                    });
+                }
+            }
+        }
+
+        Ok(drift)
+    }
+}
+
+/// A configured endpoint that did not answer on a running pod.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct EndpointDrift {
+    /// Pod name (replica instance name for replicated pods).
+    pub pod: String,
+    /// Exposed port.
+    pub port: u16,
+    /// Public endpoint URL.
+    pub url: String,
+    /// Why the probe failed.
+    pub error: String,
+}
+
+impl std::fmt::Display for EndpointDrift {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{} ({}) unreachable: {}",
+            self.pod, self.port, self.url, self.error
+        )
+    }
+}
+
+/// Mismatch between configured and served models on a pod.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ModelDrift {
+    /// Pod name (replica instance name for replicated pods).
+    pub pod: String,
+    /// Model names expected from the configuration.
+    pub expected: Vec<String>,
+    /// Model names reported by the engine's `/v1/models`.
+    pub served: Vec<String>,
+}
+
+impl std::fmt::Display for ModelDrift {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: serving [{}], expected [{}]",
+            self.pod,
+            self.served.join(", "),
+            self.expected.join(", ")
+        )
+    }
 }
 
 /// Report of drift detection.
@@ -293,6 +537,14 @@ pub struct DriftReport {
     pub has_drift: bool,
     /// Resources that have drifted.
     pub drifted_resources: Vec<String>,
+    /// Pods serving different models than configured.
+    pub model_drift: Vec<ModelDrift>,
+    /// Configured endpoints that are unreachable on running pods.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub endpoint_drift: Vec<EndpointDrift>,
+    /// Pods under maintenance, left out of the report.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub maintenance: Vec<String>,
     /// Total number of resources in config.
     pub total_resources: usize,
     /// Number of resources observed on `RunPod`.
@@ -314,9 +566,22 @@ impl std::fmt::Display for DriftReport {
             for resource in &self.drifted_resources {
                 writeln!(f, "  - {resource}")?;
             }
+            for models in &self.model_drift {
+                writeln!(f, "  - model mismatch on {models}")?;
+            }
+            for endpoint in &self.endpoint_drift {
+                writeln!(f, "  - endpoint {endpoint}")?;
+            }
         } else {
             write!(f, "No drift detected - state is converged")?;
         }
+        if !self.maintenance.is_empty() {
+            write!(
+                f,
+                "\nNot checked (under maintenance): {}",
+                self.maintenance.join(", ")
+            )?;
+        }
         Ok(())
     }
 }
@@ -340,3 +605,69 @@ impl std::fmt::Display for ReconciliationResult {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runpod::RunPodClient;
+    use crate::state::{LocalStateStore, MaintenanceState};
+    use wiremock::matchers::body_string_contains;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_maintenance_skips_reconcile_and_drift() {
+        let yaml = "project:\n  name: proj\nstate:\n  backend: local\npods:\n  - name: web\n    gpu:\n      type: A40\n    runtime:\n      image: img:2\n";
+        let config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+
+        // RunPod runs `web` with an older image, and no pod may be touched
+        let server = MockServer::start().await;
+        let web = serde_json::json!({
+            "id": "id-web",
+            "name": "proj-dev-web",
+            "desiredStatus": "RUNNING",
+            "imageName": "img:1",
+            "gpuCount": 1,
+            "customTags": {
+                "halldyll.io/project": "proj",
+                "halldyll.io/env": "dev",
+                "halldyll.io/pod": "web",
+            },
+        });
+        Mock::given(body_string_contains("myself"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "data": { "myself": { "pods": [web] } } })),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(body_string_contains("mutation"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let client = RunPodClient::new("test-key")
+            .unwrap()
+            .with_api_url(server.uri());
+        let provisioner = PodProvisioner::new(client.clone());
+        let observer = PodObserver::new(client);
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = LocalStateStore::with_base_dir(dir.path());
+        let reconciler = Reconciler::new(&config, &store, &provisioner, &observer);
+
+        let drift = reconciler.check_drift().await.unwrap();
+        assert_eq!(drift.drifted_resources, ["web"]);
+
+        let mut state = DeploymentState::new("proj", "dev");
+        state.set_maintenance("web", MaintenanceState::new("debugging", "ops"));
+        store.save(&state).await.unwrap();
+
+        let held = reconciler.check_drift().await.unwrap();
+        assert!(!held.has_drift);
+        assert_eq!(held.maintenance, ["web"]);
+
+        let result = reconciler.reconcile().await.unwrap();
+        assert!(result.success);
+        assert_eq!((result.updated, result.unchanged), (0, 1));
+    }
+}
@@ -0,0 +1,257 @@
+//! Container registry client.
+//!
+//! Resolves image tags to content digests through the OCI distribution API,
+//! following the registry's bearer token challenge for anonymous pulls (or
+//! with `HALLDYLL_REGISTRY_TOKEN` for private images).
+
+use reqwest::{Client, StatusCode, header};
+use std::time::Duration;
+use tracing::debug;
+
+use crate::error::{HalldyllError, Result};
+use crate::version::USER_AGENT;
+
+/// Registry used for references without a registry host.
+const DOCKER_HUB_REGISTRY: &str = "registry-1.docker.io";
+
+/// Manifest media types accepted when resolving digests (indexes first).
+const MANIFEST_ACCEPT: &str = "application/vnd.oci.image.index.v1+json, \
+    application/vnd.docker.distribution.manifest.list.v2+json, \
+    application/vnd.oci.image.manifest.v1+json, \
+    application/vnd.docker.distribution.manifest.v2+json";
+
+/// Request timeout in seconds.
+const REGISTRY_TIMEOUT_SECS: u64 = 15;
+
+/// A parsed image reference.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageReference {
+    /// Registry host.
+    pub registry: String,
+    /// Repository path within the registry.
+    pub repository: String,
+    /// Tag (`latest` if not given).
+    pub tag: String,
+    /// Digest, if the reference is already pinned.
+    pub digest: Option<String>,
+}
+
+impl ImageReference {
+    /// Parses a reference such as `vllm/vllm-openai:v0.6.0` or
+    /// `ghcr.io/org/app@sha256:...`.
+    #[must_use]
+    pub fn parse(image: &str) -> Self {
+        let (name, digest) = match image.split_once('@') {
+            Some((name, digest)) => (name, Some(digest.to_string())),
+            None => (image, None),
+        };
+
+        // A tag is the part after the last ':' that is not part of a host:port
+        let (name, tag) = match name.rsplit_once(':') {
+            Some((repo, tag)) if !tag.contains('/') => (repo, tag.to_string()),
+            _ => (name, String::from("latest")),
+        };
+
+        let (registry, repository) = match name.split_once('/') {
+            Some((host, rest))
+                if host.contains('.') || host.contains(':') || host == "localhost" =>
+            {
+                (host.to_string(), rest.to_string())
+            }
+            Some(_) => (DOCKER_HUB_REGISTRY.to_string(), name.to_string()),
+            None => (DOCKER_HUB_REGISTRY.to_string(), format!("library/{name}")),
+        };
+
+        Self {
+            registry,
+            repository,
+            tag,
+            digest,
+        }
+    }
+}
+
+/// Client for querying container registries.
+#[derive(Debug)]
+pub struct RegistryClient {
+    /// HTTP client.
+    client: Client,
+}
+
+impl RegistryClient {
+    /// Creates a new registry client.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP client cannot be created.
+    pub fn new() -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(REGISTRY_TIMEOUT_SECS))
+            .user_agent(USER_AGENT)
+            .build()
+            .map_err(|e| HalldyllError::internal(format!("Failed to create HTTP client: {e}")))?;
+
+        Ok(Self { client })
+    }
+
+    /// Resolves an image reference to its content digest (`sha256:...`).
+    ///
+    /// Pinned references return their digest without a request.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the registry cannot be reached, denies access, or
+    /// does not report a digest.
+    pub async fn resolve_digest(&self, image: &str) -> Result<String> {
+        let reference = ImageReference::parse(image);
+        if let Some(digest) = reference.digest {
+            return Ok(digest);
+        }
+
+        let url = format!(
+            "https://{}/v2/{}/manifests/{}",
+            reference.registry, reference.repository, reference.tag
+        );
+        debug!("Resolving digest of {image} via {url}");
+
+        let mut token = std::env::var("HALLDYLL_REGISTRY_TOKEN").ok();
+        let mut response = self.head_manifest(&url, token.as_deref()).await?;
+
+        if response.status() == StatusCode::UNAUTHORIZED && token.is_none() {
+            let challenge = response
+                .headers()
+                .get(header::WWW_AUTHENTICATE)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or_default()
+                .to_string();
+            token = Some(self.fetch_token(&challenge, image).await?);
+            response = self.head_manifest(&url, token.as_deref()).await?;
+        }
+
+        if !response.status().is_success() {
+            return Err(HalldyllError::internal(format!(
+                "Registry answered {} for {image}",
+                response.status()
+            )));
+        }
+
+        response
+            .headers()
+            .get("docker-content-digest")
+            .and_then(|v| v.to_str().ok())
+            .map(String::from)
+            .ok_or_else(|| {
+                HalldyllError::internal(format!("Registry reported no digest for {image}"))
+            })
+    }
+
+    /// Sends a manifest `HEAD` request.
+    async fn head_manifest(&self, url: &str, token: Option<&str>) -> Result<reqwest::Response> {
+        let mut request = self
+            .client
+            .head(url)
+            .header(header::ACCEPT, MANIFEST_ACCEPT);
+        if let Some(token) = token {
+            request = request.bearer_auth(token);
+        }
+
+        request
+            .send()
+            .await
+            .map_err(|e| HalldyllError::internal(format!("Failed to reach registry: {e}")))
+    }
+
+    /// Obtains an anonymous pull token from a `Bearer` challenge.
+    async fn fetch_token(&self, challenge: &str, image: &str) -> Result<String> {
+        let params = parse_challenge(challenge);
+        let realm = params
+            .iter()
+            .find(|(key, _)| key == "realm")
+            .map(|(_, value)| value.clone())
+            .ok_or_else(|| {
+                HalldyllError::internal(format!("Registry requires credentials for {image}"))
+            })?;
+        let query: Vec<_> = params
+            .into_iter()
+            .filter(|(key, _)| key != "realm")
+            .collect();
+
+        let json: serde_json::Value = self
+            .client
+            .get(&realm)
+            .query(&query)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| {
+                HalldyllError::internal(format!("Failed to get registry token for {image}: {e}"))
+            })?
+            .json()
+            .await
+            .map_err(|e| {
+                HalldyllError::internal(format!("Invalid registry token response: {e}"))
+            })?;
+
+        json.get("token")
+            .or_else(|| json.get("access_token"))
+            .and_then(serde_json::Value::as_str)
+            .map(String::from)
+            .ok_or_else(|| {
+                HalldyllError::internal(format!("Registry returned no token for {image}"))
+            })
+    }
+}
+
+/// Parses the parameters of a `Bearer realm="...",service="...",scope="..."` challenge.
+fn parse_challenge(challenge: &str) -> Vec<(String, String)> {
+    let Some(params) = challenge.strip_prefix("Bearer ") else {
+        return Vec::new();
+    };
+
+    let mut parsed = Vec::new();
+    let mut rest = params;
+    while let Some((key, after)) = rest.split_once("=\"") {
+        let Some((value, remainder)) = after.split_once('"') else {
+            break;
+        };
+        parsed.push((
+            key.trim_start_matches(',').trim().to_string(),
+            value.to_string(),
+        ));
+        rest = remainder;
+    }
+    parsed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_reference_parse() {
+        let hub = ImageReference::parse("vllm/vllm-openai:v0.6.0");
+        assert_eq!(hub.registry, DOCKER_HUB_REGISTRY);
+        assert_eq!(hub.repository, "vllm/vllm-openai");
+        assert_eq!(hub.tag, "v0.6.0");
+
+        assert_eq!(ImageReference::parse("ubuntu").repository, "library/ubuntu");
+        assert_eq!(ImageReference::parse("ubuntu").tag, "latest");
+
+        let private = ImageReference::parse("localhost:5000/app@sha256:abc");
+        assert_eq!(private.registry, "localhost:5000");
+        assert_eq!(private.repository, "app");
+        assert_eq!(private.digest.as_deref(), Some("sha256:abc"));
+
+        let params = parse_challenge(
+            r#"Bearer realm="https://auth.docker.io/token",service="registry.docker.io",scope="repository:library/ubuntu:pull""#,
+        );
+        assert_eq!(
+            params[0],
+            (
+                String::from("realm"),
+                String::from("https://auth.docker.io/token")
+            )
+        );
+        assert_eq!(params[2].0, "scope");
+    }
+}
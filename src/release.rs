@@ -0,0 +1,263 @@
+//! Blue/green releases with an explicit promote step.
+//!
+//! `halldyll deploy --blue-green` creates a full copy of the desired pods
+//! next to the live ones instead of replacing them in place. Release pods are
+//! tagged with the `<env>-<color>` environment, so plans and reconciles of the
+//! live pods never see (or remove) them. `halldyll promote` terminates the
+//! live pods and records the release pods in their place; `halldyll rollback`
+//! terminates the release pods and keeps the live ones.
+
+use chrono::Utc;
+use std::collections::HashMap;
+use tracing::info;
+
+use crate::config::{ConfigHasher, DeployConfig, ProjectConfig};
+use crate::error::{HalldyllError, Result};
+use crate::planner::DiffEngine;
+use crate::runpod::{ObservedPod, PodObserver, PodProvisioner};
+use crate::state::{DeploymentState, PodState, ReleaseColor, ReleaseState};
+
+/// Returns the environment pods of a `color` release of `environment` are tagged with.
+#[must_use]
+pub fn release_environment(environment: &str, color: ReleaseColor) -> String {
+    format!("{environment}-{color}")
+}
+
+/// Returns the project settings pods of a `color` release are provisioned with.
+#[must_use]
+pub fn release_project(project: &ProjectConfig, color: ReleaseColor) -> ProjectConfig {
+    let mut release = project.clone();
+    release.environment = release_environment(&project.environment, color);
+    release
+}
+
+/// Outcome of deploying, promoting or rolling back a release.
+#[derive(Debug, Clone, Default)]
+pub struct ReleaseResult {
+    /// Pods created or terminated, with details.
+    pub changed: Vec<String>,
+    /// Pods that were already in the requested state.
+    pub unchanged: Vec<String>,
+    /// Pods that could not be changed, with the error.
+    pub failed: Vec<(String, String)>,
+}
+
+impl ReleaseResult {
+    /// Returns true if every pod reached the requested state.
+    #[must_use]
+    pub const fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// Creates, promotes and rolls back blue/green releases.
+#[derive(Debug)]
+pub struct ReleaseManager<'a> {
+    /// Deployment configuration.
+    config: &'a DeployConfig,
+    /// Pod provisioner (GPU types must be initialized to deploy).
+    provisioner: &'a PodProvisioner,
+    /// Pod observer.
+    observer: &'a PodObserver,
+}
+
+impl<'a> ReleaseManager<'a> {
+    /// Creates a release manager.
+    #[must_use]
+    pub const fn new(
+        config: &'a DeployConfig,
+        provisioner: &'a PodProvisioner,
+        observer: &'a PodObserver,
+    ) -> Self {
+        Self {
+            config,
+            provisioner,
+            observer,
+        }
+    }
+
+    /// Lists the pods of a `color` release currently on `RunPod`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pods cannot be listed.
+    pub async fn release_pods(&self, color: ReleaseColor) -> Result<Vec<ObservedPod>> {
+        self.observer
+            .list_project_pods(
+                &self.config.project.name,
+                &release_environment(&self.config.project.environment, color),
+            )
+            .await
+    }
+
+    /// Creates the missing pods of a release in the color the live pods are not using.
+    ///
+    /// Re-running continues a release whose pods were not all created; pods
+    /// created before a failure are still recorded in `state`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the existing release pods cannot be listed.
+    pub async fn deploy(&self, state: &mut DeploymentState) -> Result<ReleaseResult> {
+        let hasher = ConfigHasher::new();
+        let config_hash = hasher.hash_config(self.config);
+        let color = state
+            .release
+            .as_ref()
+            .map_or_else(|| state.live_color().other(), |r| r.color);
+        let project = release_project(&self.config.project, color);
+        let existing = self.release_pods(color).await?;
+        let desired = DiffEngine::desired_instances(self.config, Some(state));
+        let release = state.release.get_or_insert_with(|| ReleaseState {
+            color,
+            config_hash: config_hash.clone(),
+            created_at: Utc::now(),
+            pods: HashMap::new(),
+        });
+        release.config_hash = config_hash;
+        let mut result = ReleaseResult::default();
+
+        for pod in desired {
+            if existing
+                .iter()
+                .any(|p| p.pod_name.as_deref() == Some(pod.name.as_str()))
+            {
+                result.unchanged.push(pod.name.clone());
+                continue;
+            }
+
+            let spec_hash = hasher.hash_pod(&pod);
+            match self
+                .provisioner
+                .create_pod(&pod, &project, &spec_hash)
+                .await
+            {
+                Ok(created) => {
+                    result
+                        .changed
+                        .push(format!("{} ({})", pod.name, created.id));
+                    let mut pod_state = PodState::new(&pod.name, &created.id, &spec_hash);
+                    pod_state.gpu_type = created.gpu_type_name().unwrap_or("").to_string();
+                    pod_state.gpu_count = created.gpu_count;
+                    pod_state.image.clone_from(&created.image_name);
+                    pod_state.region.clone_from(&created.data_center_id);
+                    pod_state.color = Some(color);
+                    release.pods.insert(pod.name.clone(), pod_state);
+                }
+                Err(e) => result.failed.push((pod.name.clone(), e.to_string())),
+            }
+        }
+
+        info!("Release {color}: {} pods created", result.changed.len());
+        Ok(result)
+    }
+
+    /// Terminates the live pods and makes the release pods live.
+    ///
+    /// The release only becomes live once every live pod is terminated.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no release to promote.
+    pub async fn promote(&self, state: &mut DeploymentState) -> Result<ReleaseResult> {
+        let release = state.release.clone().ok_or_else(Self::no_release)?;
+        let mut result = ReleaseResult::default();
+
+        let live: Vec<PodState> = state.pods.values().cloned().collect();
+        for pod in live {
+            match self.provisioner.terminate_pod(&pod.runpod_id).await {
+                Ok(()) => {
+                    result
+                        .changed
+                        .push(format!("{} ({})", pod.name, pod.runpod_id));
+                    state.remove_pod(&pod.name);
+                }
+                Err(e) => result.failed.push((pod.name.clone(), e.to_string())),
+            }
+        }
+
+        if result.is_success() {
+            for pod in release.pods.into_values() {
+                state.set_pod(pod);
+            }
+            state.config_hash = release.config_hash;
+            state.release = None;
+            info!("Promoted the {} release", release.color);
+        }
+        Ok(result)
+    }
+
+    /// Terminates the release pods and keeps the live ones.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no release or its pods cannot be listed.
+    pub async fn rollback(&self, state: &mut DeploymentState) -> Result<ReleaseResult> {
+        let color = state.release.as_ref().ok_or_else(Self::no_release)?.color;
+        let mut result = ReleaseResult::default();
+
+        for pod in self.release_pods(color).await? {
+            let name = pod.pod_name.clone().unwrap_or_else(|| pod.name.clone());
+            match self.provisioner.terminate_pod(&pod.id).await {
+                Ok(()) => result.changed.push(format!("{name} ({})", pod.id)),
+                Err(e) => result.failed.push((name, e.to_string())),
+            }
+        }
+
+        if result.is_success() {
+            state.release = None;
+            info!("Rolled back the {color} release");
+        } else if let Some(release) = &mut state.release {
+            release
+                .pods
+                .retain(|name, _| result.failed.iter().any(|(failed, _)| failed == name));
+        }
+        Ok(result)
+    }
+
+    /// Error for promote or rollback without a release.
+    fn no_release() -> HalldyllError {
+        HalldyllError::internal(
+            "No blue/green release in progress; start one with `halldyll deploy --blue-green`",
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_release_colors() {
+        let yaml = r"
+project:
+  name: proj
+  environment: prod
+state:
+  backend: local
+pods: []
+";
+        let config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+        let mut state = DeploymentState::new("proj", "prod");
+        assert_eq!(state.live_color(), ReleaseColor::Blue);
+
+        let color = state.live_color().other();
+        assert_eq!(
+            release_project(&config.project, color).environment,
+            "prod-green"
+        );
+
+        // After promoting green, the next release is blue
+        let mut promoted = PodState::new("llm", "pod-1", "hash");
+        promoted.color = Some(color);
+        state.set_pod(promoted);
+        assert_eq!(state.live_color().other(), ReleaseColor::Blue);
+
+        let json = serde_json::to_string(&state).unwrap();
+        let restored: DeploymentState = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            restored.get_pod("llm").and_then(|p| p.color),
+            Some(ReleaseColor::Green)
+        );
+    }
+}
@@ -2,14 +2,32 @@
 //!
 //! This module provides the HTTP client for interacting with the `RunPod` GraphQL API.
 
-use reqwest::{header, Client};
+use reqwest::{Client, header};
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
-use tracing::{debug, trace};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, Semaphore};
+use tracing::{debug, info, trace, warn};
 
-use crate::error::{HalldyllError, Result, RunPodError};
+use crate::error::{ApiErrorKind, ConfigError, HalldyllError, Result, RunPodError};
+use crate::version::USER_AGENT;
 
-use super::types::{CreatePodRequest, GpuType, Pod, UpdatePodRequest};
+use super::ssh::OutputStream;
+#[cfg(feature = "ssh")]
+use super::ssh_client::SshClient;
+use super::types::{
+    AccountInfo, CreatePodRequest, GpuType, NetworkVolume, Pod, PodTemplate, ServerlessEndpoint,
+    UpdatePodRequest,
+};
+
+/// Base URL of `RunPod`'s exec job API.
+const EXEC_API_URL: &str = "https://api.runpod.ai/v2";
+
+/// Interval between exec job status polls in seconds.
+const EXEC_POLL_INTERVAL_SECS: u64 = 2;
+
+/// Interval between progress reports for a running exec job in seconds.
+const EXEC_HEARTBEAT_SECS: u64 = 30;
 
 /// `RunPod` API base URL.
 const RUNPOD_API_URL: &str = "https://api.runpod.io/graphql";
@@ -23,6 +41,12 @@ const MAX_RETRIES: u32 = 3;
 /// Delay between retries in milliseconds.
 const RETRY_DELAY_MS: u64 = 1000;
 
+/// Default number of API requests in flight at once.
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 4;
+
+/// How long a fetched GPU type list answers availability checks, in seconds.
+const GPU_TYPES_CACHE_SECS: u64 = 30;
+
 /// `RunPod` API client.
 #[derive(Debug, Clone)]
 pub struct RunPodClient {
@@ -30,6 +54,36 @@ pub struct RunPodClient {
     client: Client,
     /// API key.
     api_key: String,
+    /// GraphQL endpoint the API calls go to.
+    api_url: String,
+    /// Base URL of the exec job API.
+    exec_url: String,
+    /// Extra headers sent with every request.
+    headers: header::HeaderMap,
+    /// SSH access to pods, if a key is configured.
+    #[cfg(feature = "ssh")]
+    ssh: Option<Arc<SshClient>>,
+    /// Limits the API requests in flight across clones of the client.
+    requests: Arc<Semaphore>,
+    /// Recently fetched GPU types, shared across clones of the client.
+    gpu_types: Arc<Mutex<Option<CachedGpuTypes>>>,
+}
+
+/// GPU types fetched by [`RunPodClient::list_gpu_types`].
+#[derive(Debug)]
+struct CachedGpuTypes {
+    /// When the list was fetched.
+    fetched: Instant,
+    /// The GPU types.
+    gpu_types: Vec<GpuType>,
+}
+
+impl CachedGpuTypes {
+    /// Returns the GPU types if they were fetched recently enough.
+    fn fresh(&self) -> Option<&[GpuType]> {
+        (self.fetched.elapsed() < Duration::from_secs(GPU_TYPES_CACHE_SECS))
+            .then_some(self.gpu_types.as_slice())
+    }
 }
 
 /// GraphQL request structure.
@@ -51,6 +105,14 @@ struct GraphQLResponse<T> {
 #[derive(Debug, Deserialize)]
 struct GraphQLError {
     message: String,
+    #[serde(default)]
+    extensions: Option<GraphQLErrorExtensions>,
+}
+
+/// Extensions of a GraphQL error.
+#[derive(Debug, Deserialize)]
+struct GraphQLErrorExtensions {
+    code: Option<String>,
 }
 
 impl RunPodClient {
@@ -60,15 +122,7 @@ impl RunPodClient {
     ///
     /// Returns an error if the HTTP client cannot be created.
     pub fn new(api_key: &str) -> Result<Self> {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(DEFAULT_TIMEOUT_SECS))
-            .build()
-            .map_err(|e| RunPodError::network(format!("Failed to create HTTP client: {e}")))?;
-
-        Ok(Self {
-            client,
-            api_key: api_key.to_string(),
-        })
+        Self::with_timeout(api_key, DEFAULT_TIMEOUT_SECS)
     }
 
     /// Creates a client with a custom timeout.
@@ -79,15 +133,77 @@ impl RunPodClient {
     pub fn with_timeout(api_key: &str, timeout_secs: u64) -> Result<Self> {
         let client = Client::builder()
             .timeout(Duration::from_secs(timeout_secs))
+            .user_agent(USER_AGENT)
             .build()
             .map_err(|e| RunPodError::network(format!("Failed to create HTTP client: {e}")))?;
 
         Ok(Self {
             client,
             api_key: api_key.to_string(),
+            api_url: RUNPOD_API_URL.to_string(),
+            exec_url: EXEC_API_URL.to_string(),
+            headers: header::HeaderMap::new(),
+            #[cfg(feature = "ssh")]
+            ssh: None,
+            requests: Arc::new(Semaphore::new(DEFAULT_MAX_CONCURRENT_REQUESTS)),
+            gpu_types: Arc::new(Mutex::new(None)),
         })
     }
 
+    /// Sets how many API requests may be in flight at once.
+    ///
+    /// Parallel applies queue behind this limit instead of tripping
+    /// `RunPod`'s rate limiting.
+    #[must_use]
+    pub fn with_max_concurrent_requests(mut self, max: usize) -> Self {
+        self.requests = Arc::new(Semaphore::new(max.max(1)));
+        self
+    }
+
+    /// Runs pod commands over SSH when the pod exposes `22/tcp`.
+    #[cfg(feature = "ssh")]
+    #[must_use]
+    pub fn with_ssh(mut self, ssh: SshClient) -> Self {
+        self.ssh = Some(Arc::new(ssh));
+        self
+    }
+
+    /// Sends API calls to `url` instead of `RunPod`'s GraphQL endpoint.
+    #[must_use]
+    pub fn with_api_url(mut self, url: impl Into<String>) -> Self {
+        self.api_url = url.into();
+        self
+    }
+
+    /// Sends pod commands to `url` instead of `RunPod`'s exec job API.
+    #[must_use]
+    pub fn with_exec_url(mut self, url: impl Into<String>) -> Self {
+        self.exec_url = url.into();
+        self
+    }
+
+    /// Adds a header sent with every API call (e.g. for proxy routing).
+    ///
+    /// Setting `User-Agent` replaces the default `halldyll/<version>`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name or value is not a valid HTTP header.
+    pub fn with_header(mut self, name: &str, value: &str) -> Result<Self> {
+        let invalid = |e: &dyn std::fmt::Display| {
+            HalldyllError::Config(ConfigError::validation(
+                format!("Invalid header '{name}': {e}"),
+                "headers",
+            ))
+        };
+        let header_name =
+            header::HeaderName::from_bytes(name.as_bytes()).map_err(|e| invalid(&e))?;
+        let header_value = header::HeaderValue::from_str(value).map_err(|e| invalid(&e))?;
+
+        self.headers.insert(header_name, header_value);
+        Ok(self)
+    }
+
     /// Executes a GraphQL query.
     async fn execute<T: for<'de> Deserialize<'de>>(
         &self,
@@ -99,13 +215,19 @@ impl RunPodClient {
             variables,
         };
 
-        let mut last_error = None;
+        let mut last_error: Option<HalldyllError> = None;
 
         for attempt in 0..MAX_RETRIES {
             if attempt > 0 {
                 debug!("Retry attempt {attempt} of {MAX_RETRIES}");
-                tokio::time::sleep(Duration::from_millis(RETRY_DELAY_MS * u64::from(attempt)))
-                    .await;
+                // Back off as long as the API asked when rate limited
+                let delay = match last_error.as_ref().map(HalldyllError::root) {
+                    Some(HalldyllError::RunPod(RunPodError::RateLimited { retry_after_secs })) => {
+                        Duration::from_secs(*retry_after_secs)
+                    }
+                    _ => Duration::from_millis(RETRY_DELAY_MS * u64::from(attempt)),
+                };
+                tokio::time::sleep(delay).await;
             }
 
             match self.execute_once::<T>(&request).await {
@@ -134,11 +256,17 @@ impl RunPodClient {
     ) -> Result<T> {
         trace!("Executing GraphQL query: {}", request.query);
 
+        let _permit = self
+            .requests
+            .acquire()
+            .await
+            .map_err(|e| RunPodError::network(e.to_string()))?;
         let response = self
             .client
-            .post(RUNPOD_API_URL)
+            .post(&self.api_url)
             .header(header::CONTENT_TYPE, "application/json")
             .header(header::AUTHORIZATION, format!("Bearer {}", self.api_key))
+            .headers(self.headers.clone())
             .json(request)
             .send()
             .await
@@ -172,7 +300,7 @@ impl RunPodClient {
 
         if !status.is_success() {
             let body = response.text().await.unwrap_or_default();
-            return Err(HalldyllError::RunPod(RunPodError::api_error(
+            return Err(HalldyllError::RunPod(RunPodError::from_api_response(
                 status.as_u16(),
                 body,
             )));
@@ -190,7 +318,15 @@ impl RunPodClient {
                 .map(|e| e.message.as_str())
                 .collect::<Vec<_>>()
                 .join("; ");
-            return Err(HalldyllError::RunPod(RunPodError::api_error(400, message)));
+            // The first recognized code decides the kind; messages are kept together
+            let code = errors
+                .iter()
+                .filter_map(|e| e.extensions.as_ref()?.code.as_deref())
+                .find(|code| ApiErrorKind::classify(Some(code), "").is_some())
+                .map(String::from);
+            return Err(HalldyllError::RunPod(RunPodError::from_graphql(
+                code, message,
+            )));
         }
 
         gql_response.data.ok_or_else(|| {
@@ -320,19 +456,27 @@ impl RunPodClient {
 
     /// Creates a new pod.
     ///
+    /// Requests with a bid per GPU are rented as interruptible (spot) pods.
+    ///
     /// # Errors
     ///
     /// Returns an error if the pod cannot be created.
     pub async fn create_pod(&self, request: &CreatePodRequest) -> Result<Pod> {
         #[derive(Deserialize)]
         struct Response {
-            #[serde(rename = "podFindAndDeployOnDemand")]
+            #[serde(rename = "podFindAndDeployOnDemand", alias = "podRentInterruptable")]
             pod: Pod,
         }
 
-        let query = r"
-            mutation CreatePod($input: PodFindAndDeployOnDemandInput!) {
-                podFindAndDeployOnDemand(input: $input) {
+        let (mutation, input_type) = if request.bid_per_gpu.is_some() {
+            ("podRentInterruptable", "PodRentInterruptableInput")
+        } else {
+            ("podFindAndDeployOnDemand", "PodFindAndDeployOnDemandInput")
+        };
+        let query = format!(
+            r"
+            mutation CreatePod($input: {input_type}!) {{
+                {mutation}(input: $input) {{
                     id
                     name
                     desiredStatus
@@ -343,18 +487,19 @@ impl RunPodClient {
                     memoryInGb
                     vcpuCount
                     ports
-                    machine {
+                    machine {{
                         gpuTypeId
-                    }
-                    env {
+                    }}
+                    env {{
                         key
                         value
-                    }
-                }
-            }
-        ";
+                    }}
+                }}
+            }}
+        "
+        );
 
-        let input = serde_json::json!({
+        let mut input = serde_json::json!({
             "cloudType": request.cloud_type,
             "gpuTypeId": request.gpu_type_id,
             "gpuCount": request.gpu_count,
@@ -369,10 +514,15 @@ impl RunPodClient {
             }).collect::<Vec<_>>(),
             "dockerArgs": request.docker_args,
             "dataCenterId": request.data_center_id,
+            "networkVolumeId": request.network_volume_id,
+            "templateId": request.template_id,
         });
+        if let Some(bid) = request.bid_per_gpu {
+            input["bidPerGpu"] = serde_json::json!(bid);
+        }
 
         let variables = serde_json::json!({ "input": input });
-        let response: Response = self.execute(query, Some(variables)).await?;
+        let response: Response = self.execute(&query, Some(variables)).await?;
 
         Ok(response.pod)
     }
@@ -454,6 +604,105 @@ impl RunPodClient {
         let variables = serde_json::json!({ "podId": pod_id });
         let _: Response = self.execute(query, Some(variables)).await?;
 
+        #[cfg(feature = "ssh")]
+        if let Some(ssh) = &self.ssh {
+            ssh.forget_host(pod_id);
+        }
+
+        Ok(())
+    }
+
+    /// Lists the account's network volumes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the API call fails.
+    pub async fn list_network_volumes(&self) -> Result<Vec<NetworkVolume>> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Myself {
+            #[serde(default)]
+            network_volumes: Vec<NetworkVolume>,
+        }
+        #[derive(Deserialize)]
+        struct Response {
+            myself: Myself,
+        }
+
+        let query = r"
+            query {
+                myself {
+                    networkVolumes {
+                        id
+                        name
+                        size
+                        dataCenterId
+                    }
+                }
+            }
+        ";
+
+        let response: Response = self.execute(query, None).await?;
+        Ok(response.myself.network_volumes)
+    }
+
+    /// Creates a network volume of `size_gb` in `data_center_id`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the volume cannot be created.
+    pub async fn create_network_volume(
+        &self,
+        name: &str,
+        size_gb: u32,
+        data_center_id: &str,
+    ) -> Result<NetworkVolume> {
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "createNetworkVolume")]
+            volume: NetworkVolume,
+        }
+
+        let query = r"
+            mutation CreateNetworkVolume($input: CreateNetworkVolumeInput!) {
+                createNetworkVolume(input: $input) {
+                    id
+                    name
+                    size
+                    dataCenterId
+                }
+            }
+        ";
+
+        let variables = serde_json::json!({
+            "input": { "name": name, "size": size_gb, "dataCenterId": data_center_id }
+        });
+        let response: Response = self.execute(query, Some(variables)).await?;
+        Ok(response.volume)
+    }
+
+    /// Deletes a network volume.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the volume cannot be deleted (e.g. it is still
+    /// attached to a pod).
+    pub async fn delete_network_volume(&self, volume_id: &str) -> Result<()> {
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "deleteNetworkVolume")]
+            _result: Option<serde_json::Value>,
+        }
+
+        let query = r"
+            mutation DeleteNetworkVolume($id: String!) {
+                deleteNetworkVolume(input: { id: $id })
+            }
+        ";
+
+        let variables = serde_json::json!({ "id": volume_id });
+        let _: Response = self.execute(query, Some(variables)).await?;
+
         Ok(())
     }
 
@@ -487,10 +736,11 @@ impl RunPodClient {
         }
 
         if let Some(env) = &request.env {
-            input["env"] = serde_json::json!(env
-                .iter()
-                .map(|e| serde_json::json!({ "key": e.key, "value": e.value }))
-                .collect::<Vec<_>>());
+            input["env"] = serde_json::json!(
+                env.iter()
+                    .map(|e| serde_json::json!({ "key": e.key, "value": e.value }))
+                    .collect::<Vec<_>>()
+            );
         }
 
         let variables = serde_json::json!({ "input": input });
@@ -499,12 +749,265 @@ impl RunPodClient {
         Ok(response.pod)
     }
 
+    /// Lists the pod templates saved on the account.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the API call fails.
+    pub async fn list_templates(&self) -> Result<Vec<PodTemplate>> {
+        #[derive(Deserialize)]
+        struct Myself {
+            #[serde(rename = "podTemplates", default)]
+            templates: Vec<PodTemplate>,
+        }
+
+        #[derive(Deserialize)]
+        struct Response {
+            myself: Myself,
+        }
+
+        let query = r"
+            query {
+                myself {
+                    podTemplates {
+                        id
+                        name
+                        imageName
+                        containerDiskInGb
+                        volumeInGb
+                        volumeMountPath
+                        ports
+                        env {
+                            key
+                            value
+                        }
+                        dockerArgs
+                        readme
+                        isServerless
+                    }
+                }
+            }
+        ";
+
+        let response: Response = self.execute(query, None).await?;
+        Ok(response.myself.templates)
+    }
+
+    /// Creates a pod template, or updates it in place when `template.id` is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the template cannot be saved.
+    pub async fn save_template(&self, template: &PodTemplate) -> Result<PodTemplate> {
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "saveTemplate")]
+            template: PodTemplate,
+        }
+
+        let query = r"
+            mutation SaveTemplate($input: SaveTemplateInput!) {
+                saveTemplate(input: $input) {
+                    id
+                    name
+                    imageName
+                    containerDiskInGb
+                    volumeInGb
+                    volumeMountPath
+                    ports
+                    env {
+                        key
+                        value
+                    }
+                    dockerArgs
+                    readme
+                    isServerless
+                }
+            }
+        ";
+
+        let mut input = serde_json::json!({
+            "name": template.name,
+            "imageName": template.image_name,
+            "containerDiskInGb": template.container_disk_in_gb,
+            "volumeInGb": template.volume_in_gb,
+            "volumeMountPath": template.volume_mount_path,
+            "ports": template.ports.as_deref().unwrap_or_default(),
+            "env": template.env.iter().map(|e| {
+                serde_json::json!({ "key": e.key, "value": e.value })
+            }).collect::<Vec<_>>(),
+            "dockerArgs": template.docker_args.as_deref().unwrap_or_default(),
+            "readme": template.readme.as_deref().unwrap_or_default(),
+            "isServerless": template.is_serverless,
+        });
+        if let Some(id) = &template.id {
+            input["id"] = serde_json::json!(id);
+        }
+
+        let variables = serde_json::json!({ "input": input });
+        let response: Response = self.execute(query, Some(variables)).await?;
+
+        Ok(response.template)
+    }
+
+    /// Deletes the template named `name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the template cannot be deleted (e.g., pods or
+    /// endpoints still use it).
+    pub async fn delete_template(&self, name: &str) -> Result<()> {
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "deleteTemplate")]
+            _result: Option<serde_json::Value>,
+        }
+
+        let query = r"
+            mutation DeleteTemplate($templateName: String!) {
+                deleteTemplate(templateName: $templateName)
+            }
+        ";
+
+        let variables = serde_json::json!({ "templateName": name });
+        let _: Response = self.execute(query, Some(variables)).await?;
+
+        Ok(())
+    }
+
+    /// Lists the serverless endpoints on the account.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the API call fails.
+    pub async fn list_endpoints(&self) -> Result<Vec<ServerlessEndpoint>> {
+        #[derive(Deserialize)]
+        struct Myself {
+            #[serde(default)]
+            endpoints: Vec<ServerlessEndpoint>,
+        }
+        #[derive(Deserialize)]
+        struct Response {
+            myself: Myself,
+        }
+
+        let query = r"
+            query {
+                myself {
+                    endpoints {
+                        id
+                        name
+                        templateId
+                        gpuIds
+                        gpuCount
+                        workersMin
+                        workersMax
+                        idleTimeout
+                    }
+                }
+            }
+        ";
+
+        let response: Response = self.execute(query, None).await?;
+        Ok(response.myself.endpoints)
+    }
+
+    /// Creates a serverless endpoint, or updates it in place when `endpoint.id` is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the endpoint cannot be saved.
+    pub async fn save_endpoint(&self, endpoint: &ServerlessEndpoint) -> Result<ServerlessEndpoint> {
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "saveEndpoint")]
+            endpoint: ServerlessEndpoint,
+        }
+
+        let query = r"
+            mutation SaveEndpoint($input: EndpointInput!) {
+                saveEndpoint(input: $input) {
+                    id
+                    name
+                    templateId
+                    gpuIds
+                    gpuCount
+                    workersMin
+                    workersMax
+                    idleTimeout
+                }
+            }
+        ";
+
+        let mut input = serde_json::json!({
+            "name": endpoint.name,
+            "templateId": endpoint.template_id,
+            "gpuIds": endpoint.gpu_ids,
+            "gpuCount": endpoint.gpu_count,
+            "workersMin": endpoint.workers_min,
+            "workersMax": endpoint.workers_max,
+            "idleTimeout": endpoint.idle_timeout,
+        });
+        if let Some(id) = &endpoint.id {
+            input["id"] = serde_json::json!(id);
+        }
+
+        let variables = serde_json::json!({ "input": input });
+        let response: Response = self.execute(query, Some(variables)).await?;
+
+        Ok(response.endpoint)
+    }
+
+    /// Deletes a serverless endpoint.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the endpoint cannot be deleted.
+    pub async fn delete_endpoint(&self, endpoint_id: &str) -> Result<()> {
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "deleteEndpoint")]
+            _result: Option<serde_json::Value>,
+        }
+
+        let query = r"
+            mutation DeleteEndpoint($id: String!) {
+                deleteEndpoint(id: $id)
+            }
+        ";
+
+        let variables = serde_json::json!({ "id": endpoint_id });
+        let _: Response = self.execute(query, Some(variables)).await?;
+
+        Ok(())
+    }
+
     /// Gets available GPU types.
     ///
+    /// The list is reused for a few seconds, so the availability checks of
+    /// pods created together share one request.
+    ///
     /// # Errors
     ///
     /// Returns an error if the API call fails.
     pub async fn list_gpu_types(&self) -> Result<Vec<GpuType>> {
+        // Holding the lock makes concurrent callers wait for a single fetch
+        let mut cached = self.gpu_types.lock().await;
+        if let Some(gpu_types) = cached.as_ref().and_then(CachedGpuTypes::fresh) {
+            return Ok(gpu_types.to_vec());
+        }
+
+        let gpu_types = self.fetch_gpu_types().await?;
+        *cached = Some(CachedGpuTypes {
+            fetched: Instant::now(),
+            gpu_types: gpu_types.clone(),
+        });
+        drop(cached);
+        Ok(gpu_types)
+    }
+
+    /// Fetches the GPU types from the API.
+    async fn fetch_gpu_types(&self) -> Result<Vec<GpuType>> {
         #[derive(Deserialize)]
         struct Response {
             #[serde(rename = "gpuTypes")]
@@ -529,6 +1032,31 @@ impl RunPodClient {
         Ok(response.gpu_types)
     }
 
+    /// Gets the account balance and current spend rate.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the API call fails.
+    pub async fn get_account(&self) -> Result<AccountInfo> {
+        #[derive(Deserialize)]
+        struct Response {
+            myself: AccountInfo,
+        }
+
+        let query = r"
+            query {
+                myself {
+                    clientBalance
+                    currentSpendPerHr
+                    spendLimit
+                }
+            }
+        ";
+
+        let response: Response = self.execute(query, None).await?;
+        Ok(response.myself)
+    }
+
     /// Checks if a GPU type is available.
     ///
     /// # Errors
@@ -582,7 +1110,7 @@ impl RunPodClient {
         }
     }
 
-    /// Executes a command on a running pod using RunPod's exec API.
+    /// Executes a command on a running pod.
     ///
     /// # Errors
     ///
@@ -593,13 +1121,68 @@ impl RunPodClient {
         command: &str,
         timeout_secs: u64,
     ) -> Result<super::executor::CommandResult> {
-        use super::executor::CommandResult;
+        self.exec_command_streaming(pod_id, command, timeout_secs, |_, _| {})
+            .await
+    }
 
-        // RunPod uses a REST API for pod exec, not GraphQL
-        let _exec_url = format!(
-            "https://api.runpod.ai/v2/{}/run",
-            pod_id
-        );
+    /// Executes a command on a running pod, passing output chunks to
+    /// `on_output` as they arrive.
+    ///
+    /// Output streams live over SSH; the exec API only returns it once the
+    /// command has finished.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the command cannot be executed.
+    pub async fn exec_command_streaming(
+        &self,
+        pod_id: &str,
+        command: &str,
+        timeout_secs: u64,
+        mut on_output: impl FnMut(OutputStream, &str) + Send,
+    ) -> Result<super::executor::CommandResult> {
+        // SSH runs the command directly when a key is configured
+        #[cfg(feature = "ssh")]
+        if let Some(ssh) = &self.ssh {
+            let pod = self.get_pod(pod_id).await?;
+            if let Some((host, port)) = pod.ssh_endpoint() {
+                return ssh
+                    .exec(
+                        pod_id,
+                        &host,
+                        port,
+                        command,
+                        Duration::from_secs(timeout_secs),
+                        on_output,
+                    )
+                    .await;
+            }
+            debug!("Pod {pod_id} does not expose SSH, using the exec API");
+        }
+
+        let result = self.exec_command_api(pod_id, command, timeout_secs).await?;
+        for (stream, output) in [
+            (OutputStream::Stdout, &result.stdout),
+            (OutputStream::Stderr, &result.stderr),
+        ] {
+            if !output.is_empty() {
+                on_output(stream, output);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Executes a command on a running pod using `RunPod`'s exec API.
+    ///
+    /// Commands that outlast the synchronous call come back as a job, which
+    /// is polled until it finishes or `timeout_secs` runs out.
+    async fn exec_command_api(
+        &self,
+        pod_id: &str,
+        command: &str,
+        timeout_secs: u64,
+    ) -> Result<super::executor::CommandResult> {
+        use super::executor::CommandResult;
 
         #[derive(Serialize)]
         struct ExecRequest {
@@ -611,27 +1194,11 @@ impl RunPodClient {
             command: String,
         }
 
-        #[derive(Deserialize)]
-        struct ExecResponse {
-            id: Option<String>,
-            #[allow(dead_code)]
-            status: Option<String>,
-            output: Option<ExecOutput>,
-            error: Option<String>,
-        }
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(timeout_secs);
 
-        #[derive(Deserialize)]
-        struct ExecOutput {
-            stdout: Option<String>,
-            stderr: Option<String>,
-            exit_code: Option<i32>,
-        }
-
-        // First, try using the runsync endpoint for immediate execution
-        let runsync_url = format!(
-            "https://api.runpod.ai/v2/{}/runsync",
-            pod_id
-        );
+        // RunPod uses a REST API for pod exec, not GraphQL.
+        // Try the runsync endpoint first for immediate execution.
+        let runsync_url = format!("{}/{pod_id}/runsync", self.exec_url);
 
         let request = ExecRequest {
             input: ExecInput {
@@ -660,87 +1227,111 @@ impl RunPodClient {
             return self.exec_via_ssh(pod_id, command).await;
         }
 
-        let exec_response: ExecResponse = response.json().await.map_err(|e| {
+        let job: ExecJob = response.json().await.map_err(|e| {
             HalldyllError::RunPod(RunPodError::InvalidResponse {
                 message: format!("Failed to parse exec response: {e}"),
             })
         })?;
 
-        if let Some(error) = exec_response.error {
-            return Ok(CommandResult {
-                success: false,
-                stdout: String::new(),
-                stderr: error,
-                exit_code: Some(1),
-            });
+        if let Some(result) = job.result() {
+            return Ok(result);
         }
 
-        if let Some(output) = exec_response.output {
-            let exit_code = output.exit_code.unwrap_or(0);
-            return Ok(CommandResult {
-                success: exit_code == 0,
-                stdout: output.stdout.unwrap_or_default(),
-                stderr: output.stderr.unwrap_or_default(),
-                exit_code: Some(exit_code),
-            });
-        }
-
-        // If we got a job ID, we need to poll for results
-        if let Some(_job_id) = exec_response.id {
-            // For now, assume it succeeded if we got this far
-            Ok(CommandResult {
-                success: true,
-                stdout: String::new(),
-                stderr: String::new(),
-                exit_code: None,
-            })
-        } else {
-            Ok(CommandResult {
+        // The command is still running as a job
+        match job.id {
+            Some(job_id) => {
+                self.poll_exec_job(pod_id, &job_id, deadline, timeout_secs)
+                    .await
+            }
+            None => Ok(CommandResult {
                 success: false,
                 stdout: String::new(),
                 stderr: "No output or job ID received".to_string(),
                 exit_code: None,
-            })
+            }),
         }
     }
 
-    /// Executes a command via SSH on a pod.
-    /// This is a fallback when the RunPod exec API is not available.
-    async fn exec_via_ssh(
+    /// Polls an exec job until it finishes, cancelling it at `deadline`.
+    async fn poll_exec_job(
         &self,
         pod_id: &str,
-        command: &str,
+        job_id: &str,
+        deadline: tokio::time::Instant,
+        timeout_secs: u64,
     ) -> Result<super::executor::CommandResult> {
-        use super::executor::CommandResult;
+        let status_url = format!("{}/{pod_id}/status/{job_id}", self.exec_url);
+        let started = tokio::time::Instant::now();
+        let mut next_heartbeat = started + Duration::from_secs(EXEC_HEARTBEAT_SECS);
+        // Cancels the job if this future is dropped before it finishes
+        let mut guard = ExecJobGuard {
+            client: self.client.clone(),
+            cancel_url: format!("{}/{pod_id}/cancel/{job_id}", self.exec_url),
+            api_key: self.api_key.clone(),
+            armed: true,
+        };
 
-        // Get pod details to find SSH endpoint
-        let pod = self.get_pod(pod_id).await?;
+        debug!("Polling exec job {job_id} on {pod_id}");
+        loop {
+            let now = tokio::time::Instant::now();
+            if now >= deadline {
+                guard.cancel().await;
+                return Err(HalldyllError::RunPod(RunPodError::ExecTimeout {
+                    pod_id: pod_id.to_string(),
+                    job_id: job_id.to_string(),
+                    timeout_secs,
+                }));
+            }
+            tokio::time::sleep_until(
+                deadline.min(now + Duration::from_secs(EXEC_POLL_INTERVAL_SECS)),
+            )
+            .await;
+
+            let response = self
+                .client
+                .get(&status_url)
+                .header(header::AUTHORIZATION, format!("Bearer {}", self.api_key))
+                .timeout(Duration::from_secs(DEFAULT_TIMEOUT_SECS))
+                .send()
+                .await
+                .and_then(reqwest::Response::error_for_status);
+            let job: ExecJob = match response {
+                Ok(response) => response.json().await.map_err(|e| {
+                    HalldyllError::RunPod(RunPodError::InvalidResponse {
+                        message: format!("Failed to parse exec job status: {e}"),
+                    })
+                })?,
+                Err(e) => {
+                    // A missed poll is retried on the next interval
+                    debug!("Exec job {job_id} status request failed: {e}");
+                    continue;
+                }
+            };
 
-        // For RunPod pods, commands can be executed through the web terminal API
-        // or by connecting to the pod's public IP if SSH is enabled
-        
-        // Check if pod has SSH port exposed
-        let ssh_available = pod.ports.as_ref()
-            .map(|p| p.contains("22"))
-            .unwrap_or(false);
+            if let Some(result) = job.result() {
+                guard.armed = false;
+                return Ok(result);
+            }
 
-        if !ssh_available {
-            return Ok(CommandResult {
-                success: false,
-                stdout: String::new(),
-                stderr: "SSH not available on this pod. Enable port 22/tcp in your config.".to_string(),
-                exit_code: Some(1),
-            });
+            if tokio::time::Instant::now() >= next_heartbeat {
+                info!(
+                    "Command on {pod_id} still {} after {}s (job {job_id})",
+                    job.status.as_deref().unwrap_or("running").to_lowercase(),
+                    started.elapsed().as_secs()
+                );
+                next_heartbeat += Duration::from_secs(EXEC_HEARTBEAT_SECS);
+            }
         }
+    }
 
-        // Note: Actual SSH execution would require an SSH library like thrussh or openssh
-        // For now, we'll use RunPod's web-based execution method
-        
-        // RunPod provides a way to execute commands via their internal API
-        // This endpoint may vary based on pod type
-        let _internal_exec_url = format!(
-            "https://api.runpod.io/graphql"
-        );
+    /// Executes a command on a pod with SSH exposed through the GraphQL exec.
+    /// This is a fallback when the `RunPod` exec API is not available.
+    async fn exec_via_ssh(
+        &self,
+        pod_id: &str,
+        command: &str,
+    ) -> Result<super::executor::CommandResult> {
+        use super::executor::CommandResult;
 
         #[derive(Deserialize)]
         struct ExecResponse {
@@ -756,7 +1347,27 @@ impl RunPodClient {
             exit_code: Option<i32>,
         }
 
-        let query = r#"
+        // Get pod details to find SSH endpoint
+        let pod = self.get_pod(pod_id).await?;
+
+        // For RunPod pods, commands can be executed through the web terminal API
+        // or by connecting to the pod's public IP if SSH is enabled
+
+        // Check if pod has SSH port exposed
+        let ssh_available = pod.ports.as_ref().is_some_and(|p| p.contains("22"));
+
+        if !ssh_available {
+            return Ok(CommandResult {
+                success: false,
+                stdout: String::new(),
+                stderr: "SSH not available on this pod. Enable port 22/tcp in your config."
+                    .to_string(),
+                exit_code: Some(1),
+            });
+        }
+
+        // Without a key (RUNPOD_SSH_KEY), fall back to RunPod's GraphQL exec
+        let query = r"
             mutation PodExec($podId: String!, $command: String!) {
                 podExec(input: { podId: $podId, command: $command }) {
                     stdout
@@ -764,7 +1375,7 @@ impl RunPodClient {
                     exitCode
                 }
             }
-        "#;
+        ";
 
         let variables = serde_json::json!({
             "podId": pod_id,
@@ -772,8 +1383,16 @@ impl RunPodClient {
         });
 
         match self.execute::<ExecResponse>(query, Some(variables)).await {
-            Ok(response) => {
-                if let Some(result) = response.pod_exec {
+            Ok(response) => response.pod_exec.map_or_else(
+                || {
+                    Ok(CommandResult {
+                        success: false,
+                        stdout: String::new(),
+                        stderr: "No exec result returned".to_string(),
+                        exit_code: None,
+                    })
+                },
+                |result| {
                     let exit_code = result.exit_code.unwrap_or(0);
                     Ok(CommandResult {
                         success: exit_code == 0,
@@ -781,15 +1400,8 @@ impl RunPodClient {
                         stderr: result.stderr.unwrap_or_default(),
                         exit_code: Some(exit_code),
                     })
-                } else {
-                    Ok(CommandResult {
-                        success: false,
-                        stdout: String::new(),
-                        stderr: "No exec result returned".to_string(),
-                        exit_code: None,
-                    })
-                }
-            }
+                },
+            ),
             Err(_) => {
                 // If GraphQL exec fails, the pod might not support it
                 // Return a helpful message
@@ -798,8 +1410,7 @@ impl RunPodClient {
                     stdout: String::new(),
                     stderr: format!(
                         "Command execution not available. Ensure the pod is running and supports exec. \
-                        Command attempted: {}",
-                        command
+                        Command attempted: {command}"
                     ),
                     exit_code: Some(1),
                 })
@@ -807,3 +1418,206 @@ impl RunPodClient {
         }
     }
 }
+
+/// Exec job as returned by the runsync and status endpoints.
+#[derive(Debug, Deserialize)]
+struct ExecJob {
+    /// Job ID, when the command is still running.
+    id: Option<String>,
+    /// Job status (`IN_QUEUE`, `IN_PROGRESS`, `COMPLETED`, `FAILED`, ...).
+    status: Option<String>,
+    /// Command output once finished.
+    output: Option<ExecOutput>,
+    /// Error reported for the job.
+    error: Option<String>,
+}
+
+/// Output of a finished exec job.
+#[derive(Debug, Deserialize)]
+struct ExecOutput {
+    /// Standard output.
+    stdout: Option<String>,
+    /// Standard error.
+    stderr: Option<String>,
+    /// Exit code.
+    exit_code: Option<i32>,
+}
+
+impl ExecJob {
+    /// Returns the command result once the job has finished.
+    fn result(&self) -> Option<super::executor::CommandResult> {
+        use super::executor::CommandResult;
+
+        if let Some(error) = &self.error {
+            return Some(CommandResult {
+                success: false,
+                stdout: String::new(),
+                stderr: error.clone(),
+                exit_code: Some(1),
+            });
+        }
+
+        if let Some(output) = &self.output {
+            let exit_code = output.exit_code.unwrap_or(0);
+            return Some(CommandResult {
+                success: exit_code == 0,
+                stdout: output.stdout.clone().unwrap_or_default(),
+                stderr: output.stderr.clone().unwrap_or_default(),
+                exit_code: Some(exit_code),
+            });
+        }
+
+        match self.status.as_deref()? {
+            "COMPLETED" => Some(CommandResult {
+                success: true,
+                stdout: String::new(),
+                stderr: String::new(),
+                exit_code: None,
+            }),
+            status @ ("FAILED" | "CANCELLED" | "TIMED_OUT") => Some(CommandResult {
+                success: false,
+                stdout: String::new(),
+                stderr: format!("Exec job ended with status {status}"),
+                exit_code: None,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Cancels an exec job that is abandoned before it finishes.
+struct ExecJobGuard {
+    /// HTTP client.
+    client: Client,
+    /// Cancel endpoint of the job.
+    cancel_url: String,
+    /// API key.
+    api_key: String,
+    /// Whether the job still needs cancelling.
+    armed: bool,
+}
+
+impl ExecJobGuard {
+    /// Builds the cancel request.
+    fn request(&self) -> reqwest::RequestBuilder {
+        self.client
+            .post(&self.cancel_url)
+            .header(header::AUTHORIZATION, format!("Bearer {}", self.api_key))
+            .timeout(Duration::from_secs(DEFAULT_TIMEOUT_SECS))
+    }
+
+    /// Cancels the job now.
+    async fn cancel(&mut self) {
+        self.armed = false;
+        if let Err(e) = self
+            .request()
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+        {
+            warn!("Failed to cancel exec job ({}): {e}", self.cancel_url);
+        }
+    }
+}
+
+impl Drop for ExecJobGuard {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            let request = self.request();
+            runtime.spawn(async move {
+                let _ = request.send().await;
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_header() {
+        let client = RunPodClient::new("key")
+            .unwrap()
+            .with_header("X-Route", "eu")
+            .unwrap();
+        assert_eq!(client.headers["x-route"], "eu");
+        assert!(USER_AGENT.starts_with("halldyll/"));
+
+        assert!(
+            RunPodClient::new("key")
+                .unwrap()
+                .with_header("bad header", "x")
+                .is_err()
+        );
+        assert!(
+            RunPodClient::new("key")
+                .unwrap()
+                .with_header("X-Route", "a\nb")
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_gpu_types_cache_is_shared() {
+        let client = RunPodClient::new("key")
+            .unwrap()
+            .with_max_concurrent_requests(0);
+        assert_eq!(client.requests.available_permits(), 1);
+
+        let gpu_types = vec![GpuType {
+            id: String::from("NVIDIA A40"),
+            display_name: String::from("A40"),
+            memory_in_gb: 48,
+            secure_cloud: true,
+            community_cloud: false,
+            secure_price: Some(0.4),
+            community_price: None,
+        }];
+        *client.gpu_types.lock().await = Some(CachedGpuTypes {
+            fetched: Instant::now(),
+            gpu_types,
+        });
+
+        // Served from the cache by a clone, without an API call
+        let clone = client.clone();
+        assert!(
+            clone
+                .is_gpu_available("NVIDIA A40", "SECURE")
+                .await
+                .unwrap()
+        );
+        assert!(
+            !clone
+                .is_gpu_available("NVIDIA A40", "COMMUNITY")
+                .await
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_exec_job_result() {
+        let parse = |json: &str| serde_json::from_str::<ExecJob>(json).unwrap().result();
+
+        let running =
+            serde_json::from_str::<ExecJob>(r#"{"id":"job-1","status":"IN_PROGRESS"}"#).unwrap();
+        assert!(running.result().is_none());
+        assert_eq!(running.id.as_deref(), Some("job-1"));
+        assert!(parse(r#"{"id":"job-1","status":"IN_QUEUE"}"#).is_none());
+
+        let done = parse(
+            r#"{"id":"job-1","status":"COMPLETED","output":{"stdout":"ok\n","exit_code":3}}"#,
+        )
+        .unwrap();
+        assert!(!done.success);
+        assert_eq!(done.exit_code, Some(3));
+        assert_eq!(done.stdout, "ok\n");
+
+        let timed_out = parse(r#"{"id":"job-1","status":"TIMED_OUT"}"#).unwrap();
+        assert!(!timed_out.success);
+        assert!(timed_out.stderr.contains("TIMED_OUT"));
+    }
+}
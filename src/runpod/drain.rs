@@ -0,0 +1,276 @@
+//! Graceful drain of inference engines.
+//!
+//! Before a pod with `drain:` is terminated, its engine is asked to stop
+//! taking requests (when a drain endpoint is configured) and its metrics are
+//! polled until no request is in flight or the timeout passes. When the
+//! metrics cannot be read a few times in a row, a short grace period is
+//! waited instead of the whole timeout. Draining never blocks the
+//! termination: failures are logged and the pod is removed anyway.
+
+use reqwest::Client;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+use crate::config::DrainConfig;
+use crate::error::{HalldyllError, Result};
+
+use super::metrics::MetricsScraper;
+use super::observer::ObservedPod;
+
+/// Seconds between in-flight request checks.
+const DRAIN_POLL_SECS: u64 = 2;
+
+/// Failed metrics reads in a row after which the metrics count as unavailable.
+const MAX_FAILED_SCRAPES: u32 = 3;
+
+/// Seconds waited for requests to finish when the metrics are unavailable.
+const DRAIN_GRACE_SECS: u64 = 10;
+
+/// Drain endpoint request timeout in seconds.
+const DRAIN_REQUEST_TIMEOUT_SECS: u64 = 10;
+
+/// Outcome of draining an engine.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DrainOutcome {
+    /// Seconds spent waiting.
+    pub waited_secs: u64,
+    /// In-flight requests at the last check (if metrics could be read).
+    pub in_flight: Option<f64>,
+}
+
+impl DrainOutcome {
+    /// Returns true if the engine was seen with no request in flight.
+    #[must_use]
+    pub fn is_drained(&self) -> bool {
+        self.in_flight.is_some_and(|requests| requests <= 0.0)
+    }
+}
+
+/// Drains inference engines before their pods go away.
+#[derive(Debug)]
+pub struct EngineDrainer {
+    /// HTTP client for drain endpoints.
+    client: Client,
+    /// Metrics scraper for in-flight requests.
+    scraper: MetricsScraper,
+    /// Time between in-flight request checks.
+    poll_interval: Duration,
+    /// Time waited instead of polling when the metrics are unavailable.
+    grace_period: Duration,
+}
+
+impl EngineDrainer {
+    /// Creates a new drainer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP clients cannot be created.
+    pub fn new() -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(DRAIN_REQUEST_TIMEOUT_SECS))
+            .build()
+            .map_err(|e| HalldyllError::internal(format!("Failed to create HTTP client: {e}")))?;
+
+        Ok(Self {
+            client,
+            scraper: MetricsScraper::new()?,
+            poll_interval: Duration::from_secs(DRAIN_POLL_SECS),
+            grace_period: Duration::from_secs(DRAIN_GRACE_SECS),
+        })
+    }
+
+    /// Sets the time between in-flight request checks.
+    #[must_use]
+    pub const fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Sets the time waited when the metrics cannot be read (at most the timeout).
+    #[must_use]
+    pub const fn with_grace_period(mut self, grace_period: Duration) -> Self {
+        self.grace_period = grace_period;
+        self
+    }
+
+    /// Calls the drain endpoint, then waits for in-flight requests to finish.
+    pub async fn drain(&self, pod: &ObservedPod, drain: &DrainConfig) -> DrainOutcome {
+        let name = pod.pod_name.as_deref().unwrap_or(&pod.name);
+        let Some(base) = drain.port.and_then(|port| pod.endpoints.get(&port)) else {
+            warn!("Cannot drain {name}: engine port is not exposed");
+            return DrainOutcome {
+                waited_secs: 0,
+                in_flight: None,
+            };
+        };
+
+        if let Some(endpoint) = &drain.endpoint {
+            let url = format!("{base}{endpoint}");
+            match self.client.post(&url).send().await {
+                Ok(response) if response.status().is_success() => {
+                    debug!("Drain requested at {url}");
+                }
+                Ok(response) => warn!("Drain endpoint {url} returned {}", response.status()),
+                Err(e) => warn!("Failed to call drain endpoint {url}: {e}"),
+            }
+        }
+
+        let port = drain.port.unwrap_or_default();
+        let started = tokio::time::Instant::now();
+        let timeout = Duration::from_secs(drain.timeout_secs);
+        let mut in_flight = None;
+        let mut failed_scrapes = 0;
+
+        loop {
+            match self.scraper.scrape(pod, port, &drain.metrics_path).await {
+                Ok(metrics) => {
+                    in_flight = Some(metrics.concurrent_requests());
+                    failed_scrapes = 0;
+                }
+                Err(e) => {
+                    debug!("Failed to read in-flight requests of {name}: {e}");
+                    failed_scrapes += 1;
+                }
+            }
+
+            // Without metrics there is nothing to wait for but a grace period
+            if failed_scrapes >= MAX_FAILED_SCRAPES {
+                let grace = self
+                    .grace_period
+                    .min(timeout.saturating_sub(started.elapsed()));
+                warn!(
+                    "Cannot read in-flight requests of {name}, waiting {}s before terminating",
+                    grace.as_secs()
+                );
+                tokio::time::sleep(grace).await;
+                return DrainOutcome {
+                    waited_secs: started.elapsed().as_secs(),
+                    in_flight: None,
+                };
+            }
+
+            let outcome = DrainOutcome {
+                waited_secs: started.elapsed().as_secs(),
+                in_flight,
+            };
+            if outcome.is_drained() {
+                info!("Drained {name} after {}s", outcome.waited_secs);
+                return outcome;
+            }
+            if started.elapsed() >= timeout {
+                warn!(
+                    "Drain of {name} timed out after {}s with {} request(s) in flight",
+                    outcome.waited_secs,
+                    in_flight.map_or_else(|| String::from("unknown"), |r| r.to_string())
+                );
+                return outcome;
+            }
+
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runpod::PodStatus;
+    use std::collections::HashMap;
+    use wiremock::matchers::path;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    /// A pod whose engine port 8000 is served by `server`.
+    fn pod(server: &MockServer) -> ObservedPod {
+        ObservedPod {
+            id: String::from("id-llm"),
+            name: String::from("proj-dev-llm"),
+            project: Some(String::from("proj")),
+            environment: Some(String::from("dev")),
+            pod_name: Some(String::from("llm")),
+            spec_hash: None,
+            status: PodStatus::Running,
+            gpu_type: None,
+            gpu_count: 1,
+            image: String::from("img"),
+            endpoints: HashMap::from([(8000, server.uri())]),
+            gpu_utilization: None,
+            tags: HashMap::new(),
+            volume_gb: 0,
+            uptime_secs: 0,
+            env: None,
+        }
+    }
+
+    fn config(timeout_secs: u64) -> DrainConfig {
+        DrainConfig {
+            endpoint: Some(String::from("/drain")),
+            port: Some(8000),
+            metrics_path: String::from("/metrics"),
+            timeout_secs,
+        }
+    }
+
+    fn drainer() -> EngineDrainer {
+        EngineDrainer::new()
+            .unwrap()
+            .with_poll_interval(Duration::from_millis(20))
+            .with_grace_period(Duration::from_millis(50))
+    }
+
+    async fn serve_metrics(server: &MockServer, response: ResponseTemplate) {
+        Mock::given(path("/metrics"))
+            .respond_with(response)
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_drain_stops_once_idle() {
+        let server = MockServer::start().await;
+        Mock::given(path("/drain"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let idle = "vllm:num_requests_running 0\nvllm:num_requests_waiting 0\n";
+        serve_metrics(&server, ResponseTemplate::new(200).set_body_string(idle)).await;
+
+        let outcome = drainer().drain(&pod(&server), &config(60)).await;
+        assert!(outcome.is_drained());
+        assert_eq!(outcome.waited_secs, 0);
+    }
+
+    #[tokio::test]
+    async fn test_drain_times_out_with_requests_in_flight() {
+        let server = MockServer::start().await;
+        let busy = "vllm:num_requests_running 2\nvllm:num_requests_waiting 1\n";
+        serve_metrics(&server, ResponseTemplate::new(200).set_body_string(busy)).await;
+
+        let outcome = drainer().drain(&pod(&server), &config(1)).await;
+        assert!(!outcome.is_drained());
+        assert_eq!(outcome.in_flight, Some(3.0));
+        assert_eq!(outcome.waited_secs, 1);
+    }
+
+    #[tokio::test]
+    async fn test_drain_waits_grace_period_without_metrics() {
+        let server = MockServer::start().await;
+        serve_metrics(&server, ResponseTemplate::new(500)).await;
+
+        // Three failed reads, then the grace period instead of the 60s timeout
+        let started = std::time::Instant::now();
+        let outcome = drainer().drain(&pod(&server), &config(60)).await;
+        assert_eq!(outcome.in_flight, None);
+        assert!(started.elapsed() < Duration::from_secs(5));
+        let requests = server.received_requests().await.unwrap();
+        let scrapes = requests.iter().filter(|r| r.url.path() == "/metrics");
+        assert_eq!(scrapes.count(), 3);
+
+        // A pod without the engine port is not waited on at all
+        let mut closed = pod(&server);
+        closed.endpoints.clear();
+        let skipped = drainer().drain(&closed, &config(60)).await;
+        assert_eq!(skipped.waited_secs, 0);
+        assert_eq!(skipped.in_flight, None);
+    }
+}
@@ -0,0 +1,330 @@
+//! Inference engine introspection.
+//!
+//! This module queries the OpenAI-compatible `/v1/models` endpoint of a
+//! running engine so drift detection can tell when a pod is up but serving
+//! a different model than the one configured. It also reads a model's
+//! architecture from the `HuggingFace` Hub to size tensor parallelism.
+
+use reqwest::Client;
+use std::time::Duration;
+use tracing::debug;
+
+use crate::error::{HalldyllError, Result, RunPodError};
+
+use super::observer::ObservedPod;
+
+/// Model list request timeout in seconds.
+const MODELS_TIMEOUT_SECS: u64 = 10;
+
+/// `HuggingFace` Hub base URL.
+const HF_HUB_URL: &str = "https://huggingface.co";
+
+/// Client for querying a running engine's API.
+#[derive(Debug)]
+pub struct EngineProbe {
+    /// HTTP client for engine requests.
+    client: Client,
+}
+
+impl EngineProbe {
+    /// Creates a new engine probe.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP client cannot be created.
+    pub fn new() -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(MODELS_TIMEOUT_SECS))
+            .build()
+            .map_err(|e| HalldyllError::internal(format!("Failed to create HTTP client: {e}")))?;
+
+        Ok(Self { client })
+    }
+
+    /// Lists the models served by the engine behind a pod's port.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the port is not exposed or the endpoint cannot be read.
+    pub async fn served_models(&self, pod: &ObservedPod, port: u16) -> Result<Vec<String>> {
+        let base = pod.endpoints.get(&port).ok_or_else(|| {
+            HalldyllError::RunPod(RunPodError::InvalidResponse {
+                message: format!("Pod {} does not expose port {port}", pod.id),
+            })
+        })?;
+        let url = format!("{base}/v1/models");
+
+        debug!("Listing served models from {url}");
+
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| RunPodError::network(format!("Failed to query {url}: {e}")))?;
+
+        if !response.status().is_success() {
+            return Err(HalldyllError::RunPod(RunPodError::api_error(
+                response.status().as_u16(),
+                format!("Model list endpoint {url} returned an error"),
+            )));
+        }
+
+        let body = response
+            .text()
+            .await
+            .map_err(|e| RunPodError::network(format!("Failed to read {url}: {e}")))?;
+
+        parse_model_list(&body)
+    }
+}
+
+impl EngineProbe {
+    /// Reads the number of attention heads from a Hub model's `config.json`
+    /// at `revision` (the `main` branch if unset).
+    ///
+    /// The Hub `mirror` is tried first when given, then the Hub itself. Uses
+    /// `HF_TOKEN` for gated models. Returns `None` if the config does not
+    /// declare a head count.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config cannot be fetched or parsed.
+    pub async fn attention_heads(
+        &self,
+        repo: &str,
+        revision: Option<&str>,
+        mirror: Option<&str>,
+    ) -> Result<Option<u32>> {
+        if let Some(mirror) = mirror {
+            match self
+                .fetch_attention_heads(&config_url(mirror, repo, revision), repo)
+                .await
+            {
+                Ok(heads) => return Ok(heads),
+                Err(e) => debug!("Mirror {mirror} has no config for {repo}, trying the Hub: {e}"),
+            }
+        }
+        self.fetch_attention_heads(&config_url(HF_HUB_URL, repo, revision), repo)
+            .await
+    }
+
+    /// Fetches a `config.json` and reads its attention head count.
+    async fn fetch_attention_heads(&self, url: &str, repo: &str) -> Result<Option<u32>> {
+        debug!("Fetching model config from {url}");
+
+        let mut request = self.client.get(url);
+        if let Ok(token) = std::env::var("HF_TOKEN") {
+            request = request.bearer_auth(token);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| RunPodError::network(format!("Failed to fetch {url}: {e}")))?;
+
+        if !response.status().is_success() {
+            return Err(HalldyllError::RunPod(RunPodError::api_error(
+                response.status().as_u16(),
+                format!("Could not read model config for {repo}"),
+            )));
+        }
+
+        let body = response
+            .text()
+            .await
+            .map_err(|e| RunPodError::network(format!("Failed to read {url}: {e}")))?;
+
+        parse_attention_heads(&body)
+    }
+
+    /// Resolves the commit currently served by a Hub model's `main` branch.
+    ///
+    /// Uses `HF_TOKEN` for gated models.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the model info cannot be fetched or has no commit.
+    pub async fn model_revision(&self, repo: &str) -> Result<String> {
+        let url = format!("{HF_HUB_URL}/api/models/{repo}");
+
+        debug!("Fetching model info from {url}");
+
+        let mut request = self.client.get(&url);
+        if let Ok(token) = std::env::var("HF_TOKEN") {
+            request = request.bearer_auth(token);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| RunPodError::network(format!("Failed to fetch {url}: {e}")))?;
+
+        if !response.status().is_success() {
+            return Err(HalldyllError::RunPod(RunPodError::api_error(
+                response.status().as_u16(),
+                format!("Could not read model info for {repo}"),
+            )));
+        }
+
+        let json: serde_json::Value = response.json().await.map_err(|e| {
+            HalldyllError::RunPod(RunPodError::InvalidResponse {
+                message: format!("Invalid model info for {repo}: {e}"),
+            })
+        })?;
+
+        json.get("sha")
+            .and_then(serde_json::Value::as_str)
+            .map(String::from)
+            .ok_or_else(|| {
+                HalldyllError::RunPod(RunPodError::InvalidResponse {
+                    message: format!("Model info for {repo} has no commit"),
+                })
+            })
+    }
+}
+
+/// Returns the URL of a model's `config.json` at `revision` on a Hub endpoint.
+///
+/// Slashes in the revision (`refs/pr/1`) are escaped as the Hub expects.
+fn config_url(endpoint: &str, repo: &str, revision: Option<&str>) -> String {
+    let revision = revision.unwrap_or("main").replace('/', "%2F");
+    format!(
+        "{}/{repo}/resolve/{revision}/config.json",
+        endpoint.trim_end_matches('/')
+    )
+}
+
+/// Extracts the attention head count from a model `config.json`.
+///
+/// # Errors
+///
+/// Returns an error if the body is not valid JSON.
+pub fn parse_attention_heads(body: &str) -> Result<Option<u32>> {
+    let json: serde_json::Value = serde_json::from_str(body).map_err(|e| {
+        HalldyllError::RunPod(RunPodError::InvalidResponse {
+            message: format!("Invalid model config: {e}"),
+        })
+    })?;
+
+    // Multimodal models nest the language model config
+    let heads = ["num_attention_heads", "n_head"]
+        .iter()
+        .find_map(|key| {
+            json.get(*key)
+                .or_else(|| json.get("text_config").and_then(|c| c.get(*key)))
+        })
+        .and_then(serde_json::Value::as_u64)
+        .and_then(|h| u32::try_from(h).ok());
+
+    Ok(heads)
+}
+
+/// Picks the tensor parallel size to use for a model.
+///
+/// An explicit size must divide the head count; otherwise the largest size up
+/// to the GPU count that does is chosen.
+///
+/// # Errors
+///
+/// Returns a description of the problem if an explicit size is unsupported.
+pub fn fit_parallelism(
+    requested: u32,
+    explicit: bool,
+    heads: Option<u32>,
+) -> std::result::Result<u32, String> {
+    let Some(heads) = heads.filter(|h| *h > 0) else {
+        return Ok(requested);
+    };
+    if requested == 0 || heads % requested == 0 {
+        return Ok(requested);
+    }
+    if explicit {
+        return Err(format!(
+            "tensor parallel size {requested} does not divide the model's {heads} attention heads"
+        ));
+    }
+
+    Ok((1..=requested)
+        .rev()
+        .find(|size| heads % size == 0)
+        .unwrap_or(1))
+}
+
+/// Parses the model IDs from an OpenAI-compatible `/v1/models` response.
+///
+/// # Errors
+///
+/// Returns an error if the body is not a model list.
+pub fn parse_model_list(body: &str) -> Result<Vec<String>> {
+    let json: serde_json::Value = serde_json::from_str(body).map_err(|e| {
+        HalldyllError::RunPod(RunPodError::InvalidResponse {
+            message: format!("Invalid model list: {e}"),
+        })
+    })?;
+
+    let data = json
+        .get("data")
+        .and_then(serde_json::Value::as_array)
+        .ok_or_else(|| {
+            HalldyllError::RunPod(RunPodError::InvalidResponse {
+                message: String::from("Model list has no 'data' array"),
+            })
+        })?;
+
+    Ok(data
+        .iter()
+        .filter_map(|m| m.get("id").and_then(serde_json::Value::as_str))
+        .map(String::from)
+        .collect())
+}
+
+/// Returns true if a served model ID matches the configured name.
+///
+/// Ollama reports tags (`llama3:latest`), so an implicit `:latest` is accepted.
+#[must_use]
+pub fn model_matches(served: &str, expected: &str) -> bool {
+    served == expected || served.strip_suffix(":latest") == Some(expected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_url() {
+        assert_eq!(
+            config_url(HF_HUB_URL, "org/m", None),
+            "https://huggingface.co/org/m/resolve/main/config.json"
+        );
+        assert_eq!(
+            config_url("https://hf-mirror.com/", "org/m", Some("refs/pr/1")),
+            "https://hf-mirror.com/org/m/resolve/refs%2Fpr%2F1/config.json"
+        );
+    }
+
+    #[test]
+    fn test_parse_model_list() {
+        let body = r#"{"object":"list","data":[{"id":"meta-llama/Meta-Llama-3-8B-Instruct","object":"model"}]}"#;
+        let models = parse_model_list(body).unwrap();
+
+        assert_eq!(
+            models,
+            vec![String::from("meta-llama/Meta-Llama-3-8B-Instruct")]
+        );
+        assert!(parse_model_list(r#"{"error":"not found"}"#).is_err());
+        assert!(model_matches("llama3:latest", "llama3"));
+        assert!(!model_matches("llama3:8b", "llama3"));
+    }
+
+    #[test]
+    fn test_fit_parallelism() {
+        let heads = parse_attention_heads(r#"{"num_attention_heads": 40}"#).unwrap();
+        assert_eq!(heads, Some(40));
+
+        assert_eq!(fit_parallelism(4, false, heads), Ok(4));
+        assert_eq!(fit_parallelism(3, false, heads), Ok(2));
+        assert!(fit_parallelism(3, true, heads).is_err());
+        assert_eq!(fit_parallelism(3, true, None), Ok(3));
+    }
+}
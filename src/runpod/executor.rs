@@ -1,15 +1,24 @@
 //! Pod command executor for post-provisioning tasks.
 //!
-//! This module handles executing commands on running pods via the RunPod API,
+//! This module handles executing commands on running pods via the `RunPod` API,
 //! including model downloads and inference engine startup.
 
+use futures::stream::{self, StreamExt};
+use std::collections::HashSet;
 use std::time::Duration;
+use tokio::sync::mpsc;
 use tracing::{debug, error, info, warn};
 
-use crate::config::{LoadConfig, ModelConfig, ModelProvider, PodConfig};
+use crate::config::{
+    DownloadConfig, LoadConfig, ModelConfig, ModelDownloadsConfig, ModelProvider, PodConfig,
+    SmokeTestConfig, explicit_parallelism, option_args, parallelism_option, requested_parallelism,
+};
 use crate::error::{HalldyllError, Result, RunPodError};
 
 use super::client::RunPodClient;
+use super::engine::{EngineProbe, fit_parallelism};
+use super::ssh::{OutputStream, authorized_keys_command};
+use super::transcript::{Transcript, TranscriptEntry};
 use super::types::PodStatus;
 
 /// Default timeout for command execution in seconds.
@@ -18,14 +27,43 @@ const DEFAULT_COMMAND_TIMEOUT_SECS: u64 = 600;
 /// Default timeout for model download in seconds.
 const MODEL_DOWNLOAD_TIMEOUT_SECS: u64 = 1800;
 
+/// Delay before the first download retry, doubled on each further retry.
+const DOWNLOAD_RETRY_BASE_SECS: u64 = 10;
+
+/// Hub errors a retry cannot fix (missing repo or revision, no access).
+const PERMANENT_DOWNLOAD_ERRORS: &[&str] = &[
+    "RepositoryNotFoundError",
+    "RevisionNotFoundError",
+    "GatedRepoError",
+    "401 Client Error",
+    "403 Client Error",
+    "404 Client Error",
+];
+
+/// Manifest of the models and components installed on a pod, one key per
+/// line. Kept next to the models so both go away together.
+const MANIFEST_PATH: &str = "/models/.halldyll-manifest";
+
 /// Polling interval for command status checks.
 const POLL_INTERVAL_SECS: u64 = 5;
 
+/// Timeout for log reads in seconds.
+const LOG_READ_TIMEOUT_SECS: u64 = 30;
+
+/// Log files written by the inference engines halldyll starts.
+pub const ENGINE_LOG_FILES: &[&str] = &[
+    "/var/log/vllm.log",
+    "/var/log/tgi.log",
+    "/var/log/ollama.log",
+];
+
 /// Pod command executor for post-provisioning tasks.
 #[derive(Debug)]
 pub struct PodExecutor {
     /// `RunPod` API client.
     client: RunPodClient,
+    /// Where commands are recorded, if anywhere.
+    transcript: Option<Transcript>,
 }
 
 /// Result of a command execution.
@@ -41,6 +79,36 @@ pub struct CommandResult {
     pub exit_code: Option<i32>,
 }
 
+impl CommandResult {
+    /// Turns a non-zero exit into an error carrying the remote exit code.
+    ///
+    /// A command that ended without an exit code counts as exit code 1 unless
+    /// it succeeded.
+    ///
+    /// # Errors
+    ///
+    /// Returns `RemoteCommandFailed` if the command on `pod` failed.
+    pub fn check(&self, pod: &str) -> Result<()> {
+        match self.exit_code {
+            Some(0) => Ok(()),
+            None if self.success => Ok(()),
+            exit_code => Err(HalldyllError::RunPod(RunPodError::RemoteCommandFailed {
+                pod: pod.to_string(),
+                exit_code: exit_code.unwrap_or(1),
+            })),
+        }
+    }
+}
+
+/// A chunk of output from a running command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputChunk {
+    /// Stream the chunk was read from.
+    pub stream: OutputStream,
+    /// Output text (not necessarily whole lines).
+    pub text: String,
+}
+
 /// Model setup result.
 #[derive(Debug, Clone)]
 pub struct ModelSetupResult {
@@ -52,6 +120,30 @@ pub struct ModelSetupResult {
     pub model_path: Option<String>,
     /// Error message if failed.
     pub error: Option<String>,
+    /// Hub mirror the model was downloaded from, if not the default endpoint.
+    pub mirror: Option<String>,
+    /// Whether the pod's manifest showed the model already installed.
+    pub cached: bool,
+}
+
+/// Read position in a log file on a pod.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogCursor {
+    /// Log file path on the pod.
+    pub file: String,
+    /// Number of lines already read.
+    pub lines_read: usize,
+}
+
+/// Log files followed on a pod.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PodLogs {
+    /// Pod (replica instance) name.
+    pub name: String,
+    /// `RunPod` pod ID.
+    pub pod_id: String,
+    /// Read positions of the pod's log files.
+    pub cursors: Vec<LogCursor>,
 }
 
 /// Engine startup result.
@@ -65,13 +157,32 @@ pub struct EngineStartResult {
     pub endpoint: Option<String>,
     /// Error message if failed.
     pub error: Option<String>,
+    /// Raw smoke test response (status and body), if a smoke test ran.
+    pub smoke_response: Option<String>,
 }
 
 impl PodExecutor {
     /// Creates a new pod executor.
     #[must_use]
     pub const fn new(client: RunPodClient) -> Self {
-        Self { client }
+        Self {
+            client,
+            transcript: None,
+        }
+    }
+
+    /// Records every command run, with its output, in a transcript.
+    #[must_use]
+    pub fn with_transcript(mut self, transcript: Transcript) -> Self {
+        self.transcript = Some(transcript);
+        self
+    }
+
+    /// Names a pod's transcript file after `name` instead of its ID.
+    pub fn name_pod(&self, pod_id: &str, name: &str) {
+        if let Some(transcript) = &self.transcript {
+            transcript.name_pod(pod_id, name);
+        }
     }
 
     /// Executes a command on a running pod.
@@ -84,15 +195,113 @@ impl PodExecutor {
         pod_id: &str,
         command: &str,
         timeout_secs: Option<u64>,
+    ) -> Result<CommandResult> {
+        self.run(pod_id, command, timeout_secs, |_, _| {}).await
+    }
+
+    /// Replaces the pod's `~/.ssh/authorized_keys` with `keys`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the command cannot be run or fails.
+    pub async fn install_authorized_keys(&self, pod_id: &str, keys: &[String]) -> Result<()> {
+        let result = self
+            .execute_command(pod_id, &authorized_keys_command(keys), Some(60))
+            .await?;
+        if result.success {
+            Ok(())
+        } else {
+            Err(HalldyllError::internal(format!(
+                "Failed to install SSH keys on pod {pod_id}: {}",
+                result.stderr.trim()
+            )))
+        }
+    }
+
+    /// Executes a command on a running pod, sending its output to `output`
+    /// as it arrives.
+    ///
+    /// The returned result still holds the complete output. The channel is
+    /// closed once the command has finished.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the command cannot be executed.
+    pub async fn execute_command_streaming(
+        &self,
+        pod_id: &str,
+        command: &str,
+        timeout_secs: Option<u64>,
+        output: mpsc::UnboundedSender<OutputChunk>,
+    ) -> Result<CommandResult> {
+        self.run(pod_id, command, timeout_secs, move |stream, text| {
+            // The receiver may have stopped listening; the result keeps the output
+            let _ = output.send(OutputChunk {
+                stream,
+                text: text.to_string(),
+            });
+        })
+        .await
+    }
+
+    /// Runs a long setup command, logging its output lines as they arrive.
+    async fn execute_logged(
+        &self,
+        pod_id: &str,
+        command: &str,
+        timeout_secs: u64,
+    ) -> Result<CommandResult> {
+        let (sender, mut receiver) = mpsc::unbounded_channel::<OutputChunk>();
+        let log_output = async {
+            while let Some(chunk) = receiver.recv().await {
+                for line in chunk
+                    .text
+                    .split(['\n', '\r'])
+                    .map(str::trim)
+                    .filter(|l| !l.is_empty())
+                {
+                    info!("[{pod_id}] {line}");
+                }
+            }
+        };
+
+        let (result, ()) = tokio::join!(
+            self.execute_command_streaming(pod_id, command, Some(timeout_secs), sender),
+            log_output
+        );
+        result
+    }
+
+    /// Runs a command through the client and records it in the transcript.
+    async fn run(
+        &self,
+        pod_id: &str,
+        command: &str,
+        timeout_secs: Option<u64>,
+        on_output: impl FnMut(OutputStream, &str) + Send,
     ) -> Result<CommandResult> {
         let timeout = timeout_secs.unwrap_or(DEFAULT_COMMAND_TIMEOUT_SECS);
-        
+
         debug!("Executing command on pod {}: {}", pod_id, command);
 
-        // Use RunPod's exec endpoint
-        let result = self.client.exec_command(pod_id, command, timeout).await?;
+        let started_at = chrono::Utc::now();
+        let started = std::time::Instant::now();
+        let result = self
+            .client
+            .exec_command_streaming(pod_id, command, timeout, on_output)
+            .await;
+
+        if let Some(transcript) = &self.transcript {
+            let entry = TranscriptEntry {
+                started_at,
+                duration_secs: started.elapsed().as_secs_f64(),
+                command,
+                result: result.as_ref().map_err(ToString::to_string),
+            };
+            transcript.record(pod_id, &entry);
+        }
 
-        Ok(result)
+        result
     }
 
     /// Waits for a pod to be ready for command execution.
@@ -137,7 +346,11 @@ impl PodExecutor {
         }
     }
 
-    /// Downloads and sets up models on a pod.
+    /// Downloads and sets up models on a pod, `downloads.parallel` at a time.
+    ///
+    /// Models and components listed in the pod's manifest at the same
+    /// revision are skipped, and successful downloads are added to it.
+    /// Results are in the order of `models`.
     ///
     /// # Errors
     ///
@@ -146,19 +359,26 @@ impl PodExecutor {
         &self,
         pod_id: &str,
         models: &[ModelConfig],
+        downloads: &ModelDownloadsConfig,
     ) -> Result<Vec<ModelSetupResult>> {
         if models.is_empty() {
             return Ok(Vec::new());
         }
 
-        info!("Setting up {} model(s) on pod {}", models.len(), pod_id);
-
-        let mut results = Vec::with_capacity(models.len());
+        info!(
+            "Setting up {} model(s) on pod {} ({} at a time)",
+            models.len(),
+            pod_id,
+            downloads.concurrency()
+        );
 
-        for model in models {
-            let result = self.setup_single_model(pod_id, model).await;
-            results.push(result);
-        }
+        let limit = downloads.per_download_kb_per_sec();
+        let installed = self.read_manifest(pod_id).await;
+        let results: Vec<ModelSetupResult> = stream::iter(models)
+            .map(|model| self.setup_single_model(pod_id, model, limit, &installed))
+            .buffered(downloads.concurrency())
+            .collect()
+            .await;
 
         // Check if any critical models failed
         let failed: Vec<_> = results.iter().filter(|r| !r.success).collect();
@@ -173,16 +393,56 @@ impl PodExecutor {
         Ok(results)
     }
 
-    /// Sets up a single model on a pod.
-    async fn setup_single_model(&self, pod_id: &str, model: &ModelConfig) -> ModelSetupResult {
+    /// Reads the keys of the pod's manifest (empty if it has none yet).
+    async fn read_manifest(&self, pod_id: &str) -> HashSet<String> {
+        let command = format!("cat {MANIFEST_PATH} 2>/dev/null || true");
+        match self.execute_command(pod_id, &command, Some(30)).await {
+            Ok(result) => result
+                .stdout
+                .lines()
+                .map(str::trim)
+                .filter(|l| !l.is_empty())
+                .map(String::from)
+                .collect(),
+            Err(e) => {
+                debug!("Could not read the manifest of pod {pod_id}: {e}");
+                HashSet::new()
+            }
+        }
+    }
+
+    /// Adds `key` to the pod's manifest; a failure only costs a re-download.
+    async fn record_installed(&self, pod_id: &str, key: &str) {
+        let command = format!(
+            "mkdir -p \"$(dirname {MANIFEST_PATH})\" && echo '{}' >> {MANIFEST_PATH}",
+            key.replace('\'', "'\\''")
+        );
+        let error = match self.execute_command(pod_id, &command, Some(30)).await {
+            Ok(result) if result.success => return,
+            Ok(result) => last_line(&result.stderr),
+            Err(e) => e.to_string(),
+        };
+        warn!("Could not record '{key}' in the manifest of pod {pod_id}: {error}");
+    }
+
+    /// Sets up a single model on a pod, downloading at up to `limit` KB/s.
+    async fn setup_single_model(
+        &self,
+        pod_id: &str,
+        model: &ModelConfig,
+        limit: Option<u32>,
+        installed: &HashSet<String>,
+    ) -> ModelSetupResult {
         info!("Setting up model '{}' on pod {}", model.id, pod_id);
 
         match model.provider {
             ModelProvider::Huggingface => {
-                self.setup_huggingface_model(pod_id, model).await
+                self.setup_huggingface_model(pod_id, model, limit, installed)
+                    .await
             }
             ModelProvider::Bundle => {
-                self.setup_bundle_model(pod_id, model).await
+                self.setup_bundle_model(pod_id, model, limit, installed)
+                    .await
             }
             ModelProvider::Custom => {
                 // Custom models are expected to be already available
@@ -191,88 +451,167 @@ impl PodExecutor {
                     success: true,
                     model_path: None,
                     error: None,
+                    mirror: None,
+                    cached: false,
                 }
             }
         }
     }
 
-    /// Downloads a HuggingFace model.
+    /// Downloads a `HuggingFace` model.
     async fn setup_huggingface_model(
         &self,
         pod_id: &str,
         model: &ModelConfig,
+        limit: Option<u32>,
+        installed: &HashSet<String>,
     ) -> ModelSetupResult {
-        let repo = match &model.repo {
-            Some(r) => r,
-            None => {
-                return ModelSetupResult {
-                    model_id: model.id.clone(),
-                    success: false,
-                    model_path: None,
-                    error: Some("Missing 'repo' field for HuggingFace model".to_string()),
-                };
-            }
+        let Some(repo) = &model.repo else {
+            return ModelSetupResult {
+                model_id: model.id.clone(),
+                success: false,
+                model_path: None,
+                error: Some("Missing 'repo' field for HuggingFace model".to_string()),
+                mirror: None,
+                cached: false,
+            };
         };
 
-        let model_path = format!("/root/.cache/huggingface/hub/models--{}", repo.replace('/', "--"));
+        let revision = model.revision.as_deref().unwrap_or("main");
+        let model_path = format!("/models/{}", model.id);
 
-        // Check if model already exists
-        let check_cmd = format!("test -d '{}' && echo 'exists' || echo 'missing'", model_path);
-        match self.execute_command(pod_id, &check_cmd, Some(30)).await {
-            Ok(result) if result.stdout.trim() == "exists" => {
-                info!("Model '{}' already exists on pod {}", model.id, pod_id);
-                return ModelSetupResult {
-                    model_id: model.id.clone(),
-                    success: true,
-                    model_path: Some(model_path),
-                    error: None,
-                };
-            }
-            _ => {}
+        // Skip models the manifest lists at this revision
+        let key = manifest_key(&model_path, repo, revision);
+        if installed.contains(&key) {
+            info!(
+                "Model '{}' ({repo}@{revision}) already installed on pod {}",
+                model.id, pod_id
+            );
+            return ModelSetupResult {
+                model_id: model.id.clone(),
+                success: true,
+                model_path: Some(model_path),
+                error: None,
+                mirror: None,
+                cached: true,
+            };
         }
 
         // Download the model using huggingface-cli
-        info!("Downloading model '{}' ({}) on pod {}", model.id, repo, pod_id);
+        info!(
+            "Downloading model '{}' ({}) on pod {}",
+            model.id, repo, pod_id
+        );
 
+        let (prelude, dl) = throttle(limit);
         let download_cmd = format!(
-            "huggingface-cli download {} --local-dir /models/{} 2>&1 || \
-             python -c \"from huggingface_hub import snapshot_download; snapshot_download('{}')\" 2>&1",
-            repo, model.id, repo
+            "{prelude}{dl}huggingface-cli download {repo} --revision {revision} --local-dir /models/{} 2>&1 || \
+             {dl}python -c \"from huggingface_hub import snapshot_download; snapshot_download('{repo}', revision='{revision}')\" 2>&1",
+            model.id
         );
 
-        match self.execute_command(pod_id, &download_cmd, Some(MODEL_DOWNLOAD_TIMEOUT_SECS)).await {
-            Ok(result) if result.success => {
-                info!("Successfully downloaded model '{}' on pod {}", model.id, pod_id);
+        match self
+            .download_with_retries(pod_id, model, &download_cmd)
+            .await
+        {
+            Ok(mirror) => {
+                info!(
+                    "Successfully downloaded model '{}' on pod {}",
+                    model.id, pod_id
+                );
+                self.record_installed(pod_id, &key).await;
                 ModelSetupResult {
                     model_id: model.id.clone(),
                     success: true,
-                    model_path: Some(format!("/models/{}", model.id)),
+                    model_path: Some(model_path),
                     error: None,
+                    mirror,
+                    cached: false,
                 }
             }
-            Ok(result) => {
-                error!("Failed to download model '{}': {}", model.id, result.stderr);
+            Err(e) => {
+                error!("Failed to download model '{}': {}", model.id, e);
                 ModelSetupResult {
                     model_id: model.id.clone(),
                     success: false,
                     model_path: None,
-                    error: Some(result.stderr),
+                    error: Some(e),
+                    mirror: None,
+                    cached: false,
                 }
             }
-            Err(e) => {
-                error!("Error downloading model '{}': {}", model.id, e);
-                ModelSetupResult {
-                    model_id: model.id.clone(),
-                    success: false,
-                    model_path: None,
-                    error: Some(e.to_string()),
+        }
+    }
+
+    /// Runs a download command until it succeeds, returning the mirror used.
+    ///
+    /// The configured mirror (exported as `HF_ENDPOINT`) is tried first,
+    /// then the pod's default endpoint, each `download.retries` more times
+    /// with exponential backoff. Errors no retry can fix, such as a missing
+    /// repo or no access, move on to the next endpoint at once.
+    async fn download_with_retries(
+        &self,
+        pod_id: &str,
+        model: &ModelConfig,
+        command: &str,
+    ) -> std::result::Result<Option<String>, String> {
+        let download = model.download.clone().unwrap_or_default();
+        let endpoints = download
+            .mirror_endpoint()
+            .into_iter()
+            .map(Some)
+            .chain([None]);
+
+        let mut last_error = String::from("no download attempted");
+        for endpoint in endpoints {
+            let command = endpoint.as_ref().map_or_else(
+                || command.to_string(),
+                |url| format!("export HF_ENDPOINT={url}; {command}"),
+            );
+            for attempt in 0..=download.retries {
+                if attempt > 0 {
+                    let delay = DOWNLOAD_RETRY_BASE_SECS << (attempt - 1).min(5);
+                    warn!(
+                        "Retrying download of '{}' in {delay}s ({last_error})",
+                        model.id
+                    );
+                    tokio::time::sleep(Duration::from_secs(delay)).await;
+                }
+
+                last_error = match self
+                    .execute_logged(pod_id, &command, MODEL_DOWNLOAD_TIMEOUT_SECS)
+                    .await
+                {
+                    Ok(result) if result.success => return Ok(endpoint),
+                    // The download commands send their errors to stdout
+                    Ok(result) => last_line(&format!("{}\n{}", result.stdout, result.stderr)),
+                    Err(e) => e.to_string(),
+                };
+                if PERMANENT_DOWNLOAD_ERRORS
+                    .iter()
+                    .any(|e| last_error.contains(e))
+                {
+                    break;
                 }
             }
+            if let Some(url) = &endpoint {
+                warn!(
+                    "Download of '{}' from mirror {url} failed, trying the default endpoint",
+                    model.id
+                );
+            }
         }
+        Err(last_error)
     }
 
     /// Sets up a bundle of models/components.
-    async fn setup_bundle_model(&self, pod_id: &str, model: &ModelConfig) -> ModelSetupResult {
+    async fn setup_bundle_model(
+        &self,
+        pod_id: &str,
+        model: &ModelConfig,
+        limit: Option<u32>,
+        installed: &HashSet<String>,
+    ) -> ModelSetupResult {
         let components = match &model.components {
             Some(c) if !c.is_empty() => c,
             _ => {
@@ -281,21 +620,36 @@ impl PodExecutor {
                     success: false,
                     model_path: None,
                     error: Some("Missing 'components' field for bundle model".to_string()),
+                    mirror: None,
+                    cached: false,
                 };
             }
         };
 
-        info!("Setting up bundle '{}' with {} components", model.id, components.len());
+        info!(
+            "Setting up bundle '{}' with {} components",
+            model.id,
+            components.len()
+        );
 
         // Download each component
+        let (prelude, dl) = throttle(limit);
+        let mut mirror = None;
+        let mut cached = true;
         for component in components {
-            let cmd = format!(
-                "huggingface-cli download {} 2>&1 || echo 'Failed to download {}'",
-                component, component
-            );
-            
-            if let Err(e) = self.execute_command(pod_id, &cmd, Some(MODEL_DOWNLOAD_TIMEOUT_SECS)).await {
-                warn!("Failed to download component '{}': {}", component, e);
+            let key = format!("component {component}");
+            if installed.contains(&key) {
+                debug!("Component '{component}' already installed on pod {pod_id}");
+                continue;
+            }
+            cached = false;
+            let cmd = format!("{prelude}{dl}huggingface-cli download {component} 2>&1");
+            match self.download_with_retries(pod_id, model, &cmd).await {
+                Ok(used) => {
+                    mirror = mirror.or(used);
+                    self.record_installed(pod_id, &key).await;
+                }
+                Err(e) => warn!("Failed to download component '{}': {}", component, e),
             }
         }
 
@@ -304,11 +658,16 @@ impl PodExecutor {
             success: true,
             model_path: Some("/root/.cache/huggingface".to_string()),
             error: None,
+            mirror,
+            cached,
         }
     }
 
     /// Starts an inference engine on the pod.
     ///
+    /// For vLLM and TGI on multi-GPU pods, the model is sharded across
+    /// `gpu_count` GPUs unless the parallelism option is set explicitly.
+    ///
     /// # Errors
     ///
     /// Returns an error if the engine cannot be started.
@@ -317,26 +676,53 @@ impl PodExecutor {
         pod_id: &str,
         model: &ModelConfig,
         port: u16,
+        gpu_count: u32,
     ) -> Result<EngineStartResult> {
-        let load_config = match &model.load {
-            Some(c) => c,
-            None => {
+        let Some(load_config) = &model.load else {
+            return Ok(EngineStartResult {
+                engine: "none".to_string(),
+                success: true,
+                endpoint: None,
+                error: None,
+                smoke_response: None,
+            });
+        };
+
+        let engine = load_config.engine.to_lowercase();
+        info!(
+            "Starting {} engine for model '{}' on pod {}",
+            engine, model.id, pod_id
+        );
+
+        let parallel_args = match self.parallelism_args(model, load_config, gpu_count).await {
+            Ok(args) => args,
+            Err(reason) => {
                 return Ok(EngineStartResult {
-                    engine: "none".to_string(),
-                    success: true,
+                    engine,
+                    success: false,
                     endpoint: None,
-                    error: None,
+                    error: Some(reason),
+                    smoke_response: None,
                 });
             }
         };
 
-        let engine = load_config.engine.to_lowercase();
-        info!("Starting {} engine for model '{}' on pod {}", engine, model.id, pod_id);
-
         match engine.as_str() {
-            "vllm" => self.start_vllm(pod_id, model, load_config, port).await,
+            "vllm" => {
+                let result = self
+                    .start_vllm(pod_id, model, load_config, port, &parallel_args)
+                    .await?;
+                Ok(self
+                    .with_smoke_test(pod_id, model, load_config, port, result)
+                    .await)
+            }
             "tgi" | "text-generation-inference" => {
-                self.start_tgi(pod_id, model, load_config, port).await
+                let result = self
+                    .start_tgi(pod_id, model, load_config, port, &parallel_args)
+                    .await?;
+                Ok(self
+                    .with_smoke_test(pod_id, model, load_config, port, result)
+                    .await)
             }
             "ollama" => self.start_ollama(pod_id, model, port).await,
             "transformers" => {
@@ -346,6 +732,7 @@ impl PodExecutor {
                     success: true,
                     endpoint: None,
                     error: None,
+                    smoke_response: None,
                 })
             }
             other => {
@@ -354,12 +741,166 @@ impl PodExecutor {
                     engine: other.to_string(),
                     success: true,
                     endpoint: None,
-                    error: Some(format!("Unknown engine '{}', manual start required", other)),
+                    error: Some(format!("Unknown engine '{other}', manual start required")),
+                    smoke_response: None,
                 })
             }
         }
     }
 
+    /// Runs the configured smoke test against a started engine.
+    ///
+    /// A failing smoke test marks the result as failed and keeps the response.
+    async fn with_smoke_test(
+        &self,
+        pod_id: &str,
+        model: &ModelConfig,
+        load_config: &LoadConfig,
+        port: u16,
+        mut result: EngineStartResult,
+    ) -> EngineStartResult {
+        let Some(smoke) = &load_config.smoke_test else {
+            return result;
+        };
+        if !result.success {
+            return result;
+        }
+
+        info!(
+            "Running smoke test for model '{}' on pod {}",
+            model.id, pod_id
+        );
+        let (outcome, response) = self.run_smoke_test(pod_id, model, smoke, port).await;
+        result.smoke_response = response;
+
+        if let Err(reason) = outcome {
+            error!(
+                "Smoke test failed for model '{}' on pod {}: {}",
+                model.id, pod_id, reason
+            );
+            result.success = false;
+            result.error = Some(format!("Smoke test failed: {reason}"));
+        }
+
+        result
+    }
+
+    /// Sends a tiny `/v1/completions` request from inside the pod.
+    ///
+    /// Retries while the server is still loading the model (connection refused
+    /// or 503) until the smoke test timeout elapses.
+    async fn run_smoke_test(
+        &self,
+        pod_id: &str,
+        model: &ModelConfig,
+        smoke: &SmokeTestConfig,
+        port: u16,
+    ) -> (std::result::Result<(), String>, Option<String>) {
+        let body = serde_json::json!({
+            "model": model.repo.as_deref().unwrap_or(&model.id),
+            "prompt": smoke.prompt,
+            "max_tokens": smoke.max_tokens,
+        })
+        .to_string();
+        let cmd = format!(
+            "curl -s -m 60 -o /tmp/halldyll-smoke.json -w '%{{http_code}}' \
+             -H 'Content-Type: application/json' -d '{}' http://localhost:{port}/v1/completions; \
+             echo; cat /tmp/halldyll-smoke.json 2>/dev/null",
+            body.replace('\'', "'\\''")
+        );
+
+        let start = std::time::Instant::now();
+        let timeout = Duration::from_secs(smoke.timeout_secs);
+
+        loop {
+            let last = match self.execute_command(pod_id, &cmd, Some(90)).await {
+                Ok(output) => {
+                    let (status, response) = output
+                        .stdout
+                        .split_once('\n')
+                        .unwrap_or((&output.stdout, ""));
+                    let status = status.trim();
+                    if status != "000" && status != "503" {
+                        let captured = format!("HTTP {status}: {}", response.trim());
+                        return (check_smoke_response(status, response), Some(captured));
+                    }
+                    format!("engine not accepting requests yet (HTTP {status})")
+                }
+                Err(e) => e.to_string(),
+            };
+
+            if start.elapsed() > timeout {
+                return (
+                    Err(format!(
+                        "no response within {}s: {last}",
+                        smoke.timeout_secs
+                    )),
+                    None,
+                );
+            }
+
+            debug!("Smoke test for pod {} not ready: {}", pod_id, last);
+            tokio::time::sleep(Duration::from_secs(POLL_INTERVAL_SECS)).await;
+        }
+    }
+
+    /// Derives tensor parallelism arguments from the pod's GPU count, split
+    /// between the pipeline stages.
+    ///
+    /// Checks the requested size against the model's attention heads: an
+    /// unsupported explicit size is an error, a derived one is reduced.
+    async fn parallelism_args(
+        &self,
+        model: &ModelConfig,
+        load_config: &LoadConfig,
+        gpu_count: u32,
+    ) -> std::result::Result<Vec<String>, String> {
+        let Some(option) = parallelism_option(&load_config.engine) else {
+            return Ok(vec![]);
+        };
+
+        let explicit = explicit_parallelism(load_config);
+        let requested = requested_parallelism(load_config, gpu_count);
+        if requested <= 1 {
+            return Ok(vec![]);
+        }
+
+        let repo = model.repo.as_deref().unwrap_or(&model.id);
+        let mirror = model
+            .download
+            .as_ref()
+            .and_then(DownloadConfig::mirror_endpoint);
+        let heads = match EngineProbe::new() {
+            Ok(probe) => probe
+                .attention_heads(repo, model.revision.as_deref(), mirror.as_deref())
+                .await
+                .unwrap_or_else(|e| {
+                    warn!("Could not read model config for {}: {}", repo, e);
+                    None
+                }),
+            Err(e) => {
+                warn!("Could not check model architecture: {}", e);
+                None
+            }
+        };
+
+        let size = fit_parallelism(requested, explicit.is_some(), heads)
+            .map_err(|reason| format!("Model '{}': {reason}", model.id))?;
+        if size != requested {
+            warn!(
+                "Model '{}' cannot be split across {} GPUs, using --{} {}",
+                model.id, requested, option, size
+            );
+        }
+
+        // An explicit size is already passed through with the other options
+        if explicit.is_some() || size <= 1 {
+            return Ok(vec![]);
+        }
+
+        Ok(vec![format!("--{option} {size}")])
+    }
+
     /// Starts vLLM server.
     async fn start_vllm(
         &self,
@@ -367,13 +908,14 @@ impl PodExecutor {
         model: &ModelConfig,
         load_config: &LoadConfig,
         port: u16,
+        parallel_args: &[String],
     ) -> Result<EngineStartResult> {
         let repo = model.repo.as_deref().unwrap_or(&model.id);
-        
+
         let mut cmd_parts = vec![
             "nohup python -m vllm.entrypoints.openai.api_server".to_string(),
-            format!("--model {}", repo),
-            format!("--port {}", port),
+            format!("--model {repo}"),
+            format!("--port {port}"),
             "--host 0.0.0.0".to_string(),
         ];
 
@@ -393,21 +935,12 @@ impl PodExecutor {
 
         // Add max sequence length
         if let Some(max_len) = load_config.max_seq_len {
-            cmd_parts.push(format!("--max-model-len {}", max_len));
+            cmd_parts.push(format!("--max-model-len {max_len}"));
         }
 
         // Add any extra options
-        for (key, value) in &load_config.options {
-            if let Some(v) = value.as_str() {
-                cmd_parts.push(format!("--{} {}", key, v));
-            } else if let Some(v) = value.as_bool() {
-                if v {
-                    cmd_parts.push(format!("--{}", key));
-                }
-            } else if let Some(v) = value.as_i64() {
-                cmd_parts.push(format!("--{} {}", key, v));
-            }
-        }
+        cmd_parts.extend(option_args(load_config));
+        cmd_parts.extend_from_slice(parallel_args);
 
         cmd_parts.push("> /var/log/vllm.log 2>&1 &".to_string());
 
@@ -427,22 +960,26 @@ impl PodExecutor {
                         Ok(EngineStartResult {
                             engine: "vllm".to_string(),
                             success: true,
-                            endpoint: Some(format!("http://localhost:{}", port)),
+                            endpoint: Some(format!("http://localhost:{port}")),
                             error: None,
+                            smoke_response: None,
                         })
                     }
                     _ => {
                         // Check logs for errors
                         let log_cmd = "tail -50 /var/log/vllm.log 2>/dev/null || echo 'No logs'";
-                        let logs = self.execute_command(pod_id, log_cmd, Some(30)).await
+                        let logs = self
+                            .execute_command(pod_id, log_cmd, Some(30))
+                            .await
                             .map(|r| r.stdout)
                             .unwrap_or_default();
-                        
+
                         Ok(EngineStartResult {
                             engine: "vllm".to_string(),
                             success: false,
                             endpoint: None,
-                            error: Some(format!("vLLM failed to start. Logs: {}", logs)),
+                            error: Some(format!("vLLM failed to start. Logs: {logs}")),
+                            smoke_response: None,
                         })
                     }
                 }
@@ -452,6 +989,7 @@ impl PodExecutor {
                 success: false,
                 endpoint: None,
                 error: Some(e.to_string()),
+                smoke_response: None,
             }),
         }
     }
@@ -463,26 +1001,31 @@ impl PodExecutor {
         model: &ModelConfig,
         load_config: &LoadConfig,
         port: u16,
+        parallel_args: &[String],
     ) -> Result<EngineStartResult> {
         let repo = model.repo.as_deref().unwrap_or(&model.id);
 
         let mut cmd_parts = vec![
             "nohup text-generation-launcher".to_string(),
-            format!("--model-id {}", repo),
+            format!("--model-id {repo}"),
             format!("--port {}", port),
             "--hostname 0.0.0.0".to_string(),
         ];
 
         // Add quantization
         if let Some(quant) = &load_config.quant {
-            cmd_parts.push(format!("--quantize {}", quant));
+            cmd_parts.push(format!("--quantize {quant}"));
         }
 
         // Add max sequence length
         if let Some(max_len) = load_config.max_seq_len {
-            cmd_parts.push(format!("--max-input-length {}", max_len));
+            cmd_parts.push(format!("--max-input-length {max_len}"));
         }
 
+        // Add any extra options
+        cmd_parts.extend(option_args(load_config));
+        cmd_parts.extend_from_slice(parallel_args);
+
         cmd_parts.push("> /var/log/tgi.log 2>&1 &".to_string());
 
         let cmd = cmd_parts.join(" ");
@@ -494,19 +1037,19 @@ impl PodExecutor {
 
                 let check_cmd = "pgrep -f 'text-generation-launcher' || echo 'not running'";
                 match self.execute_command(pod_id, check_cmd, Some(30)).await {
-                    Ok(result) if !result.stdout.contains("not running") => {
-                        Ok(EngineStartResult {
-                            engine: "tgi".to_string(),
-                            success: true,
-                            endpoint: Some(format!("http://localhost:{}", port)),
-                            error: None,
-                        })
-                    }
+                    Ok(result) if !result.stdout.contains("not running") => Ok(EngineStartResult {
+                        engine: "tgi".to_string(),
+                        success: true,
+                        endpoint: Some(format!("http://localhost:{port}")),
+                        error: None,
+                        smoke_response: None,
+                    }),
                     _ => Ok(EngineStartResult {
                         engine: "tgi".to_string(),
                         success: false,
                         endpoint: None,
                         error: Some("TGI failed to start".to_string()),
+                        smoke_response: None,
                     }),
                 }
             }
@@ -515,6 +1058,7 @@ impl PodExecutor {
                 success: false,
                 endpoint: None,
                 error: Some(e.to_string()),
+                smoke_response: None,
             }),
         }
     }
@@ -532,26 +1076,27 @@ impl PodExecutor {
             model.id
         );
 
-        match self.execute_command(pod_id, &start_cmd, Some(300)).await {
-            Ok(result) if result.success => {
-                Ok(EngineStartResult {
-                    engine: "ollama".to_string(),
-                    success: true,
-                    endpoint: Some(format!("http://localhost:{}", port)),
-                    error: None,
-                })
-            }
+        match self.execute_logged(pod_id, &start_cmd, 300).await {
+            Ok(result) if result.success => Ok(EngineStartResult {
+                engine: "ollama".to_string(),
+                success: true,
+                endpoint: Some(format!("http://localhost:{port}")),
+                error: None,
+                smoke_response: None,
+            }),
             Ok(result) => Ok(EngineStartResult {
                 engine: "ollama".to_string(),
                 success: false,
                 endpoint: None,
                 error: Some(result.stderr),
+                smoke_response: None,
             }),
             Err(e) => Ok(EngineStartResult {
                 engine: "ollama".to_string(),
                 success: false,
                 endpoint: None,
                 error: Some(e.to_string()),
+                smoke_response: None,
             }),
         }
     }
@@ -572,32 +1117,34 @@ impl PodExecutor {
         pod_config: &PodConfig,
     ) -> Result<PostProvisionResult> {
         info!("Starting post-provisioning setup for pod {}", pod_id);
+        self.name_pod(pod_id, &pod_config.name);
 
         // Wait for pod to be ready
         self.wait_for_ready(pod_id, 300).await?;
 
         // Setup models
-        let model_results = self.setup_models(pod_id, &pod_config.models).await?;
+        let downloads = pod_config.model_downloads.unwrap_or_default();
+        let model_results = self
+            .setup_models(pod_id, &pod_config.models, &downloads)
+            .await?;
 
         // Start engines for each model that has a load config
         let mut engine_results = Vec::new();
-        
+
         // Get the primary HTTP port
-        let port = pod_config
-            .http_ports()
-            .first()
-            .copied()
-            .unwrap_or(8000);
+        let port = pod_config.http_ports().first().copied().unwrap_or(8000);
 
         for model in &pod_config.models {
             if model.load.is_some() {
-                let result = self.start_inference_engine(pod_id, model, port).await?;
+                let result = self
+                    .start_inference_engine(pod_id, model, port, pod_config.gpu.count)
+                    .await?;
                 engine_results.push(result);
             }
         }
 
-        let success = model_results.iter().all(|r| r.success)
-            && engine_results.iter().all(|r| r.success);
+        let success =
+            model_results.iter().all(|r| r.success) && engine_results.iter().all(|r| r.success);
 
         Ok(PostProvisionResult {
             pod_id: pod_id.to_string(),
@@ -606,6 +1153,172 @@ impl PodExecutor {
             engine_results,
         })
     }
+
+    /// Returns the log files of the engines a pod runs.
+    ///
+    /// Falls back to every known engine log when the pod is unknown or has
+    /// no engine configured.
+    #[must_use]
+    pub fn log_files(pod_config: Option<&PodConfig>) -> Vec<String> {
+        let mut files: Vec<String> = pod_config
+            .into_iter()
+            .flat_map(|pod| &pod.models)
+            .filter_map(|model| model.load.as_ref())
+            .map(|load| match load.engine.as_str() {
+                "text-generation-inference" => String::from("/var/log/tgi.log"),
+                engine => format!("/var/log/{engine}.log"),
+            })
+            .collect();
+        files.sort();
+        files.dedup();
+
+        if files.is_empty() {
+            files = ENGINE_LOG_FILES.iter().map(ToString::to_string).collect();
+        }
+        files
+    }
+
+    /// Opens the log files that exist on a pod, positioned `tail` lines
+    /// before their end.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the command cannot be executed.
+    pub async fn open_logs(
+        &self,
+        pod_id: &str,
+        files: &[String],
+        tail: u32,
+    ) -> Result<Vec<LogCursor>> {
+        let quoted: Vec<String> = files.iter().map(|f| format!("'{f}'")).collect();
+        let command = format!(
+            "for f in {}; do [ -f \"$f\" ] && echo \"$(wc -l < \"$f\") $f\"; done; true",
+            quoted.join(" ")
+        );
+        let result = self
+            .execute_command(pod_id, &command, Some(LOG_READ_TIMEOUT_SECS))
+            .await?;
+
+        Ok(parse_log_listing(&result.stdout, tail))
+    }
+
+    /// Reads the lines appended to a log since the cursor and advances it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the command cannot be executed.
+    pub async fn read_logs(&self, pod_id: &str, cursor: &mut LogCursor) -> Result<Vec<String>> {
+        let command = format!("sed -n '{},$p' '{}'", cursor.lines_read + 1, cursor.file);
+        let result = self
+            .execute_command(pod_id, &command, Some(LOG_READ_TIMEOUT_SECS))
+            .await?;
+
+        let lines: Vec<String> = result.stdout.lines().map(ToString::to_string).collect();
+        cursor.lines_read += lines.len();
+        Ok(lines)
+    }
+
+    /// Passes new log lines of `pods` to `on_line` with the pod name.
+    ///
+    /// Reads once, or with `follow` every interval until `stop` completes. A
+    /// failed read is logged and retried from the same position on the next
+    /// poll.
+    pub async fn follow_logs(
+        &self,
+        pods: &mut [PodLogs],
+        follow: Option<Duration>,
+        stop: impl Future<Output = ()>,
+        mut on_line: impl FnMut(&str, &str),
+    ) {
+        tokio::pin!(stop);
+        loop {
+            for pod in pods.iter_mut() {
+                for cursor in &mut pod.cursors {
+                    match self.read_logs(&pod.pod_id, cursor).await {
+                        Ok(lines) => {
+                            for line in lines {
+                                on_line(&pod.name, &line);
+                            }
+                        }
+                        Err(e) => warn!("Failed to read {} on {}: {e}", cursor.file, pod.name),
+                    }
+                }
+            }
+
+            let Some(interval) = follow else {
+                return;
+            };
+            tokio::select! {
+                () = tokio::time::sleep(interval) => {}
+                () = &mut stop => return,
+            }
+        }
+    }
+}
+
+/// Returns the manifest key of a model downloaded from `repo` at `revision`.
+fn manifest_key(model_path: &str, repo: &str, revision: &str) -> String {
+    format!("model {model_path} {repo}@{revision}")
+}
+
+/// Returns a shell prelude and the prefix that runs a download command at up
+/// to `limit` KB/s with `trickle`, or unthrottled if the pod lacks it.
+fn throttle(limit: Option<u32>) -> (String, &'static str) {
+    limit.map_or_else(
+        || (String::new(), ""),
+        |limit| {
+            let prelude = format!(
+                "hdl_dl() {{ if command -v trickle >/dev/null 2>&1; then trickle -s -d {limit} \"$@\"; \
+                 else echo 'trickle not found, downloading without a bandwidth cap' >&2; \"$@\"; fi; }}; "
+            );
+            (prelude, "hdl_dl ")
+        },
+    )
+}
+
+/// Returns the last non-empty line of a command's output.
+fn last_line(output: &str) -> String {
+    output
+        .lines()
+        .map(str::trim)
+        .rfind(|l| !l.is_empty())
+        .unwrap_or("download failed")
+        .to_string()
+}
+
+/// Parses `<line count> <path>` lines into cursors `tail` lines before the end.
+fn parse_log_listing(listing: &str, tail: u32) -> Vec<LogCursor> {
+    listing
+        .lines()
+        .filter_map(|line| {
+            let (count, file) = line.trim().split_once(' ')?;
+            let count: usize = count.parse().ok()?;
+            Some(LogCursor {
+                file: file.to_string(),
+                lines_read: count.saturating_sub(tail as usize),
+            })
+        })
+        .collect()
+}
+
+/// Checks that a completion response is a 200 with non-empty generated text.
+fn check_smoke_response(status: &str, body: &str) -> std::result::Result<(), String> {
+    if status != "200" {
+        return Err(format!("expected HTTP 200, got {status}"));
+    }
+
+    let json: serde_json::Value =
+        serde_json::from_str(body).map_err(|e| format!("invalid JSON response: {e}"))?;
+    let text = json
+        .pointer("/choices/0/text")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or_default();
+
+    if text.trim().is_empty() {
+        return Err(String::from("completion returned no text"));
+    }
+
+    Ok(())
 }
 
 /// Result of post-provisioning setup.
@@ -630,9 +1343,195 @@ impl PostProvisionResult {
         let engines_ok = self.engine_results.iter().filter(|r| r.success).count();
         let engines_total = self.engine_results.len();
 
+        let mut mirrors: Vec<&str> = self
+            .model_results
+            .iter()
+            .filter_map(|r| r.mirror.as_deref())
+            .collect();
+        mirrors.dedup();
+        let via = if mirrors.is_empty() {
+            String::new()
+        } else {
+            format!(" (via {})", mirrors.join(", "))
+        };
+        let cached = self.model_results.iter().filter(|r| r.cached).count();
+        let skipped = if cached == 0 {
+            String::new()
+        } else {
+            format!(" ({cached} already installed)")
+        };
+
         format!(
-            "Pod {}: Models {}/{} OK, Engines {}/{} OK",
+            "Pod {}: Models {}/{} OK{skipped}, Engines {}/{} OK{via}",
             self.pod_id, models_ok, models_total, engines_ok, engines_total
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_string_contains, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    /// Mounts a one-off exec response for commands containing `command`.
+    async fn mount_exec(server: &MockServer, command: &str, response: ResponseTemplate) {
+        Mock::given(path("/pod-a/runsync"))
+            .and(body_string_contains(command))
+            .respond_with(response)
+            .up_to_n_times(1)
+            .mount(server)
+            .await;
+    }
+
+    fn output(stdout: &str) -> ResponseTemplate {
+        ResponseTemplate::new(200)
+            .set_body_json(serde_json::json!({ "output": { "stdout": stdout, "exit_code": 0 } }))
+    }
+
+    #[tokio::test]
+    async fn test_follow_logs_retries_and_stops() {
+        let server = MockServer::start().await;
+        // The first read fails, then the log grows by two lines and one more
+        mount_exec(&server, "1,$p", ResponseTemplate::new(500)).await;
+        mount_exec(&server, "1,$p", output("one\ntwo\n")).await;
+        mount_exec(&server, "3,$p", output("three\n")).await;
+        Mock::given(path("/pod-a/runsync"))
+            .respond_with(output(""))
+            .mount(&server)
+            .await;
+
+        let client = RunPodClient::new("test-key")
+            .unwrap()
+            .with_api_url(server.uri())
+            .with_exec_url(server.uri());
+        let executor = PodExecutor::new(client);
+        let mut pods = [PodLogs {
+            name: String::from("llm"),
+            pod_id: String::from("pod-a"),
+            cursors: vec![LogCursor {
+                file: String::from("/var/log/vllm.log"),
+                lines_read: 0,
+            }],
+        }];
+        let mut lines = Vec::new();
+
+        // Without following, one pass ends the loop even though the read failed
+        executor
+            .follow_logs(&mut pods, None, std::future::pending(), |_, line| {
+                lines.push(line.to_string());
+            })
+            .await;
+        assert!(lines.is_empty());
+        assert_eq!(pods[0].cursors[0].lines_read, 0);
+
+        // Following picks up where the failed read left off until stopped
+        executor
+            .follow_logs(
+                &mut pods,
+                Some(Duration::from_millis(10)),
+                tokio::time::sleep(Duration::from_millis(300)),
+                |name, line| lines.push(format!("[{name}] {line}")),
+            )
+            .await;
+        assert_eq!(lines, ["[llm] one", "[llm] two", "[llm] three"]);
+        assert_eq!(pods[0].cursors[0].lines_read, 3);
+    }
+
+    #[test]
+    fn test_command_result_exit_code() {
+        let finished = |success, exit_code| CommandResult {
+            success,
+            stdout: String::new(),
+            stderr: String::new(),
+            exit_code,
+        };
+        let process_exit = |result: CommandResult| result.check("llm").map_err(|e| e.exit_code());
+
+        assert_eq!(process_exit(finished(true, Some(0))), Ok(()));
+        assert_eq!(process_exit(finished(true, None)), Ok(()));
+        assert_eq!(process_exit(finished(false, Some(3))), Err(3));
+        assert_eq!(process_exit(finished(false, None)), Err(1));
+        // Codes a process cannot exit with fall back to 1
+        assert_eq!(process_exit(finished(false, Some(256))), Err(1));
+        assert_eq!(process_exit(finished(false, Some(-1))), Err(1));
+    }
+
+    #[test]
+    fn test_check_smoke_response() {
+        let ok = r#"{"choices":[{"index":0,"text":" world!"}]}"#;
+        assert!(check_smoke_response("200", ok).is_ok());
+
+        let empty = r#"{"choices":[{"index":0,"text":""}]}"#;
+        assert!(check_smoke_response("200", empty).is_err());
+        assert!(check_smoke_response("500", ok).is_err());
+        assert!(check_smoke_response("200", "Internal Server Error").is_err());
+    }
+
+    #[test]
+    fn test_download_throttle() {
+        let downloads = ModelDownloadsConfig {
+            parallel: 4,
+            max_mb_per_sec: Some(100),
+        };
+        assert_eq!(downloads.concurrency(), 4);
+        let limit = downloads.per_download_kb_per_sec();
+        assert_eq!(limit, Some(25_600));
+
+        let (prelude, dl) = throttle(limit);
+        assert!(prelude.contains("trickle -s -d 25600"));
+        assert_eq!(dl, "hdl_dl ");
+        assert_eq!(throttle(None), (String::new(), ""));
+        assert_eq!(
+            ModelDownloadsConfig::default().per_download_kb_per_sec(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_summary_counts_cached_models() {
+        let model = |id: &str, cached| ModelSetupResult {
+            model_id: id.to_string(),
+            success: true,
+            model_path: Some(format!("/models/{id}")),
+            error: None,
+            mirror: None,
+            cached,
+        };
+        let result = PostProvisionResult {
+            pod_id: String::from("pod1"),
+            success: true,
+            model_results: vec![model("llm", true), model("embed", false)],
+            engine_results: vec![],
+        };
+        assert_eq!(
+            result.summary(),
+            "Pod pod1: Models 2/2 OK (1 already installed), Engines 0/0 OK"
+        );
+        assert_eq!(
+            manifest_key("/models/llm", "org/llm", "main"),
+            "model /models/llm org/llm@main"
+        );
+    }
+
+    #[test]
+    fn test_parse_log_listing() {
+        let cursors = parse_log_listing(
+            "1500 /var/log/vllm.log\n20 /var/log/tgi.log\ngarbage\n",
+            100,
+        );
+        assert_eq!(
+            cursors,
+            vec![
+                LogCursor {
+                    file: String::from("/var/log/vllm.log"),
+                    lines_read: 1400
+                },
+                LogCursor {
+                    file: String::from("/var/log/tgi.log"),
+                    lines_read: 0
+                },
+            ]
+        );
+    }
+}
@@ -4,6 +4,7 @@
 //! including HTTP endpoint checks and service availability monitoring.
 
 use reqwest::Client;
+use std::collections::HashMap;
 use std::time::Duration;
 use tracing::{debug, warn};
 
@@ -57,32 +58,111 @@ pub struct HealthChecker {
     client: Client,
     /// Default health check configuration.
     default_config: HealthCheckConfig,
+    /// Request timeout overriding each check's `timeout_secs`.
+    timeout: Option<Duration>,
 }
 
-impl HealthChecker {
-    /// Creates a new health checker.
+/// Builder for [`HealthChecker`].
+#[derive(Debug, Default)]
+pub struct HealthCheckerBuilder {
+    /// Request timeout override.
+    timeout: Option<Duration>,
+    /// Connection timeout for the built-in client.
+    connect_timeout: Option<Duration>,
+    /// Caller-provided HTTP client.
+    client: Option<Client>,
+    /// Check used for pods without their own configuration.
+    default_config: Option<HealthCheckConfig>,
+}
+
+impl HealthCheckerBuilder {
+    /// Sets the request timeout, overriding each check's `timeout_secs`.
+    #[must_use]
+    pub const fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the connection timeout of the built-in client.
+    ///
+    /// Ignored when a custom client is supplied.
+    #[must_use]
+    pub const fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Uses a caller-configured HTTP client (proxies, custom TLS roots, mocks).
+    #[must_use]
+    pub fn client(mut self, client: Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Sets the check used for pods without their own configuration.
+    #[must_use]
+    pub fn default_config(mut self, config: HealthCheckConfig) -> Self {
+        self.default_config = Some(config);
+        self
+    }
+
+    /// Builds the health checker.
     ///
     /// # Errors
     ///
-    /// Returns an error if the HTTP client cannot be created.
-    pub fn new() -> Result<Self> {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(DEFAULT_TIMEOUT_SECS))
-            .connect_timeout(Duration::from_secs(DEFAULT_CONNECT_TIMEOUT_SECS))
-            .build()
-            .map_err(|e| crate::error::HalldyllError::internal(format!("Failed to create HTTP client: {e}")))?;
-
-        Ok(Self {
+    /// Returns an error if the built-in HTTP client cannot be created.
+    pub fn build(self) -> Result<HealthChecker> {
+        let client = match self.client {
+            Some(client) => client,
+            None => Client::builder()
+                .timeout(
+                    self.timeout
+                        .unwrap_or(Duration::from_secs(DEFAULT_TIMEOUT_SECS)),
+                )
+                .connect_timeout(
+                    self.connect_timeout
+                        .unwrap_or(Duration::from_secs(DEFAULT_CONNECT_TIMEOUT_SECS)),
+                )
+                .build()
+                .map_err(|e| {
+                    crate::error::HalldyllError::internal(format!(
+                        "Failed to create HTTP client: {e}"
+                    ))
+                })?,
+        };
+
+        Ok(HealthChecker {
             client,
-            default_config: HealthCheckConfig {
+            default_config: self.default_config.unwrap_or_else(|| HealthCheckConfig {
                 endpoint: String::from("/health"),
                 port: 8000,
                 interval_secs: 30,
                 timeout_secs: 5,
                 failure_threshold: 3,
-            },
+                headers: HashMap::new(),
+                expect_status: None,
+                expect_body_contains: None,
+            }),
+            timeout: self.timeout,
         })
     }
+}
+
+impl HealthChecker {
+    /// Returns a builder for configuring timeouts or the HTTP client.
+    #[must_use]
+    pub fn builder() -> HealthCheckerBuilder {
+        HealthCheckerBuilder::default()
+    }
+
+    /// Creates a new health checker.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP client cannot be created.
+    pub fn new() -> Result<Self> {
+        Self::builder().build()
+    }
 
     /// Creates a health checker with a custom configuration.
     ///
@@ -90,20 +170,19 @@ impl HealthChecker {
     ///
     /// Returns an error if the HTTP client cannot be created.
     pub fn with_config(config: HealthCheckConfig) -> Result<Self> {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(u64::from(config.timeout_secs)))
-            .connect_timeout(Duration::from_secs(DEFAULT_CONNECT_TIMEOUT_SECS))
-            .build()
-            .map_err(|e| crate::error::HalldyllError::internal(format!("Failed to create HTTP client: {e}")))?;
-
-        Ok(Self {
-            client,
-            default_config: config,
-        })
+        Self::builder().default_config(config).build()
     }
 
     /// Checks the health of a pod.
-    pub async fn check_pod(&self, pod: &ObservedPod, config: Option<&HealthCheckConfig>) -> HealthStatus {
+    ///
+    /// With an explicit configuration only the configured port is probed;
+    /// otherwise every exposed endpoint is checked with the default settings.
+    pub async fn check_pod(
+        &self,
+        pod: &ObservedPod,
+        config: Option<&HealthCheckConfig>,
+    ) -> HealthStatus {
+        let explicit = config.is_some();
         let config = config.map_or(&self.default_config, |c| c);
 
         debug!("Checking health of pod: {}", pod.id);
@@ -113,8 +192,12 @@ impl HealthChecker {
 
         // Check each endpoint
         for (port, url) in &pod.endpoints {
+            if explicit && *port != config.port {
+                continue;
+            }
+
             let check_url = format!("{url}{}", config.endpoint);
-            let check = self.check_endpoint(*port, &check_url).await;
+            let check = self.check_endpoint(*port, &check_url, config).await;
 
             if !check.healthy {
                 all_healthy = false;
@@ -123,6 +206,19 @@ impl HealthChecker {
             checks.push(check);
         }
 
+        // A configured port that is not exposed can never pass
+        if explicit && checks.is_empty() && pod.is_running() {
+            all_healthy = false;
+            checks.push(EndpointCheck {
+                port: config.port,
+                url: String::new(),
+                healthy: false,
+                status_code: None,
+                response_time_ms: None,
+                error: Some(format!("Port {} is not exposed", config.port)),
+            });
+        }
+
         // If no endpoints, check if pod is running
         if checks.is_empty() {
             all_healthy = pod.is_running();
@@ -142,45 +238,114 @@ impl HealthChecker {
     }
 
     /// Checks a single HTTP endpoint.
-    async fn check_endpoint(&self, port: u16, url: &str) -> EndpointCheck {
+    async fn check_endpoint(
+        &self,
+        port: u16,
+        url: &str,
+        config: &HealthCheckConfig,
+    ) -> EndpointCheck {
+        let failed = |error: String| EndpointCheck {
+            port,
+            url: url.to_string(),
+            healthy: false,
+            status_code: None,
+            response_time_ms: None,
+            error: Some(error),
+        };
+
+        let timeout = self
+            .timeout
+            .unwrap_or_else(|| Duration::from_secs(u64::from(config.timeout_secs.max(1))));
+        let mut request = self.client.get(url).timeout(timeout);
+        for (name, value) in &config.headers {
+            match resolve_header_value(value) {
+                Ok(resolved) => request = request.header(name, resolved),
+                Err(missing) => {
+                    return failed(format!(
+                        "Header '{name}' references unset environment variable {missing}"
+                    ));
+                }
+            }
+        }
+
         let start = std::time::Instant::now();
 
-        match self.client.get(url).send().await {
+        match request.send().await {
             Ok(response) => {
                 let status = response.status();
                 let response_time = u64::try_from(start.elapsed().as_millis()).unwrap_or(u64::MAX);
 
-                let healthy = status.is_success();
+                let mut error = match config.expect_status {
+                    Some(expected) if status.as_u16() != expected => {
+                        Some(format!("HTTP {status}, expected {expected}"))
+                    }
+                    None if !status.is_success() => Some(format!("HTTP {status}")),
+                    _ => None,
+                };
+
+                if error.is_none()
+                    && let Some(needle) = &config.expect_body_contains
+                {
+                    match response.text().await {
+                        Ok(body) if body.contains(needle.as_str()) => {}
+                        Ok(_) => error = Some(format!("Response body does not contain '{needle}'")),
+                        Err(e) => error = Some(format!("Failed to read response body: {e}")),
+                    }
+                }
 
-                if !healthy {
-                    debug!("Endpoint {url} returned status {status}");
+                if let Some(reason) = &error {
+                    debug!("Endpoint {url} failed health check: {reason}");
                 }
 
                 EndpointCheck {
                     port,
                     url: url.to_string(),
-                    healthy,
+                    healthy: error.is_none(),
                     status_code: Some(status.as_u16()),
                     response_time_ms: Some(response_time),
-                    error: if healthy {
-                        None
-                    } else {
-                        Some(format!("HTTP {status}"))
-                    },
+                    error,
                 }
             }
             Err(e) => {
                 warn!("Health check failed for {url}: {e}");
+                failed(e.to_string())
+            }
+        }
+    }
+
+    /// Probes whether an endpoint is reachable at all.
+    ///
+    /// Unlike a health check, any response below 500 counts as live: the
+    /// server answered, even if the path is unknown. Gateway errors from the
+    /// `RunPod` proxy mean nothing is listening behind the port.
+    pub async fn probe_endpoint(&self, port: u16, url: &str) -> EndpointCheck {
+        let timeout = self.timeout.unwrap_or_else(|| {
+            Duration::from_secs(u64::from(self.default_config.timeout_secs.max(1)))
+        });
+        let start = std::time::Instant::now();
 
+        match self.client.get(url).timeout(timeout).send().await {
+            Ok(response) => {
+                let status = response.status();
                 EndpointCheck {
                     port,
                     url: url.to_string(),
-                    healthy: false,
-                    status_code: None,
-                    response_time_ms: None,
-                    error: Some(e.to_string()),
+                    healthy: !status.is_server_error(),
+                    status_code: Some(status.as_u16()),
+                    response_time_ms: Some(
+                        u64::try_from(start.elapsed().as_millis()).unwrap_or(u64::MAX),
+                    ),
+                    error: status.is_server_error().then(|| format!("HTTP {status}")),
                 }
             }
+            Err(e) => EndpointCheck {
+                port,
+                url: url.to_string(),
+                healthy: false,
+                status_code: None,
+                response_time_ms: None,
+                error: Some(e.to_string()),
+            },
         }
     }
 
@@ -195,6 +360,25 @@ impl HealthChecker {
         results
     }
 
+    /// Checks health of multiple pods using each pod's configured health check.
+    ///
+    /// `configs` maps local pod names (replica instance names included) to
+    /// their health check; pods without an entry use the default check.
+    pub async fn check_pods_with_configs(
+        &self,
+        pods: &[ObservedPod],
+        configs: &HashMap<String, HealthCheckConfig>,
+    ) -> Vec<HealthStatus> {
+        let mut results = Vec::with_capacity(pods.len());
+
+        for pod in pods {
+            let config = pod.pod_name.as_ref().and_then(|name| configs.get(name));
+            results.push(self.check_pod(pod, config).await);
+        }
+
+        results
+    }
+
     /// Waits for a pod to become healthy.
     ///
     /// # Errors
@@ -232,6 +416,32 @@ impl HealthChecker {
     }
 }
 
+/// Expands `${VAR}` references in a header value from the environment.
+///
+/// Returns the name of the first unset variable on failure. Resolved values
+/// are never logged, so secrets can be kept out of the configuration file.
+fn resolve_header_value(value: &str) -> std::result::Result<String, String> {
+    let mut resolved = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(start) = rest.find("${") {
+        resolved.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('}') else {
+            resolved.push_str(&rest[start..]);
+            return Ok(resolved);
+        };
+
+        let name = &after[..end];
+        let var = std::env::var(name).map_err(|_| name.to_string())?;
+        resolved.push_str(&var);
+        rest = &after[end + 1..];
+    }
+
+    resolved.push_str(rest);
+    Ok(resolved)
+}
+
 impl HealthStatus {
     /// Returns true if all endpoints are healthy.
     #[must_use]
@@ -283,3 +493,63 @@ impl std::fmt::Display for HealthStatus {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_builder_timeout_and_client() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/health"))
+            .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_millis(500)))
+            .mount(&server)
+            .await;
+
+        let url = format!("{}/health", server.uri());
+        let config = HealthCheckConfig {
+            endpoint: String::from("/health"),
+            port: 8000,
+            interval_secs: 30,
+            timeout_secs: 5,
+            failure_threshold: 3,
+            headers: HashMap::new(),
+            expect_status: None,
+            expect_body_contains: None,
+        };
+
+        let patient = HealthChecker::builder()
+            .client(Client::new())
+            .build()
+            .unwrap();
+        assert!(patient.check_endpoint(8000, &url, &config).await.healthy);
+
+        let impatient = HealthChecker::builder()
+            .timeout(Duration::from_millis(100))
+            .build()
+            .unwrap();
+        assert!(!impatient.check_endpoint(8000, &url, &config).await.healthy);
+    }
+
+    #[tokio::test]
+    async fn test_probe_endpoint_liveness() {
+        let server = MockServer::start().await;
+        Mock::given(path("/down"))
+            .respond_with(ResponseTemplate::new(502))
+            .mount(&server)
+            .await;
+
+        let checker = HealthChecker::new().unwrap();
+        // Unknown paths still prove the server is listening
+        assert!(checker.probe_endpoint(8000, &server.uri()).await.healthy);
+
+        let down = checker
+            .probe_endpoint(8000, &format!("{}/down", server.uri()))
+            .await;
+        assert!(!down.healthy);
+        assert_eq!(down.status_code, Some(502));
+    }
+}
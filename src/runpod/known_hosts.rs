@@ -0,0 +1,189 @@
+//! Trust-on-first-use host keys for SSH connections to pods.
+//!
+//! The first host key a pod presents is recorded in `.halldyll/known_hosts`
+//! under the pod's ID, and later connections must present the same key. A
+//! recreated pod gets a new ID and so starts with a fresh entry; entries are
+//! dropped when their pod is terminated.
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::info;
+
+use crate::error::{HalldyllError, Result};
+
+/// Outcome of checking a pod's host key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HostKeyCheck {
+    /// The key matches the recorded one.
+    Trusted,
+    /// The pod had no recorded key; this one was recorded.
+    Recorded,
+    /// The key differs from the recorded one.
+    Mismatch,
+}
+
+/// A `known_hosts` file keyed by pod ID.
+///
+/// Each line holds `<pod id> <host:port> <key type> <base64 key>`.
+#[derive(Debug)]
+pub struct KnownHosts {
+    /// File the keys are stored in.
+    path: PathBuf,
+    /// Serializes reads and writes from concurrent connections.
+    lock: Mutex<()>,
+}
+
+/// A recorded host key.
+struct Entry {
+    /// Pod the key belongs to.
+    pod_id: String,
+    /// Endpoint the key was first seen on.
+    endpoint: String,
+    /// Key in OpenSSH format.
+    key: String,
+}
+
+impl KnownHosts {
+    /// Creates a store backed by `path` (usually `.halldyll/known_hosts`).
+    #[must_use]
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Returns the file the keys are stored in.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Checks `key` (OpenSSH format) against the one recorded for the pod,
+    /// recording it if the pod has none yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or written.
+    pub fn check(&self, pod_id: &str, endpoint: &str, key: &str) -> Result<HostKeyCheck> {
+        let _guard = self
+            .lock
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let mut entries = self.read()?;
+
+        if let Some(entry) = entries.iter().find(|e| e.pod_id == pod_id) {
+            return Ok(if entry.key == key.trim() {
+                HostKeyCheck::Trusted
+            } else {
+                HostKeyCheck::Mismatch
+            });
+        }
+
+        info!(
+            "Recording SSH host key of pod {pod_id} ({endpoint}) in {}",
+            self.path.display()
+        );
+        entries.push(Entry {
+            pod_id: pod_id.to_string(),
+            endpoint: endpoint.to_string(),
+            key: key.trim().to_string(),
+        });
+        self.write(&entries)?;
+        Ok(HostKeyCheck::Recorded)
+    }
+
+    /// Drops the key recorded for a pod.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or written.
+    pub fn forget(&self, pod_id: &str) -> Result<()> {
+        let _guard = self
+            .lock
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let mut entries = self.read()?;
+        let before = entries.len();
+        entries.retain(|e| e.pod_id != pod_id);
+        if entries.len() == before {
+            return Ok(());
+        }
+        self.write(&entries)
+    }
+
+    /// Reads the recorded keys; a missing file has none.
+    fn read(&self) -> Result<Vec<Entry>> {
+        let content = match std::fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(self.io_error(&e)),
+        };
+
+        Ok(content
+            .lines()
+            .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let mut parts = line.splitn(3, ' ');
+                Some(Entry {
+                    pod_id: parts.next()?.to_string(),
+                    endpoint: parts.next()?.to_string(),
+                    key: parts.next()?.trim().to_string(),
+                })
+            })
+            .collect())
+    }
+
+    /// Rewrites the file with `entries`.
+    fn write(&self, entries: &[Entry]) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| self.io_error(&e))?;
+        }
+        let mut content = String::new();
+        for e in entries {
+            let _ = writeln!(content, "{} {} {}", e.pod_id, e.endpoint, e.key);
+        }
+        std::fs::write(&self.path, content).map_err(|e| self.io_error(&e))
+    }
+
+    /// Wraps an I/O failure on the file.
+    fn io_error(&self, e: &std::io::Error) -> HalldyllError {
+        HalldyllError::internal(format!("Failed to access {}: {e}", self.path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trust_on_first_use() {
+        let temp = tempfile::tempdir().unwrap();
+        let known_hosts = KnownHosts::new(temp.path().join(".halldyll/known_hosts"));
+
+        let key = "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIExample";
+        assert_eq!(
+            known_hosts.check("abc", "1.2.3.4:40122", key).unwrap(),
+            HostKeyCheck::Recorded
+        );
+        assert_eq!(
+            known_hosts.check("abc", "1.2.3.4:40122", key).unwrap(),
+            HostKeyCheck::Trusted
+        );
+        assert_eq!(
+            known_hosts
+                .check("abc", "1.2.3.4:40122", "ssh-ed25519 AAAAOther")
+                .unwrap(),
+            HostKeyCheck::Mismatch
+        );
+
+        known_hosts.forget("abc").unwrap();
+        assert_eq!(
+            known_hosts
+                .check("abc", "1.2.3.4:40122", "ssh-ed25519 AAAAOther")
+                .unwrap(),
+            HostKeyCheck::Recorded
+        );
+    }
+}
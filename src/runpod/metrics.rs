@@ -0,0 +1,168 @@
+//! Inference engine metrics scraping.
+//!
+//! This module scrapes the Prometheus endpoint exposed by inference engines
+//! (vLLM, TGI) and extracts the load signals used by the autoscaler.
+
+use reqwest::Client;
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::debug;
+
+use crate::error::{HalldyllError, Result, RunPodError};
+
+use super::observer::ObservedPod;
+
+/// Metrics scrape timeout in seconds.
+const SCRAPE_TIMEOUT_SECS: u64 = 5;
+
+/// Metric names reporting requests currently being processed.
+const RUNNING_METRICS: &[&str] = &["vllm:num_requests_running", "tgi_batch_current_size"];
+
+/// Metric names reporting requests waiting in the queue.
+const WAITING_METRICS: &[&str] = &["vllm:num_requests_waiting", "tgi_queue_size"];
+
+/// Load signals scraped from an inference engine.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EngineMetrics {
+    /// Requests currently being processed.
+    pub running_requests: f64,
+    /// Requests waiting in the queue.
+    pub waiting_requests: f64,
+}
+
+/// Scraper for engine metrics endpoints.
+#[derive(Debug)]
+pub struct MetricsScraper {
+    /// HTTP client for scraping.
+    client: Client,
+}
+
+impl MetricsScraper {
+    /// Creates a new metrics scraper.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP client cannot be created.
+    pub fn new() -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(SCRAPE_TIMEOUT_SECS))
+            .build()
+            .map_err(|e| HalldyllError::internal(format!("Failed to create HTTP client: {e}")))?;
+
+        Ok(Self { client })
+    }
+
+    /// Scrapes engine metrics from a pod's public endpoint.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the port is not exposed or the endpoint cannot be read.
+    pub async fn scrape(&self, pod: &ObservedPod, port: u16, path: &str) -> Result<EngineMetrics> {
+        let base = pod.endpoints.get(&port).ok_or_else(|| {
+            HalldyllError::RunPod(RunPodError::InvalidResponse {
+                message: format!("Pod {} does not expose port {port}", pod.id),
+            })
+        })?;
+        let url = format!("{base}{path}");
+
+        debug!("Scraping metrics from {url}");
+
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| RunPodError::network(format!("Failed to scrape {url}: {e}")))?;
+
+        if !response.status().is_success() {
+            return Err(HalldyllError::RunPod(RunPodError::api_error(
+                response.status().as_u16(),
+                format!("Metrics endpoint {url} returned an error"),
+            )));
+        }
+
+        let body = response
+            .text()
+            .await
+            .map_err(|e| RunPodError::network(format!("Failed to read {url}: {e}")))?;
+
+        Ok(EngineMetrics::from_samples(&parse_prometheus(&body)))
+    }
+}
+
+impl EngineMetrics {
+    /// Builds engine metrics from parsed Prometheus samples.
+    #[must_use]
+    pub fn from_samples(samples: &HashMap<String, f64>) -> Self {
+        let sum = |names: &[&str]| names.iter().filter_map(|n| samples.get(*n)).sum();
+
+        Self {
+            running_requests: sum(RUNNING_METRICS),
+            waiting_requests: sum(WAITING_METRICS),
+        }
+    }
+
+    /// Returns the number of in-flight requests (running + waiting).
+    #[must_use]
+    pub fn concurrent_requests(&self) -> f64 {
+        self.running_requests + self.waiting_requests
+    }
+}
+
+/// Parses Prometheus text exposition format.
+///
+/// Samples sharing a metric name (e.g. one per model label) are summed.
+#[must_use]
+pub fn parse_prometheus(text: &str) -> HashMap<String, f64> {
+    let mut samples = HashMap::new();
+
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (name, rest) = match line.find(['{', ' ']) {
+            Some(idx) if line.as_bytes()[idx] == b'{' => {
+                let Some(close) = line[idx..].find('}') else {
+                    continue;
+                };
+                (&line[..idx], &line[idx + close + 1..])
+            }
+            Some(idx) => (&line[..idx], &line[idx..]),
+            None => continue,
+        };
+
+        if let Some(value) = rest
+            .split_whitespace()
+            .next()
+            .and_then(|v| v.parse::<f64>().ok())
+        {
+            *samples.entry(name.to_string()).or_insert(0.0) += value;
+        }
+    }
+
+    samples
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_vllm_metrics() {
+        let body = r#"
+# HELP vllm:num_requests_running Number of requests currently running on GPU.
+# TYPE vllm:num_requests_running gauge
+vllm:num_requests_running{model_name="llama-3-8b"} 4.0
+vllm:num_requests_running{model_name="mistral-7b"} 2.0
+vllm:num_requests_waiting{model_name="llama-3-8b"} 3.0
+process_cpu_seconds_total 12.5
+"#;
+        let metrics = EngineMetrics::from_samples(&parse_prometheus(body));
+
+        assert!((metrics.running_requests - 6.0).abs() < f64::EPSILON);
+        assert!((metrics.waiting_requests - 3.0).abs() < f64::EPSILON);
+        assert!((metrics.concurrent_requests() - 9.0).abs() < f64::EPSILON);
+    }
+}
@@ -4,19 +4,47 @@
 //! including pod creation, management, observation, and health checking.
 
 mod client;
-mod types;
-mod provisioner;
-mod observer;
-mod health;
+mod drain;
+mod engine;
 mod executor;
+mod health;
+mod known_hosts;
+mod metrics;
+mod observer;
+mod provisioner;
+mod serverless;
+mod ssh;
+#[cfg(feature = "ssh")]
+mod ssh_client;
+mod templates;
+mod transcript;
+mod types;
+mod volumes;
 
 pub use client::RunPodClient;
-pub use types::{
-    CreatePodRequest, GpuType, Pod, PodEndpoint, PodStatus, RunPodGpu, UpdatePodRequest,
+pub use drain::{DrainOutcome, EngineDrainer};
+pub use engine::{
+    EngineProbe, fit_parallelism, model_matches, parse_attention_heads, parse_model_list,
 };
-pub use provisioner::PodProvisioner;
-pub use observer::{PodObserver, ObservedPod, ProjectStatus};
-pub use health::{HealthChecker, HealthStatus};
 pub use executor::{
-    PodExecutor, CommandResult, ModelSetupResult, EngineStartResult, PostProvisionResult,
+    CommandResult, ENGINE_LOG_FILES, EngineStartResult, LogCursor, ModelSetupResult, OutputChunk,
+    PodExecutor, PodLogs, PostProvisionResult,
+};
+pub use health::{HealthChecker, HealthCheckerBuilder, HealthStatus};
+pub use known_hosts::{HostKeyCheck, KnownHosts};
+pub use metrics::{EngineMetrics, MetricsScraper, parse_prometheus};
+pub use observer::{LEGACY_TAG_PREFIX, ObservedPod, PodObserver, ProjectStatus, tag_key};
+pub use provisioner::PodProvisioner;
+pub use serverless::{EndpointManager, endpoint_name};
+pub use ssh::{
+    OutputStream, PUBLIC_KEY_ENV, SSH_KEY_ENV, authorized_keys_command, keys_fingerprint,
+};
+#[cfg(feature = "ssh")]
+pub use ssh_client::SshClient;
+pub use templates::{TemplateManager, template_name};
+pub use transcript::{Transcript, TranscriptEntry};
+pub use types::{
+    AccountInfo, CreatePodRequest, GpuType, NetworkVolume, Pod, PodEndpoint, PodStatus,
+    PodTemplate, RunPodGpu, ServerlessEndpoint, UpdatePodRequest,
 };
+pub use volumes::{VolumeManager, network_volume_name};
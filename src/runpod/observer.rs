@@ -2,36 +2,65 @@
 //!
 //! This module provides functionality for observing and querying the state
 //! of pods on `RunPod`, including filtering by tags for project-specific queries.
+//! Pods adopted with `halldyll import` have no tags and pods promoted from a
+//! blue/green release carry their release's tags, so both are matched by ID.
+//!
+//! System tags are namespaced (`halldyll.io/project`, ...) so other tools
+//! tagging the same pods cannot collide with them. Pods created before
+//! namespacing carry legacy `halldyll_*` tags, which are read only from pods
+//! without any tag in the namespace.
 
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use tracing::{debug, info};
 
+use crate::config::DEFAULT_TAG_NAMESPACE;
 use crate::error::Result;
+use crate::state::DeploymentState;
 
 use super::client::RunPodClient;
 use super::types::{Pod, PodStatus};
 
-/// Tag key for project identification.
-pub const TAG_PROJECT: &str = "halldyll_project";
+/// Prefix of the system tags written before they were namespaced.
+pub const LEGACY_TAG_PREFIX: &str = "halldyll_";
+
+/// Tag name for project identification.
+pub const TAG_PROJECT: &str = "project";
+
+/// Tag name for environment identification.
+pub const TAG_ENV: &str = "env";
 
-/// Tag key for environment identification.
-pub const TAG_ENV: &str = "halldyll_env";
+/// Tag name for pod name.
+pub const TAG_POD: &str = "pod";
 
-/// Tag key for pod name.
-pub const TAG_POD: &str = "halldyll_pod";
+/// Tag name for spec hash.
+pub const TAG_SPEC_HASH: &str = "spec_hash";
+
+/// Returns the key of system tag `name` in `namespace`.
+#[must_use]
+pub fn tag_key(namespace: &str, name: &str) -> String {
+    format!("{namespace}/{name}")
+}
 
-/// Tag key for spec hash.
-pub const TAG_SPEC_HASH: &str = "halldyll_spec_hash";
+/// `RunPod` price of a stopped pod's volume disk, in USD per GB per month.
+const STOPPED_VOLUME_PRICE_PER_GB_MONTH: f64 = 0.20;
+
+/// Hours in an average month, as used by `RunPod` billing.
+const HOURS_PER_MONTH: f64 = 730.0;
 
 /// Pod observer for monitoring pods.
 #[derive(Debug)]
 pub struct PodObserver {
     /// `RunPod` API client.
     client: RunPodClient,
+    /// Local names and environments of imported or promoted pods, by `RunPod` ID.
+    imported: HashMap<String, (String, String)>,
+    /// Namespace of the system tags.
+    tag_namespace: String,
 }
 
 /// Observed pod information.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ObservedPod {
     /// Pod ID.
     pub id: String,
@@ -55,15 +84,49 @@ pub struct ObservedPod {
     pub image: String,
     /// Endpoints.
     pub endpoints: HashMap<u16, String>,
+    /// Average GPU utilization in percent (if reported).
+    pub gpu_utilization: Option<f32>,
     /// All tags.
     pub tags: HashMap<String, String>,
+    /// Size of the pod's volume disk in GB (billed while the pod is stopped).
+    #[serde(default)]
+    pub volume_gb: u32,
+    /// Seconds since the pod last started (0 unless running).
+    #[serde(default)]
+    pub uptime_secs: u64,
+    /// Environment variables as set on `RunPod` (`None` if not observed).
+    #[serde(default)]
+    pub env: Option<HashMap<String, String>>,
 }
 
 impl PodObserver {
     /// Creates a new pod observer.
     #[must_use]
-    pub const fn new(client: RunPodClient) -> Self {
-        Self { client }
+    pub fn new(client: RunPodClient) -> Self {
+        Self {
+            client,
+            imported: HashMap::new(),
+            tag_namespace: DEFAULT_TAG_NAMESPACE.to_string(),
+        }
+    }
+
+    /// Reads system tags from `namespace` instead of the default one.
+    #[must_use]
+    pub fn with_tag_namespace(mut self, namespace: &str) -> Self {
+        self.tag_namespace = namespace.to_string();
+        self
+    }
+
+    /// Counts the pods imported into or promoted in `state` as project pods.
+    #[must_use]
+    pub fn with_imported_pods(mut self, state: Option<&DeploymentState>) -> Self {
+        self.imported = state
+            .iter()
+            .flat_map(|s| s.pods.values().map(move |p| (s, p)))
+            .filter(|(_, p)| p.imported || p.color.is_some())
+            .map(|(s, p)| (p.runpod_id.clone(), (p.name.clone(), s.environment.clone())))
+            .collect();
+        self
     }
 
     /// Lists all pods owned by the current account.
@@ -75,7 +138,7 @@ impl PodObserver {
         info!("Listing all pods");
 
         let pods = self.client.list_pods().await?;
-        let observed: Vec<ObservedPod> = pods.iter().map(Self::to_observed).collect();
+        let observed: Vec<ObservedPod> = pods.iter().map(|pod| self.to_observed(pod)).collect();
 
         debug!("Found {} pods", observed.len());
         Ok(observed)
@@ -97,6 +160,14 @@ impl PodObserver {
 
         let filtered: Vec<ObservedPod> = all_pods
             .into_iter()
+            .map(|mut p| {
+                if let Some((name, env)) = self.imported.get(&p.id) {
+                    p.project = Some(project.to_string());
+                    p.environment = Some(env.clone());
+                    p.pod_name = Some(name.clone());
+                }
+                p
+            })
             .filter(|p| {
                 p.project.as_deref() == Some(project)
                     && p.environment.as_deref() == Some(environment)
@@ -113,6 +184,32 @@ impl PodObserver {
         Ok(filtered)
     }
 
+    /// Lists the pods of a project across all of its environments.
+    ///
+    /// Pods are sorted by environment, then name.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the API call fails.
+    pub async fn list_project_pods_all_envs(&self, project: &str) -> Result<Vec<ObservedPod>> {
+        info!("Listing pods for project: {project} (all environments)");
+
+        let mut pods: Vec<ObservedPod> = self
+            .list_all_pods()
+            .await?
+            .into_iter()
+            .filter(|p| p.project.as_deref() == Some(project))
+            .collect();
+        pods.sort_by(|a, b| {
+            a.environment
+                .cmp(&b.environment)
+                .then_with(|| a.pod_name.cmp(&b.pod_name))
+                .then_with(|| a.id.cmp(&b.id))
+        });
+
+        Ok(pods)
+    }
+
     /// Gets a specific pod by ID.
     ///
     /// # Errors
@@ -122,7 +219,7 @@ impl PodObserver {
         debug!("Getting pod: {pod_id}");
 
         let pod = self.client.get_pod(pod_id).await?;
-        Ok(Self::to_observed(&pod))
+        Ok(self.to_observed(&pod))
     }
 
     /// Finds a pod by its local name within a project.
@@ -143,8 +240,18 @@ impl PodObserver {
             .find(|p| p.pod_name.as_deref() == Some(pod_name)))
     }
 
+    /// Returns the value of system tag `name`, from the namespace or else the legacy tags.
+    fn system_tag(&self, tags: &HashMap<String, String>, name: &str) -> Option<String> {
+        let prefix = format!("{}/", self.tag_namespace);
+        if tags.keys().any(|key| key.starts_with(&prefix)) {
+            tags.get(&tag_key(&self.tag_namespace, name)).cloned()
+        } else {
+            tags.get(&format!("{LEGACY_TAG_PREFIX}{name}")).cloned()
+        }
+    }
+
     /// Converts a `RunPod` Pod to an `ObservedPod`.
-    fn to_observed(pod: &Pod) -> ObservedPod {
+    fn to_observed(&self, pod: &Pod) -> ObservedPod {
         let tags = pod.custom_tags.clone().unwrap_or_default();
 
         let endpoints = pod
@@ -153,19 +260,42 @@ impl PodObserver {
             .map(|e| (e.port, e.url))
             .collect();
 
+        let gpu_utilization = pod
+            .runtime
+            .as_ref()
+            .filter(|r| !r.gpus.is_empty())
+            .map(|r| {
+                #[allow(clippy::cast_precision_loss)]
+                let count = r.gpus.len() as f32;
+                r.gpus
+                    .iter()
+                    .map(|g| g.gpu_utilization_percent)
+                    .sum::<f32>()
+                    / count
+            });
+
         ObservedPod {
             id: pod.id.clone(),
             name: pod.name.clone(),
-            project: tags.get(TAG_PROJECT).cloned(),
-            environment: tags.get(TAG_ENV).cloned(),
-            pod_name: tags.get(TAG_POD).cloned(),
-            spec_hash: tags.get(TAG_SPEC_HASH).cloned(),
+            project: self.system_tag(&tags, TAG_PROJECT),
+            environment: self.system_tag(&tags, TAG_ENV),
+            pod_name: self.system_tag(&tags, TAG_POD),
+            spec_hash: self.system_tag(&tags, TAG_SPEC_HASH),
             status: pod.desired_status,
             gpu_type: pod.gpu_type_name().map(String::from),
             gpu_count: pod.gpu_count,
             image: pod.image_name.clone(),
             endpoints,
+            gpu_utilization,
             tags,
+            volume_gb: pod.volume_in_gb,
+            uptime_secs: pod.runtime.as_ref().map_or(0, |r| r.uptime_in_seconds),
+            env: Some(
+                pod.env
+                    .iter()
+                    .map(|e| (e.key.clone(), e.value.clone()))
+                    .collect(),
+            ),
         }
     }
 
@@ -199,6 +329,20 @@ impl PodObserver {
     ) -> Result<ProjectStatus> {
         let pods = self.list_project_pods(project, environment).await?;
 
+        Ok(ProjectStatus::from_pods(project, environment, pods))
+    }
+
+    /// Gets the underlying client reference.
+    #[must_use]
+    pub const fn client(&self) -> &RunPodClient {
+        &self.client
+    }
+}
+
+impl ProjectStatus {
+    /// Summarizes a list of observed pods.
+    #[must_use]
+    pub fn from_pods(project: &str, environment: &str, pods: Vec<ObservedPod>) -> Self {
         let mut running = 0;
         let mut stopped = 0;
         let mut error = 0;
@@ -213,7 +357,7 @@ impl PodObserver {
             }
         }
 
-        Ok(ProjectStatus {
+        Self {
             project: project.to_string(),
             environment: environment.to_string(),
             total_pods: pods.len(),
@@ -222,18 +366,33 @@ impl PodObserver {
             error,
             other,
             pods,
-        })
+            maintenance: BTreeMap::new(),
+            annotations: BTreeMap::new(),
+        }
     }
 
-    /// Gets the underlying client reference.
+    /// Annotates pods with maintenance notes.
     #[must_use]
-    pub const fn client(&self) -> &RunPodClient {
-        &self.client
+    pub fn with_maintenance(mut self, notes: impl IntoIterator<Item = (String, String)>) -> Self {
+        self.maintenance.extend(notes);
+        self
+    }
+
+    /// Annotates pods with operator notes (`notes:` and `halldyll annotate`).
+    #[must_use]
+    pub fn with_annotations(
+        mut self,
+        annotations: impl IntoIterator<Item = (String, BTreeMap<String, String>)>,
+    ) -> Self {
+        for (pod, values) in annotations {
+            self.annotations.entry(pod).or_default().extend(values);
+        }
+        self
     }
 }
 
 /// Status summary for a project.
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProjectStatus {
     /// Project name.
     pub project: String,
@@ -251,6 +410,12 @@ pub struct ProjectStatus {
     pub other: usize,
     /// All pods.
     pub pods: Vec<ObservedPod>,
+    /// Maintenance notes, by pod name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub maintenance: BTreeMap<String, String>,
+    /// Operator annotations, by pod name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub annotations: BTreeMap<String, BTreeMap<String, String>>,
 }
 
 impl ProjectStatus {
@@ -265,6 +430,25 @@ impl ProjectStatus {
     pub const fn has_errors(&self) -> bool {
         self.error > 0
     }
+
+    /// Returns the volume storage (in GB) still billed for stopped pods.
+    #[must_use]
+    pub fn stopped_volume_gb(&self) -> u32 {
+        self.pods
+            .iter()
+            .filter(|p| p.is_stopped())
+            .map(|p| p.volume_gb)
+            .sum()
+    }
+
+    /// Returns the monthly storage cost of the stopped pods in USD.
+    #[must_use]
+    pub fn stopped_storage_cost_per_month(&self) -> f64 {
+        self.pods
+            .iter()
+            .filter_map(ObservedPod::stopped_storage_cost_per_month)
+            .sum()
+    }
 }
 
 impl ObservedPod {
@@ -274,6 +458,30 @@ impl ObservedPod {
         matches!(self.status, PodStatus::Running)
     }
 
+    /// Returns true if this pod is stopped (its volume disk is still billed).
+    #[must_use]
+    pub const fn is_stopped(&self) -> bool {
+        matches!(self.status, PodStatus::Stopped | PodStatus::Exited)
+    }
+
+    /// Returns the hourly cost of keeping this pod's volume while stopped.
+    ///
+    /// `None` if the pod is not stopped.
+    #[must_use]
+    pub fn stopped_storage_cost_per_hour(&self) -> Option<f64> {
+        self.stopped_storage_cost_per_month()
+            .map(|cost| cost / HOURS_PER_MONTH)
+    }
+
+    /// Returns the monthly cost of keeping this pod's volume while stopped.
+    ///
+    /// `None` if the pod is not stopped.
+    #[must_use]
+    pub fn stopped_storage_cost_per_month(&self) -> Option<f64> {
+        self.is_stopped()
+            .then(|| f64::from(self.volume_gb) * STOPPED_VOLUME_PRICE_PER_GB_MONTH)
+    }
+
     /// Returns true if this pod is managed by Halldyll.
     #[must_use]
     pub const fn is_managed(&self) -> bool {
@@ -289,3 +497,42 @@ impl ObservedPod {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_system_tags_namespaced_and_legacy() {
+        let observer = PodObserver::new(RunPodClient::new("test-key").unwrap())
+            .with_tag_namespace("acme.dev/halldyll");
+        let pod = |tags: serde_json::Value| -> Pod {
+            serde_json::from_value(
+                serde_json::json!({ "id": "abc", "name": "n", "customTags": tags }),
+            )
+            .unwrap()
+        };
+
+        let namespaced = observer.to_observed(&pod(serde_json::json!({
+            "acme.dev/halldyll/project": "proj",
+            "acme.dev/halldyll/pod": "llm",
+            "halldyll_project": "other",
+        })));
+        assert_eq!(namespaced.project.as_deref(), Some("proj"));
+        assert_eq!(namespaced.pod_name.as_deref(), Some("llm"));
+        // Namespaced tags win outright: no legacy fallback for the missing ones
+        assert_eq!(namespaced.environment, None);
+
+        let legacy = observer.to_observed(&pod(serde_json::json!({
+            "halldyll_project": "proj",
+            "halldyll_env": "prod",
+        })));
+        assert_eq!(legacy.project.as_deref(), Some("proj"));
+        assert_eq!(legacy.environment.as_deref(), Some("prod"));
+
+        // Another tool's tags are not read as ours
+        let foreign =
+            observer.to_observed(&pod(serde_json::json!({ "halldyll.io/project": "proj" })));
+        assert_eq!(foreign.project, None);
+    }
+}
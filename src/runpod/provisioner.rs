@@ -4,13 +4,21 @@
 //! resource mapping, creation, and lifecycle management.
 
 use std::collections::HashMap;
+use std::path::PathBuf;
 use tracing::{debug, info, warn};
 
-use crate::config::{CloudType, GpuConfig, PodConfig, PortConfig, ProjectConfig, RuntimeConfig};
-use crate::error::{HalldyllError, Result, RunPodError};
+use crate::config::{
+    CloudType, GpuConfig, MaxPriceAction, PodConfig, PortConfig, ProjectConfig, RuntimeConfig,
+    VolumeConfig,
+};
+use crate::error::{ApiErrorKind, ErrorContext, HalldyllError, Result, ResultExt, RunPodError};
 
 use super::client::RunPodClient;
-use super::types::{CreatePodRequest, Pod, PodStatus};
+use super::observer::{TAG_ENV, TAG_POD, TAG_PROJECT, TAG_SPEC_HASH, tag_key};
+use super::ssh::PUBLIC_KEY_ENV;
+use super::templates::TemplateManager;
+use super::transcript::Transcript;
+use super::types::{CreatePodRequest, GpuType, Pod, PodEnvVar, PodStatus, PodTemplate};
 
 /// Default volume size in GB.
 const DEFAULT_VOLUME_GB: u32 = 50;
@@ -25,6 +33,10 @@ pub struct PodProvisioner {
     client: RunPodClient,
     /// GPU type mapping (display name -> ID).
     gpu_type_map: HashMap<String, String>,
+    /// VRAM per GPU in GB, by GPU type ID.
+    gpu_memory: HashMap<String, u32>,
+    /// Directory for setup command transcripts, if recorded.
+    transcript_dir: Option<PathBuf>,
 }
 
 impl PodProvisioner {
@@ -34,9 +46,18 @@ impl PodProvisioner {
         Self {
             client,
             gpu_type_map: HashMap::new(),
+            gpu_memory: HashMap::new(),
+            transcript_dir: None,
         }
     }
 
+    /// Records post-provisioning commands in `<dir>/<pod>.log`.
+    #[must_use]
+    pub fn with_transcript_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.transcript_dir = Some(dir.into());
+        self
+    }
+
     /// Initializes the GPU type mapping by fetching available types.
     ///
     /// # Errors
@@ -48,10 +69,12 @@ impl PodProvisioner {
         let gpu_types = self.client.list_gpu_types().await?;
 
         self.gpu_type_map.clear();
+        self.gpu_memory.clear();
         for gpu in gpu_types {
             // Map both ID and display name to the ID
             self.gpu_type_map
                 .insert(gpu.display_name.clone(), gpu.id.clone());
+            self.gpu_memory.insert(gpu.id.clone(), gpu.memory_in_gb);
             self.gpu_type_map.insert(gpu.id.clone(), gpu.id);
         }
 
@@ -64,6 +87,15 @@ impl PodProvisioner {
         self.gpu_type_map.get(gpu_type)
     }
 
+    /// Returns the VRAM of a GPU type if it is below `gpu.min_vram_gb`.
+    fn vram_shortfall(&self, gpu_config: &GpuConfig, gpu_id: &str) -> Option<u32> {
+        let min_vram = gpu_config.min_vram_gb?;
+        self.gpu_memory
+            .get(gpu_id)
+            .copied()
+            .filter(|memory| *memory < min_vram)
+    }
+
     /// Creates a pod from a pod configuration.
     ///
     /// # Errors
@@ -74,6 +106,34 @@ impl PodProvisioner {
         pod_config: &PodConfig,
         project: &ProjectConfig,
         spec_hash: &str,
+    ) -> Result<Pod> {
+        self.create_pod_in(pod_config, project, spec_hash, None)
+            .await
+    }
+
+    /// Creates a pod from a pod configuration in a specific data center.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pod cannot be created there.
+    pub async fn create_pod_in_data_center(
+        &self,
+        pod_config: &PodConfig,
+        project: &ProjectConfig,
+        spec_hash: &str,
+        data_center: &str,
+    ) -> Result<Pod> {
+        self.create_pod_in(pod_config, project, spec_hash, Some(data_center))
+            .await
+    }
+
+    /// Creates a pod, optionally pinned to a data center.
+    async fn create_pod_in(
+        &self,
+        pod_config: &PodConfig,
+        project: &ProjectConfig,
+        spec_hash: &str,
+        data_center: Option<&str>,
     ) -> Result<Pod> {
         let full_name = pod_config.full_name(project);
         info!("Creating pod: {full_name}");
@@ -81,22 +141,136 @@ impl PodProvisioner {
         // Resolve GPU type
         let gpu_type_id = self
             .resolve_gpu_type_with_fallback(&pod_config.gpu, &project.cloud_type)
-            .await?;
+            .await
+            .context(|| ErrorContext::new("resolving GPU type for").with_resource(&full_name))?;
 
         // Build the create request
-        let request = Self::build_create_request(pod_config, project, &gpu_type_id, spec_hash);
+        let mut request = Self::build_create_request(pod_config, project, &gpu_type_id, spec_hash);
+        if let Some(ssh) = pod_config.ssh_config(project) {
+            let keys = ssh.public_keys()?;
+            if !keys.is_empty() {
+                request = request.with_env(PUBLIC_KEY_ENV, &keys.join("\n"));
+            }
+        }
+        if let Some(data_center) = data_center {
+            request = request.with_data_center(data_center);
+        }
+        if let Some(template) = &pod_config.template {
+            let saved = TemplateManager::new(self.client.clone())
+                .find(project, template)
+                .await?;
+            if let Some(saved) = saved {
+                request = request.with_container_disk_gb(saved.container_disk_in_gb);
+                if let Some(id) = &saved.id {
+                    request = request.with_template_id(id);
+                }
+            }
+        }
 
-        // Create the pod
-        let pod = self.client.create_pod(&request).await?;
+        // A pinned data center (failover side or network volume) is the only
+        // place the pod can go; otherwise the regions are tried in order
+        let regions = if request.data_center_id.is_some() {
+            vec![]
+        } else {
+            pod_config.regions(project)
+        };
+        let result = if regions.is_empty() {
+            self.create_with_gpu_fallbacks(&mut request, pod_config, project)
+                .await
+        } else {
+            self.create_in_regions(&mut request, pod_config, project, &regions)
+                .await
+        };
+        let mut pod =
+            result.context(|| ErrorContext::new("creating pod").with_resource(&full_name))?;
+        pod.data_center_id = request.data_center_id;
 
-        info!(
-            "Created pod: {} (ID: {})",
-            full_name, pod.id
-        );
+        info!("Created pod: {} (ID: {})", full_name, pod.id);
 
         Ok(pod)
     }
 
+    /// Tries `regions` in order until one has capacity for the pod.
+    async fn create_in_regions(
+        &self,
+        request: &mut CreatePodRequest,
+        pod_config: &PodConfig,
+        project: &ProjectConfig,
+        regions: &[&str],
+    ) -> Result<Pod> {
+        let gpu_type_id = request.gpu_type_id.clone();
+        let mut last_error = None;
+        for region in regions {
+            request.gpu_type_id.clone_from(&gpu_type_id);
+            request.data_center_id = Some((*region).to_string());
+            match self
+                .create_with_gpu_fallbacks(request, pod_config, project)
+                .await
+            {
+                Ok(pod) => return Ok(pod),
+                Err(e)
+                    if matches!(
+                        e.api_kind(),
+                        Some(
+                            ApiErrorKind::NoCapacity
+                                | ApiErrorKind::RegionUnsupported
+                                | ApiErrorKind::InvalidGpuType
+                        )
+                    ) =>
+                {
+                    warn!("Could not create {} in {region}: {e}", request.name);
+                    last_error = Some(e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Err(last_error.unwrap_or_else(|| HalldyllError::internal("No region to create the pod in")))
+    }
+
+    /// Sends `request`, moving on to the fallback GPU types if `RunPod`
+    /// rejects the GPU type, region or capacity (the price ceiling already
+    /// picked the cheapest).
+    async fn create_with_gpu_fallbacks(
+        &self,
+        request: &mut CreatePodRequest,
+        pod_config: &PodConfig,
+        project: &ProjectConfig,
+    ) -> Result<Pod> {
+        let mut result = self.client.create_pod(request).await;
+        let retry_fallbacks = !pod_config.gpu.is_auto()
+            && (!matches!(project.cloud_type, CloudType::Community)
+                || pod_config.gpu.max_price_per_hour.is_none());
+        for fallback in pod_config.gpu.fallback.iter().filter(|_| retry_fallbacks) {
+            let Err(e) = &result else { break };
+            if !matches!(
+                e.api_kind(),
+                Some(
+                    ApiErrorKind::InvalidGpuType
+                        | ApiErrorKind::RegionUnsupported
+                        | ApiErrorKind::NoCapacity
+                )
+            ) {
+                break;
+            }
+            let Some(gpu_id) = self
+                .resolve_gpu_type(fallback)
+                .filter(|id| **id != request.gpu_type_id)
+            else {
+                continue;
+            };
+            if self.vram_shortfall(&pod_config.gpu, gpu_id).is_some() {
+                continue;
+            }
+            warn!(
+                "RunPod rejected {} for {} ({e}), trying {fallback}",
+                request.gpu_type_id, request.name
+            );
+            request.gpu_type_id.clone_from(gpu_id);
+            result = self.client.create_pod(request).await;
+        }
+        result
+    }
+
     /// Creates a pod and performs post-provisioning setup (model download, engine start).
     ///
     /// # Errors
@@ -114,12 +288,23 @@ impl PodProvisioner {
         // If there are models to setup, do post-provisioning
         if !pod_config.models.is_empty() {
             info!("Starting post-provisioning setup for pod {}", pod.id);
-            
-            let executor = super::executor::PodExecutor::new(self.client.clone());
-            
+
+            let mut executor = super::executor::PodExecutor::new(self.client.clone());
+            if let Some(dir) = &self.transcript_dir {
+                executor = executor.with_transcript(Transcript::new(dir));
+            }
+
             match executor.post_provision_setup(&pod.id, pod_config).await {
                 Ok(result) => {
                     info!("Post-provisioning completed: {}", result.summary());
+                    for engine in result.engine_results.iter().filter(|r| !r.success) {
+                        warn!(
+                            "Engine {} on pod {} failed: {}",
+                            engine.engine,
+                            pod.id,
+                            engine.error.as_deref().unwrap_or("unknown error")
+                        );
+                    }
                     return Ok((pod, Some(result)));
                 }
                 Err(e) => {
@@ -143,47 +328,177 @@ impl PodProvisioner {
             CloudType::Community => "COMMUNITY",
         };
 
-        // Try primary GPU type
-        if let Some(gpu_id) = self.resolve_gpu_type(&gpu_config.gpu_type) {
-            if self
-                .client
-                .is_gpu_available(gpu_id, cloud_type_str)
-                .await?
+        if gpu_config.is_auto() {
+            let gpu_types = self.client.list_gpu_types().await?;
+            return Self::select_cheapest(gpu_config, &gpu_types, cloud_type_str);
+        }
+
+        // Community prices vary, so candidates are filtered by the ceiling
+        if let (CloudType::Community, Some(max_price)) = (cloud_type, gpu_config.max_price_per_hour)
+        {
+            let gpu_types = self.client.list_gpu_types().await?;
+            return Self::select_within_price(gpu_config, &gpu_types, max_price);
+        }
+
+        // Try the primary GPU type, then the fallbacks, skipping those below the VRAM floor
+        let mut too_small = Vec::new();
+        let candidates = std::iter::once(&gpu_config.gpu_type).chain(&gpu_config.fallback);
+        for (i, name) in candidates.enumerate() {
+            let Some(gpu_id) = self.resolve_gpu_type(name) else {
+                continue;
+            };
+            if let Some(memory) = self.vram_shortfall(gpu_config, gpu_id) {
+                debug!("GPU type {name} has {memory} GB of VRAM, below gpu.min_vram_gb");
+                too_small.push(format!("{name} ({memory} GB)"));
+                continue;
+            }
+
+            let available = self.client.is_gpu_available(gpu_id, cloud_type_str).await?;
+            match (available, i) {
+                (true, 0) => debug!("Using primary GPU type: {name} ({gpu_id})"),
+                (true, _) => info!("Using fallback GPU type: {name} ({gpu_id})"),
+                (false, 0) => {
+                    warn!("Primary GPU type {name} not available in {cloud_type_str} cloud");
+                }
+                (false, _) => debug!("Fallback GPU type {name} not available"),
+            }
+            if available {
+                return Ok(gpu_id.clone());
+            }
+        }
+
+        Err(Self::no_gpu_error(gpu_config, &too_small, cloud_type_str))
+    }
+
+    /// Returns the error for a pod no candidate GPU type can serve.
+    fn no_gpu_error(
+        gpu_config: &GpuConfig,
+        too_small: &[String],
+        cloud_type: &str,
+    ) -> HalldyllError {
+        let error = gpu_config
+            .min_vram_gb
+            .filter(|_| !too_small.is_empty())
+            .map_or_else(
+                || RunPodError::GpuNotAvailable {
+                    gpu_type: gpu_config.gpu_type.clone(),
+                    region: cloud_type.to_string(),
+                },
+                |min_vram_gb| RunPodError::InsufficientVram {
+                    gpu_type: gpu_config.gpu_type.clone(),
+                    min_vram_gb,
+                    too_small: too_small.join(", "),
+                },
+            );
+        HalldyllError::RunPod(error)
+    }
+
+    /// Picks the first community GPU type (primary, then fallbacks) that is
+    /// available at or under `max_price` per GPU per hour.
+    fn select_within_price(
+        gpu_config: &GpuConfig,
+        gpu_types: &[GpuType],
+        max_price: f64,
+    ) -> Result<String> {
+        let mut over_ceiling = Vec::new();
+        let mut too_small = Vec::new();
+
+        for name in std::iter::once(&gpu_config.gpu_type).chain(&gpu_config.fallback) {
+            let Some(gpu) = gpu_types
+                .iter()
+                .find(|g| (g.id == *name || g.display_name == *name) && g.community_cloud)
+            else {
+                debug!("GPU type {name} not available in COMMUNITY cloud");
+                continue;
+            };
+            if gpu_config
+                .min_vram_gb
+                .is_some_and(|min_vram| gpu.memory_in_gb < min_vram)
             {
                 debug!(
-                    "Using primary GPU type: {} ({})",
-                    gpu_config.gpu_type, gpu_id
+                    "GPU type {name} has {} GB of VRAM, below gpu.min_vram_gb",
+                    gpu.memory_in_gb
                 );
-                return Ok(gpu_id.clone());
+                too_small.push(format!("{name} ({} GB)", gpu.memory_in_gb));
+                continue;
             }
-            warn!(
-                "Primary GPU type {} not available in {} cloud",
-                gpu_config.gpu_type, cloud_type_str
-            );
-        }
 
-        // Try fallback GPU types
-        for fallback in &gpu_config.fallback {
-            if let Some(gpu_id) = self.resolve_gpu_type(fallback) {
-                if self
-                    .client
-                    .is_gpu_available(gpu_id, cloud_type_str)
-                    .await?
-                {
-                    info!(
-                        "Using fallback GPU type: {} ({})",
-                        fallback, gpu_id
+            match gpu.hourly_price("COMMUNITY") {
+                Some(price) if price <= max_price => {
+                    info!("Using GPU type {name} at ${price:.2}/hr (ceiling ${max_price:.2}/hr)");
+                    return Ok(gpu.id.clone());
+                }
+                Some(price) => {
+                    debug!(
+                        "GPU type {name} costs ${price:.2}/hr, above the ${max_price:.2}/hr ceiling"
                     );
-                    return Ok(gpu_id.clone());
+                    over_ceiling.push((gpu, price));
                 }
-                debug!("Fallback GPU type {fallback} not available");
+                None => debug!("GPU type {name} has no community price, skipping"),
+            }
+        }
+
+        let Some((cheapest, price)) = over_ceiling.into_iter().min_by(|a, b| a.1.total_cmp(&b.1))
+        else {
+            return Err(Self::no_gpu_error(gpu_config, &too_small, "COMMUNITY"));
+        };
+
+        match gpu_config.max_price_action {
+            MaxPriceAction::Cheapest => {
+                warn!(
+                    "No GPU type under ${max_price:.2}/hr, using {} at ${price:.2}/hr",
+                    cheapest.display_name
+                );
+                Ok(cheapest.id.clone())
+            }
+            MaxPriceAction::Fail => Err(HalldyllError::RunPod(RunPodError::PriceCeilingExceeded {
+                gpu_type: gpu_config.gpu_type.clone(),
+                max_price,
+                cheapest: format!("{} at ${price:.2}/hr", cheapest.display_name),
+            })),
+        }
+    }
+
+    /// Picks the cheapest GPU type available in `cloud_type` with at least
+    /// `min_vram_gb` of memory, for `type: cheapest`.
+    ///
+    /// In community cloud, a cheapest type above `max_price_per_hour` is
+    /// only used with `max_price_action: cheapest`.
+    fn select_cheapest(
+        gpu_config: &GpuConfig,
+        gpu_types: &[GpuType],
+        cloud_type: &str,
+    ) -> Result<String> {
+        let min_vram = gpu_config.min_vram_gb.unwrap_or_default();
+        let Some((gpu, price)) = GpuType::cheapest(gpu_types, cloud_type, min_vram) else {
+            return Err(HalldyllError::RunPod(RunPodError::GpuNotAvailable {
+                gpu_type: format!("any GPU with {min_vram} GB"),
+                region: cloud_type.to_string(),
+            }));
+        };
+
+        if cloud_type == "COMMUNITY"
+            && let Some(max_price) = gpu_config.max_price_per_hour
+            && price > max_price
+        {
+            if gpu_config.max_price_action == MaxPriceAction::Fail {
+                return Err(HalldyllError::RunPod(RunPodError::PriceCeilingExceeded {
+                    gpu_type: gpu_config.gpu_type.clone(),
+                    max_price,
+                    cheapest: format!("{} at ${price:.2}/hr", gpu.display_name),
+                }));
             }
+            warn!(
+                "No GPU type under ${max_price:.2}/hr, using {} at ${price:.2}/hr",
+                gpu.display_name
+            );
         }
 
-        Err(HalldyllError::RunPod(RunPodError::GpuNotAvailable {
-            gpu_type: gpu_config.gpu_type.clone(),
-            region: cloud_type_str.to_string(),
-        }))
+        info!(
+            "Using cheapest GPU type {} ({} GB) at ${price:.2}/hr in {cloud_type} cloud",
+            gpu.display_name, gpu.memory_in_gb
+        );
+        Ok(gpu.id.clone())
     }
 
     /// Builds a pod creation request from configuration.
@@ -205,16 +520,23 @@ impl PodProvisioner {
             .filter_map(|v| v.size_gb)
             .max()
             .unwrap_or_default();
-        let volume_gb = if volume_gb == 0 { DEFAULT_VOLUME_GB } else { volume_gb };
+        let volume_gb = if volume_gb == 0 {
+            DEFAULT_VOLUME_GB
+        } else {
+            volume_gb
+        };
 
-        // Get primary volume mount path
-        let mount_path = pod_config
+        // Get primary volume mount path, preferring a network volume
+        let network_volume = pod_config
             .volumes
-            .first()
+            .iter()
+            .find(|v| v.network_volume_id.is_some());
+        let mount_path = network_volume
+            .or_else(|| pod_config.volumes.first())
             .map(|v| v.mount.clone());
 
         // Build environment variables
-        let env = Self::build_env_vars(&pod_config.runtime);
+        let env = Self::build_env_vars(pod_config);
 
         // Build tags
         let tags = Self::build_tags(pod_config, project, spec_hash);
@@ -237,11 +559,181 @@ impl PodProvisioner {
             request = request.with_mount_path(&path);
         }
 
+        if let Some(volume) = network_volume
+            && let Some(id) = &volume.network_volume_id
+        {
+            request = request.with_network_volume(id);
+            // A network volume only mounts on pods in its data center
+            if let Some(data_center) = &volume.data_center {
+                request = request.with_data_center(data_center);
+            }
+        }
+
+        if let Some(args) = pod_config.docker_args() {
+            request = request.with_docker_args(&args);
+        }
+
+        if let Some(bid) = pod_config.pricing.and_then(|p| p.spot_bid()) {
+            request = request.with_bid_per_gpu(bid);
+        }
+
         request
     }
 
+    /// Saves a pod's configuration as a `RunPod` template named after the pod.
+    ///
+    /// An existing template with the same name is updated in place.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the templates cannot be listed or saved.
+    pub async fn push_template(
+        &self,
+        pod_config: &PodConfig,
+        project: &ProjectConfig,
+    ) -> Result<PodTemplate> {
+        let mut template = Self::build_template(pod_config, project);
+        let context = || ErrorContext::new("saving template").with_resource(&template.name);
+
+        let existing = self.client.list_templates().await.context(context)?;
+        template.id = existing
+            .into_iter()
+            .find(|t| t.name == template.name)
+            .and_then(|t| t.id);
+
+        let saved = self
+            .client
+            .save_template(&template)
+            .await
+            .context(context)?;
+        info!(
+            "Saved template: {} (ID: {})",
+            saved.name,
+            saved.id.as_deref().unwrap_or("-")
+        );
+        Ok(saved)
+    }
+
+    /// Builds a `RunPod` template from a pod configuration.
+    ///
+    /// Only the settings a template can hold are kept: image, disks, ports and
+    /// environment. GPUs are picked when a pod is deployed from the template.
+    #[must_use]
+    pub fn build_template(pod_config: &PodConfig, project: &ProjectConfig) -> PodTemplate {
+        let volume_gb = pod_config
+            .volumes
+            .iter()
+            .filter_map(|v| v.size_gb)
+            .max()
+            .unwrap_or(DEFAULT_VOLUME_GB);
+
+        let mut env: Vec<PodEnvVar> = pod_config
+            .resolved_env()
+            .into_iter()
+            .map(|(key, value)| PodEnvVar { key, value })
+            .collect();
+        env.sort_by(|a, b| a.key.cmp(&b.key));
+
+        PodTemplate {
+            id: None,
+            name: pod_config.full_name(project),
+            image_name: pod_config.runtime.image.clone(),
+            container_disk_in_gb: DEFAULT_CONTAINER_DISK_GB,
+            volume_in_gb: volume_gb,
+            volume_mount_path: pod_config.volumes.first().map(|v| v.mount.clone()),
+            ports: Some(Self::build_ports_string(&pod_config.ports)),
+            env,
+            docker_args: pod_config.docker_args(),
+            readme: Some(format!(
+                "Managed by halldyll: pod `{}` of project `{}` ({}).",
+                pod_config.name, project.name, project.environment
+            )),
+            is_serverless: false,
+        }
+    }
+
+    /// Builds a pod configuration from a `RunPod` template.
+    ///
+    /// Templates carry no GPU settings, so the GPU type is passed in.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the template's ports cannot be parsed.
+    pub fn pod_config_from_template(
+        template: &PodTemplate,
+        name: &str,
+        gpu_type: &str,
+    ) -> Result<PodConfig> {
+        let ports = template
+            .ports
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .map(PortConfig::parse)
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| HalldyllError::internal(format!("Template '{}': {e}", template.name)))?;
+
+        let volumes = template
+            .volume_mount_path
+            .iter()
+            .filter(|_| template.volume_in_gb > 0)
+            .map(|mount| VolumeConfig {
+                name: String::from("data"),
+                mount: mount.clone(),
+                persistent: true,
+                size_gb: Some(template.volume_in_gb),
+                network_volume_id: None,
+                data_center: None,
+            })
+            .collect();
+
+        Ok(PodConfig {
+            name: name.to_string(),
+            enabled: true,
+            gpu: GpuConfig {
+                gpu_type: gpu_type.to_string(),
+                count: 1,
+                min_vram_gb: None,
+                fallback: vec![],
+                max_price_per_hour: None,
+                max_price_action: MaxPriceAction::default(),
+            },
+            pricing: None,
+            region: None,
+            ports,
+            volumes,
+            runtime: RuntimeConfig {
+                image: template.image_name.clone(),
+                env: template
+                    .env
+                    .iter()
+                    .map(|e| (e.key.clone(), e.value.clone()))
+                    .collect(),
+                command: None,
+                args: None,
+            },
+            models: vec![],
+            health_check: None,
+            tags: HashMap::new(),
+            replicas: 1,
+            autoscale: None,
+            load_balancer: None,
+            replace_strategy: None,
+            warm_pool: None,
+            drain: None,
+            depends_on: vec![],
+            ssh: None,
+            notes: None,
+            model_downloads: None,
+            template: None,
+        })
+    }
+
     /// Builds the ports string for the API request.
-    fn build_ports_string(ports: &[PortConfig]) -> String {
+    #[must_use]
+    pub fn build_ports_string(ports: &[PortConfig]) -> String {
         if ports.is_empty() {
             return String::from("8000/http");
         }
@@ -251,7 +743,9 @@ impl PodProvisioner {
             .map(|p| {
                 let protocol = match p.protocol {
                     crate::config::PortProtocol::Tcp => "tcp",
-                    crate::config::PortProtocol::Http | crate::config::PortProtocol::Https => "http",
+                    crate::config::PortProtocol::Http | crate::config::PortProtocol::Https => {
+                        "http"
+                    }
                     crate::config::PortProtocol::Udp => "udp",
                 };
                 format!("{}/{protocol}", p.port)
@@ -261,13 +755,12 @@ impl PodProvisioner {
     }
 
     /// Builds environment variables map.
-    fn build_env_vars(runtime: &RuntimeConfig) -> HashMap<String, String> {
-        let mut env = runtime.env.clone();
+    fn build_env_vars(pod_config: &PodConfig) -> HashMap<String, String> {
+        let mut env = pod_config.resolved_env();
 
         // Add HF token if available
         if let Ok(hf_token) = std::env::var("HF_TOKEN") {
-            env.entry(String::from("HF_TOKEN"))
-                .or_insert(hf_token);
+            env.entry(String::from("HF_TOKEN")).or_insert(hf_token);
         }
 
         env
@@ -282,10 +775,11 @@ impl PodProvisioner {
         let mut tags = pod_config.tags.clone();
 
         // Add system tags
-        tags.insert(String::from("halldyll_project"), project.name.clone());
-        tags.insert(String::from("halldyll_env"), project.environment.clone());
-        tags.insert(String::from("halldyll_pod"), pod_config.name.clone());
-        tags.insert(String::from("halldyll_spec_hash"), spec_hash.to_string());
+        let namespace = project.tag_namespace();
+        tags.insert(tag_key(namespace, TAG_PROJECT), project.name.clone());
+        tags.insert(tag_key(namespace, TAG_ENV), project.environment.clone());
+        tags.insert(tag_key(namespace, TAG_POD), pod_config.name.clone());
+        tags.insert(tag_key(namespace, TAG_SPEC_HASH), spec_hash.to_string());
 
         tags
     }
@@ -297,7 +791,10 @@ impl PodProvisioner {
     /// Returns an error if the pod cannot be terminated.
     pub async fn terminate_pod(&self, pod_id: &str) -> Result<()> {
         info!("Terminating pod: {pod_id}");
-        self.client.terminate_pod(pod_id).await?;
+        self.client
+            .terminate_pod(pod_id)
+            .await
+            .context(|| ErrorContext::new("terminating pod").with_resource(pod_id))?;
         info!("Pod terminated: {pod_id}");
         Ok(())
     }
@@ -309,7 +806,10 @@ impl PodProvisioner {
     /// Returns an error if the pod cannot be stopped.
     pub async fn stop_pod(&self, pod_id: &str) -> Result<()> {
         info!("Stopping pod: {pod_id}");
-        self.client.stop_pod(pod_id).await?;
+        self.client
+            .stop_pod(pod_id)
+            .await
+            .context(|| ErrorContext::new("stopping pod").with_resource(pod_id))?;
         info!("Pod stopped: {pod_id}");
         Ok(())
     }
@@ -321,7 +821,11 @@ impl PodProvisioner {
     /// Returns an error if the pod cannot be resumed.
     pub async fn resume_pod(&self, pod_id: &str) -> Result<Pod> {
         info!("Resuming pod: {pod_id}");
-        let pod = self.client.resume_pod(pod_id).await?;
+        let pod = self
+            .client
+            .resume_pod(pod_id)
+            .await
+            .context(|| ErrorContext::new("resuming pod").with_resource(pod_id))?;
         info!("Pod resumed: {pod_id}");
         Ok(pod)
     }
@@ -364,3 +868,162 @@ impl PodProvisioner {
         &self.client
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::DeployConfig;
+    use wiremock::matchers::body_string_contains;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn test_template_round_trip() {
+        let yaml = r#"
+project:
+  name: proj
+state:
+  backend: local
+pods:
+  - name: llm
+    gpu:
+      type: "NVIDIA A40"
+    ports:
+      - "8000/http"
+      - "22/tcp"
+    volumes:
+      - name: cache
+        mount: /root/.cache
+        size_gb: 80
+    runtime:
+      image: "vllm/vllm-openai:v0.6.0"
+      env:
+        B: "2"
+        A: "1"
+"#;
+        let config: DeployConfig = serde_yaml::from_str(yaml).unwrap();
+        let template = PodProvisioner::build_template(&config.pods[0], &config.project);
+
+        assert_eq!(template.name, "proj-dev-llm");
+        assert_eq!(template.ports.as_deref(), Some("8000/http,22/tcp"));
+        assert_eq!(template.volume_in_gb, 80);
+        assert_eq!(template.env[0].key, "A");
+
+        let pod = PodProvisioner::pod_config_from_template(&template, "llm", "NVIDIA A40").unwrap();
+        assert_eq!(pod.ports, config.pods[0].ports);
+        assert_eq!(pod.runtime, config.pods[0].runtime);
+        assert_eq!(pod.volumes[0].mount, "/root/.cache");
+        assert_eq!(pod.volumes[0].size_gb, Some(80));
+    }
+
+    #[test]
+    fn test_select_within_price() {
+        let gpu_types: Vec<GpuType> = serde_json::from_value(serde_json::json!([
+            {"id": "NVIDIA A40", "displayName": "A40", "memoryInGb": 48, "communityCloud": true, "communityPrice": 0.5},
+            {"id": "NVIDIA L40S", "displayName": "L40S", "memoryInGb": 48, "communityCloud": true, "communityPrice": 0.8},
+            {"id": "NVIDIA RTX A6000", "displayName": "RTX A6000", "memoryInGb": 48, "communityCloud": true, "communityPrice": 0.3}
+        ]))
+        .unwrap();
+        let mut gpu: GpuConfig =
+            serde_yaml::from_str("type: A40\nfallback: [L40S, RTX A6000]\nmax_price_per_hour: 0.4")
+                .unwrap();
+
+        let under_ceiling = PodProvisioner::select_within_price(&gpu, &gpu_types, 0.4).unwrap();
+        assert_eq!(under_ceiling, "NVIDIA RTX A6000");
+
+        gpu.fallback.pop();
+        let err = PodProvisioner::select_within_price(&gpu, &gpu_types, 0.4).unwrap_err();
+        assert!(matches!(
+            err,
+            HalldyllError::RunPod(RunPodError::PriceCeilingExceeded { .. })
+        ));
+
+        gpu.max_price_action = MaxPriceAction::Cheapest;
+        let cheapest = PodProvisioner::select_within_price(&gpu, &gpu_types, 0.4).unwrap();
+        assert_eq!(cheapest, "NVIDIA A40");
+
+        // Candidates below the VRAM floor are skipped
+        gpu.min_vram_gb = Some(80);
+        let too_large = PodProvisioner::select_within_price(&gpu, &gpu_types, 0.4).unwrap_err();
+        assert!(matches!(
+            too_large,
+            HalldyllError::RunPod(RunPodError::InsufficientVram { ref too_small, .. }) if too_small == "A40 (48 GB), L40S (48 GB)"
+        ));
+    }
+
+    #[test]
+    fn test_select_cheapest() {
+        let gpu_types: Vec<GpuType> = serde_json::from_value(serde_json::json!([
+            {"id": "NVIDIA RTX 4090", "displayName": "RTX 4090", "memoryInGb": 24, "secureCloud": true, "securePrice": 0.69},
+            {"id": "NVIDIA A40", "displayName": "A40", "memoryInGb": 48, "secureCloud": true, "securePrice": 0.4},
+            {"id": "NVIDIA L40S", "displayName": "L40S", "memoryInGb": 48, "secureCloud": true, "securePrice": 0.86},
+            {"id": "NVIDIA A100 80GB PCIe", "displayName": "A100 PCIe", "memoryInGb": 80, "secureCloud": true, "securePrice": 1.64},
+            {"id": "NVIDIA H100 PCIe", "displayName": "H100 PCIe", "memoryInGb": 80, "communityCloud": true, "communityPrice": 1.99}
+        ]))
+        .unwrap();
+        let mut gpu: GpuConfig = serde_yaml::from_str(
+            "type: cheapest
+min_vram_gb: 40",
+        )
+        .unwrap();
+        assert!(gpu.is_auto());
+
+        assert_eq!(
+            PodProvisioner::select_cheapest(&gpu, &gpu_types, "SECURE").unwrap(),
+            "NVIDIA A40"
+        );
+
+        gpu.min_vram_gb = Some(80);
+        assert_eq!(
+            PodProvisioner::select_cheapest(&gpu, &gpu_types, "SECURE").unwrap(),
+            "NVIDIA A100 80GB PCIe"
+        );
+
+        // Only types offered in the pod's cloud are considered
+        gpu.max_price_per_hour = Some(1.5);
+        let err = PodProvisioner::select_cheapest(&gpu, &gpu_types, "COMMUNITY").unwrap_err();
+        assert!(matches!(
+            err,
+            HalldyllError::RunPod(RunPodError::PriceCeilingExceeded { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_fallback_skips_types_below_min_vram() {
+        let server = MockServer::start().await;
+        Mock::given(body_string_contains("gpuTypes"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(
+                serde_json::json!({ "data": { "gpuTypes": [
+                    {"id": "NVIDIA A40", "displayName": "A40", "memoryInGb": 48, "secureCloud": true},
+                    {"id": "NVIDIA L40S", "displayName": "L40S", "memoryInGb": 48, "secureCloud": true},
+                    {"id": "NVIDIA A100 80GB PCIe", "displayName": "A100 PCIe", "memoryInGb": 80, "secureCloud": true}
+                ] } }),
+            ))
+            .mount(&server)
+            .await;
+        let client = RunPodClient::new("test-key")
+            .unwrap()
+            .with_api_url(server.uri());
+        let mut provisioner = PodProvisioner::new(client);
+        provisioner.init_gpu_types().await.unwrap();
+
+        let mut gpu: GpuConfig =
+            serde_yaml::from_str("type: A40\nfallback: [L40S, A100 PCIe]\nmin_vram_gb: 80")
+                .unwrap();
+        let resolved = provisioner
+            .resolve_gpu_type_with_fallback(&gpu, &CloudType::Secure)
+            .await
+            .unwrap();
+        assert_eq!(resolved, "NVIDIA A100 80GB PCIe");
+
+        // No candidate meets the floor
+        gpu.fallback.pop();
+        let err = provisioner
+            .resolve_gpu_type_with_fallback(&gpu, &CloudType::Secure)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            HalldyllError::RunPod(RunPodError::InsufficientVram { ref too_small, .. }) if too_small == "A40 (48 GB), L40S (48 GB)"
+        ));
+    }
+}
@@ -0,0 +1,145 @@
+//! Serverless endpoints deployed alongside pods.
+//!
+//! Each configured endpoint is a `RunPod` serverless endpoint named
+//! `<project>-<env>-<endpoint>`. Unlike pods, endpoints are updated in place:
+//! worker bounds, GPU pools and the template change without recreating the
+//! endpoint, so its ID (and the URL clients call) stays the same.
+
+use tracing::info;
+
+use crate::config::{EndpointConfig, ProjectConfig};
+use crate::error::{ErrorContext, HalldyllError, Result, ResultExt};
+
+use super::client::RunPodClient;
+use super::templates::TemplateManager;
+use super::types::ServerlessEndpoint;
+
+/// Returns the `RunPod` name of the serverless endpoint `endpoint`.
+#[must_use]
+pub fn endpoint_name(project: &ProjectConfig, endpoint: &str) -> String {
+    format!("{}-{}-{endpoint}", project.name, project.environment)
+}
+
+/// Creates, updates and deletes serverless endpoints.
+#[derive(Debug, Clone)]
+pub struct EndpointManager {
+    /// `RunPod` client.
+    client: RunPodClient,
+}
+
+impl EndpointManager {
+    /// Creates an endpoint manager.
+    #[must_use]
+    pub const fn new(client: RunPodClient) -> Self {
+        Self { client }
+    }
+
+    /// Lists the project's serverless endpoints on `RunPod`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the endpoints cannot be listed.
+    pub async fn list_project(&self, project: &ProjectConfig) -> Result<Vec<ServerlessEndpoint>> {
+        let prefix = endpoint_name(project, "");
+        let mut endpoints = self.client.list_endpoints().await?;
+        endpoints.retain(|e| e.name.starts_with(&prefix));
+        Ok(endpoints)
+    }
+
+    /// Creates the endpoint, or updates the endpoint `existing_id` in place.
+    ///
+    /// When creating, an endpoint of the same name left by an interrupted
+    /// apply is updated instead, so re-running never creates a second copy.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the endpoint cannot be saved.
+    pub async fn save(
+        &self,
+        project: &ProjectConfig,
+        endpoint: &EndpointConfig,
+        existing_id: Option<&str>,
+    ) -> Result<ServerlessEndpoint> {
+        let name = endpoint_name(project, &endpoint.name);
+        let id = match existing_id {
+            Some(id) => Some(id.to_string()),
+            None => self
+                .client
+                .list_endpoints()
+                .await?
+                .into_iter()
+                .find(|e| e.name == name)
+                .and_then(|e| e.id),
+        };
+
+        let template_id = match &endpoint.template {
+            Some(template) => TemplateManager::new(self.client.clone())
+                .find(project, template)
+                .await?
+                .and_then(|t| t.id)
+                .ok_or_else(|| {
+                    HalldyllError::internal(format!(
+                        "Template '{template}' of endpoint '{}' has not been saved",
+                        endpoint.name
+                    ))
+                })?,
+            None => endpoint.template_id.clone(),
+        };
+
+        let request = ServerlessEndpoint {
+            id,
+            name: name.clone(),
+            template_id,
+            gpu_ids: endpoint.gpu.pools.join(","),
+            gpu_count: endpoint.gpu.count,
+            workers_min: endpoint.workers.min,
+            workers_max: endpoint.workers.max,
+            idle_timeout: endpoint.idle_timeout_secs,
+        };
+        let saved = self
+            .client
+            .save_endpoint(&request)
+            .await
+            .context(|| ErrorContext::new("saving serverless endpoint").with_resource(&name))?;
+        info!(
+            "Saved serverless endpoint {name} ({})",
+            saved.id.as_deref().unwrap_or_default()
+        );
+        Ok(saved)
+    }
+
+    /// Scales an endpoint's workers to zero and deletes it.
+    ///
+    /// `RunPod` refuses to delete an endpoint that still has workers.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the endpoint cannot be scaled down or deleted.
+    pub async fn delete(&self, endpoint_id: &str) -> Result<()> {
+        let context =
+            || ErrorContext::new("deleting serverless endpoint").with_resource(endpoint_id);
+        let existing = self
+            .client
+            .list_endpoints()
+            .await?
+            .into_iter()
+            .find(|e| e.id.as_deref() == Some(endpoint_id));
+        let Some(mut endpoint) = existing else {
+            info!("Serverless endpoint {endpoint_id} is already gone");
+            return Ok(());
+        };
+
+        if endpoint.workers_min > 0 || endpoint.workers_max > 0 {
+            endpoint.workers_min = 0;
+            endpoint.workers_max = 0;
+            self.client
+                .save_endpoint(&endpoint)
+                .await
+                .context(context)?;
+        }
+        self.client
+            .delete_endpoint(endpoint_id)
+            .await
+            .context(context)
+    }
+}
@@ -0,0 +1,89 @@
+//! SSH helpers shared by the exec paths and pod provisioning.
+//!
+//! Pods that expose `22/tcp` get a public IP and port for SSH. The key-based
+//! client running commands over it, `SshClient`, is built with the `ssh`
+//! feature.
+
+/// Environment variable holding the private key (path or contents).
+pub const SSH_KEY_ENV: &str = "RUNPOD_SSH_KEY";
+
+/// Environment variable `RunPod` images read authorized public keys from.
+pub const PUBLIC_KEY_ENV: &str = "PUBLIC_KEY";
+
+/// Stream an output chunk was read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputStream {
+    /// Standard output.
+    Stdout,
+    /// Standard error.
+    Stderr,
+}
+
+/// Returns a short fingerprint of a set of public keys, to notice changes.
+#[must_use]
+pub fn keys_fingerprint(keys: &[String]) -> String {
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+    for key in keys {
+        hasher.update(key.as_bytes());
+        hasher.update(b"\n");
+    }
+    hex::encode(&hasher.finalize()[..8])
+}
+
+/// Returns the shell command replacing `~/.ssh/authorized_keys` with `keys`.
+#[must_use]
+pub fn authorized_keys_command(keys: &[String]) -> String {
+    let quoted: Vec<String> = keys
+        .iter()
+        .map(|key| format!("'{}'", key.replace('\'', "'\\''")))
+        .collect();
+    format!(
+        "mkdir -p ~/.ssh && chmod 700 ~/.ssh && printf '%s\\n' {} > ~/.ssh/authorized_keys.tmp \
+         && chmod 600 ~/.ssh/authorized_keys.tmp && mv ~/.ssh/authorized_keys.tmp ~/.ssh/authorized_keys",
+        quoted.join(" ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runpod::Pod;
+
+    #[test]
+    fn test_ssh_endpoint() {
+        let json = r#"{"id":"abc","name":"llm","runtime":{"ports":[
+            {"ip":"100.65.0.2","privatePort":8000,"publicPort":8000,"type":"http"},
+            {"ip":"213.173.1.9","privatePort":22,"publicPort":40122,"type":"tcp"}]}}"#;
+        let pod: Pod = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            pod.ssh_endpoint(),
+            Some((String::from("213.173.1.9"), 40122))
+        );
+
+        let no_ssh: Pod = serde_json::from_str(r#"{"id":"def","name":"db"}"#).unwrap();
+        assert_eq!(no_ssh.ssh_endpoint(), None);
+    }
+
+    #[test]
+    fn test_authorized_keys() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(
+            &mut file,
+            b"# team\nssh-ed25519 BBBB bob\n\nssh-ed25519 AAAA alice\n",
+        )
+        .unwrap();
+        let ssh = crate::config::SshConfig {
+            authorized_keys: vec![String::from("ssh-ed25519 AAAA alice")],
+            from_file: Some(file.path().display().to_string()),
+        };
+        let keys = ssh.public_keys().unwrap();
+        assert_eq!(keys, vec!["ssh-ed25519 AAAA alice", "ssh-ed25519 BBBB bob"]);
+        assert_ne!(keys_fingerprint(&keys), keys_fingerprint(&keys[..1]));
+
+        let command = authorized_keys_command(&[String::from("ssh-ed25519 CCCC o'brien")]);
+        assert!(command.contains("'ssh-ed25519 CCCC o'\\''brien'"));
+        assert!(command.ends_with("~/.ssh/authorized_keys"));
+    }
+}
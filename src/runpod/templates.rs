@@ -0,0 +1,133 @@
+//! `RunPod` templates managed by halldyll.
+//!
+//! Each entry under `templates:` is saved as a `RunPod` template named
+//! `<project>-<env>-<template>`. Templates are updated in place; pods and
+//! endpoints deployed from one are referred to it by its ID, so a template
+//! change is picked up by recreating its pods and updating its endpoints.
+
+use tracing::info;
+
+use crate::config::{ProjectConfig, TemplateConfig};
+use crate::error::{ErrorContext, Result, ResultExt};
+
+use super::client::RunPodClient;
+use super::provisioner::PodProvisioner;
+use super::types::{PodEnvVar, PodTemplate};
+
+/// Returns the `RunPod` name of the template `template`.
+#[must_use]
+pub fn template_name(project: &ProjectConfig, template: &str) -> String {
+    format!("{}-{}-{template}", project.name, project.environment)
+}
+
+/// Creates, updates and deletes configured templates.
+#[derive(Debug, Clone)]
+pub struct TemplateManager {
+    /// `RunPod` client.
+    client: RunPodClient,
+}
+
+impl TemplateManager {
+    /// Creates a template manager.
+    #[must_use]
+    pub const fn new(client: RunPodClient) -> Self {
+        Self { client }
+    }
+
+    /// Returns the saved template for the configured template `template`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the templates cannot be listed.
+    pub async fn find(
+        &self,
+        project: &ProjectConfig,
+        template: &str,
+    ) -> Result<Option<PodTemplate>> {
+        let name = template_name(project, template);
+        let templates = self.client.list_templates().await?;
+        Ok(templates.into_iter().find(|t| t.name == name))
+    }
+
+    /// Creates the template, or updates the template `existing_id` in place.
+    ///
+    /// When creating, a template of the same name left by an interrupted
+    /// apply is updated instead, so re-running never creates a second copy.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the template cannot be saved.
+    pub async fn save(
+        &self,
+        project: &ProjectConfig,
+        template: &TemplateConfig,
+        existing_id: Option<&str>,
+    ) -> Result<PodTemplate> {
+        let mut request = Self::build(project, template);
+        let context = || ErrorContext::new("saving template").with_resource(&request.name);
+        request.id = match existing_id {
+            Some(id) => Some(id.to_string()),
+            None => self
+                .find(project, &template.name)
+                .await
+                .context(context)?
+                .and_then(|t| t.id),
+        };
+
+        let saved = self.client.save_template(&request).await.context(context)?;
+        info!(
+            "Saved template {} ({})",
+            saved.name,
+            saved.id.as_deref().unwrap_or_default()
+        );
+        Ok(saved)
+    }
+
+    /// Deletes the configured template `template`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the template cannot be deleted.
+    pub async fn delete(&self, project: &ProjectConfig, template: &str) -> Result<()> {
+        let name = template_name(project, template);
+        if self.find(project, template).await?.is_none() {
+            info!("Template {name} is already gone");
+            return Ok(());
+        }
+        self.client
+            .delete_template(&name)
+            .await
+            .context(|| ErrorContext::new("deleting template").with_resource(&name))
+    }
+
+    /// Builds the `RunPod` template for a configured template.
+    #[must_use]
+    pub fn build(project: &ProjectConfig, template: &TemplateConfig) -> PodTemplate {
+        let mut env: Vec<PodEnvVar> = template
+            .env
+            .iter()
+            .map(|(key, value)| PodEnvVar {
+                key: key.clone(),
+                value: value.clone(),
+            })
+            .collect();
+        env.sort_by(|a, b| a.key.cmp(&b.key));
+
+        PodTemplate {
+            id: None,
+            name: template_name(project, &template.name),
+            image_name: template.image.clone(),
+            container_disk_in_gb: template.container_disk_gb,
+            volume_in_gb: template.volume_gb,
+            volume_mount_path: template.volume_mount.clone(),
+            ports: Some(PodProvisioner::build_ports_string(&template.ports)),
+            env,
+            docker_args: None,
+            readme: Some(format!(
+                "Managed by halldyll: template `{}` of project `{}` ({}).",
+                template.name, project.name, project.environment
+            )),
+            is_serverless: template.serverless,
+        }
+    }
+}
@@ -0,0 +1,183 @@
+//! Transcripts of commands run on pods.
+//!
+//! Every command the [`PodExecutor`](super::PodExecutor) runs is appended to
+//! `.halldyll/transcripts/<pod>.log` with its timestamp, duration, exit code
+//! and the tail of its output, so a bad setup can be debugged without
+//! running it again.
+
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::warn;
+
+use super::executor::CommandResult;
+
+/// Bytes of each output stream kept in a transcript entry.
+const MAX_OUTPUT_BYTES: usize = 4096;
+
+/// One command run on a pod.
+#[derive(Debug, Clone)]
+pub struct TranscriptEntry<'a> {
+    /// When the command started.
+    pub started_at: DateTime<Utc>,
+    /// How long it took in seconds.
+    pub duration_secs: f64,
+    /// Command line as sent to the pod.
+    pub command: &'a str,
+    /// Result, or the error if the command could not be run.
+    pub result: std::result::Result<&'a CommandResult, String>,
+}
+
+impl TranscriptEntry<'_> {
+    /// Renders the entry as transcript text.
+    #[must_use]
+    pub fn render(&self) -> String {
+        let mut out = format!(
+            "=== {} ({:.1}s)\n$ {}\n",
+            self.started_at
+                .to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            self.duration_secs,
+            self.command
+        );
+
+        match &self.result {
+            Ok(result) => {
+                let exit_code = result
+                    .exit_code
+                    .map_or_else(|| String::from("unknown"), |code| code.to_string());
+                let _ = writeln!(out, "exit code: {exit_code}");
+                for (stream, output) in [("stdout", &result.stdout), ("stderr", &result.stderr)] {
+                    if !output.trim().is_empty() {
+                        let _ =
+                            writeln!(out, "--- {stream}\n{}", truncate_output(output).trim_end());
+                    }
+                }
+            }
+            Err(e) => {
+                let _ = writeln!(out, "failed: {e}");
+            }
+        }
+
+        out
+    }
+}
+
+/// Appends command transcripts to one file per pod.
+#[derive(Debug)]
+pub struct Transcript {
+    /// Directory holding the transcript files.
+    dir: PathBuf,
+    /// Pod names by `RunPod` pod ID, for file names.
+    names: Mutex<HashMap<String, String>>,
+}
+
+impl Transcript {
+    /// Creates a transcript writing into `dir` (usually `.halldyll/transcripts`).
+    #[must_use]
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            names: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Records the name of a pod so its commands go to `<name>.log`.
+    ///
+    /// Commands on unnamed pods are written under their pod ID.
+    pub fn name_pod(&self, pod_id: &str, name: &str) {
+        if let Ok(mut names) = self.names.lock() {
+            names.insert(pod_id.to_string(), name.to_string());
+        }
+    }
+
+    /// Returns the transcript file of a pod.
+    #[must_use]
+    pub fn path(&self, pod_id: &str) -> PathBuf {
+        let name = self
+            .names
+            .lock()
+            .ok()
+            .and_then(|names| names.get(pod_id).cloned())
+            .unwrap_or_else(|| pod_id.to_string());
+        self.dir.join(format!("{name}.log"))
+    }
+
+    /// Appends an entry to a pod's transcript.
+    ///
+    /// Failures are logged; they never fail the command.
+    pub fn record(&self, pod_id: &str, entry: &TranscriptEntry<'_>) {
+        let path = self.path(pod_id);
+        if let Err(e) = append(&path, &entry.render()) {
+            warn!("Failed to write transcript {}: {e}", path.display());
+        }
+    }
+}
+
+/// Appends text to a file, creating it and its directory if needed.
+fn append(path: &Path, text: &str) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    file.write_all(text.as_bytes())
+}
+
+/// Keeps the last [`MAX_OUTPUT_BYTES`] of an output, where errors usually are.
+fn truncate_output(output: &str) -> String {
+    if output.len() <= MAX_OUTPUT_BYTES {
+        return output.to_string();
+    }
+
+    let mut start = output.len() - MAX_OUTPUT_BYTES;
+    while !output.is_char_boundary(start) {
+        start += 1;
+    }
+    format!("[... {start} bytes truncated ...]\n{}", &output[start..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_record_appends_per_pod() {
+        let temp_dir = TempDir::new().unwrap();
+        let transcript = Transcript::new(temp_dir.path().join("transcripts"));
+        transcript.name_pod("abc123", "llm");
+
+        let result = CommandResult {
+            success: false,
+            stdout: "x".repeat(MAX_OUTPUT_BYTES + 10),
+            stderr: String::from("CUDA out of memory\n"),
+            exit_code: Some(1),
+        };
+        let entry = TranscriptEntry {
+            started_at: Utc::now(),
+            duration_secs: 2.5,
+            command: "vllm serve /models/llm",
+            result: Ok(&result),
+        };
+        transcript.record("abc123", &entry);
+        transcript.record(
+            "abc123",
+            &TranscriptEntry {
+                result: Err(String::from("timed out")),
+                ..entry
+            },
+        );
+
+        let text = std::fs::read_to_string(temp_dir.path().join("transcripts/llm.log")).unwrap();
+        assert_eq!(text.matches("$ vllm serve /models/llm").count(), 2);
+        assert!(text.contains("exit code: 1"));
+        assert!(text.contains("[... 10 bytes truncated ...]"));
+        assert!(text.contains("--- stderr\nCUDA out of memory"));
+        assert!(text.contains("failed: timed out"));
+    }
+}
@@ -49,6 +49,9 @@ pub struct Pod {
     /// Custom tags on the pod.
     #[serde(default)]
     pub custom_tags: Option<HashMap<String, String>>,
+    /// Data center the pod was requested in (set by the provisioner, not the API).
+    #[serde(skip)]
+    pub data_center_id: Option<String>,
 }
 
 /// Pod machine information.
@@ -90,7 +93,7 @@ pub struct PodPort {
 }
 
 /// Pod environment variable.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct PodEnvVar {
     /// Variable key.
     pub key: String,
@@ -172,6 +175,12 @@ pub struct CreatePodRequest {
     /// Network volume ID.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub network_volume_id: Option<String>,
+    /// Template the pod is deployed from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template_id: Option<String>,
+    /// Bid per GPU in USD per hour; set for interruptible (spot) pods.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bid_per_gpu: Option<f64>,
     /// Custom tags.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub custom_tags: Option<HashMap<String, String>>,
@@ -191,6 +200,43 @@ pub struct UpdatePodRequest {
     pub env: Option<Vec<PodEnvVar>>,
 }
 
+/// A saved `RunPod` pod template, as shown in the console's template list.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PodTemplate {
+    /// Template ID (`None` until saved).
+    #[serde(default)]
+    pub id: Option<String>,
+    /// Template name (unique per account).
+    pub name: String,
+    /// Container image.
+    pub image_name: String,
+    /// Container disk in GB.
+    #[serde(default)]
+    pub container_disk_in_gb: u32,
+    /// Volume in GB.
+    #[serde(default)]
+    pub volume_in_gb: u32,
+    /// Volume mount path.
+    #[serde(default)]
+    pub volume_mount_path: Option<String>,
+    /// Ports to expose (e.g., "8000/http,22/tcp").
+    #[serde(default)]
+    pub ports: Option<String>,
+    /// Environment variables.
+    #[serde(default)]
+    pub env: Vec<PodEnvVar>,
+    /// Docker command arguments.
+    #[serde(default)]
+    pub docker_args: Option<String>,
+    /// Markdown shown on the template's page.
+    #[serde(default)]
+    pub readme: Option<String>,
+    /// Whether the template is for serverless endpoint workers.
+    #[serde(default)]
+    pub is_serverless: bool,
+}
+
 /// GPU type information.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -215,6 +261,99 @@ pub struct GpuType {
     pub community_price: Option<f64>,
 }
 
+impl GpuType {
+    /// Returns the hourly price of one GPU in the given cloud (`SECURE` or `COMMUNITY`).
+    #[must_use]
+    pub fn hourly_price(&self, cloud_type: &str) -> Option<f64> {
+        match cloud_type {
+            "SECURE" => self.secure_price,
+            "COMMUNITY" => self.community_price,
+            _ => None,
+        }
+        .filter(|price| *price > 0.0)
+    }
+
+    /// Returns the cheapest GPU type available in a cloud (`SECURE` or
+    /// `COMMUNITY`) with at least `min_vram_gb` of memory, and its hourly price.
+    #[must_use]
+    pub fn cheapest<'a>(
+        gpu_types: &'a [Self],
+        cloud_type: &str,
+        min_vram_gb: u32,
+    ) -> Option<(&'a Self, f64)> {
+        gpu_types
+            .iter()
+            .filter(|gpu| gpu.memory_in_gb >= min_vram_gb)
+            .filter(|gpu| match cloud_type {
+                "SECURE" => gpu.secure_cloud,
+                "COMMUNITY" => gpu.community_cloud,
+                _ => false,
+            })
+            .filter_map(|gpu| Some((gpu, gpu.hourly_price(cloud_type)?)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+}
+
+/// `RunPod` account funds and spend.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountInfo {
+    /// Remaining account balance in USD.
+    #[serde(default)]
+    pub client_balance: f64,
+    /// Current spend across all pods in USD per hour.
+    #[serde(default)]
+    pub current_spend_per_hr: f64,
+    /// Account spend limit in USD per hour, if set.
+    #[serde(default)]
+    pub spend_limit: Option<f64>,
+}
+
+/// A `RunPod` network volume.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkVolume {
+    /// Volume ID.
+    pub id: String,
+    /// Volume name.
+    pub name: String,
+    /// Size in GB.
+    #[serde(default)]
+    pub size: u32,
+    /// Data center the volume lives in.
+    #[serde(default)]
+    pub data_center_id: String,
+}
+
+/// A `RunPod` serverless endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerlessEndpoint {
+    /// Endpoint ID (`None` until saved).
+    #[serde(default)]
+    pub id: Option<String>,
+    /// Endpoint name.
+    pub name: String,
+    /// Serverless template the workers run.
+    #[serde(default)]
+    pub template_id: String,
+    /// GPU pools workers may run on, comma-separated (e.g. `AMPERE_24,ADA_24`).
+    #[serde(default)]
+    pub gpu_ids: String,
+    /// GPUs per worker.
+    #[serde(default)]
+    pub gpu_count: u32,
+    /// Minimum number of workers.
+    #[serde(default)]
+    pub workers_min: u32,
+    /// Maximum number of workers.
+    #[serde(default)]
+    pub workers_max: u32,
+    /// Seconds an idle worker is kept.
+    #[serde(default)]
+    pub idle_timeout: u32,
+}
+
 /// Pod endpoint information.
 #[derive(Debug, Clone)]
 pub struct PodEndpoint {
@@ -230,9 +369,7 @@ impl Pod {
     /// Returns the GPU type name if available.
     #[must_use]
     pub fn gpu_type_name(&self) -> Option<&str> {
-        self.machine
-            .as_ref()
-            .and_then(|m| m.gpu_type_id.as_deref())
+        self.machine.as_ref().and_then(|m| m.gpu_type_id.as_deref())
     }
 
     /// Returns the public endpoints for this pod.
@@ -257,6 +394,17 @@ impl Pod {
         endpoints
     }
 
+    /// Returns the public IP and port mapped to the pod's SSH port.
+    #[must_use]
+    pub fn ssh_endpoint(&self) -> Option<(String, u16)> {
+        self.runtime
+            .as_ref()?
+            .ports
+            .iter()
+            .find(|port| port.private_port == 22 && !port.ip.is_empty())
+            .and_then(|port| Some((port.ip.clone(), port.public_port?)))
+    }
+
     /// Checks if the pod is running.
     #[must_use]
     pub const fn is_running(&self) -> bool {
@@ -292,6 +440,8 @@ impl CreatePodRequest {
             min_vcpu_count: None,
             min_memory_in_gb: None,
             network_volume_id: None,
+            template_id: None,
+            bid_per_gpu: None,
             custom_tags: None,
         }
     }
@@ -331,6 +481,14 @@ impl CreatePodRequest {
         self
     }
 
+    /// Mounts a network volume instead of creating a pod volume.
+    #[must_use]
+    pub fn with_network_volume(mut self, network_volume_id: &str) -> Self {
+        self.network_volume_id = Some(network_volume_id.to_string());
+        self.volume_in_gb = 0;
+        self
+    }
+
     /// Sets the ports to expose.
     #[must_use]
     pub fn with_ports(mut self, ports: &str) -> Self {
@@ -338,6 +496,34 @@ impl CreatePodRequest {
         self
     }
 
+    /// Deploys the pod from a saved template.
+    #[must_use]
+    pub fn with_template_id(mut self, template_id: &str) -> Self {
+        self.template_id = Some(template_id.to_string());
+        self
+    }
+
+    /// Rents the pod as an interruptible (spot) pod at `bid` per GPU.
+    #[must_use]
+    pub const fn with_bid_per_gpu(mut self, bid: f64) -> Self {
+        self.bid_per_gpu = Some(bid);
+        self
+    }
+
+    /// Pins the pod to a data center.
+    #[must_use]
+    pub fn with_data_center(mut self, data_center_id: &str) -> Self {
+        self.data_center_id = Some(data_center_id.to_string());
+        self
+    }
+
+    /// Sets the container start command.
+    #[must_use]
+    pub fn with_docker_args(mut self, args: &str) -> Self {
+        self.docker_args = Some(args.to_string());
+        self
+    }
+
     /// Adds an environment variable.
     #[must_use]
     pub fn with_env(mut self, key: &str, value: &str) -> Self {
@@ -0,0 +1,109 @@
+//! Network volumes created and deleted by halldyll.
+//!
+//! A persistent volume with a `data_center` and no `network_volume_id` is a
+//! managed network volume: apply creates one per pod instance, named
+//! `<project>-<env>-<instance>-<volume>`, before the pod that mounts it, and
+//! records it in state. The volume outlives its pod, so a replaced pod gets
+//! the same data back; `destroy` deletes it unless `--keep-volumes` is set.
+
+use tracing::info;
+
+use crate::config::{ProjectConfig, VolumeConfig};
+use crate::error::{ErrorContext, HalldyllError, Result, ResultExt};
+
+use super::client::RunPodClient;
+use super::types::NetworkVolume;
+
+/// Returns the `RunPod` name of the network volume `volume` of pod instance `instance`.
+#[must_use]
+pub fn network_volume_name(project: &ProjectConfig, instance: &str, volume: &str) -> String {
+    format!(
+        "{}-{}-{instance}-{volume}",
+        project.name, project.environment
+    )
+}
+
+/// Creates and deletes managed network volumes.
+#[derive(Debug, Clone)]
+pub struct VolumeManager {
+    /// `RunPod` client.
+    client: RunPodClient,
+}
+
+impl VolumeManager {
+    /// Creates a volume manager.
+    #[must_use]
+    pub const fn new(client: RunPodClient) -> Self {
+        Self { client }
+    }
+
+    /// Returns the network volume for `volume` of pod instance `instance`, creating it if needed.
+    ///
+    /// A volume of the same name left by an interrupted apply is reused, so
+    /// re-running never creates a second copy.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the volume is not managed, exists in another data
+    /// center, or cannot be created.
+    pub async fn ensure(
+        &self,
+        project: &ProjectConfig,
+        instance: &str,
+        volume: &VolumeConfig,
+    ) -> Result<NetworkVolume> {
+        let name = network_volume_name(project, instance, &volume.name);
+        let Some(data_center) = volume
+            .data_center
+            .as_deref()
+            .filter(|_| volume.is_managed())
+        else {
+            return Err(HalldyllError::internal(format!(
+                "Volume '{}' is not a managed network volume",
+                volume.name
+            )));
+        };
+
+        if let Some(existing) = self
+            .client
+            .list_network_volumes()
+            .await?
+            .into_iter()
+            .find(|v| v.name == name)
+        {
+            if existing.data_center_id != data_center {
+                return Err(HalldyllError::internal(format!(
+                    "Network volume '{name}' ({}) is in {}, not {data_center}; delete it to move the volume",
+                    existing.id, existing.data_center_id
+                )));
+            }
+            info!("Using existing network volume {name} ({})", existing.id);
+            return Ok(existing);
+        }
+
+        let size_gb = volume.size_gb.unwrap_or_default();
+        let created = self
+            .client
+            .create_network_volume(&name, size_gb, data_center)
+            .await
+            .context(|| ErrorContext::new("creating network volume").with_resource(&name))?;
+        info!(
+            "Created network volume {name} ({}) in {data_center}",
+            created.id
+        );
+        Ok(created)
+    }
+
+    /// Deletes a network volume.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the volume cannot be deleted (e.g. it is still
+    /// attached to a pod).
+    pub async fn delete(&self, volume_id: &str) -> Result<()> {
+        self.client
+            .delete_network_volume(volume_id)
+            .await
+            .context(|| ErrorContext::new("deleting network volume").with_resource(volume_id))
+    }
+}
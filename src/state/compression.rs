@@ -0,0 +1,115 @@
+//! Compression of remote state payloads.
+//!
+//! Compressed objects are tagged with a `Content-Encoding`, and loading also
+//! recognizes the gzip and zstd magic bytes, so state written with any
+//! `state.compression` setting can always be read back.
+
+use std::io::{Read, Write};
+
+use crate::config::StateCompression;
+use crate::error::{HalldyllError, Result, StateError};
+
+/// Leading bytes of a gzip stream.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Leading bytes of a zstd frame.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Zstd compression level (the library default).
+const ZSTD_LEVEL: i32 = 3;
+
+/// Returns the `Content-Encoding` for a compression setting.
+#[must_use]
+pub const fn content_encoding(compression: StateCompression) -> Option<&'static str> {
+    match compression {
+        StateCompression::None => None,
+        StateCompression::Gzip => Some("gzip"),
+        StateCompression::Zstd => Some("zstd"),
+    }
+}
+
+/// Compresses a payload.
+///
+/// # Errors
+///
+/// Returns an error if the encoder fails.
+pub fn compress(data: &[u8], compression: StateCompression) -> Result<Vec<u8>> {
+    match compression {
+        StateCompression::None => Ok(data.to_vec()),
+        StateCompression::Gzip => {
+            let mut encoder =
+                flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(data).map_err(|e| compression_error(&e))?;
+            encoder.finish().map_err(|e| compression_error(&e))
+        }
+        StateCompression::Zstd => {
+            zstd::encode_all(data, ZSTD_LEVEL).map_err(|e| compression_error(&e))
+        }
+    }
+}
+
+/// Decompresses a payload based on its `Content-Encoding` or magic bytes.
+///
+/// Uncompressed payloads are returned unchanged.
+///
+/// # Errors
+///
+/// Returns an error if the payload is not valid for its encoding.
+pub fn decompress(data: Vec<u8>, encoding: Option<&str>) -> Result<Vec<u8>> {
+    let compression = match encoding.map(str::to_ascii_lowercase).as_deref() {
+        Some("gzip") => StateCompression::Gzip,
+        Some("zstd") => StateCompression::Zstd,
+        _ if data.starts_with(&GZIP_MAGIC) => StateCompression::Gzip,
+        _ if data.starts_with(&ZSTD_MAGIC) => StateCompression::Zstd,
+        _ => return Ok(data),
+    };
+
+    match compression {
+        StateCompression::Gzip => {
+            let mut out = Vec::new();
+            flate2::read::GzDecoder::new(data.as_slice())
+                .read_to_end(&mut out)
+                .map_err(|e| corrupted(&e))?;
+            Ok(out)
+        }
+        StateCompression::Zstd => zstd::decode_all(data.as_slice()).map_err(|e| corrupted(&e)),
+        StateCompression::None => Ok(data),
+    }
+}
+
+/// Maps an encoder failure.
+fn compression_error(e: &std::io::Error) -> HalldyllError {
+    StateError::serialization(format!("Failed to compress state: {e}")).into()
+}
+
+/// Maps a decoder failure.
+fn corrupted(e: &std::io::Error) -> HalldyllError {
+    StateError::Corrupted {
+        message: format!("Failed to decompress state: {e}"),
+    }
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let json = br#"{"project":"demo","pods":{}}"#;
+
+        for compression in [
+            StateCompression::None,
+            StateCompression::Gzip,
+            StateCompression::Zstd,
+        ] {
+            let packed = compress(json, compression).unwrap();
+            // Magic-byte detection works without the content encoding
+            assert_eq!(decompress(packed.clone(), None).unwrap(), json);
+            assert_eq!(
+                decompress(packed, content_encoding(compression)).unwrap(),
+                json
+            );
+        }
+    }
+}
@@ -0,0 +1,222 @@
+//! History of health check outcomes.
+//!
+//! `halldyll watch` appends one sample per pod and tick to
+//! `.halldyll/health_history.jsonl`, which `halldyll slo` reads to report
+//! availability over a time window. The history is local to the machine
+//! running watch.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+use tracing::{debug, warn};
+
+use crate::error::{Result, StateError};
+
+/// Health history file name.
+const HISTORY_FILE: &str = "health_history.jsonl";
+
+/// Outcome of one health check of one pod.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HealthSample {
+    /// When the check ran.
+    pub at: DateTime<Utc>,
+    /// Local pod name (replica instance name for replicated pods).
+    pub pod: String,
+    /// Whether the pod was healthy.
+    pub healthy: bool,
+    /// Average endpoint response time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_time_ms: Option<u64>,
+    /// Why the pod was unhealthy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl HealthSample {
+    /// Returns whether the sample is of `pod` or one of its replicas.
+    #[must_use]
+    pub fn belongs_to(&self, pod: &str) -> bool {
+        self.pod == pod
+            || self
+                .pod
+                .rsplit_once('-')
+                .is_some_and(|(base, index)| base == pod && index.parse::<u32>().is_ok())
+    }
+}
+
+/// Append-only file of health samples.
+#[derive(Debug, Clone)]
+pub struct HealthHistory {
+    /// Path to the history file.
+    path: PathBuf,
+}
+
+impl HealthHistory {
+    /// Creates a health history stored in a directory (usually `.halldyll`).
+    #[must_use]
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self {
+            path: dir.as_ref().join(HISTORY_FILE),
+        }
+    }
+
+    /// Returns the history file path.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends samples to the history.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub async fn append(&self, samples: &[HealthSample]) -> Result<()> {
+        if samples.is_empty() {
+            return Ok(());
+        }
+
+        let mut lines = String::new();
+        for sample in samples {
+            lines.push_str(
+                &serde_json::to_string(sample)
+                    .map_err(|e| StateError::serialization(e.to_string()))?,
+            );
+            lines.push('\n');
+        }
+
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .await
+                .map_err(|e| StateError::io(dir, e))?;
+        }
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await
+            .map_err(|e| StateError::io(&self.path, e))?;
+        file.write_all(lines.as_bytes())
+            .await
+            .map_err(|e| StateError::io(&self.path, e))?;
+
+        debug!(
+            "Recorded {} health samples in {}",
+            samples.len(),
+            self.path.display()
+        );
+        Ok(())
+    }
+
+    /// Reads the samples taken at or after `since`, oldest first.
+    ///
+    /// Lines that cannot be parsed (e.g. a write cut short) are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read.
+    pub async fn read_since(&self, since: DateTime<Utc>) -> Result<Vec<HealthSample>> {
+        let content = match fs::read_to_string(&self.path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(StateError::io(&self.path, e).into()),
+        };
+
+        let mut samples: Vec<HealthSample> = content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| match serde_json::from_str::<HealthSample>(line) {
+                Ok(sample) => Some(sample),
+                Err(e) => {
+                    warn!("Skipping unreadable line in {}: {e}", self.path.display());
+                    None
+                }
+            })
+            .filter(|sample| sample.at >= since)
+            .collect();
+        samples.sort_by_key(|sample| sample.at);
+        Ok(samples)
+    }
+
+    /// Drops samples taken before `before`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or rewritten.
+    pub async fn prune(&self, before: DateTime<Utc>) -> Result<()> {
+        if !fs::try_exists(&self.path).await.unwrap_or(false) {
+            return Ok(());
+        }
+
+        let kept = self.read_since(before).await?;
+        let mut content = String::new();
+        for sample in &kept {
+            content.push_str(
+                &serde_json::to_string(sample)
+                    .map_err(|e| StateError::serialization(e.to_string()))?,
+            );
+            content.push('\n');
+        }
+
+        let temp_path = self.path.with_extension("jsonl.tmp");
+        fs::write(&temp_path, content)
+            .await
+            .map_err(|e| StateError::io(&temp_path, e))?;
+        fs::rename(&temp_path, &self.path)
+            .await
+            .map_err(|e| StateError::io(&self.path, e))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_append_read_and_prune() {
+        let temp = TempDir::new().expect("Failed to create temp dir");
+        let history = HealthHistory::new(temp.path().join(".halldyll"));
+        let now = Utc::now();
+        let sample = |hours: i64, pod: &str, healthy: bool| HealthSample {
+            at: now - chrono::Duration::hours(hours),
+            pod: pod.to_string(),
+            healthy,
+            response_time_ms: None,
+            error: None,
+        };
+
+        history
+            .append(&[sample(48, "llm-0", true), sample(1, "llm-1", false)])
+            .await
+            .expect("Append should succeed");
+        history
+            .append(&[sample(2, "cache", true)])
+            .await
+            .expect("Append should succeed");
+
+        let recent = history
+            .read_since(now - chrono::Duration::hours(24))
+            .await
+            .expect("Read should succeed");
+        assert_eq!(
+            recent.iter().map(|s| s.pod.as_str()).collect::<Vec<_>>(),
+            ["cache", "llm-1"]
+        );
+        assert!(recent[1].belongs_to("llm"));
+        assert!(!recent[0].belongs_to("llm"));
+
+        history
+            .prune(now - chrono::Duration::hours(24))
+            .await
+            .expect("Prune should succeed");
+        let all = history
+            .read_since(DateTime::<Utc>::MIN_UTC)
+            .await
+            .expect("Read should succeed");
+        assert_eq!(all.len(), 2);
+    }
+}
@@ -0,0 +1,447 @@
+//! HTTP-based state storage backend.
+//!
+//! Works like Terraform's `http` backend, so any service implementing that
+//! protocol can hold the state:
+//!
+//! - `GET address` loads the state (`404` or `204` means no state yet),
+//!   `POST address` saves it and `DELETE address` deletes it.
+//! - `LOCK lock_address` with the lock as JSON body acquires the lock; the
+//!   service answers `423 Locked` or `409 Conflict` with the current lock
+//!   when someone else holds it. `UNLOCK unlock_address` releases it.
+//!
+//! Saves made while holding a lock carry its ID as `?ID=<lock id>`. Without
+//! a `lock_address`, locking is disabled. Requests use basic auth when a
+//! username is configured.
+
+use async_trait::async_trait;
+use reqwest::{Client, Method, RequestBuilder, Response, StatusCode};
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+use tracing::{debug, info};
+
+use crate::config::StateConfig;
+use crate::error::{HalldyllError, Result, StateError};
+
+use super::lock::{LOCK_EXPIRY_SECS, LockInfo, generate_holder_id};
+use super::store::StateStore;
+use super::types::DeploymentState;
+
+/// Timeout for state requests in seconds.
+const REQUEST_TIMEOUT_SECS: u64 = 30;
+
+/// HTTP-based state store.
+#[derive(Debug)]
+pub struct HttpStateStore {
+    /// HTTP client.
+    client: Client,
+    /// State URL.
+    address: String,
+    /// Lock URL (locking is disabled without one).
+    lock_address: Option<String>,
+    /// Unlock URL.
+    unlock_address: Option<String>,
+    /// Basic auth credentials.
+    credentials: Option<(String, Option<String>)>,
+    /// Lock held by this store, if any.
+    held_lock: Mutex<Option<LockInfo>>,
+}
+
+impl HttpStateStore {
+    /// Creates a store for a state URL, without locking or authentication.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP client cannot be created.
+    pub fn new(address: impl Into<String>) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .build()
+            .map_err(|e| HalldyllError::internal(format!("Failed to create HTTP client: {e}")))?;
+
+        Ok(Self {
+            client,
+            address: address.into(),
+            lock_address: None,
+            unlock_address: None,
+            credentials: None,
+            held_lock: Mutex::new(None),
+        })
+    }
+
+    /// Creates an HTTP state store from the `state` configuration section.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no address is configured.
+    pub fn from_config(state: &StateConfig) -> Result<Self> {
+        let address = state
+            .address
+            .as_deref()
+            .ok_or_else(|| HalldyllError::internal("HTTP state address not configured"))?;
+
+        let mut store = Self::new(address)?;
+        if let Some(lock_address) = &state.lock_address {
+            store = store.with_lock_addresses(lock_address, state.unlock_address.as_deref());
+        }
+        if let Some(username) = &state.username {
+            store = store.with_basic_auth(username, state.password.as_deref());
+        }
+        Ok(store)
+    }
+
+    /// Enables locking against `lock_address`, unlocking against
+    /// `unlock_address` (defaults to the lock address).
+    #[must_use]
+    pub fn with_lock_addresses(mut self, lock_address: &str, unlock_address: Option<&str>) -> Self {
+        self.lock_address = Some(lock_address.to_string());
+        self.unlock_address = unlock_address.map(String::from);
+        self
+    }
+
+    /// Authenticates requests with basic auth.
+    #[must_use]
+    pub fn with_basic_auth(mut self, username: &str, password: Option<&str>) -> Self {
+        self.credentials = Some((username.to_string(), password.map(String::from)));
+        self
+    }
+
+    /// Builds a request, with credentials if configured.
+    fn request(&self, method: Method, url: &str) -> RequestBuilder {
+        let request = self.client.request(method, url);
+        match &self.credentials {
+            Some((username, password)) => request.basic_auth(username, password.as_deref()),
+            None => request,
+        }
+    }
+
+    /// Sends a request, turning transport failures into state errors.
+    async fn send(&self, request: RequestBuilder, what: &str) -> Result<Response> {
+        request
+            .send()
+            .await
+            .map_err(|e| HalldyllError::State(StateError::http(format!("{what} failed: {e}"))))
+    }
+
+    /// Returns the error for an unexpected response.
+    async fn unexpected(response: Response, what: &str) -> HalldyllError {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        HalldyllError::State(StateError::http(format!(
+            "{what} returned {status}: {}",
+            body.trim()
+        )))
+    }
+
+    /// Returns the held lock if it has the given ID.
+    fn held_lock(&self, lock_id: &str) -> Option<LockInfo> {
+        self.held_lock
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+            .filter(|lock| lock.lock_id == lock_id)
+    }
+
+    /// Records the held lock.
+    fn set_held_lock(&self, lock: Option<LockInfo>) {
+        *self
+            .held_lock
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = lock;
+    }
+
+    /// Sends `LOCK` with a lock, returning the lock held by someone else if
+    /// the service refuses it.
+    async fn send_lock(
+        &self,
+        lock_address: &str,
+        lock_info: &LockInfo,
+    ) -> Result<Option<LockInfo>> {
+        let lock_method =
+            Method::from_bytes(b"LOCK").map_err(|e| HalldyllError::internal(e.to_string()))?;
+        let response = self
+            .send(
+                self.request(lock_method, lock_address).json(lock_info),
+                "Locking state",
+            )
+            .await?;
+
+        match response.status() {
+            status if status.is_success() => Ok(None),
+            StatusCode::LOCKED | StatusCode::CONFLICT => {
+                let body = response.text().await.unwrap_or_default();
+                let existing = serde_json::from_str(&body).unwrap_or_else(|_| LockInfo {
+                    holder: String::from("unknown"),
+                    ..LockInfo::new("")
+                });
+                Ok(Some(existing))
+            }
+            _ => Err(Self::unexpected(response, "Locking state").await),
+        }
+    }
+}
+
+#[async_trait]
+impl StateStore for HttpStateStore {
+    async fn load(&self) -> Result<Option<DeploymentState>> {
+        debug!("Loading state from {}", self.address);
+        let response = self
+            .send(self.request(Method::GET, &self.address), "Loading state")
+            .await?;
+
+        match response.status() {
+            StatusCode::NOT_FOUND | StatusCode::NO_CONTENT => {
+                debug!("No state found at {}", self.address);
+                Ok(None)
+            }
+            status if status.is_success() => {
+                let body = response.text().await.map_err(|e| {
+                    HalldyllError::State(StateError::http(format!("Failed to read state: {e}")))
+                })?;
+                if body.trim().is_empty() {
+                    return Ok(None);
+                }
+                let state: DeploymentState = serde_json::from_str(&body).map_err(|e| {
+                    HalldyllError::State(StateError::Corrupted {
+                        message: format!("Failed to parse state: {e}"),
+                    })
+                })?;
+                info!(
+                    "Loaded state for project: {}/{}",
+                    state.project, state.environment
+                );
+                Ok(Some(state))
+            }
+            _ => Err(Self::unexpected(response, "Loading state").await),
+        }
+    }
+
+    async fn save(&self, state: &DeploymentState) -> Result<()> {
+        info!("Saving state to {}", self.address);
+        let content = serde_json::to_string_pretty(state).map_err(|e| {
+            HalldyllError::State(StateError::serialization(format!(
+                "Failed to serialize state: {e}"
+            )))
+        })?;
+
+        let mut request = self
+            .request(Method::POST, &self.address)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(content);
+        let held = self
+            .held_lock
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        if let Some(lock) = held {
+            request = request.query(&[("ID", lock.lock_id.as_str())]);
+        }
+
+        let response = self.send(request, "Saving state").await?;
+        match response.status() {
+            status if status.is_success() => Ok(()),
+            StatusCode::LOCKED | StatusCode::CONFLICT => {
+                Err(HalldyllError::State(StateError::Conflict {
+                    message: format!("{} refused the save: {}", self.address, response.status()),
+                }))
+            }
+            _ => Err(Self::unexpected(response, "Saving state").await),
+        }
+    }
+
+    async fn delete(&self) -> Result<()> {
+        info!("Deleting state at {}", self.address);
+        let response = self
+            .send(
+                self.request(Method::DELETE, &self.address),
+                "Deleting state",
+            )
+            .await?;
+        match response.status() {
+            status if status.is_success() || status == StatusCode::NOT_FOUND => Ok(()),
+            _ => Err(Self::unexpected(response, "Deleting state").await),
+        }
+    }
+
+    async fn exists(&self) -> Result<bool> {
+        Ok(self.load().await?.is_some())
+    }
+
+    async fn acquire_lock(&self, holder: &str) -> Result<LockInfo> {
+        let holder_id = if holder.is_empty() {
+            generate_holder_id()
+        } else {
+            holder.to_string()
+        };
+        let lock_info = LockInfo::new(&holder_id);
+
+        let Some(lock_address) = &self.lock_address else {
+            debug!("No state.lock_address configured, state locking is disabled");
+            return Ok(lock_info);
+        };
+
+        if let Some(existing) = self.send_lock(lock_address, &lock_info).await? {
+            return Err(HalldyllError::State(StateError::LockedByOther {
+                holder: existing.holder,
+                since: existing.acquired_at.to_rfc3339(),
+            }));
+        }
+
+        self.set_held_lock(Some(lock_info.clone()));
+        info!(
+            "Acquired state lock: {} (expires in {}s)",
+            lock_info.lock_id, LOCK_EXPIRY_SECS
+        );
+        Ok(lock_info)
+    }
+
+    async fn release_lock(&self, lock_id: &str) -> Result<()> {
+        let Some(lock_address) = &self.lock_address else {
+            return Ok(());
+        };
+        let unlock_address = self.unlock_address.as_deref().unwrap_or(lock_address);
+        let lock_info = self.held_lock(lock_id).unwrap_or_else(|| LockInfo {
+            lock_id: lock_id.to_string(),
+            ..LockInfo::new("")
+        });
+
+        let unlock_method =
+            Method::from_bytes(b"UNLOCK").map_err(|e| HalldyllError::internal(e.to_string()))?;
+        let response = self
+            .send(
+                self.request(unlock_method, unlock_address).json(&lock_info),
+                "Unlocking state",
+            )
+            .await?;
+        if !response.status().is_success() {
+            return Err(Self::unexpected(response, "Unlocking state").await);
+        }
+
+        self.set_held_lock(None);
+        info!("Released state lock: {lock_id}");
+        Ok(())
+    }
+
+    async fn renew_lock(&self, lock_id: &str, ttl_secs: i64) -> Result<LockInfo> {
+        let lost = || {
+            HalldyllError::State(StateError::LockLost {
+                lock_id: lock_id.to_string(),
+            })
+        };
+        let mut lock_info = self.held_lock(lock_id).ok_or_else(lost)?;
+        lock_info.refresh_for(ttl_secs);
+
+        // Re-locking with the same ID extends the lock; the service reports
+        // a different holder if the lock was taken over
+        if let Some(lock_address) = &self.lock_address
+            && let Some(existing) = self.send_lock(lock_address, &lock_info).await?
+            && existing.lock_id != lock_id
+        {
+            self.set_held_lock(None);
+            return Err(lost());
+        }
+
+        self.set_held_lock(Some(lock_info.clone()));
+        debug!("Renewed state lock {lock_id} for {ttl_secs}s");
+        Ok(lock_info)
+    }
+
+    async fn get_lock_info(&self) -> Result<Option<LockInfo>> {
+        let Some(lock_address) = &self.lock_address else {
+            return Ok(None);
+        };
+
+        // The protocol has no lock query; services that answer GET on the
+        // lock URL report the current lock, others fall back to our own
+        let response = self
+            .send(
+                self.request(Method::GET, lock_address),
+                "Reading state lock",
+            )
+            .await?;
+        if response.status().is_success() {
+            let body = response.text().await.unwrap_or_default();
+            if let Ok(lock_info) = serde_json::from_str::<LockInfo>(&body) {
+                return Ok(Some(lock_info));
+            }
+        }
+        Ok(self
+            .held_lock
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone())
+    }
+
+    async fn is_locked(&self) -> Result<bool> {
+        Ok(self
+            .get_lock_info()
+            .await?
+            .is_some_and(|lock| !lock.is_expired()))
+    }
+
+    fn backend_type(&self) -> &'static str {
+        "http"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{header_exists, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_locked_save_and_conflict() {
+        let server = MockServer::start().await;
+        let state = DeploymentState::new("demo", "dev");
+
+        Mock::given(method("GET"))
+            .and(path("/state"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+        Mock::given(method("LOCK"))
+            .and(path("/lock"))
+            .and(header_exists("authorization"))
+            .respond_with(ResponseTemplate::new(200))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("LOCK"))
+            .and(path("/lock"))
+            .respond_with(ResponseTemplate::new(423).set_body_json(LockInfo::new("ci-runner")))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/state"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let store = HttpStateStore::new(format!("{}/state", server.uri()))
+            .unwrap()
+            .with_lock_addresses(&format!("{}/lock", server.uri()), None)
+            .with_basic_auth("halldyll", Some("secret"));
+
+        assert!(store.load().await.unwrap().is_none());
+        let lock = store.acquire_lock("me").await.unwrap();
+        store.save(&state).await.unwrap();
+
+        let received = server.received_requests().await.unwrap();
+        let save = received
+            .iter()
+            .find(|r| r.method.as_str() == "POST")
+            .unwrap();
+        assert_eq!(
+            save.url.query(),
+            Some(format!("ID={}", lock.lock_id).as_str())
+        );
+
+        let other = HttpStateStore::new(format!("{}/state", server.uri()))
+            .unwrap()
+            .with_lock_addresses(&format!("{}/lock", server.uri()), None);
+        let err = other.acquire_lock("someone-else").await.unwrap_err();
+        assert!(
+            matches!(err, HalldyllError::State(StateError::LockedByOther { ref holder, .. }) if holder == "ci-runner")
+        );
+    }
+}
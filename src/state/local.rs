@@ -2,8 +2,19 @@
 //!
 //! This module provides a simple file-based state storage for local development
 //! and single-machine deployments.
+//!
+//! Besides the logical lock file, every read and write of the state and lock
+//! files happens under an OS advisory lock (`flock`/`LockFileEx`), so two
+//! halldyll processes on the same machine cannot interleave their updates.
+//!
+//! Before `apply` and `reconcile` change the state, a copy is kept in
+//! `snapshots/` (the last [`DEFAULT_KEEP_SNAPSHOTS`] by default); these are
+//! the versions listed by `state history --versions` and restored by
+//! `state rollback`.
 
 use async_trait::async_trait;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use fs2::FileExt;
 use std::path::{Path, PathBuf};
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
@@ -11,9 +22,9 @@ use tracing::{debug, info};
 
 use crate::error::{HalldyllError, Result, StateError};
 
-use super::lock::{generate_holder_id, LockInfo, LOCK_EXPIRY_SECS};
+use super::lock::{LOCK_EXPIRY_SECS, LockInfo, generate_holder_id};
 use super::store::StateStore;
-use super::types::DeploymentState;
+use super::types::{DeploymentState, StateVersion};
 
 /// Default state directory name.
 const STATE_DIR: &str = ".halldyll";
@@ -24,6 +35,33 @@ const STATE_FILE: &str = "state.json";
 /// Lock file name.
 const LOCK_FILE: &str = "state.lock";
 
+/// File holding the OS advisory lock.
+const FLOCK_FILE: &str = "state.flock";
+
+/// Directory holding state snapshots.
+const SNAPSHOTS_DIR: &str = "snapshots";
+
+/// Snapshot file names (version IDs); they sort oldest first.
+const SNAPSHOT_ID_FORMAT: &str = "%Y%m%dT%H%M%S%.3fZ";
+
+/// Number of snapshots kept by default.
+pub const DEFAULT_KEEP_SNAPSHOTS: usize = 10;
+
+/// Version ID of the current state file.
+const CURRENT_VERSION: &str = "current";
+
+/// A held OS advisory lock, released when dropped.
+struct FileLockGuard {
+    /// Locked file handle.
+    file: std::fs::File,
+}
+
+impl Drop for FileLockGuard {
+    fn drop(&mut self) {
+        let _ = FileExt::unlock(&self.file);
+    }
+}
+
 /// Local file-based state store.
 #[derive(Debug)]
 pub struct LocalStateStore {
@@ -33,6 +71,12 @@ pub struct LocalStateStore {
     state_path: PathBuf,
     /// Path to the lock file.
     lock_path: PathBuf,
+    /// Path to the OS advisory lock file.
+    flock_path: PathBuf,
+    /// Directory holding state snapshots.
+    snapshots_dir: PathBuf,
+    /// Number of snapshots kept (0 disables snapshots).
+    keep_snapshots: usize,
 }
 
 impl LocalStateStore {
@@ -43,7 +87,9 @@ impl LocalStateStore {
     /// Returns an error if the base directory cannot be determined.
     pub fn new() -> Result<Self> {
         let base_dir = std::env::current_dir()
-            .map_err(|e| HalldyllError::internal(format!("Cannot determine current directory: {e}")))?
+            .map_err(|e| {
+                HalldyllError::internal(format!("Cannot determine current directory: {e}"))
+            })?
             .join(STATE_DIR);
 
         Ok(Self::with_base_dir(base_dir))
@@ -55,11 +101,16 @@ impl LocalStateStore {
         let base_dir = base_dir.into();
         let state_path = base_dir.join(STATE_FILE);
         let lock_path = base_dir.join(LOCK_FILE);
+        let flock_path = base_dir.join(FLOCK_FILE);
+        let snapshots_dir = base_dir.join(SNAPSHOTS_DIR);
 
         Self {
             base_dir,
             state_path,
             lock_path,
+            flock_path,
+            snapshots_dir,
+            keep_snapshots: DEFAULT_KEEP_SNAPSHOTS,
         }
     }
 
@@ -71,38 +122,114 @@ impl LocalStateStore {
             .parent()
             .map_or_else(|| PathBuf::from("."), Path::to_path_buf);
         let lock_path = base_dir.join(LOCK_FILE);
+        let flock_path = base_dir.join(FLOCK_FILE);
+        let snapshots_dir = base_dir.join(SNAPSHOTS_DIR);
 
         Self {
             base_dir,
             state_path,
             lock_path,
+            flock_path,
+            snapshots_dir,
+            keep_snapshots: DEFAULT_KEEP_SNAPSHOTS,
         }
     }
 
+    /// Sets how many snapshots are kept (0 disables snapshots).
+    #[must_use]
+    pub const fn with_keep_snapshots(mut self, keep: usize) -> Self {
+        self.keep_snapshots = keep;
+        self
+    }
+
+    /// Returns the path of a snapshot, rejecting IDs that are not file names.
+    fn snapshot_path(&self, version_id: &str) -> Result<PathBuf> {
+        let parsed = NaiveDateTime::parse_from_str(version_id, SNAPSHOT_ID_FORMAT);
+        if parsed.is_err() {
+            return Err(StateError::NotFound {
+                path: self.snapshots_dir.join(version_id),
+            }
+            .into());
+        }
+        Ok(self.snapshots_dir.join(format!("{version_id}.json")))
+    }
+
+    /// Lists snapshot IDs, oldest first.
+    async fn snapshot_ids(&self) -> Result<Vec<String>> {
+        let mut entries = match fs::read_dir(&self.snapshots_dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(StateError::io(&self.snapshots_dir, e).into()),
+        };
+
+        let mut ids = Vec::new();
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(|e| StateError::io(&self.snapshots_dir, e))?
+        {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if let Some(id) = name.strip_suffix(".json")
+                && NaiveDateTime::parse_from_str(id, SNAPSHOT_ID_FORMAT).is_ok()
+            {
+                ids.push(id.to_string());
+            }
+        }
+        ids.sort();
+        Ok(ids)
+    }
+
     /// Ensures the state directory exists.
     async fn ensure_dir(&self) -> Result<()> {
         if !self.base_dir.exists() {
             debug!("Creating state directory: {}", self.base_dir.display());
-            fs::create_dir_all(&self.base_dir).await.map_err(|e| {
-                HalldyllError::State(StateError::S3Error {
-                    message: format!("Failed to create state directory: {e}"),
-                })
-            })?;
+            fs::create_dir_all(&self.base_dir)
+                .await
+                .map_err(|e| StateError::io(&self.base_dir, e))?;
         }
         Ok(())
     }
 
+    /// Takes the OS advisory lock guarding the state and lock files.
+    ///
+    /// Waits (off the async runtime) until other processes release it.
+    async fn os_lock(&self, exclusive: bool) -> Result<FileLockGuard> {
+        self.ensure_dir().await?;
+
+        let path = self.flock_path.clone();
+        let locked = tokio::task::spawn_blocking(move || {
+            let file = std::fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(&path)?;
+            if exclusive {
+                FileExt::lock_exclusive(&file)?;
+            } else {
+                FileExt::lock_shared(&file)?;
+            }
+            Ok::<_, std::io::Error>(FileLockGuard { file })
+        })
+        .await
+        .map_err(|e| HalldyllError::internal(format!("State file lock task failed: {e}")))?;
+
+        locked.map_err(|e| {
+            HalldyllError::State(StateError::LockFailed {
+                message: format!("Failed to lock {}: {e}", self.flock_path.display()),
+            })
+        })
+    }
+
     /// Reads the lock file if it exists.
     async fn read_lock_file(&self) -> Result<Option<LockInfo>> {
         if !self.lock_path.exists() {
             return Ok(None);
         }
 
-        let content = fs::read_to_string(&self.lock_path).await.map_err(|e| {
-            HalldyllError::State(StateError::Corrupted {
-                message: format!("Failed to read lock file: {e}"),
-            })
-        })?;
+        let content = fs::read_to_string(&self.lock_path)
+            .await
+            .map_err(|e| StateError::io(&self.lock_path, e))?;
 
         let lock_info: LockInfo = serde_json::from_str(&content).map_err(|e| {
             HalldyllError::State(StateError::Corrupted {
@@ -123,23 +250,10 @@ impl LocalStateStore {
             })
         })?;
 
-        let mut file = fs::File::create(&self.lock_path).await.map_err(|e| {
-            HalldyllError::State(StateError::LockFailed {
-                message: format!("Failed to create lock file: {e}"),
-            })
-        })?;
-
-        file.write_all(content.as_bytes()).await.map_err(|e| {
-            HalldyllError::State(StateError::LockFailed {
-                message: format!("Failed to write lock file: {e}"),
-            })
-        })?;
-
-        file.sync_all().await.map_err(|e| {
-            HalldyllError::State(StateError::LockFailed {
-                message: format!("Failed to sync lock file: {e}"),
-            })
-        })?;
+        let io_error = |e| StateError::io(&self.lock_path, e);
+        let mut file = fs::File::create(&self.lock_path).await.map_err(io_error)?;
+        file.write_all(content.as_bytes()).await.map_err(io_error)?;
+        file.sync_all().await.map_err(io_error)?;
 
         Ok(())
     }
@@ -147,11 +261,9 @@ impl LocalStateStore {
     /// Deletes the lock file.
     async fn delete_lock_file(&self) -> Result<()> {
         if self.lock_path.exists() {
-            fs::remove_file(&self.lock_path).await.map_err(|e| {
-                HalldyllError::State(StateError::LockFailed {
-                    message: format!("Failed to delete lock file: {e}"),
-                })
-            })?;
+            fs::remove_file(&self.lock_path)
+                .await
+                .map_err(|e| StateError::io(&self.lock_path, e))?;
         }
         Ok(())
     }
@@ -167,11 +279,11 @@ impl StateStore for LocalStateStore {
 
         info!("Loading state from: {}", self.state_path.display());
 
-        let content = fs::read_to_string(&self.state_path).await.map_err(|e| {
-            HalldyllError::State(StateError::Corrupted {
-                message: format!("Failed to read state file: {e}"),
-            })
-        })?;
+        let _guard = self.os_lock(false).await?;
+
+        let content = fs::read_to_string(&self.state_path)
+            .await
+            .map_err(|e| StateError::io(&self.state_path, e))?;
 
         let state: DeploymentState = serde_json::from_str(&content).map_err(|e| {
             HalldyllError::State(StateError::Corrupted {
@@ -183,7 +295,7 @@ impl StateStore for LocalStateStore {
     }
 
     async fn save(&self, state: &DeploymentState) -> Result<()> {
-        self.ensure_dir().await?;
+        let _guard = self.os_lock(true).await?;
 
         info!("Saving state to: {}", self.state_path.display());
 
@@ -196,43 +308,31 @@ impl StateStore for LocalStateStore {
         // Write to a temporary file first, then rename for atomicity
         let temp_path = self.state_path.with_extension("tmp");
 
-        let mut file = fs::File::create(&temp_path).await.map_err(|e| {
-            HalldyllError::State(StateError::S3Error {
-                message: format!("Failed to create temp state file: {e}"),
-            })
-        })?;
-
-        file.write_all(content.as_bytes()).await.map_err(|e| {
-            HalldyllError::State(StateError::S3Error {
-                message: format!("Failed to write state file: {e}"),
-            })
-        })?;
-
-        file.sync_all().await.map_err(|e| {
-            HalldyllError::State(StateError::S3Error {
-                message: format!("Failed to sync state file: {e}"),
-            })
-        })?;
+        let io_error = |e| StateError::io(&temp_path, e);
+        let mut file = fs::File::create(&temp_path).await.map_err(io_error)?;
+        file.write_all(content.as_bytes()).await.map_err(io_error)?;
+        file.sync_all().await.map_err(io_error)?;
 
-        // Atomic rename
-        fs::rename(&temp_path, &self.state_path).await.map_err(|e| {
-            HalldyllError::State(StateError::S3Error {
-                message: format!("Failed to rename state file: {e}"),
-            })
-        })?;
+        // Atomic rename (retried while Windows reports a sharing violation)
+        crate::platform::replace_file(&temp_path, &self.state_path)
+            .await
+            .map_err(|e| StateError::io(&self.state_path, e))?;
 
         debug!("State saved successfully");
         Ok(())
     }
 
     async fn delete(&self) -> Result<()> {
+        if !self.base_dir.exists() {
+            return Ok(());
+        }
+        let _guard = self.os_lock(true).await?;
+
         if self.state_path.exists() {
             info!("Deleting state file: {}", self.state_path.display());
-            fs::remove_file(&self.state_path).await.map_err(|e| {
-                HalldyllError::State(StateError::S3Error {
-                    message: format!("Failed to delete state file: {e}"),
-                })
-            })?;
+            fs::remove_file(&self.state_path)
+                .await
+                .map_err(|e| StateError::io(&self.state_path, e))?;
         }
 
         // Also delete lock file
@@ -246,6 +346,9 @@ impl StateStore for LocalStateStore {
     }
 
     async fn acquire_lock(&self, holder: &str) -> Result<LockInfo> {
+        // Hold the OS lock so the expiry check and the write are one step
+        let _guard = self.os_lock(true).await?;
+
         // Check for existing lock
         if let Some(existing) = self.read_lock_file().await? {
             if !existing.is_expired() {
@@ -276,6 +379,8 @@ impl StateStore for LocalStateStore {
     }
 
     async fn release_lock(&self, lock_id: &str) -> Result<()> {
+        let _guard = self.os_lock(true).await?;
+
         if let Some(existing) = self.read_lock_file().await? {
             if existing.lock_id == lock_id {
                 self.delete_lock_file().await?;
@@ -290,6 +395,26 @@ impl StateStore for LocalStateStore {
         Ok(())
     }
 
+    async fn renew_lock(&self, lock_id: &str, ttl_secs: i64) -> Result<LockInfo> {
+        let _guard = self.os_lock(true).await?;
+
+        let Some(mut lock_info) = self
+            .read_lock_file()
+            .await?
+            .filter(|l| l.lock_id == lock_id)
+        else {
+            return Err(HalldyllError::State(StateError::LockLost {
+                lock_id: lock_id.to_string(),
+            }));
+        };
+
+        lock_info.refresh_for(ttl_secs);
+        self.write_lock_file(&lock_info).await?;
+        debug!("Renewed state lock {lock_id} for {ttl_secs}s");
+
+        Ok(lock_info)
+    }
+
     async fn get_lock_info(&self) -> Result<Option<LockInfo>> {
         self.read_lock_file().await
     }
@@ -304,6 +429,88 @@ impl StateStore for LocalStateStore {
     fn backend_type(&self) -> &'static str {
         "local"
     }
+
+    async fn list_versions(&self) -> Result<Vec<StateVersion>> {
+        let mut versions = Vec::new();
+        if let Ok(metadata) = fs::metadata(&self.state_path).await {
+            versions.push(StateVersion {
+                version_id: CURRENT_VERSION.to_string(),
+                last_modified: metadata.modified().ok().map(DateTime::<Utc>::from),
+                size: metadata.len(),
+                is_latest: true,
+            });
+        }
+
+        for id in self.snapshot_ids().await?.into_iter().rev() {
+            let path = self.snapshot_path(&id)?;
+            let size = fs::metadata(&path)
+                .await
+                .map_err(|e| StateError::io(&path, e))?
+                .len();
+            versions.push(StateVersion {
+                last_modified: NaiveDateTime::parse_from_str(&id, SNAPSHOT_ID_FORMAT)
+                    .ok()
+                    .map(|t| t.and_utc()),
+                version_id: id,
+                size,
+                is_latest: false,
+            });
+        }
+
+        Ok(versions)
+    }
+
+    async fn load_version(&self, version_id: &str) -> Result<DeploymentState> {
+        let path = if version_id == CURRENT_VERSION {
+            self.state_path.clone()
+        } else {
+            self.snapshot_path(version_id)?
+        };
+
+        let content = match fs::read_to_string(&path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(StateError::NotFound { path }.into());
+            }
+            Err(e) => return Err(StateError::io(&path, e).into()),
+        };
+
+        serde_json::from_str(&content).map_err(|e| {
+            HalldyllError::State(StateError::Corrupted {
+                message: format!("Failed to parse state snapshot {version_id}: {e}"),
+            })
+        })
+    }
+
+    async fn snapshot(&self) -> Result<Option<String>> {
+        if self.keep_snapshots == 0 || !self.state_path.exists() {
+            return Ok(None);
+        }
+
+        let _guard = self.os_lock(false).await?;
+
+        fs::create_dir_all(&self.snapshots_dir)
+            .await
+            .map_err(|e| StateError::io(&self.snapshots_dir, e))?;
+        let id = Utc::now().format(SNAPSHOT_ID_FORMAT).to_string();
+        let path = self.snapshot_path(&id)?;
+        fs::copy(&self.state_path, &path)
+            .await
+            .map_err(|e| StateError::io(&path, e))?;
+        info!("Saved state snapshot {id}");
+
+        // Drop the oldest snapshots beyond the limit
+        let ids = self.snapshot_ids().await?;
+        for old in &ids[..ids.len().saturating_sub(self.keep_snapshots)] {
+            let old_path = self.snapshot_path(old)?;
+            fs::remove_file(&old_path)
+                .await
+                .map_err(|e| StateError::io(&old_path, e))?;
+            debug!("Removed state snapshot {old}");
+        }
+
+        Ok(Some(id))
+    }
 }
 
 #[cfg(test)]
@@ -311,7 +518,7 @@ mod tests {
     use super::*;
     use tempfile::TempDir;
 
-    async fn create_test_store() -> (LocalStateStore, TempDir) {
+    fn create_test_store() -> (LocalStateStore, TempDir) {
         let temp_dir = TempDir::new().expect("Failed to create temp dir");
         let store = LocalStateStore::with_base_dir(temp_dir.path());
         (store, temp_dir)
@@ -319,7 +526,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_save_and_load() {
-        let (store, _temp) = create_test_store().await;
+        let (store, _temp) = create_test_store();
 
         let state = DeploymentState::new("test-project", "dev");
         store.save(&state).await.expect("Failed to save state");
@@ -334,9 +541,68 @@ mod tests {
         assert_eq!(loaded.environment, "dev");
     }
 
+    #[tokio::test]
+    async fn test_unwritable_dir_reports_path() {
+        let temp = TempDir::new().expect("Failed to create temp dir");
+        let blocker = temp.path().join("not-a-dir");
+        std::fs::write(&blocker, "").expect("Failed to create file");
+        let store = LocalStateStore::with_base_dir(blocker.join("state"));
+
+        let err = store
+            .save(&DeploymentState::new("test-project", "dev"))
+            .await
+            .expect_err("Save should fail");
+
+        assert!(matches!(
+            &err,
+            HalldyllError::State(StateError::Io { path, .. }) if path.ends_with("state")
+        ));
+        assert!(!err.to_string().contains("S3"));
+    }
+
+    #[tokio::test]
+    async fn test_snapshots_keep_last_versions() {
+        let temp = TempDir::new().expect("Failed to create temp dir");
+        let store = LocalStateStore::with_base_dir(temp.path()).with_keep_snapshots(2);
+        assert!(
+            store
+                .snapshot()
+                .await
+                .expect("Snapshot should succeed")
+                .is_none()
+        );
+
+        let mut ids = Vec::new();
+        for version in 0..3 {
+            let state = DeploymentState::new(&format!("v{version}"), "dev");
+            store.save(&state).await.expect("Failed to save state");
+            ids.push(
+                store
+                    .snapshot()
+                    .await
+                    .expect("Snapshot should succeed")
+                    .expect("Snapshot taken"),
+            );
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+
+        let versions = store.list_versions().await.expect("List should succeed");
+        let listed: Vec<&str> = versions.iter().map(|v| v.version_id.as_str()).collect();
+        assert_eq!(listed, [CURRENT_VERSION, &ids[2], &ids[1]]);
+        assert!(versions[0].is_latest);
+
+        let restored = store
+            .load_version(&ids[1])
+            .await
+            .expect("Load should succeed");
+        assert_eq!(restored.project, "v1");
+        assert!(store.load_version(&ids[0]).await.is_err());
+        assert!(store.load_version("../state").await.is_err());
+    }
+
     #[tokio::test]
     async fn test_load_nonexistent() {
-        let (store, _temp) = create_test_store().await;
+        let (store, _temp) = create_test_store();
 
         let result = store.load().await.expect("Load should not fail");
         assert!(result.is_none());
@@ -344,7 +610,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_exists() {
-        let (store, _temp) = create_test_store().await;
+        let (store, _temp) = create_test_store();
 
         assert!(!store.exists().await.expect("exists check failed"));
 
@@ -356,7 +622,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_lock_acquire_release() {
-        let (store, _temp) = create_test_store().await;
+        let (store, _temp) = create_test_store();
 
         let lock = store
             .acquire_lock("test-holder")
@@ -375,7 +641,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_lock_conflict() {
-        let (store, _temp) = create_test_store().await;
+        let (store, _temp) = create_test_store();
 
         let _lock1 = store
             .acquire_lock("holder-1")
@@ -385,4 +651,25 @@ mod tests {
         let result = store.acquire_lock("holder-2").await;
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn test_concurrent_lock_acquire() {
+        let temp = TempDir::new().expect("Failed to create temp dir");
+
+        let attempts: Vec<_> = (0..8)
+            .map(|i| {
+                let store = LocalStateStore::with_base_dir(temp.path());
+                tokio::spawn(async move { store.acquire_lock(&format!("holder-{i}")).await })
+            })
+            .collect();
+
+        let mut acquired = 0;
+        for attempt in attempts {
+            if attempt.await.expect("Lock task panicked").is_ok() {
+                acquired += 1;
+            }
+        }
+
+        assert_eq!(acquired, 1);
+    }
 }
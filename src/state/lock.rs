@@ -51,7 +51,12 @@ impl LockInfo {
 
     /// Refreshes the lock expiry time.
     pub fn refresh(&mut self) {
-        self.expires_at = Utc::now() + chrono::Duration::seconds(LOCK_EXPIRY_SECS);
+        self.refresh_for(LOCK_EXPIRY_SECS);
+    }
+
+    /// Sets the lock to expire `secs` seconds from now.
+    pub fn refresh_for(&mut self, secs: i64) {
+        self.expires_at = Utc::now() + chrono::Duration::seconds(secs);
     }
 
     /// Returns the remaining time until expiry in seconds.
@@ -110,7 +115,10 @@ impl StateLock {
 /// Generates a unique holder identifier for the current process.
 #[must_use]
 pub fn generate_holder_id() -> String {
-    let hostname = hostname::get().map_or_else(|_| String::from("unknown"), |h| h.to_string_lossy().to_string());
+    let hostname = hostname::get().map_or_else(
+        |_| String::from("unknown"),
+        |h| h.to_string_lossy().to_string(),
+    );
 
     let pid = std::process::id();
     let uuid = &Uuid::new_v4().to_string()[..8];
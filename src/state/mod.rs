@@ -3,14 +3,29 @@
 //! This module provides persistent state storage for tracking deployed resources,
 //! including pod mappings, volume identifiers, and deployment history.
 
-mod store;
+#[cfg(feature = "s3")]
+mod compression;
+mod health_history;
+mod http;
 mod local;
-mod s3;
 mod lock;
+#[cfg(feature = "s3")]
+mod s3;
+mod status_cache;
+mod store;
 mod types;
 
-pub use store::StateStore;
+pub use health_history::{HealthHistory, HealthSample};
+pub use http::HttpStateStore;
 pub use local::LocalStateStore;
+pub use lock::{LockInfo, StateLock, generate_holder_id};
+#[cfg(feature = "s3")]
 pub use s3::S3StateStore;
-pub use lock::{StateLock, LockInfo};
-pub use types::{DeploymentState, PodState, VolumeState, DeploymentStatus, DeploymentHistoryEntry, DeploymentOperation};
+pub use status_cache::{CachedStatus, StatusCache};
+pub use store::StateStore;
+pub use types::{
+    ActionTiming, AlertState, DeploymentHistoryEntry, DeploymentOperation, DeploymentState,
+    DeploymentStatus, EndpointState, FailoverSide, FailoverState, MaintenanceState,
+    POD_STATE_FIELDS, PodState, ReleaseColor, ReleaseState, ReplicaState, STATE_VERSION,
+    StandbyPod, StateVersion, StopReason, TemplateState, VolumeState,
+};
@@ -2,16 +2,36 @@
 //!
 //! This module provides a remote state storage using AWS S3 (or compatible services)
 //! for distributed deployments and team collaboration.
+//!
+//! State writes are conditional: the `ETag` seen on load is sent as `If-Match`
+//! (or `If-None-Match: *` when no state existed), so a concurrent writer gets
+//! a [`StateError::Conflict`] instead of silently overwriting newer state.
+//!
+//! The lock object is written the same way: `acquire_lock` creates it with
+//! `If-None-Match: *` (or takes over an expired lock with `If-Match` on its
+//! `ETag`), so when several processes race for the lock exactly one wins.
+//!
+//! Stores that return an object without an `ETag` cannot be written
+//! conditionally; such objects are overwritten unconditionally, with a
+//! warning.
+//!
+//! When bucket versioning is enabled, previous state objects can be listed
+//! and restored (`state history --remote`, `state rollback`).
 
 use async_trait::async_trait;
 use aws_sdk_s3::Client;
-use tracing::{debug, info};
+use aws_sdk_s3::types::BucketVersioningStatus;
+use chrono::{DateTime, Utc};
+use std::sync::{Mutex, PoisonError};
+use tracing::{debug, info, warn};
 
+use crate::config::{StateCompression, StateConfig};
 use crate::error::{HalldyllError, Result, StateError};
 
-use super::lock::{generate_holder_id, LockInfo, LOCK_EXPIRY_SECS};
+use super::compression::{compress, content_encoding, decompress};
+use super::lock::{LOCK_EXPIRY_SECS, LockInfo, generate_holder_id};
 use super::store::StateStore;
-use super::types::DeploymentState;
+use super::types::{DeploymentState, StateVersion};
 
 /// State file key suffix.
 const STATE_KEY: &str = "state.json";
@@ -19,6 +39,20 @@ const STATE_KEY: &str = "state.json";
 /// Lock file key suffix.
 const LOCK_KEY: &str = "state.lock";
 
+/// Region used for custom endpoints when none is configured.
+const DEFAULT_REGION: &str = "us-east-1";
+
+/// Version of the remote state object last seen by this store.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum RemoteVersion {
+    /// No state object existed.
+    Absent,
+    /// State object with this `ETag`.
+    ETag(String),
+    /// State object without an `ETag`, which can only be overwritten unconditionally.
+    Untagged,
+}
+
 /// S3-based state store.
 #[derive(Debug)]
 pub struct S3StateStore {
@@ -28,6 +62,10 @@ pub struct S3StateStore {
     bucket: String,
     /// Key prefix.
     prefix: String,
+    /// State version seen on the last load or save (`None` if never loaded).
+    state_version: Mutex<Option<RemoteVersion>>,
+    /// Compression applied when saving state.
+    compression: StateCompression,
 }
 
 impl S3StateStore {
@@ -46,24 +84,58 @@ impl S3StateStore {
             aws_config::load_from_env().await
         };
 
-        let client = Client::new(&config);
+        Ok(Self::with_client(Client::new(&config), bucket, prefix))
+    }
 
-        let prefix = prefix
-            .map(|p| {
-                let p = p.trim_matches('/');
-                if p.is_empty() {
-                    String::new()
-                } else {
-                    format!("{p}/")
-                }
-            })
-            .unwrap_or_default();
+    /// Creates an S3 state store from the `state` configuration section.
+    ///
+    /// Honors `endpoint_url` and `force_path_style`, so `MinIO` and other
+    /// S3-compatible stores work. Credentials still come from the AWS
+    /// environment.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no bucket is configured.
+    pub async fn from_config(state: &StateConfig) -> Result<Self> {
+        let bucket = state
+            .bucket
+            .as_deref()
+            .ok_or_else(|| HalldyllError::internal("S3 bucket not configured"))?;
+
+        // S3-compatible stores usually ignore the region, but the SDK requires one
+        let region = state.region.clone().or_else(|| {
+            state
+                .endpoint_url
+                .as_ref()
+                .map(|_| String::from(DEFAULT_REGION))
+        });
+
+        let mut loader = aws_config::from_env();
+        if let Some(region) = region {
+            loader = loader.region(aws_config::Region::new(region));
+        }
+        let sdk_config = loader.load().await;
+
+        Ok(Self::with_client(
+            Client::from_conf(Self::client_config(&sdk_config, state)),
+            bucket,
+            state.prefix.as_deref(),
+        )
+        .with_compression(state.compression))
+    }
 
-        Ok(Self {
-            client,
-            bucket: bucket.to_string(),
-            prefix,
-        })
+    /// Builds the S3 client configuration, applying `endpoint_url` and `force_path_style`.
+    fn client_config(
+        sdk_config: &aws_config::SdkConfig,
+        state: &StateConfig,
+    ) -> aws_sdk_s3::Config {
+        let mut builder =
+            aws_sdk_s3::config::Builder::from(sdk_config).force_path_style(state.force_path_style);
+        if let Some(endpoint) = &state.endpoint_url {
+            debug!("Using custom S3 endpoint: {endpoint}");
+            builder = builder.endpoint_url(endpoint);
+        }
+        builder.build()
     }
 
     /// Creates a new S3 state store with an existing client.
@@ -84,16 +156,44 @@ impl S3StateStore {
             client,
             bucket: bucket.to_string(),
             prefix,
+            state_version: Mutex::new(None),
+            compression: StateCompression::None,
         }
     }
 
+    /// Sets the compression applied when saving state.
+    ///
+    /// Loading detects the encoding of the stored object, so this can be
+    /// changed at any time.
+    #[must_use]
+    pub const fn with_compression(mut self, compression: StateCompression) -> Self {
+        self.compression = compression;
+        self
+    }
+
     /// Gets the full S3 key for a file.
     fn key(&self, file: &str) -> String {
         format!("{}{file}", self.prefix)
     }
 
-    /// Gets an object from S3.
-    async fn get_object(&self, key: &str) -> Result<Option<String>> {
+    /// Records the state version seen on load or save.
+    fn set_state_version(&self, version: Option<RemoteVersion>) {
+        *self
+            .state_version
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = version;
+    }
+
+    /// Returns the state version seen on the last load or save.
+    fn state_version(&self) -> Option<RemoteVersion> {
+        self.state_version
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Gets an object and its `ETag` from S3.
+    async fn get_object_versioned(&self, key: &str) -> Result<Option<(String, Option<String>)>> {
         let result = self
             .client
             .get_object()
@@ -104,17 +204,20 @@ impl S3StateStore {
 
         match result {
             Ok(response) => {
+                let etag = response.e_tag().map(String::from);
+                let encoding = response.content_encoding().map(String::from);
                 let bytes = response.body.collect().await.map_err(|e| {
                     HalldyllError::State(StateError::s3(format!("Failed to read S3 object: {e}")))
                 })?;
 
-                let content = String::from_utf8(bytes.to_vec()).map_err(|e| {
+                let bytes = decompress(bytes.to_vec(), encoding.as_deref())?;
+                let content = String::from_utf8(bytes).map_err(|e| {
                     HalldyllError::State(StateError::Corrupted {
                         message: format!("Invalid UTF-8 in S3 object: {e}"),
                     })
                 })?;
 
-                Ok(Some(content))
+                Ok(Some((content, etag)))
             }
             Err(sdk_err) => {
                 let service_err = sdk_err.into_service_error();
@@ -129,36 +232,124 @@ impl S3StateStore {
         }
     }
 
-    /// Puts an object to S3.
-    async fn put_object(&self, key: &str, content: &str) -> Result<()> {
-        self.client
+    /// Puts an object to S3, only if it is still at the expected version.
+    ///
+    /// Returns the new `ETag`.
+    async fn put_object_if(
+        &self,
+        key: &str,
+        body: Vec<u8>,
+        encoding: Option<&str>,
+        expected: Option<&RemoteVersion>,
+    ) -> Result<Option<String>> {
+        let mut request = self
+            .client
             .put_object()
             .bucket(&self.bucket)
             .key(key)
-            .body(content.as_bytes().to_vec().into())
+            .body(body.into())
             .content_type("application/json")
-            .send()
-            .await
-            .map_err(|e| {
+            .set_content_encoding(encoding.map(String::from));
+
+        request = match expected {
+            Some(RemoteVersion::ETag(etag)) => request.if_match(etag),
+            Some(RemoteVersion::Absent) => request.if_none_match("*"),
+            Some(RemoteVersion::Untagged) | None => request,
+        };
+
+        let response = request.send().await.map_err(|e| {
+            // 412 Precondition Failed, or 409 when a concurrent conditional write wins
+            let status = e.raw_response().map(|r| r.status().as_u16());
+            if matches!(
+                expected,
+                Some(RemoteVersion::ETag(_) | RemoteVersion::Absent)
+            ) && matches!(status, Some(409 | 412))
+            {
+                HalldyllError::State(StateError::Conflict {
+                    message: format!(
+                        "s3://{}/{key} changed since it was loaded; reload and retry",
+                        self.bucket
+                    ),
+                })
+            } else {
                 HalldyllError::State(StateError::s3(format!("S3 put error: {e}")))
-            })?;
+            }
+        })?;
 
-        Ok(())
+        Ok(response.e_tag().map(String::from))
+    }
+
+    /// Gets the lock and its `ETag`.
+    async fn get_lock_versioned(&self) -> Result<Option<(LockInfo, Option<String>)>> {
+        let Some((content, etag)) = self.get_object_versioned(&self.key(LOCK_KEY)).await? else {
+            return Ok(None);
+        };
+
+        let lock_info = serde_json::from_str(&content).map_err(|e| {
+            HalldyllError::State(StateError::Corrupted {
+                message: format!("Failed to parse lock: {e}"),
+            })
+        })?;
+        Ok(Some((lock_info, etag)))
+    }
+
+    /// Serializes a lock for storage.
+    fn lock_content(lock_info: &LockInfo) -> Result<String> {
+        serde_json::to_string_pretty(lock_info).map_err(|e| {
+            HalldyllError::State(StateError::serialization(format!(
+                "Failed to serialize lock: {e}"
+            )))
+        })
     }
 
     /// Deletes an object from S3.
     async fn delete_object(&self, key: &str) -> Result<()> {
-        self.client
+        self.delete_object_if(key, None).await.map(|_| ())
+    }
+
+    /// Deletes an object from S3, only if it still has the given `ETag`.
+    ///
+    /// Returns false if the object changed since the `ETag` was read.
+    async fn delete_object_if(&self, key: &str, etag: Option<&str>) -> Result<bool> {
+        let result = self
+            .client
             .delete_object()
             .bucket(&self.bucket)
             .key(key)
+            .set_if_match(etag.map(String::from))
+            .send()
+            .await;
+
+        match result {
+            Ok(_) => Ok(true),
+            Err(e) if e.raw_response().is_some_and(|r| r.status().as_u16() == 412) => Ok(false),
+            Err(e) => Err(HalldyllError::State(StateError::s3(format!(
+                "S3 delete error: {e}"
+            )))),
+        }
+    }
+
+    /// Fails unless versioning is enabled on the bucket.
+    async fn ensure_versioning(&self) -> Result<()> {
+        let response = self
+            .client
+            .get_bucket_versioning()
+            .bucket(&self.bucket)
             .send()
             .await
             .map_err(|e| {
-                HalldyllError::State(StateError::s3(format!("S3 delete error: {e}")))
+                HalldyllError::State(StateError::s3(format!("S3 get versioning error: {e}")))
             })?;
 
-        Ok(())
+        if response.status() == Some(&BucketVersioningStatus::Enabled) {
+            Ok(())
+        } else {
+            Err(StateError::s3(format!(
+                "Versioning is not enabled on bucket {}; enable it to keep state history",
+                self.bucket
+            ))
+            .into())
+        }
     }
 
     /// Checks if an object exists in S3.
@@ -193,9 +384,17 @@ impl StateStore for S3StateStore {
         let key = self.key(STATE_KEY);
         debug!("Loading state from s3://{}/{key}", self.bucket);
 
-        let content = self.get_object(&key).await?;
+        let content = self.get_object_versioned(&key).await?;
+        self.set_state_version(match &content {
+            Some((_, Some(etag))) => Some(RemoteVersion::ETag(etag.clone())),
+            Some((_, None)) => {
+                warn!("State object has no ETag; saves will overwrite it unconditionally");
+                Some(RemoteVersion::Untagged)
+            }
+            None => Some(RemoteVersion::Absent),
+        });
 
-        if let Some(json) = content {
+        if let Some((json, _)) = content {
             let state: DeploymentState = serde_json::from_str(&json).map_err(|e| {
                 HalldyllError::State(StateError::Corrupted {
                     message: format!("Failed to parse state: {e}"),
@@ -217,13 +416,37 @@ impl StateStore for S3StateStore {
         let key = self.key(STATE_KEY);
         info!("Saving state to s3://{}/{key}", self.bucket);
 
-        let content = serde_json::to_string_pretty(state).map_err(|e| {
+        // Pretty-printing only helps when the object is read as plain text
+        let content = if self.compression == StateCompression::None {
+            serde_json::to_vec_pretty(state)
+        } else {
+            serde_json::to_vec(state)
+        }
+        .map_err(|e| {
             HalldyllError::State(StateError::serialization(format!(
                 "Failed to serialize state: {e}"
             )))
         })?;
+        let body = compress(&content, self.compression)?;
+        debug!(
+            "State payload: {} bytes ({} uncompressed)",
+            body.len(),
+            content.len()
+        );
 
-        self.put_object(&key, &content).await?;
+        // Without a prior load, only create the state; never overwrite one unseen
+        let expected = self.state_version().unwrap_or(RemoteVersion::Absent);
+        let etag = self
+            .put_object_if(
+                &key,
+                body,
+                content_encoding(self.compression),
+                Some(&expected),
+            )
+            .await?;
+        self.set_state_version(Some(
+            etag.map_or(RemoteVersion::Untagged, RemoteVersion::ETag),
+        ));
 
         debug!("State saved successfully to S3");
         Ok(())
@@ -237,6 +460,7 @@ impl StateStore for S3StateStore {
 
         self.delete_object(&state_key).await?;
         self.delete_object(&lock_key).await?;
+        self.set_state_version(Some(RemoteVersion::Absent));
 
         Ok(())
     }
@@ -249,22 +473,24 @@ impl StateStore for S3StateStore {
     async fn acquire_lock(&self, holder: &str) -> Result<LockInfo> {
         let key = self.key(LOCK_KEY);
 
-        // Check for existing lock
-        if let Some(content) = self.get_object(&key).await? {
-            let existing: LockInfo = serde_json::from_str(&content).map_err(|e| {
-                HalldyllError::State(StateError::Corrupted {
-                    message: format!("Failed to parse lock: {e}"),
-                })
-            })?;
-
-            if !existing.is_expired() {
+        // Check for existing lock, remembering which version we saw
+        let expected = match self.get_lock_versioned().await? {
+            Some((existing, _)) if !existing.is_expired() => {
                 return Err(HalldyllError::State(StateError::LockedByOther {
-                    holder: existing.holder.clone(),
+                    holder: existing.holder,
                     since: existing.acquired_at.to_rfc3339(),
                 }));
             }
-            debug!("Expired lock found, taking over");
-        }
+            Some((_, Some(etag))) => {
+                debug!("Expired lock found, taking over");
+                Some(RemoteVersion::ETag(etag))
+            }
+            Some((_, None)) => {
+                warn!("Expired lock has no ETag; taking it over unconditionally");
+                None
+            }
+            None => Some(RemoteVersion::Absent),
+        };
 
         let holder_id = if holder.is_empty() {
             generate_holder_id()
@@ -274,13 +500,29 @@ impl StateStore for S3StateStore {
 
         let lock_info = LockInfo::new(&holder_id);
 
-        let content = serde_json::to_string_pretty(&lock_info).map_err(|e| {
-            HalldyllError::State(StateError::serialization(format!(
-                "Failed to serialize lock: {e}"
-            )))
-        })?;
-
-        self.put_object(&key, &content).await?;
+        // Only one of several concurrent writers gets past the precondition
+        match self
+            .put_object_if(
+                &key,
+                Self::lock_content(&lock_info)?.into_bytes(),
+                None,
+                expected.as_ref(),
+            )
+            .await
+        {
+            Ok(_) => {}
+            Err(HalldyllError::State(StateError::Conflict { .. })) => {
+                let winner = self.get_lock_info().await.ok().flatten();
+                return Err(HalldyllError::State(StateError::LockedByOther {
+                    holder: winner
+                        .as_ref()
+                        .map_or_else(|| String::from("unknown"), |l| l.holder.clone()),
+                    since: winner
+                        .map_or_else(|| Utc::now().to_rfc3339(), |l| l.acquired_at.to_rfc3339()),
+                }));
+            }
+            Err(e) => return Err(e),
+        }
 
         info!(
             "Acquired state lock: {} (expires in {}s)",
@@ -293,41 +535,63 @@ impl StateStore for S3StateStore {
     async fn release_lock(&self, lock_id: &str) -> Result<()> {
         let key = self.key(LOCK_KEY);
 
-        if let Some(content) = self.get_object(&key).await? {
-            let existing: LockInfo = serde_json::from_str(&content).map_err(|e| {
-                HalldyllError::State(StateError::Corrupted {
-                    message: format!("Failed to parse lock: {e}"),
-                })
-            })?;
-
-            if existing.lock_id == lock_id {
-                self.delete_object(&key).await?;
-                info!("Released state lock: {lock_id}");
-            } else {
+        if let Some((existing, etag)) = self.get_lock_versioned().await? {
+            if existing.lock_id != lock_id {
                 debug!(
                     "Lock ID mismatch: expected {lock_id}, found {}",
                     existing.lock_id
                 );
+            } else if self.delete_object_if(&key, etag.as_deref()).await? {
+                info!("Released state lock: {lock_id}");
+            } else {
+                // Another process took the lock over after we read it
+                debug!("State lock {lock_id} is no longer ours; leaving it in place");
             }
         }
 
         Ok(())
     }
 
-    async fn get_lock_info(&self) -> Result<Option<LockInfo>> {
-        let key = self.key(LOCK_KEY);
-
-        if let Some(content) = self.get_object(&key).await? {
-            let lock_info: LockInfo = serde_json::from_str(&content).map_err(|e| {
-                HalldyllError::State(StateError::Corrupted {
-                    message: format!("Failed to parse lock: {e}"),
-                })
-            })?;
+    async fn renew_lock(&self, lock_id: &str, ttl_secs: i64) -> Result<LockInfo> {
+        let lost = || {
+            HalldyllError::State(StateError::LockLost {
+                lock_id: lock_id.to_string(),
+            })
+        };
+        let Some((mut lock_info, etag)) = self
+            .get_lock_versioned()
+            .await?
+            .filter(|(l, _)| l.lock_id == lock_id)
+        else {
+            return Err(lost());
+        };
 
-            return Ok(Some(lock_info));
+        // Fails if another process took the lock over since we read it
+        lock_info.refresh_for(ttl_secs);
+        let expected = etag.map(RemoteVersion::ETag);
+        match self
+            .put_object_if(
+                &self.key(LOCK_KEY),
+                Self::lock_content(&lock_info)?.into_bytes(),
+                None,
+                expected.as_ref(),
+            )
+            .await
+        {
+            Ok(_) => {}
+            Err(HalldyllError::State(StateError::Conflict { .. })) => return Err(lost()),
+            Err(e) => return Err(e),
         }
+        debug!("Renewed state lock {lock_id} for {ttl_secs}s");
+
+        Ok(lock_info)
+    }
 
-        Ok(None)
+    async fn get_lock_info(&self) -> Result<Option<LockInfo>> {
+        Ok(self
+            .get_lock_versioned()
+            .await?
+            .map(|(lock_info, _)| lock_info))
     }
 
     async fn is_locked(&self) -> Result<bool> {
@@ -337,7 +601,330 @@ impl StateStore for S3StateStore {
         Ok(false)
     }
 
+    async fn list_versions(&self) -> Result<Vec<StateVersion>> {
+        self.ensure_versioning().await?;
+
+        let key = self.key(STATE_KEY);
+        let mut versions = Vec::new();
+        let mut key_marker: Option<String> = None;
+        let mut version_marker: Option<String> = None;
+
+        loop {
+            let response = self
+                .client
+                .list_object_versions()
+                .bucket(&self.bucket)
+                .prefix(&key)
+                .set_key_marker(key_marker.take())
+                .set_version_id_marker(version_marker.take())
+                .send()
+                .await
+                .map_err(|e| {
+                    HalldyllError::State(StateError::s3(format!("S3 list versions error: {e}")))
+                })?;
+
+            versions.extend(
+                response
+                    .versions()
+                    .iter()
+                    .filter(|v| v.key() == Some(key.as_str()))
+                    .filter_map(|v| {
+                        Some(StateVersion {
+                            version_id: v.version_id()?.to_string(),
+                            last_modified: v.last_modified().and_then(|t| {
+                                DateTime::<Utc>::from_timestamp(t.secs(), t.subsec_nanos())
+                            }),
+                            size: v.size().and_then(|s| u64::try_from(s).ok()).unwrap_or(0),
+                            is_latest: v.is_latest().unwrap_or(false),
+                        })
+                    }),
+            );
+
+            if response.is_truncated() != Some(true) {
+                break;
+            }
+            key_marker = response.next_key_marker().map(String::from);
+            version_marker = response.next_version_id_marker().map(String::from);
+        }
+
+        versions.sort_by_key(|v| std::cmp::Reverse(v.last_modified));
+        Ok(versions)
+    }
+
+    async fn load_version(&self, version_id: &str) -> Result<DeploymentState> {
+        let key = self.key(STATE_KEY);
+        debug!(
+            "Loading state version {version_id} from s3://{}/{key}",
+            self.bucket
+        );
+
+        let response = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(&key)
+            .version_id(version_id)
+            .send()
+            .await
+            .map_err(|e| {
+                HalldyllError::State(StateError::s3(format!(
+                    "S3 get error for version {version_id}: {e}"
+                )))
+            })?;
+
+        let encoding = response.content_encoding().map(String::from);
+        let bytes = response.body.collect().await.map_err(|e| {
+            HalldyllError::State(StateError::s3(format!("Failed to read S3 object: {e}")))
+        })?;
+        let bytes = decompress(bytes.to_vec(), encoding.as_deref())?;
+
+        serde_json::from_slice(&bytes).map_err(|e| {
+            HalldyllError::State(StateError::Corrupted {
+                message: format!("Failed to parse state version {version_id}: {e}"),
+            })
+        })
+    }
+
     fn backend_type(&self) -> &'static str {
         "s3"
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_sdk_s3::config::{BehaviorVersion, Credentials, Region};
+    use wiremock::matchers::{header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn mock_client(endpoint: &str) -> Client {
+        let config = aws_sdk_s3::Config::builder()
+            .behavior_version(BehaviorVersion::latest())
+            .region(Region::new("us-east-1"))
+            .endpoint_url(endpoint)
+            .force_path_style(true)
+            .credentials_provider(Credentials::new("test", "test", None, None, "test"))
+            .build();
+        Client::from_conf(config)
+    }
+
+    #[tokio::test]
+    async fn test_custom_endpoint_config() {
+        let server = MockServer::start().await;
+
+        // Path-style requests to the configured endpoint, as MinIO expects
+        Mock::given(method("GET"))
+            .and(path("/my-bucket/demo/state.json"))
+            .respond_with(ResponseTemplate::new(404).set_body_string(
+                "<Error><Code>NoSuchKey</Code><Message>The specified key does not exist.</Message></Error>",
+            ))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let yaml = format!(
+            "backend: s3\nbucket: my-bucket\nprefix: demo\nendpoint_url: \"{}\"\nforce_path_style: true\n",
+            server.uri()
+        );
+        let state: StateConfig = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(state.endpoint_url.as_deref(), Some(server.uri().as_str()));
+        assert!(state.force_path_style);
+
+        let sdk_config = aws_config::SdkConfig::builder()
+            .behavior_version(BehaviorVersion::latest())
+            .region(Region::new(DEFAULT_REGION))
+            .credentials_provider(aws_sdk_s3::config::SharedCredentialsProvider::new(
+                Credentials::new("test", "test", None, None, "test"),
+            ))
+            .build();
+        let store = S3StateStore::with_client(
+            Client::from_conf(S3StateStore::client_config(&sdk_config, &state)),
+            "my-bucket",
+            state.prefix.as_deref(),
+        );
+        assert!(store.load().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_save_conflict_on_stale_etag() {
+        let server = MockServer::start().await;
+        let state = DeploymentState::new("demo", "dev");
+
+        Mock::given(method("GET"))
+            .and(path("/bucket/demo/state.json"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("ETag", "\"v1\"")
+                    .set_body_string(serde_json::to_string(&state).unwrap()),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/bucket/demo/state.json"))
+            .and(header("If-Match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(412))
+            .mount(&server)
+            .await;
+
+        let store = S3StateStore::with_client(mock_client(&server.uri()), "bucket", Some("demo"));
+        let loaded = store.load().await.unwrap().unwrap();
+
+        let err = store.save(&loaded).await.unwrap_err();
+        assert!(matches!(
+            err,
+            HalldyllError::State(StateError::Conflict { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_save_without_etag_is_unconditional() {
+        let server = MockServer::start().await;
+        let state = DeploymentState::new("demo", "dev");
+
+        // Some S3-compatible stores omit the ETag
+        Mock::given(method("GET"))
+            .and(path("/bucket/demo/state.json"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(serde_json::to_string(&state).unwrap()),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/bucket/demo/state.json"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let store = S3StateStore::with_client(mock_client(&server.uri()), "bucket", Some("demo"));
+        let loaded = store.load().await.unwrap().unwrap();
+        store.save(&loaded).await.unwrap();
+        store.save(&loaded).await.unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        for put in requests.iter().filter(|r| r.method.as_str() == "PUT") {
+            assert!(!put.headers.contains_key("if-match"));
+            assert!(!put.headers.contains_key("if-none-match"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_save_without_load_only_creates() {
+        let server = MockServer::start().await;
+
+        // Another run saved state this store never loaded
+        Mock::given(method("PUT"))
+            .and(path("/bucket/demo/state.json"))
+            .and(header("If-None-Match", "*"))
+            .respond_with(ResponseTemplate::new(412))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let store = S3StateStore::with_client(mock_client(&server.uri()), "bucket", Some("demo"));
+        let err = store
+            .save(&DeploymentState::new("demo", "dev"))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            HalldyllError::State(StateError::Conflict { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_lock_race_lost() {
+        let server = MockServer::start().await;
+
+        // No lock when we look, but another process creates one before our write
+        Mock::given(method("GET"))
+            .and(path("/bucket/demo/state.lock"))
+            .respond_with(ResponseTemplate::new(404).set_body_string(
+                "<Error><Code>NoSuchKey</Code><Message>The specified key does not exist.</Message></Error>",
+            ))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/bucket/demo/state.lock"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(LockInfo::new("ci-job-2")))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/bucket/demo/state.lock"))
+            .and(header("If-None-Match", "*"))
+            .respond_with(ResponseTemplate::new(412))
+            .mount(&server)
+            .await;
+
+        let store = S3StateStore::with_client(mock_client(&server.uri()), "bucket", Some("demo"));
+        let err = store.acquire_lock("ci-job-1").await.unwrap_err();
+        assert!(
+            matches!(err, HalldyllError::State(StateError::LockedByOther { ref holder, .. }) if holder == "ci-job-2")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_release_lock_after_takeover() {
+        let server = MockServer::start().await;
+        let lock = LockInfo::new("ci-job-1");
+
+        // Our lock when we read it, but another job replaces it before the delete
+        Mock::given(method("GET"))
+            .and(path("/bucket/demo/state.lock"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("ETag", "\"v1\"")
+                    .set_body_json(&lock),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/bucket/demo/state.lock"))
+            .and(header("If-Match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(412))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let store = S3StateStore::with_client(mock_client(&server.uri()), "bucket", Some("demo"));
+        store.release_lock(&lock.lock_id).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_list_versions() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/bucket/"))
+            .and(query_param("versioning", ""))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                "<VersioningConfiguration><Status>Enabled</Status></VersioningConfiguration>",
+            ))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/bucket/"))
+            .and(query_param("versions", ""))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                "<ListVersionsResult><IsTruncated>false</IsTruncated>\
+                 <Version><Key>demo/state.json</Key><VersionId>old</VersionId><IsLatest>false</IsLatest>\
+                 <LastModified>2026-01-01T00:00:00.000Z</LastModified><Size>10</Size></Version>\
+                 <Version><Key>demo/state.json.bak</Key><VersionId>other</VersionId><IsLatest>true</IsLatest>\
+                 <LastModified>2026-03-01T00:00:00.000Z</LastModified><Size>5</Size></Version>\
+                 <Version><Key>demo/state.json</Key><VersionId>new</VersionId><IsLatest>true</IsLatest>\
+                 <LastModified>2026-02-01T00:00:00.000Z</LastModified><Size>12</Size></Version>\
+                 </ListVersionsResult>",
+            ))
+            .mount(&server)
+            .await;
+
+        let store = S3StateStore::with_client(mock_client(&server.uri()), "bucket", Some("demo"));
+        let versions = store.list_versions().await.unwrap();
+
+        let ids: Vec<&str> = versions.iter().map(|v| v.version_id.as_str()).collect();
+        assert_eq!(ids, ["new", "old"]);
+        assert!(versions[0].is_latest);
+        assert_eq!(versions[0].size, 12);
+    }
+}
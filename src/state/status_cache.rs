@@ -0,0 +1,122 @@
+//! Cache of the last observed project status.
+//!
+//! Commands that list the project's pods record what they saw, so
+//! `halldyll status --cached` can render it instantly without reaching `RunPod`.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use tracing::debug;
+
+use crate::error::{Result, StateError};
+use crate::runpod::ProjectStatus;
+
+/// Status cache file name.
+const STATUS_FILE: &str = "last_status.json";
+
+/// A project status together with when it was observed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedStatus {
+    /// When the pods were observed.
+    pub observed_at: DateTime<Utc>,
+    /// Observed project status.
+    pub status: ProjectStatus,
+}
+
+/// File holding the last observed project status.
+#[derive(Debug, Clone)]
+pub struct StatusCache {
+    /// Path to the cache file.
+    path: PathBuf,
+}
+
+impl StatusCache {
+    /// Creates a status cache stored in a directory (usually `.halldyll`).
+    #[must_use]
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self {
+            path: dir.as_ref().join(STATUS_FILE),
+        }
+    }
+
+    /// Returns the cache file path.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Records a freshly observed status.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub async fn write(&self, status: &ProjectStatus) -> Result<()> {
+        let cached = CachedStatus {
+            observed_at: Utc::now(),
+            status: status.clone(),
+        };
+        let json = serde_json::to_string_pretty(&cached)
+            .map_err(|e| StateError::serialization(e.to_string()))?;
+
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .await
+                .map_err(|e| StateError::io(dir, e))?;
+        }
+
+        // Write to a temporary file first so readers never see partial JSON
+        let temp_path = self.path.with_extension("json.tmp");
+        fs::write(&temp_path, json)
+            .await
+            .map_err(|e| StateError::io(&temp_path, e))?;
+        fs::rename(&temp_path, &self.path)
+            .await
+            .map_err(|e| StateError::io(&self.path, e))?;
+
+        debug!("Cached project status at {}", self.path.display());
+        Ok(())
+    }
+
+    /// Reads the cached status, if any has been recorded.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read or parsed.
+    pub async fn read(&self) -> Result<Option<CachedStatus>> {
+        let content = match fs::read_to_string(&self.path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(StateError::io(&self.path, e).into()),
+        };
+
+        serde_json::from_str(&content)
+            .map(Some)
+            .map_err(|e| StateError::serialization(format!("{}: {e}", self.path.display())).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_status_cache_round_trip() {
+        let temp = TempDir::new().expect("Failed to create temp dir");
+        let cache = StatusCache::new(temp.path().join(".halldyll"));
+        assert!(cache.read().await.expect("Read should not fail").is_none());
+
+        let status = ProjectStatus::from_pods("proj", "dev", Vec::new());
+        cache.write(&status).await.expect("Write should succeed");
+
+        let cached = cache
+            .read()
+            .await
+            .expect("Read should succeed")
+            .expect("Cache should exist");
+        assert_eq!(cached.status.project, "proj");
+        assert_eq!(cached.status.total_pods, 0);
+        assert!(cached.observed_at <= Utc::now());
+    }
+}
@@ -4,9 +4,9 @@
 
 use async_trait::async_trait;
 
-use crate::error::Result;
-use super::types::DeploymentState;
 use super::lock::LockInfo;
+use super::types::{DeploymentState, StateVersion};
+use crate::error::{Result, StateError};
 
 /// Trait for state storage backends.
 #[async_trait]
@@ -33,6 +33,12 @@ pub trait StateStore: Send + Sync {
     /// Releases a lock on the state.
     async fn release_lock(&self, lock_id: &str) -> Result<()>;
 
+    /// Extends a lock this process holds to expire `ttl_secs` from now.
+    ///
+    /// Fails with [`StateError::LockLost`] if the lock was released or taken
+    /// over by another holder.
+    async fn renew_lock(&self, lock_id: &str, ttl_secs: i64) -> Result<LockInfo>;
+
     /// Gets current lock information if locked.
     async fn get_lock_info(&self) -> Result<Option<LockInfo>>;
 
@@ -41,6 +47,34 @@ pub trait StateStore: Send + Sync {
 
     /// Gets the backend type name.
     fn backend_type(&self) -> &'static str;
+
+    /// Lists stored versions of the state, newest first.
+    ///
+    /// Only backends that keep object versions support this.
+    async fn list_versions(&self) -> Result<Vec<StateVersion>> {
+        Err(StateError::Unsupported {
+            operation: String::from("Listing state versions"),
+            backend: self.backend_type().to_string(),
+        }
+        .into())
+    }
+
+    /// Loads a specific stored version of the state.
+    async fn load_version(&self, version_id: &str) -> Result<DeploymentState> {
+        Err(StateError::Unsupported {
+            operation: format!("Loading state version {version_id}"),
+            backend: self.backend_type().to_string(),
+        }
+        .into())
+    }
+
+    /// Keeps a restorable copy of the current state before it is changed.
+    ///
+    /// Returns the version ID of the copy, or `None` if nothing was recorded.
+    /// Backends that version every write (S3 with versioning) need no copy.
+    async fn snapshot(&self) -> Result<Option<String>> {
+        Ok(None)
+    }
 }
 
 #[async_trait]
@@ -69,6 +103,10 @@ impl StateStore for Box<dyn StateStore> {
         (**self).release_lock(lock_id).await
     }
 
+    async fn renew_lock(&self, lock_id: &str, ttl_secs: i64) -> Result<LockInfo> {
+        (**self).renew_lock(lock_id, ttl_secs).await
+    }
+
     async fn get_lock_info(&self) -> Result<Option<LockInfo>> {
         (**self).get_lock_info().await
     }
@@ -80,4 +118,16 @@ impl StateStore for Box<dyn StateStore> {
     fn backend_type(&self) -> &'static str {
         (**self).backend_type()
     }
+
+    async fn list_versions(&self) -> Result<Vec<StateVersion>> {
+        (**self).list_versions().await
+    }
+
+    async fn load_version(&self, version_id: &str) -> Result<DeploymentState> {
+        (**self).load_version(version_id).await
+    }
+
+    async fn snapshot(&self) -> Result<Option<String>> {
+        (**self).snapshot().await
+    }
 }
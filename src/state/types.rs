@@ -5,11 +5,31 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// Current version of the state format.
 pub const STATE_VERSION: &str = "1.0";
 
+/// Fields of a [`PodState`], as selected by `halldyll state show --fields`.
+pub const POD_STATE_FIELDS: &[&str] = &[
+    "name",
+    "runpod_id",
+    "config_hash",
+    "status",
+    "gpu_type",
+    "gpu_count",
+    "image",
+    "endpoints",
+    "created_at",
+    "updated_at",
+    "tags",
+    "ssh_keys",
+    "imported",
+    "color",
+    "region",
+    "stop_reason",
+];
+
 /// The complete deployment state.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeploymentState {
@@ -30,6 +50,288 @@ pub struct DeploymentState {
     /// Deployment history (recent entries).
     #[serde(default)]
     pub history: Vec<DeploymentHistoryEntry>,
+    /// Replica counts chosen at runtime (e.g. by the autoscaler), by pod name.
+    #[serde(default)]
+    pub replicas: HashMap<String, ReplicaState>,
+    /// How long plan actions took, by action type and by `action/pod`.
+    #[serde(default)]
+    pub timings: HashMap<String, ActionTiming>,
+    /// When each stopped pod was first seen stopped, by `RunPod` pod ID.
+    #[serde(default)]
+    pub stopped_since: HashMap<String, DateTime<Utc>>,
+    /// Pods under maintenance, by pod name (a replicated pod covers its replicas).
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub maintenance: HashMap<String, MaintenanceState>,
+    /// Which side of an active/passive deployment serves traffic.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failover: Option<FailoverState>,
+    /// Operator annotations (`halldyll annotate`), by pod name.
+    ///
+    /// Kept by name rather than on [`PodState`] so they survive the pod
+    /// being replaced.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub annotations: HashMap<String, BTreeMap<String, String>>,
+    /// Blue/green release waiting for `halldyll promote` or `halldyll rollback`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub release: Option<ReleaseState>,
+    /// Alert conditions `halldyll watch` currently sees, by alert key.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub alerts: HashMap<String, AlertState>,
+    /// Serverless endpoints, by endpoint name.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub endpoints: HashMap<String, EndpointState>,
+    /// Templates saved from the `templates:` section, by template name.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub templates: HashMap<String, TemplateState>,
+}
+
+/// State of a template saved from the configuration.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TemplateState {
+    /// Template name in the configuration.
+    pub name: String,
+    /// `RunPod` template ID.
+    pub runpod_id: String,
+    /// Hash of the template configuration last applied.
+    pub config_hash: String,
+    /// When the template was created.
+    pub created_at: DateTime<Utc>,
+    /// When the template was last updated.
+    pub updated_at: DateTime<Utc>,
+}
+
+/// State of a serverless endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EndpointState {
+    /// Endpoint name in the configuration.
+    pub name: String,
+    /// `RunPod` endpoint ID.
+    pub runpod_id: String,
+    /// Hash of the endpoint configuration last applied.
+    pub config_hash: String,
+    /// When the endpoint was created.
+    pub created_at: DateTime<Utc>,
+    /// When the endpoint was last updated.
+    pub updated_at: DateTime<Utc>,
+}
+
+/// An alert condition that holds, and when it was last sent.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AlertState {
+    /// When the condition was first seen.
+    pub since: DateTime<Utc>,
+    /// When the alert was last sent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notified_at: Option<DateTime<Utc>>,
+}
+
+/// Color of a blue/green release.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReleaseColor {
+    /// Blue release.
+    Blue,
+    /// Green release.
+    Green,
+}
+
+impl ReleaseColor {
+    /// Returns the other color.
+    #[must_use]
+    pub const fn other(self) -> Self {
+        match self {
+            Self::Blue => Self::Green,
+            Self::Green => Self::Blue,
+        }
+    }
+}
+
+impl std::fmt::Display for ReleaseColor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Blue => write!(f, "blue"),
+            Self::Green => write!(f, "green"),
+        }
+    }
+}
+
+/// Why halldyll stopped a pod.
+///
+/// Pods stopped on purpose are converged; a configured pod found stopped
+/// without a reason was stopped outside halldyll and is resumed.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StopReason {
+    /// Warm pool clone kept stopped for fast scale-ups.
+    WarmPool,
+    /// Put to sleep by `watch` after the pod's idle timeout.
+    Idle,
+    /// Stopped because the pod is disabled (`enabled: false`).
+    Disabled,
+}
+
+impl std::fmt::Display for StopReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::WarmPool => write!(f, "warm pool"),
+            Self::Idle => write!(f, "idle"),
+            Self::Disabled => write!(f, "disabled"),
+        }
+    }
+}
+
+/// Pods of a blue/green release created alongside the live ones.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseState {
+    /// Color the release pods are tagged with.
+    pub color: ReleaseColor,
+    /// Hash of the configuration the release was created from.
+    pub config_hash: String,
+    /// When the release was started.
+    pub created_at: DateTime<Utc>,
+    /// Release pods, by pod name.
+    #[serde(default)]
+    pub pods: HashMap<String, PodState>,
+}
+
+/// Why and by whom a pod was put under maintenance.
+///
+/// Automated changes (reconcile, watch) leave such pods alone and drift
+/// checks do not report them.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MaintenanceState {
+    /// Why the pod is under maintenance.
+    pub reason: String,
+    /// Who put it under maintenance.
+    pub owner: String,
+    /// When maintenance started.
+    pub since: DateTime<Utc>,
+}
+
+impl MaintenanceState {
+    /// Starts maintenance now.
+    #[must_use]
+    pub fn new(reason: &str, owner: &str) -> Self {
+        Self {
+            reason: reason.to_string(),
+            owner: owner.to_string(),
+            since: Utc::now(),
+        }
+    }
+}
+
+impl std::fmt::Display for MaintenanceState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({}, since {})",
+            self.reason,
+            self.owner,
+            self.since.format("%Y-%m-%d %H:%M UTC")
+        )
+    }
+}
+
+/// Side of an active/passive deployment.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FailoverSide {
+    /// The pods in the configured region.
+    #[default]
+    Primary,
+    /// The standby pods in the failover data centers.
+    Secondary,
+}
+
+impl std::fmt::Display for FailoverSide {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Primary => write!(f, "primary"),
+            Self::Secondary => write!(f, "secondary"),
+        }
+    }
+}
+
+/// Failover status of a deployment.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FailoverState {
+    /// Active side.
+    pub active: FailoverSide,
+    /// When the active side last changed.
+    #[serde(default)]
+    pub changed_at: Option<DateTime<Utc>>,
+    /// Standby pods, by pod name.
+    #[serde(default)]
+    pub standby_pods: HashMap<String, StandbyPod>,
+}
+
+/// A standby pod provisioned by `failover activate`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StandbyPod {
+    /// `RunPod` pod ID.
+    pub runpod_id: String,
+    /// Data center the pod was placed in.
+    pub data_center: String,
+    /// Hash of the pod configuration when deployed.
+    pub config_hash: String,
+}
+
+/// Running average of how long an action took.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub struct ActionTiming {
+    /// Number of recorded runs.
+    pub samples: u32,
+    /// Mean duration in seconds.
+    pub mean_secs: f64,
+    /// Duration of the most recent run in seconds.
+    pub last_secs: f64,
+    /// Shortest recorded run in seconds.
+    #[serde(default)]
+    pub min_secs: f64,
+    /// Longest recorded run in seconds.
+    #[serde(default)]
+    pub max_secs: f64,
+}
+
+impl ActionTiming {
+    /// Adds one run to the aggregates.
+    pub fn record(&mut self, secs: f64) {
+        // Timings recorded before min/max were tracked start from their mean
+        if self.max_secs <= 0.0 && self.samples > 0 {
+            self.min_secs = self.mean_secs;
+            self.max_secs = self.mean_secs;
+        }
+        self.min_secs = if self.samples == 0 {
+            secs
+        } else {
+            self.min_secs.min(secs)
+        };
+        self.max_secs = self.max_secs.max(secs);
+        self.samples = self.samples.saturating_add(1);
+        self.mean_secs += (secs - self.mean_secs) / f64::from(self.samples);
+        self.last_secs = secs;
+    }
+}
+
+/// Runtime replica count for a replicated pod.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicaState {
+    /// Desired number of replicas.
+    pub desired: u32,
+    /// When the replica count last changed.
+    #[serde(default)]
+    pub last_scaled_at: Option<DateTime<Utc>>,
+    /// When the pod was first seen without in-flight requests.
+    #[serde(default)]
+    pub idle_since: Option<DateTime<Utc>>,
+    /// Whether all replicas were stopped for being idle.
+    #[serde(default)]
+    pub sleeping: bool,
+    /// Configured `replicas` when `halldyll scale` set `desired`.
+    ///
+    /// Changing `replicas` in the configuration discards the manual count.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub configured: Option<u32>,
 }
 
 /// State of a single pod.
@@ -57,6 +359,26 @@ pub struct PodState {
     pub updated_at: DateTime<Utc>,
     /// Tags applied to the pod.
     pub tags: HashMap<String, String>,
+    /// Fingerprint of the SSH public keys installed on the pod.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssh_keys: Option<String>,
+    /// Whether the pod was created outside halldyll and adopted with `halldyll import`.
+    ///
+    /// Imported pods carry no halldyll tags, so they are matched by ID.
+    #[serde(default)]
+    pub imported: bool,
+    /// Release color of a pod promoted from a blue/green release.
+    ///
+    /// Such pods are tagged with their release environment, so they are
+    /// matched by ID like imported pods.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<ReleaseColor>,
+    /// Data center the pod was created in, when one was requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    /// Why halldyll stopped the pod, while it is stopped.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_reason: Option<StopReason>,
 }
 
 /// State of a persistent volume.
@@ -72,6 +394,30 @@ pub struct VolumeState {
     pub size_gb: u32,
     /// When the volume was created.
     pub created_at: DateTime<Utc>,
+    /// Local name of the pod instance mounting the volume.
+    #[serde(default)]
+    pub pod: String,
+    /// Whether no pod mounts the volume (it is re-attached when the pod is recreated).
+    #[serde(default)]
+    pub detached: bool,
+    /// Data center the volume lives in (if known).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_center: Option<String>,
+    /// Whether halldyll created the volume from a volume's `data_center`.
+    #[serde(default)]
+    pub managed: bool,
+}
+
+impl VolumeState {
+    /// Returns the key of the volume in state: `<pod>/<name>`, or the name without a pod.
+    #[must_use]
+    pub fn key(&self) -> String {
+        if self.pod.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{}/{}", self.pod, self.name)
+        }
+    }
 }
 
 /// Deployment status.
@@ -110,6 +456,22 @@ pub struct DeploymentHistoryEntry {
     /// Optional error message.
     #[serde(default)]
     pub error: Option<String>,
+    /// Optional human-readable details (e.g. scaling reason).
+    #[serde(default)]
+    pub details: Option<String>,
+}
+
+/// A stored version of the state object, as kept by a versioning backend.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateVersion {
+    /// Backend-specific version identifier.
+    pub version_id: String,
+    /// When this version was written.
+    pub last_modified: Option<DateTime<Utc>>,
+    /// Size of the stored state in bytes.
+    pub size: u64,
+    /// Whether this is the current version.
+    pub is_latest: bool,
 }
 
 /// Types of deployment operations.
@@ -141,6 +503,16 @@ impl DeploymentState {
             volumes: HashMap::new(),
             last_updated: Utc::now(),
             history: Vec::new(),
+            replicas: HashMap::new(),
+            timings: HashMap::new(),
+            stopped_since: HashMap::new(),
+            maintenance: HashMap::new(),
+            failover: None,
+            annotations: HashMap::new(),
+            release: None,
+            alerts: HashMap::new(),
+            endpoints: HashMap::new(),
+            templates: HashMap::new(),
         }
     }
 
@@ -170,15 +542,66 @@ impl DeploymentState {
         result
     }
 
-    /// Gets a volume by name.
+    /// Adds or updates a serverless endpoint.
+    pub fn set_endpoint(&mut self, endpoint: EndpointState) {
+        self.endpoints.insert(endpoint.name.clone(), endpoint);
+        self.last_updated = Utc::now();
+    }
+
+    /// Removes a serverless endpoint by name.
+    pub fn remove_endpoint(&mut self, name: &str) -> Option<EndpointState> {
+        let result = self.endpoints.remove(name);
+        if result.is_some() {
+            self.last_updated = Utc::now();
+        }
+        result
+    }
+
+    /// Adds or updates a template.
+    pub fn set_template(&mut self, template: TemplateState) {
+        self.templates.insert(template.name.clone(), template);
+        self.last_updated = Utc::now();
+    }
+
+    /// Removes a template by name.
+    pub fn remove_template(&mut self, name: &str) -> Option<TemplateState> {
+        let result = self.templates.remove(name);
+        if result.is_some() {
+            self.last_updated = Utc::now();
+        }
+        result
+    }
+
+    /// Gets a volume by its key (see [`VolumeState::key`]).
+    #[must_use]
+    pub fn get_volume(&self, key: &str) -> Option<&VolumeState> {
+        self.volumes.get(key)
+    }
+
+    /// Gets the volume `name` of pod instance `pod`.
     #[must_use]
-    pub fn get_volume(&self, name: &str) -> Option<&VolumeState> {
-        self.volumes.get(name)
+    pub fn volume_for(&self, pod: &str, name: &str) -> Option<&VolumeState> {
+        self.volumes
+            .values()
+            .find(|v| v.pod == pod && v.name == name)
     }
 
-    /// Adds or updates a volume.
+    /// Adds or updates a volume, replacing the pod's volume of the same name.
+    ///
+    /// Volumes are keyed by pod and name, so replicas can each have their own;
+    /// entries keyed by name alone (from older states) are replaced too.
     pub fn set_volume(&mut self, volume: VolumeState) {
-        self.volumes.insert(volume.name.clone(), volume);
+        self.volumes
+            .retain(|_, v| v.pod != volume.pod || v.name != volume.name);
+        self.volumes.insert(volume.key(), volume);
+        self.last_updated = Utc::now();
+    }
+
+    /// Marks the volumes mounted by a pod as detached.
+    pub fn detach_volumes(&mut self, pod: &str) {
+        for volume in self.volumes.values_mut().filter(|v| v.pod == pod) {
+            volume.detached = true;
+        }
         self.last_updated = Utc::now();
     }
 
@@ -192,6 +615,162 @@ impl DeploymentState {
         self.history.push(entry);
     }
 
+    /// Records how long an action took, overall and for the specific pod.
+    pub fn record_timing(&mut self, action: &str, pod: &str, secs: f64) {
+        self.timings
+            .entry(action.to_string())
+            .or_default()
+            .record(secs);
+        self.timings
+            .entry(format!("{action}/{pod}"))
+            .or_default()
+            .record(secs);
+    }
+
+    /// Returns the recorded timing of an action, preferring the pod's own history.
+    #[must_use]
+    pub fn timing(&self, action: &str, pod: &str) -> Option<&ActionTiming> {
+        self.timings
+            .get(&format!("{action}/{pod}"))
+            .or_else(|| self.timings.get(action))
+    }
+
+    /// Returns the maintenance covering a pod or replica instance, if any.
+    ///
+    /// Maintenance set on a replicated pod covers every `<pod>-<index>` replica.
+    #[must_use]
+    pub fn maintenance_for(&self, name: &str) -> Option<&MaintenanceState> {
+        self.maintenance.get(name).or_else(|| {
+            let (pod, index) = name.rsplit_once('-')?;
+            index
+                .parse::<u32>()
+                .ok()
+                .and_then(|_| self.maintenance.get(pod))
+        })
+    }
+
+    /// Puts a pod under maintenance.
+    pub fn set_maintenance(&mut self, pod_name: &str, maintenance: MaintenanceState) {
+        self.maintenance.insert(pod_name.to_string(), maintenance);
+        self.last_updated = Utc::now();
+    }
+
+    /// Ends a pod's maintenance, returning it if there was one.
+    pub fn clear_maintenance(&mut self, pod_name: &str) -> Option<MaintenanceState> {
+        let result = self.maintenance.remove(pod_name);
+        if result.is_some() {
+            self.last_updated = Utc::now();
+        }
+        result
+    }
+
+    /// Sets an annotation on a pod.
+    pub fn annotate(&mut self, pod_name: &str, key: &str, value: &str) {
+        self.annotations
+            .entry(pod_name.to_string())
+            .or_default()
+            .insert(key.to_string(), value.to_string());
+        self.last_updated = Utc::now();
+    }
+
+    /// Removes an annotation from a pod, returning its value if there was one.
+    pub fn remove_annotation(&mut self, pod_name: &str, key: &str) -> Option<String> {
+        let annotations = self.annotations.get_mut(pod_name)?;
+        let result = annotations.remove(key);
+        if annotations.is_empty() {
+            self.annotations.remove(pod_name);
+        }
+        if result.is_some() {
+            self.last_updated = Utc::now();
+        }
+        result
+    }
+
+    /// Returns the side of an active/passive deployment that is active.
+    #[must_use]
+    pub fn active_side(&self) -> FailoverSide {
+        self.failover.as_ref().map(|f| f.active).unwrap_or_default()
+    }
+
+    /// Returns the color of the live pods (untagged pods count as blue).
+    #[must_use]
+    pub fn live_color(&self) -> ReleaseColor {
+        self.pods
+            .values()
+            .find_map(|p| p.color)
+            .unwrap_or(ReleaseColor::Blue)
+    }
+
+    /// Records which side is active.
+    pub fn set_active_side(&mut self, side: FailoverSide) {
+        let failover = self.failover.get_or_insert_with(FailoverState::default);
+        if failover.active != side {
+            failover.active = side;
+            failover.changed_at = Some(Utc::now());
+        }
+        self.last_updated = Utc::now();
+    }
+
+    /// Returns the runtime replica count for a pod, if one was recorded.
+    #[must_use]
+    pub fn desired_replicas(&self, pod_name: &str) -> Option<u32> {
+        self.replicas.get(pod_name).map(|r| r.desired)
+    }
+
+    /// Returns when a pod's replica count last changed.
+    #[must_use]
+    pub fn last_scaled_at(&self, pod_name: &str) -> Option<DateTime<Utc>> {
+        self.replicas.get(pod_name).and_then(|r| r.last_scaled_at)
+    }
+
+    /// Records a new runtime replica count for a pod.
+    pub fn set_desired_replicas(&mut self, pod_name: &str, desired: u32) {
+        let now = Utc::now();
+        let replica = self.replica_state_mut(pod_name, desired);
+        replica.desired = desired;
+        replica.last_scaled_at = Some(now);
+        self.last_updated = now;
+    }
+
+    /// Records a replica count set by hand for a pod configured with `configured` replicas.
+    pub fn scale_replicas(&mut self, pod_name: &str, desired: u32, configured: u32) {
+        self.set_desired_replicas(pod_name, desired);
+        if let Some(replica) = self.replicas.get_mut(pod_name) {
+            replica.configured = Some(configured);
+        }
+    }
+
+    /// Records which pods are currently stopped.
+    ///
+    /// Pods keep the time they were first seen stopped; pods no longer in
+    /// `stopped` (resumed or terminated) are forgotten.
+    pub fn track_stopped(&mut self, stopped: &[&str], now: DateTime<Utc>) {
+        self.stopped_since
+            .retain(|id, _| stopped.contains(&id.as_str()));
+        for id in stopped {
+            self.stopped_since.entry((*id).to_string()).or_insert(now);
+        }
+    }
+
+    /// Returns the replica state for a pod, creating it with `desired` if missing.
+    pub fn replica_state_mut(&mut self, pod_name: &str, desired: u32) -> &mut ReplicaState {
+        self.replicas
+            .entry(pod_name.to_string())
+            .or_insert_with(|| ReplicaState {
+                desired,
+                last_scaled_at: None,
+                idle_since: None,
+                sleeping: false,
+                configured: None,
+            })
+    }
+
+    /// Returns true if a pod's replicas were stopped for being idle.
+    #[must_use]
+    pub fn is_sleeping(&self, pod_name: &str) -> bool {
+        self.replicas.get(pod_name).is_some_and(|r| r.sleeping)
+    }
+
     /// Returns all running pods.
     #[must_use]
     pub fn running_pods(&self) -> Vec<&PodState> {
@@ -206,6 +785,26 @@ impl DeploymentState {
     pub fn pod_names(&self) -> Vec<&str> {
         self.pods.keys().map(String::as_str).collect()
     }
+
+    /// Returns the pods named `name` or its `<name>-<index>` replicas (all pods
+    /// if `None`), sorted by name.
+    #[must_use]
+    pub fn select_pods(&self, name: Option<&str>) -> Vec<&PodState> {
+        let mut pods: Vec<&PodState> = self
+            .pods
+            .values()
+            .filter(|p| {
+                name.is_none_or(|name| {
+                    p.name == name
+                        || p.name.rsplit_once('-').is_some_and(|(base, index)| {
+                            base == name && index.parse::<u32>().is_ok()
+                        })
+                })
+            })
+            .collect();
+        pods.sort_by(|a, b| a.name.cmp(&b.name));
+        pods
+    }
 }
 
 impl PodState {
@@ -225,15 +824,46 @@ impl PodState {
             created_at: now,
             updated_at: now,
             tags: HashMap::new(),
+            ssh_keys: None,
+            imported: false,
+            color: None,
+            region: None,
+            stop_reason: None,
         }
     }
 
+    /// Returns the given [`POD_STATE_FIELDS`] (all of them if empty) as JSON.
+    ///
+    /// Unset optional fields are `null`.
+    #[must_use]
+    pub fn fields(&self, fields: &[String]) -> serde_json::Map<String, serde_json::Value> {
+        let serde_json::Value::Object(all) = serde_json::to_value(self).unwrap_or_default() else {
+            return serde_json::Map::new();
+        };
+        if fields.is_empty() {
+            return all;
+        }
+        fields
+            .iter()
+            .map(|field| (field.clone(), all.get(field).cloned().unwrap_or_default()))
+            .collect()
+    }
+
     /// Updates the status.
     pub fn set_status(&mut self, status: DeploymentStatus) {
+        if status != DeploymentStatus::Stopped {
+            self.stop_reason = None;
+        }
         self.status = status;
         self.updated_at = Utc::now();
     }
 
+    /// Marks the pod as stopped by halldyll for `reason`.
+    pub fn stop(&mut self, reason: StopReason) {
+        self.set_status(DeploymentStatus::Stopped);
+        self.stop_reason = Some(reason);
+    }
+
     /// Adds an endpoint mapping.
     pub fn add_endpoint(&mut self, port: u16, url: String) {
         self.endpoints.insert(port, url);
@@ -258,9 +888,17 @@ impl DeploymentHistoryEntry {
             resources,
             success: true,
             error: None,
+            details: None,
         }
     }
 
+    /// Attaches human-readable details to the entry.
+    #[must_use]
+    pub fn with_details(mut self, details: impl Into<String>) -> Self {
+        self.details = Some(details.into());
+        self
+    }
+
     /// Creates a failed history entry.
     #[must_use]
     pub fn failed(
@@ -276,6 +914,7 @@ impl DeploymentHistoryEntry {
             resources,
             success: false,
             error: Some(error.to_string()),
+            details: None,
         }
     }
 }
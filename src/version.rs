@@ -0,0 +1,135 @@
+//! Version information and update checks.
+//!
+//! Reports the crate version, the git commit it was built from and the state
+//! format it reads and writes. The opt-in update check asks crates.io for the
+//! latest published release.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::error::{HalldyllError, Result};
+use crate::state::STATE_VERSION;
+
+/// Crate version.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Short git commit the binary was built from (`unknown` outside a git checkout).
+pub const GIT_SHA: &str = env!("HALLDYLL_GIT_SHA");
+
+/// `User-Agent` sent with outgoing HTTP requests.
+pub const USER_AGENT: &str = concat!("halldyll/", env!("CARGO_PKG_VERSION"));
+
+/// crates.io API endpoint for this crate.
+const CRATES_IO_URL: &str = "https://crates.io/api/v1/crates/halldyll_deploy_pods";
+
+/// Timeout for the update check in seconds.
+const CHECK_TIMEOUT_SECS: u64 = 5;
+
+/// Version details reported by `halldyll version`.
+#[derive(Debug, Clone, Serialize)]
+pub struct VersionInfo {
+    /// Crate version.
+    pub version: String,
+    /// Git commit the binary was built from.
+    pub git_sha: String,
+    /// State format version read and written.
+    pub state_version: String,
+    /// Latest published version (only when checked).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latest: Option<String>,
+}
+
+/// Subset of the crates.io crate response.
+#[derive(Debug, Deserialize)]
+struct CratesIoResponse {
+    #[serde(rename = "crate")]
+    krate: CratesIoCrate,
+}
+
+/// Crate metadata from crates.io.
+#[derive(Debug, Deserialize)]
+struct CratesIoCrate {
+    max_stable_version: Option<String>,
+    max_version: String,
+}
+
+impl VersionInfo {
+    /// Returns the version details of this build.
+    #[must_use]
+    pub fn current() -> Self {
+        Self {
+            version: VERSION.to_string(),
+            git_sha: GIT_SHA.to_string(),
+            state_version: STATE_VERSION.to_string(),
+            latest: None,
+        }
+    }
+
+    /// Returns the latest version if it is newer than this build.
+    #[must_use]
+    pub fn update_available(&self) -> Option<&str> {
+        self.latest
+            .as_deref()
+            .filter(|latest| is_newer(latest, &self.version))
+    }
+}
+
+/// Fetches the latest stable version published on crates.io.
+///
+/// # Errors
+///
+/// Returns an error if crates.io cannot be reached or answers unexpectedly.
+pub async fn latest_release() -> Result<String> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(CHECK_TIMEOUT_SECS))
+        .user_agent(USER_AGENT)
+        .build()
+        .map_err(|e| HalldyllError::internal(format!("Failed to create HTTP client: {e}")))?;
+
+    let response: CratesIoResponse = client
+        .get(CRATES_IO_URL)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(|e| HalldyllError::internal(format!("Update check failed: {e}")))?
+        .json()
+        .await
+        .map_err(|e| HalldyllError::internal(format!("Unexpected crates.io response: {e}")))?;
+
+    Ok(response
+        .krate
+        .max_stable_version
+        .unwrap_or(response.krate.max_version))
+}
+
+/// Returns true if `candidate` is a newer `major.minor.patch` version than `current`.
+///
+/// Pre-release and build suffixes are ignored.
+#[must_use]
+pub fn is_newer(candidate: &str, current: &str) -> bool {
+    let parse = |version: &str| -> Vec<u64> {
+        version
+            .split(['-', '+'])
+            .next()
+            .unwrap_or_default()
+            .split('.')
+            .map(|part| part.parse().unwrap_or(0))
+            .collect()
+    };
+    parse(candidate) > parse(current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_newer() {
+        assert!(is_newer("0.2.0", "0.1.9"));
+        assert!(is_newer("1.0.0", "0.10.0"));
+        assert!(is_newer("0.1.10", "0.1.9"));
+        assert!(!is_newer("0.1.0", "0.1.0"));
+        assert!(!is_newer("0.1.0-beta.1", "0.1.0"));
+        assert!(!is_newer("0.0.9", "0.1.0"));
+    }
+}
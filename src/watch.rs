@@ -0,0 +1,1147 @@
+//! Watch mode for continuous operation.
+//!
+//! This module implements the long-running loop behind `halldyll watch`:
+//! on every tick it observes the project's pods, lets the autoscaler adjust
+//! replica counts from engine metrics, and then reconciles the deployment
+//! so the new replica counts (and any drift) are applied.
+//!
+//! Pods with `autoscale.idle_timeout_secs` are put to sleep (all replicas
+//! stopped) once they have had no in-flight requests for that long, and are
+//! resumed by [`Watcher::wake`].
+//!
+//! With `guardrails.stopped_retention_hours`, pods left stopped (and still
+//! billing storage) for longer are reported or terminated.
+//!
+//! Pods under maintenance (`halldyll maintenance on`) are neither scaled,
+//! expired nor reconciled.
+//!
+//! With a health history, every tick also health checks the pods that should
+//! be running and records the outcomes for `halldyll slo`; pods under
+//! maintenance or asleep are not sampled.
+//!
+//! With an `alerts:` section, every tick ends by checking the alert
+//! thresholds; alerts that are due are printed and posted to the webhooks.
+
+use chrono::Utc;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use tracing::{debug, info, warn};
+
+use crate::config::{ConfigHasher, DeployConfig, PodConfig, StoppedRetentionAction};
+use crate::error::{HalldyllError, Result};
+use crate::loadbalancer::ManifestGenerator;
+use crate::notify::WebhookNotifier;
+use crate::planner::{
+    Alert, AlertInputs, Autoscaler, CostReport, DiffEngine, GpuPrices, ReplicaLoad, ScaleDecision,
+    evaluate_alerts,
+};
+use crate::reconciler::{Reconciler, ReconciliationResult};
+use crate::runpod::{
+    HealthChecker, MetricsScraper, ObservedPod, PodObserver, PodProvisioner, PodStatus,
+};
+use crate::state::{
+    DeploymentHistoryEntry, DeploymentOperation, DeploymentState, DeploymentStatus, HealthHistory,
+    HealthSample, StateStore, StopReason,
+};
+
+/// Watcher driving autoscaling and reconciliation.
+pub struct Watcher<'a, S: StateStore> {
+    /// Configuration.
+    config: &'a DeployConfig,
+    /// State store.
+    state_store: &'a S,
+    /// Pod provisioner.
+    provisioner: &'a PodProvisioner,
+    /// Pod observer.
+    observer: &'a PodObserver,
+    /// Engine metrics scraper.
+    scraper: MetricsScraper,
+    /// Autoscaler.
+    autoscaler: Autoscaler,
+    /// Health checker for the health history.
+    checker: HealthChecker,
+    /// Where health check outcomes are recorded, if anywhere.
+    health_history: Option<HealthHistory>,
+    /// Whether to reconcile after each tick.
+    reconcile: bool,
+    /// Where alerts are posted, if any webhooks are configured.
+    notifier: Option<WebhookNotifier>,
+}
+
+/// Outcome of a single watch tick.
+#[derive(Debug, Default)]
+pub struct WatchTick {
+    /// Scaling decisions applied during this tick.
+    pub scale_events: Vec<ScaleDecision>,
+    /// Reconciliation result (if reconciliation ran).
+    pub reconciliation: Option<ReconciliationResult>,
+    /// Load balancer manifests rewritten during this tick.
+    pub manifests: Vec<PathBuf>,
+    /// Pods found stopped past `guardrails.stopped_retention_hours`.
+    pub expired_stopped: Vec<String>,
+    /// Pods that failed their health check (with a health history only).
+    pub unhealthy: Vec<String>,
+    /// Alerts raised during this tick.
+    pub alerts: Vec<Alert>,
+}
+
+impl<'a, S: StateStore> Watcher<'a, S> {
+    /// Creates a new watcher.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the metrics, health check or webhook HTTP client cannot be created.
+    pub fn new(
+        config: &'a DeployConfig,
+        state_store: &'a S,
+        provisioner: &'a PodProvisioner,
+        observer: &'a PodObserver,
+    ) -> Result<Self> {
+        Ok(Self {
+            config,
+            state_store,
+            provisioner,
+            observer,
+            scraper: MetricsScraper::new()?,
+            autoscaler: Autoscaler::new(),
+            checker: HealthChecker::new()?,
+            health_history: None,
+            reconcile: true,
+            notifier: config
+                .alerts
+                .as_ref()
+                .filter(|a| !a.webhooks.is_empty())
+                .map(|a| WebhookNotifier::new(&a.webhooks))
+                .transpose()?,
+        })
+    }
+
+    /// Sets whether to reconcile after evaluating autoscaling.
+    #[must_use]
+    pub const fn with_reconcile(mut self, reconcile: bool) -> Self {
+        self.reconcile = reconcile;
+        self
+    }
+
+    /// Records health check outcomes in `history` on every tick.
+    #[must_use]
+    pub fn with_health_history(mut self, history: HealthHistory) -> Self {
+        self.health_history = Some(history);
+        self
+    }
+
+    /// Runs a single watch iteration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if observing pods, saving state, or reconciling fails.
+    pub async fn tick(&self) -> Result<WatchTick> {
+        let mut tick = WatchTick {
+            expired_stopped: self.enforce_stopped_retention().await?,
+            ..WatchTick::default()
+        };
+
+        if let Some(history) = &self.health_history {
+            tick.unhealthy = self.record_health(history).await?;
+        }
+
+        let scale_events = self.autoscale().await?;
+        tick.scale_events = scale_events;
+
+        if self.reconcile {
+            let reconciler = Reconciler::new(
+                self.config,
+                self.state_store,
+                self.provisioner,
+                self.observer,
+            );
+            let result = reconciler.reconcile().await?;
+
+            if self.config.pods.iter().any(|p| p.load_balancer.is_some()) {
+                let observed = self
+                    .observer
+                    .list_project_pods(&self.config.project.name, &self.config.project.environment)
+                    .await?;
+                tick.manifests = ManifestGenerator::new().write_all(
+                    self.config,
+                    result.final_state.as_ref(),
+                    &observed,
+                )?;
+            }
+
+            tick.reconciliation = Some(result);
+        }
+
+        tick.alerts = self.check_alerts(&tick.unhealthy).await?;
+
+        Ok(tick)
+    }
+
+    /// Evaluates the alert thresholds and sends the alerts that are due.
+    ///
+    /// Drift is checked after reconciling, so only drift the reconcile could
+    /// not fix (or that is not reconciled) keeps an alert condition holding.
+    async fn check_alerts(&self, unhealthy: &[String]) -> Result<Vec<Alert>> {
+        let Some(alerts) = &self.config.alerts else {
+            return Ok(vec![]);
+        };
+        let (project, environment) = (&self.config.project.name, &self.config.project.environment);
+
+        let drifted = if alerts.drift_minutes.is_some() {
+            Reconciler::new(
+                self.config,
+                self.state_store,
+                self.provisioner,
+                self.observer,
+            )
+            .check_drift()
+            .await?
+            .drifted_resources
+        } else {
+            vec![]
+        };
+
+        let mut state = self
+            .state_store
+            .load()
+            .await?
+            .unwrap_or_else(|| DeploymentState::new(project, environment));
+
+        let cost_per_hour = if alerts.hourly_cost_above.is_some() {
+            let observed = self
+                .observer
+                .list_project_pods(project, environment)
+                .await?;
+            let gpu_types = self.provisioner.client().list_gpu_types().await?;
+            let prices = GpuPrices::new(&gpu_types, self.config.project.cloud_type);
+            Some(
+                CostReport::new(
+                    project,
+                    environment,
+                    &observed,
+                    Some(&state),
+                    &prices,
+                    Utc::now(),
+                )
+                .cost_per_hour(),
+            )
+        } else {
+            None
+        };
+
+        let inputs = AlertInputs {
+            cost_per_hour,
+            unhealthy: unhealthy.to_vec(),
+            drifted,
+        };
+        let tracked = state.alerts.clone();
+        let fired = evaluate_alerts(alerts, &inputs, &mut state, Utc::now());
+
+        // Only write state when a condition started, cleared or was notified
+        if state.alerts != tracked {
+            self.state_store.save(&state).await?;
+        }
+
+        for alert in &fired {
+            warn!("Alert: {alert}");
+        }
+        if let Some(notifier) = &self.notifier {
+            notifier.send(project, &fired).await;
+        }
+        Ok(fired)
+    }
+
+    /// Health checks the pods that should be running and records the outcomes.
+    ///
+    /// A pod that should be running but is not counts as unhealthy. Returns
+    /// the unhealthy pods; failing to write the history is only logged.
+    async fn record_health(&self, history: &HealthHistory) -> Result<Vec<String>> {
+        let state = self.state_store.load().await?;
+        let observed = self
+            .observer
+            .list_project_pods(&self.config.project.name, &self.config.project.environment)
+            .await?;
+
+        let now = Utc::now();
+        let mut samples = Vec::new();
+        for pod in &self.config.pods {
+            if !pod.enabled || state.as_ref().is_some_and(|s| s.is_sleeping(&pod.name)) {
+                continue;
+            }
+
+            for instance in pod.instances(DiffEngine::replica_count(pod, state.as_ref())) {
+                if state
+                    .as_ref()
+                    .is_some_and(|s| s.maintenance_for(&instance.name).is_some())
+                {
+                    continue;
+                }
+
+                let running = observed.iter().find(|p| {
+                    p.pod_name.as_deref() == Some(instance.name.as_str()) && p.is_running()
+                });
+                let sample = match running {
+                    Some(running) => {
+                        let health = self
+                            .checker
+                            .check_pod(running, instance.health_check.as_ref())
+                            .await;
+                        HealthSample {
+                            at: now,
+                            pod: instance.name,
+                            healthy: health.healthy,
+                            response_time_ms: health.average_response_time_ms(),
+                            error: health.error.or_else(|| {
+                                health
+                                    .checks
+                                    .into_iter()
+                                    .find(|c| !c.healthy)
+                                    .and_then(|c| c.error)
+                            }),
+                        }
+                    }
+                    None => HealthSample {
+                        at: now,
+                        pod: instance.name,
+                        healthy: false,
+                        response_time_ms: None,
+                        error: Some(String::from("pod is not running")),
+                    },
+                };
+                samples.push(sample);
+            }
+        }
+
+        if let Err(e) = history.append(&samples).await {
+            warn!("Failed to record health history: {e}");
+        }
+
+        Ok(samples
+            .into_iter()
+            .filter(|s| !s.healthy)
+            .map(|s| s.pod)
+            .collect())
+    }
+
+    /// Tracks stopped pods and warns about or terminates those stopped too long.
+    ///
+    /// Warm pool pods and sleeping replicas are stopped on purpose and never
+    /// expire. A terminated pod that is still configured is recreated by the
+    /// next reconcile.
+    async fn enforce_stopped_retention(&self) -> Result<Vec<String>> {
+        let Some((guardrails, retention_hours)) = self
+            .config
+            .guardrails
+            .as_ref()
+            .and_then(|g| Some((g, g.stopped_retention_hours?)))
+        else {
+            return Ok(vec![]);
+        };
+
+        let mut state = self.state_store.load().await?.unwrap_or_else(|| {
+            DeploymentState::new(&self.config.project.name, &self.config.project.environment)
+        });
+
+        let observed = self
+            .observer
+            .list_project_pods(&self.config.project.name, &self.config.project.environment)
+            .await?;
+
+        let now = Utc::now();
+        let stopped: Vec<&ObservedPod> = observed.iter().filter(|p| p.is_stopped()).collect();
+        let stopped_ids: Vec<&str> = stopped.iter().map(|p| p.id.as_str()).collect();
+        let tracked = state.stopped_since.clone();
+        state.track_stopped(&stopped_ids, now);
+        let mut terminated = false;
+
+        let exempt: HashSet<String> = self
+            .config
+            .pods
+            .iter()
+            .flat_map(|pod| {
+                let mut names: Vec<String> =
+                    pod.warm_instances().into_iter().map(|p| p.name).collect();
+                if state.is_sleeping(&pod.name) {
+                    let current = DiffEngine::replica_count(pod, Some(&state));
+                    names.extend(pod.instances(current).into_iter().map(|p| p.name));
+                }
+                names
+            })
+            .collect();
+
+        let retention = chrono::Duration::hours(i64::from(retention_hours));
+        let mut expired = Vec::new();
+        for pod in stopped {
+            let name = pod.pod_name.clone().unwrap_or_else(|| pod.name.clone());
+            let Some(since) = state.stopped_since.get(&pod.id).copied() else {
+                continue;
+            };
+            if exempt.contains(&name)
+                || state.maintenance_for(&name).is_some()
+                || now - since < retention
+            {
+                continue;
+            }
+
+            let stopped_hours = (now - since).num_hours();
+            let cost = pod.stopped_storage_cost_per_month().unwrap_or_default();
+            match guardrails.stopped_retention_action {
+                StoppedRetentionAction::Warn => warn!(
+                    "Pod {name} has been stopped for {stopped_hours}h (retention {retention_hours}h), \
+                     still billing ~${cost:.2}/month of storage"
+                ),
+                StoppedRetentionAction::Terminate => {
+                    if let Err(e) = self.provisioner.terminate_pod(&pod.id).await {
+                        warn!("Failed to terminate long-stopped pod {name}: {e}");
+                        continue;
+                    }
+
+                    info!(
+                        "Terminated pod {name}, stopped for {stopped_hours}h (retention {retention_hours}h)"
+                    );
+                    terminated = true;
+                    if state.get_pod(&name).is_some_and(|p| p.runpod_id == pod.id) {
+                        state.remove_pod(&name);
+                    }
+                    state.add_history(
+                        DeploymentHistoryEntry::new(
+                            DeploymentOperation::Destroy,
+                            &ConfigHasher::new().hash_config(self.config),
+                            vec![name.clone()],
+                        )
+                        .with_details(format!(
+                            "stopped for {stopped_hours}h, past the {retention_hours}h retention"
+                        )),
+                    );
+                }
+            }
+            expired.push(name);
+        }
+
+        // Only write state when a pod was terminated or stopped tracking changed
+        if terminated || state.stopped_since != tracked {
+            self.state_store.save(&state).await?;
+        }
+
+        Ok(expired)
+    }
+
+    /// Evaluates autoscaling for every autoscaled pod and records decisions in state.
+    async fn autoscale(&self) -> Result<Vec<ScaleDecision>> {
+        if !self.config.pods.iter().any(|p| p.autoscale.is_some()) {
+            return Ok(vec![]);
+        }
+
+        let mut state = self.state_store.load().await?.unwrap_or_else(|| {
+            DeploymentState::new(&self.config.project.name, &self.config.project.environment)
+        });
+
+        let observed = self
+            .observer
+            .list_project_pods(&self.config.project.name, &self.config.project.environment)
+            .await?;
+
+        let config_hash = ConfigHasher::new().hash_config(self.config);
+        let now = Utc::now();
+        let mut decisions = Vec::new();
+        let mut idle_tracked = false;
+
+        for pod in self
+            .config
+            .pods
+            .iter()
+            .filter(|p| p.enabled && p.autoscale.is_some())
+        {
+            if state.is_sleeping(&pod.name) {
+                debug!("Pod {} is asleep, waiting for a wake request", pod.name);
+                continue;
+            }
+            if let Some(maintenance) = state.maintenance.get(&pod.name) {
+                debug!(
+                    "Pod {} is under maintenance, not scaling: {maintenance}",
+                    pod.name
+                );
+                continue;
+            }
+
+            let current = DiffEngine::replica_count(pod, Some(&state));
+            let load = self.sample_load(pod, current, &observed).await;
+
+            let idle_since = |tracked: &DeploymentState| {
+                tracked.replicas.get(&pod.name).and_then(|r| r.idle_since)
+            };
+            let was_idle_since = idle_since(&state);
+            let slept = self
+                .check_idle(pod, current, &load, &observed, &mut state)
+                .await;
+            idle_tracked |= idle_since(&state) != was_idle_since;
+            if let Some(decision) = slept {
+                info!("{decision}");
+                state.add_history(
+                    DeploymentHistoryEntry::new(
+                        DeploymentOperation::Scale,
+                        &config_hash,
+                        vec![pod.name.clone()],
+                    )
+                    .with_details(decision.to_string()),
+                );
+                decisions.push(decision);
+                continue;
+            }
+
+            let Some(decision) =
+                self.autoscaler
+                    .evaluate(pod, current, &load, state.last_scaled_at(&pod.name), now)
+            else {
+                continue;
+            };
+
+            info!("{decision}");
+            state.set_desired_replicas(&pod.name, decision.desired);
+            state.add_history(
+                DeploymentHistoryEntry::new(
+                    DeploymentOperation::Scale,
+                    &config_hash,
+                    vec![pod.name.clone()],
+                )
+                .with_details(decision.to_string()),
+            );
+            decisions.push(decision);
+        }
+
+        // Only write state when idle tracking or a decision changed it
+        if idle_tracked || !decisions.is_empty() {
+            self.state_store.save(&state).await?;
+        }
+
+        Ok(decisions)
+    }
+
+    /// Tracks idleness and stops all replicas once the idle timeout elapses.
+    ///
+    /// Returns a decision (down to zero running replicas) when the pod was put to sleep.
+    async fn check_idle(
+        &self,
+        pod: &PodConfig,
+        current: u32,
+        load: &ReplicaLoad,
+        observed: &[ObservedPod],
+        state: &mut DeploymentState,
+    ) -> Option<ScaleDecision> {
+        let idle_timeout = pod.autoscale.as_ref()?.idle_timeout_secs?;
+        let now = Utc::now();
+
+        // Only trust an idle reading when at least one replica was scraped
+        let idle = load.sampled > 0 && load.concurrent_requests.is_some_and(|r| r <= 0.0);
+        let replica = state.replica_state_mut(&pod.name, current);
+        if !idle {
+            replica.idle_since = None;
+            return None;
+        }
+
+        let since = *replica.idle_since.get_or_insert(now);
+        let idle_secs = (now - since).num_seconds();
+        if idle_secs < i64::try_from(idle_timeout).unwrap_or(i64::MAX) {
+            debug!(
+                "Pod {} idle for {idle_secs}s (timeout {idle_timeout}s)",
+                pod.name
+            );
+            return None;
+        }
+
+        let mut stopped = 0;
+        for instance in pod.instances(current) {
+            let Some(running) = observed
+                .iter()
+                .find(|p| p.pod_name.as_deref() == Some(instance.name.as_str()) && p.is_running())
+            else {
+                continue;
+            };
+
+            match self.provisioner.stop_pod(&running.id).await {
+                Ok(()) => {
+                    stopped += 1;
+                    if let Some(pod_state) = state.get_pod_mut(&instance.name) {
+                        pod_state.stop(StopReason::Idle);
+                    }
+                }
+                Err(e) => warn!("Failed to stop idle replica {}: {e}", instance.name),
+            }
+        }
+
+        let asleep = state.replica_state_mut(&pod.name, current);
+        asleep.sleeping = true;
+        asleep.idle_since = None;
+
+        Some(ScaleDecision {
+            pod: pod.name.clone(),
+            current: stopped,
+            desired: 0,
+            reason: format!("no in-flight requests for {idle_secs}s, replicas stopped"),
+        })
+    }
+
+    /// Resumes every stopped replica of a pod and clears its sleeping flag.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pod is not configured, or observing pods or
+    /// saving state fails.
+    pub async fn wake(&self, pod_name: &str) -> Result<ScaleDecision> {
+        let pod = self
+            .config
+            .pods
+            .iter()
+            .find(|p| p.name == pod_name)
+            .ok_or_else(|| {
+                HalldyllError::internal(format!(
+                    "Pod '{pod_name}' is not defined in the configuration"
+                ))
+            })?;
+        if !pod.enabled {
+            return Err(HalldyllError::internal(format!(
+                "Pod '{pod_name}' is disabled; set `enabled: true` to run it"
+            )));
+        }
+
+        let mut state = self.state_store.load().await?.unwrap_or_else(|| {
+            DeploymentState::new(&self.config.project.name, &self.config.project.environment)
+        });
+
+        let observed = self
+            .observer
+            .list_project_pods(&self.config.project.name, &self.config.project.environment)
+            .await?;
+
+        let current = DiffEngine::replica_count(pod, Some(&state));
+        let mut resumed = 0;
+        for instance in pod.instances(current) {
+            let Some(stopped) = observed.iter().find(|p| {
+                p.pod_name.as_deref() == Some(instance.name.as_str())
+                    && matches!(p.status, PodStatus::Stopped | PodStatus::Exited)
+            }) else {
+                continue;
+            };
+
+            match self.provisioner.resume_pod(&stopped.id).await {
+                Ok(_) => {
+                    resumed += 1;
+                    if let Some(pod_state) = state.get_pod_mut(&instance.name) {
+                        pod_state.set_status(DeploymentStatus::Running);
+                    }
+                }
+                Err(e) => warn!("Failed to resume replica {}: {e}", instance.name),
+            }
+        }
+
+        let replica = state.replica_state_mut(&pod.name, current);
+        replica.sleeping = false;
+        replica.idle_since = None;
+        replica.last_scaled_at = Some(Utc::now());
+
+        let decision = ScaleDecision {
+            pod: pod.name.clone(),
+            current: 0,
+            desired: resumed,
+            reason: String::from("wake requested"),
+        };
+        info!("{decision}");
+
+        state.add_history(
+            DeploymentHistoryEntry::new(
+                DeploymentOperation::Scale,
+                &ConfigHasher::new().hash_config(self.config),
+                vec![pod.name.clone()],
+            )
+            .with_details(decision.to_string()),
+        );
+        self.state_store.save(&state).await?;
+
+        Ok(decision)
+    }
+
+    /// Collects average load across the running replicas of a pod.
+    async fn sample_load(
+        &self,
+        pod: &PodConfig,
+        current: u32,
+        observed: &[ObservedPod],
+    ) -> ReplicaLoad {
+        let Some(autoscale) = &pod.autoscale else {
+            return ReplicaLoad::default();
+        };
+        let metrics_port = autoscale
+            .metrics_port
+            .or_else(|| pod.http_ports().first().copied());
+
+        let mut requests = Vec::new();
+        let mut gpu_util = Vec::new();
+
+        for index in 0..current {
+            let name = pod.replica_name(index);
+            let Some(replica) = observed
+                .iter()
+                .find(|p| p.pod_name.as_deref() == Some(name.as_str()) && p.is_running())
+            else {
+                debug!("Replica {name} is not running, skipping sample");
+                continue;
+            };
+
+            if autoscale.target_gpu_util.is_some()
+                && let Some(util) = replica.gpu_utilization
+            {
+                gpu_util.push(f64::from(util));
+            }
+
+            if autoscale.uses_request_metrics()
+                && let Some(port) = metrics_port
+            {
+                match self
+                    .scraper
+                    .scrape(replica, port, &autoscale.metrics_path)
+                    .await
+                {
+                    Ok(metrics) => requests.push(metrics.concurrent_requests()),
+                    Err(e) => warn!("Failed to scrape metrics for {name}: {e}"),
+                }
+            }
+        }
+
+        let average = |samples: &[f64]| {
+            #[allow(clippy::cast_precision_loss)]
+            let count = samples.len() as f64;
+            (!samples.is_empty()).then(|| samples.iter().sum::<f64>() / count)
+        };
+
+        ReplicaLoad {
+            concurrent_requests: average(&requests),
+            gpu_util: average(&gpu_util),
+            sampled: u32::try_from(requests.len().max(gpu_util.len())).unwrap_or(u32::MAX),
+        }
+    }
+}
+
+impl std::fmt::Display for WatchTick {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for event in &self.scale_events {
+            writeln!(f, "{event}")?;
+        }
+
+        for pod in &self.expired_stopped {
+            writeln!(f, "Pod {pod} is past its stopped retention")?;
+        }
+
+        for pod in &self.unhealthy {
+            writeln!(f, "Pod {pod} failed its health check")?;
+        }
+
+        for alert in &self.alerts {
+            writeln!(f, "Alert: {alert}")?;
+        }
+
+        for path in &self.manifests {
+            writeln!(f, "Updated load balancer manifest: {}", path.display())?;
+        }
+
+        match &self.reconciliation {
+            Some(result)
+                if result.created + result.updated + result.deleted > 0 || !result.success =>
+            {
+                write!(f, "{result}")
+            }
+            Some(_) => write!(f, "Deployment is converged"),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runpod::RunPodClient;
+    use crate::state::{LocalStateStore, PodState};
+    use std::collections::HashMap;
+    use wiremock::matchers::body_string_contains;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const YAML: &str = "project:\n  name: proj\nstate:\n  backend: local\npods:\n  - name: llm\n    replicas: 2\n    gpu:\n      type: A40\n    runtime:\n      image: img\n    autoscale:\n      min: 1\n      max: 4\n      target_concurrent_requests: 8\n      idle_timeout_secs: 60\n";
+
+    /// Name of replica `index` of `llm`.
+    fn replica(index: u32) -> String {
+        if index == 0 {
+            String::from("llm")
+        } else {
+            format!("llm-{index}")
+        }
+    }
+
+    /// A pod as `RunPod` lists it, tagged as replica `index` of `llm`.
+    fn listed(id: &str, index: u32, status: &str) -> serde_json::Value {
+        serde_json::json!({
+            "id": id,
+            "name": format!("proj-dev-{}", replica(index)),
+            "desiredStatus": status,
+            "customTags": {
+                "halldyll.io/project": "proj",
+                "halldyll.io/env": "dev",
+                "halldyll.io/pod": replica(index),
+            },
+        })
+    }
+
+    fn observed(id: &str, index: u32, status: PodStatus) -> ObservedPod {
+        ObservedPod {
+            id: id.to_string(),
+            name: format!("proj-dev-{}", replica(index)),
+            project: Some(String::from("proj")),
+            environment: Some(String::from("dev")),
+            pod_name: Some(replica(index)),
+            spec_hash: None,
+            status,
+            gpu_type: None,
+            gpu_count: 1,
+            image: String::from("img"),
+            endpoints: HashMap::new(),
+            gpu_utilization: None,
+            tags: HashMap::new(),
+            volume_gb: 0,
+            uptime_secs: 0,
+            env: None,
+        }
+    }
+
+    fn client(server: &MockServer) -> RunPodClient {
+        RunPodClient::new("test-key")
+            .unwrap()
+            .with_api_url(server.uri())
+    }
+
+    #[tokio::test]
+    async fn test_idle_timeout_stops_all_replicas() {
+        let config: DeployConfig = serde_yaml::from_str(YAML).unwrap();
+        let server = MockServer::start().await;
+        Mock::given(body_string_contains("podStop"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "data": { "podStop": { "id": "a" } } })),
+            )
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = LocalStateStore::with_base_dir(dir.path());
+        let provisioner = PodProvisioner::new(client(&server));
+        let observer = PodObserver::new(client(&server));
+        let watcher = Watcher::new(&config, &store, &provisioner, &observer).unwrap();
+
+        let pod = &config.pods[0];
+        let replicas = [
+            observed("a", 0, PodStatus::Running),
+            observed("b", 1, PodStatus::Running),
+        ];
+        let mut state = DeploymentState::new("proj", "dev");
+        state.set_pod(PodState::new("llm", "a", "hash"));
+        state.set_pod(PodState::new("llm-1", "b", "hash"));
+        let idle = ReplicaLoad {
+            concurrent_requests: Some(0.0),
+            gpu_util: None,
+            sampled: 2,
+        };
+
+        // Busy replicas clear idle tracking, a fresh idle reading starts it
+        let busy = ReplicaLoad {
+            concurrent_requests: Some(3.0),
+            ..idle
+        };
+        assert!(
+            watcher
+                .check_idle(pod, 2, &busy, &replicas, &mut state)
+                .await
+                .is_none()
+        );
+        assert!(
+            watcher
+                .check_idle(pod, 2, &idle, &replicas, &mut state)
+                .await
+                .is_none()
+        );
+        assert!(state.replicas["llm"].idle_since.is_some());
+        assert!(!state.is_sleeping("llm"));
+
+        // Past the timeout every running replica is stopped
+        state.replica_state_mut("llm", 2).idle_since =
+            Some(Utc::now() - chrono::Duration::seconds(120));
+        let decision = watcher
+            .check_idle(pod, 2, &idle, &replicas, &mut state)
+            .await
+            .unwrap();
+        assert_eq!((decision.current, decision.desired), (2, 0));
+        assert!(state.is_sleeping("llm"));
+        assert_eq!(state.replicas["llm"].idle_since, None);
+        for name in ["llm", "llm-1"] {
+            let replica = &state.pods[name];
+            assert_eq!(replica.status, DeploymentStatus::Stopped);
+            assert_eq!(replica.stop_reason, Some(StopReason::Idle));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wake_resumes_sleeping_replicas() {
+        let config: DeployConfig = serde_yaml::from_str(YAML).unwrap();
+        let server = MockServer::start().await;
+        let pods = serde_json::json!([listed("a", 0, "EXITED"), listed("b", 1, "EXITED")]);
+        Mock::given(body_string_contains("myself"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "data": { "myself": { "pods": pods } } })),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(body_string_contains("podResume"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(
+                serde_json::json!({ "data": { "podResume": listed("a", 0, "RUNNING") } }),
+            ))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = LocalStateStore::with_base_dir(dir.path());
+        let mut state = DeploymentState::new("proj", "dev");
+        for (name, id) in [("llm", "a"), ("llm-1", "b")] {
+            let mut replica = PodState::new(name, id, "hash");
+            replica.stop(StopReason::Idle);
+            state.set_pod(replica);
+        }
+        state.replica_state_mut("llm", 2).sleeping = true;
+        store.save(&state).await.unwrap();
+
+        let provisioner = PodProvisioner::new(client(&server));
+        let observer = PodObserver::new(client(&server));
+        let watcher = Watcher::new(&config, &store, &provisioner, &observer).unwrap();
+
+        let decision = watcher.wake("llm").await.unwrap();
+        assert_eq!((decision.current, decision.desired), (0, 2));
+
+        let woken = store.load().await.unwrap().unwrap();
+        assert!(!woken.is_sleeping("llm"));
+        assert!(woken.replicas["llm"].last_scaled_at.is_some());
+        for name in ["llm", "llm-1"] {
+            assert_eq!(woken.pods[name].status, DeploymentStatus::Running);
+            assert_eq!(woken.pods[name].stop_reason, None);
+        }
+        assert!(watcher.wake("missing").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_maintenance_skips_scaling_and_drift_alerts() {
+        let yaml = YAML.replace("target_concurrent_requests: 8", "target_gpu_util: 50")
+            + "alerts:\n  drift_minutes: 0\n";
+        let config: DeployConfig = serde_yaml::from_str(&yaml).unwrap();
+
+        // Both replicas run an older image with their GPUs saturated
+        let server = MockServer::start().await;
+        let pods: Vec<_> = [("a", 0), ("b", 1)]
+            .into_iter()
+            .map(|(id, index)| {
+                let mut pod = listed(id, index, "RUNNING");
+                pod["imageName"] = serde_json::json!("old-img");
+                pod["runtime"] =
+                    serde_json::json!({ "gpus": [{ "id": "gpu", "gpuUtilizationPercent": 100.0 }] });
+                pod
+            })
+            .collect();
+        Mock::given(body_string_contains("myself"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "data": { "myself": { "pods": pods } } })),
+            )
+            .mount(&server)
+            .await;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = LocalStateStore::with_base_dir(dir.path());
+        let provisioner = PodProvisioner::new(client(&server));
+        let observer = PodObserver::new(client(&server));
+        let watcher = Watcher::new(&config, &store, &provisioner, &observer).unwrap();
+
+        let mut state = DeploymentState::new("proj", "dev");
+        state.set_maintenance(
+            "llm",
+            crate::state::MaintenanceState::new("debugging", "ops"),
+        );
+        store.save(&state).await.unwrap();
+
+        assert!(watcher.autoscale().await.unwrap().is_empty());
+        assert!(watcher.check_alerts(&[]).await.unwrap().is_empty());
+        let held = store.load().await.unwrap().unwrap();
+        assert!(!held.replicas.contains_key("llm"));
+
+        // Out of maintenance the same pods raise drift alerts and scale up
+        state.clear_maintenance("llm");
+        store.save(&state).await.unwrap();
+
+        let alerts: Vec<_> = watcher
+            .check_alerts(&[])
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|a| a.key)
+            .collect();
+        assert_eq!(alerts, ["drift/llm", "drift/llm-1"]);
+        let decisions = watcher.autoscale().await.unwrap();
+        assert_eq!(decisions.len(), 1);
+        assert_eq!(decisions[0].desired, 4);
+    }
+
+    #[tokio::test]
+    async fn test_tick_drops_drained_replica_from_manifest() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let manifest = dir.path().join("llm.conf");
+        let yaml = format!(
+            "project:\n  name: proj\nstate:\n  backend: local\npods:\n  - name: llm\n    gpu:\n      type: A40\n    ports:\n      - \"8000/http\"\n    runtime:\n      image: img\n    load_balancer:\n      format: nginx\n      output: {}\n",
+            manifest.display()
+        );
+        let config: DeployConfig = serde_yaml::from_str(&yaml).unwrap();
+
+        // Scaled in to one replica: `llm-1` is still running and goes
+        let server = MockServer::start().await;
+        let pods = serde_json::json!([listed("a", 0, "RUNNING"), listed("b", 1, "RUNNING")]);
+        Mock::given(body_string_contains("myself"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "data": { "myself": { "pods": pods } } })),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(body_string_contains("podTerminate"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "data": { "podTerminate": null } })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let store = LocalStateStore::with_base_dir(dir.path());
+        let hash = crate::config::ConfigHasher::new().hash_pod(&config.pods[0]);
+        let mut state = DeploymentState::new("proj", "dev");
+        state.set_pod(PodState::new("llm", "a", &hash));
+        state.set_pod(PodState::new("llm-1", "b", &hash));
+        store.save(&state).await.unwrap();
+
+        let provisioner = PodProvisioner::new(client(&server));
+        let observer = PodObserver::new(client(&server));
+        let watcher = Watcher::new(&config, &store, &provisioner, &observer).unwrap();
+
+        let tick = watcher.tick().await.unwrap();
+        assert_eq!(tick.reconciliation.unwrap().deleted, 1);
+        assert_eq!(tick.manifests, std::slice::from_ref(&manifest));
+
+        let content = std::fs::read_to_string(&manifest).unwrap();
+        assert!(content.contains("a-8000.proxy.runpod.net"));
+        assert!(!content.contains("b-8000.proxy.runpod.net"));
+        assert!(!content.contains("draining"));
+    }
+
+    #[tokio::test]
+    async fn test_autoscale_saves_only_on_change() {
+        let config: DeployConfig = serde_yaml::from_str(YAML).unwrap();
+        let server = MockServer::start().await;
+        Mock::given(body_string_contains("myself"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "data": { "myself": { "pods": [] } } })),
+            )
+            .mount(&server)
+            .await;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = LocalStateStore::with_base_dir(dir.path());
+        let provisioner = PodProvisioner::new(client(&server));
+        let observer = PodObserver::new(client(&server));
+        let watcher = Watcher::new(&config, &store, &provisioner, &observer).unwrap();
+
+        // Nothing running: no load, no idle reading, no decision, no write
+        assert!(watcher.autoscale().await.unwrap().is_empty());
+        assert!(store.load().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_alerts_and_retention_save_only_on_change() {
+        let yaml = format!(
+            "{YAML}alerts:\n  unhealthy_minutes: 5\nguardrails:\n  stopped_retention_hours: 24\n"
+        );
+        let config: DeployConfig = serde_yaml::from_str(&yaml).unwrap();
+        let server = MockServer::start().await;
+        Mock::given(body_string_contains("myself"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "data": { "myself": { "pods": [] } } })),
+            )
+            .mount(&server)
+            .await;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = LocalStateStore::with_base_dir(dir.path());
+        let provisioner = PodProvisioner::new(client(&server));
+        let observer = PodObserver::new(client(&server));
+        let watcher = Watcher::new(&config, &store, &provisioner, &observer).unwrap();
+
+        // No condition and no stopped pod: nothing to write
+        assert!(watcher.check_alerts(&[]).await.unwrap().is_empty());
+        assert!(
+            watcher
+                .enforce_stopped_retention()
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(store.load().await.unwrap().is_none());
+
+        // A pod turning unhealthy starts tracking its condition
+        let unhealthy = [String::from("llm")];
+        assert!(watcher.check_alerts(&unhealthy).await.unwrap().is_empty());
+        let tracked = store.load().await.unwrap().unwrap();
+        assert!(tracked.alerts.contains_key("unhealthy/llm"));
+
+        // A stopped pod is tracked once, then left alone until it changes
+        let stopped_server = MockServer::start().await;
+        Mock::given(body_string_contains("myself"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": { "myself": { "pods": [listed("a", 1, "EXITED")] } }
+            })))
+            .mount(&stopped_server)
+            .await;
+        let stopped_observer = PodObserver::new(client(&stopped_server));
+        let stopped_watcher =
+            Watcher::new(&config, &store, &provisioner, &stopped_observer).unwrap();
+        assert!(
+            stopped_watcher
+                .enforce_stopped_retention()
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(
+            store
+                .load()
+                .await
+                .unwrap()
+                .unwrap()
+                .stopped_since
+                .contains_key("a")
+        );
+
+        let modified = || {
+            std::fs::metadata(dir.path().join("state.json"))
+                .unwrap()
+                .modified()
+                .unwrap()
+        };
+        let written = modified();
+        stopped_watcher.enforce_stopped_retention().await.unwrap();
+        assert_eq!(modified(), written);
+    }
+}